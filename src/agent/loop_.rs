@@ -1,6 +1,7 @@
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::observability::{self, Observer, ObserverEvent};
+use crate::providers::traits::ChatOptions;
 use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
 use crate::security::SecurityPolicy;
@@ -39,14 +40,20 @@ fn trim_history(history: &mut Vec<ChatMessage>) {
     history.drain(start..start + to_remove);
 }
 
-/// Build context preamble by searching memory for relevant entries
-async fn build_context(mem: &dyn Memory, user_msg: &str) -> String {
+/// Build context preamble by searching memory for relevant entries,
+/// plus the entries related to them when `include_related` is set
+async fn build_context(mem: &dyn Memory, user_msg: &str, include_related: bool) -> String {
     let mut context = String::new();
 
     // Pull relevant memories for this message
-    if let Ok(entries) = mem.recall(user_msg, 5).await {
+    let recalled = if include_related {
+        mem.recall_with_related(user_msg, 5).await
+    } else {
+        mem.recall(user_msg, 5).await
+    };
+    if let Ok(entries) = recalled {
         if !entries.is_empty() {
-            context.push_str("[Memory context]\n");
+            context.push_str(providers::middleware::MEMORY_CONTEXT_HEADER);
             for entry in &entries {
                 let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
             }
@@ -131,12 +138,13 @@ async fn agent_turn(
     tools_registry: &[Box<dyn Tool>],
     observer: &dyn Observer,
     model: &str,
-    temperature: f64,
+    options: &ChatOptions,
 ) -> Result<String> {
     for _iteration in 0..MAX_TOOL_ITERATIONS {
         let response = provider
-            .chat_with_history(history, model, temperature)
-            .await?;
+            .chat_with_history(history, model, options)
+            .await?
+            .text;
 
         let (text, tool_calls) = parse_tool_calls(&response);
 
@@ -261,13 +269,14 @@ pub async fn run(
     } else {
         None
     };
-    let tools_registry = tools::all_tools_with_runtime(
+    let mut tools_registry = tools::all_tools_with_runtime(
         &security,
         runtime,
         mem.clone(),
         composio_key,
         &config.browser,
     );
+    tools_registry.push(Box::new(tools::ScheduleMessageTool::new(config.clone())));
 
     // ── Resolve provider ─────────────────────────────────────────
     let provider_name = provider_override
@@ -280,13 +289,13 @@ pub async fn run(
         .or(config.default_model.as_deref())
         .unwrap_or("anthropic/claude-sonnet-4-20250514");
 
-    let provider: Box<dyn Provider> = providers::create_routed_provider(
-        provider_name,
-        config.api_key.as_deref(),
-        &config.reliability,
-        &config.model_routes,
-        model_name,
-    )?;
+    let options = ChatOptions {
+        temperature,
+        ..config.chat_options()
+    };
+
+    let provider: Box<dyn Provider> =
+        providers::create_configured_provider(&config, provider_name, model_name, mem.clone())?;
 
     observer.record_event(&ObserverEvent::AgentStart {
         provider: provider_name.to_string(),
@@ -321,6 +330,10 @@ pub async fn run(
             "Delete a memory entry. Use when: memory is incorrect/stale or explicitly requested for removal. Don't use when: impact is uncertain.",
         ),
     ];
+    tool_descs.push((
+        "schedule_message",
+        "Send a message through a channel later. Use when: the user asks for a reminder or follow-up at a given time. Don't use when: the message should go out now.",
+    ));
     tool_descs.push((
         "screenshot",
         "Capture a screenshot of the current screen. Returns file path and base64-encoded PNG. Use when: visual verification, UI inspection, debugging displays.",
@@ -351,6 +364,8 @@ pub async fn run(
 
     // Append structured tool-use instructions with schemas
     system_prompt.push_str(&build_tool_instructions(&tools_registry));
+    let system_prompt =
+        crate::channels::with_persona(config.system_prompt_for("cli"), &system_prompt);
 
     // ── Execute ──────────────────────────────────────────────────
     let start = Instant::now();
@@ -364,7 +379,7 @@ pub async fn run(
         }
 
         // Inject memory context into user message
-        let context = build_context(mem.as_ref(), &msg).await;
+        let context = build_context(mem.as_ref(), &msg, config.memory.recall_related).await;
         let enriched = if context.is_empty() {
             msg.clone()
         } else {
@@ -382,7 +397,7 @@ pub async fn run(
             &tools_registry,
             observer.as_ref(),
             model_name,
-            temperature,
+            &options,
         )
         .await?;
         println!("{response}");
@@ -418,7 +433,8 @@ pub async fn run(
             }
 
             // Inject memory context into user message
-            let context = build_context(mem.as_ref(), &msg.content).await;
+            let context =
+                build_context(mem.as_ref(), &msg.content, config.memory.recall_related).await;
             let enriched = if context.is_empty() {
                 msg.content.clone()
             } else {
@@ -433,7 +449,7 @@ pub async fn run(
                 &tools_registry,
                 observer.as_ref(),
                 model_name,
-                temperature,
+                &options,
             )
             .await
             {
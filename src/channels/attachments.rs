@@ -0,0 +1,190 @@
+//! Files on channel messages: text attachments are shown to the model and
+//! ingested into memory like `POST /ingest` documents, and fenced blocks the
+//! model labels `file:<name>` go back to the user as files.
+
+use super::traits::Attachment;
+use crate::memory::{self, Memory, MemoryCategory};
+use std::fmt::Write;
+
+/// Characters of one text attachment shown to the model.
+const MAX_PROMPT_CHARS: usize = 20_000;
+
+/// Info-string prefix that turns a fenced block of a reply into a file.
+const FILE_FENCE_PREFIX: &str = "file:";
+
+/// System prompt section telling the model how to send files.
+pub const REPLY_FILES_PROMPT: &str = "## Files\n\n\
+     To send the user a file, put its full contents in a fenced code block \
+     whose info string is `file:<name>`, e.g. ```file:report.csv. Each such \
+     block is delivered as an attachment instead of inline text.\n\n";
+
+/// The attachments as text for the model's turn: contents of text files
+/// (truncated), a one-line note for anything else. `None` without attachments.
+pub fn prompt_text(attachments: &[Attachment]) -> Option<String> {
+    if attachments.is_empty() {
+        return None;
+    }
+    let mut text = String::new();
+    for attachment in attachments {
+        match attachment.text() {
+            Some(contents) => {
+                let shown = crate::util::truncate_with_ellipsis(contents, MAX_PROMPT_CHARS);
+                let _ = writeln!(text, "[Attachment: {}]\n{shown}\n", attachment.name);
+            }
+            None => {
+                let _ = writeln!(
+                    text,
+                    "[Attachment: {} ({}, {} bytes) — not readable as text]\n",
+                    attachment.name,
+                    attachment.mime_type,
+                    attachment.data.len()
+                );
+            }
+        }
+    }
+    Some(text.trim_end().to_string())
+}
+
+/// Store the text attachments as `document` memory chunks, keyed like
+/// `POST /ingest` uploads. Returns the names of the stored documents.
+pub async fn ingest(
+    mem: &dyn Memory,
+    attachments: &[Attachment],
+    chunk_max_tokens: usize,
+) -> anyhow::Result<Vec<String>> {
+    let category = MemoryCategory::from(memory::DOCUMENT_CATEGORY);
+    let mut stored = Vec::new();
+    for attachment in attachments {
+        let Some(text) = attachment.text().filter(|t| !t.trim().is_empty()) else {
+            continue;
+        };
+        let name = memory::document_name(Some(&attachment.name));
+        memory::ingest_document(mem, &name, text, &category, chunk_max_tokens).await?;
+        stored.push(name);
+    }
+    Ok(stored)
+}
+
+/// Split a reply into its text and the files it carries: every fenced block
+/// opened with ```` ```file:<name> ```` becomes an [`Attachment`] and is
+/// removed from the text.
+pub fn extract_reply_files(reply: &str) -> (String, Vec<Attachment>) {
+    let mut text = String::new();
+    let mut files = Vec::new();
+    let mut open: Option<(String, String)> = None;
+    for line in reply.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+        match (&mut open, fence) {
+            (Some(_), Some(rest)) if rest.trim().is_empty() => {
+                let (name, body) = open.take().unwrap_or_default();
+                let mime = mime_for(&name);
+                files.push(Attachment::new(name, mime, body.into_bytes()));
+            }
+            (Some((_, body)), _) => {
+                body.push_str(line);
+                body.push('\n');
+            }
+            (None, Some(rest)) if rest.trim().starts_with(FILE_FENCE_PREFIX) => {
+                let name = rest.trim()[FILE_FENCE_PREFIX.len()..].trim();
+                let name = if name.is_empty() { "reply.txt" } else { name };
+                open = Some((name.to_string(), String::new()));
+            }
+            (None, _) => {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+    }
+    // An unclosed file block still carries everything after its fence
+    if let Some((name, body)) = open {
+        let mime = mime_for(&name);
+        files.push(Attachment::new(name, mime, body.into_bytes()));
+    }
+    (text.trim().to_string(), files)
+}
+
+/// MIME type for a reply file, from its extension.
+fn mime_for(name: &str) -> &'static str {
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "csv" => "text/csv",
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        "js" => "text/javascript",
+        _ => "text/plain",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::InMemoryMemory;
+
+    #[test]
+    fn reply_file_blocks_become_attachments() {
+        let reply = "Here you go:\n\n```file:report.csv\na,b\n1,2\n```\n\n```rust\nfn main() {}\n```\nDone.";
+        let (text, files) = extract_reply_files(reply);
+        assert_eq!(text, "Here you go:\n\n\n```rust\nfn main() {}\n```\nDone.");
+        assert_eq!(
+            files,
+            [Attachment::new(
+                "report.csv",
+                "text/csv",
+                b"a,b\n1,2\n".to_vec()
+            )]
+        );
+
+        let (text, files) = extract_reply_files("no files here");
+        assert_eq!(text, "no files here");
+        assert!(files.is_empty());
+
+        let (_, files) = extract_reply_files("```file:\nunterminated");
+        assert_eq!(files[0].name, "reply.txt");
+        assert_eq!(files[0].data, b"unterminated\n");
+    }
+
+    #[test]
+    fn prompt_text_inlines_text_and_describes_binaries() {
+        assert_eq!(prompt_text(&[]), None);
+        let text = prompt_text(&[
+            Attachment::new("notes.md", "text/markdown", b"# Notes\nhi".to_vec()),
+            Attachment::new("photo.zip", "application/zip", vec![0, 1, 2]),
+        ])
+        .unwrap();
+        assert!(text.contains("[Attachment: notes.md]\n# Notes\nhi"));
+        assert!(text.contains("photo.zip (application/zip, 3 bytes) — not readable as text"));
+    }
+
+    #[tokio::test]
+    async fn ingest_stores_text_attachments_as_documents() {
+        let mem = InMemoryMemory::new();
+        let stored = ingest(
+            &mem,
+            &[
+                Attachment::new(
+                    "My Notes.md",
+                    "text/markdown",
+                    b"# Notes\nremember".to_vec(),
+                ),
+                Attachment::new("blob.bin", "application/octet-stream", vec![0xff]),
+            ],
+            512,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stored, ["My-Notes.md"]);
+        let entries = mem
+            .list(Some(&MemoryCategory::from(memory::DOCUMENT_CATEGORY)))
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "doc:My-Notes.md:0");
+    }
+}
@@ -0,0 +1,254 @@
+//! Relaying of messages from one channel into another.
+//!
+//! Each `[[channels_config.bridges]]` entry mirrors the new messages of a
+//! conversation (or of a whole channel) into a conversation elsewhere —
+//! a Telegram group into a Slack channel, say — with the author and origin
+//! in front. A bridge with a `prompt` has the model rewrite each message
+//! first, to translate or summarize it.
+
+use super::manager::ChannelManager;
+use super::traits::ChannelMessage;
+use crate::config::schema::ChannelBridgeConfig;
+use crate::config::Config;
+use crate::cost::CostTracker;
+use crate::providers::Provider;
+
+/// The bridges relaying `msg`, in config order.
+pub fn matching<'a>(
+    bridges: &'a [ChannelBridgeConfig],
+    msg: &ChannelMessage,
+) -> Vec<&'a ChannelBridgeConfig> {
+    bridges
+        .iter()
+        .filter(|bridge| bridge.matches(&msg.channel, &msg.sender))
+        .collect()
+}
+
+/// `text` as relayed for `msg`: `[telegram] alice: hello`, naming the
+/// author where the sender is a group conversation.
+pub fn relay_text(msg: &ChannelMessage, text: &str) -> String {
+    let author = msg.author.as_deref().unwrap_or(&msg.sender);
+    format!("[{}] {author}: {text}", msg.channel)
+}
+
+/// Relay `msg` over `bridge`, rewritten by the model when the bridge has a
+/// prompt. When the model fails the message is relayed as written.
+pub async fn relay(
+    manager: &ChannelManager,
+    provider: &dyn Provider,
+    model: &str,
+    config: &Config,
+    bridge: &ChannelBridgeConfig,
+    msg: &ChannelMessage,
+) -> anyhow::Result<()> {
+    let channel = manager
+        .get(&bridge.to)
+        .ok_or_else(|| anyhow::anyhow!("Channel not running: {}", bridge.to))?;
+    let prompt = bridge
+        .prompt
+        .as_deref()
+        .map(str::trim)
+        .filter(|prompt| !prompt.is_empty());
+    let text = match prompt {
+        Some(prompt) => {
+            let options = config.chat_options_for(&msg.channel);
+            match provider
+                .chat_with_system(Some(prompt), &msg.content, model, &options)
+                .await
+            {
+                Ok(reply) => {
+                    CostTracker::new(config).record_reply(
+                        config.default_provider.as_deref().unwrap_or("openrouter"),
+                        &msg.channel,
+                        model,
+                        &reply,
+                    );
+                    reply.text
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Bridge {} → {}: model failed, relaying as is: {e}",
+                        bridge.from,
+                        bridge.to
+                    );
+                    msg.content.clone()
+                }
+            }
+        }
+        None => msg.content.clone(),
+    };
+    channel.send(&relay_text(msg, &text), &bridge.target).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::traits::{Audience, Channel};
+    use crate::providers::traits::{ChatOptions, ChatReply};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    fn bridge(source: Option<&str>, prompt: Option<&str>) -> ChannelBridgeConfig {
+        ChannelBridgeConfig {
+            from: "telegram".into(),
+            source: source.map(str::to_string),
+            to: "slack".into(),
+            target: "C0123".into(),
+            prompt: prompt.map(str::to_string),
+            answer: false,
+        }
+    }
+
+    fn message(channel: &str, sender: &str, author: Option<&str>) -> ChannelMessage {
+        ChannelMessage {
+            id: "1".into(),
+            sender: sender.into(),
+            content: "bonjour".into(),
+            channel: channel.into(),
+            timestamp: 0,
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: author.map(str::to_string),
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience: Audience::Group,
+        }
+    }
+
+    #[test]
+    fn bridges_match_their_channel_and_conversation() {
+        let bridges = [
+            bridge(Some("-100"), None),
+            bridge(None, None),
+            ChannelBridgeConfig {
+                from: "slack".into(),
+                source: Some("C1".into()),
+                ..bridge(None, None)
+            },
+        ];
+        assert_eq!(
+            matching(&bridges, &message("telegram", "-100", None)).len(),
+            2
+        );
+        assert_eq!(
+            matching(&bridges, &message("telegram", "-200", None)).len(),
+            1
+        );
+        assert_eq!(
+            matching(&bridges, &message("discord", "-100", None)).len(),
+            0
+        );
+        assert_eq!(
+            matching(&bridges, &message("slack", "C1:1700000000.000100", None)).len(),
+            1
+        );
+        assert!(matching(&bridges, &message("slack", "C12", None)).is_empty());
+    }
+
+    #[test]
+    fn relayed_text_names_origin_and_author() {
+        assert_eq!(
+            relay_text(&message("telegram", "-100", Some("alice")), "hi"),
+            "[telegram] alice: hi"
+        );
+        assert_eq!(
+            relay_text(&message("telegram", "42", None), "hi"),
+            "[telegram] 42: hi"
+        );
+    }
+
+    struct RecordingChannel {
+        sent: Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "slack"
+        }
+
+        async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((message.to_string(), recipient.to_string()));
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct TranslatingProvider;
+
+    #[async_trait]
+    impl Provider for TranslatingProvider {
+        async fn chat_with_system(
+            &self,
+            system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            assert_eq!(system_prompt, Some("Translate into English."));
+            assert_eq!(message, "bonjour");
+            Ok("hello".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn relay_posts_to_the_target_rewritten_by_the_prompt() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let manager = ChannelManager::new(vec![Arc::new(RecordingChannel {
+            sent: Arc::clone(&sent),
+        })]);
+        let config = Config::default();
+        let msg = message("telegram", "-100", Some("alice"));
+
+        relay(
+            &manager,
+            &TranslatingProvider,
+            "m",
+            &config,
+            &bridge(None, None),
+            &msg,
+        )
+        .await
+        .unwrap();
+        relay(
+            &manager,
+            &TranslatingProvider,
+            "m",
+            &config,
+            &bridge(None, Some("Translate into English.")),
+            &msg,
+        )
+        .await
+        .unwrap();
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(
+            *sent,
+            [
+                ("[telegram] alice: bonjour".to_string(), "C0123".to_string()),
+                ("[telegram] alice: hello".to_string(), "C0123".to_string()),
+            ]
+        );
+
+        let missing = ChannelBridgeConfig {
+            to: "discord".into(),
+            ..bridge(None, None)
+        };
+        assert!(
+            relay(&manager, &TranslatingProvider, "m", &config, &missing, &msg)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::traits::{Channel, ChannelMessage};
+use super::traits::{Audience, Channel, ChannelMessage};
 use async_trait::async_trait;
 use tokio::io::{self, AsyncBufReadExt, BufReader};
 use uuid::Uuid;
@@ -42,6 +42,14 @@ impl Channel for CliChannel {
                 sender: "user".to_string(),
                 content: line,
                 channel: "cli".to_string(),
+                images: Vec::new(),
+                context: None,
+                reply_to: None,
+                author: None,
+                attachments: Vec::new(),
+                reaction: None,
+                update: None,
+                audience: Audience::Direct,
                 timestamp: std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap_or_default()
@@ -92,6 +100,14 @@ mod tests {
             sender: "user".into(),
             content: "hello".into(),
             channel: "cli".into(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: None,
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience: Audience::Direct,
             timestamp: 1_234_567_890,
         };
         assert_eq!(msg.id, "test-id");
@@ -108,6 +124,14 @@ mod tests {
             sender: "s".into(),
             content: "c".into(),
             channel: "ch".into(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: None,
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience: Audience::Direct,
             timestamp: 0,
         };
         let cloned = msg.clone();
@@ -1,4 +1,4 @@
-use super::traits::{Channel, ChannelMessage};
+use super::traits::{Audience, Channel, ChannelMessage, MessageUpdate, ReactionAction};
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
@@ -10,6 +10,8 @@ pub struct DiscordChannel {
     bot_token: String,
     guild_id: Option<String>,
     allowed_users: Vec<String>,
+    channel_ids: Vec<String>,
+    mention_only: bool,
     client: reqwest::Client,
 }
 
@@ -19,10 +21,212 @@ impl DiscordChannel {
             bot_token,
             guild_id,
             allowed_users,
-            client: reqwest::Client::new(),
+            channel_ids: Vec::new(),
+            mention_only: false,
+            client: crate::proxy::client("discord"),
         }
     }
 
+    /// Only listen in these channel IDs; empty listens everywhere.
+    #[must_use]
+    pub fn with_channel_ids(mut self, channel_ids: Vec<String>) -> Self {
+        self.channel_ids = channel_ids;
+        self
+    }
+
+    /// In guild channels, only answer messages that mention the bot.
+    #[must_use]
+    pub fn with_mention_only(mut self, mention_only: bool) -> Self {
+        self.mention_only = mention_only;
+        self
+    }
+
+    /// Turn a `MESSAGE_CREATE` payload into a [`ChannelMessage`] addressed to
+    /// its channel, with any mention of the bot removed. Returns `None` for
+    /// bot messages, unauthorized authors, other guilds or channels, guild
+    /// messages without a mention in mention-only mode, and empty messages.
+    fn to_channel_message(
+        &self,
+        d: &serde_json::Value,
+        bot_user_id: &str,
+    ) -> Option<ChannelMessage> {
+        let author = d.get("author");
+        let author_id = author
+            .and_then(|a| a.get("id"))
+            .and_then(|i| i.as_str())
+            .unwrap_or("");
+        let is_bot = author
+            .and_then(|a| a.get("bot"))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        if author_id == bot_user_id || is_bot {
+            return None;
+        }
+
+        if !self.is_user_allowed(author_id) {
+            tracing::warn!("Discord: ignoring message from unauthorized user: {author_id}");
+            return None;
+        }
+
+        // DMs have no guild_id — let them through; for guild messages, enforce the filter
+        let msg_guild = d.get("guild_id").and_then(serde_json::Value::as_str);
+        if let (Some(gid), Some(g)) = (self.guild_id.as_deref(), msg_guild) {
+            if g != gid {
+                return None;
+            }
+        }
+
+        let channel_id = d.get("channel_id").and_then(|c| c.as_str()).unwrap_or("");
+        if !self.channel_ids.is_empty() && !self.channel_ids.iter().any(|c| c == channel_id) {
+            return None;
+        }
+
+        let content = d.get("content").and_then(|c| c.as_str()).unwrap_or("");
+        let mentioned = !bot_user_id.is_empty()
+            && d.get("mentions")
+                .and_then(|m| m.as_array())
+                .is_some_and(|mentions| {
+                    mentions
+                        .iter()
+                        .any(|m| m.get("id").and_then(|i| i.as_str()) == Some(bot_user_id))
+                });
+        if self.mention_only && msg_guild.is_some() && !mentioned {
+            return None;
+        }
+        let replied_to_bot = !bot_user_id.is_empty()
+            && d.get("referenced_message")
+                .and_then(|r| r.get("author"))
+                .and_then(|a| a.get("id"))
+                .and_then(|i| i.as_str())
+                == Some(bot_user_id);
+        let audience = match msg_guild {
+            None => Audience::Direct,
+            Some(_) if mentioned || replied_to_bot => Audience::Mention,
+            Some(_) => Audience::Group,
+        };
+        let content = if bot_user_id.is_empty() {
+            content.trim().to_string()
+        } else {
+            content
+                .replace(&format!("<@{bot_user_id}>"), "")
+                .replace(&format!("<@!{bot_user_id}>"), "")
+                .trim()
+                .to_string()
+        };
+        if content.is_empty() {
+            return None;
+        }
+
+        Some(ChannelMessage {
+            id: d
+                .get("id")
+                .and_then(|i| i.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            sender: channel_id.to_string(),
+            content,
+            channel: "discord".to_string(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: Some(author_id.to_string()),
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
+    /// Turn a `MESSAGE_UPDATE` payload for an edited message (embed unfurls
+    /// also arrive as updates, without `edited_timestamp`) or a
+    /// `MESSAGE_DELETE` payload into an update of the original message.
+    /// Edits pass the same filters as new messages; deletions carry no
+    /// author, so only the channel filter applies.
+    fn update_message(
+        &self,
+        event_type: &str,
+        d: &serde_json::Value,
+        bot_user_id: &str,
+    ) -> Option<ChannelMessage> {
+        if event_type == "MESSAGE_UPDATE" {
+            d.get("edited_timestamp")?.as_str()?;
+            let mut msg = self.to_channel_message(d, bot_user_id)?;
+            msg.update = Some(MessageUpdate::Edited);
+            return Some(msg);
+        }
+        let channel_id = d.get("channel_id")?.as_str()?;
+        if !self.channel_ids.is_empty() && !self.channel_ids.iter().any(|c| c == channel_id) {
+            return None;
+        }
+        Some(ChannelMessage {
+            id: d.get("id")?.as_str()?.to_string(),
+            sender: channel_id.to_string(),
+            content: String::new(),
+            channel: "discord".to_string(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: None,
+            attachments: Vec::new(),
+            reaction: None,
+            update: Some(MessageUpdate::Deleted),
+            audience: Audience::Direct,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
+    /// Turn a `MESSAGE_REACTION_ADD` payload whose emoji maps to a
+    /// [`ReactionAction`] into a message from the reacting user, addressed
+    /// to the channel of the reacted message. Same user, guild and channel
+    /// filters as messages.
+    fn reaction_message(&self, d: &serde_json::Value, bot_user_id: &str) -> Option<ChannelMessage> {
+        let emoji = d.get("emoji")?.get("name")?.as_str()?;
+        let action = ReactionAction::from_emoji(emoji)?;
+        let user_id = d.get("user_id")?.as_str()?;
+        if user_id == bot_user_id {
+            return None;
+        }
+        if !self.is_user_allowed(user_id) {
+            tracing::warn!("Discord: ignoring reaction from unauthorized user: {user_id}");
+            return None;
+        }
+        let msg_guild = d.get("guild_id").and_then(serde_json::Value::as_str);
+        if let (Some(gid), Some(g)) = (self.guild_id.as_deref(), msg_guild) {
+            if g != gid {
+                return None;
+            }
+        }
+        let channel_id = d.get("channel_id")?.as_str()?;
+        if !self.channel_ids.is_empty() && !self.channel_ids.iter().any(|c| c == channel_id) {
+            return None;
+        }
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender: channel_id.to_string(),
+            content: String::new(),
+            channel: "discord".to_string(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: Some(user_id.to_string()),
+            attachments: Vec::new(),
+            reaction: Some(action),
+            update: None,
+            audience: Audience::Direct,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
     /// Check if a Discord user ID is in the allowlist.
     /// Empty list means deny everyone until explicitly configured.
     /// `"*"` means allow everyone.
@@ -39,50 +243,6 @@ impl DiscordChannel {
 
 const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
 
-/// Discord's maximum message length for regular messages
-const DISCORD_MAX_MESSAGE_LENGTH: usize = 4000;
-
-/// Split a message into chunks that respect Discord's 4000 character limit.
-/// Tries to split at word boundaries when possible, and adds continuation markers.
-fn split_message_for_discord(message: &str) -> Vec<String> {
-    if message.len() <= DISCORD_MAX_MESSAGE_LENGTH {
-        return vec![message.to_string()];
-    }
-
-    let mut chunks = Vec::new();
-    let mut remaining = message;
-
-    while !remaining.is_empty() {
-        let chunk_end = if remaining.len() <= DISCORD_MAX_MESSAGE_LENGTH {
-            remaining.len()
-        } else {
-            // Try to find a good break point (newline, then space)
-            let search_area = &remaining[..DISCORD_MAX_MESSAGE_LENGTH];
-
-            // Prefer splitting at newline
-            if let Some(pos) = search_area.rfind('\n') {
-                // Don't split if the newline is too close to the end
-                if pos >= DISCORD_MAX_MESSAGE_LENGTH / 2 {
-                    pos + 1
-                } else {
-                    // Try space as fallback
-                    search_area.rfind(' ').unwrap_or(DISCORD_MAX_MESSAGE_LENGTH) + 1
-                }
-            } else if let Some(pos) = search_area.rfind(' ') {
-                pos + 1
-            } else {
-                // Hard split at the limit
-                DISCORD_MAX_MESSAGE_LENGTH
-            }
-        };
-
-        chunks.push(remaining[..chunk_end].to_string());
-        remaining = &remaining[chunk_end..];
-    }
-
-    chunks
-}
-
 /// Minimal base64 decode (no extra dep) — only needs to decode the user ID portion
 #[allow(clippy::cast_possible_truncation)]
 fn base64_decode(input: &str) -> Option<String> {
@@ -127,34 +287,27 @@ impl Channel for DiscordChannel {
         "discord"
     }
 
+    /// Sends `message` as is; [`super::split::SplitChannel`] keeps it under
+    /// Discord's 2000-character limit.
     async fn send(&self, message: &str, channel_id: &str) -> anyhow::Result<()> {
-        let chunks = split_message_for_discord(message);
-
-        for (i, chunk) in chunks.iter().enumerate() {
-            let url = format!("https://discord.com/api/v10/channels/{channel_id}/messages");
-            let body = json!({ "content": chunk });
-
-            let resp = self
-                .client
-                .post(&url)
-                .header("Authorization", format!("Bot {}", self.bot_token))
-                .json(&body)
-                .send()
-                .await?;
-
-            if !resp.status().is_success() {
-                let status = resp.status();
-                let err = resp
-                    .text()
-                    .await
-                    .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
-                anyhow::bail!("Discord send message failed ({status}): {err}");
-            }
+        let url = format!("https://discord.com/api/v10/channels/{channel_id}/messages");
+        let body = json!({ "content": message });
 
-            // Add a small delay between chunks to avoid rate limiting
-            if i < chunks.len() - 1 {
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-            }
+        let resp = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Discord send message failed ({status}): {err}");
         }
 
         Ok(())
@@ -182,7 +335,7 @@ impl Channel for DiscordChannel {
         let ws_url = format!("{gw_url}/?v=10&encoding=json");
         tracing::info!("Discord: connecting to gateway...");
 
-        let (ws_stream, _) = tokio_tungstenite::connect_async(&ws_url).await?;
+        let ws_stream = crate::proxy::connect_websocket("discord", &ws_url).await?;
         let (mut write, mut read) = ws_stream.split();
 
         // Read Hello (opcode 10)
@@ -199,7 +352,9 @@ impl Channel for DiscordChannel {
             "op": 2,
             "d": {
                 "token": self.bot_token,
-                "intents": 37377, // GUILDS | GUILD_MESSAGES | MESSAGE_CONTENT | DIRECT_MESSAGES
+                // GUILDS | GUILD_MESSAGES | GUILD_MESSAGE_REACTIONS | DIRECT_MESSAGES |
+                // DIRECT_MESSAGE_REACTIONS | MESSAGE_CONTENT
+                "intents": 46593,
                 "properties": {
                     "os": "linux",
                     "browser": "zeroclaw",
@@ -229,8 +384,6 @@ impl Channel for DiscordChannel {
             }
         });
 
-        let guild_filter = self.guild_id.clone();
-
         loop {
             tokio::select! {
                 _ = hb_rx.recv() => {
@@ -282,60 +435,22 @@ impl Channel for DiscordChannel {
                         _ => {}
                     }
 
-                    // Only handle MESSAGE_CREATE (opcode 0, type "MESSAGE_CREATE")
+                    // Only handle message, edit, deletion and reaction dispatches (opcode 0)
                     let event_type = event.get("t").and_then(|t| t.as_str()).unwrap_or("");
-                    if event_type != "MESSAGE_CREATE" {
-                        continue;
-                    }
-
                     let Some(d) = event.get("d") else {
                         continue;
                     };
 
-                    // Skip messages from the bot itself
-                    let author_id = d.get("author").and_then(|a| a.get("id")).and_then(|i| i.as_str()).unwrap_or("");
-                    if author_id == bot_user_id {
-                        continue;
-                    }
-
-                    // Skip bot messages
-                    if d.get("author").and_then(|a| a.get("bot")).and_then(serde_json::Value::as_bool).unwrap_or(false) {
-                        continue;
-                    }
-
-                    // Sender validation
-                    if !self.is_user_allowed(author_id) {
-                        tracing::warn!("Discord: ignoring message from unauthorized user: {author_id}");
-                        continue;
-                    }
-
-                    // Guild filter
-                    if let Some(ref gid) = guild_filter {
-                        let msg_guild = d.get("guild_id").and_then(serde_json::Value::as_str);
-                        // DMs have no guild_id — let them through; for guild messages, enforce the filter
-                        if let Some(g) = msg_guild {
-                            if g != gid {
-                                continue;
-                            }
+                    let channel_msg = match event_type {
+                        "MESSAGE_CREATE" => self.to_channel_message(d, &bot_user_id),
+                        "MESSAGE_UPDATE" | "MESSAGE_DELETE" => {
+                            self.update_message(event_type, d, &bot_user_id)
                         }
-                    }
-
-                    let content = d.get("content").and_then(|c| c.as_str()).unwrap_or("");
-                    if content.is_empty() {
+                        "MESSAGE_REACTION_ADD" => self.reaction_message(d, &bot_user_id),
+                        _ => None,
+                    };
+                    let Some(channel_msg) = channel_msg else {
                         continue;
-                    }
-
-                    let channel_id = d.get("channel_id").and_then(|c| c.as_str()).unwrap_or("").to_string();
-
-                    let channel_msg = ChannelMessage {
-                        id: Uuid::new_v4().to_string(),
-                        sender: channel_id,
-                        content: content.to_string(),
-                        channel: "discord".to_string(),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs(),
                     };
 
                     if tx.send(channel_msg).await.is_err() {
@@ -436,149 +551,158 @@ mod tests {
         assert!(!ch.is_user_allowed("Abc"));
     }
 
-    #[test]
-    fn base64_decode_empty_string() {
-        let decoded = base64_decode("");
-        assert_eq!(decoded, Some(String::new()));
-    }
-
-    #[test]
-    fn base64_decode_invalid_chars() {
-        let decoded = base64_decode("!!!!");
-        assert!(decoded.is_none());
-    }
-
-    #[test]
-    fn bot_user_id_from_empty_token() {
-        let id = DiscordChannel::bot_user_id_from_token("");
-        assert_eq!(id, Some(String::new()));
+    fn message(
+        guild: Option<&str>,
+        channel: &str,
+        content: &str,
+        mentions: &[&str],
+    ) -> serde_json::Value {
+        json!({
+            "author": {"id": "111"},
+            "guild_id": guild,
+            "channel_id": channel,
+            "content": content,
+            "mentions": mentions.iter().map(|id| json!({"id": id})).collect::<Vec<_>>(),
+        })
     }
 
-    // Message splitting tests
-
     #[test]
-    fn split_empty_message() {
-        let chunks = split_message_for_discord("");
-        assert_eq!(chunks, vec![""]);
-    }
-
-    #[test]
-    fn split_short_message_under_limit() {
-        let msg = "Hello, world!";
-        let chunks = split_message_for_discord(msg);
-        assert_eq!(chunks, vec![msg]);
-    }
-
-    #[test]
-    fn split_message_exactly_4000_chars() {
-        let msg = "a".repeat(4000);
-        let chunks = split_message_for_discord(&msg);
-        assert_eq!(chunks.len(), 1);
-        assert_eq!(chunks[0].len(), 4000);
-    }
-
-    #[test]
-    fn split_message_just_over_limit() {
-        let msg = "a".repeat(4001);
-        let chunks = split_message_for_discord(&msg);
-        assert_eq!(chunks.len(), 2);
-        assert_eq!(chunks[0].len(), 4000);
-        assert_eq!(chunks[1].len(), 1);
-    }
-
-    #[test]
-    fn split_very_long_message() {
-        let msg = "word ".repeat(2000); // 10000 characters (5 chars per "word ")
-        let chunks = split_message_for_discord(&msg);
-        // Should split into 3 chunks: ~4000, ~4000, ~2000
-        assert_eq!(chunks.len(), 3);
-        assert!(chunks[0].len() <= 4000);
-        assert!(chunks[1].len() <= 4000);
-        assert!(chunks[2].len() <= 4000);
-        // Verify total content is preserved
-        let reconstructed = chunks.concat();
-        assert_eq!(reconstructed, msg);
-    }
-
-    #[test]
-    fn split_prefer_newline_break() {
-        let msg = format!("{}\n{}", "a".repeat(3000), "b".repeat(2000));
-        let chunks = split_message_for_discord(&msg);
-        // Should split at the newline
-        assert_eq!(chunks.len(), 2);
-        assert!(chunks[0].ends_with('\n'));
-        assert!(chunks[1].starts_with('b'));
-    }
-
-    #[test]
-    fn split_prefer_space_break() {
-        let msg = format!("{} {}", "a".repeat(3000), "b".repeat(2000));
-        let chunks = split_message_for_discord(&msg);
-        assert_eq!(chunks.len(), 2);
+    fn reactions_map_to_actions_with_message_filters() {
+        let ch = DiscordChannel::new("fake".into(), Some("G1".into()), vec!["111".into()])
+            .with_channel_ids(vec!["C1".into()]);
+        let reaction = |user: &str, channel: &str, emoji: &str| {
+            json!({
+                "user_id": user,
+                "guild_id": "G1",
+                "channel_id": channel,
+                "message_id": "M1",
+                "emoji": {"id": null, "name": emoji},
+            })
+        };
+        let msg = ch
+            .reaction_message(&reaction("111", "C1", "🔁"), "999")
+            .unwrap();
+        assert_eq!(msg.reaction, Some(ReactionAction::Regenerate));
+        assert_eq!(msg.sender, "C1");
+        assert_eq!(msg.content, "");
+        assert_eq!(
+            ch.reaction_message(&reaction("111", "C1", "🗑️"), "999")
+                .unwrap()
+                .reaction,
+            Some(ReactionAction::Forget)
+        );
+
+        assert!(ch
+            .reaction_message(&reaction("111", "C1", "👍"), "999")
+            .is_none());
+        assert!(ch
+            .reaction_message(&reaction("222", "C1", "🔁"), "999")
+            .is_none());
+        assert!(ch
+            .reaction_message(&reaction("111", "C2", "🔁"), "999")
+            .is_none());
+        assert!(ch
+            .reaction_message(&reaction("111", "C1", "🔁"), "111")
+            .is_none());
     }
 
     #[test]
-    fn split_without_good_break_points_hard_split() {
-        // No spaces or newlines - should hard split at 4000
-        let msg = "a".repeat(5000);
-        let chunks = split_message_for_discord(&msg);
-        assert_eq!(chunks.len(), 2);
-        assert_eq!(chunks[0].len(), 4000);
-        assert_eq!(chunks[1].len(), 1000);
+    fn edits_and_deletions_refer_to_the_original_message() {
+        let ch = DiscordChannel::new("fake".into(), Some("G1".into()), vec!["111".into()])
+            .with_channel_ids(vec!["C1".into()]);
+        let mut edited = message(Some("G1"), "C1", "fixed typo", &[]);
+        edited["id"] = "M1".into();
+        edited["edited_timestamp"] = "2026-01-01T00:00:00+00:00".into();
+        let msg = ch.update_message("MESSAGE_UPDATE", &edited, "999").unwrap();
+        assert_eq!(msg.update, Some(MessageUpdate::Edited));
+        assert_eq!(msg.id, "M1");
+        assert_eq!(msg.content, "fixed typo");
+
+        let mut unfurl = edited.clone();
+        unfurl["edited_timestamp"] = serde_json::Value::Null;
+        assert!(ch
+            .update_message("MESSAGE_UPDATE", &unfurl, "999")
+            .is_none());
+
+        let deleted = json!({"id": "M1", "channel_id": "C1", "guild_id": "G1"});
+        let msg = ch
+            .update_message("MESSAGE_DELETE", &deleted, "999")
+            .unwrap();
+        assert_eq!(msg.update, Some(MessageUpdate::Deleted));
+        assert_eq!(msg.id, "M1");
+        assert_eq!(msg.sender, "C1");
+        let elsewhere = json!({"id": "M2", "channel_id": "C2"});
+        assert!(ch
+            .update_message("MESSAGE_DELETE", &elsewhere, "999")
+            .is_none());
     }
 
     #[test]
-    fn split_multiple_breaks() {
-        // Create a message with multiple newlines
-        let part1 = "a".repeat(1500);
-        let part2 = "b".repeat(1500);
-        let part3 = "c".repeat(1500);
-        let msg = format!("{part1}\n{part2}\n{part3}");
-        let chunks = split_message_for_discord(&msg);
-        // Should split into 2 chunks (first two parts + third part)
-        assert_eq!(chunks.len(), 2);
-        assert!(chunks[0].len() <= 4000);
-        assert!(chunks[1].len() <= 4000);
+    fn messages_filtered_by_guild_channel_and_author() {
+        let ch = DiscordChannel::new("fake".into(), Some("G1".into()), vec!["111".into()])
+            .with_channel_ids(vec!["C1".into()]);
+        let msg = ch
+            .to_channel_message(&message(Some("G1"), "C1", "hello", &[]), "999")
+            .unwrap();
+        assert_eq!(msg.sender, "C1");
+        assert_eq!(msg.content, "hello");
+        assert_eq!(msg.audience, Audience::Group);
+
+        assert!(ch
+            .to_channel_message(&message(Some("G2"), "C1", "hello", &[]), "999")
+            .is_none());
+        assert!(ch
+            .to_channel_message(&message(Some("G1"), "C2", "hello", &[]), "999")
+            .is_none());
+        assert!(ch
+            .to_channel_message(&message(None, "C1", "dm", &[]), "999")
+            .is_some());
+        let mut from_bot = message(Some("G1"), "C1", "beep", &[]);
+        from_bot["author"] = json!({"id": "111", "bot": true});
+        assert!(ch.to_channel_message(&from_bot, "999").is_none());
+        assert!(ch
+            .to_channel_message(&message(Some("G1"), "C1", "mine", &[]), "111")
+            .is_none());
     }
 
     #[test]
-    fn split_preserves_content() {
-        let original = "Hello world! This is a test message with some content. ".repeat(200);
-        let chunks = split_message_for_discord(&original);
-        let reconstructed = chunks.concat();
-        assert_eq!(reconstructed, original);
+    fn mention_only_answers_mentions_and_dms() {
+        let ch = DiscordChannel::new("fake".into(), None, vec!["*".into()]).with_mention_only(true);
+        let msg = ch
+            .to_channel_message(
+                &message(Some("G1"), "C1", "<@!999> what's up?", &["999"]),
+                "999",
+            )
+            .unwrap();
+        assert_eq!(msg.content, "what's up?");
+        assert_eq!(msg.audience, Audience::Mention);
+
+        assert!(ch
+            .to_channel_message(&message(Some("G1"), "C1", "chatter", &[]), "999")
+            .is_none());
+        assert!(ch
+            .to_channel_message(&message(Some("G1"), "C1", "<@999>", &["999"]), "999")
+            .is_none());
+        assert!(ch
+            .to_channel_message(&message(None, "D1", "hi", &[]), "999")
+            .is_some());
     }
 
     #[test]
-    fn split_unicode_content() {
-        // Test with emoji and multi-byte characters
-        let msg = "🦀 Rust is awesome! ".repeat(500);
-        let chunks = split_message_for_discord(&msg);
-        // All chunks should be valid UTF-8
-        for chunk in &chunks {
-            assert!(std::str::from_utf8(chunk.as_bytes()).is_ok());
-            assert!(chunk.len() <= 4000);
-        }
-        // Reconstruct and verify
-        let reconstructed = chunks.concat();
-        assert_eq!(reconstructed, msg);
+    fn base64_decode_empty_string() {
+        let decoded = base64_decode("");
+        assert_eq!(decoded, Some(String::new()));
     }
 
     #[test]
-    fn split_newline_too_close_to_end() {
-        // If newline is in the first half, don't use it - use space instead or hard split
-        let msg = format!("{}\n{}", "a".repeat(3900), "b".repeat(2000));
-        let chunks = split_message_for_discord(&msg);
-        // Should split at newline since it's > 2000 chars (half of 4000)
-        assert_eq!(chunks.len(), 2);
+    fn base64_decode_invalid_chars() {
+        let decoded = base64_decode("!!!!");
+        assert!(decoded.is_none());
     }
 
     #[test]
-    fn split_message_with_multiple_newlines() {
-        let msg = "Line 1\nLine 2\nLine 3\n".repeat(1000);
-        let chunks = split_message_for_discord(&msg);
-        assert!(chunks.len() > 1);
-        let reconstructed = chunks.concat();
-        assert_eq!(reconstructed, msg);
+    fn bot_user_id_from_empty_token() {
+        let id = DiscordChannel::bot_user_id_from_token("");
+        assert_eq!(id, Some(String::new()));
     }
 }
@@ -14,17 +14,19 @@ use lettre::transport::smtp::authentication::Credentials;
 use lettre::{Message, SmtpTransport, Transport};
 use mail_parser::{MessageParser, MimeHeaders};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::Write as IoWrite;
 use std::net::TcpStream;
 use std::sync::Mutex;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
-use tokio::time::{interval, sleep};
+use tokio::time::sleep;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use super::traits::{Channel, ChannelMessage};
+use super::poll::AdaptivePoll;
+use super::traits::{Audience, Channel, ChannelMessage};
+use crate::config::schema::ChannelPollingConfig;
 
 /// Email channel configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,20 +95,125 @@ impl Default for EmailConfig {
     }
 }
 
+/// An email fetched from the mailbox
+struct InboundEmail {
+    id: String,
+    sender: String,
+    content: String,
+    timestamp: u64,
+    thread: Option<ReplyThread>,
+}
+
+/// What a reply needs to land in the sender's existing conversation
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReplyThread {
+    subject: String,
+    /// `Message-ID` of the email being answered, in angle brackets
+    message_id: String,
+    /// Its `References`, in angle brackets
+    references: Vec<String>,
+}
+
+impl ReplyThread {
+    fn from_parsed(parsed: &mail_parser::Message) -> Option<Self> {
+        let bracket = |id: &str| format!("<{}>", id.trim_matches(|c| c == '<' || c == '>'));
+        Some(Self {
+            subject: parsed.subject().unwrap_or_default().to_string(),
+            message_id: bracket(parsed.message_id()?),
+            references: parsed
+                .references()
+                .as_text_list()
+                .unwrap_or_default()
+                .iter()
+                .map(|id| bracket(id))
+                .collect(),
+        })
+    }
+
+    /// The original subject with a single `Re: ` prefix
+    fn reply_subject(&self) -> String {
+        let subject = self.subject.trim();
+        if subject.is_empty() {
+            "Re: ZeroClaw Message".into()
+        } else if subject.to_lowercase().starts_with("re:") {
+            subject.to_string()
+        } else {
+            format!("Re: {}", subject)
+        }
+    }
+
+    /// `References` for the reply: the original's references plus its own id
+    fn reply_references(&self) -> String {
+        let mut ids = self.references.clone();
+        ids.push(self.message_id.clone());
+        ids.join(" ")
+    }
+}
+
 /// Email channel — IMAP polling for inbound, SMTP for outbound
 pub struct EmailChannel {
     pub config: EmailConfig,
+    polling: Option<ChannelPollingConfig>,
     seen_messages: Mutex<HashSet<String>>,
+    /// Latest email from each sender (lowercased), so replies thread under it
+    threads: Mutex<HashMap<String, ReplyThread>>,
 }
 
 impl EmailChannel {
     pub fn new(config: EmailConfig) -> Self {
         Self {
             config,
+            polling: None,
             seen_messages: Mutex::new(HashSet::new()),
+            threads: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Poll interval bounds instead of the default: `poll_interval_secs`
+    /// while mail arrives, backing off to ten times that (at most five
+    /// minutes) while the mailbox is quiet.
+    #[must_use]
+    pub fn with_polling(mut self, polling: ChannelPollingConfig) -> Self {
+        self.polling = Some(polling);
+        self
+    }
+
+    /// Build the outgoing email. A message starting with `Subject: ` opens a
+    /// new conversation; anything else answers the recipient's latest email
+    /// with `Re:` and the `In-Reply-To` / `References` headers mail clients
+    /// use for threading.
+    fn compose(&self, message: &str, recipient: &str) -> Result<Message> {
+        let explicit = message
+            .strip_prefix("Subject: ")
+            .and_then(|rest| rest.split_once('\n'));
+        let builder = Message::builder()
+            .from(self.config.from_address.parse()?)
+            .to(recipient.parse()?);
+        let email = if let Some((subject, body)) = explicit {
+            builder
+                .subject(subject.trim())
+                .body(body.trim().to_string())?
+        } else {
+            let thread = self
+                .threads
+                .lock()
+                .unwrap()
+                .get(&recipient.to_lowercase())
+                .cloned();
+            match thread {
+                Some(thread) => builder
+                    .subject(thread.reply_subject())
+                    .in_reply_to(thread.message_id.clone())
+                    .references(thread.reply_references())
+                    .body(message.to_string())?,
+                None => builder
+                    .subject("ZeroClaw Message")
+                    .body(message.to_string())?,
+            }
+        };
+        Ok(email)
+    }
+
     /// Check if a sender email is in the allowlist
     pub fn is_sender_allowed(&self, email: &str) -> bool {
         if self.config.allowed_senders.is_empty() {
@@ -178,7 +285,7 @@ impl EmailChannel {
     }
 
     /// Fetch unseen emails via IMAP (blocking, run in spawn_blocking)
-    fn fetch_unseen_imap(config: &EmailConfig) -> Result<Vec<(String, String, String, u64)>> {
+    fn fetch_unseen_imap(config: &EmailConfig) -> Result<Vec<InboundEmail>> {
         use rustls::ClientConfig as TlsConfig;
         use rustls_pki_types::ServerName;
         use std::sync::Arc;
@@ -319,7 +426,13 @@ impl EmailChannel {
                             .unwrap_or(0)
                     });
 
-                results.push((msg_id, sender, content, ts));
+                results.push(InboundEmail {
+                    id: msg_id,
+                    sender,
+                    content,
+                    timestamp: ts,
+                    thread: ReplyThread::from_parsed(&parsed),
+                });
             }
 
             // Mark as seen with unique tag
@@ -363,22 +476,7 @@ impl Channel for EmailChannel {
     }
 
     async fn send(&self, message: &str, recipient: &str) -> Result<()> {
-        let (subject, body) = if message.starts_with("Subject: ") {
-            if let Some(pos) = message.find('\n') {
-                (&message[9..pos], message[pos + 1..].trim())
-            } else {
-                ("ZeroClaw Message", message)
-            }
-        } else {
-            ("ZeroClaw Message", message)
-        };
-
-        let email = Message::builder()
-            .from(self.config.from_address.parse()?)
-            .to(recipient.parse()?)
-            .subject(subject)
-            .body(body.to_string())?;
-
+        let email = self.compose(message, recipient)?;
         let transport = self.create_smtp_transport()?;
         transport.send(&email)?;
         info!("Email sent to {}", recipient);
@@ -386,22 +484,38 @@ impl Channel for EmailChannel {
     }
 
     async fn listen(&self, tx: mpsc::Sender<ChannelMessage>) -> Result<()> {
+        let polling = self.polling.unwrap_or_else(|| {
+            ChannelPollingConfig::for_channel(
+                &BTreeMap::new(),
+                "email",
+                self.config.poll_interval_secs,
+            )
+        });
         info!(
-            "Email polling every {}s on {}",
-            self.config.poll_interval_secs, self.config.imap_folder
+            "Email polling every {}-{}s on {}",
+            polling.min_interval_secs, polling.max_interval_secs, self.config.imap_folder
         );
-        let mut tick = interval(Duration::from_secs(self.config.poll_interval_secs));
+        let mut poll = AdaptivePoll::new(polling);
         let config = self.config.clone();
 
         loop {
-            tick.tick().await;
             let cfg = config.clone();
             match tokio::task::spawn_blocking(move || Self::fetch_unseen_imap(&cfg)).await {
                 Ok(Ok(messages)) => {
-                    for (id, sender, content, ts) in messages {
+                    poll.record(!messages.is_empty());
+                    for email in messages {
+                        let InboundEmail {
+                            id,
+                            sender,
+                            content,
+                            timestamp,
+                            thread,
+                        } = email;
                         {
                             let mut seen = self.seen_messages.lock().unwrap();
-                            if seen.contains(&id) {
+                            // The persistent check covers messages a previous
+                            // run fetched but did not get to flag as seen
+                            if seen.contains(&id) || !super::state::mark_seen("email", &id) {
                                 continue;
                             }
                             if !self.is_sender_allowed(&sender) {
@@ -410,12 +524,26 @@ impl Channel for EmailChannel {
                             }
                             seen.insert(id.clone());
                         } // MutexGuard dropped before await
+                        if let Some(thread) = thread {
+                            self.threads
+                                .lock()
+                                .unwrap()
+                                .insert(sender.to_lowercase(), thread);
+                        }
                         let msg = ChannelMessage {
                             id,
                             sender,
                             content,
                             channel: "email".to_string(),
-                            timestamp: ts,
+                            images: Vec::new(),
+                            context: None,
+                            reply_to: None,
+                            author: None,
+                            attachments: Vec::new(),
+                            reaction: None,
+                            update: None,
+                            audience: Audience::Direct,
+                            timestamp,
                         };
                         if tx.send(msg).await.is_err() {
                             return Ok(());
@@ -431,6 +559,7 @@ impl Channel for EmailChannel {
                     sleep(Duration::from_secs(10)).await;
                 }
             }
+            sleep(poll.delay()).await;
         }
     }
 
@@ -444,3 +573,70 @@ impl Channel for EmailChannel {
         .unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel() -> EmailChannel {
+        EmailChannel::new(EmailConfig {
+            from_address: "claw@example.com".into(),
+            ..EmailConfig::default()
+        })
+    }
+
+    fn headers(email: &Message) -> String {
+        String::from_utf8_lossy(&email.formatted()).to_string()
+    }
+
+    #[test]
+    fn reply_thread_from_parsed_email() {
+        let raw = "From: alice@example.com\r\n\
+                   Subject: Re: Quarterly report\r\n\
+                   Message-ID: <m2@example.com>\r\n\
+                   References: <m0@example.com> <m1@example.com>\r\n\r\n\
+                   Numbers attached.\r\n";
+        let parsed = MessageParser::default().parse(raw.as_bytes()).unwrap();
+        let thread = ReplyThread::from_parsed(&parsed).unwrap();
+        assert_eq!(thread.message_id, "<m2@example.com>");
+        assert_eq!(thread.reply_subject(), "Re: Quarterly report");
+        assert_eq!(
+            thread.reply_references(),
+            "<m0@example.com> <m1@example.com> <m2@example.com>"
+        );
+
+        let no_id = MessageParser::default()
+            .parse(b"From: a@example.com\r\nSubject: hi\r\n\r\nbody\r\n".as_slice())
+            .unwrap();
+        assert!(ReplyThread::from_parsed(&no_id).is_none());
+    }
+
+    #[test]
+    fn replies_thread_under_the_senders_latest_email() {
+        let ch = channel();
+        ch.threads.lock().unwrap().insert(
+            "alice@example.com".into(),
+            ReplyThread {
+                subject: "Deploy window".into(),
+                message_id: "<m1@example.com>".into(),
+                references: vec![],
+            },
+        );
+
+        let reply = headers(&ch.compose("Tuesday works.", "Alice@Example.com").unwrap());
+        assert!(reply.contains("Subject: Re: Deploy window"));
+        assert!(reply.contains("In-Reply-To: <m1@example.com>"));
+        assert!(reply.contains("References: <m1@example.com>"));
+
+        let fresh = headers(
+            &ch.compose("Subject: Weekly digest\nAll green.", "alice@example.com")
+                .unwrap(),
+        );
+        assert!(fresh.contains("Subject: Weekly digest"));
+        assert!(!fresh.contains("In-Reply-To"));
+
+        let stranger = headers(&ch.compose("hello", "bob@example.com").unwrap());
+        assert!(stranger.contains("Subject: ZeroClaw Message"));
+        assert!(!stranger.contains("In-Reply-To"));
+    }
+}
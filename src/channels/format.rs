@@ -0,0 +1,341 @@
+//! Conversion of the model's Markdown into each platform's own markup.
+//!
+//! Replies are written in Markdown, but Slack reads `mrkdwn`, Telegram is
+//! sent HTML (its Markdown modes reject unbalanced markers outright) and
+//! IRC shows plain text. Headings, bullet lists, fenced and inline code,
+//! bold, italics, strikethrough and links are rewritten; anything else is
+//! passed through. Text inside code is never touched.
+
+use std::sync::OnceLock;
+
+/// Inline spans, tried at each position in this order: inline code,
+/// `**bold**`, `*italic*`, `~~strike~~`, `[text](url)`. Underscores are
+/// left alone, as they are far more common in identifiers than as markup.
+fn inline_pattern() -> &'static regex::Regex {
+    static INLINE: OnceLock<regex::Regex> = OnceLock::new();
+    INLINE.get_or_init(|| {
+        regex::Regex::new(concat!(
+            r"`([^`]+)`",
+            r"|\*\*(.+?)\*\*",
+            r"|\*([^*\s](?:[^*]*[^*\s])?)\*",
+            r"|~~(.+?)~~",
+            r"|\[([^\]]+)\]\((https?://[^)\s]+)\)",
+        ))
+        .expect("valid inline pattern")
+    })
+}
+
+/// How one platform writes each piece of Markdown.
+trait Dialect {
+    fn text(&self, text: &str) -> String;
+    fn code(&self, code: &str) -> String;
+    fn bold(&self, inner: &str) -> String;
+    fn italic(&self, inner: &str) -> String;
+    fn strike(&self, inner: &str) -> String;
+    fn link(&self, text: &str, url: &str) -> String;
+    fn heading(&self, inner: &str) -> String;
+    fn bullet(&self) -> &'static str;
+    fn code_block(&self, lang: &str, code: &str) -> String;
+}
+
+/// Slack `mrkdwn`.
+struct Mrkdwn;
+
+impl Dialect for Mrkdwn {
+    fn text(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn code(&self, code: &str) -> String {
+        format!("`{code}`")
+    }
+
+    fn bold(&self, inner: &str) -> String {
+        format!("*{inner}*")
+    }
+
+    fn italic(&self, inner: &str) -> String {
+        format!("_{inner}_")
+    }
+
+    fn strike(&self, inner: &str) -> String {
+        format!("~{inner}~")
+    }
+
+    fn link(&self, text: &str, url: &str) -> String {
+        format!("<{url}|{text}>")
+    }
+
+    fn heading(&self, inner: &str) -> String {
+        self.bold(inner)
+    }
+
+    fn bullet(&self) -> &'static str {
+        "•"
+    }
+
+    fn code_block(&self, _lang: &str, code: &str) -> String {
+        format!("```\n{code}\n```")
+    }
+}
+
+/// Telegram's HTML `parse_mode`.
+struct TelegramHtml;
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Dialect for TelegramHtml {
+    fn text(&self, text: &str) -> String {
+        escape_html(text)
+    }
+
+    fn code(&self, code: &str) -> String {
+        format!("<code>{}</code>", escape_html(code))
+    }
+
+    fn bold(&self, inner: &str) -> String {
+        format!("<b>{inner}</b>")
+    }
+
+    fn italic(&self, inner: &str) -> String {
+        format!("<i>{inner}</i>")
+    }
+
+    fn strike(&self, inner: &str) -> String {
+        format!("<s>{inner}</s>")
+    }
+
+    fn link(&self, text: &str, url: &str) -> String {
+        format!(
+            "<a href=\"{}\">{text}</a>",
+            escape_html(url).replace('"', "&quot;")
+        )
+    }
+
+    fn heading(&self, inner: &str) -> String {
+        self.bold(inner)
+    }
+
+    fn bullet(&self) -> &'static str {
+        "•"
+    }
+
+    fn code_block(&self, lang: &str, code: &str) -> String {
+        let lang: String = lang
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+'))
+            .collect();
+        if lang.is_empty() {
+            format!("<pre>{}</pre>", escape_html(code))
+        } else {
+            format!(
+                "<pre><code class=\"language-{lang}\">{}</code></pre>",
+                escape_html(code)
+            )
+        }
+    }
+}
+
+/// Plain text, for clients that show markup literally.
+struct PlainText;
+
+impl Dialect for PlainText {
+    fn text(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn code(&self, code: &str) -> String {
+        code.to_string()
+    }
+
+    fn bold(&self, inner: &str) -> String {
+        inner.to_string()
+    }
+
+    fn italic(&self, inner: &str) -> String {
+        inner.to_string()
+    }
+
+    fn strike(&self, inner: &str) -> String {
+        inner.to_string()
+    }
+
+    fn link(&self, text: &str, url: &str) -> String {
+        if text == url {
+            url.to_string()
+        } else {
+            format!("{text} ({url})")
+        }
+    }
+
+    fn heading(&self, inner: &str) -> String {
+        inner.to_string()
+    }
+
+    fn bullet(&self) -> &'static str {
+        "-"
+    }
+
+    fn code_block(&self, _lang: &str, code: &str) -> String {
+        code.to_string()
+    }
+}
+
+/// Rewrite the inline spans of one line of prose.
+fn render_inline(text: &str, dialect: &dyn Dialect) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in inline_pattern().captures_iter(text) {
+        let whole = caps.get(0).expect("match");
+        out.push_str(&dialect.text(&text[last..whole.start()]));
+        let inner = |group: usize| caps.get(group).map(|m| render_inline(m.as_str(), dialect));
+        let span = if let Some(code) = caps.get(1) {
+            dialect.code(code.as_str())
+        } else if let Some(bold) = inner(2) {
+            dialect.bold(&bold)
+        } else if let Some(italic) = inner(3) {
+            dialect.italic(&italic)
+        } else if let Some(strike) = inner(4) {
+            dialect.strike(&strike)
+        } else {
+            let label = inner(5).unwrap_or_default();
+            dialect.link(&label, caps.get(6).map_or("", |m| m.as_str()))
+        };
+        out.push_str(&span);
+        last = whole.end();
+    }
+    out.push_str(&dialect.text(&text[last..]));
+    out
+}
+
+/// Rewrite one line of prose: headings and bullets, then inline spans.
+fn render_line(line: &str, dialect: &dyn Dialect) -> String {
+    let heading = line.trim_start_matches('#');
+    let level = line.len() - heading.len();
+    if (1..=6).contains(&level) && heading.starts_with(' ') {
+        return dialect.heading(&render_inline(heading.trim(), dialect));
+    }
+    let body = line.trim_start();
+    let indent = &line[..line.len() - body.len()];
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(item) = body.strip_prefix(marker) {
+            return format!(
+                "{}{} {}",
+                dialect.text(indent),
+                dialect.bullet(),
+                render_inline(item, dialect)
+            );
+        }
+    }
+    render_inline(line, dialect)
+}
+
+fn render(markdown: &str, dialect: &dyn Dialect) -> String {
+    let mut out = Vec::new();
+    let mut fence: Option<(&str, Vec<&str>)> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            match fence.take() {
+                Some((lang, code)) => out.push(dialect.code_block(lang, &code.join("\n"))),
+                None => fence = Some((trimmed.trim_start_matches('`').trim(), Vec::new())),
+            }
+            continue;
+        }
+        match fence.as_mut() {
+            Some((_, code)) => code.push(line),
+            None => out.push(render_line(line, dialect)),
+        }
+    }
+    // An unclosed fence still renders as code.
+    if let Some((lang, code)) = fence {
+        out.push(dialect.code_block(lang, &code.join("\n")));
+    }
+    out.join("\n")
+}
+
+/// Markdown as Slack `mrkdwn`: `**bold**` and headings become `*bold*`,
+/// `*italic*` becomes `_italic_`, `[text](url)` becomes `<url|text>`.
+pub fn to_slack_mrkdwn(markdown: &str) -> String {
+    render(markdown, &Mrkdwn)
+}
+
+/// Markdown as HTML for Telegram's `parse_mode: "HTML"`, with `<`, `>` and
+/// `&` escaped everywhere else.
+pub fn to_telegram_html(markdown: &str) -> String {
+    render(markdown, &TelegramHtml)
+}
+
+/// Markdown with its markup removed; links keep their URL in parentheses.
+pub fn to_plain_text(markdown: &str) -> String {
+    render(markdown, &PlainText)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLY: &str = "## Fix\n\
+        Run **this** with *care*, see [docs](https://example.com/d?a=1&b=2):\n\
+        - call `a < b`\n\
+        - ~~old~~ way\n\
+        ```rust\n\
+        let x = **y** < 1;\n\
+        ```";
+
+    #[test]
+    fn slack_gets_mrkdwn() {
+        assert_eq!(
+            to_slack_mrkdwn(REPLY),
+            "*Fix*\n\
+             Run *this* with _care_, see <https://example.com/d?a=1&b=2|docs>:\n\
+             • call `a < b`\n\
+             • ~old~ way\n\
+             ```\nlet x = **y** < 1;\n```"
+        );
+    }
+
+    #[test]
+    fn telegram_gets_escaped_html() {
+        assert_eq!(
+            to_telegram_html(REPLY),
+            "<b>Fix</b>\n\
+             Run <b>this</b> with <i>care</i>, see \
+             <a href=\"https://example.com/d?a=1&amp;b=2\">docs</a>:\n\
+             • call <code>a &lt; b</code>\n\
+             • <s>old</s> way\n\
+             <pre><code class=\"language-rust\">let x = **y** &lt; 1;</code></pre>"
+        );
+        assert_eq!(to_telegram_html("```\n<tag>"), "<pre>&lt;tag&gt;</pre>");
+    }
+
+    #[test]
+    fn plain_text_drops_markup() {
+        assert_eq!(
+            to_plain_text(REPLY),
+            "Fix\n\
+             Run this with care, see docs (https://example.com/d?a=1&b=2):\n\
+             - call a < b\n\
+             - old way\n\
+             let x = **y** < 1;"
+        );
+    }
+
+    #[test]
+    fn nested_spans_and_stray_markers() {
+        assert_eq!(
+            to_telegram_html("**bold `code` and [link](https://x.io)**"),
+            "<b>bold <code>code</code> and <a href=\"https://x.io\">link</a></b>"
+        );
+        assert_eq!(to_slack_mrkdwn("2 * 3 * 4 = 24"), "2 * 3 * 4 = 24");
+        assert_eq!(
+            to_slack_mrkdwn("#hashtag and __init__"),
+            "#hashtag and __init__"
+        );
+        assert_eq!(to_plain_text("  * nested item"), "  - nested item");
+    }
+}
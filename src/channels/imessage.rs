@@ -1,16 +1,22 @@
-use crate::channels::traits::{Channel, ChannelMessage};
+use crate::channels::poll::AdaptivePoll;
+use crate::channels::traits::{Audience, Channel, ChannelMessage};
+use crate::config::schema::ChannelPollingConfig;
 use async_trait::async_trait;
 use directories::UserDirs;
 use rusqlite::{Connection, OpenFlags};
+use std::collections::BTreeMap;
 use std::path::Path;
 use tokio::sync::mpsc;
 
 /// iMessage channel using macOS `AppleScript` bridge.
-/// Polls the Messages database for new messages and sends replies via `osascript`.
+/// Polls the Messages database for new messages and sends replies via
+/// `osascript`, or through a Shortcuts shortcut when one is configured.
 #[derive(Clone)]
 pub struct IMessageChannel {
     allowed_contacts: Vec<String>,
     poll_interval_secs: u64,
+    polling: Option<ChannelPollingConfig>,
+    shortcut: Option<String>,
 }
 
 impl IMessageChannel {
@@ -18,9 +24,58 @@ impl IMessageChannel {
         Self {
             allowed_contacts,
             poll_interval_secs: 3,
+            polling: None,
+            shortcut: None,
         }
     }
 
+    /// Check the Messages database every `secs` seconds (minimum 1).
+    #[must_use]
+    pub fn with_poll_interval_secs(mut self, secs: u64) -> Self {
+        self.poll_interval_secs = secs.max(1);
+        self
+    }
+
+    /// Poll interval bounds instead of the default: the poll interval while
+    /// busy, backing off to ten times that while quiet.
+    #[must_use]
+    pub fn with_polling(mut self, polling: ChannelPollingConfig) -> Self {
+        self.polling = Some(polling);
+        self
+    }
+
+    /// Send replies by running this Shortcuts shortcut, which receives
+    /// `{"recipient": ..., "message": ...}` as its input.
+    #[must_use]
+    pub fn with_shortcut(mut self, shortcut: Option<String>) -> Self {
+        self.shortcut = shortcut.filter(|s| !s.trim().is_empty());
+        self
+    }
+
+    /// Run `shortcut` with the reply as a JSON input file.
+    async fn send_via_shortcut(shortcut: &str, message: &str, target: &str) -> anyhow::Result<()> {
+        let input = serde_json::json!({ "recipient": target, "message": message });
+        let input_path =
+            std::env::temp_dir().join(format!("zeroclaw-imessage-{}.json", uuid::Uuid::new_v4()));
+        tokio::fs::write(&input_path, input.to_string()).await?;
+
+        let output = tokio::process::Command::new("shortcuts")
+            .arg("run")
+            .arg(shortcut)
+            .arg("--input-path")
+            .arg(&input_path)
+            .output()
+            .await;
+        let _ = tokio::fs::remove_file(&input_path).await;
+
+        let output = output?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("iMessage send via shortcut \"{shortcut}\" failed: {stderr}");
+        }
+        Ok(())
+    }
+
     fn is_contact_allowed(&self, sender: &str) -> bool {
         if self.allowed_contacts.iter().any(|u| u == "*") {
             return true;
@@ -44,6 +99,33 @@ fn escape_applescript(s: &str) -> String {
         .replace('\r', "\\r")
 }
 
+/// Recover the plain text of a message from its `attributedBody` column.
+///
+/// Recent macOS versions often leave `message.text` NULL and store the text
+/// only in this `NSAttributedString` typedstream. The string follows the
+/// `NSString` class name as a `+` marker, a length (one byte, or `0x81` /
+/// `0x82` followed by a little-endian `u16` / `u32`) and UTF-8 bytes.
+fn decode_attributed_body(blob: &[u8]) -> Option<String> {
+    let class = b"NSString";
+    let after_class = blob.windows(class.len()).position(|w| w == class)? + class.len();
+    let rest = &blob[after_class..];
+    let marker = rest.iter().take(16).position(|&b| b == b'+')?;
+    let rest = &rest[marker + 1..];
+    let (len, header) = match *rest.first()? {
+        0x81 => (
+            usize::from(u16::from_le_bytes([*rest.get(1)?, *rest.get(2)?])),
+            3,
+        ),
+        0x82 => {
+            let bytes = [*rest.get(1)?, *rest.get(2)?, *rest.get(3)?, *rest.get(4)?];
+            (usize::try_from(u32::from_le_bytes(bytes)).ok()?, 5)
+        }
+        len => (usize::from(len), 1),
+    };
+    let text = rest.get(header..header + len)?;
+    Some(String::from_utf8_lossy(text).into_owned())
+}
+
 /// Validate that a target looks like a valid phone number or email address.
 ///
 /// This is a defense-in-depth measure to reject obviously malicious targets
@@ -103,6 +185,10 @@ impl Channel for IMessageChannel {
             );
         }
 
+        if let Some(shortcut) = &self.shortcut {
+            return Self::send_via_shortcut(shortcut, message, target).await;
+        }
+
         // SECURITY: Escape both message AND target to prevent AppleScript injection
         // See: CWE-78 (OS Command Injection)
         let escaped_msg = escape_applescript(message);
@@ -149,13 +235,17 @@ end tell"#
         // Track the last ROWID we've seen
         let mut last_rowid = get_max_rowid(&db_path).await.unwrap_or(0);
 
+        let mut poll = AdaptivePoll::new(self.polling.unwrap_or_else(|| {
+            ChannelPollingConfig::for_channel(&BTreeMap::new(), "imessage", self.poll_interval_secs)
+        }));
         loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(self.poll_interval_secs)).await;
+            tokio::time::sleep(poll.delay()).await;
 
             let new_messages = fetch_new_messages(&db_path, last_rowid).await;
 
             match new_messages {
                 Ok(messages) => {
+                    poll.record(!messages.is_empty());
                     for (rowid, sender, text) in messages {
                         if rowid > last_rowid {
                             last_rowid = rowid;
@@ -174,6 +264,14 @@ end tell"#
                             sender: sender.clone(),
                             content: text,
                             channel: "imessage".to_string(),
+                            images: Vec::new(),
+                            context: None,
+                            reply_to: None,
+                            author: None,
+                            attachments: Vec::new(),
+                            reaction: None,
+                            update: None,
+                            audience: Audience::Direct,
                             timestamp: std::time::SystemTime::now()
                                 .duration_since(std::time::UNIX_EPOCH)
                                 .unwrap_or_default()
@@ -237,21 +335,25 @@ async fn fetch_new_messages(
                 OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
             )?;
             let mut stmt = conn.prepare(
-                "SELECT m.ROWID, h.id, m.text \
+                "SELECT m.ROWID, h.id, m.text, m.attributedBody \
              FROM message m \
              JOIN handle h ON m.handle_id = h.ROWID \
              WHERE m.ROWID > ?1 \
              AND m.is_from_me = 0 \
-             AND m.text IS NOT NULL \
+             AND (m.text IS NOT NULL OR m.attributedBody IS NOT NULL) \
              ORDER BY m.ROWID ASC \
              LIMIT 20",
             )?;
             let rows = stmt.query_map([since_rowid], |row| {
-                Ok((
-                    row.get::<_, i64>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, String>(2)?,
-                ))
+                let text = match row.get::<_, Option<String>>(2)? {
+                    Some(text) => text,
+                    None => row
+                        .get::<_, Option<Vec<u8>>>(3)?
+                        .as_deref()
+                        .and_then(decode_attributed_body)
+                        .unwrap_or_default(),
+                };
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, text))
             })?;
             rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
         })
@@ -270,6 +372,48 @@ mod tests {
         assert_eq!(ch.poll_interval_secs, 3);
     }
 
+    #[test]
+    fn builders_set_poll_interval_and_shortcut() {
+        let ch = IMessageChannel::new(vec![])
+            .with_poll_interval_secs(0)
+            .with_shortcut(Some("  ".into()));
+        assert_eq!(ch.poll_interval_secs, 1);
+        assert!(ch.shortcut.is_none());
+        let ch = ch.with_shortcut(Some("Reply via Messages".into()));
+        assert_eq!(ch.shortcut.as_deref(), Some("Reply via Messages"));
+    }
+
+    fn attributed_body(text: &str) -> Vec<u8> {
+        let mut blob = b"\x04\x0bstreamtyped\x81\xe8\x03\x84\x01@\x84\x84\x84\x12NSAttributedString\x00\x84\x84\x08NSObject\x00\x85\x92\x84\x84\x84\x08NSString\x01\x94\x84\x01+".to_vec();
+        let len = text.len();
+        if len < 0x80 {
+            blob.push(u8::try_from(len).unwrap());
+        } else {
+            blob.push(0x81);
+            blob.extend_from_slice(&u16::try_from(len).unwrap().to_le_bytes());
+        }
+        blob.extend_from_slice(text.as_bytes());
+        blob.extend_from_slice(b"\x86\x84\x02iI\x01");
+        blob
+    }
+
+    #[test]
+    fn decodes_text_from_attributed_body() {
+        assert_eq!(
+            decode_attributed_body(&attributed_body("see you at 6 🦀")).as_deref(),
+            Some("see you at 6 🦀")
+        );
+        let long = "x".repeat(300);
+        assert_eq!(
+            decode_attributed_body(&attributed_body(&long)).as_deref(),
+            Some(long.as_str())
+        );
+        assert!(decode_attributed_body(b"no class marker here").is_none());
+        let mut truncated = attributed_body("hello");
+        truncated.truncate(truncated.len() - 8);
+        assert!(decode_attributed_body(&truncated).is_none());
+    }
+
     #[test]
     fn creates_with_empty_contacts() {
         let ch = IMessageChannel::new(vec![]);
@@ -554,6 +698,7 @@ mod tests {
                 ROWID INTEGER PRIMARY KEY,
                 handle_id INTEGER,
                 text TEXT,
+                attributedBody BLOB,
                 is_from_me INTEGER DEFAULT 0,
                 FOREIGN KEY (handle_id) REFERENCES handle(ROWID)
             );",
@@ -746,6 +891,29 @@ mod tests {
         assert_eq!(result[0].2, "Has text");
     }
 
+    #[tokio::test]
+    async fn fetch_new_messages_falls_back_to_attributed_body() {
+        let (_dir, db_path) = create_test_db();
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute(
+                "INSERT INTO handle (ROWID, id) VALUES (1, '+1234567890')",
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO message (ROWID, handle_id, text, attributedBody, is_from_me) \
+                 VALUES (10, 1, NULL, ?1, 0)",
+                [attributed_body("sent from Ventura")],
+            )
+            .unwrap();
+        }
+
+        let result = fetch_new_messages(&db_path, 0).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].2, "sent from Ventura");
+    }
+
     #[tokio::test]
     async fn fetch_new_messages_respects_limit() {
         let (_dir, db_path) = create_test_db();
@@ -1,4 +1,6 @@
-use crate::channels::traits::{Channel, ChannelMessage};
+use crate::channels::format::to_plain_text;
+use crate::channels::split::split_message_bytes;
+use crate::channels::traits::{Audience, Channel, ChannelMessage};
 use async_trait::async_trait;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -31,6 +33,7 @@ pub struct IrcChannel {
     nickserv_password: Option<String>,
     sasl_password: Option<String>,
     verify_tls: bool,
+    mention_only: bool,
     /// Shared write half of the TLS stream for sending messages.
     writer: Arc<Mutex<Option<WriteHalf>>>,
 }
@@ -113,6 +116,33 @@ impl IrcMessage {
     }
 }
 
+/// Whether `c` can be part of an IRC nickname.
+fn is_nick_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-_[]\\`^{}|".contains(c)
+}
+
+/// `text` if it is addressed to `nick`: with a leading `nick:` / `nick,`
+/// removed, or unchanged when the nick appears elsewhere as a whole word.
+pub(super) fn addressed_text<'a>(text: &'a str, nick: &str) -> Option<&'a str> {
+    let lower = text.to_ascii_lowercase();
+    let nick = nick.to_ascii_lowercase();
+    if nick.is_empty() {
+        return None;
+    }
+    if lower.starts_with(&nick) && !text[nick.len()..].starts_with(is_nick_char) {
+        let rest = text[nick.len()..]
+            .trim_start_matches([':', ','])
+            .trim_start();
+        return Some(rest);
+    }
+    let mentioned = lower.match_indices(&nick).any(|(start, _)| {
+        let before = lower[..start].chars().next_back();
+        let after = lower[start + nick.len()..].chars().next();
+        !before.is_some_and(is_nick_char) && !after.is_some_and(is_nick_char)
+    });
+    mentioned.then_some(text)
+}
+
 /// Encode SASL PLAIN credentials: base64(\0nick\0password).
 fn encode_sasl_plain(nick: &str, password: &str) -> String {
     // Simple base64 encoder — avoids adding a base64 crate dependency.
@@ -189,27 +219,9 @@ fn split_message(message: &str, max_bytes: usize) -> Vec<String> {
         }
 
         // Line exceeds max_bytes — split at safe UTF-8 boundaries
-        let mut remaining = line;
-        while !remaining.is_empty() {
-            if remaining.len() <= max_bytes {
-                chunks.push(remaining.to_string());
-                break;
-            }
-
-            let mut split_at = max_bytes;
-            while split_at > 0 && !remaining.is_char_boundary(split_at) {
-                split_at -= 1;
-            }
-            if split_at == 0 {
-                // No valid boundary found going backward — advance forward instead
-                split_at = max_bytes;
-                while split_at < remaining.len() && !remaining.is_char_boundary(split_at) {
-                    split_at += 1;
-                }
-            }
-
-            chunks.push(remaining[..split_at].to_string());
-            remaining = &remaining[split_at..];
+        // (a reopened code fence would start a new line, so flatten those)
+        for piece in split_message_bytes(line, max_bytes) {
+            chunks.extend(piece.lines().filter(|l| !l.is_empty()).map(str::to_string));
         }
     }
 
@@ -246,10 +258,18 @@ impl IrcChannel {
             nickserv_password,
             sasl_password,
             verify_tls,
+            mention_only: true,
             writer: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// In channels, only answer messages addressed to the bot.
+    #[must_use]
+    pub fn with_mention_only(mut self, mention_only: bool) -> Self {
+        self.mention_only = mention_only;
+        self
+    }
+
     fn is_user_allowed(&self, nick: &str) -> bool {
         if self.allowed_users.iter().any(|u| u == "*") {
             return true;
@@ -353,7 +373,7 @@ impl Channel for IrcChannel {
         // 512 - sender prefix (~64 bytes for :nick!user@host) - "PRIVMSG " - target - " :" - "\r\n"
         let overhead = SENDER_PREFIX_RESERVE + 10 + recipient.len() + 2;
         let max_payload = 512_usize.saturating_sub(overhead);
-        let chunks = split_message(message, max_payload);
+        let chunks = split_message(&to_plain_text(message), max_payload);
 
         for chunk in chunks {
             Self::send_raw(writer, &format!("PRIVMSG {recipient} :{chunk}")).await?;
@@ -540,6 +560,26 @@ impl Channel for IrcChannel {
                     // Determine reply target: if sent to a channel, reply to channel;
                     // if DM (target == our nick), reply to sender
                     let is_channel = target.starts_with('#') || target.starts_with('&');
+                    let addressed = if is_channel {
+                        addressed_text(text, &current_nick).filter(|t| !t.is_empty())
+                    } else {
+                        None
+                    };
+                    let text = if is_channel && self.mention_only {
+                        match addressed {
+                            Some(text) => text,
+                            None => continue,
+                        }
+                    } else {
+                        text
+                    };
+                    let audience = if !is_channel {
+                        Audience::Direct
+                    } else if addressed.is_some() {
+                        Audience::Mention
+                    } else {
+                        Audience::Group
+                    };
                     let reply_to = if is_channel {
                         target.to_string()
                     } else {
@@ -557,6 +597,14 @@ impl Channel for IrcChannel {
                         sender: reply_to,
                         content,
                         channel: "irc".to_string(),
+                        images: Vec::new(),
+                        context: None,
+                        reply_to: None,
+                        author: Some(sender_nick.to_string()),
+                        attachments: Vec::new(),
+                        reaction: None,
+                        update: None,
+                        audience,
                         timestamp: std::time::SystemTime::now()
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_default()
@@ -920,6 +968,35 @@ mod tests {
         assert!(!ch.verify_tls);
     }
 
+    // ── Addressing ──────────────────────────────────────────
+
+    #[test]
+    fn addressed_text_strips_leading_nick() {
+        assert_eq!(
+            addressed_text("zcbot: deploy status?", "zcbot"),
+            Some("deploy status?")
+        );
+        assert_eq!(addressed_text("ZCBot, hi", "zcbot"), Some("hi"));
+        assert_eq!(addressed_text("zcbot", "zcbot"), Some(""));
+    }
+
+    #[test]
+    fn addressed_text_accepts_nick_as_whole_word() {
+        assert_eq!(
+            addressed_text("what does zcbot think?", "zcbot"),
+            Some("what does zcbot think?")
+        );
+        assert_eq!(addressed_text("zcbot_: ping", "zcbot_"), Some("ping"));
+    }
+
+    #[test]
+    fn addressed_text_ignores_other_messages() {
+        assert_eq!(addressed_text("morning all", "zcbot"), None);
+        assert_eq!(addressed_text("zcbot_: not me", "zcbot"), None);
+        assert_eq!(addressed_text("ask zcbots later", "zcbot"), None);
+        assert_eq!(addressed_text("anything", ""), None);
+    }
+
     // ── Config serde ────────────────────────────────────────
 
     #[test]
@@ -937,6 +1014,7 @@ mod tests {
             nickserv_password: Some("secret".into()),
             sasl_password: None,
             verify_tls: Some(true),
+            mention_only: false,
         };
 
         let toml_str = toml::to_string(&config).unwrap();
@@ -951,6 +1029,7 @@ mod tests {
         assert_eq!(parsed.nickserv_password.as_deref(), Some("secret"));
         assert!(parsed.sasl_password.is_none());
         assert_eq!(parsed.verify_tls, Some(true));
+        assert!(!parsed.mention_only);
     }
 
     #[test]
@@ -972,6 +1051,7 @@ nickname = "bot"
         assert!(parsed.nickserv_password.is_none());
         assert!(parsed.sasl_password.is_none());
         assert!(parsed.verify_tls.is_none());
+        assert!(parsed.mention_only);
     }
 
     #[test]
@@ -0,0 +1,574 @@
+use super::traits::{Attachment, Channel, ChannelMessage};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Messages the shared inbox buffers before listeners wait.
+const INBOX_CAPACITY: usize = 100;
+
+/// Longest one channel health check may take before it counts as failed.
+const HEALTH_CHECK_TIMEOUT_SECS: u64 = 10;
+
+/// Runs every configured channel together: one supervised listener per
+/// channel, all feeding a single inbox, and replies routed back to the
+/// channel each message came from (its [`ChannelMessage::channel`] tag).
+pub struct ChannelManager {
+    channels: Vec<Arc<dyn Channel>>,
+    initial_backoff_secs: u64,
+    max_backoff_secs: u64,
+    alert_after_failures: u32,
+    /// `(channel, recipient)` that channel-down alerts are sent to
+    alert_target: Option<(String, String)>,
+    /// Seconds between health checks of every channel; 0 never checks
+    health_check_interval_secs: u64,
+    handles: Vec<JoinHandle<()>>,
+    monitor: Option<JoinHandle<()>>,
+}
+
+/// How a supervised listener is restarted and when its failures are
+/// reported.
+#[derive(Clone)]
+struct RestartPolicy {
+    initial_backoff_secs: u64,
+    max_backoff_secs: u64,
+    /// Consecutive failures before alerting; 0 never alerts
+    alert_after_failures: u32,
+    alert: Option<(Arc<dyn Channel>, String)>,
+}
+
+impl ChannelManager {
+    pub fn new(channels: Vec<Arc<dyn Channel>>) -> Self {
+        Self {
+            channels,
+            initial_backoff_secs: super::DEFAULT_CHANNEL_INITIAL_BACKOFF_SECS,
+            max_backoff_secs: super::DEFAULT_CHANNEL_MAX_BACKOFF_SECS,
+            alert_after_failures: 0,
+            alert_target: None,
+            health_check_interval_secs: 0,
+            handles: Vec::new(),
+            monitor: None,
+        }
+    }
+
+    /// Delay before restarting a failed listener, doubling per consecutive
+    /// failure up to `max_secs`.
+    #[must_use]
+    pub fn with_backoff(mut self, initial_secs: u64, max_secs: u64) -> Self {
+        self.initial_backoff_secs = initial_secs;
+        self.max_backoff_secs = max_secs;
+        self
+    }
+
+    /// Report a channel as down after `after_failures` consecutive listener
+    /// failures: logged as an error and, when `target` is `channel:recipient`
+    /// (`telegram:123456789`), sent there through that channel.
+    #[must_use]
+    pub fn with_alerts(mut self, after_failures: u32, target: Option<&str>) -> Self {
+        self.alert_after_failures = after_failures;
+        self.alert_target = target.and_then(|t| {
+            let parsed = t
+                .split_once(':')
+                .map(|(channel, recipient)| (channel.trim(), recipient.trim()))
+                .filter(|(channel, recipient)| !channel.is_empty() && !recipient.is_empty());
+            if parsed.is_none() {
+                tracing::warn!("Ignoring channel alert target {t:?}: expected channel:recipient");
+            }
+            parsed.map(|(channel, recipient)| (channel.to_string(), recipient.to_string()))
+        });
+        self
+    }
+
+    /// Call every channel's [`Channel::health_check`] each `interval_secs`
+    /// (0 = never), recording the results for `/health` and `zeroclaw
+    /// status`. Failed checks count toward the [`Self::with_alerts`]
+    /// threshold like listener failures.
+    #[must_use]
+    pub fn with_health_checks(mut self, interval_secs: u64) -> Self {
+        self.health_check_interval_secs = interval_secs;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.channels.iter().map(|c| c.name()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Channel>> {
+        self.channels.iter().find(|c| c.name() == name)
+    }
+
+    /// Spawn a listener for every channel and return the merged inbox. It
+    /// closes once every listener has stopped.
+    pub fn start(&mut self) -> mpsc::Receiver<ChannelMessage> {
+        let alert = self.alert_target.as_ref().and_then(|(name, recipient)| {
+            let channel = self.get(name).map(|ch| (Arc::clone(ch), recipient.clone()));
+            if channel.is_none() {
+                tracing::warn!("Channel alerts only logged: channel {name} is not running");
+            }
+            channel
+        });
+        let policy = RestartPolicy {
+            initial_backoff_secs: self.initial_backoff_secs,
+            max_backoff_secs: self.max_backoff_secs,
+            alert_after_failures: self.alert_after_failures,
+            alert,
+        };
+        if self.health_check_interval_secs > 0 {
+            self.monitor = Some(spawn_health_monitor(
+                self.channels.clone(),
+                self.health_check_interval_secs,
+                policy.clone(),
+            ));
+        }
+        let (tx, rx) = mpsc::channel(INBOX_CAPACITY);
+        for ch in &self.channels {
+            self.handles.push(spawn_supervised_listener(
+                Arc::clone(ch),
+                tx.clone(),
+                policy.clone(),
+            ));
+        }
+        rx
+    }
+
+    /// Send `text` back to the sender of `msg` on the channel it came from.
+    pub async fn reply(&self, msg: &ChannelMessage, text: &str) -> anyhow::Result<()> {
+        let channel = self
+            .get(&msg.channel)
+            .ok_or_else(|| anyhow::anyhow!("Channel not running: {}", msg.channel))?;
+        channel.send(text, &msg.sender).await
+    }
+
+    /// Earlier messages `msg` replies to, fetched from the channel it came
+    /// from.
+    pub async fn fetch_context(&self, msg: &ChannelMessage) -> anyhow::Result<Option<String>> {
+        let channel = self
+            .get(&msg.channel)
+            .ok_or_else(|| anyhow::anyhow!("Channel not running: {}", msg.channel))?;
+        channel.fetch_context(msg).await
+    }
+
+    /// Reply to `msg` with `files`, the first carrying `text` as its
+    /// caption; a plain reply when there are no files.
+    pub async fn reply_with_attachments(
+        &self,
+        msg: &ChannelMessage,
+        text: &str,
+        files: &[Attachment],
+    ) -> anyhow::Result<()> {
+        let Some((first, rest)) = files.split_first() else {
+            return self.reply(msg, text).await;
+        };
+        let channel = self
+            .get(&msg.channel)
+            .ok_or_else(|| anyhow::anyhow!("Channel not running: {}", msg.channel))?;
+        channel.send_attachment(first, text, &msg.sender).await?;
+        for file in rest {
+            channel.send_attachment(file, "", &msg.sender).await?;
+        }
+        Ok(())
+    }
+
+    /// Wait for every listener to stop, then stop the health checks.
+    pub async fn join(self) {
+        for handle in self.handles {
+            let _ = handle.await;
+        }
+        if let Some(monitor) = self.monitor {
+            monitor.abort();
+        }
+    }
+}
+
+/// Run `ch.listen` until the inbox closes, restarting it after failures
+/// with exponential backoff. A listener that stayed up longer than the
+/// maximum backoff counts as recovered: backoff and the failure streak start
+/// over.
+fn spawn_supervised_listener(
+    ch: Arc<dyn Channel>,
+    tx: mpsc::Sender<ChannelMessage>,
+    policy: RestartPolicy,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let component = format!("channel:{}", ch.name());
+        let initial_backoff = policy.initial_backoff_secs.max(1);
+        let max_backoff = policy.max_backoff_secs.max(initial_backoff);
+        let mut backoff = initial_backoff;
+        let mut failures: u32 = 0;
+
+        loop {
+            crate::health::mark_component_ok(&component);
+            let started = tokio::time::Instant::now();
+            let result = ch.listen(tx.clone()).await;
+
+            if tx.is_closed() {
+                break;
+            }
+
+            if started.elapsed() >= Duration::from_secs(max_backoff) {
+                if failures >= policy.alert_after_failures && policy.alert_after_failures > 0 {
+                    tracing::info!("Channel {} recovered after {failures} failures", ch.name());
+                }
+                backoff = initial_backoff;
+                failures = 0;
+            }
+
+            let error = match result {
+                Ok(()) => {
+                    tracing::warn!("Channel {} exited unexpectedly; restarting", ch.name());
+                    // Clean exit — reset backoff since the listener ran successfully
+                    backoff = initial_backoff;
+                    "listener exited unexpectedly".to_string()
+                }
+                Err(e) => {
+                    tracing::error!("Channel {} error: {e}; restarting", ch.name());
+                    e.to_string()
+                }
+            };
+            crate::health::mark_component_error(&component, &error);
+            failures = failures.saturating_add(1);
+            if failures == policy.alert_after_failures {
+                alert_channel_down(ch.name(), failures, &error, policy.alert.as_ref()).await;
+            }
+
+            crate::health::bump_component_restart(&component);
+            tokio::time::sleep(Duration::from_secs(backoff)).await;
+            // Double backoff AFTER sleeping so first error uses initial_backoff
+            backoff = backoff.saturating_mul(2).min(max_backoff);
+        }
+    })
+}
+
+/// Check every channel each `interval_secs`, first right away.
+fn spawn_health_monitor(
+    channels: Vec<Arc<dyn Channel>>,
+    interval_secs: u64,
+    policy: RestartPolicy,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            for ch in &channels {
+                check_channel_health(ch.as_ref(), &policy).await;
+            }
+        }
+    })
+}
+
+/// Run one health check of `ch` and record it; report the channel as down
+/// when the failure streak reaches the alert threshold. The alert only goes
+/// through the target channel while that channel's own checks pass.
+async fn check_channel_health(ch: &dyn Channel, policy: &RestartPolicy) {
+    let healthy = tokio::time::timeout(
+        Duration::from_secs(HEALTH_CHECK_TIMEOUT_SECS),
+        ch.health_check(),
+    )
+    .await
+    .unwrap_or(false);
+    let failures = crate::health::record_channel_check(ch.name(), healthy);
+    if healthy {
+        return;
+    }
+    tracing::warn!(
+        "Channel {} health check failed ({failures} in a row)",
+        ch.name()
+    );
+    if failures == policy.alert_after_failures {
+        let target = policy
+            .alert
+            .as_ref()
+            .filter(|(alert_channel, _)| !crate::health::channel_failing(alert_channel.name()));
+        alert_channel_down(ch.name(), failures, "health check failed", target).await;
+    }
+}
+
+/// Report that `channel` failed `failures` times in a row, once per streak.
+async fn alert_channel_down(
+    channel: &str,
+    failures: u32,
+    error: &str,
+    target: Option<&(Arc<dyn Channel>, String)>,
+) {
+    let text = format!(
+        "⚠️ ZeroClaw channel {channel} is down: {failures} consecutive failures, last error: {error}"
+    );
+    tracing::error!("{text}");
+    let Some((alert_channel, recipient)) = target else {
+        return;
+    };
+    if alert_channel.name() == channel {
+        return;
+    }
+    if let Err(e) = alert_channel.send(&text, recipient).await {
+        tracing::warn!(
+            "Failed to send channel alert via {}: {e}",
+            alert_channel.name()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::traits::Audience;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    struct AlwaysFailChannel {
+        name: &'static str,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Channel for AlwaysFailChannel {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, _message: &str, _recipient: &str) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn listen(&self, _tx: mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            anyhow::bail!("listen boom")
+        }
+
+        async fn health_check(&self) -> bool {
+            false
+        }
+    }
+
+    /// Delivers one message from `sender`, then stays connected; records
+    /// what it is asked to send.
+    struct EchoChannel {
+        name: &'static str,
+        sender: &'static str,
+        sent: Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Channel for EchoChannel {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((recipient.to_string(), message.to_string()));
+            Ok(())
+        }
+
+        async fn listen(&self, tx: mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+            tx.send(ChannelMessage {
+                id: "1".into(),
+                sender: self.sender.into(),
+                content: format!("hello from {}", self.name),
+                channel: self.name.into(),
+                images: Vec::new(),
+                context: None,
+                reply_to: None,
+                author: None,
+                attachments: Vec::new(),
+                reaction: None,
+                update: None,
+                audience: Audience::Direct,
+                timestamp: 0,
+            })
+            .await?;
+            tx.closed().await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn supervised_listener_marks_error_and_restarts_on_failures() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let channel: Arc<dyn Channel> = Arc::new(AlwaysFailChannel {
+            name: "test-supervised-fail",
+            calls: Arc::clone(&calls),
+        });
+
+        let (tx, rx) = mpsc::channel::<ChannelMessage>(1);
+        let policy = RestartPolicy {
+            initial_backoff_secs: 1,
+            max_backoff_secs: 1,
+            alert_after_failures: 0,
+            alert: None,
+        };
+        let handle = spawn_supervised_listener(channel, tx, policy);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        drop(rx);
+        handle.abort();
+        let _ = handle.await;
+
+        let snapshot = crate::health::snapshot_json();
+        let component = &snapshot["components"]["channel:test-supervised-fail"];
+        assert_eq!(component["status"], "error");
+        assert!(component["restart_count"].as_u64().unwrap_or(0) >= 1);
+        assert!(component["last_error"]
+            .as_str()
+            .unwrap_or("")
+            .contains("listen boom"));
+        assert!(calls.load(Ordering::SeqCst) >= 1);
+    }
+
+    #[tokio::test]
+    async fn repeated_failures_are_alerted_through_the_target_channel() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let alerts: Arc<dyn Channel> = Arc::new(EchoChannel {
+            name: "test-alerts-telegram",
+            sender: "ops",
+            sent: Arc::clone(&sent),
+        });
+        let failing: Arc<dyn Channel> = Arc::new(AlwaysFailChannel {
+            name: "test-alerts-failing",
+            calls: Arc::new(AtomicUsize::new(0)),
+        });
+
+        let (tx, rx) = mpsc::channel::<ChannelMessage>(1);
+        let policy = RestartPolicy {
+            initial_backoff_secs: 1,
+            max_backoff_secs: 1,
+            alert_after_failures: 1,
+            alert: Some((alerts, "123".into())),
+        };
+        let handle = spawn_supervised_listener(failing, tx, policy);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        drop(rx);
+        handle.abort();
+        let _ = handle.await;
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, "123");
+        assert!(sent[0].1.contains("test-alerts-failing is down"));
+        assert!(sent[0].1.contains("listen boom"));
+    }
+
+    #[tokio::test]
+    async fn failing_health_checks_are_recorded_and_alerted_once() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let alerts: Arc<dyn Channel> = Arc::new(EchoChannel {
+            name: "test-health-alerts",
+            sender: "ops",
+            sent: Arc::clone(&sent),
+        });
+        let failing = AlwaysFailChannel {
+            name: "test-health-failing",
+            calls: Arc::new(AtomicUsize::new(0)),
+        };
+        let policy = RestartPolicy {
+            initial_backoff_secs: 1,
+            max_backoff_secs: 1,
+            alert_after_failures: 2,
+            alert: Some((Arc::clone(&alerts), "ops".into())),
+        };
+
+        check_channel_health(alerts.as_ref(), &policy).await;
+        for _ in 0..3 {
+            check_channel_health(&failing, &policy).await;
+        }
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0]
+            .1
+            .contains("test-health-failing is down: 2 consecutive failures"));
+        let channels = crate::health::snapshot().channels;
+        let failing = &channels["test-health-failing"];
+        assert!(!failing.healthy);
+        assert_eq!(failing.consecutive_failures, 3);
+        assert_eq!(failing.last_success, None);
+        let alerts = &channels["test-health-alerts"];
+        assert!(alerts.healthy);
+        assert!(alerts.last_success.is_some());
+    }
+
+    #[test]
+    fn alert_target_must_name_channel_and_recipient() {
+        let manager = ChannelManager::new(Vec::new()).with_alerts(3, Some("telegram: 42 "));
+        assert_eq!(manager.alert_after_failures, 3);
+        assert_eq!(
+            manager.alert_target,
+            Some(("telegram".to_string(), "42".to_string()))
+        );
+        for bad in ["telegram", ":42", "telegram:"] {
+            let manager = ChannelManager::new(Vec::new()).with_alerts(3, Some(bad));
+            assert_eq!(manager.alert_target, None, "{bad}");
+        }
+    }
+
+    #[tokio::test]
+    async fn merges_inboxes_and_routes_replies_to_the_source_channel() {
+        let slack_sent = Arc::new(Mutex::new(Vec::new()));
+        let irc_sent = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = ChannelManager::new(vec![
+            Arc::new(EchoChannel {
+                name: "test-manager-slack",
+                sender: "C1",
+                sent: Arc::clone(&slack_sent),
+            }),
+            Arc::new(EchoChannel {
+                name: "test-manager-irc",
+                sender: "#ops",
+                sent: Arc::clone(&irc_sent),
+            }),
+        ]);
+        assert_eq!(manager.names(), ["test-manager-slack", "test-manager-irc"]);
+
+        let mut rx = manager.start();
+        for _ in 0..2 {
+            let msg = rx.recv().await.unwrap();
+            manager
+                .reply(&msg, &format!("re: {}", msg.content))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            *slack_sent.lock().unwrap(),
+            [(
+                "C1".to_string(),
+                "re: hello from test-manager-slack".to_string()
+            )]
+        );
+        assert_eq!(
+            *irc_sent.lock().unwrap(),
+            [(
+                "#ops".to_string(),
+                "re: hello from test-manager-irc".to_string()
+            )]
+        );
+
+        let stray = ChannelMessage {
+            id: "2".into(),
+            sender: "someone".into(),
+            content: "hi".into(),
+            channel: "test-manager-gone".into(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: None,
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience: Audience::Direct,
+            timestamp: 0,
+        };
+        assert!(manager.reply(&stray, "hi").await.is_err());
+
+        drop(rx);
+        tokio::time::timeout(Duration::from_secs(5), manager.join())
+            .await
+            .unwrap();
+    }
+}
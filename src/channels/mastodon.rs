@@ -0,0 +1,486 @@
+use super::split::split_message;
+use super::traits::{Audience, Channel, ChannelMessage};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+/// Default status length limit of Mastodon instances.
+const MAX_STATUS_CHARS: usize = 500;
+
+/// Who can see a status, from most to least public.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Visibility {
+    Public,
+    Unlisted,
+    Private,
+    Direct,
+}
+
+impl Visibility {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "public" => Some(Self::Public),
+            "unlisted" => Some(Self::Unlisted),
+            "private" => Some(Self::Private),
+            "direct" => Some(Self::Direct),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Unlisted => "unlisted",
+            Self::Private => "private",
+            Self::Direct => "direct",
+        }
+    }
+}
+
+/// The status a reply to an account continues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReplyTarget {
+    status_id: String,
+    visibility: Visibility,
+}
+
+/// Mastodon channel — streams mention notifications over the streaming API
+/// and answers with replies to the mentioning toot.
+pub struct MastodonChannel {
+    instance_url: String,
+    access_token: String,
+    visibility: Visibility,
+    allowed_users: Vec<String>,
+    client: reqwest::Client,
+    /// Latest mention per account (lowercased `acct`), answered by `send`.
+    reply_to: Mutex<HashMap<String, ReplyTarget>>,
+}
+
+impl MastodonChannel {
+    /// `allowed_users` holds accounts as shown in mentions (`alice` for
+    /// local accounts, `alice@other.social` for remote ones) or `"*"`.
+    pub fn new(instance_url: String, access_token: String, allowed_users: Vec<String>) -> Self {
+        let mut instance_url = instance_url;
+        while instance_url.ends_with('/') {
+            instance_url.pop();
+        }
+        Self {
+            instance_url,
+            access_token,
+            visibility: Visibility::Unlisted,
+            allowed_users,
+            client: crate::proxy::client("mastodon"),
+            reply_to: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Visibility of the bot's statuses. Replies never widen the audience
+    /// of the toot they answer: a mention in a direct message is answered
+    /// directly even when this is `Public`.
+    #[must_use]
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    fn is_user_allowed(&self, acct: &str) -> bool {
+        self.allowed_users
+            .iter()
+            .any(|u| u == "*" || u.trim_start_matches('@').eq_ignore_ascii_case(acct))
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/api/v1/{path}", self.instance_url)
+    }
+
+    async fn get_json(&self, path: &str) -> anyhow::Result<Value> {
+        let resp = self
+            .client
+            .get(self.api_url(path))
+            .bearer_auth(&self.access_token)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Mastodon {path} failed ({status}): {err}");
+        }
+        Ok(resp.json().await?)
+    }
+
+    /// WebSocket URL of the notification stream. Instances may serve the
+    /// streaming API from another host, advertised in `/api/v1/instance`.
+    async fn streaming_url(&self) -> anyhow::Result<reqwest::Url> {
+        let advertised = self
+            .get_json("instance")
+            .await
+            .ok()
+            .and_then(|i| i["urls"]["streaming_api"].as_str().map(str::to_string));
+        let base = advertised.unwrap_or_else(|| {
+            self.instance_url
+                .replacen("https://", "wss://", 1)
+                .replacen("http://", "ws://", 1)
+        });
+        let mut url =
+            reqwest::Url::parse(&format!("{}/api/v1/streaming", base.trim_end_matches('/')))?;
+        url.query_pairs_mut()
+            .append_pair("stream", "user:notification")
+            .append_pair("access_token", &self.access_token);
+        Ok(url)
+    }
+
+    /// Turn a notification into a [`ChannelMessage`] from the mentioning
+    /// account and remember its status for the reply. Returns `None` for
+    /// anything but mentions, bot accounts, unauthorized accounts and
+    /// toots that only mention the bot.
+    fn to_channel_message(
+        &self,
+        notification: &Value,
+        bot_username: &str,
+    ) -> Option<ChannelMessage> {
+        if notification["type"] != "mention" {
+            return None;
+        }
+        let status = &notification["status"];
+        let account = &status["account"];
+        let acct = account["acct"].as_str()?;
+        if account["bot"].as_bool().unwrap_or(false) {
+            return None;
+        }
+        if !self.is_user_allowed(acct) {
+            tracing::warn!("Mastodon: ignoring mention from unauthorized account: {acct}");
+            return None;
+        }
+
+        let text = status_text(status["content"].as_str().unwrap_or_default());
+        let content = strip_leading_mention(&text, bot_username);
+        if content.is_empty() {
+            return None;
+        }
+
+        let status_id = status["id"].as_str()?.to_string();
+        let visibility = status["visibility"]
+            .as_str()
+            .and_then(Visibility::parse)
+            .unwrap_or(Visibility::Public);
+        if let Ok(mut reply_to) = self.reply_to.lock() {
+            reply_to.insert(
+                acct.to_lowercase(),
+                ReplyTarget {
+                    status_id,
+                    visibility,
+                },
+            );
+        }
+
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender: acct.to_string(),
+            content: content.to_string(),
+            channel: "mastodon".to_string(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: None,
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience: Audience::Mention,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
+    async fn post_status(
+        &self,
+        text: &str,
+        in_reply_to: Option<&str>,
+        visibility: Visibility,
+    ) -> anyhow::Result<String> {
+        let mut body = json!({ "status": text, "visibility": visibility.as_str() });
+        if let Some(id) = in_reply_to {
+            body["in_reply_to_id"] = json!(id);
+        }
+        let resp = self
+            .client
+            .post(self.api_url("statuses"))
+            .bearer_auth(&self.access_token)
+            .json(&body)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Mastodon post status failed ({status}): {err}");
+        }
+        let posted: Value = resp.json().await?;
+        Ok(posted["id"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+/// Plain text of a status's HTML `content`: paragraphs and line breaks
+/// kept, tags dropped, entities decoded.
+fn status_text(html: &str) -> String {
+    let html = html
+        .replace("</p><p>", "\n\n")
+        .replace("<br>", "\n")
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n");
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// `text` without leading mentions of the bot (`@claw`, `@claw@instance`).
+fn strip_leading_mention<'a>(text: &'a str, bot_username: &str) -> &'a str {
+    let mut rest = text.trim_start();
+    loop {
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..word_end];
+        let is_bot = word.strip_prefix('@').is_some_and(|handle| {
+            let user = handle.split('@').next().unwrap_or_default();
+            !bot_username.is_empty() && user.eq_ignore_ascii_case(bot_username)
+        });
+        if !is_bot {
+            return rest;
+        }
+        rest = rest[word_end..].trim_start();
+    }
+}
+
+/// Split `text` into statuses of at most `limit` characters, at whitespace
+/// where possible.
+fn split_status(text: &str, limit: usize) -> Vec<String> {
+    if text.trim().is_empty() {
+        return Vec::new();
+    }
+    split_message(text.trim(), limit)
+}
+
+#[async_trait]
+impl Channel for MastodonChannel {
+    fn name(&self) -> &str {
+        "mastodon"
+    }
+
+    /// Reply to the recipient's latest mention, threading multi-part
+    /// answers under each other. Without a mention to answer, posts a new
+    /// status addressed to the recipient.
+    async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+        let acct = recipient.trim_start_matches('@');
+        let target = self
+            .reply_to
+            .lock()
+            .ok()
+            .and_then(|r| r.get(&acct.to_lowercase()).cloned());
+        let visibility = target
+            .as_ref()
+            .map_or(self.visibility, |t| t.visibility.max(self.visibility));
+        let mut in_reply_to = target.map(|t| t.status_id);
+
+        let prefix = format!("@{acct} ");
+        let limit = MAX_STATUS_CHARS
+            .saturating_sub(prefix.chars().count())
+            .max(1);
+        for chunk in split_status(message, limit) {
+            let id = self
+                .post_status(
+                    &format!("{prefix}{chunk}"),
+                    in_reply_to.as_deref(),
+                    visibility,
+                )
+                .await?;
+            in_reply_to = Some(id).filter(|id| !id.is_empty());
+        }
+        Ok(())
+    }
+
+    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        let me = self.get_json("accounts/verify_credentials").await?;
+        let bot_username = me["username"].as_str().unwrap_or_default().to_string();
+
+        tracing::info!(
+            "Mastodon: connecting to {} streaming API...",
+            self.instance_url
+        );
+        let streaming_url = self.streaming_url().await?;
+        let ws_stream = crate::proxy::connect_websocket("mastodon", streaming_url.as_str()).await?;
+        let (_write, mut read) = ws_stream.split();
+        tracing::info!("Mastodon: listening for mentions of @{bot_username}");
+
+        while let Some(msg) = read.next().await {
+            let text = match msg? {
+                Message::Text(t) => t,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let Ok(event) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            if event["event"] != "notification" {
+                continue;
+            }
+            // The payload is itself a JSON document, sent as a string
+            let Some(notification) = event["payload"]
+                .as_str()
+                .and_then(|p| serde_json::from_str::<Value>(p).ok())
+            else {
+                continue;
+            };
+            let Some(channel_msg) = self.to_channel_message(&notification, &bot_username) else {
+                continue;
+            };
+            if tx.send(channel_msg).await.is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> bool {
+        self.get_json("accounts/verify_credentials").await.is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel() -> MastodonChannel {
+        MastodonChannel::new(
+            "https://social.example/".into(),
+            "token".into(),
+            vec!["alice".into(), "@bob@other.social".into()],
+        )
+    }
+
+    fn mention(acct: &str, content: &str, visibility: &str) -> Value {
+        json!({
+            "type": "mention",
+            "status": {
+                "id": "1001",
+                "visibility": visibility,
+                "content": content,
+                "account": { "acct": acct, "bot": false }
+            }
+        })
+    }
+
+    #[test]
+    fn new_trims_instance_url() {
+        let ch = channel();
+        assert_eq!(
+            ch.api_url("statuses"),
+            "https://social.example/api/v1/statuses"
+        );
+        assert_eq!(ch.name(), "mastodon");
+    }
+
+    #[test]
+    fn visibility_parses_and_orders() {
+        assert_eq!(Visibility::parse(" Direct "), Some(Visibility::Direct));
+        assert_eq!(Visibility::parse("followers"), None);
+        assert!(Visibility::Public < Visibility::Unlisted);
+        assert_eq!(
+            Visibility::Private.max(Visibility::Unlisted),
+            Visibility::Private
+        );
+        assert_eq!(Visibility::Unlisted.as_str(), "unlisted");
+    }
+
+    #[test]
+    fn status_text_keeps_paragraphs_and_decodes_entities() {
+        let html = "<p><span class=\"h-card\"><a href=\"https://social.example/@claw\">@<span>claw</span></a></span> is 1 &lt; 2?</p><p>line<br />two &amp; more</p>";
+        assert_eq!(status_text(html), "@claw is 1 < 2?\n\nline\ntwo & more");
+    }
+
+    #[test]
+    fn strips_only_bot_mentions() {
+        assert_eq!(
+            strip_leading_mention("@claw @Claw@social.example hi", "claw"),
+            "hi"
+        );
+        assert_eq!(
+            strip_leading_mention("@claw @bob what now", "claw"),
+            "@bob what now"
+        );
+        assert_eq!(strip_leading_mention("@claw", "claw"), "");
+    }
+
+    #[test]
+    fn mentions_become_messages_and_reply_targets() {
+        let ch = channel();
+        let msg = ch
+            .to_channel_message(
+                &mention("bob@other.social", "<p>@claw deploy status?</p>", "direct"),
+                "claw",
+            )
+            .unwrap();
+        assert_eq!(msg.sender, "bob@other.social");
+        assert_eq!(msg.content, "deploy status?");
+        assert_eq!(msg.channel, "mastodon");
+        assert_eq!(
+            ch.reply_to.lock().unwrap().get("bob@other.social"),
+            Some(&ReplyTarget {
+                status_id: "1001".into(),
+                visibility: Visibility::Direct,
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_other_notifications_bots_and_strangers() {
+        let ch = channel();
+        let mut favourite = mention("alice", "<p>@claw hi</p>", "public");
+        favourite["type"] = json!("favourite");
+        let mut bot = mention("alice", "<p>@claw hi</p>", "public");
+        bot["status"]["account"]["bot"] = json!(true);
+        for ignored in [
+            favourite,
+            bot,
+            mention("eve", "<p>@claw hi</p>", "public"),
+            mention("alice", "<p>@claw</p>", "public"),
+        ] {
+            assert!(ch.to_channel_message(&ignored, "claw").is_none());
+        }
+        assert!(ch.reply_to.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn split_status_breaks_at_whitespace() {
+        assert_eq!(split_status("short", 10), vec!["short"]);
+        assert_eq!(
+            split_status("one two three four", 9),
+            vec!["one two", "three", "four"]
+        );
+        assert_eq!(split_status("ééééé", 2), vec!["éé", "éé", "é"]);
+    }
+}
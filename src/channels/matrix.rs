@@ -1,9 +1,12 @@
-use crate::channels::traits::{Channel, ChannelMessage};
+use crate::channels::traits::{Audience, Channel, ChannelMessage};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::Deserialize;
 use tokio::sync::mpsc;
 
+/// Earlier messages of a reply chain included as context for a reply.
+const REPLY_CHAIN_MESSAGES: usize = 5;
+
 /// Matrix channel using the Client-Server API (no SDK needed).
 /// Connects to any Matrix homeserver (Element, Synapse, etc.).
 #[derive(Clone)]
@@ -55,6 +58,30 @@ struct EventContent {
     body: Option<String>,
     #[serde(default)]
     msgtype: Option<String>,
+    #[serde(default, rename = "m.relates_to")]
+    relates_to: Option<RelatesTo>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RelatesTo {
+    #[serde(default, rename = "m.in_reply_to")]
+    in_reply_to: Option<InReplyTo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InReplyTo {
+    event_id: String,
+}
+
+impl EventContent {
+    /// ID of the event this message replies to.
+    fn reply_to(&self) -> Option<&str> {
+        self.relates_to
+            .as_ref()?
+            .in_reply_to
+            .as_ref()
+            .map(|r| r.event_id.as_str())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -79,7 +106,7 @@ impl MatrixChannel {
             access_token,
             room_id,
             allowed_users,
-            client: Client::new(),
+            client: crate::proxy::client("matrix"),
         }
     }
 
@@ -92,6 +119,51 @@ impl MatrixChannel {
             .any(|u| u.eq_ignore_ascii_case(sender))
     }
 
+    /// The event `event_id` of the room.
+    async fn fetch_event(&self, event_id: &str) -> anyhow::Result<TimelineEvent> {
+        let url = format!(
+            "{}/_matrix/client/v3/rooms/{}/event/{}",
+            self.homeserver, self.room_id, event_id
+        );
+        let resp = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let err = resp.text().await?;
+            anyhow::bail!("Matrix event fetch failed: {err}");
+        }
+
+        Ok(resp.json().await?)
+    }
+
+    /// `chain` (oldest first) as `sender: body` lines, with the bot's own
+    /// messages attributed to `assistant`.
+    fn format_reply_chain(chain: &[TimelineEvent], my_user_id: &str) -> Option<String> {
+        let lines: Vec<String> = chain
+            .iter()
+            .filter_map(|event| {
+                let body = strip_reply_fallback(event.content.body.as_deref()?);
+                if body.is_empty() {
+                    return None;
+                }
+                let author = if event.sender == my_user_id {
+                    "assistant"
+                } else {
+                    &event.sender
+                };
+                Some(format!("{author}: {body}"))
+            })
+            .collect();
+        if lines.is_empty() {
+            return None;
+        }
+        Some(format!("[Replying to]\n{}", lines.join("\n")))
+    }
+
     async fn get_my_user_id(&self) -> anyhow::Result<String> {
         let url = format!("{}/_matrix/client/v3/account/whoami", self.homeserver);
         let resp = self
@@ -111,6 +183,16 @@ impl MatrixChannel {
     }
 }
 
+/// `body` without the `> <@user> ...` quote of the replied-to message that
+/// clients prepend to replies.
+fn strip_reply_fallback(body: &str) -> &str {
+    let mut rest = body;
+    while rest.starts_with('>') {
+        rest = rest.split_once('\n').map_or("", |(_, tail)| tail);
+    }
+    rest.trim()
+}
+
 #[async_trait]
 impl Channel for MatrixChannel {
     fn name(&self) -> &str {
@@ -230,8 +312,16 @@ impl Channel for MatrixChannel {
                     let msg = ChannelMessage {
                         id: format!("mx_{}", chrono::Utc::now().timestamp_millis()),
                         sender: event.sender.clone(),
-                        content: body.clone(),
+                        content: strip_reply_fallback(body).to_string(),
                         channel: "matrix".to_string(),
+                        images: Vec::new(),
+                        context: None,
+                        reply_to: event.content.reply_to().map(str::to_string),
+                        author: None,
+                        attachments: Vec::new(),
+                        reaction: None,
+                        update: None,
+                        audience: Audience::Direct,
                         timestamp: std::time::SystemTime::now()
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_default()
@@ -246,6 +336,24 @@ impl Channel for MatrixChannel {
         }
     }
 
+    /// The messages the reply chain of `msg` leads back through, up to
+    /// [`REPLY_CHAIN_MESSAGES`] of them.
+    async fn fetch_context(&self, msg: &ChannelMessage) -> anyhow::Result<Option<String>> {
+        let mut next = msg.reply_to.clone();
+        let mut chain = Vec::new();
+        while let Some(event_id) = next.take() {
+            if chain.len() == REPLY_CHAIN_MESSAGES {
+                break;
+            }
+            let event = self.fetch_event(&event_id).await?;
+            next = event.content.reply_to().map(str::to_string);
+            chain.push(event);
+        }
+        chain.reverse();
+        let my_user_id = self.get_my_user_id().await?;
+        Ok(Self::format_reply_chain(&chain, &my_user_id))
+    }
+
     async fn health_check(&self) -> bool {
         let url = format!("{}/_matrix/client/v3/account/whoami", self.homeserver);
         let Ok(resp) = self
@@ -458,6 +566,41 @@ mod tests {
         assert!(event.content.msgtype.is_none());
     }
 
+    #[test]
+    fn replies_carry_their_target_and_drop_the_quote() {
+        let json = r#"{
+            "type": "m.room.message",
+            "sender": "@user:matrix.org",
+            "content": {
+                "msgtype": "m.text",
+                "body": "> <@bot:matrix.org> Deployed.\n> to staging\n\nand prod?",
+                "m.relates_to": {"m.in_reply_to": {"event_id": "$abc"}}
+            }
+        }"#;
+        let event: TimelineEvent = serde_json::from_str(json).unwrap();
+        assert_eq!(event.content.reply_to(), Some("$abc"));
+        assert_eq!(
+            strip_reply_fallback(event.content.body.as_deref().unwrap()),
+            "and prod?"
+        );
+        assert_eq!(strip_reply_fallback("plain message"), "plain message");
+
+        let chain: Vec<TimelineEvent> = serde_json::from_str(
+            r#"[
+                {"type": "m.room.message", "sender": "@user:matrix.org",
+                 "content": {"body": "deploy please"}},
+                {"type": "m.room.message", "sender": "@bot:matrix.org",
+                 "content": {"body": "> <@user:matrix.org> deploy please\n\nDeployed."}}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            MatrixChannel::format_reply_chain(&chain, "@bot:matrix.org").as_deref(),
+            Some("[Replying to]\n@user:matrix.org: deploy please\nassistant: Deployed.")
+        );
+        assert!(MatrixChannel::format_reply_chain(&[], "@bot:matrix.org").is_none());
+    }
+
     #[test]
     fn sync_response_missing_rooms_defaults() {
         let json = r#"{"next_batch":"s0"}"#;
@@ -0,0 +1,113 @@
+//! Adaptive polling intervals for channels that poll for messages.
+//!
+//! A polling channel waits [`AdaptivePoll::delay`] between polls and reports
+//! each poll's outcome with [`AdaptivePoll::record`]: any new message drops
+//! the interval back to its minimum, every quiet poll doubles it up to the
+//! maximum (see [`ChannelPollingConfig::for_channel`]). Idle deployments
+//! then poll a handful of times an hour instead of every few seconds.
+
+use crate::config::schema::ChannelPollingConfig;
+use std::time::Duration;
+
+/// Poll interval that backs off while a channel is quiet.
+#[derive(Debug, Clone)]
+pub struct AdaptivePoll {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl AdaptivePoll {
+    pub fn new(config: ChannelPollingConfig) -> Self {
+        let min = Duration::from_secs(config.min_interval_secs.max(1));
+        let max = Duration::from_secs(config.max_interval_secs).max(min);
+        Self {
+            min,
+            max,
+            current: min,
+        }
+    }
+
+    /// How long to wait before the next poll.
+    pub fn delay(&self) -> Duration {
+        self.current
+    }
+
+    /// Account for a poll: back to the minimum when it found messages,
+    /// else twice as long, up to the maximum.
+    pub fn record(&mut self, found_messages: bool) {
+        self.current = if found_messages {
+            self.min
+        } else {
+            (self.current * 2).min(self.max)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn quiet_polls_back_off_to_the_maximum_and_traffic_resets() {
+        let mut poll = AdaptivePoll::new(ChannelPollingConfig {
+            min_interval_secs: 3,
+            max_interval_secs: 20,
+        });
+        assert_eq!(poll.delay(), Duration::from_secs(3));
+        let delays: Vec<u64> = (0..4)
+            .map(|_| {
+                poll.record(false);
+                poll.delay().as_secs()
+            })
+            .collect();
+        assert_eq!(delays, [6, 12, 20, 20]);
+
+        poll.record(true);
+        assert_eq!(poll.delay(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn maximum_at_or_below_minimum_keeps_a_fixed_interval() {
+        let mut poll = AdaptivePoll::new(ChannelPollingConfig {
+            min_interval_secs: 60,
+            max_interval_secs: 0,
+        });
+        poll.record(false);
+        assert_eq!(poll.delay(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn bounds_default_to_the_channel_interval_unless_configured() {
+        let mut configured = BTreeMap::new();
+        assert_eq!(
+            ChannelPollingConfig::for_channel(&configured, "slack", 3),
+            ChannelPollingConfig {
+                min_interval_secs: 3,
+                max_interval_secs: 30,
+            }
+        );
+        assert_eq!(
+            ChannelPollingConfig::for_channel(&configured, "email", 60),
+            ChannelPollingConfig {
+                min_interval_secs: 60,
+                max_interval_secs: 300,
+            }
+        );
+        assert_eq!(
+            ChannelPollingConfig::for_channel(&configured, "email", 600).max_interval_secs,
+            600
+        );
+
+        let email = ChannelPollingConfig {
+            min_interval_secs: 30,
+            max_interval_secs: 900,
+        };
+        configured.insert("email".to_string(), email);
+        assert_eq!(
+            ChannelPollingConfig::for_channel(&configured, "email", 60),
+            email
+        );
+    }
+}
@@ -1,12 +1,47 @@
-use super::traits::{Channel, ChannelMessage};
+use super::format::to_slack_mrkdwn;
+use super::poll::AdaptivePoll;
+use super::split::split_message;
+use super::traits::{Attachment, Audience, Channel, ChannelMessage, MessageUpdate, ReactionAction};
+use crate::config::schema::ChannelPollingConfig;
+use crate::providers::traits::ImageInput;
 use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use tokio_tungstenite::tungstenite::Message;
 use uuid::Uuid;
 
-/// Slack channel — polls conversations.history via Web API
+/// Largest image downloaded from Slack; vision APIs reject bigger inline images.
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Largest other file downloaded from Slack.
+const MAX_ATTACHMENT_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Earlier thread messages included as context for a threaded reply.
+const THREAD_CONTEXT_MESSAGES: usize = 20;
+
+/// Slack's limit on the text of one section block.
+const MAX_SECTION_CHARS: usize = 3000;
+
+/// Slack's limit on blocks per message; longer replies go out as plain text.
+const MAX_BLOCKS: usize = 50;
+
+/// Polling rounds between refreshes of the bot's DM list.
+const DM_REFRESH_ROUNDS: u32 = 20;
+
+/// Seconds between polls while messages arrive, without Socket Mode.
+pub const POLL_INTERVAL_SECS: u64 = 3;
+
+/// Slack channel — receives events over Socket Mode when an app-level token
+/// is configured, otherwise polls conversations.history via Web API
 pub struct SlackChannel {
     bot_token: String,
+    app_token: Option<String>,
     channel_id: Option<String>,
+    channel_ids: Vec<String>,
+    direct_messages: bool,
     allowed_users: Vec<String>,
+    polling: ChannelPollingConfig,
     client: reqwest::Client,
 }
 
@@ -14,10 +49,193 @@ impl SlackChannel {
     pub fn new(bot_token: String, channel_id: Option<String>, allowed_users: Vec<String>) -> Self {
         Self {
             bot_token,
+            app_token: None,
             channel_id,
+            channel_ids: Vec::new(),
+            direct_messages: true,
             allowed_users,
-            client: reqwest::Client::new(),
+            polling: ChannelPollingConfig::for_channel(
+                &BTreeMap::new(),
+                "slack",
+                POLL_INTERVAL_SECS,
+            ),
+            client: crate::proxy::client("slack"),
+        }
+    }
+
+    /// Also listen in these channel IDs besides the one given to [`Self::new`].
+    #[must_use]
+    pub fn with_channel_ids(mut self, channel_ids: Vec<String>) -> Self {
+        self.channel_ids = channel_ids;
+        self
+    }
+
+    /// Answer direct messages to the bot even when the channel filter would
+    /// not let their conversation through. On by default.
+    #[must_use]
+    pub fn with_direct_messages(mut self, direct_messages: bool) -> Self {
+        self.direct_messages = direct_messages;
+        self
+    }
+
+    /// Poll interval bounds without Socket Mode; by default every
+    /// [`POLL_INTERVAL_SECS`] while busy, backing off to ten times that.
+    #[must_use]
+    pub fn with_polling(mut self, polling: ChannelPollingConfig) -> Self {
+        self.polling = polling;
+        self
+    }
+
+    /// The configured channel IDs: `channel_id` and `channel_ids`.
+    fn configured_channels(&self) -> impl Iterator<Item = &str> {
+        self.channel_id
+            .iter()
+            .chain(&self.channel_ids)
+            .map(String::as_str)
+    }
+
+    /// Whether events from `conversation` are handled: DMs (IDs starting
+    /// with `D`) when direct messages are on, otherwise any configured
+    /// channel, or every channel when none is configured.
+    fn listens_in(&self, conversation: &str) -> bool {
+        if conversation.starts_with('D') {
+            return self.direct_messages;
         }
+        let mut configured = self.configured_channels().peekable();
+        configured.peek().is_none() || configured.any(|id| id == conversation)
+    }
+
+    /// Use Socket Mode with this app-level token (`xapp-...`, scope
+    /// `connections:write`): events arrive over a WebSocket in real time and
+    /// no public URL is needed.
+    #[must_use]
+    pub fn with_app_token(mut self, app_token: Option<String>) -> Self {
+        self.app_token = app_token.filter(|t| !t.trim().is_empty());
+        self
+    }
+
+    /// A `mrkdwn` section block.
+    pub fn section_block(text: &str) -> Value {
+        serde_json::json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": text }
+        })
+    }
+
+    /// A preformatted (code) block.
+    pub fn code_block(code: &str) -> Value {
+        serde_json::json!({
+            "type": "rich_text",
+            "elements": [{
+                "type": "rich_text_preformatted",
+                "elements": [{ "type": "text", "text": code }]
+            }]
+        })
+    }
+
+    /// Approve / Reject buttons. A click arrives as a message whose content
+    /// is `approve:<request_id>` or `reject:<request_id>` (Socket Mode only).
+    pub fn approval_block(request_id: &str) -> Value {
+        serde_json::json!({
+            "type": "actions",
+            "block_id": format!("approval:{request_id}"),
+            "elements": [
+                {
+                    "type": "button",
+                    "action_id": "approve",
+                    "style": "primary",
+                    "text": { "type": "plain_text", "text": "Approve" },
+                    "value": format!("approve:{request_id}")
+                },
+                {
+                    "type": "button",
+                    "action_id": "reject",
+                    "style": "danger",
+                    "text": { "type": "plain_text", "text": "Reject" },
+                    "value": format!("reject:{request_id}")
+                }
+            ]
+        })
+    }
+
+    /// Block Kit layout for a Markdown reply: prose as `mrkdwn` sections,
+    /// fenced code as preformatted blocks. Empty when the reply needs more
+    /// than [`MAX_BLOCKS`] blocks, so it goes out as plain text instead.
+    pub fn markdown_blocks(markdown: &str) -> Vec<Value> {
+        let mut blocks = Vec::new();
+        let mut prose = String::new();
+        let mut code: Option<String> = None;
+        let flush_prose = |prose: &mut String, blocks: &mut Vec<Value>| {
+            let text = to_slack_mrkdwn(prose.trim());
+            for piece in split_message(&text, MAX_SECTION_CHARS)
+                .into_iter()
+                .filter(|piece| !piece.is_empty())
+            {
+                blocks.push(Self::section_block(&piece));
+            }
+            prose.clear();
+        };
+        let push_code = |body: &str, blocks: &mut Vec<Value>| {
+            for piece in split_message(body.trim_end_matches('\n'), MAX_SECTION_CHARS)
+                .into_iter()
+                .filter(|piece| !piece.is_empty())
+            {
+                blocks.push(Self::code_block(&piece));
+            }
+        };
+        for line in markdown.lines() {
+            if line.trim_start().starts_with("```") {
+                if let Some(body) = code.take() {
+                    push_code(&body, &mut blocks);
+                } else {
+                    flush_prose(&mut prose, &mut blocks);
+                    code = Some(String::new());
+                }
+                continue;
+            }
+            let buf = code.as_mut().unwrap_or(&mut prose);
+            buf.push_str(line);
+            buf.push('\n');
+        }
+        // An unclosed fence still renders as code.
+        if let Some(body) = code {
+            push_code(&body, &mut blocks);
+        }
+        flush_prose(&mut prose, &mut blocks);
+        if blocks.len() > MAX_BLOCKS {
+            blocks.clear();
+        }
+        blocks
+    }
+
+    /// `chat.postMessage` body for `recipient` (`channel` or
+    /// `channel:thread_ts`), with `fallback` as the notification text.
+    fn post_message_body(recipient: &str, fallback: &str, blocks: &[Value]) -> Value {
+        let (channel, thread_ts) = Self::split_recipient(recipient);
+        let mut body = serde_json::json!({
+            "channel": channel,
+            "text": fallback
+        });
+        if let Some(thread_ts) = thread_ts {
+            body["thread_ts"] = thread_ts.into();
+        }
+        if !blocks.is_empty() {
+            body["blocks"] = blocks.into();
+        }
+        body
+    }
+
+    async fn post_message(&self, body: &Value) -> anyhow::Result<()> {
+        let resp = self
+            .client
+            .post("https://slack.com/api/chat.postMessage")
+            .bearer_auth(&self.bot_token)
+            .json(body)
+            .send()
+            .await?;
+        // Slack returns 200 for most app-level errors; `api_result` checks "ok"
+        Self::api_result("chat.postMessage", resp).await?;
+        Ok(())
     }
 
     /// Check if a Slack user ID is in the allowlist.
@@ -44,138 +262,947 @@ impl SlackChannel {
             .and_then(|u| u.as_str())
             .map(String::from)
     }
-}
 
-#[async_trait]
-impl Channel for SlackChannel {
-    fn name(&self) -> &str {
-        "slack"
+    /// `(url, mimetype)` of each image in a message's `files`, skipping
+    /// files over [`MAX_IMAGE_BYTES`].
+    fn image_files(msg: &serde_json::Value) -> Vec<(String, String)> {
+        let Some(files) = msg.get("files").and_then(|f| f.as_array()) else {
+            return Vec::new();
+        };
+        files
+            .iter()
+            .filter_map(|file| {
+                let mimetype = file.get("mimetype")?.as_str()?;
+                if !mimetype.starts_with("image/") {
+                    return None;
+                }
+                let size = file.get("size").and_then(serde_json::Value::as_u64);
+                if size.is_some_and(|size| size > MAX_IMAGE_BYTES) {
+                    tracing::warn!("Slack: skipping image over {MAX_IMAGE_BYTES} bytes");
+                    return None;
+                }
+                let url = file
+                    .get("url_private_download")
+                    .or_else(|| file.get("url_private"))?
+                    .as_str()?;
+                Some((url.to_string(), mimetype.to_string()))
+            })
+            .collect()
     }
 
-    async fn send(&self, message: &str, channel: &str) -> anyhow::Result<()> {
-        let body = serde_json::json!({
-            "channel": channel,
-            "text": message
-        });
+    /// `(name, url, mimetype)` of each non-image file in a message's
+    /// `files`, skipping files over [`MAX_ATTACHMENT_BYTES`].
+    fn attachment_files(msg: &serde_json::Value) -> Vec<(String, String, String)> {
+        let Some(files) = msg.get("files").and_then(|f| f.as_array()) else {
+            return Vec::new();
+        };
+        files
+            .iter()
+            .filter_map(|file| {
+                let mimetype = file
+                    .get("mimetype")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("application/octet-stream");
+                if mimetype.starts_with("image/") {
+                    return None;
+                }
+                let size = file.get("size").and_then(serde_json::Value::as_u64);
+                if size.is_some_and(|size| size > MAX_ATTACHMENT_BYTES) {
+                    tracing::warn!("Slack: skipping file over {MAX_ATTACHMENT_BYTES} bytes");
+                    return None;
+                }
+                let url = file
+                    .get("url_private_download")
+                    .or_else(|| file.get("url_private"))?
+                    .as_str()?;
+                let name = file
+                    .get("name")
+                    .or_else(|| file.get("title"))
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("file");
+                Some((name.to_string(), url.to_string(), mimetype.to_string()))
+            })
+            .collect()
+    }
 
+    /// Fetch a private Slack file (needs the `files:read` scope), refusing
+    /// anything over `max_bytes`.
+    async fn download_file(&self, url: &str, max_bytes: u64) -> anyhow::Result<Vec<u8>> {
         let resp = self
             .client
-            .post("https://slack.com/api/chat.postMessage")
+            .get(url)
             .bearer_auth(&self.bot_token)
-            .json(&body)
             .send()
-            .await?;
+            .await?
+            .error_for_status()?;
+        let bytes = resp.bytes().await?;
+        if bytes.len() as u64 > max_bytes {
+            anyhow::bail!("file exceeds {max_bytes} bytes");
+        }
+        Ok(bytes.to_vec())
+    }
 
+    /// Fetch a private Slack file as an inline image.
+    async fn download_image(&self, url: &str, mimetype: &str) -> anyhow::Result<ImageInput> {
+        let bytes = self.download_file(url, MAX_IMAGE_BYTES).await?;
+        Ok(ImageInput::base64(mimetype, &bytes))
+    }
+
+    /// Check a Slack Web API response: HTTP success and `"ok": true`.
+    async fn api_result(method: &str, resp: reqwest::Response) -> anyhow::Result<Value> {
         let status = resp.status();
         let body = resp
             .text()
             .await
             .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
-
         if !status.is_success() {
-            anyhow::bail!("Slack chat.postMessage failed ({status}): {body}");
+            anyhow::bail!("Slack {method} failed ({status}): {body}");
         }
-
-        // Slack returns 200 for most app-level errors; check JSON "ok" field
-        let parsed: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
-        if parsed.get("ok") == Some(&serde_json::Value::Bool(false)) {
+        let parsed: Value = serde_json::from_str(&body).unwrap_or_default();
+        if parsed.get("ok") == Some(&Value::Bool(false)) {
             let err = parsed
                 .get("error")
                 .and_then(|e| e.as_str())
                 .unwrap_or("unknown");
-            anyhow::bail!("Slack chat.postMessage failed: {err}");
+            anyhow::bail!("Slack {method} failed: {err}");
         }
+        Ok(parsed)
+    }
 
+    /// Upload a file into `recipient` (`channel` or `channel:thread_ts`)
+    /// with Slack's external upload flow (needs the `files:write` scope).
+    async fn upload_file(
+        &self,
+        attachment: &Attachment,
+        caption: &str,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        let length = attachment.data.len().to_string();
+        let resp = self
+            .client
+            .get("https://slack.com/api/files.getUploadURLExternal")
+            .bearer_auth(&self.bot_token)
+            .query(&[
+                ("filename", attachment.name.as_str()),
+                ("length", length.as_str()),
+            ])
+            .send()
+            .await?;
+        let ticket = Self::api_result("files.getUploadURLExternal", resp).await?;
+        let (Some(upload_url), Some(file_id)) = (
+            ticket.get("upload_url").and_then(|u| u.as_str()),
+            ticket.get("file_id").and_then(|f| f.as_str()),
+        ) else {
+            anyhow::bail!("Slack files.getUploadURLExternal returned no upload_url");
+        };
+
+        self.client
+            .post(upload_url)
+            .header(reqwest::header::CONTENT_TYPE, attachment.mime_type.as_str())
+            .body(attachment.data.clone())
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let resp = self
+            .client
+            .post("https://slack.com/api/files.completeUploadExternal")
+            .bearer_auth(&self.bot_token)
+            .json(&Self::complete_upload_body(
+                file_id,
+                &attachment.name,
+                caption,
+                recipient,
+            ))
+            .send()
+            .await?;
+        Self::api_result("files.completeUploadExternal", resp).await?;
         Ok(())
     }
 
-    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
-        let channel_id = self
-            .channel_id
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("Slack channel_id required for listening"))?;
+    /// `files.completeUploadExternal` body sharing `file_id` into
+    /// `recipient` with `caption` as the message.
+    fn complete_upload_body(file_id: &str, title: &str, caption: &str, recipient: &str) -> Value {
+        let (channel, thread_ts) = Self::split_recipient(recipient);
+        let mut body = serde_json::json!({
+            "files": [{ "id": file_id, "title": title }],
+            "channel_id": channel,
+        });
+        if !caption.trim().is_empty() {
+            body["initial_comment"] = to_slack_mrkdwn(caption).into();
+        }
+        if let Some(thread_ts) = thread_ts {
+            body["thread_ts"] = thread_ts.into();
+        }
+        body
+    }
 
-        let bot_user_id = self.get_bot_user_id().await.unwrap_or_default();
-        let mut last_ts = String::new();
+    /// Earlier messages of the thread rooted at `thread_ts` (needs the
+    /// `channels:history` scope), oldest first, without the message at
+    /// `current_ts`.
+    async fn fetch_thread(
+        &self,
+        channel_id: &str,
+        thread_ts: &str,
+        current_ts: &str,
+    ) -> anyhow::Result<Vec<serde_json::Value>> {
+        let data: serde_json::Value = self
+            .client
+            .get("https://slack.com/api/conversations.replies")
+            .bearer_auth(&self.bot_token)
+            .query(&[("channel", channel_id), ("ts", thread_ts), ("limit", "200")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if data.get("ok") == Some(&serde_json::Value::Bool(false)) {
+            let err = data
+                .get("error")
+                .and_then(|e| e.as_str())
+                .unwrap_or("unknown");
+            anyhow::bail!("Slack conversations.replies failed: {err}");
+        }
+        let messages = data
+            .get("messages")
+            .and_then(|m| m.as_array())
+            .cloned()
+            .unwrap_or_default();
+        Ok(messages
+            .into_iter()
+            .filter(|m| m.get("ts").and_then(|t| t.as_str()) != Some(current_ts))
+            .collect())
+    }
 
-        tracing::info!("Slack channel listening on #{channel_id}...");
+    /// The message at `ts` in `channel_id`, top-level or in a thread (needs
+    /// the `channels:history` scope).
+    async fn fetch_message(&self, channel_id: &str, ts: &str) -> anyhow::Result<Option<Value>> {
+        let find = |data: &Value| {
+            data.get("messages")
+                .and_then(|m| m.as_array())
+                .and_then(|messages| {
+                    messages
+                        .iter()
+                        .find(|m| m.get("ts").and_then(|t| t.as_str()) == Some(ts))
+                })
+                .cloned()
+        };
+        let resp = self
+            .client
+            .get("https://slack.com/api/conversations.history")
+            .bearer_auth(&self.bot_token)
+            .query(&[
+                ("channel", channel_id),
+                ("latest", ts),
+                ("inclusive", "true"),
+                ("limit", "1"),
+            ])
+            .send()
+            .await?;
+        let data = Self::api_result("conversations.history", resp).await?;
+        if let Some(message) = find(&data) {
+            return Ok(Some(message));
+        }
+        // Thread replies are not in the channel history
+        let resp = self
+            .client
+            .get("https://slack.com/api/conversations.replies")
+            .bearer_auth(&self.bot_token)
+            .query(&[("channel", channel_id), ("ts", ts), ("limit", "1")])
+            .send()
+            .await?;
+        let data = Self::api_result("conversations.replies", resp).await?;
+        Ok(find(&data))
+    }
 
-        loop {
-            tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    /// The last [`THREAD_CONTEXT_MESSAGES`] of `thread` as `user: text`
+    /// lines, with the bot's own messages attributed to `assistant`.
+    fn format_thread_context(thread: &[serde_json::Value], bot_user_id: &str) -> Option<String> {
+        let lines: Vec<String> = thread
+            .iter()
+            .filter_map(|m| {
+                let text = m.get("text").and_then(|t| t.as_str())?.trim();
+                if text.is_empty() {
+                    return None;
+                }
+                let user = m.get("user").and_then(|u| u.as_str()).unwrap_or("unknown");
+                let author = if m.get("bot_id").is_some() || user == bot_user_id {
+                    "assistant"
+                } else {
+                    user
+                };
+                Some(format!("{author}: {text}"))
+            })
+            .collect();
+        if lines.is_empty() {
+            return None;
+        }
+        let start = lines.len().saturating_sub(THREAD_CONTEXT_MESSAGES);
+        Some(format!("[Thread context]\n{}", lines[start..].join("\n")))
+    }
+
+    /// `ts` of the thread a message was posted in, when it is a reply
+    /// rather than a top-level message or the thread's parent.
+    fn reply_thread_ts(msg: &serde_json::Value) -> Option<&str> {
+        let thread_ts = msg.get("thread_ts").and_then(|t| t.as_str())?;
+        let ts = msg.get("ts").and_then(|t| t.as_str());
+        (ts != Some(thread_ts)).then_some(thread_ts)
+    }
 
-            let mut params = vec![("channel", channel_id.clone()), ("limit", "10".to_string())];
-            if !last_ts.is_empty() {
-                params.push(("oldest", last_ts.clone()));
+    /// Split a `send` recipient into the channel ID and, for threaded
+    /// conversations (`channel:thread_ts`), the thread to reply in.
+    fn split_recipient(recipient: &str) -> (&str, Option<&str>) {
+        match recipient.split_once(':') {
+            Some((channel, thread_ts)) if !thread_ts.is_empty() => (channel, Some(thread_ts)),
+            _ => (recipient, None),
+        }
+    }
+
+    /// Who a message in `channel_id` was written for, and its text without
+    /// the bot's `<@U...>` mention: DMs are direct, channel messages that
+    /// mention the bot are mentions.
+    fn addressing(channel_id: &str, text: &str, bot_user_id: &str) -> (Audience, String) {
+        let mention = format!("<@{bot_user_id}>");
+        let audience = if channel_id.starts_with('D') {
+            Audience::Direct
+        } else if !bot_user_id.is_empty() && text.contains(&mention) {
+            Audience::Mention
+        } else {
+            Audience::Group
+        };
+        if bot_user_id.is_empty() {
+            return (audience, text.to_string());
+        }
+        (audience, text.replace(&mention, "").trim().to_string())
+    }
+
+    /// Turn a Slack message event into a [`ChannelMessage`] addressed to the
+    /// conversation it came from — the channel, or `channel:thread_ts` for
+    /// thread replies, whose `reply_to` is the thread for
+    /// [`Channel::fetch_context`].
+    /// Returns `None` for the bot's own messages, edits and other subtypes,
+    /// unauthorized senders and empty messages. Its `id` is `channel:ts`, the
+    /// same one [`Self::message_update`] gives later edits and deletions.
+    async fn to_channel_message(
+        &self,
+        msg: &serde_json::Value,
+        channel_id: &str,
+        bot_user_id: &str,
+    ) -> Option<ChannelMessage> {
+        let subtype = msg.get("subtype").and_then(|s| s.as_str());
+        if subtype.is_some_and(|s| s != "file_share") || msg.get("bot_id").is_some() {
+            return None;
+        }
+        let user = msg
+            .get("user")
+            .and_then(|u| u.as_str())
+            .unwrap_or("unknown");
+        let text = msg.get("text").and_then(|t| t.as_str()).unwrap_or("");
+        let image_files = Self::image_files(msg);
+        let attachment_files = Self::attachment_files(msg);
+
+        // Skip bot's own messages
+        if user == bot_user_id {
+            return None;
+        }
+
+        // Sender validation
+        if !self.is_user_allowed(user) {
+            tracing::warn!("Slack: ignoring message from unauthorized user: {user}");
+            return None;
+        }
+
+        if text.is_empty() && image_files.is_empty() && attachment_files.is_empty() {
+            return None;
+        }
+
+        let mut images = Vec::with_capacity(image_files.len());
+        for (url, mimetype) in &image_files {
+            match self.download_image(url, mimetype).await {
+                Ok(image) => images.push(image),
+                Err(e) => tracing::warn!("Slack: failed to download image: {e}"),
+            }
+        }
+        let mut attachments = Vec::with_capacity(attachment_files.len());
+        for (name, url, mimetype) in attachment_files {
+            match self.download_file(&url, MAX_ATTACHMENT_BYTES).await {
+                Ok(data) => attachments.push(Attachment {
+                    name,
+                    mime_type: mimetype,
+                    url: Some(url),
+                    data,
+                }),
+                Err(e) => tracing::warn!("Slack: failed to download {name}: {e}"),
+            }
+        }
+
+        let thread_ts = Self::reply_thread_ts(msg);
+        let sender = match thread_ts {
+            Some(thread_ts) => format!("{channel_id}:{thread_ts}"),
+            None => channel_id.to_string(),
+        };
+
+        let ts = msg.get("ts").and_then(|t| t.as_str()).unwrap_or_default();
+        let (audience, text) = Self::addressing(channel_id, text, bot_user_id);
+        Some(ChannelMessage {
+            id: format!("{channel_id}:{ts}"),
+            sender,
+            content: text,
+            channel: "slack".to_string(),
+            images,
+            context: None,
+            reply_to: thread_ts.map(str::to_string),
+            author: Some(user.to_string()),
+            attachments,
+            reaction: None,
+            update: None,
+            audience,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
+    /// Open a Socket Mode connection and return its WebSocket URL.
+    async fn open_socket_url(&self, app_token: &str) -> anyhow::Result<String> {
+        let resp: serde_json::Value = self
+            .client
+            .post("https://slack.com/api/apps.connections.open")
+            .bearer_auth(app_token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if resp.get("ok") != Some(&serde_json::Value::Bool(true)) {
+            let err = resp
+                .get("error")
+                .and_then(|e| e.as_str())
+                .unwrap_or("unknown");
+            anyhow::bail!("Slack apps.connections.open failed: {err}");
+        }
+        resp.get("url")
+            .and_then(|u| u.as_str())
+            .map(String::from)
+            .ok_or_else(|| anyhow::anyhow!("Slack apps.connections.open returned no url"))
+    }
+
+    /// Receive events over one Socket Mode connection. Every envelope is
+    /// acknowledged right away so Slack does not redeliver it. Returns when
+    /// Slack asks for a reconnect or the connection drops; the channel
+    /// supervisor then reconnects.
+    async fn listen_socket_mode(
+        &self,
+        app_token: &str,
+        bot_user_id: &str,
+        tx: &tokio::sync::mpsc::Sender<ChannelMessage>,
+    ) -> anyhow::Result<()> {
+        let url = self.open_socket_url(app_token).await?;
+        let ws_stream = crate::proxy::connect_websocket("slack", &url).await?;
+        let (mut write, mut read) = ws_stream.split();
+        tracing::info!("Slack: connected via Socket Mode");
+
+        while let Some(frame) = read.next().await {
+            let text = match frame? {
+                Message::Text(text) => text,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let Ok(envelope) = serde_json::from_str::<serde_json::Value>(&text) else {
+                continue;
+            };
+
+            if let Some(envelope_id) = envelope.get("envelope_id").and_then(|e| e.as_str()) {
+                let ack = serde_json::json!({ "envelope_id": envelope_id });
+                write.send(Message::Text(ack.to_string())).await?;
             }
 
-            let resp = match self
-                .client
-                .get("https://slack.com/api/conversations.history")
-                .bearer_auth(&self.bot_token)
-                .query(&params)
-                .send()
+            if let Some(event) = Self::socket_reaction_event(&envelope) {
+                if let Some(msg) = self.reaction_event_message(event, bot_user_id).await {
+                    if tx.send(msg).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                continue;
+            }
+            if let Some(msg) = self.block_action_message(&envelope) {
+                if tx.send(msg).await.is_err() {
+                    return Ok(());
+                }
+                continue;
+            }
+            let Some(event) = Self::socket_message_event(&envelope) else {
+                if envelope.get("type").and_then(|t| t.as_str()) == Some("disconnect") {
+                    tracing::info!("Slack: Socket Mode disconnect requested, reconnecting");
+                    break;
+                }
+                continue;
+            };
+            let Some(event_channel) = event.get("channel").and_then(|c| c.as_str()) else {
+                continue;
+            };
+            if !self.listens_in(event_channel) {
+                continue;
+            }
+            // Slack redelivers events it considers unacknowledged, also
+            // across restarts
+            let ts = event.get("ts").and_then(|t| t.as_str()).unwrap_or("");
+            if !ts.is_empty() && !super::state::mark_seen("slack", &format!("{event_channel}:{ts}"))
+            {
+                continue;
+            }
+            if let Some(msg) = self.message_update(event, event_channel, bot_user_id) {
+                if tx.send(msg).await.is_err() {
+                    return Ok(());
+                }
+                continue;
+            }
+            if let Some(msg) = self
+                .to_channel_message(event, event_channel, bot_user_id)
                 .await
             {
-                Ok(r) => r,
-                Err(e) => {
-                    tracing::warn!("Slack poll error: {e}");
-                    continue;
+                if tx.send(msg).await.is_err() {
+                    return Ok(());
                 }
-            };
+            }
+        }
+        Ok(())
+    }
 
-            let data: serde_json::Value = match resp.json().await {
-                Ok(d) => d,
-                Err(e) => {
-                    tracing::warn!("Slack parse error: {e}");
-                    continue;
+    /// A `message_changed` or `message_deleted` event as an update to the
+    /// message it refers to, with the edited text as content. Edits that keep
+    /// the text (Slack sends those for link unfurls), the bot's own messages
+    /// and unauthorized senders yield `None`.
+    fn message_update(
+        &self,
+        event: &Value,
+        channel_id: &str,
+        bot_user_id: &str,
+    ) -> Option<ChannelMessage> {
+        let previous = event.get("previous_message");
+        let (update, message, ts) = match event.get("subtype")?.as_str()? {
+            "message_changed" => {
+                let message = event.get("message")?;
+                let text = message.get("text").and_then(|t| t.as_str());
+                if text
+                    == previous
+                        .and_then(|p| p.get("text"))
+                        .and_then(|t| t.as_str())
+                {
+                    return None;
                 }
-            };
+                (MessageUpdate::Edited, message, message.get("ts")?.as_str()?)
+            }
+            "message_deleted" => (
+                MessageUpdate::Deleted,
+                previous?,
+                event.get("deleted_ts")?.as_str()?,
+            ),
+            _ => return None,
+        };
+        if message.get("bot_id").is_some() {
+            return None;
+        }
+        let user = message.get("user")?.as_str()?;
+        if user == bot_user_id || !self.is_user_allowed(user) {
+            return None;
+        }
+        let sender = match Self::reply_thread_ts(message) {
+            Some(thread_ts) => format!("{channel_id}:{thread_ts}"),
+            None => channel_id.to_string(),
+        };
+        let text = match update {
+            MessageUpdate::Edited => message.get("text")?.as_str()?,
+            MessageUpdate::Deleted => "",
+        };
+        let (audience, content) = Self::addressing(channel_id, text, bot_user_id);
+        Some(ChannelMessage {
+            id: format!("{channel_id}:{ts}"),
+            sender,
+            content,
+            channel: "slack".to_string(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: Some(user.to_string()),
+            attachments: Vec::new(),
+            reaction: None,
+            update: Some(update),
+            audience,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
 
-            if let Some(messages) = data.get("messages").and_then(|m| m.as_array()) {
-                // Messages come newest-first, reverse to process oldest first
-                for msg in messages.iter().rev() {
-                    let ts = msg.get("ts").and_then(|t| t.as_str()).unwrap_or("");
-                    let user = msg
-                        .get("user")
-                        .and_then(|u| u.as_str())
-                        .unwrap_or("unknown");
-                    let text = msg.get("text").and_then(|t| t.as_str()).unwrap_or("");
-
-                    // Skip bot's own messages
-                    if user == bot_user_id {
-                        continue;
-                    }
+    /// A button click from a Socket Mode `interactive` envelope, as a message
+    /// from the clicking user whose content is the button's `value` (or
+    /// `action_id`). Replies go to the channel or thread holding the button.
+    fn block_action_message(&self, envelope: &Value) -> Option<ChannelMessage> {
+        if envelope.get("type")?.as_str()? != "interactive" {
+            return None;
+        }
+        let payload = envelope.get("payload")?;
+        if payload.get("type")?.as_str()? != "block_actions" {
+            return None;
+        }
+        let user = payload.get("user")?.get("id")?.as_str()?;
+        if !self.is_user_allowed(user) {
+            tracing::warn!("Slack: ignoring button click from unauthorized user: {user}");
+            return None;
+        }
+        let channel_id = payload.get("channel")?.get("id")?.as_str()?;
+        if !self.listens_in(channel_id) {
+            return None;
+        }
+        let action = payload.get("actions")?.as_array()?.first()?;
+        let content = action
+            .get("value")
+            .or_else(|| action.get("action_id"))?
+            .as_str()?;
+        let sender = match payload
+            .get("container")
+            .and_then(|c| c.get("thread_ts"))
+            .and_then(|t| t.as_str())
+        {
+            Some(thread_ts) => format!("{channel_id}:{thread_ts}"),
+            None => channel_id.to_string(),
+        };
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender,
+            content: content.to_string(),
+            channel: "slack".to_string(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: Some(user.to_string()),
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience: Audience::Direct,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
 
-                    // Sender validation
-                    if !self.is_user_allowed(user) {
-                        tracing::warn!("Slack: ignoring message from unauthorized user: {user}");
-                        continue;
-                    }
+    /// Look up the message a `reaction_added` event points at and turn the
+    /// reaction into a message. Reactions that map to no
+    /// [`ReactionAction`] and the bot's own are dropped without a lookup.
+    async fn reaction_event_message(
+        &self,
+        event: &Value,
+        bot_user_id: &str,
+    ) -> Option<ChannelMessage> {
+        let reaction = event.get("reaction")?.as_str()?;
+        ReactionAction::from_emoji(reaction)?;
+        if event.get("user").and_then(|u| u.as_str()) == Some(bot_user_id) {
+            return None;
+        }
+        let item = event.get("item")?;
+        let channel_id = item.get("channel")?.as_str()?;
+        let ts = item.get("ts")?.as_str()?;
+        let target = match self.fetch_message(channel_id, ts).await {
+            Ok(target) => target,
+            Err(e) => {
+                tracing::warn!("Slack: failed to fetch reacted message: {e}");
+                None
+            }
+        };
+        self.reaction_message(event, target.as_ref())
+    }
 
-                    // Skip empty or already-seen
-                    if text.is_empty() || ts <= last_ts.as_str() {
-                        continue;
+    /// A `reaction_added` event as a message from the reacting user, given
+    /// the message it was added to. Its conversation is that message's, and
+    /// ✅ on a message with Approve/Reject buttons carries the same
+    /// `approve:<request_id>` content as clicking Approve.
+    fn reaction_message(&self, event: &Value, target: Option<&Value>) -> Option<ChannelMessage> {
+        let action = ReactionAction::from_emoji(event.get("reaction")?.as_str()?)?;
+        let user = event.get("user")?.as_str()?;
+        if !self.is_user_allowed(user) {
+            tracing::warn!("Slack: ignoring reaction from unauthorized user: {user}");
+            return None;
+        }
+        let item = event.get("item")?;
+        if item.get("type")?.as_str()? != "message" {
+            return None;
+        }
+        let channel_id = item.get("channel")?.as_str()?;
+        if !self.listens_in(channel_id) {
+            return None;
+        }
+        let sender = match target.and_then(Self::reply_thread_ts) {
+            Some(thread_ts) => format!("{channel_id}:{thread_ts}"),
+            None => channel_id.to_string(),
+        };
+        let approval = target
+            .and_then(|m| m.get("blocks"))
+            .and_then(|b| b.as_array())
+            .and_then(|blocks| {
+                blocks
+                    .iter()
+                    .find_map(|block| block.get("block_id")?.as_str()?.strip_prefix("approval:"))
+            });
+        let content = match (action, approval) {
+            (ReactionAction::Approve, Some(request_id)) => format!("approve:{request_id}"),
+            _ => String::new(),
+        };
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender,
+            content,
+            channel: "slack".to_string(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: Some(user.to_string()),
+            attachments: Vec::new(),
+            reaction: Some(action),
+            update: None,
+            audience: Audience::Direct,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
+    /// The `reaction_added` event inside a Socket Mode `events_api` envelope.
+    fn socket_reaction_event(envelope: &Value) -> Option<&Value> {
+        if envelope.get("type")?.as_str()? != "events_api" {
+            return None;
+        }
+        let event = envelope.get("payload")?.get("event")?;
+        (event.get("type")?.as_str()? == "reaction_added").then_some(event)
+    }
+
+    /// The `message` event inside a Socket Mode `events_api` envelope.
+    fn socket_message_event(envelope: &serde_json::Value) -> Option<&serde_json::Value> {
+        if envelope.get("type")?.as_str()? != "events_api" {
+            return None;
+        }
+        let event = envelope.get("payload")?.get("event")?;
+        (event.get("type")?.as_str()? == "message").then_some(event)
+    }
+
+    /// IDs of the bot's direct-message conversations (`im:read` scope).
+    async fn list_dm_conversations(&self) -> anyhow::Result<Vec<String>> {
+        let resp = self
+            .client
+            .get("https://slack.com/api/conversations.list")
+            .bearer_auth(&self.bot_token)
+            .query(&[("types", "im"), ("limit", "200")])
+            .send()
+            .await?;
+        let data = Self::api_result("conversations.list", resp).await?;
+        Ok(data
+            .get("channels")
+            .and_then(|c| c.as_array())
+            .map(|channels| {
+                channels
+                    .iter()
+                    .filter_map(|c| c.get("id")?.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Fallback without an app token: poll conversations.history of every
+    /// configured channel, and of the bot's DMs, every 3s.
+    async fn poll_history(
+        &self,
+        bot_user_id: &str,
+        tx: &tokio::sync::mpsc::Sender<ChannelMessage>,
+    ) -> anyhow::Result<()> {
+        let channels: Vec<String> = self.configured_channels().map(String::from).collect();
+        if channels.is_empty() && !self.direct_messages {
+            anyhow::bail!("Slack channel_id or direct_messages required for listening");
+        }
+
+        // Resume after the last message handled before a restart; on first
+        // start, skip the history of the conversations known now instead of
+        // answering it. DMs opened later are read from their first message.
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let started = format!("{}.{:06}", now.as_secs(), now.subsec_micros());
+        let mut cursors: HashMap<String, String> = channels
+            .iter()
+            .map(|id| {
+                let cursor = super::state::cursor("slack", id).unwrap_or_else(|| started.clone());
+                (id.clone(), cursor)
+            })
+            .collect();
+        let mut dms_listed = false;
+
+        tracing::info!(
+            "Slack channel polling {} (set app_token for Socket Mode)...",
+            if channels.is_empty() {
+                "direct messages".to_string()
+            } else {
+                channels.join(", ")
+            }
+        );
+
+        let mut poll = AdaptivePoll::new(self.polling);
+        let mut round: u32 = 0;
+        loop {
+            if self.direct_messages && round.is_multiple_of(DM_REFRESH_ROUNDS) {
+                match self.list_dm_conversations().await {
+                    Ok(dms) => {
+                        for dm in dms {
+                            let fallback = if dms_listed {
+                                String::new()
+                            } else {
+                                started.clone()
+                            };
+                            cursors.entry(dm).or_insert_with_key(|dm| {
+                                super::state::cursor("slack", dm).unwrap_or(fallback)
+                            });
+                        }
+                        dms_listed = true;
                     }
+                    Err(e) => tracing::warn!("Slack: failed to list direct messages: {e}"),
+                }
+            }
+            round = round.wrapping_add(1);
+
+            tokio::time::sleep(poll.delay()).await;
+
+            let mut found = false;
+            for (channel_id, last_ts) in &mut cursors {
+                match self
+                    .poll_conversation(channel_id, last_ts, bot_user_id, tx)
+                    .await
+                {
+                    Some(new) => found |= new,
+                    None => return Ok(()),
+                }
+            }
+            poll.record(found);
+        }
+    }
+
+    /// Hand the messages of `channel_id` newer than `last_ts` to the inbox,
+    /// oldest first, advancing the cursor. Returns whether there were any,
+    /// or `None` once the inbox is closed.
+    async fn poll_conversation(
+        &self,
+        channel_id: &str,
+        last_ts: &mut String,
+        bot_user_id: &str,
+        tx: &tokio::sync::mpsc::Sender<ChannelMessage>,
+    ) -> Option<bool> {
+        let mut params = vec![
+            ("channel", channel_id.to_string()),
+            ("limit", "10".to_string()),
+        ];
+        if !last_ts.is_empty() {
+            params.push(("oldest", last_ts.clone()));
+        }
+
+        let resp = match self
+            .client
+            .get("https://slack.com/api/conversations.history")
+            .bearer_auth(&self.bot_token)
+            .query(&params)
+            .send()
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!("Slack poll error: {e}");
+                return Some(false);
+            }
+        };
 
-                    last_ts = ts.to_string();
+        let data: serde_json::Value = match resp.json().await {
+            Ok(d) => d,
+            Err(e) => {
+                tracing::warn!("Slack parse error: {e}");
+                return Some(false);
+            }
+        };
 
-                    let channel_msg = ChannelMessage {
-                        id: Uuid::new_v4().to_string(),
-                        sender: channel_id.clone(),
-                        content: text.to_string(),
-                        channel: "slack".to_string(),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs(),
-                    };
+        let mut found = false;
+        if let Some(messages) = data.get("messages").and_then(|m| m.as_array()) {
+            // Messages come newest-first, reverse to process oldest first
+            for msg in messages.iter().rev() {
+                let ts = msg.get("ts").and_then(|t| t.as_str()).unwrap_or("");
+                // Skip already-seen
+                if ts <= last_ts.as_str() {
+                    continue;
+                }
+                *last_ts = ts.to_string();
+                super::state::set_cursor("slack", channel_id, last_ts);
+                found = true;
 
+                if let Some(channel_msg) =
+                    self.to_channel_message(msg, channel_id, bot_user_id).await
+                {
                     if tx.send(channel_msg).await.is_err() {
-                        return Ok(());
+                        return None;
                     }
                 }
             }
         }
+        Some(found)
+    }
+}
+
+#[async_trait]
+impl Channel for SlackChannel {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, message: &str, channel: &str) -> anyhow::Result<()> {
+        let blocks = Self::markdown_blocks(message);
+        let fallback = to_slack_mrkdwn(message);
+        self.post_message(&Self::post_message_body(channel, &fallback, &blocks))
+            .await
+    }
+
+    async fn send_blocks(
+        &self,
+        blocks: &[Value],
+        fallback: &str,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        self.post_message(&Self::post_message_body(recipient, fallback, blocks))
+            .await
+    }
+
+    async fn send_attachment(
+        &self,
+        attachment: &Attachment,
+        caption: &str,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        self.upload_file(attachment, caption, recipient).await
+    }
+
+    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        let bot_user_id = self.get_bot_user_id().await.unwrap_or_default();
+        match self.app_token.as_deref() {
+            Some(app_token) => self.listen_socket_mode(app_token, &bot_user_id, &tx).await,
+            None => self.poll_history(&bot_user_id, &tx).await,
+        }
+    }
+
+    /// The earlier messages of the thread a reply was posted in.
+    async fn fetch_context(&self, msg: &ChannelMessage) -> anyhow::Result<Option<String>> {
+        let Some(thread_ts) = msg.reply_to.as_deref() else {
+            return Ok(None);
+        };
+        let (channel_id, _) = Self::split_recipient(&msg.sender);
+        let current_ts = msg.id.split_once(':').map_or("", |(_, ts)| ts);
+        let thread = self.fetch_thread(channel_id, thread_ts, current_ts).await?;
+        let bot_user_id = self.get_bot_user_id().await.unwrap_or_default();
+        Ok(Self::format_thread_context(&thread, &bot_user_id))
     }
 
     async fn health_check(&self) -> bool {
@@ -205,6 +1232,37 @@ mod tests {
         assert_eq!(ch.channel_id, Some("C12345".to_string()));
     }
 
+    #[test]
+    fn addressing_detects_dms_and_strips_mentions() {
+        assert_eq!(
+            SlackChannel::addressing("C1", "<@UBOT> deploy status?", "UBOT"),
+            (Audience::Mention, "deploy status?".to_string())
+        );
+        assert_eq!(
+            SlackChannel::addressing("C1", "lunch?", "UBOT"),
+            (Audience::Group, "lunch?".to_string())
+        );
+        assert_eq!(
+            SlackChannel::addressing("D1", "hi <@UBOT>", "UBOT"),
+            (Audience::Direct, "hi".to_string())
+        );
+    }
+
+    #[test]
+    fn listens_in_configured_channels_and_dms() {
+        let ch = SlackChannel::new("xoxb-fake".into(), Some("C1".into()), vec![])
+            .with_channel_ids(vec!["C2".into()]);
+        assert!(ch.listens_in("C1"));
+        assert!(ch.listens_in("C2"));
+        assert!(!ch.listens_in("C3"));
+        assert!(ch.listens_in("D024BE91L"));
+        assert!(!ch.with_direct_messages(false).listens_in("D024BE91L"));
+
+        let everywhere = SlackChannel::new("xoxb-fake".into(), None, vec![]);
+        assert!(everywhere.listens_in("C3"));
+        assert!(everywhere.listens_in("D024BE91L"));
+    }
+
     #[test]
     fn empty_allowlist_denies_everyone() {
         let ch = SlackChannel::new("xoxb-fake".into(), None, vec![]);
@@ -252,4 +1310,319 @@ mod tests {
         assert!(ch.is_user_allowed("U111"));
         assert!(ch.is_user_allowed("anyone"));
     }
+
+    #[test]
+    fn blank_app_token_keeps_polling() {
+        let ch =
+            SlackChannel::new("xoxb-fake".into(), None, vec![]).with_app_token(Some(" ".into()));
+        assert!(ch.app_token.is_none());
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec![])
+            .with_app_token(Some("xapp-1-A".into()));
+        assert_eq!(ch.app_token.as_deref(), Some("xapp-1-A"));
+    }
+
+    #[test]
+    fn socket_envelope_yields_message_events_only() {
+        let envelope = serde_json::json!({
+            "envelope_id": "e1",
+            "type": "events_api",
+            "payload": {"event": {"type": "message", "channel": "C1", "user": "U1", "text": "hi"}}
+        });
+        let event = SlackChannel::socket_message_event(&envelope).unwrap();
+        assert_eq!(event["text"], "hi");
+
+        let reaction = serde_json::json!({
+            "type": "events_api",
+            "payload": {"event": {"type": "reaction_added"}}
+        });
+        assert!(SlackChannel::socket_message_event(&reaction).is_none());
+        let hello = serde_json::json!({"type": "hello"});
+        assert!(SlackChannel::socket_message_event(&hello).is_none());
+    }
+
+    #[tokio::test]
+    async fn message_events_skip_bots_edits_and_strangers() {
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec!["U1".into(), "UBOT".into()]);
+        let msg = serde_json::json!({"type": "message", "user": "U1", "text": "hello"});
+        let parsed = ch.to_channel_message(&msg, "C1", "UBOT").await.unwrap();
+        assert_eq!(parsed.sender, "C1");
+        assert_eq!(parsed.content, "hello");
+        assert_eq!(parsed.reply_to, None);
+
+        let reply = serde_json::json!({
+            "user": "U1",
+            "text": "and staging?",
+            "ts": "1700000002.000200",
+            "thread_ts": "1700000001.000100"
+        });
+        let parsed = ch.to_channel_message(&reply, "C1", "UBOT").await.unwrap();
+        assert_eq!(parsed.sender, "C1:1700000001.000100");
+        assert_eq!(parsed.reply_to.as_deref(), Some("1700000001.000100"));
+        assert_eq!(parsed.context, None);
+
+        for skipped in [
+            serde_json::json!({"user": "UBOT", "text": "my own reply"}),
+            serde_json::json!({"user": "U1", "text": "x", "subtype": "message_changed"}),
+            serde_json::json!({"user": "U1", "text": "x", "bot_id": "B1"}),
+            serde_json::json!({"user": "U2", "text": "not allowed"}),
+            serde_json::json!({"user": "U1", "text": ""}),
+        ] {
+            assert!(ch
+                .to_channel_message(&skipped, "C1", "UBOT")
+                .await
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn thread_replies_are_addressed_to_their_thread() {
+        let reply =
+            serde_json::json!({"ts": "1700000002.000200", "thread_ts": "1700000001.000100"});
+        assert_eq!(
+            SlackChannel::reply_thread_ts(&reply),
+            Some("1700000001.000100")
+        );
+        let parent =
+            serde_json::json!({"ts": "1700000001.000100", "thread_ts": "1700000001.000100"});
+        assert_eq!(SlackChannel::reply_thread_ts(&parent), None);
+        let top_level = serde_json::json!({"ts": "1700000001.000100"});
+        assert_eq!(SlackChannel::reply_thread_ts(&top_level), None);
+
+        assert_eq!(
+            SlackChannel::split_recipient("C1:1700000001.000100"),
+            ("C1", Some("1700000001.000100"))
+        );
+        assert_eq!(SlackChannel::split_recipient("C1"), ("C1", None));
+        assert_eq!(SlackChannel::split_recipient("C1:"), ("C1:", None));
+    }
+
+    #[test]
+    fn thread_context_attributes_bot_messages_and_keeps_the_tail() {
+        let thread = vec![
+            serde_json::json!({"user": "U1", "text": "deploy failed"}),
+            serde_json::json!({"user": "UBOT", "text": "which env?"}),
+            serde_json::json!({"bot_id": "B2", "text": "CI: build red"}),
+            serde_json::json!({"user": "U2", "text": "  "}),
+        ];
+        assert_eq!(
+            SlackChannel::format_thread_context(&thread, "UBOT").unwrap(),
+            "[Thread context]\nU1: deploy failed\nassistant: which env?\nassistant: CI: build red"
+        );
+        assert!(SlackChannel::format_thread_context(&[], "UBOT").is_none());
+
+        let long: Vec<_> = (0..30)
+            .map(|i| serde_json::json!({"user": "U1", "text": format!("m{i}")}))
+            .collect();
+        let context = SlackChannel::format_thread_context(&long, "UBOT").unwrap();
+        assert_eq!(context.lines().count(), THREAD_CONTEXT_MESSAGES + 1);
+        assert!(context.ends_with("U1: m29"));
+        assert!(!context.contains("U1: m9\n"));
+    }
+
+    #[test]
+    fn markdown_replies_become_sections_and_code_blocks() {
+        let blocks = SlackChannel::markdown_blocks(
+            "## Fix\nRun **this**, see [docs](https://example.com/d):\n```sh\ncargo test\n```\nDone.",
+        );
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(
+            blocks[0]["text"]["text"],
+            "*Fix*\nRun *this*, see <https://example.com/d|docs>:"
+        );
+        assert_eq!(blocks[1]["type"], "rich_text");
+        assert_eq!(
+            blocks[1]["elements"][0]["elements"][0]["text"],
+            "cargo test"
+        );
+        assert_eq!(blocks[2]["text"]["text"], "Done.");
+
+        let unclosed = SlackChannel::markdown_blocks("```\nfn main() {}");
+        assert_eq!(unclosed.len(), 1);
+        assert_eq!(unclosed[0]["type"], "rich_text");
+
+        let long = SlackChannel::markdown_blocks(&"é".repeat(MAX_SECTION_CHARS + 1));
+        assert_eq!(long.len(), 2);
+
+        let too_many = "```\nx\n```\n".repeat(MAX_BLOCKS + 1);
+        assert!(SlackChannel::markdown_blocks(&too_many).is_empty());
+    }
+
+    #[test]
+    fn post_body_carries_fallback_text_thread_and_blocks() {
+        let blocks = vec![SlackChannel::approval_block("deploy-42")];
+        let body = SlackChannel::post_message_body("C1:1700000001.000100", "Approve?", &blocks);
+        assert_eq!(body["channel"], "C1");
+        assert_eq!(body["thread_ts"], "1700000001.000100");
+        assert_eq!(body["text"], "Approve?");
+        assert_eq!(
+            body["blocks"][0]["elements"][0]["value"],
+            "approve:deploy-42"
+        );
+
+        let plain = SlackChannel::post_message_body("C1", "hi", &[]);
+        assert!(plain.get("blocks").is_none());
+        assert!(plain.get("thread_ts").is_none());
+    }
+
+    #[test]
+    fn button_clicks_arrive_as_messages() {
+        let ch = SlackChannel::new("xoxb-fake".into(), Some("C1".into()), vec!["U1".into()]);
+        let click = |user: &str, channel: &str| {
+            serde_json::json!({
+                "type": "interactive",
+                "envelope_id": "e1",
+                "payload": {
+                    "type": "block_actions",
+                    "user": {"id": user},
+                    "channel": {"id": channel},
+                    "container": {"thread_ts": "1700000001.000100"},
+                    "actions": [{"action_id": "approve", "value": "approve:deploy-42"}]
+                }
+            })
+        };
+        let msg = ch.block_action_message(&click("U1", "C1")).unwrap();
+        assert_eq!(msg.content, "approve:deploy-42");
+        assert_eq!(msg.sender, "C1:1700000001.000100");
+        assert!(ch.block_action_message(&click("U2", "C1")).is_none());
+        assert!(ch.block_action_message(&click("U1", "C2")).is_none());
+    }
+
+    #[test]
+    fn image_files_picks_small_images_only() {
+        let msg = serde_json::json!({
+            "text": "what is this?",
+            "files": [
+                {"mimetype": "image/png", "size": 1024,
+                 "url_private": "https://files.slack.com/a.png",
+                 "url_private_download": "https://files.slack.com/download/a.png"},
+                {"mimetype": "application/pdf", "size": 10,
+                 "url_private": "https://files.slack.com/b.pdf"},
+                {"mimetype": "image/jpeg", "size": MAX_IMAGE_BYTES + 1,
+                 "url_private": "https://files.slack.com/huge.jpg"}
+            ]
+        });
+        assert_eq!(
+            SlackChannel::image_files(&msg),
+            vec![(
+                "https://files.slack.com/download/a.png".to_string(),
+                "image/png".to_string()
+            )]
+        );
+        assert!(SlackChannel::image_files(&serde_json::json!({"text": "hi"})).is_empty());
+    }
+
+    #[test]
+    fn reactions_map_to_actions_in_the_reacted_conversation() {
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec!["U1".into()]);
+        let envelope = serde_json::json!({
+            "type": "events_api",
+            "payload": {"event": {
+                "type": "reaction_added", "user": "U1", "reaction": "white_check_mark",
+                "item": {"type": "message", "channel": "C1", "ts": "2.0"}
+            }}
+        });
+        let event = SlackChannel::socket_reaction_event(&envelope).unwrap();
+        let target = serde_json::json!({
+            "ts": "2.0", "thread_ts": "1.0",
+            "blocks": [SlackChannel::section_block("Deploy?"), SlackChannel::approval_block("req-7")]
+        });
+        let msg = ch.reaction_message(event, Some(&target)).unwrap();
+        assert_eq!(msg.reaction, Some(ReactionAction::Approve));
+        assert_eq!(msg.content, "approve:req-7");
+        assert_eq!(msg.sender, "C1:1.0");
+        assert_eq!(msg.author.as_deref(), Some("U1"));
+
+        let msg = ch.reaction_message(event, None).unwrap();
+        assert_eq!(msg.content, "");
+        assert_eq!(msg.sender, "C1");
+
+        let mut repeat = event.clone();
+        repeat["reaction"] = "repeat".into();
+        let msg = ch.reaction_message(&repeat, None).unwrap();
+        assert_eq!(msg.reaction, Some(ReactionAction::Regenerate));
+
+        let mut thumbs = event.clone();
+        thumbs["reaction"] = "+1".into();
+        assert!(ch.reaction_message(&thumbs, None).is_none());
+        let mut stranger = event.clone();
+        stranger["user"] = "U2".into();
+        assert!(ch.reaction_message(&stranger, None).is_none());
+    }
+
+    #[test]
+    fn edits_and_deletions_refer_to_the_original_message() {
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec!["U1".into()]);
+        let edited = serde_json::json!({
+            "type": "message", "subtype": "message_changed", "channel": "C1", "ts": "3.0",
+            "message": {"user": "U1", "text": "new", "ts": "2.0", "thread_ts": "1.0"},
+            "previous_message": {"user": "U1", "text": "old", "ts": "2.0", "thread_ts": "1.0"}
+        });
+        let msg = ch.message_update(&edited, "C1", "UBOT").unwrap();
+        assert_eq!(msg.update, Some(MessageUpdate::Edited));
+        assert_eq!(msg.id, "C1:2.0");
+        assert_eq!(msg.sender, "C1:1.0");
+        assert_eq!(msg.content, "new");
+
+        let mut unfurl = edited.clone();
+        unfurl["message"]["text"] = "old".into();
+        assert!(ch.message_update(&unfurl, "C1", "UBOT").is_none());
+
+        let deleted = serde_json::json!({
+            "type": "message", "subtype": "message_deleted", "channel": "C1", "ts": "4.0",
+            "deleted_ts": "2.0",
+            "previous_message": {"user": "U1", "text": "old", "ts": "2.0"}
+        });
+        let msg = ch.message_update(&deleted, "C1", "UBOT").unwrap();
+        assert_eq!(msg.update, Some(MessageUpdate::Deleted));
+        assert_eq!(msg.id, "C1:2.0");
+        assert_eq!(msg.sender, "C1");
+
+        let mut own = deleted.clone();
+        own["previous_message"]["user"] = "UBOT".into();
+        assert!(ch.message_update(&own, "C1", "UBOT").is_none());
+        let plain = serde_json::json!({"type": "message", "user": "U1", "text": "hi", "ts": "5.0"});
+        assert!(ch.message_update(&plain, "C1", "UBOT").is_none());
+    }
+
+    #[test]
+    fn attachment_files_picks_small_non_images() {
+        let msg = serde_json::json!({
+            "files": [
+                {"mimetype": "image/png", "size": 1024, "name": "a.png",
+                 "url_private": "https://files.slack.com/a.png"},
+                {"mimetype": "text/csv", "size": 10, "name": "data.csv",
+                 "url_private": "https://files.slack.com/data.csv",
+                 "url_private_download": "https://files.slack.com/download/data.csv"},
+                {"mimetype": "application/zip", "size": MAX_ATTACHMENT_BYTES + 1,
+                 "name": "huge.zip", "url_private": "https://files.slack.com/huge.zip"}
+            ]
+        });
+        assert_eq!(
+            SlackChannel::attachment_files(&msg),
+            vec![(
+                "data.csv".to_string(),
+                "https://files.slack.com/download/data.csv".to_string(),
+                "text/csv".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn complete_upload_body_shares_into_thread_with_caption() {
+        let body = SlackChannel::complete_upload_body(
+            "F123",
+            "report.csv",
+            "Here is **the** report",
+            "C1:1700000000.000100",
+        );
+        assert_eq!(body["files"][0]["id"], "F123");
+        assert_eq!(body["files"][0]["title"], "report.csv");
+        assert_eq!(body["channel_id"], "C1");
+        assert_eq!(body["thread_ts"], "1700000000.000100");
+        assert_eq!(body["initial_comment"], "Here is *the* report");
+
+        let body = SlackChannel::complete_upload_body("F123", "a.txt", " ", "C1");
+        assert!(body.get("initial_comment").is_none());
+        assert!(body.get("thread_ts").is_none());
+    }
 }
@@ -0,0 +1,378 @@
+//! Splitting of long replies to fit a channel's message size limit.
+//!
+//! Every send through a [`SplitChannel`] goes out in pieces of at most the
+//! channel's limit (see [`ChannelsConfig::max_message_chars_for`]), broken at
+//! paragraphs or around code fences where possible, then at lines, then at
+//! words. A fence still open at a break is closed at the end of the piece and
+//! reopened, with its language, at the start of the next one.
+
+use super::traits::{Attachment, Channel, ChannelMessage};
+use crate::config::schema::ChannelsConfig;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Closing line added to a piece that ends inside a code fence.
+const FENCE_CLOSE: &str = "\n```";
+
+fn is_fence(line: &str) -> bool {
+    line.trim_start().starts_with("```")
+}
+
+/// The opening line of the code fence `text` ends inside, if any, given
+/// the fence `open` at its start.
+fn open_fence_after(text: &str, open: Option<&str>) -> Option<String> {
+    let mut open = open.map(str::to_string);
+    for line in text.lines().filter(|line| is_fence(line)) {
+        open = match open {
+            Some(_) => None,
+            None => Some(line.trim().to_string()),
+        };
+    }
+    open
+}
+
+/// Byte offset to break `window` at: the last paragraph break — a blank
+/// line, or the edge of a code fence — in its second half, else the last
+/// line break there, then the last space, else the whole window.
+fn break_point(window: &str, mut in_fence: bool) -> usize {
+    let mut floor = window.len() / 2;
+    while !window.is_char_boundary(floor) {
+        floor -= 1;
+    }
+    let mut paragraph = None;
+    let mut line = None;
+    let mut offset = 0;
+    let mut after_blank = false;
+    let mut after_fence_close = false;
+    for current in window.split_inclusive('\n') {
+        let fence = is_fence(current);
+        if offset > 0 && offset >= floor {
+            if !in_fence && (after_blank || after_fence_close || fence) {
+                paragraph = Some(offset);
+            }
+            line = Some(offset);
+        }
+        after_fence_close = fence && in_fence;
+        if fence {
+            in_fence = !in_fence;
+        }
+        after_blank = current.trim().is_empty();
+        offset += current.len();
+    }
+    paragraph
+        .or(line)
+        .or_else(|| window[floor..].rfind(' ').map(|pos| floor + pos + 1))
+        .unwrap_or(window.len())
+}
+
+/// What a size limit counts: characters for chat platforms, bytes for
+/// line protocols like IRC.
+#[derive(Clone, Copy)]
+enum Unit {
+    Chars,
+    Bytes,
+}
+
+impl Unit {
+    fn len(self, text: &str) -> usize {
+        match self {
+            Self::Chars => text.chars().count(),
+            Self::Bytes => text.len(),
+        }
+    }
+
+    /// Byte length of the longest prefix of `text` within `max` units,
+    /// but at least one character so splitting always makes progress.
+    fn prefix_end(self, text: &str, max: usize) -> usize {
+        let end = match self {
+            Self::Chars => text
+                .char_indices()
+                .nth(max)
+                .map_or(text.len(), |(pos, _)| pos),
+            Self::Bytes => {
+                let mut end = max.min(text.len());
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                end
+            }
+        };
+        if end == 0 {
+            text.chars().next().map_or(0, char::len_utf8)
+        } else {
+            end
+        }
+    }
+}
+
+/// Split `text` into pieces of at most `max_chars` characters. `0` keeps
+/// it whole.
+pub fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    split(text, max_chars, Unit::Chars)
+}
+
+/// Split `text` into pieces of at most `max_bytes` bytes, never inside a
+/// character. `0` keeps it whole.
+pub fn split_message_bytes(text: &str, max_bytes: usize) -> Vec<String> {
+    split(text, max_bytes, Unit::Bytes)
+}
+
+fn split(text: &str, max: usize, unit: Unit) -> Vec<String> {
+    if max == 0 || unit.len(text) <= max {
+        return vec![text.to_string()];
+    }
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    let mut reopen: Option<String> = None;
+    while !rest.is_empty() {
+        let prefix = reopen
+            .as_deref()
+            .map(|fence| format!("{fence}\n"))
+            .unwrap_or_default();
+        let prefix_len = unit.len(&prefix);
+        if prefix_len + unit.len(rest) <= max {
+            pieces.push(format!("{prefix}{rest}"));
+            break;
+        }
+        // Room for a closing fence is only kept when the piece needs one
+        let mut cut = 0;
+        let mut open = None;
+        for reserve in [0, FENCE_CLOSE.len()] {
+            let budget = max.saturating_sub(prefix_len + reserve).max(1);
+            let limit = unit.prefix_end(rest, budget);
+            cut = break_point(&rest[..limit], reopen.is_some());
+            open = open_fence_after(&rest[..cut], reopen.as_deref());
+            if open.is_none() {
+                break;
+            }
+        }
+        let (head, tail) = rest.split_at(cut);
+        let mut piece = format!("{prefix}{}", head.trim_end());
+        reopen = open;
+        if reopen.is_some() {
+            piece.push_str(FENCE_CLOSE);
+        }
+        pieces.push(piece);
+        rest = tail.trim_start_matches('\n');
+    }
+    pieces
+}
+
+/// Channel wrapper that sends long messages in pieces under the channel's
+/// size limit. Everything else goes straight to the wrapped channel.
+pub struct SplitChannel {
+    inner: Arc<dyn Channel>,
+    max_chars: usize,
+}
+
+impl SplitChannel {
+    /// `channel` split at its limit from `config`; returned as is when it
+    /// has none.
+    pub fn wrap(channel: Arc<dyn Channel>, config: &ChannelsConfig) -> Arc<dyn Channel> {
+        match config.max_message_chars_for(channel.name()) {
+            Some(max_chars) => Arc::new(Self {
+                inner: channel,
+                max_chars,
+            }),
+            None => channel,
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for SplitChannel {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+        for piece in split_message(message, self.max_chars) {
+            self.inner.send(&piece, recipient).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_blocks(
+        &self,
+        blocks: &[serde_json::Value],
+        fallback: &str,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        self.inner.send_blocks(blocks, fallback, recipient).await
+    }
+
+    /// A caption over the limit goes out as messages ahead of the file.
+    async fn send_attachment(
+        &self,
+        attachment: &Attachment,
+        caption: &str,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        if caption.chars().count() <= self.max_chars {
+            return self
+                .inner
+                .send_attachment(attachment, caption, recipient)
+                .await;
+        }
+        self.send(caption, recipient).await?;
+        self.inner.send_attachment(attachment, "", recipient).await
+    }
+
+    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        self.inner.listen(tx).await
+    }
+
+    async fn fetch_context(&self, msg: &ChannelMessage) -> anyhow::Result<Option<String>> {
+        self.inner.fetch_context(msg).await
+    }
+
+    async fn health_check(&self) -> bool {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn short_messages_stay_whole() {
+        assert_eq!(split_message("", 10), [""]);
+        assert_eq!(split_message("Hello, world!", 13), ["Hello, world!"]);
+        assert_eq!(split_message(&"a".repeat(5000), 0).len(), 1);
+    }
+
+    #[test]
+    fn paragraphs_are_preferred_over_lines_and_words() {
+        let text = format!(
+            "{}\n\n{}\n{} {}",
+            "a".repeat(60),
+            "b".repeat(20),
+            "c".repeat(10),
+            "d".repeat(30)
+        );
+        let pieces = split_message(&text, 100);
+        assert_eq!(pieces[0], "a".repeat(60));
+        assert!(pieces[1].starts_with('b'));
+
+        let lines = "line of text\n".repeat(20);
+        for piece in split_message(&lines, 50) {
+            assert!(piece.chars().count() <= 50);
+            assert!(piece.lines().all(|line| line == "line of text"));
+        }
+
+        let words = "word ".repeat(100);
+        let pieces = split_message(&words, 42);
+        assert!(pieces
+            .iter()
+            .all(|p| p.chars().count() <= 42 && p.split_whitespace().all(|w| w == "word")));
+        assert_eq!(pieces.concat().matches("word").count(), 100);
+    }
+
+    #[test]
+    fn hard_splits_count_characters_not_bytes() {
+        let text = "é".repeat(250);
+        let pieces = split_message(&text, 100);
+        assert_eq!(pieces.len(), 3);
+        assert!(pieces.iter().all(|p| p.chars().count() <= 100));
+        assert_eq!(pieces.concat(), text);
+
+        let emoji = "🦀 Rust is awesome! ".repeat(500);
+        for piece in split_message(&emoji, 2000) {
+            assert!(piece.chars().count() <= 2000);
+        }
+    }
+
+    #[test]
+    fn mixed_width_text_without_breaks_splits_on_char_boundaries() {
+        // 2-, 3- and 4-byte characters, so half a window is rarely a boundary
+        let text = "é日🦀".repeat(1500);
+        for limit in [2000, 4096, 7, 100] {
+            let pieces = split_message(&text, limit);
+            assert!(pieces.iter().all(|p| p.chars().count() <= limit));
+            assert_eq!(pieces.concat(), text);
+        }
+
+        let accented = format!("{} {}", "ñ".repeat(3000), "日本語".repeat(800));
+        for piece in split_message(&accented, 2000) {
+            assert!(piece.chars().count() <= 2000);
+        }
+    }
+
+    #[test]
+    fn byte_limits_never_cut_a_character() {
+        let text = "é日🦀 ".repeat(100);
+        let pieces = split_message_bytes(&text, 50);
+        assert!(pieces.iter().all(|p| p.len() <= 50));
+        assert_eq!(pieces.concat().matches('🦀').count(), 100);
+        assert_eq!(split_message_bytes("🦀🦀", 3), ["🦀", "🦀"]);
+    }
+
+    #[test]
+    fn code_fences_break_at_their_edges_or_are_reopened() {
+        let text = format!(
+            "{}\n```rust\nfn main() {{}}\n```\nDone.",
+            "Intro text. ".repeat(5)
+        );
+        let pieces = split_message(&text, 70);
+        assert_eq!(pieces[0], "Intro text. ".repeat(5).trim_end());
+        assert!(pieces[1].starts_with("```rust\n"));
+
+        let code: String = (0..40).map(|i| format!("let x{i} = {i};\n")).collect();
+        let text = format!("```rust\n{code}```\nThat's all.");
+        let pieces = split_message(&text, 200);
+        assert!(pieces.len() > 2);
+        for piece in &pieces {
+            assert!(piece.chars().count() <= 200, "{piece}");
+            assert_eq!(piece.matches("```").count() % 2, 0, "{piece}");
+        }
+        assert!(pieces[1].starts_with("```rust\nlet x"));
+        assert!(pieces.last().unwrap().ends_with("That's all."));
+        let joined = pieces.join("\n");
+        assert_eq!(
+            (0..40)
+                .filter(|i| joined.contains(&format!("let x{i} = {i};")))
+                .count(),
+            40
+        );
+    }
+
+    struct RecordingChannel {
+        sent: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "discord"
+        }
+
+        async fn send(&self, message: &str, _recipient: &str) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn long_sends_go_out_in_pieces_under_the_platform_limit() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let channel = SplitChannel::wrap(
+            Arc::new(RecordingChannel {
+                sent: Arc::clone(&sent),
+            }),
+            &ChannelsConfig::default(),
+        );
+        channel.send(&"word ".repeat(1000), "c1").await.unwrap();
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 3);
+        assert!(sent.iter().all(|piece| piece.chars().count() <= 2000));
+    }
+}
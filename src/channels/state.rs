@@ -0,0 +1,394 @@
+//! Channel delivery state that survives restarts: polling cursors (Slack's
+//! last seen `ts`, Telegram's update offset), the IDs of recently processed
+//! messages and the messages waiting for a retry after the provider failed,
+//! in `workspace/channels/state.db`. `start_channels` installs the store;
+//! without it channels keep this state in memory only and failed messages
+//! are not retried.
+
+use super::traits::{Audience, ChannelMessage};
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// How long processed message IDs are remembered.
+const SEEN_RETENTION_SECS: i64 = 7 * 24 * 60 * 60;
+
+static STORE: OnceLock<ChannelState> = OnceLock::new();
+
+/// Cursors and processed message IDs per channel.
+pub struct ChannelState {
+    conn: Mutex<Connection>,
+}
+
+impl ChannelState {
+    /// Open (or create) the store under `workspace_dir`, dropping message IDs
+    /// older than a week.
+    pub fn open(workspace_dir: &Path) -> Result<Self> {
+        let dir = workspace_dir.join("channels");
+        std::fs::create_dir_all(&dir).with_context(|| {
+            format!(
+                "Failed to create channel state directory: {}",
+                dir.display()
+            )
+        })?;
+        let db_path = dir.join("state.db");
+        let conn = Connection::open(&db_path)
+            .with_context(|| format!("Failed to open channel state DB: {}", db_path.display()))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS cursors (
+                channel TEXT NOT NULL,
+                key     TEXT NOT NULL,
+                value   TEXT NOT NULL,
+                PRIMARY KEY (channel, key)
+            );
+            CREATE TABLE IF NOT EXISTS seen_messages (
+                channel    TEXT NOT NULL,
+                message_id TEXT NOT NULL,
+                seen_at    INTEGER NOT NULL,
+                PRIMARY KEY (channel, message_id)
+            );
+            CREATE TABLE IF NOT EXISTS pending_retries (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                channel         TEXT NOT NULL,
+                message_id      TEXT NOT NULL,
+                sender          TEXT NOT NULL,
+                author          TEXT,
+                content         TEXT NOT NULL,
+                context         TEXT,
+                attempts        INTEGER NOT NULL,
+                next_attempt_at INTEGER NOT NULL,
+                queued_at       INTEGER NOT NULL
+            );",
+        )
+        .context("Failed to initialize channel state schema")?;
+        conn.execute(
+            "DELETE FROM seen_messages WHERE seen_at < ?1",
+            params![chrono::Utc::now().timestamp() - SEEN_RETENTION_SECS],
+        )
+        .context("Failed to prune seen messages")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    pub fn cursor(&self, channel: &str, key: &str) -> Result<Option<String>> {
+        self.conn()
+            .query_row(
+                "SELECT value FROM cursors WHERE channel = ?1 AND key = ?2",
+                params![channel, key],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to read channel cursor")
+    }
+
+    pub fn set_cursor(&self, channel: &str, key: &str, value: &str) -> Result<()> {
+        self.conn()
+            .execute(
+                "INSERT INTO cursors (channel, key, value) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(channel, key) DO UPDATE SET value = excluded.value",
+                params![channel, key, value],
+            )
+            .context("Failed to save channel cursor")?;
+        Ok(())
+    }
+
+    /// Record `message_id` as processed. Returns `false` when it already was.
+    pub fn mark_seen(&self, channel: &str, message_id: &str) -> Result<bool> {
+        let inserted = self
+            .conn()
+            .execute(
+                "INSERT OR IGNORE INTO seen_messages (channel, message_id, seen_at)
+                 VALUES (?1, ?2, ?3)",
+                params![channel, message_id, chrono::Utc::now().timestamp()],
+            )
+            .context("Failed to record seen message")?;
+        Ok(inserted > 0)
+    }
+
+    /// Queue `message` for another attempt in `delay_secs`, counting one
+    /// failed attempt so far. Images and attachment bytes are not kept.
+    pub fn queue_retry(&self, message: &ChannelMessage, delay_secs: u64) -> Result<i64> {
+        let now = chrono::Utc::now().timestamp();
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO pending_retries
+                (channel, message_id, sender, author, content, context, attempts,
+                 next_attempt_at, queued_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7, ?8)",
+            params![
+                message.channel,
+                message.id,
+                message.sender,
+                message.author,
+                message.content,
+                message.context,
+                now.saturating_add_unsigned(delay_secs),
+                now
+            ],
+        )
+        .context("Failed to queue message for retry")?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Count another failed attempt of retry `id` and try again in
+    /// `delay_secs`.
+    pub fn reschedule_retry(&self, id: i64, delay_secs: u64) -> Result<()> {
+        self.conn()
+            .execute(
+                "UPDATE pending_retries
+                 SET attempts = attempts + 1, next_attempt_at = ?2
+                 WHERE id = ?1",
+                params![
+                    id,
+                    chrono::Utc::now()
+                        .timestamp()
+                        .saturating_add_unsigned(delay_secs)
+                ],
+            )
+            .context("Failed to reschedule retry")?;
+        Ok(())
+    }
+
+    /// Forget retry `id`, once answered or given up on.
+    pub fn finish_retry(&self, id: i64) -> Result<()> {
+        self.conn()
+            .execute("DELETE FROM pending_retries WHERE id = ?1", params![id])
+            .context("Failed to remove retry")?;
+        Ok(())
+    }
+
+    /// The retry due soonest, oldest first among equals.
+    pub fn next_retry(&self) -> Result<Option<PendingRetry>> {
+        self.conn()
+            .query_row(
+                "SELECT id, attempts, next_attempt_at, channel, message_id, sender, author,
+                        content, context, queued_at
+                 FROM pending_retries ORDER BY next_attempt_at, id LIMIT 1",
+                [],
+                |row| {
+                    Ok(PendingRetry {
+                        id: row.get(0)?,
+                        attempts: row.get(1)?,
+                        due_at: row.get(2)?,
+                        message: ChannelMessage {
+                            channel: row.get(3)?,
+                            id: row.get(4)?,
+                            sender: row.get(5)?,
+                            author: row.get(6)?,
+                            content: row.get(7)?,
+                            context: row.get(8)?,
+                            reply_to: None,
+                            timestamp: row.get::<_, i64>(9)?.try_into().unwrap_or_default(),
+                            images: Vec::new(),
+                            attachments: Vec::new(),
+                            reaction: None,
+                            update: None,
+                            audience: Audience::Direct,
+                        },
+                    })
+                },
+            )
+            .optional()
+            .context("Failed to read pending retries")
+    }
+}
+
+/// A message whose reply failed and waits for another attempt
+#[derive(Debug, Clone)]
+pub struct PendingRetry {
+    pub id: i64,
+    /// Failed attempts so far
+    pub attempts: u32,
+    /// Unix time of the next attempt
+    pub due_at: i64,
+    pub message: ChannelMessage,
+}
+
+/// Use `state` for every channel from now on. Only the first call takes
+/// effect.
+pub fn install(state: ChannelState) {
+    let _ = STORE.set(state);
+}
+
+/// The saved cursor `key` of `channel`, if the store is installed and has one.
+pub fn cursor(channel: &str, key: &str) -> Option<String> {
+    let store = STORE.get()?;
+    store.cursor(channel, key).unwrap_or_else(|e| {
+        tracing::warn!("{e:#}");
+        None
+    })
+}
+
+/// Save cursor `key` of `channel`; a no-op without an installed store.
+pub fn set_cursor(channel: &str, key: &str, value: &str) {
+    if let Some(store) = STORE.get() {
+        if let Err(e) = store.set_cursor(channel, key, value) {
+            tracing::warn!("{e:#}");
+        }
+    }
+}
+
+/// Record `message_id` as processed. Returns `false` when a previous run (or
+/// this one) already processed it; always `true` without an installed store.
+pub fn mark_seen(channel: &str, message_id: &str) -> bool {
+    let Some(store) = STORE.get() else {
+        return true;
+    };
+    store.mark_seen(channel, message_id).unwrap_or_else(|e| {
+        tracing::warn!("{e:#}");
+        true
+    })
+}
+
+/// Queue `message` for a retry in `delay_secs`. Returns `false` when it
+/// cannot be queued, including without an installed store.
+pub fn queue_retry(message: &ChannelMessage, delay_secs: u64) -> bool {
+    let Some(store) = STORE.get() else {
+        return false;
+    };
+    store
+        .queue_retry(message, delay_secs)
+        .map_err(|e| tracing::warn!("{e:#}"))
+        .is_ok()
+}
+
+/// See [`ChannelState::reschedule_retry`]; a no-op without an installed store.
+pub fn reschedule_retry(id: i64, delay_secs: u64) {
+    if let Some(store) = STORE.get() {
+        if let Err(e) = store.reschedule_retry(id, delay_secs) {
+            tracing::warn!("{e:#}");
+        }
+    }
+}
+
+/// See [`ChannelState::finish_retry`]; a no-op without an installed store.
+pub fn finish_retry(id: i64) {
+    if let Some(store) = STORE.get() {
+        if let Err(e) = store.finish_retry(id) {
+            tracing::warn!("{e:#}");
+        }
+    }
+}
+
+/// The retry due soonest, if the store is installed and has one.
+pub fn next_retry() -> Option<PendingRetry> {
+    let store = STORE.get()?;
+    store.next_retry().unwrap_or_else(|e| {
+        tracing::warn!("{e:#}");
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn cursors_survive_reopening() {
+        let tmp = TempDir::new().unwrap();
+        let state = ChannelState::open(tmp.path()).unwrap();
+        assert_eq!(state.cursor("slack", "C1").unwrap(), None);
+        state
+            .set_cursor("slack", "C1", "1700000000.000100")
+            .unwrap();
+        state
+            .set_cursor("slack", "C1", "1700000000.000200")
+            .unwrap();
+        state.set_cursor("telegram", "offset", "42").unwrap();
+        drop(state);
+
+        let state = ChannelState::open(tmp.path()).unwrap();
+        assert_eq!(
+            state.cursor("slack", "C1").unwrap().as_deref(),
+            Some("1700000000.000200")
+        );
+        assert_eq!(
+            state.cursor("telegram", "offset").unwrap().as_deref(),
+            Some("42")
+        );
+        assert!(tmp.path().join("channels").join("state.db").exists());
+    }
+
+    #[test]
+    fn seen_messages_are_reported_once_per_channel() {
+        let tmp = TempDir::new().unwrap();
+        let state = ChannelState::open(tmp.path()).unwrap();
+        assert!(state.mark_seen("slack", "C1:1.0").unwrap());
+        assert!(!state.mark_seen("slack", "C1:1.0").unwrap());
+        assert!(state.mark_seen("email", "C1:1.0").unwrap());
+        drop(state);
+
+        let state = ChannelState::open(tmp.path()).unwrap();
+        assert!(!state.mark_seen("slack", "C1:1.0").unwrap());
+    }
+
+    #[test]
+    fn old_seen_messages_are_pruned_on_open() {
+        let tmp = TempDir::new().unwrap();
+        let state = ChannelState::open(tmp.path()).unwrap();
+        state
+            .conn()
+            .execute(
+                "INSERT INTO seen_messages VALUES ('slack', 'old', ?1)",
+                params![chrono::Utc::now().timestamp() - SEEN_RETENTION_SECS - 60],
+            )
+            .unwrap();
+        drop(state);
+
+        let state = ChannelState::open(tmp.path()).unwrap();
+        assert!(state.mark_seen("slack", "old").unwrap());
+    }
+
+    #[test]
+    fn retries_are_kept_in_due_order_until_finished() {
+        let tmp = TempDir::new().unwrap();
+        let state = ChannelState::open(tmp.path()).unwrap();
+        assert!(state.next_retry().unwrap().is_none());
+        let message = |id: &str| ChannelMessage {
+            id: id.to_string(),
+            sender: "C1".to_string(),
+            content: "deploy status?".to_string(),
+            channel: "slack".to_string(),
+            timestamp: 0,
+            images: Vec::new(),
+            context: Some("thread so far".to_string()),
+            reply_to: None,
+            author: Some("U1".to_string()),
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience: Audience::Mention,
+        };
+        let later = state.queue_retry(&message("C1:2.0"), 600).unwrap();
+        let sooner = state.queue_retry(&message("C1:1.0"), 0).unwrap();
+        drop(state);
+
+        let state = ChannelState::open(tmp.path()).unwrap();
+        let due = state.next_retry().unwrap().unwrap();
+        assert_eq!(due.id, sooner);
+        assert_eq!(due.attempts, 1);
+        assert!(due.due_at <= chrono::Utc::now().timestamp());
+        assert_eq!(due.message.id, "C1:1.0");
+        assert_eq!(due.message.author.as_deref(), Some("U1"));
+        assert_eq!(due.message.context.as_deref(), Some("thread so far"));
+        assert_eq!(due.message.content, "deploy status?");
+
+        state.reschedule_retry(sooner, 1200).unwrap();
+        let due = state.next_retry().unwrap().unwrap();
+        assert_eq!(due.id, later);
+        state.finish_retry(later).unwrap();
+        let due = state.next_retry().unwrap().unwrap();
+        assert_eq!((due.id, due.attempts), (sooner, 2));
+        state.finish_retry(sooner).unwrap();
+        assert!(state.next_retry().unwrap().is_none());
+    }
+}
@@ -1,4 +1,5 @@
-use super::traits::{Channel, ChannelMessage};
+use super::format::{to_plain_text, to_telegram_html};
+use super::traits::{Audience, Channel, ChannelMessage, MessageUpdate, ReactionAction};
 use async_trait::async_trait;
 use reqwest::multipart::{Form, Part};
 use std::path::Path;
@@ -8,16 +9,232 @@ use uuid::Uuid;
 pub struct TelegramChannel {
     bot_token: String,
     allowed_users: Vec<String>,
+    allowed_chats: Vec<String>,
+    group_mention_only: bool,
     client: reqwest::Client,
 }
 
+/// The bot's own account, from `getMe`; used to spot mentions and replies.
+#[derive(Debug, Default)]
+struct BotIdentity {
+    id: i64,
+    username: String,
+}
+
 impl TelegramChannel {
     pub fn new(bot_token: String, allowed_users: Vec<String>) -> Self {
         Self {
             bot_token,
             allowed_users,
-            client: reqwest::Client::new(),
+            allowed_chats: Vec::new(),
+            group_mention_only: true,
+            client: crate::proxy::client("telegram"),
+        }
+    }
+
+    /// Only answer in these chat IDs; empty answers in any chat.
+    #[must_use]
+    pub fn with_allowed_chats(mut self, allowed_chats: Vec<String>) -> Self {
+        self.allowed_chats = allowed_chats;
+        self
+    }
+
+    /// In groups, only answer messages that mention the bot or reply to it.
+    #[must_use]
+    pub fn with_group_mention_only(mut self, group_mention_only: bool) -> Self {
+        self.group_mention_only = group_mention_only;
+        self
+    }
+
+    async fn bot_identity(&self) -> Option<BotIdentity> {
+        let resp: serde_json::Value = self
+            .client
+            .get(self.api_url("getMe"))
+            .send()
+            .await
+            .ok()?
+            .json()
+            .await
+            .ok()?;
+        let me = resp.get("result")?;
+        Some(BotIdentity {
+            id: me.get("id")?.as_i64()?,
+            username: me.get("username")?.as_str()?.to_string(),
+        })
+    }
+
+    /// Turn an update's `message` into a [`ChannelMessage`] addressed to its
+    /// chat, with any `@bot` mention removed. Returns `None` for non-text
+    /// messages, unauthorized users or chats, and — in mention-only mode —
+    /// group messages that neither mention the bot nor reply to it. Its `id`
+    /// is `chat_id:message_id`, which an `edited_message` of it shares.
+    fn to_channel_message(
+        &self,
+        message: &serde_json::Value,
+        bot: &BotIdentity,
+    ) -> Option<ChannelMessage> {
+        let text = message.get("text").and_then(serde_json::Value::as_str)?;
+
+        let username_opt = message
+            .get("from")
+            .and_then(|f| f.get("username"))
+            .and_then(|u| u.as_str());
+        let username = username_opt.unwrap_or("unknown");
+
+        let user_id = message
+            .get("from")
+            .and_then(|f| f.get("id"))
+            .and_then(serde_json::Value::as_i64);
+        let user_id_str = user_id.map(|id| id.to_string());
+
+        let mut identities = vec![username];
+        if let Some(ref id) = user_id_str {
+            identities.push(id.as_str());
+        }
+
+        if !self.is_any_user_allowed(identities.iter().copied()) {
+            tracing::warn!(
+                "Telegram: ignoring message from unauthorized user: username={username}, user_id={}. \
+Allowlist Telegram @username or numeric user ID, then run `zeroclaw onboard --channels-only`.",
+                user_id_str.as_deref().unwrap_or("unknown")
+            );
+            return None;
+        }
+
+        let chat = message.get("chat");
+        let chat_id = chat
+            .and_then(|c| c.get("id"))
+            .and_then(serde_json::Value::as_i64)
+            .map(|id| id.to_string())
+            .unwrap_or_default();
+        if !self.allowed_chats.is_empty() && !self.allowed_chats.contains(&chat_id) {
+            tracing::warn!("Telegram: ignoring message from chat not in allowed_chats: {chat_id}");
+            return None;
+        }
+
+        let is_group = matches!(
+            chat.and_then(|c| c.get("type")).and_then(|t| t.as_str()),
+            Some("group" | "supergroup")
+        );
+        let mention = format!("@{}", bot.username);
+        let mentioned =
+            !bot.username.is_empty() && text.to_lowercase().contains(&mention.to_lowercase());
+        let replied_to_bot = message
+            .get("reply_to_message")
+            .and_then(|r| r.get("from"))
+            .and_then(|f| f.get("id"))
+            .and_then(serde_json::Value::as_i64)
+            .is_some_and(|id| id == bot.id);
+        if is_group && self.group_mention_only && !mentioned && !replied_to_bot {
+            return None;
+        }
+        let audience = if !is_group {
+            Audience::Direct
+        } else if mentioned || replied_to_bot {
+            Audience::Mention
+        } else {
+            Audience::Group
+        };
+
+        let content = if mentioned {
+            strip_mention(text, &mention)
+        } else {
+            text.trim().to_string()
+        };
+        if content.is_empty() {
+            return None;
+        }
+
+        let message_id = message
+            .get("message_id")
+            .and_then(serde_json::Value::as_i64)
+            .unwrap_or_default();
+        // The Bot API cannot fetch old messages, but embeds the one replied to
+        let replied = message.get("reply_to_message");
+        let reply_to = replied
+            .and_then(|r| r.get("message_id"))
+            .and_then(serde_json::Value::as_i64)
+            .map(|id| format!("{chat_id}:{id}"));
+        Some(ChannelMessage {
+            id: format!("{chat_id}:{message_id}"),
+            sender: chat_id,
+            content,
+            channel: "telegram".to_string(),
+            images: Vec::new(),
+            context: replied.and_then(|r| reply_context(r, bot)),
+            reply_to,
+            author: username_opt.map(str::to_string).or(user_id_str),
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
+    /// Turn a `message_reaction` update into a [`ChannelMessage`] when the
+    /// user newly added an emoji that maps to a [`ReactionAction`]. Same user
+    /// and chat allowlists as messages.
+    fn reaction_message(&self, reaction: &serde_json::Value) -> Option<ChannelMessage> {
+        let emojis = |field: &str| -> Vec<&str> {
+            reaction
+                .get(field)
+                .and_then(serde_json::Value::as_array)
+                .map(|list| {
+                    list.iter()
+                        .filter_map(|r| r.get("emoji").and_then(|e| e.as_str()))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let old = emojis("old_reaction");
+        let action = emojis("new_reaction")
+            .into_iter()
+            .filter(|emoji| !old.contains(emoji))
+            .find_map(ReactionAction::from_emoji)?;
+
+        let user = reaction.get("user")?;
+        let username_opt = user.get("username").and_then(|u| u.as_str());
+        let user_id_str = user
+            .get("id")
+            .and_then(serde_json::Value::as_i64)
+            .map(|id| id.to_string());
+        let identities = username_opt.into_iter().chain(user_id_str.as_deref());
+        if !self.is_any_user_allowed(identities) {
+            tracing::warn!("Telegram: ignoring reaction from unauthorized user");
+            return None;
+        }
+
+        let chat_id = reaction
+            .get("chat")
+            .and_then(|c| c.get("id"))
+            .and_then(serde_json::Value::as_i64)?
+            .to_string();
+        if !self.allowed_chats.is_empty() && !self.allowed_chats.contains(&chat_id) {
+            return None;
         }
+
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender: chat_id,
+            content: String::new(),
+            channel: "telegram".to_string(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author: username_opt.map(str::to_string).or(user_id_str),
+            attachments: Vec::new(),
+            reaction: Some(action),
+            update: None,
+            audience: Audience::Direct,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
     }
 
     fn api_url(&self, method: &str) -> String {
@@ -363,41 +580,79 @@ impl TelegramChannel {
     }
 }
 
+/// `text` with every case-insensitive occurrence of `mention` (`@bot`) removed.
+fn strip_mention(text: &str, mention: &str) -> String {
+    match regex::Regex::new(&format!("(?i){}", regex::escape(mention))) {
+        Ok(pattern) => pattern.replace_all(text, "").trim().to_string(),
+        Err(_) => text.trim().to_string(),
+    }
+}
+
+/// The message a reply answers as an `author: text` line, with the bot's
+/// own messages attributed to `assistant`. `None` for messages without
+/// text or caption.
+fn reply_context(replied: &serde_json::Value, bot: &BotIdentity) -> Option<String> {
+    let text = replied
+        .get("text")
+        .or_else(|| replied.get("caption"))
+        .and_then(|t| t.as_str())?
+        .trim();
+    if text.is_empty() {
+        return None;
+    }
+    let from = replied.get("from");
+    let author = if from
+        .and_then(|f| f.get("id"))
+        .and_then(serde_json::Value::as_i64)
+        .is_some_and(|id| id == bot.id)
+    {
+        "assistant".to_string()
+    } else {
+        from.and_then(|f| f.get("username").or_else(|| f.get("first_name")))
+            .and_then(|u| u.as_str())
+            .unwrap_or("unknown")
+            .to_string()
+    };
+    Some(format!("[Replying to]\n{author}: {text}"))
+}
+
 #[async_trait]
 impl Channel for TelegramChannel {
     fn name(&self) -> &str {
         "telegram"
     }
 
+    /// Sends `message` converted to Telegram HTML, or as plain text if
+    /// Telegram rejects the markup.
     async fn send(&self, message: &str, chat_id: &str) -> anyhow::Result<()> {
-        let markdown_body = serde_json::json!({
+        let html_body = serde_json::json!({
             "chat_id": chat_id,
-            "text": message,
-            "parse_mode": "Markdown"
+            "text": to_telegram_html(message),
+            "parse_mode": "HTML"
         });
 
-        let markdown_resp = self
+        let html_resp = self
             .client
             .post(self.api_url("sendMessage"))
-            .json(&markdown_body)
+            .json(&html_body)
             .send()
             .await?;
 
-        if markdown_resp.status().is_success() {
+        if html_resp.status().is_success() {
             return Ok(());
         }
 
-        let markdown_status = markdown_resp.status();
-        let markdown_err = markdown_resp.text().await.unwrap_or_default();
+        let html_status = html_resp.status();
+        let html_err = html_resp.text().await.unwrap_or_default();
         tracing::warn!(
-            status = ?markdown_status,
-            "Telegram sendMessage with Markdown failed; retrying without parse_mode"
+            status = ?html_status,
+            "Telegram sendMessage with HTML failed; retrying without parse_mode"
         );
 
         // Retry without parse_mode as a compatibility fallback.
         let plain_body = serde_json::json!({
             "chat_id": chat_id,
-            "text": message,
+            "text": to_plain_text(message),
         });
         let plain_resp = self
             .client
@@ -410,9 +665,9 @@ impl Channel for TelegramChannel {
             let plain_status = plain_resp.status();
             let plain_err = plain_resp.text().await.unwrap_or_default();
             anyhow::bail!(
-                "Telegram sendMessage failed (markdown {}: {}; plain {}: {})",
-                markdown_status,
-                markdown_err,
+                "Telegram sendMessage failed (html {}: {}; plain {}: {})",
+                html_status,
+                html_err,
                 plain_status,
                 plain_err
             );
@@ -422,7 +677,14 @@ impl Channel for TelegramChannel {
     }
 
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
-        let mut offset: i64 = 0;
+        // Resume after the last update handled before a restart
+        let mut offset: i64 = super::state::cursor("telegram", "offset")
+            .and_then(|o| o.parse().ok())
+            .unwrap_or(0);
+        let bot = self.bot_identity().await.unwrap_or_else(|| {
+            tracing::warn!("Telegram: getMe failed; group messages need a reply to the bot");
+            BotIdentity::default()
+        });
 
         tracing::info!("Telegram channel listening for messages...");
 
@@ -431,7 +693,7 @@ impl Channel for TelegramChannel {
             let body = serde_json::json!({
                 "offset": offset,
                 "timeout": 30,
-                "allowed_updates": ["message"]
+                "allowed_updates": ["message", "edited_message", "message_reaction"]
             });
 
             let resp = match self.client.post(&url).json(&body).send().await {
@@ -457,52 +719,35 @@ impl Channel for TelegramChannel {
                     // Advance offset past this update
                     if let Some(uid) = update.get("update_id").and_then(serde_json::Value::as_i64) {
                         offset = uid + 1;
+                        super::state::set_cursor("telegram", "offset", &offset.to_string());
                     }
 
-                    let Some(message) = update.get("message") else {
+                    if let Some(reaction) = update.get("message_reaction") {
+                        if let Some(msg) = self.reaction_message(reaction) {
+                            if tx.send(msg).await.is_err() {
+                                return Ok(());
+                            }
+                        }
                         continue;
-                    };
+                    }
 
-                    let Some(text) = message.get("text").and_then(serde_json::Value::as_str) else {
-                        continue;
+                    // Edits arrive as the whole message again, under its original ID
+                    let (message, update_kind) = match update.get("edited_message") {
+                        Some(edited) => (edited, Some(MessageUpdate::Edited)),
+                        None => match update.get("message") {
+                            Some(message) => (message, None),
+                            None => continue,
+                        },
                     };
 
-                    let username_opt = message
-                        .get("from")
-                        .and_then(|f| f.get("username"))
-                        .and_then(|u| u.as_str());
-                    let username = username_opt.unwrap_or("unknown");
-
-                    let user_id = message
-                        .get("from")
-                        .and_then(|f| f.get("id"))
-                        .and_then(serde_json::Value::as_i64);
-                    let user_id_str = user_id.map(|id| id.to_string());
-
-                    let mut identities = vec![username];
-                    if let Some(ref id) = user_id_str {
-                        identities.push(id.as_str());
-                    }
-
-                    if !self.is_any_user_allowed(identities.iter().copied()) {
-                        tracing::warn!(
-                            "Telegram: ignoring message from unauthorized user: username={username}, user_id={}. \
-Allowlist Telegram @username or numeric user ID, then run `zeroclaw onboard --channels-only`.",
-                            user_id_str.as_deref().unwrap_or("unknown")
-                        );
+                    let Some(mut msg) = self.to_channel_message(message, &bot) else {
                         continue;
-                    }
-
-                    let chat_id = message
-                        .get("chat")
-                        .and_then(|c| c.get("id"))
-                        .and_then(serde_json::Value::as_i64)
-                        .map(|id| id.to_string())
-                        .unwrap_or_default();
+                    };
+                    msg.update = update_kind;
 
                     // Send "typing" indicator immediately when we receive a message
                     let typing_body = serde_json::json!({
-                        "chat_id": &chat_id,
+                        "chat_id": &msg.sender,
                         "action": "typing"
                     });
                     let _ = self.client
@@ -511,17 +756,6 @@ Allowlist Telegram @username or numeric user ID, then run `zeroclaw onboard --ch
                         .send()
                         .await; // Ignore errors for typing indicator
 
-                    let msg = ChannelMessage {
-                        id: Uuid::new_v4().to_string(),
-                        sender: chat_id,
-                        content: text.to_string(),
-                        channel: "telegram".to_string(),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs(),
-                    };
-
                     if tx.send(msg).await.is_err() {
                         return Ok(());
                     }
@@ -559,6 +793,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn telegram_reactions_map_newly_added_emoji() {
+        let ch = TelegramChannel::new("t".into(), vec!["alice".into()]);
+        let reaction = serde_json::json!({
+            "chat": {"id": 42, "type": "private"},
+            "message_id": 7,
+            "user": {"id": 1, "username": "alice"},
+            "old_reaction": [{"type": "emoji", "emoji": "👍"}],
+            "new_reaction": [{"type": "emoji", "emoji": "👍"}, {"type": "emoji", "emoji": "🗑"}]
+        });
+        let msg = ch.reaction_message(&reaction).unwrap();
+        assert_eq!(msg.reaction, Some(ReactionAction::Forget));
+        assert_eq!(msg.sender, "42");
+        assert_eq!(msg.author.as_deref(), Some("alice"));
+
+        let mut removed = reaction.clone();
+        removed["old_reaction"] = removed["new_reaction"].clone();
+        assert!(ch.reaction_message(&removed).is_none());
+        let mut stranger = reaction.clone();
+        stranger["user"] = serde_json::json!({"id": 2, "username": "eve"});
+        assert!(ch.reaction_message(&stranger).is_none());
+    }
+
     #[test]
     fn telegram_user_allowed_wildcard() {
         let ch = TelegramChannel::new("t".into(), vec!["*".into()]);
@@ -608,6 +865,90 @@ mod tests {
         assert!(ch.is_user_allowed("anyone"));
     }
 
+    fn update_message(chat_type: &str, text: &str) -> serde_json::Value {
+        serde_json::json!({
+            "from": {"id": 42, "username": "alice"},
+            "chat": {"id": -100_123, "type": chat_type},
+            "message_id": 9,
+            "text": text,
+        })
+    }
+
+    fn bot() -> BotIdentity {
+        BotIdentity {
+            id: 7,
+            username: "claw_bot".into(),
+        }
+    }
+
+    #[test]
+    fn telegram_groups_need_mention_or_reply() {
+        let ch = TelegramChannel::new("t".into(), vec!["alice".into()]);
+        assert!(ch
+            .to_channel_message(&update_message("supergroup", "just chatting"), &bot())
+            .is_none());
+
+        let msg = ch
+            .to_channel_message(&update_message("group", "@Claw_Bot status?"), &bot())
+            .unwrap();
+        assert_eq!(msg.sender, "-100123");
+        assert_eq!(msg.content, "status?");
+        assert_eq!(msg.audience, Audience::Mention);
+        assert_eq!(msg.id, "-100123:9");
+
+        let mut reply = update_message("group", "and now?");
+        reply["reply_to_message"] = serde_json::json!({"from": {"id": 7}});
+        assert_eq!(
+            ch.to_channel_message(&reply, &bot()).unwrap().content,
+            "and now?"
+        );
+
+        reply["reply_to_message"] = serde_json::json!({
+            "message_id": 5,
+            "from": {"id": 7},
+            "text": "Deploy finished."
+        });
+        let msg = ch.to_channel_message(&reply, &bot()).unwrap();
+        assert_eq!(msg.reply_to.as_deref(), Some("-100123:5"));
+        assert_eq!(
+            msg.context.as_deref(),
+            Some("[Replying to]\nassistant: Deploy finished.")
+        );
+        let mut reply = update_message("private", "what's this?");
+        reply["reply_to_message"] = serde_json::json!({
+            "message_id": 4,
+            "from": {"id": 42, "username": "alice"},
+            "caption": "see screenshot"
+        });
+        let msg = ch.to_channel_message(&reply, &bot()).unwrap();
+        assert_eq!(
+            msg.context.as_deref(),
+            Some("[Replying to]\nalice: see screenshot")
+        );
+
+        assert!(ch
+            .to_channel_message(&update_message("private", "hi"), &bot())
+            .is_some());
+        let everyone =
+            TelegramChannel::new("t".into(), vec!["alice".into()]).with_group_mention_only(false);
+        assert!(everyone
+            .to_channel_message(&update_message("group", "just chatting"), &bot())
+            .is_some());
+    }
+
+    #[test]
+    fn telegram_allowed_chats_filter() {
+        let ch = TelegramChannel::new("t".into(), vec!["*".into()])
+            .with_allowed_chats(vec!["555".into()]);
+        assert!(ch
+            .to_channel_message(&update_message("private", "hi"), &bot())
+            .is_none());
+        let ch = ch.with_allowed_chats(vec!["-100123".into()]);
+        assert!(ch
+            .to_channel_message(&update_message("private", "hi"), &bot())
+            .is_some());
+    }
+
     #[test]
     fn telegram_user_allowed_by_numeric_id_identity() {
         let ch = TelegramChannel::new("t".into(), vec!["123456789".into()]);
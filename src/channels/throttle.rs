@@ -0,0 +1,280 @@
+//! Outbound rate limiting for channels.
+//!
+//! Every channel's sends draw from a token bucket that holds `burst` messages
+//! and refills at `messages_per_minute` (see
+//! [`ChannelRateLimitConfig::for_channel`]), shared by all wrappers with the
+//! same channel name — the channel daemon and the gateway's `/send` included.
+//! Sends over the limit wait in line, oldest first, instead of failing, so
+//! bulk notifications and chatty cron jobs stay under the platform's limits.
+
+use super::traits::{Attachment, Channel, ChannelMessage};
+use crate::config::schema::ChannelRateLimitConfig;
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+struct Bucket {
+    limit: ChannelRateLimitConfig,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(limit: ChannelRateLimitConfig) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.burst.max(1)),
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Take a token now, or return how long until one is available.
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        let rate = f64::from(self.limit.messages_per_minute) / 60.0;
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(f64::from(self.limit.burst.max(1)));
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+    }
+}
+
+struct Shared {
+    bucket: Mutex<Bucket>,
+    /// Held while waiting for a token, so queued sends go out in order
+    queue: tokio::sync::Mutex<()>,
+}
+
+static REGISTRY: OnceLock<Mutex<BTreeMap<String, Arc<Shared>>>> = OnceLock::new();
+
+/// Bucket for `channel`, created on first use. A later call with a different
+/// limit reconfigures the existing bucket.
+fn shared(channel: &str, limit: ChannelRateLimitConfig) -> Arc<Shared> {
+    let mut map = REGISTRY
+        .get_or_init(|| Mutex::new(BTreeMap::new()))
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let entry = map.entry(channel.to_string()).or_insert_with(|| {
+        Arc::new(Shared {
+            bucket: Mutex::new(Bucket::new(limit)),
+            queue: tokio::sync::Mutex::new(()),
+        })
+    });
+    {
+        let mut bucket = entry
+            .bucket
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if bucket.limit != limit {
+            *bucket = Bucket::new(limit);
+        }
+    }
+    Arc::clone(entry)
+}
+
+/// Channel wrapper that queues sends behind the channel's rate limit.
+/// Listening and health checks go straight to the wrapped channel.
+pub struct ThrottledChannel {
+    inner: Arc<dyn Channel>,
+    shared: Arc<Shared>,
+}
+
+impl ThrottledChannel {
+    /// `channel` behind its limit from `configured` or the platform default;
+    /// returned as is when it has none.
+    pub fn wrap(
+        channel: Arc<dyn Channel>,
+        configured: &BTreeMap<String, ChannelRateLimitConfig>,
+    ) -> Arc<dyn Channel> {
+        match ChannelRateLimitConfig::for_channel(configured, channel.name()) {
+            Some(limit) => Arc::new(Self {
+                shared: shared(channel.name(), limit),
+                inner: channel,
+            }),
+            None => channel,
+        }
+    }
+
+    /// Wait for this send's turn and a token.
+    async fn acquire(&self) {
+        let _turn = self.shared.queue.lock().await;
+        loop {
+            let wait = match self
+                .shared
+                .bucket
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .try_acquire(Instant::now())
+            {
+                Ok(()) => return,
+                Err(wait) => wait,
+            };
+            tracing::debug!(
+                channel = self.inner.name(),
+                wait_ms = wait.as_millis(),
+                "Throttling channel send"
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for ThrottledChannel {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+        self.acquire().await;
+        self.inner.send(message, recipient).await
+    }
+
+    async fn send_blocks(
+        &self,
+        blocks: &[serde_json::Value],
+        fallback: &str,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        self.acquire().await;
+        self.inner.send_blocks(blocks, fallback, recipient).await
+    }
+
+    async fn send_attachment(
+        &self,
+        attachment: &Attachment,
+        caption: &str,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        self.acquire().await;
+        self.inner
+            .send_attachment(attachment, caption, recipient)
+            .await
+    }
+
+    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        self.inner.listen(tx).await
+    }
+
+    async fn fetch_context(&self, msg: &ChannelMessage) -> anyhow::Result<Option<String>> {
+        self.inner.fetch_context(msg).await
+    }
+
+    async fn health_check(&self) -> bool {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(messages_per_minute: u32, burst: u32) -> ChannelRateLimitConfig {
+        ChannelRateLimitConfig {
+            messages_per_minute,
+            burst,
+        }
+    }
+
+    #[test]
+    fn bucket_allows_a_burst_then_the_rate() {
+        let mut bucket = Bucket::new(limit(60, 3));
+        let start = bucket.refilled_at;
+        for _ in 0..3 {
+            assert!(bucket.try_acquire(start).is_ok());
+        }
+        let wait = bucket.try_acquire(start).unwrap_err();
+        assert!((wait.as_secs_f64() - 1.0).abs() < 0.01, "{wait:?}");
+        assert!(bucket
+            .try_acquire(start + Duration::from_millis(1000))
+            .is_ok());
+        assert!(bucket
+            .try_acquire(start + Duration::from_millis(1500))
+            .is_err());
+        // Idle time refills no more than the burst
+        let later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(bucket.try_acquire(later).is_ok());
+        }
+        assert!(bucket.try_acquire(later).is_err());
+    }
+
+    #[test]
+    fn platform_defaults_apply_unless_configured() {
+        let mut configured = BTreeMap::new();
+        assert_eq!(
+            ChannelRateLimitConfig::for_channel(&configured, "slack"),
+            Some(limit(60, 3))
+        );
+        assert_eq!(
+            ChannelRateLimitConfig::for_channel(&configured, "irc"),
+            None
+        );
+
+        configured.insert("slack".to_string(), limit(0, 1));
+        configured.insert("irc".to_string(), limit(30, 2));
+        assert_eq!(
+            ChannelRateLimitConfig::for_channel(&configured, "slack"),
+            None
+        );
+        assert_eq!(
+            ChannelRateLimitConfig::for_channel(&configured, "irc"),
+            Some(limit(30, 2))
+        );
+    }
+
+    struct RecordingChannel {
+        sent: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "test-throttle"
+        }
+
+        async fn send(&self, message: &str, _recipient: &str) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_over_the_limit_wait_in_order() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut configured = BTreeMap::new();
+        // One token every 20ms after a burst of two
+        configured.insert("test-throttle".to_string(), limit(3000, 2));
+        let channel = ThrottledChannel::wrap(
+            Arc::new(RecordingChannel {
+                sent: Arc::clone(&sent),
+            }),
+            &configured,
+        );
+        assert_eq!(channel.name(), "test-throttle");
+
+        let started = Instant::now();
+        let results = tokio::join!(
+            channel.send("0", "r"),
+            channel.send("1", "r"),
+            channel.send("2", "r"),
+            channel.send("3", "r"),
+        );
+        for result in [results.0, results.1, results.2, results.3] {
+            result.unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(35));
+        assert_eq!(*sent.lock().unwrap(), ["0", "1", "2", "3"]);
+    }
+}
@@ -1,13 +1,131 @@
+use crate::providers::traits::ImageInput;
 use async_trait::async_trait;
 
 /// A message received from or sent to a channel
 #[derive(Debug, Clone)]
 pub struct ChannelMessage {
+    /// Platform message ID where the channel has one (edits and deletions
+    /// refer to it), otherwise a random UUID
     pub id: String,
     pub sender: String,
     pub content: String,
     pub channel: String,
     pub timestamp: u64,
+    /// Images attached to the message, passed to vision-capable models
+    pub images: Vec<ImageInput>,
+    /// Earlier conversation the reply should take into account (e.g. the
+    /// Slack thread being answered); sent with this turn only, not kept in
+    /// the sender's history
+    pub context: Option<String>,
+    /// Platform ID of the earlier message this one answers (a Slack
+    /// thread's `ts`, the Telegram or Matrix message replied to), which
+    /// [`Channel::fetch_context`] turns into `context`
+    pub reply_to: Option<String>,
+    /// Platform user who wrote the message, when `sender` is the
+    /// conversation rather than the person (a Slack channel, an IRC room);
+    /// checked by `[channels_config.access]` alongside `sender`
+    pub author: Option<String>,
+    /// Other files attached to the message (documents, text, archives…)
+    pub attachments: Vec<Attachment>,
+    /// Set when the message is an emoji reaction rather than something the
+    /// user wrote; `content` then holds any command the reaction resolved to
+    pub reaction: Option<ReactionAction>,
+    /// Set when the message reports an edit or deletion of the earlier
+    /// message with the same `id`; edits carry the new text in `content`
+    pub update: Option<MessageUpdate>,
+    /// Whether the message was meant for the bot, for `respond_to` filtering
+    pub audience: Audience,
+}
+
+/// Who a message was written for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Audience {
+    /// A direct message, or a channel that only knows direct messages
+    #[default]
+    Direct,
+    /// A group message that mentions or replies to the bot, with the
+    /// mention removed from `content`
+    Mention,
+    /// Any other group message
+    Group,
+}
+
+/// A change the user made to a message they sent earlier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageUpdate {
+    Edited,
+    Deleted,
+}
+
+/// What an emoji reaction on a channel message asks the agent to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReactionAction {
+    /// ✅ — approve the pending action the message asked about
+    Approve,
+    /// 🔁 — answer the conversation's last message again
+    Regenerate,
+    /// 🗑️ — forget what was saved to memory from the conversation
+    Forget,
+}
+
+impl ReactionAction {
+    /// The action for a reaction, given as the emoji itself (Discord,
+    /// Telegram) or its Slack short name. `None` for other reactions.
+    pub fn from_emoji(emoji: &str) -> Option<Self> {
+        match emoji.trim_matches(':').trim_end_matches('\u{fe0f}') {
+            "✅" | "✔" | "white_check_mark" | "heavy_check_mark" => Some(Self::Approve),
+            "🔁" | "🔄" | "repeat" | "arrows_counterclockwise" => Some(Self::Regenerate),
+            "🗑" | "wastebasket" => Some(Self::Forget),
+            _ => None,
+        }
+    }
+}
+
+/// A file received from or sent through a channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// File name, e.g. `report.csv`
+    pub name: String,
+    pub mime_type: String,
+    /// Where the platform serves the file; `None` for files the agent made
+    pub url: Option<String>,
+    pub data: Vec<u8>,
+}
+
+impl Attachment {
+    pub fn new(name: impl Into<String>, mime_type: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            mime_type: mime_type.into(),
+            url: None,
+            data,
+        }
+    }
+
+    /// Whether the file is text a model can read (`text/*`, JSON, XML, YAML…).
+    pub fn is_text(&self) -> bool {
+        let mime = self.mime_type.to_ascii_lowercase();
+        mime.starts_with("text/")
+            || [
+                "json",
+                "xml",
+                "yaml",
+                "toml",
+                "csv",
+                "markdown",
+                "javascript",
+            ]
+            .iter()
+            .any(|kind| mime.contains(kind))
+    }
+
+    /// The file contents, when it is UTF-8 text.
+    pub fn text(&self) -> Option<&str> {
+        if !self.is_text() {
+            return None;
+        }
+        std::str::from_utf8(&self.data).ok()
+    }
 }
 
 /// Core channel trait — implement for any messaging platform
@@ -19,9 +137,53 @@ pub trait Channel: Send + Sync {
     /// Send a message through this channel
     async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()>;
 
+    /// Send a structured layout (Slack Block Kit `blocks`). Channels without
+    /// rich layouts send `fallback` as plain text.
+    async fn send_blocks(
+        &self,
+        blocks: &[serde_json::Value],
+        fallback: &str,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        let _ = blocks;
+        self.send(fallback, recipient).await
+    }
+
+    /// Send a file with `caption` as its message. Channels that cannot
+    /// upload files send text files inline and otherwise only name the file.
+    async fn send_attachment(
+        &self,
+        attachment: &Attachment,
+        caption: &str,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        let body = match attachment.text() {
+            Some(text) => format!("📎 {}\n```\n{}\n```", attachment.name, text.trim_end()),
+            None => format!(
+                "📎 {} ({} bytes) — this channel cannot send files",
+                attachment.name,
+                attachment.data.len()
+            ),
+        };
+        let message = if caption.trim().is_empty() {
+            body
+        } else {
+            format!("{caption}\n\n{body}")
+        };
+        self.send(&message, recipient).await
+    }
+
     /// Start listening for incoming messages (long-running)
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()>;
 
+    /// What `msg` replies to — the earlier Slack thread, the Telegram or
+    /// Matrix reply chain — as `author: text` lines for its `context`.
+    /// `None` when the channel has nothing to add.
+    async fn fetch_context(&self, msg: &ChannelMessage) -> anyhow::Result<Option<String>> {
+        let _ = msg;
+        Ok(None)
+    }
+
     /// Check if channel is healthy
     async fn health_check(&self) -> bool {
         true
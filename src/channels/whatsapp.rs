@@ -1,4 +1,4 @@
-use super::traits::{Channel, ChannelMessage};
+use super::traits::{Audience, Channel, ChannelMessage};
 use async_trait::async_trait;
 use uuid::Uuid;
 
@@ -28,7 +28,7 @@ impl WhatsAppChannel {
             phone_number_id,
             verify_token,
             allowed_numbers,
-            client: reqwest::Client::new(),
+            client: crate::proxy::client("whatsapp"),
         }
     }
 
@@ -122,6 +122,14 @@ impl WhatsAppChannel {
                         sender: normalized_from,
                         content,
                         channel: "whatsapp".to_string(),
+                        images: Vec::new(),
+                        context: None,
+                        reply_to: None,
+                        author: None,
+                        attachments: Vec::new(),
+                        reaction: None,
+                        update: None,
+                        audience: Audience::Direct,
                         timestamp,
                     });
                 }
@@ -0,0 +1,906 @@
+use crate::channels::irc::addressed_text;
+use crate::channels::traits::{Audience, Channel, ChannelMessage};
+use anyhow::{anyhow, bail, Context};
+use async_trait::async_trait;
+use base64::Engine;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, Mutex};
+use uuid::Uuid;
+
+// Use tokio_rustls's re-export of rustls types
+use tokio_rustls::rustls;
+
+const NS_TLS: &str = "urn:ietf:params:xml:ns:xmpp-tls";
+const NS_SASL: &str = "urn:ietf:params:xml:ns:xmpp-sasl";
+const NS_BIND: &str = "urn:ietf:params:xml:ns:xmpp-bind";
+const NS_SESSION: &str = "urn:ietf:params:xml:ns:xmpp-session";
+const NS_STANZAS: &str = "urn:ietf:params:xml:ns:xmpp-stanzas";
+const NS_MUC: &str = "http://jabber.org/protocol/muc";
+const NS_PING: &str = "urn:xmpp:ping";
+
+/// Resource bound for the bot's session.
+const RESOURCE: &str = "zeroclaw";
+
+/// Whitespace keepalive interval, so idle NAT and firewall state survives.
+const KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// Largest stanza accepted from the server.
+const MAX_STANZA_BYTES: usize = 1024 * 1024;
+
+type TlsStream = tokio_rustls::client::TlsStream<tokio::net::TcpStream>;
+type WriteHalf = tokio::io::WriteHalf<TlsStream>;
+
+/// XMPP (Jabber) channel.
+///
+/// Logs in over STARTTLS with SASL PLAIN, answers direct chats and joins
+/// multi-user chat (MUC) rooms. Replies go back to the sender's bare JID or
+/// to the room.
+pub struct XmppChannel {
+    jid: String,
+    password: String,
+    server: Option<String>,
+    port: u16,
+    rooms: Vec<String>,
+    nickname: String,
+    allowed_users: Vec<String>,
+    mention_only: bool,
+    /// Shared write half of the TLS stream for sending messages.
+    writer: Arc<Mutex<Option<WriteHalf>>>,
+}
+
+impl XmppChannel {
+    /// `jid` is the bot account (`bot@example.com`). `allowed_users` holds
+    /// bare JIDs allowed to chat with the bot, room nicknames allowed to
+    /// address it in rooms, or `"*"`.
+    pub fn new(jid: String, password: String, allowed_users: Vec<String>) -> Self {
+        let mut jid = jid;
+        if let Some(slash) = jid.find('/') {
+            jid.truncate(slash);
+        }
+        let nickname = jid.split('@').next().unwrap_or("zeroclaw").to_string();
+        Self {
+            jid,
+            password,
+            server: None,
+            port: 5222,
+            rooms: Vec::new(),
+            nickname,
+            allowed_users,
+            mention_only: true,
+            writer: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Connect to `server:port` instead of the JID's domain on port 5222.
+    #[must_use]
+    pub fn with_server(mut self, server: Option<String>, port: u16) -> Self {
+        self.server = server.filter(|s| !s.trim().is_empty());
+        self.port = port;
+        self
+    }
+
+    /// Join these MUC rooms (`room@conference.example.com`), as `nickname`
+    /// when given, else the JID's local part.
+    #[must_use]
+    pub fn with_rooms(mut self, rooms: Vec<String>, nickname: Option<String>) -> Self {
+        self.rooms = rooms;
+        if let Some(nickname) = nickname.filter(|n| !n.trim().is_empty()) {
+            self.nickname = nickname;
+        }
+        self
+    }
+
+    /// In rooms, only answer messages addressed to the bot.
+    #[must_use]
+    pub fn with_mention_only(mut self, mention_only: bool) -> Self {
+        self.mention_only = mention_only;
+        self
+    }
+
+    fn domain(&self) -> &str {
+        self.jid.split_once('@').map_or("", |(_, domain)| domain)
+    }
+
+    fn is_user_allowed(&self, user: &str) -> bool {
+        self.allowed_users
+            .iter()
+            .any(|u| u == "*" || u.eq_ignore_ascii_case(user))
+    }
+
+    fn is_room(&self, jid: &str) -> bool {
+        self.rooms.iter().any(|r| r.eq_ignore_ascii_case(jid))
+    }
+
+    /// Connect, secure the stream, authenticate and bind a resource.
+    /// Returns the TLS stream with its reader, ready for presence.
+    async fn connect(&self) -> anyhow::Result<(TlsStream, StanzaReader)> {
+        let domain = self.domain().to_string();
+        if domain.is_empty() {
+            bail!("XMPP jid must look like user@domain, got {}", self.jid);
+        }
+        let host = self.server.clone().unwrap_or_else(|| domain.clone());
+        let mut tcp = tokio::net::TcpStream::connect((host.as_str(), self.port))
+            .await
+            .with_context(|| format!("Failed to connect to XMPP server {host}:{}", self.port))?;
+
+        let mut reader = StanzaReader::default();
+        let features = open_stream(&mut tcp, &mut reader, &domain).await?;
+        if features.child("starttls").is_none() {
+            bail!("XMPP server {host} does not offer STARTTLS");
+        }
+        write_stanza(&mut tcp, &format!("<starttls xmlns='{NS_TLS}'/>")).await?;
+        if reader.next(&mut tcp).await?.name != "proceed" {
+            bail!("XMPP server {host} refused STARTTLS");
+        }
+
+        let root_store: rustls::RootCertStore =
+            webpki_roots::TLS_SERVER_ROOTS.iter().cloned().collect();
+        let tls_config = rustls::ClientConfig::builder()
+            .with_root_certificates(root_store)
+            .with_no_client_auth();
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(tls_config));
+        let server_name = rustls::pki_types::ServerName::try_from(domain.clone())?;
+        let mut tls = connector.connect(server_name, tcp).await?;
+
+        let mut reader = StanzaReader::default();
+        let features = open_stream(&mut tls, &mut reader, &domain).await?;
+        let offers_plain = features.child("mechanisms").is_some_and(|m| {
+            m.children
+                .iter()
+                .any(|c| c.name == "mechanism" && c.text.trim() == "PLAIN")
+        });
+        if !offers_plain {
+            bail!("XMPP server {host} does not offer SASL PLAIN");
+        }
+        let local = self.jid.split('@').next().unwrap_or_default();
+        let credentials = base64::engine::general_purpose::STANDARD
+            .encode(format!("\0{local}\0{}", self.password));
+        write_stanza(
+            &mut tls,
+            &format!("<auth xmlns='{NS_SASL}' mechanism='PLAIN'>{credentials}</auth>"),
+        )
+        .await?;
+        let outcome = reader.next(&mut tls).await?;
+        if outcome.name != "success" {
+            let reason = outcome
+                .children
+                .first()
+                .map_or("unknown", |c| c.name.as_str());
+            bail!("XMPP authentication failed for {}: {reason}", self.jid);
+        }
+
+        let mut reader = StanzaReader::default();
+        let features = open_stream(&mut tls, &mut reader, &domain).await?;
+        write_stanza(
+            &mut tls,
+            &format!(
+                "<iq type='set' id='bind'><bind xmlns='{NS_BIND}'>\
+                 <resource>{RESOURCE}</resource></bind></iq>"
+            ),
+        )
+        .await?;
+        let bound = reader.next_iq(&mut tls, "bind").await?;
+        if bound.attr("type") != Some("result") {
+            bail!("XMPP resource binding failed");
+        }
+        // RFC 3921 session establishment, still required by some old servers
+        let session_required = features
+            .child("session")
+            .is_some_and(|s| s.child("optional").is_none());
+        if session_required {
+            write_stanza(
+                &mut tls,
+                &format!("<iq type='set' id='session'><session xmlns='{NS_SESSION}'/></iq>"),
+            )
+            .await?;
+            reader.next_iq(&mut tls, "session").await?;
+        }
+
+        Ok((tls, reader))
+    }
+
+    async fn write_raw(&self, data: &str) -> anyhow::Result<()> {
+        let mut guard = self.writer.lock().await;
+        let writer = guard
+            .as_mut()
+            .ok_or_else(|| anyhow!("XMPP not connected"))?;
+        write_stanza(writer, data).await
+    }
+
+    /// Turn a `<message>` stanza into a [`ChannelMessage`] addressed to the
+    /// sender's bare JID or the room. Returns `None` for messages without a
+    /// body, room history, the bot's own room messages, unauthorized senders
+    /// and, in mention-only mode, room messages not addressed to the bot.
+    fn to_channel_message(&self, stanza: &Element) -> Option<ChannelMessage> {
+        let body = stanza.child("body")?.text.trim();
+        if body.is_empty() || stanza.child("delay").is_some() {
+            return None;
+        }
+        let (bare, resource) = split_jid(stanza.attr("from")?);
+
+        let (content, author, audience) = match stanza.attr("type").unwrap_or("normal") {
+            "groupchat" => {
+                let nick = resource?;
+                if !self.is_room(bare) || nick.eq_ignore_ascii_case(&self.nickname) {
+                    return None;
+                }
+                if !self.is_user_allowed(nick) {
+                    tracing::warn!("XMPP: ignoring room message from unauthorized nick: {nick}");
+                    return None;
+                }
+                let addressed = addressed_text(body, &self.nickname).filter(|t| !t.is_empty());
+                let (text, audience) = match addressed {
+                    Some(text) if self.mention_only => (text, Audience::Mention),
+                    Some(_) => (body, Audience::Mention),
+                    None if self.mention_only => return None,
+                    None => (body, Audience::Group),
+                };
+                (format!("<{nick}> {text}"), Some(nick.to_string()), audience)
+            }
+            "chat" | "normal" => {
+                if !self.is_user_allowed(bare) {
+                    tracing::warn!("XMPP: ignoring message from unauthorized user: {bare}");
+                    return None;
+                }
+                (body.to_string(), None, Audience::Direct)
+            }
+            _ => return None,
+        };
+
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender: bare.to_string(),
+            content,
+            channel: "xmpp".to_string(),
+            images: Vec::new(),
+            context: None,
+            reply_to: None,
+            author,
+            attachments: Vec::new(),
+            reaction: None,
+            update: None,
+            audience,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+}
+
+/// `(bare, resource)` parts of a JID.
+fn split_jid(jid: &str) -> (&str, Option<&str>) {
+    match jid.split_once('/') {
+        Some((bare, resource)) => (bare, Some(resource)),
+        None => (jid, None),
+    }
+}
+
+/// Escape text for XML character data or a quoted attribute, dropping
+/// control characters XML 1.0 cannot carry.
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape_xml(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        if let Some(c) = decoded {
+            out.push(c);
+            rest = &rest[semi + 1..];
+        } else {
+            out.push('&');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Reply for an `<iq>` request: pong for XMPP pings, `service-unavailable`
+/// for anything else. Responses need no reply.
+fn iq_reply(iq: &Element) -> Option<String> {
+    let kind = iq.attr("type")?;
+    if kind != "get" && kind != "set" {
+        return None;
+    }
+    let id = escape_xml(iq.attr("id").unwrap_or_default());
+    let to = iq
+        .attr("from")
+        .map(|from| format!(" to='{}'", escape_xml(from)))
+        .unwrap_or_default();
+    let is_ping = iq
+        .children
+        .iter()
+        .any(|c| c.name == "ping" && c.attr("xmlns") == Some(NS_PING));
+    Some(if is_ping {
+        format!("<iq type='result' id='{id}'{to}/>")
+    } else {
+        format!(
+            "<iq type='error' id='{id}'{to}><error type='cancel'>\
+             <service-unavailable xmlns='{NS_STANZAS}'/></error></iq>"
+        )
+    })
+}
+
+async fn write_stanza<W: AsyncWrite + Unpin>(writer: &mut W, data: &str) -> anyhow::Result<()> {
+    writer.write_all(data.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Open a client stream to `domain` and return the server's features.
+async fn open_stream<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    reader: &mut StanzaReader,
+    domain: &str,
+) -> anyhow::Result<Element> {
+    write_stanza(
+        stream,
+        &format!(
+            "<?xml version='1.0'?><stream:stream to='{}' version='1.0' \
+             xmlns='jabber:client' xmlns:stream='http://etherx.jabber.org/streams'>",
+            escape_xml(domain)
+        ),
+    )
+    .await?;
+    let features = reader.next(stream).await?;
+    if features.name != "features" {
+        bail!(
+            "XMPP server sent <{}> instead of stream features",
+            features.name
+        );
+    }
+    Ok(features)
+}
+
+/// A parsed XML element — just the subset of XML that XMPP stanzas use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Element {
+    /// Local name, without a namespace prefix
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+}
+
+/// Parse one complete element.
+fn parse_element(xml: &str) -> Option<Element> {
+    let mut parser = Parser { xml, pos: 0 };
+    parser.element()
+}
+
+struct Parser<'a> {
+    xml: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.xml[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.xml.len() - trimmed.len();
+    }
+
+    fn name(&mut self) -> &str {
+        let rest = &self.xml[self.pos..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '='))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn element(&mut self) -> Option<Element> {
+        self.pos += self.rest().find('<')? + 1;
+        let qualified = self.name();
+        let mut element = Element {
+            name: qualified
+                .rsplit_once(':')
+                .map_or(qualified, |(_, local)| local)
+                .to_string(),
+            ..Element::default()
+        };
+
+        loop {
+            self.skip_whitespace();
+            if let Some(rest) = self.rest().strip_prefix("/>") {
+                self.pos = self.xml.len() - rest.len();
+                return Some(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let key = self.name().to_string();
+            if key.is_empty() {
+                return None;
+            }
+            self.skip_whitespace();
+            self.pos += usize::from(self.rest().starts_with('='));
+            self.skip_whitespace();
+            let quote = self
+                .rest()
+                .chars()
+                .next()
+                .filter(|q| matches!(q, '"' | '\''))?;
+            let len = self.rest()[1..].find(quote)?;
+            let value = unescape_xml(&self.rest()[1..=len]);
+            self.pos += len + 2;
+            element.attrs.push((key, value));
+        }
+
+        loop {
+            let lt = self.rest().find('<')?;
+            element.text.push_str(&unescape_xml(&self.rest()[..lt]));
+            self.pos += lt;
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.pos += rest.find('>')? + 1;
+                return Some(element);
+            } else if rest.starts_with("<!--") {
+                self.pos += rest.find("-->")? + 3;
+            } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let len = cdata.find("]]>")?;
+                element.text.push_str(&cdata[..len]);
+                self.pos += "<![CDATA[".len() + len + 3;
+            } else if rest.starts_with("<?") {
+                self.pos += rest.find("?>")? + 2;
+            } else {
+                element.children.push(self.element()?);
+            }
+        }
+    }
+}
+
+/// What the start of the receive buffer holds.
+#[derive(Debug, PartialEq, Eq)]
+enum Frame {
+    /// Not enough bytes yet
+    Incomplete,
+    /// Whitespace, the XML declaration or the `<stream:stream>` header:
+    /// drop this many bytes
+    Skip(usize),
+    /// A complete top-level stanza in this many bytes
+    Stanza(usize),
+    /// `</stream:stream>`
+    Closed,
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Index of the `>` closing the tag that starts at `start`, skipping quoted
+/// attribute values.
+fn tag_end(buf: &[u8], start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, &b) in buf.iter().enumerate().skip(start) {
+        match quote {
+            Some(q) if b == q => quote = None,
+            None if b == b'"' || b == b'\'' => quote = Some(b),
+            None if b == b'>' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn frame(buf: &[u8]) -> Frame {
+    let Some(start) = buf.iter().position(|&b| b == b'<') else {
+        // Whitespace keepalives and stray text between stanzas
+        return if buf.is_empty() {
+            Frame::Incomplete
+        } else {
+            Frame::Skip(buf.len())
+        };
+    };
+    if start > 0 {
+        return Frame::Skip(start);
+    }
+    if buf.starts_with(b"<?") {
+        return find(buf, b"?>").map_or(Frame::Incomplete, |i| Frame::Skip(i + 2));
+    }
+    if buf.starts_with(b"</") {
+        return Frame::Closed;
+    }
+    if buf.starts_with(b"<stream:stream") {
+        return tag_end(buf, 0).map_or(Frame::Incomplete, |i| Frame::Skip(i + 1));
+    }
+
+    let mut depth = 0usize;
+    let mut i = 0;
+    loop {
+        let Some(lt) = buf[i..].iter().position(|&b| b == b'<') else {
+            return Frame::Incomplete;
+        };
+        i += lt;
+        let rest = &buf[i..];
+        let skip_to = |end: &[u8]| find(rest, end).map(|e| i + e + end.len());
+        let skipped = if rest.starts_with(b"<!--") {
+            Some(skip_to(b"-->"))
+        } else if rest.starts_with(b"<![CDATA[") {
+            Some(skip_to(b"]]>"))
+        } else if rest.starts_with(b"<?") {
+            Some(skip_to(b"?>"))
+        } else {
+            None
+        };
+        if let Some(next) = skipped {
+            match next {
+                Some(next) => i = next,
+                None => return Frame::Incomplete,
+            }
+            continue;
+        }
+
+        let Some(end) = tag_end(buf, i) else {
+            return Frame::Incomplete;
+        };
+        if rest.starts_with(b"</") {
+            depth = depth.saturating_sub(1);
+        } else if buf[end - 1] != b'/' {
+            depth += 1;
+        }
+        i = end + 1;
+        if depth == 0 {
+            return Frame::Stanza(i);
+        }
+    }
+}
+
+/// Splits the server's XML stream into top-level stanzas.
+#[derive(Default)]
+struct StanzaReader {
+    buf: Vec<u8>,
+}
+
+impl StanzaReader {
+    /// The next stanza. Cancel-safe: bytes already read stay buffered.
+    async fn next<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> anyhow::Result<Element> {
+        loop {
+            match frame(&self.buf) {
+                Frame::Skip(n) => {
+                    self.buf.drain(..n);
+                    continue;
+                }
+                Frame::Stanza(n) => {
+                    let raw: Vec<u8> = self.buf.drain(..n).collect();
+                    let stanza = parse_element(&String::from_utf8_lossy(&raw))
+                        .ok_or_else(|| anyhow!("Malformed XMPP stanza"))?;
+                    if stanza.name == "error" {
+                        let condition = stanza
+                            .children
+                            .first()
+                            .map_or("unknown", |c| c.name.as_str());
+                        bail!("XMPP stream error: {condition}");
+                    }
+                    return Ok(stanza);
+                }
+                Frame::Closed => bail!("XMPP server closed the stream"),
+                Frame::Incomplete => {}
+            }
+            if self.buf.len() > MAX_STANZA_BYTES {
+                bail!("XMPP stanza exceeds {MAX_STANZA_BYTES} bytes");
+            }
+            let mut chunk = [0u8; 4096];
+            let n = reader.read(&mut chunk).await?;
+            if n == 0 {
+                bail!("XMPP connection closed");
+            }
+            self.buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// Skip stanzas until the `<iq>` answering request `id`.
+    async fn next_iq<R: AsyncRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        id: &str,
+    ) -> anyhow::Result<Element> {
+        loop {
+            let stanza = self.next(reader).await?;
+            if stanza.name == "iq" && stanza.attr("id") == Some(id) {
+                return Ok(stanza);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for XmppChannel {
+    fn name(&self) -> &str {
+        "xmpp"
+    }
+
+    async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+        let kind = if self.is_room(recipient) {
+            "groupchat"
+        } else {
+            "chat"
+        };
+        self.write_raw(&format!(
+            "<message to='{}' type='{kind}' id='{}'><body>{}</body></message>",
+            escape_xml(recipient),
+            Uuid::new_v4(),
+            escape_xml(message)
+        ))
+        .await
+    }
+
+    async fn listen(&self, tx: mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        tracing::info!("XMPP channel connecting as {}...", self.jid);
+        let (tls, mut reader) = self.connect().await?;
+        let (mut read_half, write_half) = tokio::io::split(tls);
+        *self.writer.lock().await = Some(write_half);
+
+        self.write_raw("<presence/>").await?;
+        for room in &self.rooms {
+            self.write_raw(&format!(
+                "<presence to='{}/{}'><x xmlns='{NS_MUC}'><history maxstanzas='0'/></x></presence>",
+                escape_xml(room),
+                escape_xml(&self.nickname)
+            ))
+            .await?;
+        }
+        tracing::info!(
+            "XMPP connected as {}/{RESOURCE}, joined {} room(s)",
+            self.jid,
+            self.rooms.len()
+        );
+
+        let mut keepalive = tokio::time::interval(KEEPALIVE);
+        keepalive.tick().await;
+        let result = loop {
+            tokio::select! {
+                _ = keepalive.tick() => {
+                    if let Err(e) = self.write_raw(" ").await {
+                        break Err(e);
+                    }
+                }
+                stanza = reader.next(&mut read_half) => {
+                    let stanza = match stanza {
+                        Ok(stanza) => stanza,
+                        Err(e) => break Err(e),
+                    };
+                    match stanza.name.as_str() {
+                        "message" => {
+                            if let Some(msg) = self.to_channel_message(&stanza) {
+                                if tx.send(msg).await.is_err() {
+                                    break Ok(());
+                                }
+                            }
+                        }
+                        "iq" => {
+                            if let Some(reply) = iq_reply(&stanza) {
+                                if let Err(e) = self.write_raw(&reply).await {
+                                    break Err(e);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        };
+        *self.writer.lock().await = None;
+        result
+    }
+
+    async fn health_check(&self) -> bool {
+        let host = self
+            .server
+            .clone()
+            .unwrap_or_else(|| self.domain().to_string());
+        tokio::net::TcpStream::connect((host.as_str(), self.port))
+            .await
+            .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel() -> XmppChannel {
+        XmppChannel::new(
+            "claw@example.com/laptop".into(),
+            "secret".into(),
+            vec!["alice@example.com".into(), "bob".into()],
+        )
+        .with_rooms(vec!["ops@conference.example.com".into()], None)
+    }
+
+    fn stanza(xml: &str) -> Element {
+        parse_element(xml).unwrap()
+    }
+
+    #[test]
+    fn new_strips_resource_and_defaults_nickname() {
+        let ch = channel();
+        assert_eq!(ch.jid, "claw@example.com");
+        assert_eq!(ch.domain(), "example.com");
+        assert_eq!(ch.nickname, "claw");
+        let ch = ch.with_rooms(vec![], Some("Claw".into()));
+        assert_eq!(ch.nickname, "Claw");
+    }
+
+    #[test]
+    fn parses_nested_stanzas_with_entities() {
+        let el = stanza(
+            "<message from='alice@example.com/phone' type=\"chat\">\
+             <body>a &lt; b &amp;&amp; c &#x1F980;</body><x:delay xmlns:x='urn:xmpp:delay'/></message>",
+        );
+        assert_eq!(el.name, "message");
+        assert_eq!(el.attr("type"), Some("chat"));
+        assert_eq!(el.child("body").unwrap().text, "a < b && c 🦀");
+        assert!(el.child("delay").is_some());
+        assert_eq!(unescape_xml("R&D &bogus; &"), "R&D &bogus; &");
+        assert_eq!(
+            escape_xml("<a href='x'>\u{7}"),
+            "&lt;a href=&apos;x&apos;&gt;"
+        );
+    }
+
+    #[test]
+    fn frames_split_the_stream_into_stanzas() {
+        let header = b"<?xml version='1.0'?><stream:stream from='example.com' id='s1'>";
+        assert_eq!(frame(header), Frame::Skip(21));
+        assert_eq!(frame(&header[21..]), Frame::Skip(header.len() - 21));
+        assert_eq!(frame(b"  \n"), Frame::Skip(3));
+        assert_eq!(frame(b"<presence/><message>"), Frame::Stanza(11));
+        assert_eq!(frame(b"<message><body a='>'>hi</body>"), Frame::Incomplete);
+        assert_eq!(
+            frame(b"<message><body>x</body><!-- <a> --></message> "),
+            Frame::Stanza(45)
+        );
+        assert_eq!(frame(b"</stream:stream>"), Frame::Closed);
+    }
+
+    #[tokio::test]
+    async fn reader_yields_stanzas_across_chunks() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            for part in [
+                "<stream:stream xmlns='jabber:client'><stream:features><starttls xmlns='",
+                "urn:ietf:params:xml:ns:xmpp-tls'/></stream:features> ",
+                "<stream:error><conflict xmlns='urn:ietf:params:xml:ns:xmpp-streams'/></stream:error>",
+            ] {
+                server.write_all(part.as_bytes()).await.unwrap();
+            }
+        });
+        let mut reader = StanzaReader::default();
+        let features = reader.next(&mut client).await.unwrap();
+        assert_eq!(features.name, "features");
+        assert!(features.child("starttls").is_some());
+        let err = reader.next(&mut client).await.unwrap_err();
+        assert!(err.to_string().contains("conflict"));
+    }
+
+    #[test]
+    fn direct_messages_reply_to_bare_jid() {
+        let ch = channel();
+        let msg = ch
+            .to_channel_message(&stanza(
+                "<message from='Alice@example.com/phone' type='chat'><body> hi </body></message>",
+            ))
+            .unwrap();
+        assert_eq!(msg.sender, "Alice@example.com");
+        assert_eq!(msg.content, "hi");
+        assert_eq!(msg.channel, "xmpp");
+
+        for ignored in [
+            "<message from='eve@example.com/x' type='chat'><body>hi</body></message>",
+            "<message from='alice@example.com/x' type='chat'><composing/></message>",
+            "<message from='alice@example.com/x' type='error'><body>hi</body></message>",
+        ] {
+            assert!(ch.to_channel_message(&stanza(ignored)).is_none());
+        }
+    }
+
+    #[test]
+    fn room_messages_need_addressing_and_skip_history() {
+        let ch = channel();
+        let msg = ch
+            .to_channel_message(&stanza(
+                "<message from='ops@conference.example.com/bob' type='groupchat'>\
+                 <body>claw: is prod up?</body></message>",
+            ))
+            .unwrap();
+        assert_eq!(msg.sender, "ops@conference.example.com");
+        assert_eq!(msg.content, "<bob> is prod up?");
+        assert_eq!(msg.audience, Audience::Mention);
+
+        for ignored in [
+            "<message from='ops@conference.example.com/bob' type='groupchat'><body>lunch?</body></message>",
+            "<message from='ops@conference.example.com/claw' type='groupchat'><body>claw: echo</body></message>",
+            "<message from='ops@conference.example.com/eve' type='groupchat'><body>claw: hi</body></message>",
+            "<message from='other@conference.example.com/bob' type='groupchat'><body>claw: hi</body></message>",
+            "<message from='ops@conference.example.com/bob' type='groupchat'><body>claw: old</body>\
+             <delay xmlns='urn:xmpp:delay' stamp='2024-01-01T00:00:00Z'/></message>",
+        ] {
+            assert!(ch.to_channel_message(&stanza(ignored)).is_none(), "{ignored}");
+        }
+
+        let chatty = channel().with_mention_only(false);
+        let msg = chatty
+            .to_channel_message(&stanza(
+                "<message from='ops@conference.example.com/bob' type='groupchat'><body>lunch?</body></message>",
+            ))
+            .unwrap();
+        assert_eq!(msg.audience, Audience::Group);
+    }
+
+    #[test]
+    fn answers_pings_and_rejects_other_requests() {
+        let ping =
+            stanza("<iq type='get' id='p1' from='example.com'><ping xmlns='urn:xmpp:ping'/></iq>");
+        assert_eq!(
+            iq_reply(&ping).unwrap(),
+            "<iq type='result' id='p1' to='example.com'/>"
+        );
+        let version = stanza("<iq type='get' id='v1'><query xmlns='jabber:iq:version'/></iq>");
+        assert!(iq_reply(&version).unwrap().contains("service-unavailable"));
+        assert!(iq_reply(&stanza("<iq type='result' id='r1'/>")).is_none());
+    }
+
+    #[tokio::test]
+    async fn send_without_connection_fails() {
+        let err = channel().send("hi", "alice@example.com").await.unwrap_err();
+        assert!(err.to_string().contains("not connected"));
+    }
+}
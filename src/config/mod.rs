@@ -1,8 +1,11 @@
 pub mod schema;
 
 pub use schema::{
-    AutonomyConfig, BrowserConfig, ChannelsConfig, ComposioConfig, Config, DiscordConfig,
-    DockerRuntimeConfig, GatewayConfig, HeartbeatConfig, IMessageConfig, IdentityConfig,
-    MatrixConfig, MemoryConfig, ModelRouteConfig, ObservabilityConfig, ReliabilityConfig,
-    RuntimeConfig, SecretsConfig, SlackConfig, TelegramConfig, TunnelConfig, WebhookConfig,
+    AutonomyConfig, BrowserConfig, ChannelsConfig, ComposioConfig, Config, CostConfig,
+    DiscordConfig, DockerRuntimeConfig, FanoutConfig, GatewayAuditConfig, GatewayConfig,
+    GatewayCorsConfig, GenerationConfig, HeartbeatConfig, IMessageConfig, IdentityConfig,
+    MatrixConfig, MemoryBackupConfig, MemoryConfig, MemorySyncConfig, MiddlewareConfig,
+    ModelPriceConfig, ModelRouteConfig, ObservabilityConfig, ProvidersConfig, ProxyConfig,
+    ReliabilityConfig, ResponseCacheConfig, RoutingRuleConfig, RuntimeConfig, SecretsConfig,
+    SlackConfig, TelegramConfig, TunnelConfig, WebhookConfig,
 };
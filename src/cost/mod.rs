@@ -0,0 +1,386 @@
+//! Token usage and spend accounting.
+//!
+//! Every completion that reports [`TokenUsage`] is priced with
+//! `[cost.prices]` and added to per-day totals in `workspace/cost/usage.db`,
+//! keyed by provider, channel and model. `zeroclaw usage` and `GET /usage`
+//! read those totals back.
+
+use crate::config::{Config, CostConfig, ModelPriceConfig};
+use crate::providers::traits::{ChatReply, TokenUsage};
+use anyhow::{Context, Result};
+use chrono::{Days, NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Dimension `zeroclaw usage --by` and `GET /usage?by=` group totals on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupBy {
+    #[default]
+    Day,
+    Provider,
+    Channel,
+    Model,
+}
+
+impl GroupBy {
+    fn column(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Provider => "provider",
+            Self::Channel => "channel",
+            Self::Model => "model",
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "day" => Ok(Self::Day),
+            "provider" => Ok(Self::Provider),
+            "channel" => Ok(Self::Channel),
+            "model" => Ok(Self::Model),
+            other => {
+                anyhow::bail!(
+                    "Unknown grouping '{other}' (expected day, provider, channel or model)"
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.column())
+    }
+}
+
+/// Accumulated usage for one group (a day, provider, channel or model).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageTotals {
+    pub key: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// Sum of `rows`, keyed `"total"`.
+    pub fn sum(rows: &[UsageTotals]) -> Self {
+        rows.iter().fold(
+            Self {
+                key: "total".into(),
+                ..Self::default()
+            },
+            |mut acc, row| {
+                acc.requests += row.requests;
+                acc.prompt_tokens += row.prompt_tokens;
+                acc.completion_tokens += row.completion_tokens;
+                acc.cost_usd += row.cost_usd;
+                acc
+            },
+        )
+    }
+}
+
+/// Look up the price for `model`: exact match first, then the name after the
+/// last `/` (so `anthropic/claude-sonnet-4` can use a `claude-sonnet-4` entry).
+pub fn price_for<'a>(prices: &'a CostConfig, model: &str) -> Option<&'a ModelPriceConfig> {
+    prices.prices.get(model).or_else(|| {
+        model
+            .rsplit_once('/')
+            .and_then(|(_, bare)| prices.prices.get(bare))
+    })
+}
+
+/// USD cost of `usage` at `price`.
+#[allow(clippy::cast_precision_loss)]
+pub fn cost_usd(price: &ModelPriceConfig, usage: TokenUsage) -> f64 {
+    (usage.prompt_tokens as f64 * price.input_per_million
+        + usage.completion_tokens as f64 * price.output_per_million)
+        / 1_000_000.0
+}
+
+/// Records and reports usage for one workspace.
+#[derive(Debug, Clone)]
+pub struct CostTracker {
+    db_path: PathBuf,
+    config: CostConfig,
+}
+
+impl CostTracker {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            db_path: config.workspace_dir.join("cost").join("usage.db"),
+            config: config.cost.clone(),
+        }
+    }
+
+    /// Add one completion to today's totals and return its cost in USD
+    /// (0 for models without a configured price).
+    pub fn record(
+        &self,
+        provider: &str,
+        channel: &str,
+        model: &str,
+        usage: TokenUsage,
+    ) -> Result<f64> {
+        let cost = price_for(&self.config, model).map_or(0.0, |p| cost_usd(p, usage));
+        let day = Utc::now().date_naive().format("%Y-%m-%d").to_string();
+
+        self.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO usage_daily
+                    (day, provider, channel, model, requests, prompt_tokens, completion_tokens, cost_usd)
+                 VALUES (?1, ?2, ?3, ?4, 1, ?5, ?6, ?7)
+                 ON CONFLICT(day, provider, channel, model) DO UPDATE SET
+                    requests = requests + 1,
+                    prompt_tokens = prompt_tokens + excluded.prompt_tokens,
+                    completion_tokens = completion_tokens + excluded.completion_tokens,
+                    cost_usd = cost_usd + excluded.cost_usd",
+                params![
+                    day,
+                    provider,
+                    channel,
+                    model,
+                    usage.prompt_tokens,
+                    usage.completion_tokens,
+                    cost
+                ],
+            )
+            .context("Failed to record usage")?;
+            Ok(())
+        })?;
+
+        Ok(cost)
+    }
+
+    /// Record a reply's usage, if it reported any. A model chosen by the router
+    /// takes precedence over `model`. Failures are logged rather than returned
+    /// so accounting never breaks a conversation.
+    pub fn record_reply(&self, provider: &str, channel: &str, model: &str, reply: &ChatReply) {
+        if !self.config.enabled {
+            return;
+        }
+        let Some(usage) = reply.usage else {
+            return;
+        };
+        let model = reply.model.as_deref().unwrap_or(model);
+        if let Err(e) = self.record(provider, channel, model, usage) {
+            tracing::warn!("Usage accounting failed: {e:#}");
+        }
+    }
+
+    /// Totals for the last `days` days (today included), grouped by `group_by`.
+    /// Day groups are newest first; other groups are ordered by spend, then tokens.
+    pub fn summarize(&self, days: u32, group_by: GroupBy) -> Result<Vec<UsageTotals>> {
+        let today = Utc::now().date_naive();
+        let since = today
+            .checked_sub_days(Days::new(u64::from(days.max(1) - 1)))
+            .unwrap_or(NaiveDate::MIN);
+        let column = group_by.column();
+        let order = if group_by == GroupBy::Day {
+            "key DESC"
+        } else {
+            "cost DESC, prompt + completion DESC, key ASC"
+        };
+
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {column} AS key, SUM(requests), SUM(prompt_tokens) AS prompt,
+                        SUM(completion_tokens) AS completion, SUM(cost_usd) AS cost
+                 FROM usage_daily WHERE day >= ?1
+                 GROUP BY {column} ORDER BY {order}"
+            ))?;
+            let rows = stmt.query_map(params![since.format("%Y-%m-%d").to_string()], |row| {
+                Ok(UsageTotals {
+                    key: row.get(0)?,
+                    requests: row.get(1)?,
+                    prompt_tokens: row.get(2)?,
+                    completion_tokens: row.get(3)?,
+                    cost_usd: row.get(4)?,
+                })
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+                .context("Failed to read usage totals")
+        })
+    }
+
+    fn with_connection<T>(&self, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        if let Some(parent) = self.db_path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create cost directory: {}", parent.display())
+            })?;
+        }
+
+        let conn = Connection::open(&self.db_path)
+            .with_context(|| format!("Failed to open usage DB: {}", self.db_path.display()))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS usage_daily (
+                day               TEXT NOT NULL,
+                provider          TEXT NOT NULL,
+                channel           TEXT NOT NULL,
+                model             TEXT NOT NULL,
+                requests          INTEGER NOT NULL DEFAULT 0,
+                prompt_tokens     INTEGER NOT NULL DEFAULT 0,
+                completion_tokens INTEGER NOT NULL DEFAULT 0,
+                cost_usd          REAL NOT NULL DEFAULT 0,
+                PRIMARY KEY (day, provider, channel, model)
+            );",
+        )
+        .context("Failed to initialize usage schema")?;
+
+        f(&conn)
+    }
+}
+
+/// `zeroclaw usage` — print totals for the last `days` days.
+pub fn handle_command(config: &Config, days: u32, by: &str, json: bool) -> Result<()> {
+    let group_by: GroupBy = by.parse()?;
+    let rows = CostTracker::new(config).summarize(days, group_by)?;
+    let total = UsageTotals::sum(&rows);
+
+    if json {
+        let body = serde_json::json!({
+            "days": days,
+            "by": group_by.to_string(),
+            "rows": rows,
+            "total": total,
+        });
+        println!("{}", serde_json::to_string_pretty(&body)?);
+        return Ok(());
+    }
+
+    if rows.is_empty() {
+        println!("No usage recorded in the last {days} day(s).");
+        if !config.cost.enabled {
+            println!("Usage tracking is off; set [cost] enabled = true to record it.");
+        }
+        return Ok(());
+    }
+
+    println!("💰 Usage for the last {days} day(s), by {group_by}:");
+    println!(
+        "  {:<40} {:>8} {:>12} {:>12} {:>10}",
+        group_by, "requests", "prompt", "completion", "cost"
+    );
+    for row in rows.iter().chain(std::iter::once(&total)) {
+        println!(
+            "  {:<40} {:>8} {:>12} {:>12} {:>10}",
+            row.key,
+            row.requests,
+            row.prompt_tokens,
+            row.completion_tokens,
+            format!("${:.4}", row.cost_usd)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_config(tmp: &TempDir) -> Config {
+        let mut config = Config {
+            workspace_dir: tmp.path().join("workspace"),
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        };
+        config.cost.prices.insert(
+            "gpt-4o".into(),
+            ModelPriceConfig {
+                input_per_million: 2.5,
+                output_per_million: 10.0,
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn prices_exact_then_bare_model_name() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+
+        assert!(price_for(&config.cost, "gpt-4o").is_some());
+        assert!(price_for(&config.cost, "openai/gpt-4o").is_some());
+        assert!(price_for(&config.cost, "gpt-4o-mini").is_none());
+
+        let price = price_for(&config.cost, "gpt-4o").unwrap();
+        let cost = cost_usd(price, TokenUsage::new(1_000_000, 100_000));
+        assert!((cost - 3.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn record_accumulates_per_provider_channel_and_model() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = CostTracker::new(&test_config(&tmp));
+
+        tracker
+            .record("openai", "gateway", "gpt-4o", TokenUsage::new(1000, 500))
+            .unwrap();
+        tracker
+            .record("openai", "gateway", "gpt-4o", TokenUsage::new(3000, 500))
+            .unwrap();
+        let unpriced = tracker
+            .record("ollama", "telegram", "llama3", TokenUsage::new(200, 50))
+            .unwrap();
+        assert!(unpriced.abs() < f64::EPSILON);
+
+        let by_provider = tracker.summarize(1, GroupBy::Provider).unwrap();
+        assert_eq!(by_provider.len(), 2);
+        assert_eq!(by_provider[0].key, "openai");
+        assert_eq!(by_provider[0].requests, 2);
+        assert_eq!(by_provider[0].prompt_tokens, 4000);
+        assert_eq!(by_provider[0].completion_tokens, 1000);
+        assert!((by_provider[0].cost_usd - 0.02).abs() < 1e-9);
+        assert_eq!(by_provider[1].key, "ollama");
+
+        let by_day = tracker.summarize(7, GroupBy::Day).unwrap();
+        assert_eq!(by_day.len(), 1);
+        let total = UsageTotals::sum(&by_day);
+        assert_eq!(total.requests, 3);
+        assert_eq!(total.total_tokens(), 5250);
+    }
+
+    #[test]
+    fn record_reply_skips_replies_without_usage_and_disabled_tracking() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = test_config(&tmp);
+        let tracker = CostTracker::new(&config);
+
+        tracker.record_reply("openai", "cli", "gpt-4o", &ChatReply::from("no usage"));
+        assert!(tracker.summarize(1, GroupBy::Day).unwrap().is_empty());
+
+        config.cost.enabled = false;
+        let disabled = CostTracker::new(&config);
+        let reply = ChatReply::new("hi", Some(TokenUsage::new(10, 5)));
+        disabled.record_reply("openai", "cli", "gpt-4o", &reply);
+        assert!(tracker.summarize(1, GroupBy::Day).unwrap().is_empty());
+
+        tracker.record_reply("openai", "cli", "gpt-4o", &reply);
+        assert_eq!(
+            tracker.summarize(1, GroupBy::Channel).unwrap()[0].key,
+            "cli"
+        );
+    }
+
+    #[test]
+    fn group_by_parses_known_dimensions() {
+        assert_eq!("Provider".parse::<GroupBy>().unwrap(), GroupBy::Provider);
+        assert_eq!("day".parse::<GroupBy>().unwrap(), GroupBy::Day);
+        assert!("week".parse::<GroupBy>().is_err());
+    }
+}
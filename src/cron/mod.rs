@@ -18,12 +18,24 @@ pub struct CronJob {
     pub last_status: Option<String>,
 }
 
+/// A message queued for delivery through a channel at a given time.
+#[derive(Debug, Clone)]
+pub struct ScheduledMessage {
+    pub id: String,
+    pub channel: String,
+    pub target: String,
+    pub message: String,
+    pub send_at: DateTime<Utc>,
+    pub attempts: u32,
+}
+
 #[allow(clippy::needless_pass_by_value)]
 pub fn handle_command(command: crate::CronCommands, config: &Config) -> Result<()> {
     match command {
         crate::CronCommands::List => {
             let jobs = list_jobs(config)?;
-            if jobs.is_empty() {
+            let messages = list_messages(config)?;
+            if jobs.is_empty() && messages.is_empty() {
                 println!("No scheduled tasks yet.");
                 println!("\nUsage:");
                 println!("  zeroclaw cron add '0 9 * * *' 'agent -m \"Good morning!\"'");
@@ -46,6 +58,19 @@ pub fn handle_command(command: crate::CronCommands, config: &Config) -> Result<(
                     job.command
                 );
             }
+            if !messages.is_empty() {
+                println!("✉️  Scheduled messages ({}):", messages.len());
+                for msg in messages {
+                    println!(
+                        "- {} | {} → {} | at={}\n    msg: {}",
+                        msg.id,
+                        msg.channel,
+                        msg.target,
+                        msg.send_at.to_rfc3339(),
+                        msg.message
+                    );
+                }
+            }
             Ok(())
         }
         crate::CronCommands::Add {
@@ -59,6 +84,20 @@ pub fn handle_command(command: crate::CronCommands, config: &Config) -> Result<(
             println!("  Cmd : {}", job.command);
             Ok(())
         }
+        crate::CronCommands::SendAt {
+            when,
+            channel,
+            target,
+            message,
+        } => {
+            let when = parse_send_time(&when, Utc::now())?;
+            let msg = send_at(config, &channel, &target, &message, when)?;
+            println!("✅ Scheduled message {}", msg.id);
+            println!("  To  : {} → {}", msg.channel, msg.target);
+            println!("  At  : {}", msg.send_at.to_rfc3339());
+            println!("  (delivered by the daemon's scheduler)");
+            Ok(())
+        }
         crate::CronCommands::Remove { id } => remove_job(config, &id),
     }
 }
@@ -135,15 +174,20 @@ pub fn list_jobs(config: &Config) -> Result<Vec<CronJob>> {
 
 pub fn remove_job(config: &Config, id: &str) -> Result<()> {
     let changed = with_connection(config, |conn| {
-        conn.execute("DELETE FROM cron_jobs WHERE id = ?1", params![id])
-            .context("Failed to delete cron job")
+        let jobs = conn
+            .execute("DELETE FROM cron_jobs WHERE id = ?1", params![id])
+            .context("Failed to delete cron job")?;
+        let messages = conn
+            .execute("DELETE FROM scheduled_messages WHERE id = ?1", params![id])
+            .context("Failed to delete scheduled message")?;
+        Ok(jobs + messages)
     })?;
 
     if changed == 0 {
-        anyhow::bail!("Cron job '{id}' not found");
+        anyhow::bail!("No cron job or scheduled message '{id}'");
     }
 
-    println!("✅ Removed cron job {id}");
+    println!("✅ Removed {id}");
     Ok(())
 }
 
@@ -214,6 +258,151 @@ pub fn reschedule_after_run(
     })
 }
 
+/// Queue `message` for delivery to `target` on `channel` at `when`. The
+/// daemon's scheduler sends it through the running channel once it is due.
+pub fn send_at(
+    config: &Config,
+    channel: &str,
+    target: &str,
+    message: &str,
+    when: DateTime<Utc>,
+) -> Result<ScheduledMessage> {
+    let (channel, target) = (channel.trim(), target.trim());
+    if channel.is_empty() || target.is_empty() {
+        anyhow::bail!("A scheduled message needs a channel and a target");
+    }
+    if message.trim().is_empty() {
+        anyhow::bail!("A scheduled message cannot be empty");
+    }
+    let id = Uuid::new_v4().to_string();
+
+    with_connection(config, |conn| {
+        conn.execute(
+            "INSERT INTO scheduled_messages (id, channel, target, message, created_at, send_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                channel,
+                target,
+                message,
+                Utc::now().to_rfc3339(),
+                when.to_rfc3339()
+            ],
+        )
+        .context("Failed to insert scheduled message")?;
+        Ok(())
+    })?;
+
+    Ok(ScheduledMessage {
+        id,
+        channel: channel.to_string(),
+        target: target.to_string(),
+        message: message.to_string(),
+        send_at: when,
+        attempts: 0,
+    })
+}
+
+/// Parse when to send a message: RFC 3339 (`2026-03-01T17:00:00+01:00`) or
+/// a delay from `now` like `30m`, `2h`, `1d` (optionally `in 2h`).
+pub fn parse_send_time(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    let when = if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        at.with_timezone(&Utc)
+    } else {
+        let delay = value.strip_prefix("in ").unwrap_or(value);
+        match crate::memory::retention::parse_retention(delay) {
+            Ok(Some(delay)) => now + delay,
+            _ => anyhow::bail!(
+                "invalid send time '{value}' (expected RFC 3339 or a delay like \"30m\", \"2h\")"
+            ),
+        }
+    };
+    if when < now {
+        anyhow::bail!("Send time {} is in the past", when.to_rfc3339());
+    }
+    Ok(when)
+}
+
+pub fn list_messages(config: &Config) -> Result<Vec<ScheduledMessage>> {
+    query_messages(config, None)
+}
+
+pub fn due_messages(config: &Config, now: DateTime<Utc>) -> Result<Vec<ScheduledMessage>> {
+    query_messages(config, Some(now))
+}
+
+fn query_messages(config: &Config, due_by: Option<DateTime<Utc>>) -> Result<Vec<ScheduledMessage>> {
+    with_connection(config, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, channel, target, message, send_at, attempts
+             FROM scheduled_messages WHERE ?1 IS NULL OR send_at <= ?1 ORDER BY send_at ASC",
+        )?;
+
+        let rows = stmt.query_map(params![due_by.map(|at| at.to_rfc3339())], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, u32>(5)?,
+            ))
+        })?;
+
+        let mut messages = Vec::new();
+        for row in rows {
+            let (id, channel, target, message, send_at_raw, attempts) = row?;
+            messages.push(ScheduledMessage {
+                id,
+                channel,
+                target,
+                message,
+                send_at: parse_rfc3339(&send_at_raw)?,
+                attempts,
+            });
+        }
+        Ok(messages)
+    })
+}
+
+/// Drop a message from the queue once it has been delivered.
+pub fn complete_message(config: &Config, msg: &ScheduledMessage) -> Result<()> {
+    with_connection(config, |conn| {
+        conn.execute(
+            "DELETE FROM scheduled_messages WHERE id = ?1",
+            params![msg.id],
+        )
+        .context("Failed to delete scheduled message")?;
+        Ok(())
+    })
+}
+
+/// Record a failed delivery. The message stays queued for the next poll
+/// until it has failed `scheduler_retries + 1` times, then it is dropped;
+/// returns whether it will be retried.
+pub fn fail_message(config: &Config, msg: &ScheduledMessage, error: &str) -> Result<bool> {
+    let attempts = msg.attempts + 1;
+    let retry = attempts <= config.reliability.scheduler_retries;
+
+    with_connection(config, |conn| {
+        if retry {
+            conn.execute(
+                "UPDATE scheduled_messages SET attempts = ?1, last_error = ?2 WHERE id = ?3",
+                params![attempts, error, msg.id],
+            )
+            .context("Failed to update scheduled message")?;
+        } else {
+            conn.execute(
+                "DELETE FROM scheduled_messages WHERE id = ?1",
+                params![msg.id],
+            )
+            .context("Failed to delete scheduled message")?;
+        }
+        Ok(retry)
+    })
+}
+
 fn next_run_for(expression: &str, from: DateTime<Utc>) -> Result<DateTime<Utc>> {
     let normalized = normalize_expression(expression)?;
     let schedule = Schedule::from_str(&normalized)
@@ -266,7 +455,18 @@ fn with_connection<T>(config: &Config, f: impl FnOnce(&Connection) -> Result<T>)
             last_status TEXT,
             last_output TEXT
         );
-        CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);",
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);
+        CREATE TABLE IF NOT EXISTS scheduled_messages (
+            id          TEXT PRIMARY KEY,
+            channel     TEXT NOT NULL,
+            target      TEXT NOT NULL,
+            message     TEXT NOT NULL,
+            created_at  TEXT NOT NULL,
+            send_at     TEXT NOT NULL,
+            attempts    INTEGER NOT NULL DEFAULT 0,
+            last_error  TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_scheduled_messages_send_at ON scheduled_messages(send_at);",
     )
     .context("Failed to initialize cron schema")?;
 
@@ -352,4 +552,76 @@ mod tests {
         assert_eq!(stored.last_status.as_deref(), Some("error"));
         assert!(stored.last_run.is_some());
     }
+
+    #[test]
+    fn send_at_queues_a_message_until_it_is_due() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let when = Utc::now() + ChronoDuration::hours(2);
+
+        let msg = send_at(&config, "telegram", " 42 ", "Stand-up in 5 minutes", when).unwrap();
+        assert_eq!(msg.target, "42");
+        assert!(due_messages(&config, Utc::now()).unwrap().is_empty());
+
+        let due = due_messages(&config, when).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, msg.id);
+        assert_eq!(due[0].channel, "telegram");
+        assert_eq!(due[0].message, "Stand-up in 5 minutes");
+
+        complete_message(&config, &due[0]).unwrap();
+        assert!(list_messages(&config).unwrap().is_empty());
+
+        assert!(send_at(&config, "telegram", "", "hi", when).is_err());
+        assert!(send_at(&config, "telegram", "42", "  ", when).is_err());
+    }
+
+    #[test]
+    fn failed_messages_are_retried_then_dropped() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = test_config(&tmp);
+        config.reliability.scheduler_retries = 1;
+        send_at(&config, "slack", "C1", "hi", Utc::now()).unwrap();
+
+        let first = due_messages(&config, Utc::now()).unwrap().remove(0);
+        assert!(fail_message(&config, &first, "timeout").unwrap());
+        let second = due_messages(&config, Utc::now()).unwrap().remove(0);
+        assert_eq!(second.attempts, 1);
+        assert!(!fail_message(&config, &second, "timeout").unwrap());
+        assert!(list_messages(&config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn remove_job_also_cancels_scheduled_messages() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+
+        let msg = send_at(&config, "slack", "C1", "hi", Utc::now()).unwrap();
+        remove_job(&config, &msg.id).unwrap();
+        assert!(list_messages(&config).unwrap().is_empty());
+        assert!(remove_job(&config, &msg.id).is_err());
+    }
+
+    #[test]
+    fn send_time_is_a_timestamp_or_a_delay() {
+        let now = DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            parse_send_time("2026-03-01T17:00:00+01:00", now).unwrap(),
+            now + ChronoDuration::hours(4)
+        );
+        assert_eq!(
+            parse_send_time("30m", now).unwrap(),
+            now + ChronoDuration::minutes(30)
+        );
+        assert_eq!(
+            parse_send_time("in 2h", now).unwrap(),
+            now + ChronoDuration::hours(2)
+        );
+        assert!(parse_send_time("2026-03-01T11:00:00Z", now).is_err());
+        assert!(parse_send_time("forever", now).is_err());
+        assert!(parse_send_time("at five", now).is_err());
+    }
 }
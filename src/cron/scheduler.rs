@@ -1,5 +1,9 @@
+use crate::channels::manager::ChannelManager;
 use crate::config::Config;
-use crate::cron::{due_jobs, reschedule_after_run, CronJob};
+use crate::cron::{
+    complete_message, due_jobs, due_messages, fail_message, reschedule_after_run, CronJob,
+    ScheduledMessage,
+};
 use crate::security::SecurityPolicy;
 use anyhow::Result;
 use chrono::Utc;
@@ -12,12 +16,30 @@ pub async fn run(config: Config) -> Result<()> {
     let poll_secs = config.reliability.scheduler_poll_secs.max(MIN_POLL_SECONDS);
     let mut interval = time::interval(Duration::from_secs(poll_secs));
     let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+    // Built on the first due message, not for deployments that never schedule one.
+    let mut channels: Option<ChannelManager> = None;
 
     crate::health::mark_component_ok("scheduler");
 
     loop {
         interval.tick().await;
 
+        match due_messages(&config, Utc::now()) {
+            Ok(messages) if !messages.is_empty() => {
+                let channels = channels.get_or_insert_with(|| {
+                    ChannelManager::new(crate::channels::build_channels(&config.channels_config))
+                });
+                for msg in messages {
+                    deliver_message(&config, channels, &msg).await;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                crate::health::mark_component_error("scheduler", e.to_string());
+                tracing::warn!("Scheduled message query failed: {e}");
+            }
+        }
+
         let jobs = match due_jobs(&config, Utc::now()) {
             Ok(jobs) => jobs,
             Err(e) => {
@@ -43,6 +65,36 @@ pub async fn run(config: Config) -> Result<()> {
     }
 }
 
+/// Send one due message and record the outcome; failures stay queued for
+/// the next poll until `scheduler_retries` is exhausted.
+async fn deliver_message(config: &Config, channels: &ChannelManager, msg: &ScheduledMessage) {
+    let sent = match channels.get(&msg.channel) {
+        Some(channel) => channel
+            .send(&msg.message, &msg.target)
+            .await
+            .map_err(|e| e.to_string()),
+        None => Err(format!("channel '{}' is not configured", msg.channel)),
+    };
+
+    let recorded = match sent {
+        Ok(()) => complete_message(config, msg),
+        Err(error) => {
+            crate::health::mark_component_error("scheduler", format!("message {} failed", msg.id));
+            fail_message(config, msg, &error).map(|retry| {
+                if retry {
+                    tracing::warn!("Scheduled message {} failed, retrying: {error}", msg.id);
+                } else {
+                    tracing::error!("Scheduled message {} dropped: {error}", msg.id);
+                }
+            })
+        }
+    };
+    if let Err(e) = recorded {
+        crate::health::mark_component_error("scheduler", e.to_string());
+        tracing::warn!("Failed to persist scheduled message state: {e}");
+    }
+}
+
 async fn execute_job_with_retry(
     config: &Config,
     security: &SecurityPolicy,
@@ -296,4 +348,54 @@ mod tests {
         assert!(!success);
         assert!(output.contains("always_missing_for_retry_test"));
     }
+
+    struct RecordingChannel {
+        sent: std::sync::Arc<std::sync::Mutex<Vec<(String, String)>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::channels::traits::Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "telegram"
+        }
+
+        async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((message.to_string(), recipient.to_string()));
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<crate::channels::traits::ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn deliver_message_sends_through_the_named_channel() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = test_config(&tmp);
+        config.reliability.scheduler_retries = 0;
+        let sent = std::sync::Arc::default();
+        let channels = ChannelManager::new(vec![std::sync::Arc::new(RecordingChannel {
+            sent: std::sync::Arc::clone(&sent),
+        })]);
+
+        crate::cron::send_at(&config, "telegram", "42", "Time to stretch", Utc::now()).unwrap();
+        crate::cron::send_at(&config, "discord", "7", "Nobody hears this", Utc::now()).unwrap();
+        for msg in due_messages(&config, Utc::now()).unwrap() {
+            deliver_message(&config, &channels, &msg).await;
+        }
+
+        assert_eq!(
+            *sent.lock().unwrap(),
+            [("Time to stretch".to_string(), "42".to_string())]
+        );
+        // Delivered and undeliverable (no retries left) messages both leave the queue.
+        assert!(crate::cron::list_messages(&config).unwrap().is_empty());
+    }
 }
@@ -1,6 +1,7 @@
 use crate::config::Config;
 use anyhow::Result;
 use chrono::Utc;
+use std::collections::BTreeMap;
 use std::future::Future;
 use std::path::PathBuf;
 use tokio::task::JoinHandle;
@@ -23,6 +24,13 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
                 .await;
     }
 
+    if config.memory.backup.enabled && config.memory.backup.restore_on_empty {
+        // Before anything can store into the fresh brain
+        if let Err(e) = restore_memory_backup(&config).await {
+            tracing::warn!("memory backup restore skipped: {e}");
+        }
+    }
+
     let mut handles: Vec<JoinHandle<()>> = vec![spawn_state_writer(config.clone())];
 
     {
@@ -66,7 +74,7 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
             max_backoff,
             move || {
                 let cfg = heartbeat_cfg.clone();
-                async move { run_heartbeat_worker(cfg).await }
+                Box::pin(run_heartbeat_worker(cfg))
             },
         ));
     }
@@ -84,6 +92,8 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
         ));
     }
 
+    handles.extend(spawn_memory_workers(&config, initial_backoff, max_backoff));
+
     println!("🧠 ZeroClaw daemon started");
     println!("   Gateway:  http://{host}:{port}");
     println!("   Components: gateway, channels, heartbeat, scheduler");
@@ -102,6 +112,68 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
     Ok(())
 }
 
+/// Supervised background memory jobs: compaction, dedup, remote backups and
+/// peer sync.
+fn spawn_memory_workers(
+    config: &Config,
+    initial_backoff: u64,
+    max_backoff: u64,
+) -> Vec<JoinHandle<()>> {
+    let mut workers = Vec::new();
+    if config.memory.compaction_after_days > 0 {
+        let compaction_cfg = config.clone();
+        workers.push(spawn_component_supervisor(
+            "memory-compaction",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = compaction_cfg.clone();
+                async move { run_compaction_worker(cfg).await }
+            },
+        ));
+    }
+
+    if config.memory.dedup_interval_secs > 0 {
+        let dedup_cfg = config.clone();
+        workers.push(spawn_component_supervisor(
+            "memory-dedup",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = dedup_cfg.clone();
+                async move { run_dedup_worker(cfg).await }
+            },
+        ));
+    }
+
+    if config.memory.backup.enabled {
+        let backup_cfg = config.clone();
+        workers.push(spawn_component_supervisor(
+            "memory-backup",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = backup_cfg.clone();
+                async move { run_backup_worker(cfg).await }
+            },
+        ));
+    }
+
+    if config.memory.sync.enabled {
+        let sync_cfg = config.clone();
+        workers.push(spawn_component_supervisor(
+            "memory-sync",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = sync_cfg.clone();
+                async move { run_sync_worker(cfg).await }
+            },
+        ));
+    }
+    workers
+}
+
 pub fn state_file_path(config: &Config) -> PathBuf {
     config
         .config_path
@@ -110,6 +182,18 @@ pub fn state_file_path(config: &Config) -> PathBuf {
         .join("daemon_state.json")
 }
 
+/// Channel health checks recorded by the running daemon, with the time its
+/// state file was written; `None` without a readable state file.
+pub fn channel_health(
+    config: &Config,
+) -> Option<(String, BTreeMap<String, crate::health::ChannelHealth>)> {
+    let raw = std::fs::read_to_string(state_file_path(config)).ok()?;
+    let mut state: serde_json::Value = serde_json::from_str(&raw).ok()?;
+    let written_at = state.get("written_at")?.as_str()?.to_string();
+    let channels = serde_json::from_value(state.get_mut("channels")?.take()).ok()?;
+    Some((written_at, channels))
+}
+
 fn spawn_state_writer(config: Config) -> JoinHandle<()> {
     tokio::spawn(async move {
         let path = state_file_path(&config);
@@ -204,6 +288,127 @@ async fn run_heartbeat_worker(config: Config) -> Result<()> {
     }
 }
 
+async fn run_compaction_worker(config: Config) -> Result<()> {
+    let provider = crate::providers::create_resilient_provider(
+        config.default_provider.as_deref().unwrap_or("openrouter"),
+        config.api_key.as_deref(),
+        &config.reliability,
+    )?;
+    let mem = crate::memory::create_memory(
+        &config.memory,
+        &config.workspace_dir,
+        config.api_key.as_deref(),
+    )?;
+    let model = config
+        .default_model
+        .clone()
+        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+
+    let interval_secs = config.memory.compaction_interval_secs.max(60);
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        match crate::memory::compaction::compact_conversations(
+            mem.as_ref(),
+            provider.as_ref(),
+            &model,
+            config.memory.compaction_after_days,
+            config.memory.compaction_batch_size,
+        )
+        .await
+        {
+            Ok(report) => {
+                crate::health::mark_component_ok("memory-compaction");
+                if report.summaries > 0 {
+                    tracing::info!(
+                        "memory: compacted {} conversation entries into {} summaries",
+                        report.compacted,
+                        report.summaries
+                    );
+                }
+            }
+            Err(e) => {
+                crate::health::mark_component_error("memory-compaction", e.to_string());
+                tracing::warn!("Memory compaction failed: {e}");
+            }
+        }
+    }
+}
+
+async fn run_dedup_worker(config: Config) -> Result<()> {
+    let mem = crate::memory::create_memory(
+        &config.memory,
+        &config.workspace_dir,
+        config.api_key.as_deref(),
+    )?;
+    let interval_secs = config.memory.dedup_interval_secs.max(60);
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        match crate::memory::dedup::dedup_memories(
+            mem.as_ref(),
+            config.memory.dedup_similarity,
+            false,
+        )
+        .await
+        {
+            Ok(report) => {
+                crate::health::mark_component_ok("memory-dedup");
+                if report.merged > 0 {
+                    tracing::info!(
+                        "memory: merged {} near-duplicate entries into {}",
+                        report.merged,
+                        report.groups.len()
+                    );
+                }
+            }
+            Err(e) => {
+                crate::health::mark_component_error("memory-dedup", e.to_string());
+                tracing::warn!("Memory dedup failed: {e}");
+            }
+        }
+    }
+}
+
+async fn restore_memory_backup(config: &Config) -> Result<()> {
+    let mem = crate::memory::create_memory(
+        &config.memory,
+        &config.workspace_dir,
+        config.api_key.as_deref(),
+    )?;
+    let target = crate::memory::backup::S3Target::from_config(&config.memory.backup)?;
+    if let Some(n) = crate::memory::backup::restore_if_empty(mem.as_ref(), &target).await? {
+        tracing::info!("memory: restored {n} entries from the latest backup");
+    }
+    Ok(())
+}
+
+async fn run_backup_worker(config: Config) -> Result<()> {
+    let mem: std::sync::Arc<dyn crate::memory::Memory> =
+        std::sync::Arc::from(crate::memory::create_memory(
+            &config.memory,
+            &config.workspace_dir,
+            config.api_key.as_deref(),
+        )?);
+    crate::health::mark_component_ok("memory-backup");
+    crate::memory::backup::run_backups(mem, &config.memory.backup).await
+}
+
+async fn run_sync_worker(config: Config) -> Result<()> {
+    let mem: std::sync::Arc<dyn crate::memory::Memory> =
+        std::sync::Arc::from(crate::memory::create_memory(
+            &config.memory,
+            &config.workspace_dir,
+            config.api_key.as_deref(),
+        )?);
+    crate::health::mark_component_ok("memory-sync");
+    crate::memory::sync::run_sync(mem, &config.memory.sync).await
+}
+
 fn has_supervised_channels(config: &Config) -> bool {
     config.channels_config.telegram.is_some()
         || config.channels_config.discord.is_some()
@@ -212,6 +417,9 @@ fn has_supervised_channels(config: &Config) -> bool {
         || config.channels_config.matrix.is_some()
         || config.channels_config.whatsapp.is_some()
         || config.channels_config.email.is_some()
+        || config.channels_config.irc.is_some()
+        || config.channels_config.xmpp.is_some()
+        || config.channels_config.mastodon.is_some()
 }
 
 #[cfg(test)]
@@ -238,6 +446,27 @@ mod tests {
         assert_eq!(path, tmp.path().join("daemon_state.json"));
     }
 
+    #[test]
+    fn channel_health_reads_the_state_file() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        assert!(channel_health(&config).is_none());
+
+        let state = serde_json::json!({
+            "written_at": "2026-01-01T00:00:00Z",
+            "channels": {"slack": {
+                "healthy": false,
+                "consecutive_failures": 2,
+                "last_check": "2026-01-01T00:00:00Z",
+                "last_success": null
+            }}
+        });
+        std::fs::write(state_file_path(&config), state.to_string()).unwrap();
+        let (written_at, channels) = channel_health(&config).unwrap();
+        assert_eq!(written_at, "2026-01-01T00:00:00Z");
+        assert_eq!(channels["slack"].consecutive_failures, 2);
+    }
+
     #[tokio::test]
     async fn supervisor_marks_error_and_restart_on_failure() {
         let handle = spawn_component_supervisor("daemon-test-fail", 1, 1, || async {
@@ -288,6 +517,8 @@ mod tests {
         config.channels_config.telegram = Some(crate::config::TelegramConfig {
             bot_token: "token".into(),
             allowed_users: vec![],
+            allowed_chats: vec![],
+            group_mention_only: true,
         });
         assert!(has_supervised_channels(&config));
     }
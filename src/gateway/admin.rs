@@ -0,0 +1,263 @@
+//! Admin routes: inspect the effective config and reload it in place.
+//!
+//! `/admin/reload` swaps the [`GatewaySettings`] behind the shared lock, so
+//! model and batch changes apply to the next request while channels and open
+//! connections stay up. Changes to anything else are reported back as
+//! needing a restart.
+
+use super::{bearer_token, matches_auth_token, AppState, GatewaySettings};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::Value;
+
+/// Placeholder for redacted secret values.
+const REDACTED: &str = "***";
+
+/// Config paths that `/admin/reload` applies without a restart.
+const RELOADABLE_PATHS: &[&str] = &[
+    "default_model",
+    "default_temperature",
+    "generation.max_tokens",
+    "generation.top_p",
+    "generation.stop",
+    "generation.seed",
+    "gateway.allowed_models",
+    "gateway.batch_max_items",
+    "gateway.batch_concurrency",
+];
+
+/// GET /admin/config — effective config with secrets redacted
+pub(super) async fn handle_admin_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(rejection) = authorize_admin(&state, &headers) {
+        return rejection;
+    }
+
+    let mut body = {
+        let config = state
+            .config
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match serde_json::to_value(&*config) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::error!("Admin: failed to serialize config: {e}");
+                let err = serde_json::json!({"error": "Failed to serialize config"});
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response();
+            }
+        }
+    };
+    redact_secrets(&mut body);
+    (StatusCode::OK, Json(body)).into_response()
+}
+
+/// POST /admin/reload — re-read config.toml and apply reloadable settings
+pub(super) async fn handle_admin_reload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(rejection) = authorize_admin(&state, &headers) {
+        return rejection;
+    }
+
+    let current = state
+        .config
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone();
+    let reloaded = match current.reload() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("Admin: config reload failed: {e:#}");
+            let err = serde_json::json!({
+                "error": format!("Config reload failed; keeping current config: {e:#}")
+            });
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(err)).into_response();
+        }
+    };
+
+    let (Ok(old), Ok(new)) = (
+        serde_json::to_value(&current),
+        serde_json::to_value(&reloaded),
+    ) else {
+        let err = serde_json::json!({"error": "Failed to serialize config"});
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response();
+    };
+    let mut changed = Vec::new();
+    changed_paths(&old, &new, "", &mut changed);
+    let (applied, restart_required): (Vec<String>, Vec<String>) =
+        changed.into_iter().partition(|path| is_reloadable(path));
+
+    *state
+        .settings
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) =
+        GatewaySettings::from_config(&reloaded);
+    *state
+        .config
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = reloaded;
+
+    tracing::info!(
+        "Admin: config reloaded (applied: {applied:?}, restart required: {restart_required:?})"
+    );
+    let body = serde_json::json!({
+        "status": "reloaded",
+        "applied": applied,
+        "restart_required": restart_required,
+    });
+    (StatusCode::OK, Json(body)).into_response()
+}
+
+/// Admin routes need a real credential: the static auth token or a paired
+/// bearer token. With neither configured they are disabled outright.
+fn authorize_admin(state: &AppState, headers: &HeaderMap) -> Option<Response> {
+    if state.auth_token.is_none() && !state.pairing.require_pairing() {
+        let err = serde_json::json!({
+            "error": "Admin API disabled — set gateway.auth_token or enable pairing"
+        });
+        return Some((StatusCode::FORBIDDEN, Json(err)).into_response());
+    }
+
+    let token = bearer_token(headers);
+    let paired = state.pairing.require_pairing() && state.pairing.is_authenticated(token);
+    if paired || matches_auth_token(state, token) {
+        return None;
+    }
+
+    tracing::warn!("Admin: rejected — invalid or missing bearer token");
+    let err = serde_json::json!({
+        "error": "Unauthorized — send Authorization: Bearer <token>"
+    });
+    Some((StatusCode::UNAUTHORIZED, Json(err)).into_response())
+}
+
+fn is_reloadable(path: &str) -> bool {
+    RELOADABLE_PATHS.contains(&path)
+}
+
+/// Field names whose values are credentials.
+fn is_secret_key(name: &str) -> bool {
+    matches!(name, "api_key" | "token" | "secret" | "paired_tokens")
+        || name.ends_with("_key")
+        || name.ends_with("_token")
+        || name.ends_with("_secret")
+        || name.contains("password")
+}
+
+/// Replace every non-empty secret value (strings and string lists) with
+/// [`REDACTED`], leaving unset secrets visible as `null`/empty.
+fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if is_secret_key(key) {
+                    redact_value(child);
+                } else {
+                    redact_secrets(child);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::String(s) if !s.is_empty() => *s = REDACTED.into(),
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Collect dotted paths of leaves that differ between two config trees.
+fn changed_paths(old: &Value, new: &Value, prefix: &str, out: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                match (old_map.get(key), new_map.get(key)) {
+                    (Some(o), Some(n)) => changed_paths(o, n, &path, out),
+                    _ => out.push(path),
+                }
+            }
+        }
+        _ if old != new => out.push(prefix.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secrets_but_keeps_lookalike_fields() {
+        let mut value = serde_json::json!({
+            "api_key": "sk-live",
+            "gateway": {"auth_token": "tok", "paired_tokens": ["a", "b"], "port": 3000},
+            "memory": {"keyword_weight": 0.3, "chunk_max_tokens": 512},
+            "channels_config": {
+                "telegram": {"bot_token": "123:abc", "allowed_users": ["alice"]},
+                "irc": {"server_password": "pw", "sasl_password": null}
+            }
+        });
+        redact_secrets(&mut value);
+
+        assert_eq!(value["api_key"], REDACTED);
+        assert_eq!(value["gateway"]["auth_token"], REDACTED);
+        assert_eq!(
+            value["gateway"]["paired_tokens"],
+            serde_json::json!([REDACTED, REDACTED])
+        );
+        assert_eq!(value["gateway"]["port"], 3000);
+        assert_eq!(value["memory"]["keyword_weight"], 0.3);
+        assert_eq!(value["memory"]["chunk_max_tokens"], 512);
+        assert_eq!(value["channels_config"]["telegram"]["bot_token"], REDACTED);
+        assert_eq!(
+            value["channels_config"]["telegram"]["allowed_users"],
+            serde_json::json!(["alice"])
+        );
+        assert_eq!(value["channels_config"]["irc"]["server_password"], REDACTED);
+        assert!(value["channels_config"]["irc"]["sasl_password"].is_null());
+    }
+
+    #[test]
+    fn changed_paths_reports_nested_leaves() {
+        let old = serde_json::json!({
+            "default_model": "a",
+            "gateway": {"port": 3000, "allowed_models": []},
+        });
+        let new = serde_json::json!({
+            "default_model": "b",
+            "gateway": {"port": 3001, "allowed_models": ["x"]},
+            "tunnel": {"provider": "none"},
+        });
+        let mut changed = Vec::new();
+        changed_paths(&old, &new, "", &mut changed);
+        assert_eq!(
+            changed,
+            vec![
+                "default_model",
+                "gateway.allowed_models",
+                "gateway.port",
+                "tunnel"
+            ]
+        );
+        assert!(is_reloadable("gateway.allowed_models"));
+        assert!(!is_reloadable("gateway.port"));
+    }
+}
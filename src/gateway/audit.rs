@@ -0,0 +1,242 @@
+//! Opt-in JSONL audit log for gateway requests.
+//!
+//! Every request produces one line with timing, caller, route, and status.
+//! Handlers that talk to the LLM attach an [`AuditContext`] to their response
+//! so the record also carries the (truncated) prompt, model, and token usage.
+
+use crate::config::GatewayAuditConfig;
+use crate::providers::traits::TokenUsage;
+use crate::util::truncate_with_ellipsis;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Prompt/model details a handler attaches to its response via extensions.
+#[derive(Debug, Clone)]
+pub struct AuditContext {
+    pub prompt: String,
+    pub model: String,
+    /// Tokens the provider reported, when known before the response is sent
+    /// (not for streamed replies)
+    pub usage: Option<TokenUsage>,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Serialize)]
+pub struct AuditRecord {
+    pub timestamp: String,
+    pub peer: String,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_tokens: Option<u64>,
+}
+
+/// Size-rotated JSONL writer.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    max_file_bytes: u64,
+    max_files: usize,
+    prompt_max_chars: usize,
+    file: Mutex<Option<File>>,
+}
+
+impl AuditLog {
+    /// Build the audit log from config, or `None` when disabled.
+    pub fn from_config(config: &GatewayAuditConfig, workspace_dir: &Path) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let path = PathBuf::from(&config.path);
+        let path = if path.is_absolute() {
+            path
+        } else {
+            workspace_dir.join(path)
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create audit log directory {}", parent.display())
+            })?;
+        }
+
+        Ok(Some(Self {
+            path,
+            max_file_bytes: config.max_file_bytes.max(1),
+            max_files: config.max_files,
+            prompt_max_chars: config.prompt_max_chars,
+            file: Mutex::new(None),
+        }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Truncate a prompt to the configured length for logging.
+    pub fn truncate_prompt(&self, prompt: &str) -> String {
+        truncate_with_ellipsis(prompt, self.prompt_max_chars)
+    }
+
+    /// Append one record, rotating first if it would overflow the active file.
+    pub fn append(&self, record: &AuditRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        let mut guard = self
+            .file
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let current_len = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if current_len > 0 && current_len + line.len() as u64 > self.max_file_bytes {
+            *guard = None;
+            self.rotate()?;
+        }
+
+        if guard.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .with_context(|| format!("Failed to open audit log {}", self.path.display()))?;
+            *guard = Some(file);
+        }
+
+        if let Some(file) = guard.as_mut() {
+            file.write_all(&line)?;
+        }
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{index}"));
+        PathBuf::from(name)
+    }
+
+    /// Shift `log.N-1 → log.N … log → log.1`, dropping the oldest file.
+    fn rotate(&self) -> Result<()> {
+        if self.max_files == 0 {
+            fs::remove_file(&self.path).ok();
+            return Ok(());
+        }
+
+        fs::remove_file(self.rotated_path(self.max_files)).ok();
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(enabled: bool) -> GatewayAuditConfig {
+        GatewayAuditConfig {
+            enabled,
+            ..GatewayAuditConfig::default()
+        }
+    }
+
+    fn record(path: &str) -> AuditRecord {
+        AuditRecord {
+            timestamp: "2026-01-01T00:00:00Z".into(),
+            peer: "127.0.0.1".into(),
+            method: "POST".into(),
+            path: path.into(),
+            status: 200,
+            latency_ms: 5,
+            prompt: Some("hello".into()),
+            model: Some("test-model".into()),
+            prompt_tokens: None,
+            completion_tokens: None,
+        }
+    }
+
+    #[test]
+    fn disabled_config_builds_nothing() {
+        let tmp = TempDir::new().unwrap();
+        assert!(AuditLog::from_config(&config(false), tmp.path())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn relative_path_resolves_against_workspace() {
+        let tmp = TempDir::new().unwrap();
+        let log = AuditLog::from_config(&config(true), tmp.path())
+            .unwrap()
+            .unwrap();
+        assert_eq!(log.path(), tmp.path().join("audit/gateway.jsonl"));
+        assert!(tmp.path().join("audit").is_dir());
+    }
+
+    #[test]
+    fn append_writes_jsonl_lines() {
+        let tmp = TempDir::new().unwrap();
+        let log = AuditLog::from_config(&config(true), tmp.path())
+            .unwrap()
+            .unwrap();
+        log.append(&record("/webhook")).unwrap();
+        log.append(&record("/health")).unwrap();
+
+        let content = fs::read_to_string(log.path()).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["path"], "/webhook");
+        assert_eq!(lines[0]["prompt"], "hello");
+        assert_eq!(lines[1]["status"], 200);
+    }
+
+    #[test]
+    fn rotates_when_file_exceeds_limit() {
+        let tmp = TempDir::new().unwrap();
+        let mut cfg = config(true);
+        cfg.max_file_bytes = 10;
+        cfg.max_files = 2;
+        let log = AuditLog::from_config(&cfg, tmp.path()).unwrap().unwrap();
+
+        for _ in 0..4 {
+            log.append(&record("/webhook")).unwrap();
+        }
+
+        assert!(log.path().exists());
+        assert!(log.rotated_path(1).exists());
+        assert!(log.rotated_path(2).exists());
+        assert!(!log.rotated_path(3).exists(), "oldest file must be dropped");
+        let active = fs::read_to_string(log.path()).unwrap();
+        assert_eq!(active.lines().count(), 1);
+    }
+
+    #[test]
+    fn prompt_truncation_respects_limit() {
+        let tmp = TempDir::new().unwrap();
+        let mut cfg = config(true);
+        cfg.prompt_max_chars = 5;
+        let log = AuditLog::from_config(&cfg, tmp.path()).unwrap().unwrap();
+        assert_eq!(log.truncate_prompt("hello world"), "hello...");
+    }
+}
@@ -7,34 +7,65 @@
 //! - Request timeouts (30s) to prevent slow-loris attacks
 //! - Header sanitization (handled by axum/hyper)
 
+pub mod admin;
+pub mod audit;
+pub mod proxy;
+
 use crate::channels::{Channel, WhatsAppChannel};
-use crate::config::Config;
-use crate::memory::{self, Memory, MemoryCategory};
-use crate::providers::{self, Provider};
+use crate::config::schema::ChannelAccessConfig;
+use crate::config::{Config, GatewayCorsConfig};
+use crate::cost::{CostTracker, GroupBy, UsageTotals};
+use crate::memory::{self, Memory, MemoryCategory, MemoryEntry};
+use crate::prompts::{self, TemplateVars};
+use crate::providers::structured::StructuredOutputError;
+use crate::providers::traits::{ChatOptions, ChatReply, ImageInput, TokenUsage};
+use crate::providers::{self, ChatMessage, Provider};
 use crate::security::pairing::{constant_time_eq, is_public_bind, PairingGuard};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
+use audit::{AuditContext, AuditLog, AuditRecord};
 use axum::{
     body::Bytes,
-    extract::{Query, State},
+    extract::{
+        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+        ConnectInfo, DefaultBodyLimit, FromRequest, Multipart, Path, Query, Request, State,
+    },
     http::{header, HeaderMap, StatusCode},
-    response::{IntoResponse, Json},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json,
+    },
     routing::{get, post},
     Router,
 };
-use std::collections::HashMap;
+use proxy::{ClientIpHeader, TrustedProxies};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::net::SocketAddr;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::timeout::TimeoutLayer;
 
 /// Maximum request body size (64KB) — prevents memory exhaustion
 pub const MAX_BODY_SIZE: usize = 65_536;
+/// Maximum `POST /memory/sync` body (16MB); peers send changes in pages
+pub const MAX_SYNC_BODY_SIZE: usize = 16 * 1024 * 1024;
 /// Request timeout (30s) — prevents slow-loris attacks
 pub const REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Upper bound on the provider check in `/readyz`.
+pub const READINESS_PROVIDER_TIMEOUT_SECS: u64 = 5;
 /// Sliding window used by gateway rate limiting.
 pub const RATE_LIMIT_WINDOW_SECS: u64 = 60;
+/// Messages (user + assistant) retained per `/ws` connection before the
+/// oldest turns are dropped.
+pub const WS_MAX_HISTORY_MESSAGES: usize = 40;
+/// Messages (user + assistant) persisted per `/webhook` `session_id`.
+pub const WEBHOOK_SESSION_MAX_MESSAGES: usize = 20;
+/// Default number of entries `GET /memory?session_id=` returns.
+pub const MEMORY_SESSION_LIST_LIMIT: usize = 100;
 
 #[derive(Debug)]
 struct SlidingWindowRateLimiter {
@@ -77,18 +108,90 @@ impl SlidingWindowRateLimiter {
     }
 }
 
+/// Per-key token bucket: refills at `per_minute / 60` tokens per second up to
+/// `burst`, one token per request.
+#[derive(Debug)]
+struct TokenBucketRateLimiter {
+    per_minute: u32,
+    burst: u32,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Buckets tracked before idle (fully refilled) entries are evicted.
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+impl TokenBucketRateLimiter {
+    fn new(per_minute: u32, burst: u32) -> Self {
+        Self {
+            per_minute,
+            burst: burst.max(1),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        f64::from(self.per_minute) / 60.0
+    }
+
+    fn refilled(&self, bucket: TokenBucket, now: Instant) -> f64 {
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        (bucket.tokens + elapsed * self.refill_per_sec()).min(f64::from(self.burst))
+    }
+
+    /// Take one token for `key`. On rejection, returns how long until the
+    /// next token becomes available.
+    fn check(&self, key: &str) -> Result<(), Duration> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let capacity = f64::from(self.burst);
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        if buckets.len() >= MAX_TRACKED_BUCKETS {
+            // A full bucket behaves exactly like an untracked key.
+            buckets.retain(|_, bucket| self.refilled(*bucket, now) < capacity);
+        }
+
+        let bucket = buckets.entry(key.to_owned()).or_insert(TokenBucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        bucket.tokens = self.refilled(*bucket, now);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait_secs = (1.0 - bucket.tokens) / self.refill_per_sec();
+            Err(Duration::from_secs_f64(wait_secs))
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GatewayRateLimiter {
     pair: SlidingWindowRateLimiter,
-    webhook: SlidingWindowRateLimiter,
+    webhook: TokenBucketRateLimiter,
 }
 
 impl GatewayRateLimiter {
-    fn new(pair_per_minute: u32, webhook_per_minute: u32) -> Self {
+    fn new(pair_per_minute: u32, webhook_per_minute: u32, webhook_burst: u32) -> Self {
         let window = Duration::from_secs(RATE_LIMIT_WINDOW_SECS);
         Self {
             pair: SlidingWindowRateLimiter::new(pair_per_minute, window),
-            webhook: SlidingWindowRateLimiter::new(webhook_per_minute, window),
+            webhook: TokenBucketRateLimiter::new(webhook_per_minute, webhook_burst),
         }
     }
 
@@ -96,8 +199,87 @@ impl GatewayRateLimiter {
         self.pair.allow(key)
     }
 
-    fn allow_webhook(&self, key: &str) -> bool {
-        self.webhook.allow(key)
+    /// Token-bucket check for `/webhook` and `/ws`; `Err` carries the wait time.
+    fn check_webhook(&self, key: &str) -> Result<(), Duration> {
+        self.webhook.check(key)
+    }
+}
+
+/// 429 response with a `Retry-After` header (whole seconds, at least 1).
+fn too_many_requests(message: &str, retry_after: Duration) -> axum::response::Response {
+    let secs = retry_after.as_secs_f64().ceil().max(1.0);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let secs = secs as u64;
+    let err = serde_json::json!({
+        "error": message,
+        "retry_after": secs,
+    });
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, secs.to_string())],
+        Json(err),
+    )
+        .into_response()
+}
+
+/// Caps in-flight requests with a semaphore and bounds how many may wait for
+/// a slot; anything beyond that is shed with 503.
+#[derive(Debug)]
+pub struct ConcurrencyLimiter {
+    permits: Option<Arc<tokio::sync::Semaphore>>,
+    max_queued: usize,
+    queued: std::sync::atomic::AtomicUsize,
+}
+
+impl ConcurrencyLimiter {
+    fn new(max_concurrent: usize, max_queued: usize) -> Self {
+        Self {
+            permits: (max_concurrent > 0)
+                .then(|| Arc::new(tokio::sync::Semaphore::new(max_concurrent))),
+            max_queued,
+            queued: std::sync::atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait for a slot. `Ok(None)` means the limit is disabled; `Err(())`
+    /// means the queue is full and the request should be rejected.
+    async fn acquire(&self) -> Result<Option<tokio::sync::OwnedSemaphorePermit>, ()> {
+        let Some(permits) = &self.permits else {
+            return Ok(None);
+        };
+        if let Ok(permit) = Arc::clone(permits).try_acquire_owned() {
+            return Ok(Some(permit));
+        }
+
+        let slot = QueueSlot::take(&self.queued);
+        if slot.position >= self.max_queued {
+            return Err(());
+        }
+        let permit = Arc::clone(permits).acquire_owned().await;
+        drop(slot);
+        permit.map(Some).map_err(|_| ())
+    }
+}
+
+/// A place in the [`ConcurrencyLimiter`] queue, given back on drop — also
+/// when the waiting request is cancelled (timeout, client disconnect).
+struct QueueSlot<'a> {
+    queued: &'a std::sync::atomic::AtomicUsize,
+    /// How many requests were already waiting when this one joined.
+    position: usize,
+}
+
+impl<'a> QueueSlot<'a> {
+    fn take(queued: &'a std::sync::atomic::AtomicUsize) -> Self {
+        let position = queued.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Self { queued, position }
+    }
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.queued
+            .fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
     }
 }
 
@@ -134,24 +316,27 @@ impl IdempotencyStore {
     }
 }
 
-fn client_key_from_headers(headers: &HeaderMap) -> String {
-    for header_name in ["X-Forwarded-For", "X-Real-IP"] {
-        if let Some(value) = headers.get(header_name).and_then(|v| v.to_str().ok()) {
-            let first = value.split(',').next().unwrap_or("").trim();
-            if !first.is_empty() {
-                return first.to_owned();
-            }
-        }
-    }
-    "unknown".into()
+/// Rate-limit and logging key for a request: the real client IP resolved
+/// through trusted proxies, otherwise the TCP peer address.
+fn client_key(
+    trusted_proxies: &TrustedProxies,
+    headers: &HeaderMap,
+    peer: Option<&ConnectInfo<SocketAddr>>,
+) -> String {
+    peer.map_or_else(
+        || "unknown".into(),
+        |ConnectInfo(addr)| trusted_proxies.client_ip(headers, addr.ip()).to_string(),
+    )
 }
 
 /// Shared state for all axum handlers
 #[derive(Clone)]
 pub struct AppState {
     pub provider: Arc<dyn Provider>,
-    pub model: String,
-    pub temperature: f64,
+    /// Settings `/admin/reload` can swap while the gateway keeps running
+    pub settings: Arc<RwLock<GatewaySettings>>,
+    /// Effective config as last loaded, for `/admin/config` and reload diffs
+    pub config: Arc<RwLock<Config>>,
     pub mem: Arc<dyn Memory>,
     pub auto_save: bool,
     pub webhook_secret: Option<Arc<str>>,
@@ -161,6 +346,153 @@ pub struct AppState {
     pub whatsapp: Option<Arc<WhatsAppChannel>>,
     /// `WhatsApp` app secret for webhook signature verification (`X-Hub-Signature-256`)
     pub whatsapp_app_secret: Option<Arc<str>>,
+    /// Slack signing secret for `/slack/commands` (`X-Slack-Signature`); the
+    /// route is off without one
+    pub slack_signing_secret: Option<Arc<str>>,
+    /// Static bearer token required on every route except `/health` and
+    /// the signed platform callbacks
+    pub auth_token: Option<Arc<str>>,
+    pub concurrency: Arc<ConcurrencyLimiter>,
+    /// Request audit log (`[gateway.audit]`), when enabled
+    pub audit: Option<Arc<AuditLog>>,
+    /// Configured channels reachable via `POST /send/{channel}`, by name
+    pub channels: Arc<HashMap<String, Arc<dyn Channel>>>,
+    /// Body size limit for `POST /ingest`
+    pub ingest_max_bytes: usize,
+    /// Chunk size for ingested documents (`memory.chunk_max_tokens`)
+    pub chunk_max_tokens: usize,
+    /// Proxies whose forwarding headers identify the real client
+    pub trusted_proxies: Arc<TrustedProxies>,
+}
+
+impl AppState {
+    /// Snapshot of the current reloadable settings.
+    fn settings(&self) -> GatewaySettings {
+        self.settings
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// Gateway settings that take effect on `/admin/reload` without a restart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GatewaySettings {
+    pub model: String,
+    /// Temperature and `[generation]` defaults for every request
+    pub options: ChatOptions,
+    /// Extra models `/webhook` callers may select (`gateway.allowed_models`)
+    pub allowed_models: Vec<String>,
+    /// Max messages per `/batch` request
+    pub batch_max_items: usize,
+    /// Max `/batch` items in flight at once
+    pub batch_concurrency: usize,
+    /// System prompt for `/webhook` and `/batch` (`system_prompts.webhook`)
+    pub webhook_system_prompt: Option<String>,
+    /// System prompt for `/ws` sessions (`system_prompts.ws`)
+    pub ws_system_prompt: Option<String>,
+    /// System prompt for `WhatsApp` replies (`system_prompts.whatsapp`)
+    pub whatsapp_system_prompt: Option<String>,
+    /// System prompt for Slack slash commands (`system_prompts.slack`)
+    pub slack_system_prompt: Option<String>,
+    /// Route (`webhook`, `ws`, `whatsapp`, `slack`) → prompt template name
+    pub prompt_templates: BTreeMap<String, String>,
+    /// Workspace holding the `prompts/` templates
+    pub workspace_dir: std::path::PathBuf,
+    /// Model, options and access list for `WhatsApp` replies
+    pub whatsapp: ChannelSettings,
+    /// Model, options and access list for Slack slash commands
+    pub slack: ChannelSettings,
+}
+
+/// How a channel answered through the gateway replies, from its
+/// `[channels_config.overrides]` and `[channels_config.access]` entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelSettings {
+    pub model: String,
+    pub options: ChatOptions,
+    pub access: Option<ChannelAccessConfig>,
+}
+
+impl ChannelSettings {
+    fn from_config(config: &Config, channel: &str, default_model: &str) -> Self {
+        Self {
+            model: config
+                .model_for(channel)
+                .unwrap_or(default_model)
+                .to_string(),
+            options: config.chat_options_for(channel),
+            access: config.channels_config.access.get(channel).cloned(),
+        }
+    }
+
+    /// Whether the access list lets these identities (conversation,
+    /// author) through.
+    fn permits(&self, identities: &[&str]) -> bool {
+        let permitted = self
+            .access
+            .as_ref()
+            .is_none_or(|access| access.permits(identities));
+        if !permitted {
+            tracing::warn!("Access denied for {}", identities.join("/"));
+        }
+        permitted
+    }
+
+    /// Reply sent to refused senders, if any.
+    fn denied_reply(&self) -> Option<&str> {
+        self.access
+            .as_ref()
+            .and_then(ChannelAccessConfig::denied_reply)
+    }
+}
+
+impl GatewaySettings {
+    pub fn from_config(config: &Config) -> Self {
+        let model = config
+            .default_model
+            .clone()
+            .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+        let whatsapp = ChannelSettings::from_config(config, "whatsapp", &model);
+        let slack = ChannelSettings::from_config(config, "slack", &model);
+        Self {
+            model,
+            options: config.chat_options(),
+            allowed_models: config.gateway.allowed_models.clone(),
+            batch_max_items: config.gateway.batch_max_items,
+            batch_concurrency: config.gateway.batch_concurrency.max(1),
+            webhook_system_prompt: config.system_prompt_for("webhook").map(str::to_string),
+            ws_system_prompt: config.system_prompt_for("ws").map(str::to_string),
+            whatsapp_system_prompt: config.system_prompt_for("whatsapp").map(str::to_string),
+            slack_system_prompt: config.system_prompt_for("slack").map(str::to_string),
+            prompt_templates: config.channels_config.prompt_templates.clone(),
+            workspace_dir: config.workspace_dir.clone(),
+            whatsapp,
+            slack,
+        }
+    }
+
+    /// System prompt for route `vars.channel`: its prompt template rendered
+    /// for this message when one is configured, else `fallback`.
+    async fn system_prompt(
+        &self,
+        vars: &TemplateVars<'_>,
+        fallback: Option<&str>,
+        mem: &dyn Memory,
+    ) -> Option<String> {
+        let template = self
+            .prompt_templates
+            .get(vars.channel)
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty());
+        if let Some(name) = template {
+            if let Some(prompt) = prompts::render_named(&self.workspace_dir, name, vars, mem).await
+            {
+                return Some(prompt);
+            }
+        }
+        fallback.map(str::to_string)
+    }
 }
 
 /// Run the HTTP gateway using axum with proper HTTP/1.1 compliance.
@@ -181,21 +513,23 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     let actual_port = listener.local_addr()?.port();
     let display_addr = format!("{host}:{actual_port}");
 
-    let provider: Arc<dyn Provider> = Arc::from(providers::create_resilient_provider(
-        config.default_provider.as_deref().unwrap_or("openrouter"),
-        config.api_key.as_deref(),
-        &config.reliability,
-    )?);
-    let model = config
-        .default_model
-        .clone()
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
-    let temperature = config.default_temperature;
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
         &config.memory,
         &config.workspace_dir,
         config.api_key.as_deref(),
     )?);
+    let provider: Arc<dyn Provider> = Arc::from(providers::create_configured_provider(
+        &config,
+        config.default_provider.as_deref().unwrap_or("openrouter"),
+        &GatewaySettings::from_config(&config).model,
+        Arc::clone(&mem),
+    )?);
+    memory::spawn_expiry_sweep(
+        Arc::clone(&mem),
+        config.memory.expiry_sweep_interval_secs,
+        memory::RetentionPolicy::from_config(&config.memory.retention)?
+            .with_trash_purge_after_days(config.memory.trash_purge_after_days),
+    );
 
     // Extract webhook secret for authentication
     let webhook_secret: Option<Arc<str>> = config
@@ -235,6 +569,35 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         })
         .map(Arc::from);
 
+    // Slack signing secret for slash commands
+    // Priority: environment variable > config file
+    let slack_signing_secret: Option<Arc<str>> = std::env::var("ZEROCLAW_SLACK_SIGNING_SECRET")
+        .ok()
+        .and_then(|secret| {
+            let secret = secret.trim();
+            (!secret.is_empty()).then(|| secret.to_owned())
+        })
+        .or_else(|| {
+            config.channels_config.slack.as_ref().and_then(|slack| {
+                slack
+                    .signing_secret
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|secret| !secret.is_empty())
+                    .map(ToOwned::to_owned)
+            })
+        })
+        .map(Arc::from);
+
+    // Static gateway bearer token (optional)
+    let auth_token: Option<Arc<str>> = config
+        .gateway
+        .auth_token
+        .as_deref()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(Arc::from);
+
     // ── Pairing guard ──────────────────────────────────────
     let pairing = Arc::new(PairingGuard::new(
         config.gateway.require_pairing,
@@ -243,10 +606,18 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     let rate_limiter = Arc::new(GatewayRateLimiter::new(
         config.gateway.pair_rate_limit_per_minute,
         config.gateway.webhook_rate_limit_per_minute,
+        config.gateway.webhook_rate_limit_burst,
     ));
     let idempotency_store = Arc::new(IdempotencyStore::new(Duration::from_secs(
         config.gateway.idempotency_ttl_secs.max(1),
     )));
+    let audit = AuditLog::from_config(&config.gateway.audit, &config.workspace_dir)?.map(Arc::new);
+    let trusted_proxies = TrustedProxies::parse(&config.gateway.trusted_proxies)?
+        .with_header(ClientIpHeader::parse(&config.gateway.client_ip_header)?);
+    let concurrency = Arc::new(ConcurrencyLimiter::new(
+        config.gateway.max_concurrent_requests,
+        config.gateway.max_queued_requests,
+    ));
 
     // ── Tunnel ────────────────────────────────────────────────
     let tunnel = crate::tunnel::create_tunnel(&config.tunnel)?;
@@ -272,10 +643,32 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     }
     println!("  POST /pair      — pair a new client (X-Pairing-Code header)");
     println!("  POST /webhook   — {{\"message\": \"your prompt\"}}");
+    println!("                    (add \"stream\": true or Accept: text/event-stream for SSE)");
+    println!("  GET  /ws        — WebSocket chat session (streams replies)");
+    println!("  GET  /memory    — list memories (?category=, ?tag= or ?session_id=)");
+    println!("  GET  /memory/search?q=... — search memories (?tag= to filter)");
+    println!("  GET|PUT|DELETE /memory/{{key}} — read, write, forget a memory");
+    println!("  POST /ingest    — chunk a document into memory (text or multipart)");
+    let channels: HashMap<String, Arc<dyn Channel>> =
+        crate::channels::build_channels(&config.channels_config)
+            .into_iter()
+            .map(|ch| (ch.name().to_string(), ch))
+            .collect();
+    if !channels.is_empty() {
+        let mut names: Vec<&str> = channels.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        println!(
+            "  POST /send/{{channel}} — deliver a message via {}",
+            names.join(", ")
+        );
+    }
     if whatsapp_channel.is_some() {
         println!("  GET  /whatsapp  — Meta webhook verification");
         println!("  POST /whatsapp  — WhatsApp message webhook");
     }
+    if slack_signing_secret.is_some() {
+        println!("  POST /slack/commands — Slack slash commands");
+    }
     println!("  GET  /health    — health check");
     if let Some(code) = pairing.pairing_code() {
         println!();
@@ -292,6 +685,30 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     if webhook_secret.is_some() {
         println!("  🔒 Webhook secret: ENABLED");
     }
+    if auth_token.is_some() {
+        println!("  🔒 Auth token: ENABLED (all routes except /health)");
+    }
+    if let Some(ref log) = audit {
+        println!("  📝 Audit log: {}", log.path().display());
+    }
+    if !config.gateway.cors.allowed_origins.is_empty() {
+        println!(
+            "  🌍 CORS origins: {}",
+            config.gateway.cors.allowed_origins.join(", ")
+        );
+    }
+    match providers::check_provider_health(
+        provider.as_ref(),
+        Duration::from_secs(READINESS_PROVIDER_TIMEOUT_SECS),
+    )
+    .await
+    {
+        Ok(()) => println!("  ✅ Provider: credentials accepted"),
+        Err(e) => {
+            println!("  ❌ Provider check failed: {e}");
+            println!("     Requests will fail until this is fixed (see GET /readyz).");
+        }
+    }
     println!("  Press Ctrl+C to stop.\n");
 
     crate::health::mark_component_ok("gateway");
@@ -299,8 +716,8 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     // Build shared state
     let state = AppState {
         provider,
-        model,
-        temperature,
+        settings: Arc::new(RwLock::new(GatewaySettings::from_config(&config))),
+        config: Arc::new(RwLock::new(config.clone())),
         mem,
         auto_save: config.memory.auto_save,
         webhook_secret,
@@ -309,52 +726,424 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         idempotency_store,
         whatsapp: whatsapp_channel,
         whatsapp_app_secret,
+        slack_signing_secret,
+        auth_token,
+        concurrency,
+        audit,
+        channels: Arc::new(channels),
+        ingest_max_bytes: config.gateway.ingest_max_bytes,
+        chunk_max_tokens: config.memory.chunk_max_tokens,
+        trusted_proxies: Arc::new(trusted_proxies),
     };
 
-    // Build router with middleware
-    let app = Router::new()
+    let mut app = build_router(state);
+    if let Some(cors) = build_cors_layer(&config.gateway.cors)? {
+        app = app.layer(cors);
+    }
+
+    // Run the server
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// AXUM HANDLERS
+// ══════════════════════════════════════════════════════════════════════════════
+
+/// Build the gateway router with all routes and middleware.
+fn build_router(state: AppState) -> Router {
+    // Documents for /ingest get their own, larger body limit.
+    let ingest = Router::new()
+        .route("/ingest", post(handle_ingest))
+        .layer(DefaultBodyLimit::max(state.ingest_max_bytes))
+        .layer(RequestBodyLimitLayer::new(state.ingest_max_bytes));
+    let sync = Router::new()
+        .route(
+            "/memory/sync",
+            get(handle_memory_sync_fetch).post(handle_memory_sync_apply),
+        )
+        .layer(DefaultBodyLimit::max(MAX_SYNC_BODY_SIZE))
+        .layer(RequestBodyLimitLayer::new(MAX_SYNC_BODY_SIZE));
+
+    Router::new()
         .route("/health", get(handle_health))
+        .route("/livez", get(handle_livez))
+        .route("/readyz", get(handle_readyz))
         .route("/pair", post(handle_pair))
         .route("/webhook", post(handle_webhook))
+        .route("/batch", post(handle_batch))
+        .route("/ws", get(handle_ws))
+        .route("/memory", get(handle_memory_list))
+        .route("/memory/search", get(handle_memory_search))
+        .route(
+            "/memory/:key",
+            get(handle_memory_get)
+                .put(handle_memory_put)
+                .patch(handle_memory_patch)
+                .delete(handle_memory_delete),
+        )
+        .route("/send/:channel", post(handle_send))
+        .route("/usage", get(handle_usage))
+        .route("/admin/config", get(admin::handle_admin_config))
+        .route("/admin/reload", post(admin::handle_admin_reload))
         .route("/whatsapp", get(handle_whatsapp_verify))
         .route("/whatsapp", post(handle_whatsapp_message))
-        .with_state(state)
+        .route("/slack/commands", post(handle_slack_command))
         .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
+        .merge(ingest)
+        .merge(sync)
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_auth_token,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            limit_concurrency,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            audit_requests,
+        ))
+        .with_state(state)
         .layer(TimeoutLayer::with_status_code(
             StatusCode::REQUEST_TIMEOUT,
             Duration::from_secs(REQUEST_TIMEOUT_SECS),
-        ));
+        ))
+}
 
-    // Run the server
-    axum::serve(listener, app).await?;
+/// Build the CORS layer from `[gateway.cors]`. Returns `None` when no origins
+/// are configured. It wraps the whole router so preflight `OPTIONS` requests
+/// are answered before auth middleware runs.
+fn build_cors_layer(cors: &GatewayCorsConfig) -> Result<Option<CorsLayer>> {
+    if cors.allowed_origins.is_empty() {
+        return Ok(None);
+    }
 
-    Ok(())
+    let origins = if cors.allowed_origins.iter().any(|o| o.trim() == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = cors
+            .allowed_origins
+            .iter()
+            .map(|o| {
+                o.trim()
+                    .parse::<header::HeaderValue>()
+                    .map_err(|e| anyhow::anyhow!("Invalid CORS origin {o:?}: {e}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
+
+    let methods = cors
+        .allowed_methods
+        .iter()
+        .map(|m| {
+            m.trim()
+                .to_ascii_uppercase()
+                .parse::<axum::http::Method>()
+                .map_err(|e| anyhow::anyhow!("Invalid CORS method {m:?}: {e}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let headers = cors
+        .allowed_headers
+        .iter()
+        .map(|h| {
+            h.trim()
+                .parse::<header::HeaderName>()
+                .map_err(|e| anyhow::anyhow!("Invalid CORS header {h:?}: {e}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .expose_headers([header::RETRY_AFTER])
+            .max_age(Duration::from_secs(cors.max_age_secs)),
+    ))
 }
 
-// ══════════════════════════════════════════════════════════════════════════════
-// AXUM HANDLERS
-// ══════════════════════════════════════════════════════════════════════════════
+/// Extract the bearer token from an `Authorization` header (empty if absent).
+fn bearer_token(headers: &HeaderMap) -> &str {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|auth| auth.strip_prefix("Bearer "))
+        .unwrap_or("")
+}
+
+/// True when `token` matches the configured static `gateway.auth_token`.
+fn matches_auth_token(state: &AppState, token: &str) -> bool {
+    state
+        .auth_token
+        .as_deref()
+        .is_some_and(|expected| constant_time_eq(token, expected))
+}
+
+/// Routes reachable without the static auth token: the health probe and
+/// the `WhatsApp` and Slack callbacks, which cannot carry custom headers and
+/// are authenticated by their request signatures instead.
+fn auth_token_exempt(path: &str) -> bool {
+    is_health_probe(path) || matches!(path, "/whatsapp" | "/slack/commands")
+}
+
+/// Health and probe routes stay public and bypass load shedding.
+fn is_health_probe(path: &str) -> bool {
+    matches!(path, "/health" | "/livez" | "/readyz")
+}
+
+/// Pairing check shared by `/webhook` and `/ws`: passes when pairing is off,
+/// or the bearer is a paired token or the static auth token.
+fn pairing_authorized(state: &AppState, headers: &HeaderMap) -> bool {
+    if !state.pairing.require_pairing() {
+        return true;
+    }
+    let token = bearer_token(headers);
+    state.pairing.is_authenticated(token) || matches_auth_token(state, token)
+}
+
+/// Middleware writing one audit record per request when `[gateway.audit]`
+/// is enabled. Runs outside auth and load shedding so rejected requests are
+/// recorded too.
+async fn audit_requests(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    let Some(log) = state.audit.clone() else {
+        return next.run(request).await;
+    };
+
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let peer = client_key(
+        &state.trusted_proxies,
+        request.headers(),
+        request.extensions().get::<ConnectInfo<SocketAddr>>(),
+    );
+
+    let response = next.run(request).await;
+
+    let context = response.extensions().get::<AuditContext>();
+    let usage = context.and_then(|c| c.usage);
+    let record = AuditRecord {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        peer,
+        method,
+        path,
+        status: response.status().as_u16(),
+        latency_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        prompt: context.map(|c| log.truncate_prompt(&c.prompt)),
+        model: context.map(|c| c.model.clone()),
+        prompt_tokens: usage.map(|u| u.prompt_tokens),
+        completion_tokens: usage.map(|u| u.completion_tokens),
+    };
+    if let Err(e) = log.append(&record) {
+        tracing::warn!("Failed to write gateway audit record: {e}");
+    }
+
+    response
+}
+
+/// Middleware applying `gateway.max_concurrent_requests` and
+/// `gateway.max_queued_requests`. `/health` is never queued so liveness
+/// probes keep answering under load. The slot is held until the handler
+/// returns its response head; streamed bodies do not count against it.
+async fn limit_concurrency(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    if is_health_probe(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let Ok(_permit) = state.concurrency.acquire().await else {
+        tracing::warn!(
+            "Gateway saturated — shedding {} with 503",
+            request.uri().path()
+        );
+        let err = serde_json::json!({
+            "error": "Server busy. Please retry shortly.",
+            "retry_after": 1,
+        });
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, "1")],
+            Json(err),
+        )
+            .into_response();
+    };
+
+    next.run(request).await
+}
+
+/// Middleware enforcing `gateway.auth_token` when configured.
+async fn require_auth_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    if state.auth_token.is_none() || auth_token_exempt(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    if matches_auth_token(&state, bearer_token(request.headers())) {
+        return next.run(request).await;
+    }
+
+    tracing::warn!(
+        "Gateway: rejected {} — missing or invalid auth token",
+        request.uri().path()
+    );
+    let err = serde_json::json!({
+        "error": "Unauthorized — send Authorization: Bearer <gateway.auth_token>"
+    });
+    (StatusCode::UNAUTHORIZED, Json(err)).into_response()
+}
 
 /// GET /health — always public (no secrets leaked)
 async fn handle_health(State(state): State<AppState>) -> impl IntoResponse {
     let body = serde_json::json!({
         "status": "ok",
         "paired": state.pairing.is_paired(),
+        "provider": provider_status(),
+        "throttle": crate::providers::throttle::snapshot(),
         "runtime": crate::health::snapshot_json(),
     });
     Json(body)
 }
 
+/// Outcome of the last provider health check (startup or `/readyz`):
+/// `"ok"`, `"error"`, or `"unknown"` before the first check.
+fn provider_status() -> String {
+    crate::health::snapshot()
+        .components
+        .remove("provider")
+        .map_or_else(|| "unknown".into(), |c| c.status)
+}
+
+/// Liveness probe body: the process is up and serving requests.
+#[derive(Debug, Serialize)]
+struct LivenessReport {
+    status: &'static str,
+    pid: u32,
+    uptime_seconds: u64,
+}
+
+/// Result of one readiness check.
+#[derive(Debug, Serialize)]
+struct ComponentCheck {
+    ready: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_ms: Option<u64>,
+}
+
+/// Readiness probe body: every dependency needed to serve traffic.
+#[derive(Debug, Serialize)]
+struct ReadinessReport {
+    ready: bool,
+    provider: ComponentCheck,
+    memory: ComponentCheck,
+    /// Supervised channel listeners (`channel:<name>`) known to this process
+    channels: BTreeMap<String, ComponentCheck>,
+}
+
+/// GET /livez — liveness probe; never touches dependencies
+async fn handle_livez() -> impl IntoResponse {
+    let snapshot = crate::health::snapshot();
+    Json(LivenessReport {
+        status: "ok",
+        pid: snapshot.pid,
+        uptime_seconds: snapshot.uptime_seconds,
+    })
+}
+
+/// GET /readyz — readiness probe; 503 unless provider, memory and every
+/// channel listener are healthy
+async fn handle_readyz(State(state): State<AppState>) -> impl IntoResponse {
+    let report = readiness_report(&state).await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+async fn readiness_report(state: &AppState) -> ReadinessReport {
+    let started = Instant::now();
+    let provider_result = providers::check_provider_health(
+        state.provider.as_ref(),
+        Duration::from_secs(READINESS_PROVIDER_TIMEOUT_SECS),
+    )
+    .await;
+    let provider = ComponentCheck {
+        ready: provider_result.is_ok(),
+        error: provider_result.err().map(|e| e.to_string()),
+        latency_ms: Some(elapsed_ms(started)),
+    };
+
+    let started = Instant::now();
+    let memory_ok = state.mem.health_check().await;
+    let memory = ComponentCheck {
+        ready: memory_ok,
+        error: (!memory_ok).then(|| format!("{} backend unhealthy", state.mem.name())),
+        latency_ms: Some(elapsed_ms(started)),
+    };
+
+    let channels: BTreeMap<String, ComponentCheck> = crate::health::snapshot()
+        .components
+        .into_iter()
+        .filter_map(|(name, health)| {
+            let channel = name.strip_prefix("channel:")?.to_string();
+            Some((
+                channel,
+                ComponentCheck {
+                    ready: health.status == "ok",
+                    error: health.last_error,
+                    latency_ms: None,
+                },
+            ))
+        })
+        .collect();
+
+    let ready = provider.ready && memory.ready && channels.values().all(|c| c.ready);
+    ReadinessReport {
+        ready,
+        provider,
+        memory,
+        channels,
+    }
+}
+
+fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
 /// POST /pair — exchange one-time code for bearer token
-async fn handle_pair(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
-    let client_key = client_key_from_headers(&headers);
+async fn handle_pair(
+    State(state): State<AppState>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let client_key = client_key(&state.trusted_proxies, &headers, peer.as_ref());
     if !state.rate_limiter.allow_pair(&client_key) {
         tracing::warn!("/pair rate limit exceeded for key: {client_key}");
-        let err = serde_json::json!({
-            "error": "Too many pairing requests. Please retry later.",
-            "retry_after": RATE_LIMIT_WINDOW_SECS,
-        });
-        return (StatusCode::TOO_MANY_REQUESTS, Json(err));
+        return too_many_requests(
+            "Too many pairing requests. Please retry later.",
+            Duration::from_secs(RATE_LIMIT_WINDOW_SECS),
+        );
     }
 
     let code = headers
@@ -370,22 +1159,21 @@ async fn handle_pair(State(state): State<AppState>, headers: HeaderMap) -> impl
                 "token": token,
                 "message": "Save this token — use it as Authorization: Bearer <token>"
             });
-            (StatusCode::OK, Json(body))
+            (StatusCode::OK, Json(body)).into_response()
         }
         Ok(None) => {
             tracing::warn!("🔐 Pairing attempt with invalid code");
             let err = serde_json::json!({"error": "Invalid pairing code"});
-            (StatusCode::FORBIDDEN, Json(err))
+            (StatusCode::FORBIDDEN, Json(err)).into_response()
         }
         Err(lockout_secs) => {
             tracing::warn!(
                 "🔐 Pairing locked out — too many failed attempts ({lockout_secs}s remaining)"
             );
-            let err = serde_json::json!({
-                "error": format!("Too many failed attempts. Try again in {lockout_secs}s."),
-                "retry_after": lockout_secs
-            });
-            (StatusCode::TOO_MANY_REQUESTS, Json(err))
+            too_many_requests(
+                &format!("Too many failed attempts. Try again in {lockout_secs}s."),
+                Duration::from_secs(lockout_secs),
+            )
         }
     }
 }
@@ -394,54 +1182,205 @@ async fn handle_pair(State(state): State<AppState>, headers: HeaderMap) -> impl
 #[derive(serde::Deserialize)]
 pub struct WebhookBody {
     pub message: String,
+    /// Stream the reply as Server-Sent Events instead of a single JSON body.
+    /// Equivalent to sending `Accept: text/event-stream`.
+    #[serde(default)]
+    pub stream: bool,
+    /// Conversation to continue. Recent turns for this id are loaded from
+    /// memory and sent along with the message; the reply is appended.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Model for this request; must be the default or in `gateway.allowed_models`.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub overrides: GenerationOverrides,
+    /// Images for vision-capable models: `{"type": "url", "url": ...}` or
+    /// `{"type": "base64", "media_type": "image/png", "data": ...}`.
+    #[serde(default)]
+    pub images: Vec<ImageInput>,
+    /// JSON Schema the reply must match. The parsed value is returned as
+    /// `json`; not available with streaming.
+    #[serde(default)]
+    pub json_schema: Option<serde_json::Value>,
 }
 
-/// POST /webhook — main webhook endpoint
-async fn handle_webhook(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    body: Result<Json<WebhookBody>, axum::extract::rejection::JsonRejection>,
-) -> impl IntoResponse {
-    let client_key = client_key_from_headers(&headers);
-    if !state.rate_limiter.allow_webhook(&client_key) {
-        tracing::warn!("/webhook rate limit exceeded for key: {client_key}");
-        let err = serde_json::json!({
-            "error": "Too many webhook requests. Please retry later.",
-            "retry_after": RATE_LIMIT_WINDOW_SECS,
-        });
-        return (StatusCode::TOO_MANY_REQUESTS, Json(err));
-    }
-
-    // ── Bearer token auth (pairing) ──
-    if state.pairing.require_pairing() {
-        let auth = headers
-            .get(header::AUTHORIZATION)
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-        let token = auth.strip_prefix("Bearer ").unwrap_or("");
-        if !state.pairing.is_authenticated(token) {
-            tracing::warn!("Webhook: rejected — not paired / invalid bearer token");
-            let err = serde_json::json!({
-                "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
-            });
-            return (StatusCode::UNAUTHORIZED, Json(err));
-        }
-    }
-
-    // ── Webhook secret auth (optional, additional layer) ──
-    if let Some(ref secret) = state.webhook_secret {
-        let header_val = headers
-            .get("X-Webhook-Secret")
-            .and_then(|v| v.to_str().ok());
-        match header_val {
-            Some(val) if constant_time_eq(val, secret.as_ref()) => {}
-            _ => {
-                tracing::warn!("Webhook: rejected request — invalid or missing X-Webhook-Secret");
-                let err = serde_json::json!({"error": "Unauthorized — invalid or missing X-Webhook-Secret header"});
-                return (StatusCode::UNAUTHORIZED, Json(err));
+/// Reject images a provider could not use: non-HTTP(S) URLs and inline
+/// data without an `image/*` media type.
+fn validate_images(images: &[ImageInput]) -> Result<(), String> {
+    for image in images {
+        match image {
+            ImageInput::Url { url }
+                if url.starts_with("https://") || url.starts_with("http://") => {}
+            ImageInput::Url { .. } => return Err("image url must be http(s)".into()),
+            ImageInput::Base64 { media_type, data }
+                if media_type.starts_with("image/") && !data.is_empty() => {}
+            ImageInput::Base64 { .. } => {
+                return Err("base64 images need an image/* media_type and data".into())
             }
         }
     }
+    Ok(())
+}
+
+/// Structured replies are validated as a whole, so they cannot be streamed.
+fn validate_json_schema(
+    schema: Option<&serde_json::Value>,
+    streaming: bool,
+) -> Result<(), &'static str> {
+    match schema {
+        Some(_) if streaming => Err("json_schema cannot be combined with streaming"),
+        Some(schema) if !schema.is_object() => Err("json_schema must be a JSON Schema object"),
+        _ => Ok(()),
+    }
+}
+
+/// Per-request generation settings accepted by `/webhook` and `/batch`.
+/// Unset fields keep the `default_temperature` / `[generation]` values.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct GenerationOverrides {
+    /// Sampling temperature (0.0–2.0).
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// Upper bound on reply tokens.
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    /// Nucleus sampling probability mass (0.0–1.0).
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    /// Stop sequences; replaces `generation.stop` when present.
+    #[serde(default)]
+    pub stop: Option<Vec<String>>,
+    /// Sampling seed for reproducible output.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// Model, generation options and system prompt used for one webhook request.
+struct WebhookGeneration {
+    model: String,
+    options: ChatOptions,
+    system_prompt: Option<String>,
+}
+
+impl WebhookGeneration {
+    /// Apply the request's overrides on top of the gateway defaults,
+    /// rejecting models outside the allowlist and out-of-range values.
+    fn resolve(
+        state: &AppState,
+        model: Option<&str>,
+        overrides: &GenerationOverrides,
+    ) -> Result<Self, String> {
+        let settings = state.settings();
+        let model = match model.map(str::trim) {
+            None | Some("") => settings.model,
+            Some(model)
+                if model == settings.model
+                    || settings.allowed_models.iter().any(|m| m == model) =>
+            {
+                model.to_string()
+            }
+            Some(model) => return Err(format!("Model not allowed: {model}")),
+        };
+
+        let mut options = settings.options;
+        match overrides.temperature {
+            None => {}
+            Some(t) if (0.0..=2.0).contains(&t) => options.temperature = t,
+            Some(_) => return Err("temperature must be between 0.0 and 2.0".into()),
+        }
+        match overrides.max_tokens {
+            None => {}
+            Some(0) => return Err("max_tokens must be greater than 0".into()),
+            Some(n) => options.max_tokens = Some(n),
+        }
+        match overrides.top_p {
+            None => {}
+            Some(p) if (0.0..=1.0).contains(&p) => options.top_p = Some(p),
+            Some(_) => return Err("top_p must be between 0.0 and 1.0".into()),
+        }
+        if let Some(stop) = &overrides.stop {
+            options.stop.clone_from(stop);
+        }
+        if overrides.seed.is_some() {
+            options.seed = overrides.seed;
+        }
+
+        Ok(Self {
+            model,
+            options,
+            system_prompt: settings.webhook_system_prompt,
+        })
+    }
+
+    /// System prompt for `message`: the `webhook` prompt template rendered
+    /// for it when one is configured, else the resolved system prompt.
+    async fn system_prompt_for(&self, state: &AppState, message: &str) -> Option<String> {
+        let vars = TemplateVars {
+            channel: "webhook",
+            sender: None,
+            message,
+        };
+        state
+            .settings()
+            .system_prompt(&vars, self.system_prompt.as_deref(), state.mem.as_ref())
+            .await
+    }
+}
+
+/// Rate limit, pairing, and `X-Webhook-Secret` checks shared by `/webhook`
+/// and `/batch`. Returns the rejection response when the caller may not proceed.
+fn authorize_webhook(
+    state: &AppState,
+    peer: Option<&ConnectInfo<SocketAddr>>,
+    headers: &HeaderMap,
+) -> Option<axum::response::Response> {
+    let client_key = client_key(&state.trusted_proxies, headers, peer);
+    if let Err(retry_after) = state.rate_limiter.check_webhook(&client_key) {
+        tracing::warn!("/webhook rate limit exceeded for key: {client_key}");
+        return Some(too_many_requests(
+            "Too many webhook requests. Please retry later.",
+            retry_after,
+        ));
+    }
+
+    // ── Bearer token auth (pairing) ──
+    if !pairing_authorized(state, headers) {
+        tracing::warn!("Webhook: rejected — not paired / invalid bearer token");
+        let err = serde_json::json!({
+            "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
+        });
+        return Some((StatusCode::UNAUTHORIZED, Json(err)).into_response());
+    }
+
+    // ── Webhook secret auth (optional, additional layer) ──
+    if let Some(ref secret) = state.webhook_secret {
+        let header_val = headers
+            .get("X-Webhook-Secret")
+            .and_then(|v| v.to_str().ok());
+        match header_val {
+            Some(val) if constant_time_eq(val, secret.as_ref()) => {}
+            _ => {
+                tracing::warn!("Webhook: rejected request — invalid or missing X-Webhook-Secret");
+                let err = serde_json::json!({"error": "Unauthorized — invalid or missing X-Webhook-Secret header"});
+                return Some((StatusCode::UNAUTHORIZED, Json(err)).into_response());
+            }
+        }
+    }
+
+    None
+}
+
+/// POST /webhook — main webhook endpoint
+async fn handle_webhook(
+    State(state): State<AppState>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    body: Result<Json<WebhookBody>, axum::extract::rejection::JsonRejection>,
+) -> impl IntoResponse {
+    if let Some(rejection) = authorize_webhook(&state, peer.as_ref(), &headers) {
+        return rejection;
+    }
 
     // ── Parse body ──
     let Json(webhook_body) = match body {
@@ -450,7 +1389,7 @@ async fn handle_webhook(
             let err = serde_json::json!({
                 "error": format!("Invalid JSON: {e}. Expected: {{\"message\": \"...\"}}")
             });
-            return (StatusCode::BAD_REQUEST, Json(err));
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
         }
     };
 
@@ -468,377 +1407,4036 @@ async fn handle_webhook(
                 "idempotent": true,
                 "message": "Request already processed for this idempotency key"
             });
-            return (StatusCode::OK, Json(body));
+            return (StatusCode::OK, Json(body)).into_response();
+        }
+    }
+
+    let mut generation = match WebhookGeneration::resolve(
+        &state,
+        webhook_body.model.as_deref(),
+        &webhook_body.overrides,
+    ) {
+        Ok(generation) => generation,
+        Err(e) => {
+            let err = serde_json::json!({ "error": e });
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
         }
+    };
+    if let Err(e) = validate_images(&webhook_body.images) {
+        let err = serde_json::json!({ "error": e });
+        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+    }
+    let streaming = webhook_body.stream || wants_event_stream(&headers);
+    if let Err(e) = validate_json_schema(webhook_body.json_schema.as_ref(), streaming) {
+        let err = serde_json::json!({ "error": e });
+        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
     }
 
     let message = &webhook_body.message;
+    let images = &webhook_body.images;
+    generation.system_prompt = generation.system_prompt_for(&state, message).await;
 
     if state.auto_save {
-        let _ = state
-            .mem
-            .store("webhook_msg", message, MemoryCategory::Conversation)
-            .await;
+        let session_id = webhook_body
+            .session_id
+            .as_deref()
+            .filter(|id| valid_session_id(id));
+        let _ = match session_id {
+            Some(id) => {
+                let key = format!("webhook_msg:{id}:{}", uuid::Uuid::new_v4());
+                state
+                    .mem
+                    .store_in_session(id, &key, message, MemoryCategory::Conversation)
+                    .await
+            }
+            None => {
+                state
+                    .mem
+                    .store("webhook_msg", message, MemoryCategory::Conversation)
+                    .await
+            }
+        };
     }
 
-    match state
-        .provider
-        .chat(message, &state.model, state.temperature)
-        .await
-    {
-        Ok(response) => {
-            let body = serde_json::json!({"response": response, "model": state.model});
-            (StatusCode::OK, Json(body))
+    let mut session = None;
+    if let Some(id) = webhook_body.session_id.as_deref() {
+        session = WebhookSession::open(state.mem.as_ref(), id, message).await;
+        if session.is_none() {
+            let err = serde_json::json!({
+                "error": "Invalid session_id: use 1-128 characters from [A-Za-z0-9_.:-]"
+            });
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+        }
+    }
+
+    let mut audit = AuditContext {
+        prompt: message.clone(),
+        model: generation.model.clone(),
+        usage: None,
+    };
+    let mut response = if streaming {
+        stream_webhook_response(&state, &generation, message, images, session).await
+    } else {
+        let schema = webhook_body.json_schema.as_ref();
+        complete_webhook_response(&state, &generation, message, images, session, schema).await
+    };
+    audit.usage = response.extensions_mut().remove::<TokenUsage>();
+    response.extensions_mut().insert(audit);
+    response
+}
+
+/// Blocking (non-streaming) webhook reply. With `json_schema` the reply is
+/// validated (and re-asked on mismatch) by [`providers::structured`]. The
+/// reply's token usage rides along in the response extensions for the audit log.
+async fn complete_webhook_response(
+    state: &AppState,
+    generation: &WebhookGeneration,
+    message: &str,
+    images: &[ImageInput],
+    session: Option<WebhookSession>,
+    json_schema: Option<&serde_json::Value>,
+) -> axum::response::Response {
+    let history = webhook_messages(generation, message, images, session.as_ref());
+    let result = match (json_schema, history) {
+        (Some(schema), history) => {
+            let messages = history.unwrap_or_else(|| {
+                let turn = [ChatMessage::user(message)];
+                with_system_message(generation.system_prompt.as_deref(), &turn).into_owned()
+            });
+            providers::structured::chat_structured(
+                state.provider.as_ref(),
+                &messages,
+                schema,
+                &generation.model,
+                &generation.options,
+            )
+            .await
+            .map(|structured| (structured.reply, Some(structured.value)))
+        }
+        (None, None) => state
+            .provider
+            .chat_with_system(
+                generation.system_prompt.as_deref(),
+                message,
+                &generation.model,
+                &generation.options,
+            )
+            .await
+            .map(|reply| (reply, None)),
+        (None, Some(history)) => state
+            .provider
+            .chat_with_history(&history, &generation.model, &generation.options)
+            .await
+            .map(|reply| (reply, None)),
+    };
+
+    match result {
+        Ok((reply, json)) => {
+            record_usage(state, "webhook", &generation.model, &reply);
+            let model = reply.model.as_deref().unwrap_or(&generation.model);
+            let mut body = serde_json::json!({"response": reply.text, "model": model});
+            if let Some(json) = json {
+                body["json"] = json;
+            }
+            if let Some(usage) = reply.usage_json() {
+                body["usage"] = usage;
+            }
+            if let Some(session) = session {
+                body["session_id"] = serde_json::json!(session.id);
+                session.finish(state.mem.as_ref(), reply.text).await;
+            }
+            let mut response = (StatusCode::OK, Json(body)).into_response();
+            if let Some(usage) = reply.usage {
+                response.extensions_mut().insert(usage);
+            }
+            response
         }
         Err(e) => {
+            if let Some(invalid) = e.downcast_ref::<StructuredOutputError>() {
+                record_usage(state, "webhook", &generation.model, &invalid.last_reply);
+                let err = serde_json::json!({
+                    "error": invalid.to_string(),
+                    "response": invalid.last_reply.text,
+                });
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(err)).into_response();
+            }
             tracing::error!(
                 "Webhook provider error: {}",
                 providers::sanitize_api_error(&e.to_string())
             );
             let err = serde_json::json!({"error": "LLM request failed"});
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(err))
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response()
         }
     }
 }
 
-/// `WhatsApp` verification query params
 #[derive(serde::Deserialize)]
-pub struct WhatsAppVerifyQuery {
-    #[serde(rename = "hub.mode")]
-    pub mode: Option<String>,
-    #[serde(rename = "hub.verify_token")]
-    pub verify_token: Option<String>,
-    #[serde(rename = "hub.challenge")]
-    pub challenge: Option<String>,
+pub struct BatchBody {
+    pub messages: Vec<String>,
+    /// Same per-request overrides as `/webhook`, applied to every item.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub overrides: GenerationOverrides,
 }
 
-/// GET /whatsapp — Meta webhook verification
-async fn handle_whatsapp_verify(
+/// POST /batch — run several independent prompts with bounded parallelism.
+/// Results come back in input order; a failed item gets an `error` object
+/// instead of failing the whole batch.
+async fn handle_batch(
     State(state): State<AppState>,
-    Query(params): Query<WhatsAppVerifyQuery>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    body: Result<Json<BatchBody>, axum::extract::rejection::JsonRejection>,
 ) -> impl IntoResponse {
-    let Some(ref wa) = state.whatsapp else {
-        return (StatusCode::NOT_FOUND, "WhatsApp not configured".to_string());
+    if let Some(rejection) = authorize_webhook(&state, peer.as_ref(), &headers) {
+        return rejection;
+    }
+
+    let Json(batch) = match body {
+        Ok(b) => b,
+        Err(e) => {
+            let err = serde_json::json!({
+                "error": format!("Invalid JSON: {e}. Expected: {{\"messages\": [\"...\"]}}")
+            });
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+        }
     };
 
-    // Verify the token matches (constant-time comparison to prevent timing attacks)
-    let token_matches = params
-        .verify_token
-        .as_deref()
-        .is_some_and(|t| constant_time_eq(t, wa.verify_token()));
-    if params.mode.as_deref() == Some("subscribe") && token_matches {
-        if let Some(ch) = params.challenge {
-            tracing::info!("WhatsApp webhook verified successfully");
-            return (StatusCode::OK, ch);
+    let max_items = state.settings().batch_max_items;
+    if batch.messages.is_empty() || batch.messages.len() > max_items {
+        let err = serde_json::json!({
+            "error": format!("messages must contain 1-{max_items} items")
+        });
+        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+    }
+
+    let generation =
+        match WebhookGeneration::resolve(&state, batch.model.as_deref(), &batch.overrides) {
+            Ok(generation) => generation,
+            Err(e) => {
+                let err = serde_json::json!({ "error": e });
+                return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+            }
+        };
+
+    let mut audit = AuditContext {
+        prompt: batch.messages.join("\n"),
+        model: generation.model.clone(),
+        usage: None,
+    };
+    let (results, usage) = run_batch(&state, &generation, batch.messages).await;
+    audit.usage = usage;
+    let body = serde_json::json!({"model": generation.model, "results": results});
+    let mut response = (StatusCode::OK, Json(body)).into_response();
+    response.extensions_mut().insert(audit);
+    response
+}
+
+/// Fan `messages` out to the provider, keeping at most
+/// `batch_concurrency` calls in flight. Also returns the token usage summed
+/// over the items that reported it.
+async fn run_batch(
+    state: &AppState,
+    generation: &WebhookGeneration,
+    messages: Vec<String>,
+) -> (Vec<serde_json::Value>, Option<TokenUsage>) {
+    let mut results = vec![serde_json::Value::Null; messages.len()];
+    let mut usage: Option<TokenUsage> = None;
+    let mut pending = messages.into_iter().enumerate();
+    let mut in_flight = tokio::task::JoinSet::new();
+    let batch_concurrency = state.settings().batch_concurrency;
+
+    loop {
+        while in_flight.len() < batch_concurrency {
+            let Some((index, message)) = pending.next() else {
+                break;
+            };
+            let provider = Arc::clone(&state.provider);
+            let model = generation.model.clone();
+            let options = generation.options.clone();
+            let system_prompt = generation.system_prompt_for(state, &message).await;
+            in_flight.spawn(async move {
+                let result = provider
+                    .chat_with_system(system_prompt.as_deref(), &message, &model, &options)
+                    .await;
+                (index, result)
+            });
         }
-        return (StatusCode::BAD_REQUEST, "Missing hub.challenge".to_string());
+
+        let Some(joined) = in_flight.join_next().await else {
+            break;
+        };
+        let (index, result) = match joined {
+            Ok(done) => done,
+            Err(e) => {
+                tracing::error!("Batch task failed: {e}");
+                continue;
+            }
+        };
+        results[index] = match result {
+            Ok(reply) => {
+                record_usage(state, "batch", &generation.model, &reply);
+                if let Some(item) = reply.usage {
+                    let total = usage.get_or_insert_with(TokenUsage::default);
+                    total.prompt_tokens += item.prompt_tokens;
+                    total.completion_tokens += item.completion_tokens;
+                }
+                let mut item = serde_json::json!({"index": index, "response": reply.text});
+                if let Some(model) = &reply.model {
+                    item["model"] = serde_json::json!(model);
+                }
+                if let Some(usage) = reply.usage_json() {
+                    item["usage"] = usage;
+                }
+                item
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Batch item {index} provider error: {}",
+                    providers::sanitize_api_error(&e.to_string())
+                );
+                serde_json::json!({"index": index, "error": {"message": "LLM request failed"}})
+            }
+        };
     }
 
-    tracing::warn!("WhatsApp webhook verification failed — token mismatch");
-    (StatusCode::FORBIDDEN, "Forbidden".to_string())
+    // A panicked task leaves its slot unset; report it like a provider error.
+    for (index, slot) in results.iter_mut().enumerate() {
+        if slot.is_null() {
+            *slot = serde_json::json!({"index": index, "error": {"message": "LLM request failed"}});
+        }
+    }
+    (results, usage)
 }
 
-/// Verify `WhatsApp` webhook signature (`X-Hub-Signature-256`).
-/// Returns true if the signature is valid, false otherwise.
-/// See: <https://developers.facebook.com/docs/graph-api/webhooks/getting-started#verification-requests>
-pub fn verify_whatsapp_signature(app_secret: &str, body: &[u8], signature_header: &str) -> bool {
-    use hmac::{Hmac, Mac};
-    use sha2::Sha256;
+/// State threaded through the SSE event stream.
+struct WebhookStreamState {
+    rx: providers::traits::ChatStream,
+    model: String,
+    mem: Arc<dyn Memory>,
+    session: Option<WebhookSession>,
+    reply: String,
+}
 
-    // Signature format: "sha256=<hex_signature>"
-    let Some(hex_sig) = signature_header.strip_prefix("sha256=") else {
-        return false;
-    };
+/// A `/webhook` conversation: prior turns plus the new user message.
+struct WebhookSession {
+    id: String,
+    history: Vec<ChatMessage>,
+}
 
-    // Decode hex signature
-    let Ok(expected) = hex::decode(hex_sig) else {
-        return false;
-    };
+impl WebhookSession {
+    /// Load prior turns for `id` and append the new user message.
+    /// Returns `None` if `id` is not a valid session id.
+    async fn open(mem: &dyn Memory, id: &str, message: &str) -> Option<Self> {
+        if !valid_session_id(id) {
+            return None;
+        }
+        let mut history = load_session_history(mem, id).await;
+        history.push(ChatMessage::user(message));
+        Some(Self {
+            id: id.to_string(),
+            history,
+        })
+    }
 
-    // Compute HMAC-SHA256
-    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(app_secret.as_bytes()) else {
-        return false;
+    /// Append the assistant reply and persist the trimmed history.
+    async fn finish(mut self, mem: &dyn Memory, reply: String) {
+        self.history.push(ChatMessage::assistant(reply));
+        trim_history(&mut self.history, WEBHOOK_SESSION_MAX_MESSAGES);
+        save_session_history(mem, &self.id, &self.history).await;
+    }
+}
+
+/// Conversation for a webhook request, with `images` on the new user turn,
+/// or `None` when a plain single-turn call suffices (no session, no images).
+fn webhook_messages(
+    generation: &WebhookGeneration,
+    message: &str,
+    images: &[ImageInput],
+    session: Option<&WebhookSession>,
+) -> Option<Vec<ChatMessage>> {
+    let mut messages = match session {
+        Some(session) => session.history.clone(),
+        None if images.is_empty() => return None,
+        None => vec![ChatMessage::user(message)],
     };
-    mac.update(body);
+    if let Some(turn) = messages.last_mut() {
+        turn.images = images.to_vec();
+    }
+    Some(with_system_message(generation.system_prompt.as_deref(), &messages).into_owned())
+}
 
-    // Constant-time comparison
-    mac.verify_slice(&expected).is_ok()
+/// `history` led by `system` as a system message. Stored session histories
+/// never include one, so the current prompt applies to every turn.
+fn with_system_message<'a>(
+    system: Option<&str>,
+    history: &'a [ChatMessage],
+) -> std::borrow::Cow<'a, [ChatMessage]> {
+    match system {
+        Some(system) => {
+            let mut messages = Vec::with_capacity(history.len() + 1);
+            messages.push(ChatMessage::system(system));
+            messages.extend_from_slice(history);
+            std::borrow::Cow::Owned(messages)
+        }
+        None => std::borrow::Cow::Borrowed(history),
+    }
 }
 
-/// POST /whatsapp — incoming message webhook
-async fn handle_whatsapp_message(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    body: Bytes,
-) -> impl IntoResponse {
-    let Some(ref wa) = state.whatsapp else {
-        return (
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({"error": "WhatsApp not configured"})),
-        );
-    };
+fn valid_session_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
 
-    // ── Security: Verify X-Hub-Signature-256 if app_secret is configured ──
-    if let Some(ref app_secret) = state.whatsapp_app_secret {
-        let signature = headers
-            .get("X-Hub-Signature-256")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
+fn session_memory_key(id: &str) -> String {
+    format!("webhook_session:{id}")
+}
 
-        if !verify_whatsapp_signature(app_secret, &body, signature) {
-            tracing::warn!(
-                "WhatsApp webhook signature verification failed (signature: {})",
-                if signature.is_empty() {
-                    "missing"
-                } else {
-                    "invalid"
-                }
-            );
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(serde_json::json!({"error": "Invalid signature"})),
-            );
+/// Load a session's history. Missing or unreadable history starts a fresh
+/// conversation rather than failing the request.
+async fn load_session_history(mem: &dyn Memory, id: &str) -> Vec<ChatMessage> {
+    match mem.get(&session_memory_key(id)).await {
+        Ok(Some(entry)) => serde_json::from_str(&entry.content).unwrap_or_else(|e| {
+            tracing::warn!("Webhook session {id}: discarding unreadable history: {e}");
+            Vec::new()
+        }),
+        Ok(None) => Vec::new(),
+        Err(e) => {
+            tracing::warn!("Webhook session {id}: failed to load history: {e}");
+            Vec::new()
         }
     }
+}
 
-    // Parse JSON body
-    let Ok(payload) = serde_json::from_slice::<serde_json::Value>(&body) else {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({"error": "Invalid JSON payload"})),
-        );
+async fn save_session_history(mem: &dyn Memory, id: &str, history: &[ChatMessage]) {
+    let content = match serde_json::to_string(history) {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::warn!("Webhook session {id}: failed to encode history: {e}");
+            return;
+        }
     };
-
-    // Parse messages from the webhook payload
-    let messages = wa.parse_webhook_payload(&payload);
-
-    if messages.is_empty() {
-        // Acknowledge the webhook even if no messages (could be status updates)
-        return (StatusCode::OK, Json(serde_json::json!({"status": "ok"})));
+    if let Err(e) = mem
+        .store_in_session(
+            id,
+            &session_memory_key(id),
+            &content,
+            MemoryCategory::Conversation,
+        )
+        .await
+    {
+        tracing::warn!("Webhook session {id}: failed to save history: {e}");
     }
+}
 
-    // Process each message
-    for msg in &messages {
-        tracing::info!(
-            "WhatsApp message from {}: {}",
-            msg.sender,
-            truncate_with_ellipsis(&msg.content, 50)
-        );
+/// True when the client asked for Server-Sent Events via the `Accept` header.
+fn wants_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+}
 
-        // Auto-save to memory
-        if state.auto_save {
-            let _ = state
-                .mem
-                .store(
-                    &format!("whatsapp_{}", msg.sender),
-                    &msg.content,
-                    MemoryCategory::Conversation,
+/// Stream provider output as SSE: one `message` event per text delta
+/// (`{"delta": "..."}`), then a final `done` event carrying the model name.
+/// Failures to open the stream are reported as a plain 500 like the
+/// blocking path; failures mid-stream become an `error` event. For session
+/// requests the assembled reply is persisted once the stream completes.
+async fn stream_webhook_response(
+    state: &AppState,
+    generation: &WebhookGeneration,
+    message: &str,
+    images: &[ImageInput],
+    session: Option<WebhookSession>,
+) -> axum::response::Response {
+    let opened = match webhook_messages(generation, message, images, session.as_ref()) {
+        None => {
+            state
+                .provider
+                .stream_chat_with_system(
+                    generation.system_prompt.as_deref(),
+                    message,
+                    &generation.model,
+                    &generation.options,
                 )
-                .await;
+                .await
         }
-
-        // Call the LLM
-        match state
+        Some(history) => {
+            state
+                .provider
+                .stream_chat_with_history(&history, &generation.model, &generation.options)
+                .await
+        }
+    };
+    let rx = match opened {
+        Ok(rx) => rx,
+        Err(e) => {
+            tracing::error!(
+                "Webhook provider error: {}",
+                providers::sanitize_api_error(&e.to_string())
+            );
+            let err = serde_json::json!({"error": "LLM request failed"});
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response();
+        }
+    };
+
+    let stream_state = WebhookStreamState {
+        rx,
+        model: generation.model.clone(),
+        mem: Arc::clone(&state.mem),
+        session,
+        reply: String::new(),
+    };
+    let events = futures_util::stream::unfold(Some(stream_state), |st| async move {
+        let mut st = st?;
+        match st.rx.recv().await {
+            Some(Ok(delta)) => {
+                let event = Event::default().data(serde_json::json!({"delta": delta}).to_string());
+                if st.session.is_some() {
+                    st.reply.push_str(&delta);
+                }
+                Some((Ok::<_, std::convert::Infallible>(event), Some(st)))
+            }
+            Some(Err(e)) => {
+                tracing::error!(
+                    "Webhook stream error: {}",
+                    providers::sanitize_api_error(&e.to_string())
+                );
+                let event = Event::default()
+                    .event("error")
+                    .data(serde_json::json!({"error": "LLM request failed"}).to_string());
+                Some((Ok(event), None))
+            }
+            None => {
+                let mut done = serde_json::json!({"model": st.model});
+                if let Some(session) = st.session {
+                    done["session_id"] = serde_json::json!(session.id);
+                    session.finish(st.mem.as_ref(), st.reply).await;
+                }
+                let event = Event::default().event("done").data(done.to_string());
+                Some((Ok(event), None))
+            }
+        }
+    });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Client frame on `/ws`. Plain (non-JSON) text frames are accepted too.
+#[derive(serde::Deserialize)]
+pub struct WsClientMessage {
+    pub message: String,
+}
+
+/// GET /ws — upgrade to a WebSocket chat session
+async fn handle_ws(
+    State(state): State<AppState>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> axum::response::Response {
+    let client_key = client_key(&state.trusted_proxies, &headers, peer.as_ref());
+    if let Err(retry_after) = state.rate_limiter.check_webhook(&client_key) {
+        tracing::warn!("/ws rate limit exceeded for key: {client_key}");
+        return too_many_requests("Too many requests. Please retry later.", retry_after);
+    }
+
+    if !pairing_authorized(&state, &headers) {
+        tracing::warn!("WebSocket: rejected — not paired / invalid bearer token");
+        let err = serde_json::json!({
+            "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
+        });
+        return (StatusCode::UNAUTHORIZED, Json(err)).into_response();
+    }
+
+    ws.on_upgrade(move |socket| run_ws_session(state, socket))
+}
+
+/// Extract the user message from a `/ws` text frame. JSON frames must match
+/// `WsClientMessage`; anything else is taken verbatim. Blank input is ignored.
+fn parse_ws_message(text: &str) -> Option<String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    if trimmed.starts_with('{') {
+        return serde_json::from_str::<WsClientMessage>(trimmed)
+            .ok()
+            .map(|m| m.message)
+            .filter(|m| !m.trim().is_empty());
+    }
+    Some(trimmed.to_string())
+}
+
+/// Drop the oldest user/assistant turns once history exceeds `max` messages.
+fn trim_history(history: &mut Vec<ChatMessage>, max: usize) {
+    while history.len() > max {
+        let drop = 2.min(history.len());
+        history.drain(..drop);
+    }
+}
+
+async fn ws_send_json(socket: &mut WebSocket, value: &serde_json::Value) -> bool {
+    socket
+        .send(WsMessage::Text(value.to_string()))
+        .await
+        .is_ok()
+}
+
+/// One WebSocket chat session. Each user message is answered with a series
+/// of `{"type":"delta"}` frames followed by `{"type":"done"}`; failures send
+/// `{"type":"error"}` and the failed turn is left out of the history.
+async fn run_ws_session(state: AppState, mut socket: WebSocket) {
+    let mut history: Vec<ChatMessage> = Vec::new();
+
+    while let Some(Ok(frame)) = socket.recv().await {
+        let text = match frame {
+            WsMessage::Text(text) => text,
+            WsMessage::Close(_) => break,
+            // Ping/pong are answered by axum; binary frames are not supported.
+            _ => continue,
+        };
+
+        let Some(message) = parse_ws_message(&text) else {
+            let err = serde_json::json!({
+                "type": "error",
+                "error": "Expected text or {\"message\": \"...\"}",
+            });
+            if !ws_send_json(&mut socket, &err).await {
+                return;
+            }
+            continue;
+        };
+
+        if state.auto_save {
+            let _ = state
+                .mem
+                .store("ws_msg", &message, MemoryCategory::Conversation)
+                .await;
+        }
+
+        let settings = state.settings();
+        let vars = TemplateVars {
+            channel: "ws",
+            sender: None,
+            message: &message,
+        };
+        let system_prompt = settings
+            .system_prompt(
+                &vars,
+                settings.ws_system_prompt.as_deref(),
+                state.mem.as_ref(),
+            )
+            .await;
+        history.push(ChatMessage::user(message));
+        let request = with_system_message(system_prompt.as_deref(), &history);
+        let mut rx = match state
             .provider
-            .chat(&msg.content, &state.model, state.temperature)
+            .stream_chat_with_history(&request, &settings.model, &settings.options)
             .await
         {
-            Ok(response) => {
-                // Send reply via WhatsApp
-                if let Err(e) = wa.send(&response, &msg.sender).await {
-                    tracing::error!("Failed to send WhatsApp reply: {e}");
+            Ok(rx) => rx,
+            Err(e) => {
+                tracing::error!(
+                    "WebSocket provider error: {}",
+                    providers::sanitize_api_error(&e.to_string())
+                );
+                history.pop();
+                let err = serde_json::json!({"type": "error", "error": "LLM request failed"});
+                if !ws_send_json(&mut socket, &err).await {
+                    return;
                 }
+                continue;
             }
-            Err(e) => {
-                tracing::error!("LLM error for WhatsApp message: {e:#}");
-                let _ = wa
-                    .send(
-                        "Sorry, I couldn't process your message right now.",
-                        &msg.sender,
-                    )
-                    .await;
+        };
+
+        let mut reply = String::new();
+        let mut failed = false;
+        while let Some(chunk) = rx.recv().await {
+            match chunk {
+                Ok(delta) => {
+                    let frame = serde_json::json!({"type": "delta", "content": delta});
+                    reply.push_str(&delta);
+                    if !ws_send_json(&mut socket, &frame).await {
+                        return;
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(
+                        "WebSocket stream error: {}",
+                        providers::sanitize_api_error(&e.to_string())
+                    );
+                    failed = true;
+                    break;
+                }
+            }
+        }
+
+        let frame = if failed {
+            history.pop();
+            serde_json::json!({"type": "error", "error": "LLM request failed"})
+        } else {
+            history.push(ChatMessage::assistant(reply));
+            trim_history(&mut history, WS_MAX_HISTORY_MESSAGES);
+            serde_json::json!({"type": "done", "model": settings.model})
+        };
+        if !ws_send_json(&mut socket, &frame).await {
+            return;
+        }
+    }
+}
+
+// ══════════════════════════════════════════════════════════════
+// Usage accounting
+// ══════════════════════════════════════════════════════════════
+
+/// Add a reply's token usage to the `[cost]` ledger under the default provider.
+fn record_usage(state: &AppState, channel: &str, model: &str, reply: &ChatReply) {
+    if reply.usage.is_none() {
+        return;
+    }
+    let (tracker, provider) = {
+        let config = state
+            .config
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        (
+            CostTracker::new(&config),
+            config
+                .default_provider
+                .clone()
+                .unwrap_or_else(|| "openrouter".into()),
+        )
+    };
+    tracker.record_reply(&provider, channel, model, reply);
+}
+
+/// Query params for `GET /usage`
+#[derive(serde::Deserialize)]
+pub struct UsageQuery {
+    /// Days to report, today included (default 30)
+    pub days: Option<u32>,
+    /// `day` (default), `provider`, `channel` or `model`
+    pub by: Option<String>,
+}
+
+/// GET /usage?days=N&by=provider — token and spend totals
+async fn handle_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<UsageQuery>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    let group_by = match params.by.as_deref().map(str::parse::<GroupBy>) {
+        None => GroupBy::Day,
+        Some(Ok(group_by)) => group_by,
+        Some(Err(e)) => {
+            let err = serde_json::json!({"error": e.to_string()});
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+        }
+    };
+    let days = params.days.unwrap_or(30).max(1);
+    let tracker = CostTracker::new(
+        &state
+            .config
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner),
+    );
+
+    match tracker.summarize(days, group_by) {
+        Ok(rows) => {
+            let total = UsageTotals::sum(&rows);
+            let body = serde_json::json!({
+                "days": days,
+                "by": group_by.to_string(),
+                "rows": rows,
+                "total": total,
+            });
+            (StatusCode::OK, Json(body)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Usage query failed: {e:#}");
+            let err = serde_json::json!({"error": "Usage query failed"});
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response()
+        }
+    }
+}
+
+// ══════════════════════════════════════════════════════════════
+// Memory REST API
+// ══════════════════════════════════════════════════════════════
+
+/// Default and maximum result counts for `GET /memory/search`.
+const MEMORY_SEARCH_DEFAULT_LIMIT: usize = 10;
+const MEMORY_SEARCH_MAX_LIMIT: usize = 100;
+
+/// 401 for memory routes when pairing is required and the caller isn't paired.
+fn require_paired(state: &AppState, headers: &HeaderMap) -> Option<axum::response::Response> {
+    if pairing_authorized(state, headers) {
+        return None;
+    }
+    tracing::warn!("Memory API: rejected — not paired / invalid bearer token");
+    let err = serde_json::json!({
+        "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
+    });
+    Some((StatusCode::UNAUTHORIZED, Json(err)).into_response())
+}
+
+fn memory_backend_error(e: &anyhow::Error) -> axum::response::Response {
+    tracing::error!("Memory API backend error: {e}");
+    let err = serde_json::json!({"error": "Memory backend error"});
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response()
+}
+
+fn memory_not_found(key: &str) -> axum::response::Response {
+    let err = serde_json::json!({"error": "Memory not found", "key": key});
+    (StatusCode::NOT_FOUND, Json(err)).into_response()
+}
+
+/// JSON view of a memory entry (category rendered as its plain name).
+fn memory_entry_json(entry: &MemoryEntry) -> serde_json::Value {
+    serde_json::json!({
+        "id": entry.id,
+        "key": entry.key,
+        "content": entry.content,
+        "category": entry.category.to_string(),
+        "timestamp": entry.timestamp,
+        "session_id": entry.session_id,
+        "tags": entry.tags,
+        "last_accessed_at": entry.last_accessed_at,
+        "access_count": entry.access_count,
+        "score": entry.score,
+    })
+}
+
+/// Query params for `GET /memory`
+#[derive(serde::Deserialize)]
+pub struct MemoryListQuery {
+    pub category: Option<String>,
+    /// List only entries stored in this conversation session
+    pub session_id: Option<String>,
+    /// List only entries labelled with this tag
+    pub tag: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Query params for `GET /memory/search`
+#[derive(serde::Deserialize)]
+pub struct MemorySearchQuery {
+    pub q: String,
+    /// Only return entries labelled with this tag
+    pub tag: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Query params for `GET /memory/sync`
+#[derive(serde::Deserialize)]
+pub struct MemorySyncQuery {
+    /// Only changes at or after this RFC 3339 time (the `generated_at` of
+    /// the previous response)
+    pub since: Option<String>,
+}
+
+/// Body for `PUT /memory/{key}`
+#[derive(serde::Deserialize)]
+pub struct MemoryPutBody {
+    pub content: String,
+    /// Category name (default: `core`)
+    pub category: Option<String>,
+    /// Topic labels; replace any previous tags for the key
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Body for `PATCH /memory/{key}`; omitted fields are left unchanged
+#[derive(serde::Deserialize)]
+pub struct MemoryPatchBody {
+    pub content: Option<String>,
+    pub category: Option<String>,
+    /// Replaces the entry's tags (`[]` clears them)
+    pub tags: Option<Vec<String>>,
+}
+
+/// GET /memory — list memories, optionally filtered by category, tag or session
+async fn handle_memory_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<MemoryListQuery>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    let listed = if let Some(session_id) = params.session_id.as_deref() {
+        let limit = params.limit.unwrap_or(MEMORY_SESSION_LIST_LIMIT);
+        state.mem.recall_session(session_id, limit).await
+    } else {
+        let category = params.category.as_deref().map(MemoryCategory::from);
+        let listed = match params.tag.as_deref() {
+            Some(tag) => state.mem.list_tagged(tag, category.as_ref()).await,
+            None => state.mem.list(category.as_ref()).await,
+        };
+        listed.map(|mut entries| {
+            if let Some(limit) = params.limit {
+                entries.truncate(limit);
             }
+            entries
+        })
+    };
+    match listed {
+        Ok(entries) => {
+            let entries: Vec<_> = entries.iter().map(memory_entry_json).collect();
+            let body = serde_json::json!({"count": entries.len(), "entries": entries});
+            (StatusCode::OK, Json(body)).into_response()
+        }
+        Err(e) => memory_backend_error(&e),
+    }
+}
+
+/// GET /memory/search?q=...&tag=...&limit=N — keyword/hybrid recall
+async fn handle_memory_search(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<MemorySearchQuery>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    let limit = params
+        .limit
+        .unwrap_or(MEMORY_SEARCH_DEFAULT_LIMIT)
+        .clamp(1, MEMORY_SEARCH_MAX_LIMIT);
+    let recalled = match params.tag.as_deref() {
+        Some(tag) => state.mem.recall_tagged(&params.q, tag, limit).await,
+        None => state.mem.recall(&params.q, limit).await,
+    };
+    match recalled {
+        Ok(entries) => {
+            let entries: Vec<_> = entries.iter().map(memory_entry_json).collect();
+            let body = serde_json::json!({"query": params.q, "entries": entries});
+            (StatusCode::OK, Json(body)).into_response()
+        }
+        Err(e) => memory_backend_error(&e),
+    }
+}
+
+/// GET /memory/{key}
+async fn handle_memory_get(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(key): Path<String>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    match state.mem.get(&key).await {
+        Ok(Some(entry)) => (StatusCode::OK, Json(memory_entry_json(&entry))).into_response(),
+        Ok(None) => memory_not_found(&key),
+        Err(e) => memory_backend_error(&e),
+    }
+}
+
+/// PUT /memory/{key} — create or overwrite a memory
+async fn handle_memory_put(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(key): Path<String>,
+    body: Result<Json<MemoryPutBody>, axum::extract::rejection::JsonRejection>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    let Json(body) = match body {
+        Ok(b) => b,
+        Err(e) => {
+            let err = serde_json::json!({
+                "error": format!("Invalid JSON: {e}. Expected: {{\"content\": \"...\", \"category\": \"core\"}}")
+            });
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+        }
+    };
+
+    let category = body
+        .category
+        .as_deref()
+        .map_or(MemoryCategory::Core, MemoryCategory::from);
+    let tags = memory::normalize_tags(&body.tags);
+    match state
+        .mem
+        .store_with_tags(&key, &body.content, category.clone(), &tags)
+        .await
+    {
+        Ok(()) => {
+            let body = serde_json::json!({
+                "stored": true,
+                "key": key,
+                "category": category.to_string(),
+                "tags": tags,
+            });
+            (StatusCode::OK, Json(body)).into_response()
+        }
+        Err(e) => memory_backend_error(&e),
+    }
+}
+
+/// PATCH /memory/{key} — change an existing memory; 404 if it doesn't exist
+async fn handle_memory_patch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(key): Path<String>,
+    body: Result<Json<MemoryPatchBody>, axum::extract::rejection::JsonRejection>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    let Json(body) = match body {
+        Ok(b) => b,
+        Err(e) => {
+            let err = serde_json::json!({"error": format!("Invalid JSON: {e}")});
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+        }
+    };
+    let patch = memory::MemoryPatch {
+        content: body.content,
+        category: body.category.as_deref().map(MemoryCategory::from),
+        tags: body.tags,
+    };
+    if patch.is_empty() {
+        let err = serde_json::json!({
+            "error": "Nothing to update. Expected any of: content, category, tags"
+        });
+        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+    }
+
+    match state.mem.get(&key).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return memory_not_found(&key),
+        Err(e) => return memory_backend_error(&e),
+    }
+    match state.mem.update(&key, &patch).await {
+        Ok(entry) => (StatusCode::OK, Json(memory_entry_json(&entry))).into_response(),
+        Err(e) => memory_backend_error(&e),
+    }
+}
+
+/// DELETE /memory/{key}
+async fn handle_memory_delete(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(key): Path<String>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    match state.mem.forget(&key).await {
+        Ok(true) => {
+            let body = serde_json::json!({"deleted": true, "key": key});
+            (StatusCode::OK, Json(body)).into_response()
+        }
+        Ok(false) => memory_not_found(&key),
+        Err(e) => memory_backend_error(&e),
+    }
+}
+
+/// GET /memory/sync?since=... — changes for a peer instance to pull
+async fn handle_memory_sync_fetch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<MemorySyncQuery>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    let since = match params.since.as_deref().map(memory::parse_time_bound) {
+        None => None,
+        Some(Ok(since)) => Some(since),
+        Some(Err(e)) => {
+            let err = serde_json::json!({"error": format!("Invalid since: {e}")});
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+        }
+    };
+    match memory::sync::Changeset::collect(state.mem.as_ref(), since).await {
+        Ok(changes) => (StatusCode::OK, Json(changes)).into_response(),
+        Err(e) => memory_backend_error(&e),
+    }
+}
+
+/// POST /memory/sync — merge a peer's changes (last write wins)
+async fn handle_memory_sync_apply(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Result<Json<memory::sync::Changeset>, axum::extract::rejection::JsonRejection>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    let Json(changes) = match body {
+        Ok(b) => b,
+        Err(e) => {
+            let err = serde_json::json!({"error": format!("Invalid changeset: {e}")});
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+        }
+    };
+    if changes.version > memory::sync::SYNC_VERSION {
+        let err = serde_json::json!({
+            "error": format!("Unsupported changeset version {}", changes.version)
+        });
+        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+    }
+    match changes.apply(state.mem.as_ref()).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => memory_backend_error(&e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct IngestQuery {
+    /// Document name for raw-text uploads (multipart parts use their filename)
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// POST /ingest — chunk documents into memory.
+///
+/// Accepts `multipart/form-data` (one document per part) or a raw text
+/// body. Each document is split with the markdown chunker and stored as
+/// `doc:<name>:<n>`; re-ingesting a name replaces its previous chunks.
+async fn handle_ingest(
+    State(state): State<AppState>,
+    Query(query): Query<IngestQuery>,
+    request: Request,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, request.headers()) {
+        return denied;
+    }
+
+    let documents = match read_ingest_documents(query.name.as_deref(), request).await {
+        Ok(documents) => documents,
+        Err(rejection) => return rejection,
+    };
+    if documents.iter().all(|(_, text)| text.trim().is_empty()) {
+        let err = serde_json::json!({"error": "No document content to ingest"});
+        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+    }
+
+    let category = MemoryCategory::from(
+        query
+            .category
+            .as_deref()
+            .unwrap_or(memory::DOCUMENT_CATEGORY),
+    );
+    let mut stored = Vec::new();
+    for (name, text) in documents {
+        match memory::ingest_document(
+            state.mem.as_ref(),
+            &name,
+            &text,
+            &category,
+            state.chunk_max_tokens,
+        )
+        .await
+        {
+            Ok(keys) => stored.push(serde_json::json!({"name": name, "keys": keys})),
+            Err(e) => return memory_backend_error(&e),
+        }
+    }
+
+    let body = serde_json::json!({
+        "category": category.to_string(),
+        "documents": stored,
+    });
+    (StatusCode::OK, Json(body)).into_response()
+}
+
+/// Pull `(name, text)` documents out of a multipart or raw-text request.
+async fn read_ingest_documents(
+    name: Option<&str>,
+    request: Request,
+) -> Result<Vec<(String, String)>, axum::response::Response> {
+    let is_multipart = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("multipart/form-data"));
+
+    if !is_multipart {
+        let bytes = Bytes::from_request(request, &())
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let text = String::from_utf8(bytes.to_vec()).map_err(|_| non_utf8_document())?;
+        return Ok(vec![(memory::document_name(name), text)]);
+    }
+
+    let mut multipart = Multipart::from_request(request, &())
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let mut documents = Vec::new();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(IntoResponse::into_response)?
+    {
+        let field_name = field
+            .file_name()
+            .or_else(|| field.name())
+            .map(ToString::to_string);
+        let bytes = field.bytes().await.map_err(IntoResponse::into_response)?;
+        let text = String::from_utf8(bytes.to_vec()).map_err(|_| non_utf8_document())?;
+        documents.push((memory::document_name(field_name.as_deref().or(name)), text));
+    }
+    Ok(documents)
+}
+
+fn non_utf8_document() -> axum::response::Response {
+    let err = serde_json::json!({"error": "Only UTF-8 text documents are supported"});
+    (StatusCode::UNSUPPORTED_MEDIA_TYPE, Json(err)).into_response()
+}
+
+#[derive(serde::Deserialize)]
+pub struct SendBody {
+    /// Channel-specific recipient: chat id, channel id, room, phone number, address…
+    pub target: String,
+    pub message: String,
+    /// Slack Block Kit layout; `message` becomes its fallback text, and
+    /// channels without rich layouts send `message` alone
+    #[serde(default)]
+    pub blocks: Vec<serde_json::Value>,
+}
+
+/// POST /send/{channel} — deliver a message through a configured channel
+async fn handle_send(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_name): Path<String>,
+    body: Result<Json<SendBody>, axum::extract::rejection::JsonRejection>,
+) -> axum::response::Response {
+    if let Some(denied) = require_paired(&state, &headers) {
+        return denied;
+    }
+
+    let Json(send_body) = match body {
+        Ok(b) => b,
+        Err(e) => {
+            let err = serde_json::json!({
+                "error": format!("Invalid JSON: {e}. Expected: {{\"target\": \"...\", \"message\": \"...\"}}")
+            });
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+        }
+    };
+    if send_body.target.trim().is_empty() || send_body.message.trim().is_empty() {
+        let err = serde_json::json!({"error": "target and message must not be empty"});
+        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+    }
+
+    let Some(channel) = state.channels.get(&channel_name) else {
+        let err = serde_json::json!({"error": format!("Channel not configured: {channel_name}")});
+        return (StatusCode::NOT_FOUND, Json(err)).into_response();
+    };
+
+    let sent = if send_body.blocks.is_empty() {
+        channel.send(&send_body.message, &send_body.target).await
+    } else {
+        channel
+            .send_blocks(&send_body.blocks, &send_body.message, &send_body.target)
+            .await
+    };
+    match sent {
+        Ok(()) => {
+            let body = serde_json::json!({
+                "status": "sent",
+                "channel": channel_name,
+                "target": send_body.target,
+            });
+            (StatusCode::OK, Json(body)).into_response()
+        }
+        Err(e) => {
+            let detail = providers::sanitize_api_error(&e.to_string());
+            tracing::error!("Send via {channel_name} failed: {detail}");
+            let err = serde_json::json!({"error": format!("Delivery via {channel_name} failed: {detail}")});
+            (StatusCode::BAD_GATEWAY, Json(err)).into_response()
+        }
+    }
+}
+
+/// `WhatsApp` verification query params
+#[derive(serde::Deserialize)]
+pub struct WhatsAppVerifyQuery {
+    #[serde(rename = "hub.mode")]
+    pub mode: Option<String>,
+    #[serde(rename = "hub.verify_token")]
+    pub verify_token: Option<String>,
+    #[serde(rename = "hub.challenge")]
+    pub challenge: Option<String>,
+}
+
+/// GET /whatsapp — Meta webhook verification
+async fn handle_whatsapp_verify(
+    State(state): State<AppState>,
+    Query(params): Query<WhatsAppVerifyQuery>,
+) -> impl IntoResponse {
+    let Some(ref wa) = state.whatsapp else {
+        return (StatusCode::NOT_FOUND, "WhatsApp not configured".to_string());
+    };
+
+    // Verify the token matches (constant-time comparison to prevent timing attacks)
+    let token_matches = params
+        .verify_token
+        .as_deref()
+        .is_some_and(|t| constant_time_eq(t, wa.verify_token()));
+    if params.mode.as_deref() == Some("subscribe") && token_matches {
+        if let Some(ch) = params.challenge {
+            tracing::info!("WhatsApp webhook verified successfully");
+            return (StatusCode::OK, ch);
+        }
+        return (StatusCode::BAD_REQUEST, "Missing hub.challenge".to_string());
+    }
+
+    tracing::warn!("WhatsApp webhook verification failed — token mismatch");
+    (StatusCode::FORBIDDEN, "Forbidden".to_string())
+}
+
+/// Verify `WhatsApp` webhook signature (`X-Hub-Signature-256`).
+/// Returns true if the signature is valid, false otherwise.
+/// See: <https://developers.facebook.com/docs/graph-api/webhooks/getting-started#verification-requests>
+pub fn verify_whatsapp_signature(app_secret: &str, body: &[u8], signature_header: &str) -> bool {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    // Signature format: "sha256=<hex_signature>"
+    let Some(hex_sig) = signature_header.strip_prefix("sha256=") else {
+        return false;
+    };
+
+    // Decode hex signature
+    let Ok(expected) = hex::decode(hex_sig) else {
+        return false;
+    };
+
+    // Compute HMAC-SHA256
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(app_secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+
+    // Constant-time comparison
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// POST /whatsapp — incoming message webhook
+async fn handle_whatsapp_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let Some(ref wa) = state.whatsapp else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "WhatsApp not configured"})),
+        );
+    };
+
+    // ── Security: Verify X-Hub-Signature-256 if app_secret is configured ──
+    if let Some(ref app_secret) = state.whatsapp_app_secret {
+        let signature = headers
+            .get("X-Hub-Signature-256")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+
+        if !verify_whatsapp_signature(app_secret, &body, signature) {
+            tracing::warn!(
+                "WhatsApp webhook signature verification failed (signature: {})",
+                if signature.is_empty() {
+                    "missing"
+                } else {
+                    "invalid"
+                }
+            );
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({"error": "Invalid signature"})),
+            );
+        }
+    }
+
+    // Parse JSON body
+    let Ok(payload) = serde_json::from_slice::<serde_json::Value>(&body) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Invalid JSON payload"})),
+        );
+    };
+
+    // Parse messages from the webhook payload
+    let messages = wa.parse_webhook_payload(&payload);
+
+    if messages.is_empty() {
+        // Acknowledge the webhook even if no messages (could be status updates)
+        return (StatusCode::OK, Json(serde_json::json!({"status": "ok"})));
+    }
+
+    // Process each message
+    for msg in &messages {
+        tracing::info!(
+            "WhatsApp message from {}: {}",
+            msg.sender,
+            truncate_with_ellipsis(&msg.content, 50)
+        );
+
+        answer_whatsapp_message(&state, wa, msg).await;
+    }
+
+    // Acknowledge the webhook
+    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
+}
+
+/// Reply to one `WhatsApp` message, unless `[channels_config.access]`
+/// refuses its sender.
+async fn answer_whatsapp_message(
+    state: &AppState,
+    wa: &WhatsAppChannel,
+    msg: &crate::channels::traits::ChannelMessage,
+) {
+    // Refuse senders `[channels_config.access]` keeps out before any
+    // memory write or provider call
+    let settings = state.settings();
+    let channel = &settings.whatsapp;
+    if !channel.permits(&crate::channels::access_identities(msg)) {
+        if let Some(reply) = channel.denied_reply() {
+            if let Err(e) = wa.send(reply, &msg.sender).await {
+                tracing::error!("Failed to send WhatsApp reply: {e}");
+            }
+        }
+        return;
+    }
+
+    // Auto-save to memory
+    if state.auto_save {
+        let _ = state
+            .mem
+            .store(
+                &format!("whatsapp_{}", msg.sender),
+                &msg.content,
+                MemoryCategory::Conversation,
+            )
+            .await;
+    }
+
+    // Call the LLM
+    let vars = TemplateVars {
+        channel: "whatsapp",
+        sender: Some(&msg.sender),
+        message: &msg.content,
+    };
+    let system_prompt = settings
+        .system_prompt(
+            &vars,
+            settings.whatsapp_system_prompt.as_deref(),
+            state.mem.as_ref(),
+        )
+        .await;
+    match state
+        .provider
+        .chat_with_system(
+            system_prompt.as_deref(),
+            &msg.content,
+            &channel.model,
+            &channel.options,
+        )
+        .await
+    {
+        Ok(reply) => {
+            record_usage(state, "whatsapp", &channel.model, &reply);
+            // Send reply via WhatsApp
+            if let Err(e) = wa.send(&reply.text, &msg.sender).await {
+                tracing::error!("Failed to send WhatsApp reply: {e}");
+            }
+        }
+        Err(e) => {
+            tracing::error!("LLM error for WhatsApp message: {e:#}");
+            let _ = wa
+                .send(
+                    "Sorry, I couldn't process your message right now.",
+                    &msg.sender,
+                )
+                .await;
+        }
+    }
+}
+
+/// How far `X-Slack-Request-Timestamp` may be from now before a slash
+/// command is rejected as a replay.
+const SLACK_SIGNATURE_MAX_AGE_SECS: u64 = 300;
+
+/// How long a slash command waits for the reply before acknowledging it and
+/// posting the answer to its `response_url` instead. Slack gives up on the
+/// request after three seconds.
+const SLACK_INLINE_REPLY_TIMEOUT: Duration = Duration::from_millis(2500);
+
+/// Verify a Slack request signature (`X-Slack-Signature`) over
+/// `v0:{timestamp}:{body}`, rejecting timestamps more than five minutes
+/// from `now` (Unix seconds).
+/// See: <https://api.slack.com/authentication/verifying-requests-from-slack>
+pub fn verify_slack_signature(
+    signing_secret: &str,
+    timestamp: &str,
+    body: &[u8],
+    signature_header: &str,
+    now: i64,
+) -> bool {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let Ok(sent_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if now.abs_diff(sent_at) > SLACK_SIGNATURE_MAX_AGE_SECS {
+        return false;
+    }
+
+    // Signature format: "v0=<hex_signature>"
+    let Some(hex_sig) = signature_header.strip_prefix("v0=") else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(hex_sig) else {
+        return false;
+    };
+
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(b"v0:");
+    mac.update(timestamp.as_bytes());
+    mac.update(b":");
+    mac.update(body);
+
+    // Constant-time comparison
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Slash command payload, posted by Slack as a form
+#[derive(serde::Deserialize)]
+pub struct SlackCommandForm {
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub text: String,
+    pub user_id: String,
+    #[serde(default)]
+    pub channel_id: String,
+    #[serde(default)]
+    pub response_url: String,
+}
+
+/// What a slash command's text asks for.
+#[derive(Debug, PartialEq, Eq)]
+enum SlackCommand<'a> {
+    Remember(&'a str),
+    Ask(&'a str),
+    Help,
+}
+
+impl<'a> SlackCommand<'a> {
+    /// `remember <fact>`, `ask <question>` or `help`; any other text is a
+    /// question.
+    fn parse(text: &'a str) -> Self {
+        let text = text.trim();
+        let (word, rest) = text
+            .split_once(char::is_whitespace)
+            .map_or((text, ""), |(word, rest)| (word, rest.trim()));
+        match word.to_ascii_lowercase().as_str() {
+            "remember" if !rest.is_empty() => Self::Remember(rest),
+            "ask" if !rest.is_empty() => Self::Ask(rest),
+            "" | "help" | "remember" | "ask" => Self::Help,
+            _ => Self::Ask(text),
+        }
+    }
+}
+
+/// Slash command response only the caller sees.
+fn slack_ephemeral(text: &str) -> Json<serde_json::Value> {
+    Json(serde_json::json!({"response_type": "ephemeral", "text": text}))
+}
+
+/// Slash command response posted to the channel.
+fn slack_in_channel(text: &str) -> serde_json::Value {
+    serde_json::json!({"response_type": "in_channel", "text": text})
+}
+
+/// Why a slash command from this user is refused, if it is: not in the
+/// Slack `allowed_users`, or kept out by `[channels_config.access]` like
+/// channel messages are.
+fn slack_command_refusal(state: &AppState, form: &SlackCommandForm) -> Option<String> {
+    let not_allowed = "You're not allowed to use this command.";
+    let allowed = state
+        .config
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .channels_config
+        .slack
+        .as_ref()
+        .is_some_and(|slack| {
+            slack
+                .allowed_users
+                .iter()
+                .any(|u| u == "*" || u == &form.user_id)
+        });
+    if !allowed {
+        tracing::warn!(
+            "Slack: ignoring slash command from unauthorized user {}",
+            form.user_id
+        );
+        return Some(not_allowed.to_string());
+    }
+    let identities: Vec<&str> = [form.channel_id.as_str(), form.user_id.as_str()]
+        .into_iter()
+        .filter(|id| !id.is_empty())
+        .collect();
+    let slack = state.settings().slack;
+    (!slack.permits(&identities)).then(|| slack.denied_reply().unwrap_or(not_allowed).to_string())
+}
+
+/// POST /slack/commands — Slack slash commands (`/zeroclaw ask ...`,
+/// `/zeroclaw remember ...`)
+async fn handle_slack_command(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let Some(ref signing_secret) = state.slack_signing_secret else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Slack slash commands not configured"})),
+        );
+    };
+
+    // ── Security: Verify X-Slack-Signature ──
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+    };
+    if !verify_slack_signature(
+        signing_secret,
+        header("X-Slack-Request-Timestamp"),
+        &body,
+        header("X-Slack-Signature"),
+        chrono::Utc::now().timestamp(),
+    ) {
+        tracing::warn!("Slack slash command signature verification failed");
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Invalid signature"})),
+        );
+    }
+
+    let Ok(form) = serde_urlencoded::from_bytes::<SlackCommandForm>(&body) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Invalid slash command payload"})),
+        );
+    };
+
+    if let Some(refusal) = slack_command_refusal(&state, &form) {
+        return (StatusCode::OK, slack_ephemeral(&refusal));
+    }
+
+    let command = if form.command.is_empty() {
+        "/zeroclaw"
+    } else {
+        form.command.as_str()
+    };
+    match SlackCommand::parse(&form.text) {
+        SlackCommand::Help => (
+            StatusCode::OK,
+            slack_ephemeral(&format!(
+                "Usage:\n• `{command} ask <question>` — ask the assistant\n\
+                 • `{command} remember <fact>` — save a fact to memory"
+            )),
+        ),
+        SlackCommand::Remember(fact) => {
+            let key = format!("slack_remember:{}:{}", form.user_id, uuid::Uuid::new_v4());
+            match state.mem.store(&key, fact, MemoryCategory::Core).await {
+                Ok(()) => (
+                    StatusCode::OK,
+                    slack_ephemeral("Got it, I'll remember that."),
+                ),
+                Err(e) => {
+                    tracing::error!("Slack: failed to store memory: {e:#}");
+                    (
+                        StatusCode::OK,
+                        slack_ephemeral("Sorry, I couldn't save that right now."),
+                    )
+                }
+            }
+        }
+        SlackCommand::Ask(question) => (
+            StatusCode::OK,
+            answer_slack_command(&state, &form, question).await,
+        ),
+    }
+}
+
+/// Answer inline when the reply is ready in time; otherwise acknowledge
+/// now and post the reply to the command's `response_url` when it is.
+async fn answer_slack_command(
+    state: &AppState,
+    form: &SlackCommandForm,
+    question: &str,
+) -> Json<serde_json::Value> {
+    let mut reply = tokio::spawn(slack_command_reply(
+        state.clone(),
+        form.user_id.clone(),
+        question.to_string(),
+    ));
+    if let Ok(text) = tokio::time::timeout(SLACK_INLINE_REPLY_TIMEOUT, &mut reply).await {
+        let text = text.unwrap_or_else(|_| slack_error_reply().to_string());
+        return Json(slack_in_channel(&text));
+    }
+
+    let response_url = form.response_url.clone();
+    tokio::spawn(async move {
+        let text = reply
+            .await
+            .unwrap_or_else(|_| slack_error_reply().to_string());
+        if let Err(e) = post_slack_response(&response_url, &text).await {
+            tracing::error!("Slack: failed to post delayed reply: {e:#}");
+        }
+    });
+    slack_ephemeral("Thinking…")
+}
+
+fn slack_error_reply() -> &'static str {
+    "Sorry, I couldn't process your message right now."
+}
+
+/// Answer a slash command question from `user_id`.
+async fn slack_command_reply(state: AppState, user_id: String, question: String) -> String {
+    if state.auto_save {
+        let _ = state
+            .mem
+            .store(
+                &format!("slack_{user_id}"),
+                &question,
+                MemoryCategory::Conversation,
+            )
+            .await;
+    }
+
+    let settings = state.settings();
+    let vars = TemplateVars {
+        channel: "slack",
+        sender: Some(&user_id),
+        message: &question,
+    };
+    let system_prompt = settings
+        .system_prompt(
+            &vars,
+            settings.slack_system_prompt.as_deref(),
+            state.mem.as_ref(),
+        )
+        .await;
+    match state
+        .provider
+        .chat_with_system(
+            system_prompt.as_deref(),
+            &question,
+            &settings.slack.model,
+            &settings.slack.options,
+        )
+        .await
+    {
+        Ok(reply) => {
+            record_usage(&state, "slack", &settings.slack.model, &reply);
+            reply.text
+        }
+        Err(e) => {
+            tracing::error!("LLM error for Slack slash command: {e:#}");
+            slack_error_reply().to_string()
+        }
+    }
+}
+
+/// Post a delayed slash command reply to its `response_url`, which must
+/// point at Slack.
+async fn post_slack_response(response_url: &str, text: &str) -> Result<()> {
+    if !response_url.starts_with("https://hooks.slack.com/") {
+        anyhow::bail!("response_url is not a Slack URL: {response_url}");
+    }
+    crate::proxy::client("slack")
+        .post(response_url)
+        .json(&slack_in_channel(text))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{Memory, MemoryCategory, MemoryEntry};
+    use crate::providers::traits::ChatReply;
+    use crate::providers::Provider;
+    use async_trait::async_trait;
+    use axum::http::HeaderValue;
+    use axum::response::IntoResponse;
+    use http_body_util::BodyExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn security_body_limit_is_64kb() {
+        assert_eq!(MAX_BODY_SIZE, 65_536);
+    }
+
+    #[test]
+    fn security_timeout_is_30_seconds() {
+        assert_eq!(REQUEST_TIMEOUT_SECS, 30);
+    }
+
+    #[test]
+    fn webhook_body_requires_message_field() {
+        let valid = r#"{"message": "hello"}"#;
+        let parsed: Result<WebhookBody, _> = serde_json::from_str(valid);
+        assert!(parsed.is_ok());
+        assert_eq!(parsed.unwrap().message, "hello");
+
+        let missing = r#"{"other": "field"}"#;
+        let parsed: Result<WebhookBody, _> = serde_json::from_str(missing);
+        assert!(parsed.is_err());
+    }
+
+    #[test]
+    fn whatsapp_query_fields_are_optional() {
+        let q = WhatsAppVerifyQuery {
+            mode: None,
+            verify_token: None,
+            challenge: None,
+        };
+        assert!(q.mode.is_none());
+    }
+
+    #[test]
+    fn app_state_is_clone() {
+        fn assert_clone<T: Clone>() {}
+        assert_clone::<AppState>();
+    }
+
+    #[test]
+    fn token_bucket_allows_burst_then_rejects_with_retry_after() {
+        let limiter = TokenBucketRateLimiter::new(60, 3);
+        for _ in 0..3 {
+            assert!(limiter.check("10.0.0.1").is_ok());
+        }
+        let retry_after = limiter.check("10.0.0.1").unwrap_err();
+        assert!(retry_after > Duration::ZERO);
+        assert!(retry_after <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn token_bucket_keys_are_independent() {
+        let limiter = TokenBucketRateLimiter::new(60, 1);
+        assert!(limiter.check("10.0.0.1").is_ok());
+        assert!(limiter.check("10.0.0.1").is_err());
+        assert!(limiter.check("10.0.0.2").is_ok());
+    }
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let limiter = TokenBucketRateLimiter::new(60, 1);
+        assert!(limiter.check("k").is_ok());
+        {
+            let mut buckets = limiter.buckets.lock().unwrap();
+            let bucket = buckets.get_mut("k").unwrap();
+            bucket.updated_at -= Duration::from_secs(2);
+        }
+        assert!(limiter.check("k").is_ok());
+    }
+
+    #[test]
+    fn token_bucket_zero_rate_disables_limit() {
+        let limiter = TokenBucketRateLimiter::new(0, 1);
+        for _ in 0..100 {
+            assert!(limiter.check("k").is_ok());
+        }
+    }
+
+    #[test]
+    fn client_key_prefers_forwarded_then_peer() {
+        let trusted =
+            TrustedProxies::parse(&["192.168.1.0/24".into(), "10.0.0.0/8".into()]).unwrap();
+        let peer = ConnectInfo(SocketAddr::from(([192, 168, 1, 9], 5555)));
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_key(&trusted, &headers, Some(&peer)), "192.168.1.9");
+        assert_eq!(client_key(&trusted, &headers, None), "unknown");
+
+        headers.insert(
+            "X-Forwarded-For",
+            HeaderValue::from_static("1.2.3.4, 10.0.0.1"),
+        );
+        assert_eq!(client_key(&trusted, &headers, Some(&peer)), "1.2.3.4");
+    }
+
+    #[test]
+    fn client_key_ignores_forwarded_from_untrusted_peer() {
+        let peer = ConnectInfo(SocketAddr::from(([203, 0, 113, 7], 5555)));
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-For", HeaderValue::from_static("1.2.3.4"));
+        headers.insert("X-Real-IP", HeaderValue::from_static("5.6.7.8"));
+        assert_eq!(
+            client_key(&TrustedProxies::default(), &headers, Some(&peer)),
+            "203.0.113.7"
+        );
+    }
+
+    #[tokio::test]
+    async fn webhook_over_limit_returns_429_with_retry_after() {
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.rate_limiter = Arc::new(GatewayRateLimiter::new(10, 1, 1));
+        let app = build_router(state);
+
+        let response = send(app.clone(), webhook_request(None)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = send(app, webhook_request(None)).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .unwrap();
+        assert!((59..=60).contains(&retry_after));
+    }
+
+    #[test]
+    fn gateway_rate_limiter_blocks_after_limit() {
+        let limiter = GatewayRateLimiter::new(2, 2, 2);
+        assert!(limiter.allow_pair("127.0.0.1"));
+        assert!(limiter.allow_pair("127.0.0.1"));
+        assert!(!limiter.allow_pair("127.0.0.1"));
+    }
+
+    #[test]
+    fn idempotency_store_rejects_duplicate_key() {
+        let store = IdempotencyStore::new(Duration::from_secs(30));
+        assert!(store.record_if_new("req-1"));
+        assert!(!store.record_if_new("req-1"));
+        assert!(store.record_if_new("req-2"));
+    }
+
+    #[derive(Default)]
+    struct MockMemory;
+
+    #[async_trait]
+    impl Memory for MockMemory {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        async fn store(
+            &self,
+            _key: &str,
+            _content: &str,
+            _category: MemoryCategory,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn recall(&self, _query: &str, _limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
+            Ok(Vec::new())
+        }
+
+        async fn get(&self, _key: &str) -> anyhow::Result<Option<MemoryEntry>> {
+            Ok(None)
+        }
+
+        async fn list(
+            &self,
+            _category: Option<&MemoryCategory>,
+        ) -> anyhow::Result<Vec<MemoryEntry>> {
+            Ok(Vec::new())
+        }
+
+        async fn list_all(
+            &self,
+            _category: Option<&MemoryCategory>,
+        ) -> anyhow::Result<Vec<MemoryEntry>> {
+            Ok(Vec::new())
+        }
+
+        async fn forget(&self, _key: &str) -> anyhow::Result<bool> {
+            Ok(false)
+        }
+
+        async fn count(&self) -> anyhow::Result<usize> {
+            Ok(0)
+        }
+
+        async fn health_check(&self) -> bool {
+            true
+        }
+    }
+
+    #[derive(Default)]
+    struct MockProvider {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok("ok".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn webhook_idempotency_skips_duplicate_provider_calls() {
+        let provider_impl = Arc::new(MockProvider::default());
+        let provider: Arc<dyn Provider> = provider_impl.clone();
+        let memory: Arc<dyn Memory> = Arc::new(MockMemory);
+
+        let state = AppState {
+            provider,
+            settings: Arc::new(RwLock::new(test_settings())),
+            config: Arc::new(RwLock::new(Config::default())),
+            mem: memory,
+            auto_save: false,
+            webhook_secret: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300))),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            slack_signing_secret: None,
+            auth_token: None,
+            concurrency: Arc::new(ConcurrencyLimiter::new(0, 0)),
+            audit: None,
+            channels: Arc::new(HashMap::new()),
+            ingest_max_bytes: 1024 * 1024,
+            chunk_max_tokens: 512,
+            trusted_proxies: Arc::new(TrustedProxies::default()),
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Idempotency-Key", HeaderValue::from_static("abc-123"));
+
+        let body = Ok(Json(WebhookBody {
+            message: "hello".into(),
+            stream: false,
+            session_id: None,
+            model: None,
+            overrides: GenerationOverrides::default(),
+            images: Vec::new(),
+            json_schema: None,
+        }));
+        let first = handle_webhook(State(state.clone()), None, headers.clone(), body)
+            .await
+            .into_response();
+        assert_eq!(first.status(), StatusCode::OK);
+
+        let body = Ok(Json(WebhookBody {
+            message: "hello".into(),
+            stream: false,
+            session_id: None,
+            model: None,
+            overrides: GenerationOverrides::default(),
+            images: Vec::new(),
+            json_schema: None,
+        }));
+        let second = handle_webhook(State(state), None, headers, body)
+            .await
+            .into_response();
+        assert_eq!(second.status(), StatusCode::OK);
+
+        let payload = second.into_body().collect().await.unwrap().to_bytes();
+        let parsed: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(parsed["status"], "duplicate");
+        assert_eq!(parsed["idempotent"], true);
+        assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 1);
+    }
+
+    fn test_settings() -> GatewaySettings {
+        GatewaySettings {
+            model: "test-model".into(),
+            options: ChatOptions::new(0.0),
+            allowed_models: Vec::new(),
+            batch_max_items: 50,
+            batch_concurrency: 4,
+            webhook_system_prompt: None,
+            ws_system_prompt: None,
+            whatsapp_system_prompt: None,
+            slack_system_prompt: None,
+            prompt_templates: BTreeMap::new(),
+            workspace_dir: std::path::PathBuf::new(),
+            whatsapp: test_channel_settings(),
+            slack: test_channel_settings(),
+        }
+    }
+
+    fn test_channel_settings() -> ChannelSettings {
+        ChannelSettings {
+            model: "test-model".into(),
+            options: ChatOptions::new(0.0),
+            access: None,
+        }
+    }
+
+    fn test_state(provider: Arc<dyn Provider>) -> AppState {
+        AppState {
+            provider,
+            settings: Arc::new(RwLock::new(test_settings())),
+            config: Arc::new(RwLock::new(Config {
+                cost: crate::config::CostConfig {
+                    enabled: false,
+                    ..crate::config::CostConfig::default()
+                },
+                ..Config::default()
+            })),
+            mem: Arc::new(MockMemory),
+            auto_save: false,
+            webhook_secret: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300))),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            slack_signing_secret: None,
+            auth_token: None,
+            concurrency: Arc::new(ConcurrencyLimiter::new(0, 0)),
+            audit: None,
+            channels: Arc::new(HashMap::new()),
+            ingest_max_bytes: 1024 * 1024,
+            chunk_max_tokens: 512,
+            trusted_proxies: Arc::new(TrustedProxies::default()),
+        }
+    }
+
+    async fn send(
+        app: Router,
+        request: axum::http::Request<axum::body::Body>,
+    ) -> axum::response::Response {
+        let mut app = app;
+        tower::Service::call(&mut app, request).await.unwrap()
+    }
+
+    fn webhook_request(bearer: Option<&str>) -> axum::http::Request<axum::body::Body> {
+        let mut builder = axum::http::Request::builder()
+            .method("POST")
+            .uri("/webhook")
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(token) = bearer {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        builder
+            .body(axum::body::Body::from(r#"{"message": "hello"}"#))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn auth_token_rejects_missing_and_wrong_bearer() {
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.auth_token = Some(Arc::from("gw-secret"));
+        let app = build_router(state);
+
+        let response = send(app.clone(), webhook_request(None)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = send(app.clone(), webhook_request(Some("wrong"))).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let pair = axum::http::Request::builder()
+            .method("POST")
+            .uri("/pair")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = send(app, pair).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn auth_token_accepts_matching_bearer() {
+        let provider_impl = Arc::new(MockProvider::default());
+        let mut state = test_state(provider_impl.clone());
+        state.auth_token = Some(Arc::from("gw-secret"));
+        let app = build_router(state);
+
+        let response = send(app, webhook_request(Some("gw-secret"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn auth_token_satisfies_pairing_requirement() {
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.pairing = Arc::new(PairingGuard::new(true, &["zc_paired".into()]));
+        state.auth_token = Some(Arc::from("gw-secret"));
+        let app = build_router(state);
+
+        let response = send(app, webhook_request(Some("gw-secret"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn auth_token_leaves_health_public() {
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.auth_token = Some(Arc::from("gw-secret"));
+        let app = build_router(state);
+
+        let request = axum::http::Request::builder()
+            .uri("/health")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = send(app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn no_auth_token_keeps_webhook_open() {
+        let app = build_router(test_state(Arc::new(MockProvider::default())));
+        let response = send(app, webhook_request(None)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn parse_ws_message_accepts_json_and_plain_text() {
+        assert_eq!(
+            parse_ws_message(r#"{"message": "hello"}"#).as_deref(),
+            Some("hello")
+        );
+        assert_eq!(
+            parse_ws_message("  plain text  ").as_deref(),
+            Some("plain text")
+        );
+        assert!(parse_ws_message("   ").is_none());
+        assert!(parse_ws_message(r#"{"other": "x"}"#).is_none());
+        assert!(parse_ws_message(r#"{"message": "  "}"#).is_none());
+    }
+
+    #[test]
+    fn trim_history_drops_oldest_turns() {
+        let mut history = Vec::new();
+        for i in 0..(WS_MAX_HISTORY_MESSAGES / 2 + 3) {
+            history.push(ChatMessage::user(format!("q{i}")));
+            history.push(ChatMessage::assistant(format!("a{i}")));
+        }
+        trim_history(&mut history, WS_MAX_HISTORY_MESSAGES);
+        assert_eq!(history.len(), WS_MAX_HISTORY_MESSAGES);
+        assert_eq!(history[0].role, "user");
+        assert_eq!(history[0].content, "q3");
+    }
+
+    struct HistoryCountProvider;
+
+    #[async_trait]
+    impl Provider for HistoryCountProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            Ok("single".into())
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            Ok(format!("messages={}", messages.len()).into())
+        }
+    }
+
+    async fn next_ws_json<S>(ws: &mut S) -> serde_json::Value
+    where
+        S: futures_util::Stream<
+                Item = Result<
+                    tokio_tungstenite::tungstenite::Message,
+                    tokio_tungstenite::tungstenite::Error,
+                >,
+            > + Unpin,
+    {
+        use futures_util::StreamExt;
+        loop {
+            if let tokio_tungstenite::tungstenite::Message::Text(text) =
+                ws.next().await.unwrap().unwrap()
+            {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ws_session_keeps_per_connection_history() {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message as ClientMessage;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = build_router(test_state(Arc::new(HistoryCountProvider)));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+            .await
+            .unwrap();
+
+        ws.send(ClientMessage::Text("first".into())).await.unwrap();
+        let delta = next_ws_json(&mut ws).await;
+        assert_eq!(delta["type"], "delta");
+        assert_eq!(delta["content"], "messages=1");
+        let done = next_ws_json(&mut ws).await;
+        assert_eq!(done["type"], "done");
+        assert_eq!(done["model"], "test-model");
+
+        ws.send(ClientMessage::Text(r#"{"message": "second"}"#.into()))
+            .await
+            .unwrap();
+        let delta = next_ws_json(&mut ws).await;
+        assert_eq!(delta["content"], "messages=3");
+        assert_eq!(next_ws_json(&mut ws).await["type"], "done");
+    }
+
+    #[tokio::test]
+    async fn ws_rejects_unpaired_client() {
+        let mut state = test_state(Arc::new(HistoryCountProvider));
+        state.pairing = Arc::new(PairingGuard::new(true, &["zc_paired".into()]));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = build_router(state);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let result = tokio_tungstenite::connect_async(format!("ws://{addr}/ws")).await;
+        match result {
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+                assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            }
+            other => panic!("expected HTTP 401 rejection, got {other:?}"),
+        }
+    }
+
+    fn cors_config(origins: &[&str]) -> GatewayCorsConfig {
+        GatewayCorsConfig {
+            allowed_origins: origins.iter().map(|o| (*o).to_string()).collect(),
+            ..GatewayCorsConfig::default()
+        }
+    }
+
+    #[test]
+    fn cors_layer_disabled_without_origins() {
+        assert!(build_cors_layer(&GatewayCorsConfig::default())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn cors_layer_rejects_invalid_entries() {
+        assert!(build_cors_layer(&cors_config(&["bad\norigin"])).is_err());
+
+        let mut cfg = cors_config(&["*"]);
+        cfg.allowed_methods = vec!["NOT A METHOD".into()];
+        assert!(build_cors_layer(&cfg).is_err());
+
+        let mut cfg = cors_config(&["*"]);
+        cfg.allowed_headers = vec!["bad header".into()];
+        assert!(build_cors_layer(&cfg).is_err());
+    }
+
+    #[tokio::test]
+    async fn cors_preflight_bypasses_auth_token() {
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.auth_token = Some(Arc::from("gw-secret"));
+        let cors = build_cors_layer(&cors_config(&["https://app.example.com"]))
+            .unwrap()
+            .unwrap();
+        let app = build_router(state).layer(cors);
+
+        let preflight = axum::http::Request::builder()
+            .method("OPTIONS")
+            .uri("/webhook")
+            .header(header::ORIGIN, "https://app.example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .header(
+                header::ACCESS_CONTROL_REQUEST_HEADERS,
+                "authorization,content-type",
+            )
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = send(app, preflight).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some(&HeaderValue::from_static("https://app.example.com"))
+        );
+        assert!(response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_METHODS)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|m| m.contains("POST")));
+    }
+
+    #[tokio::test]
+    async fn cors_headers_only_for_allowed_origin() {
+        let cors = build_cors_layer(&cors_config(&["https://app.example.com"]))
+            .unwrap()
+            .unwrap();
+        let app = build_router(test_state(Arc::new(MockProvider::default()))).layer(cors);
+
+        let mut request = webhook_request(None);
+        request.headers_mut().insert(
+            header::ORIGIN,
+            HeaderValue::from_static("https://app.example.com"),
+        );
+        let response = send(app.clone(), request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let mut request = webhook_request(None);
+        request.headers_mut().insert(
+            header::ORIGIN,
+            HeaderValue::from_static("https://evil.example"),
+        );
+        let response = send(app, request).await;
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    fn sqlite_state() -> (tempfile::TempDir, AppState) {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.mem = Arc::new(crate::memory::SqliteMemory::new(tmp.path()).unwrap());
+        (tmp, state)
+    }
+
+    fn memory_request(
+        method: &str,
+        uri: &str,
+        body: Option<&str>,
+    ) -> axum::http::Request<axum::body::Body> {
+        let builder = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json");
+        builder
+            .body(body.map_or_else(axum::body::Body::empty, |b| {
+                axum::body::Body::from(b.to_owned())
+            }))
+            .unwrap()
+    }
+
+    async fn json_body(response: axum::response::Response) -> serde_json::Value {
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    #[tokio::test]
+    async fn memory_api_put_get_list_delete_roundtrip() {
+        let (_tmp, state) = sqlite_state();
+        let app = build_router(state);
+
+        let response = send(
+            app.clone(),
+            memory_request(
+                "PUT",
+                "/memory/lang",
+                Some(r#"{"content": "User prefers Rust", "category": "core"}"#),
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["stored"], true);
+
+        let response = send(app.clone(), memory_request("GET", "/memory/lang", None)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let entry = json_body(response).await;
+        assert_eq!(entry["content"], "User prefers Rust");
+        assert_eq!(entry["category"], "core");
+
+        let response = send(
+            app.clone(),
+            memory_request("GET", "/memory?category=core", None),
+        )
+        .await;
+        let listed = json_body(response).await;
+        assert_eq!(listed["count"], 1);
+
+        let response = send(
+            app.clone(),
+            memory_request("GET", "/memory?category=daily", None),
+        )
+        .await;
+        assert_eq!(json_body(response).await["count"], 0);
+
+        let response = send(app.clone(), memory_request("DELETE", "/memory/lang", None)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = send(app, memory_request("GET", "/memory/lang", None)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn memory_api_search_finds_entries() {
+        let (_tmp, state) = sqlite_state();
+        state
+            .mem
+            .store("tz", "User timezone is UTC+2", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let app = build_router(state);
+
+        let response = send(
+            app.clone(),
+            memory_request("GET", "/memory/search?q=timezone", None),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["entries"][0]["key"], "tz");
+
+        let response = send(app, memory_request("GET", "/memory/search", None)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn memory_api_tags_filter_list_and_search() {
+        let (_tmp, state) = sqlite_state();
+        let app = build_router(state);
+
+        let response = send(
+            app.clone(),
+            memory_request(
+                "PUT",
+                "/memory/x_db",
+                Some(r#"{"content": "database is Postgres", "tags": ["Project-X"]}"#),
+            ),
+        )
+        .await;
+        assert_eq!(json_body(response).await["tags"][0], "project-x");
+        send(
+            app.clone(),
+            memory_request(
+                "PUT",
+                "/memory/y_db",
+                Some(r#"{"content": "database is SQLite"}"#),
+            ),
+        )
+        .await;
+
+        let response = send(
+            app.clone(),
+            memory_request("GET", "/memory?tag=project-x", None),
+        )
+        .await;
+        let listed = json_body(response).await;
+        assert_eq!(listed["count"], 1);
+        assert_eq!(listed["entries"][0]["key"], "x_db");
+        assert_eq!(listed["entries"][0]["tags"][0], "project-x");
+
+        let response = send(
+            app,
+            memory_request("GET", "/memory/search?q=database&tag=project-x", None),
+        )
+        .await;
+        let body = json_body(response).await;
+        assert_eq!(body["entries"].as_array().unwrap().len(), 1);
+        assert_eq!(body["entries"][0]["key"], "x_db");
+    }
+
+    #[tokio::test]
+    async fn memory_api_put_rejects_bad_json_and_delete_missing_is_404() {
+        let (_tmp, state) = sqlite_state();
+        let app = build_router(state);
+
+        let response = send(
+            app.clone(),
+            memory_request("PUT", "/memory/x", Some(r#"{"nope": 1}"#)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = send(app, memory_request("DELETE", "/memory/missing", None)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn memory_api_patch_updates_existing_entries_only() {
+        let (_tmp, state) = sqlite_state();
+        state
+            .mem
+            .store_with_tags("tz", "UTC", MemoryCategory::Core, &["profile".into()])
+            .await
+            .unwrap();
+        let app = build_router(state);
+
+        let response = send(
+            app.clone(),
+            memory_request("PATCH", "/memory/tz", Some(r#"{"content": "UTC+2"}"#)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let entry = json_body(response).await;
+        assert_eq!(entry["content"], "UTC+2");
+        assert_eq!(entry["tags"][0], "profile");
+
+        let response = send(
+            app.clone(),
+            memory_request("PATCH", "/memory/tz", Some("{}")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = send(
+            app.clone(),
+            memory_request("PATCH", "/memory/missing", Some(r#"{"content": "x"}"#)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = send(app, memory_request("GET", "/memory/missing", None)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn memory_sync_serves_and_merges_changesets() {
+        let (_tmp, state) = sqlite_state();
+        state
+            .mem
+            .store("tz", "UTC", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let app = build_router(state);
+
+        let response = send(app.clone(), memory_request("GET", "/memory/sync", None)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let changes = json_body(response).await;
+        assert_eq!(changes["entries"][0]["key"], "tz");
+        let since = changes["generated_at"]
+            .as_str()
+            .unwrap()
+            .replace('+', "%2B");
+
+        let mut peer_entry = changes["entries"][0].clone();
+        peer_entry["key"] = "lang".into();
+        peer_entry["content"] = "User prefers Rust".into();
+        let body = serde_json::json!({
+            "version": 1,
+            "generated_at": "2026-01-01T00:00:00Z",
+            "entries": [peer_entry],
+            "tombstones": [{"key": "tz", "deleted_at": "2999-01-01T00:00:00Z"}]
+        });
+        let response = send(
+            app.clone(),
+            memory_request("POST", "/memory/sync", Some(&body.to_string())),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let report = json_body(response).await;
+        assert_eq!(report["stored"], 1);
+        assert_eq!(report["deleted"], 1);
+
+        // Only the merged entry (and the tombstone) changed since
+        let uri = format!("/memory/sync?since={since}");
+        let changes = json_body(send(app.clone(), memory_request("GET", &uri, None)).await).await;
+        assert_eq!(changes["entries"].as_array().unwrap().len(), 1);
+        assert_eq!(changes["tombstones"][0]["key"], "tz");
+
+        let response = send(
+            app.clone(),
+            memory_request("GET", "/memory/sync?since=yesterday", None),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = send(app, memory_request("POST", "/memory/sync", Some("{}"))).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn memory_api_requires_pairing() {
+        let (_tmp, mut state) = sqlite_state();
+        state.pairing = Arc::new(PairingGuard::new(true, &["zc_paired".into()]));
+        let app = build_router(state);
+
+        let response = send(app.clone(), memory_request("GET", "/memory", None)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let mut request = memory_request("GET", "/memory", None);
+        request.headers_mut().insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer zc_paired"),
+        );
+        let response = send(app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    /// Replies with the model, temperature and token limit it was called with.
+    struct ModelEchoProvider;
+
+    #[async_trait]
+    impl Provider for ModelEchoProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            model: &str,
+            options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            let limit = options
+                .max_tokens
+                .map_or_else(String::new, |n| format!(":{n}"));
+            Ok(format!("{model}@{}{limit}", options.temperature).into())
+        }
+    }
+
+    fn override_request(body: &serde_json::Value) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/webhook")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn webhook_applies_allowed_model_and_temperature_overrides() {
+        let state = test_state(Arc::new(ModelEchoProvider));
+        state.settings.write().unwrap().allowed_models = vec!["cheap-model".to_string()];
+        let app = build_router(state);
+
+        let response = send(
+            app.clone(),
+            override_request(&serde_json::json!({"message": "hi"})),
+        )
+        .await;
+        let body = json_body(response).await;
+        assert_eq!(body["response"], "test-model@0");
+
+        let response = send(
+            app,
+            override_request(&serde_json::json!({
+                "message": "hi",
+                "model": "cheap-model",
+                "temperature": 1.5,
+                "max_tokens": 256
+            })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["response"], "cheap-model@1.5:256");
+        assert_eq!(body["model"], "cheap-model");
+    }
+
+    /// Echoes the system prompt it was given, for both single-turn and
+    /// session (history) requests.
+    struct SystemEchoProvider;
+
+    #[async_trait]
+    impl Provider for SystemEchoProvider {
+        async fn chat_with_system(
+            &self,
+            system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            Ok(format!("{}|{message}", system_prompt.unwrap_or("none")).into())
+        }
+    }
+
+    #[tokio::test]
+    async fn webhook_sends_configured_system_prompt() {
+        let state = test_state(Arc::new(SystemEchoProvider));
+        state.settings.write().unwrap().webhook_system_prompt = Some("You are Claw.".into());
+
+        let app = build_router(state.clone());
+        let response = send(app, override_request(&serde_json::json!({"message": "hi"}))).await;
+        assert_eq!(json_body(response).await["response"], "You are Claw.|hi");
+
+        let app = build_router(state);
+        let response = send(
+            app,
+            override_request(&serde_json::json!({"message": "again", "session_id": "s1"})),
+        )
+        .await;
+        assert_eq!(json_body(response).await["response"], "You are Claw.|again");
+
+        let app = build_router(test_state(Arc::new(SystemEchoProvider)));
+        let response = send(app, override_request(&serde_json::json!({"message": "hi"}))).await;
+        assert_eq!(json_body(response).await["response"], "none|hi");
+    }
+
+    #[tokio::test]
+    async fn webhook_renders_route_prompt_template() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("prompts")).unwrap();
+        std::fs::write(
+            tmp.path().join("prompts").join("ops.md"),
+            "Ops bot, asked: {{message}}",
+        )
+        .unwrap();
+        let state = test_state(Arc::new(SystemEchoProvider));
+        {
+            let mut settings = state.settings.write().unwrap();
+            settings.webhook_system_prompt = Some("You are Claw.".into());
+            settings.workspace_dir = tmp.path().to_path_buf();
+            settings
+                .prompt_templates
+                .insert("webhook".into(), "ops".into());
+        }
+
+        let app = build_router(state.clone());
+        let response = send(app, override_request(&serde_json::json!({"message": "hi"}))).await;
+        assert_eq!(
+            json_body(response).await["response"],
+            "Ops bot, asked: hi|hi"
+        );
+
+        // A missing template falls back to the configured system prompt.
+        std::fs::remove_file(tmp.path().join("prompts").join("ops.md")).unwrap();
+        let app = build_router(state);
+        let response = send(app, override_request(&serde_json::json!({"message": "hi"}))).await;
+        assert_eq!(json_body(response).await["response"], "You are Claw.|hi");
+    }
+
+    /// Replies with the image URLs attached to the last user message.
+    struct ImageEchoProvider;
+
+    #[async_trait]
+    impl Provider for ImageEchoProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            Ok("no images".into())
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            let last = messages.last().unwrap();
+            let urls: Vec<String> = last.images.iter().map(ImageInput::to_url).collect();
+            Ok(format!("{}|{}", last.content, urls.join(",")).into())
+        }
+    }
+
+    #[tokio::test]
+    async fn webhook_forwards_images_to_provider() {
+        let state = test_state(Arc::new(ImageEchoProvider));
+        let image = serde_json::json!({"type": "url", "url": "https://x.test/cat.png"});
+
+        let app = build_router(state.clone());
+        let response = send(
+            app,
+            override_request(&serde_json::json!({"message": "what is this?", "images": [image]})),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            json_body(response).await["response"],
+            "what is this?|https://x.test/cat.png"
+        );
+
+        let app = build_router(state.clone());
+        let response = send(
+            app,
+            override_request(&serde_json::json!({
+                "message": "and now?",
+                "session_id": "img",
+                "images": [{"type": "base64", "media_type": "image/png", "data": "cG5n"}]
+            })),
+        )
+        .await;
+        assert_eq!(
+            json_body(response).await["response"],
+            "and now?|data:image/png;base64,cG5n"
+        );
+
+        let app = build_router(state);
+        let response = send(app, override_request(&serde_json::json!({"message": "hi"}))).await;
+        assert_eq!(json_body(response).await["response"], "no images");
+    }
+
+    #[tokio::test]
+    async fn webhook_rejects_unusable_images() {
+        for images in [
+            serde_json::json!([{"type": "url", "url": "file:///etc/passwd"}]),
+            serde_json::json!([{"type": "base64", "media_type": "text/plain", "data": "aGk="}]),
+        ] {
+            let app = build_router(test_state(Arc::new(ImageEchoProvider)));
+            let response = send(
+                app,
+                override_request(&serde_json::json!({"message": "hi", "images": images})),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    /// Answers with a bare string until it is told the reply was invalid,
+    /// then with a JSON object; `always_invalid` never corrects itself.
+    struct JsonFixingProvider {
+        always_invalid: bool,
+    }
+
+    #[async_trait]
+    impl Provider for JsonFixingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            unreachable!("structured replies use chat_with_history")
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            assert!(options.response_format.is_some());
+            let corrected = messages.iter().any(|m| m.role == "assistant");
+            if corrected && !self.always_invalid {
+                Ok(ChatReply::new(r#"{"sentiment": "positive"}"#, None))
+            } else {
+                Ok(ChatReply::new("positive", None))
+            }
+        }
+    }
+
+    fn sentiment_request(extra: serde_json::Value) -> axum::http::Request<axum::body::Body> {
+        let mut body = serde_json::json!({
+            "message": "I love it",
+            "json_schema": {
+                "type": "object",
+                "properties": {"sentiment": {"enum": ["positive", "negative"]}},
+                "required": ["sentiment"]
+            }
+        });
+        if let (Some(body), Some(extra)) = (body.as_object_mut(), extra.as_object()) {
+            body.extend(extra.clone());
+        }
+        override_request(&body)
+    }
+
+    #[tokio::test]
+    async fn webhook_json_schema_returns_validated_json() {
+        let provider = Arc::new(JsonFixingProvider {
+            always_invalid: false,
+        });
+        let app = build_router(test_state(provider));
+        let response = send(app, sentiment_request(serde_json::json!({}))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["json"], serde_json::json!({"sentiment": "positive"}));
+        assert_eq!(body["response"], r#"{"sentiment": "positive"}"#);
+    }
+
+    #[tokio::test]
+    async fn webhook_json_schema_reports_exhausted_retries() {
+        let provider = Arc::new(JsonFixingProvider {
+            always_invalid: true,
+        });
+        let app = build_router(test_state(provider));
+        let response = send(app, sentiment_request(serde_json::json!({}))).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = json_body(response).await;
+        assert_eq!(body["response"], "positive");
+        assert!(body["error"].as_str().unwrap().contains("JSON schema"));
+    }
+
+    #[tokio::test]
+    async fn webhook_json_schema_rejects_streaming_and_bad_schemas() {
+        for extra in [
+            serde_json::json!({"stream": true}),
+            serde_json::json!({"json_schema": "object"}),
+        ] {
+            let provider = Arc::new(JsonFixingProvider {
+                always_invalid: false,
+            });
+            let app = build_router(test_state(provider));
+            let response = send(app, sentiment_request(extra)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    /// Reports fixed token usage with every reply.
+    struct UsageProvider;
+
+    #[async_trait]
+    impl Provider for UsageProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            Ok(ChatReply::new(
+                "counted",
+                Some(crate::providers::traits::TokenUsage::new(12, 5)),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn webhook_reports_token_usage_when_provider_returns_it() {
+        let app = build_router(test_state(Arc::new(UsageProvider)));
+        let response = send(app, override_request(&serde_json::json!({"message": "hi"}))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["response"], "counted");
+        assert_eq!(body["usage"]["prompt_tokens"], 12);
+        assert_eq!(body["usage"]["completion_tokens"], 5);
+        assert_eq!(body["usage"]["total_tokens"], 17);
+
+        let app = build_router(test_state(Arc::new(ModelEchoProvider)));
+        let response = send(app, override_request(&serde_json::json!({"message": "hi"}))).await;
+        let body = json_body(response).await;
+        assert!(body.get("usage").is_none());
+    }
+
+    #[tokio::test]
+    async fn usage_endpoint_reports_recorded_webhook_usage() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let state = test_state(Arc::new(UsageProvider));
+        {
+            let mut config = state.config.write().unwrap();
+            config.workspace_dir = tmp.path().to_path_buf();
+            config.cost.enabled = true;
+            config.cost.prices.insert(
+                "test-model".into(),
+                crate::config::ModelPriceConfig {
+                    input_per_million: 1.0,
+                    output_per_million: 2.0,
+                },
+            );
+        }
+        let app = build_router(state);
+
+        for _ in 0..2 {
+            let response = send(
+                app.clone(),
+                override_request(&serde_json::json!({"message": "hi"})),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let request = axum::http::Request::builder()
+            .uri("/usage?days=1&by=channel")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = send(app.clone(), request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["by"], "channel");
+        assert_eq!(body["rows"][0]["key"], "webhook");
+        assert_eq!(body["rows"][0]["requests"], 2);
+        assert_eq!(body["total"]["prompt_tokens"], 24);
+        assert_eq!(body["total"]["completion_tokens"], 10);
+        let cost = body["total"]["cost_usd"].as_f64().unwrap();
+        assert!((cost - 0.000_044).abs() < 1e-12);
+
+        let request = axum::http::Request::builder()
+            .uri("/usage?by=week")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = send(app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn webhook_rejects_models_outside_allowlist() {
+        let provider_impl = Arc::new(MockProvider::default());
+        let app = build_router(test_state(provider_impl.clone()));
+
+        let response = send(
+            app,
+            override_request(&serde_json::json!({"message": "hi", "model": "gpt-4-32k"})),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = json_body(response).await;
+        assert!(body["error"].as_str().unwrap().contains("gpt-4-32k"));
+        assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn webhook_rejects_out_of_range_overrides() {
+        let app = build_router(test_state(Arc::new(MockProvider::default())));
+
+        for body in [
+            serde_json::json!({"message": "hi", "temperature": 2.5}),
+            serde_json::json!({"message": "hi", "temperature": -0.1}),
+            serde_json::json!({"message": "hi", "max_tokens": 0}),
+            serde_json::json!({"message": "hi", "top_p": 1.5}),
+        ] {
+            let response = send(app.clone(), override_request(&body)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "body: {body}");
+        }
+    }
+
+    /// Echoes each message back, fails on "fail", and records peak concurrency.
+    #[derive(Default)]
+    struct BatchProvider {
+        active: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Provider for BatchProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            let now = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            if message == "fail" {
+                anyhow::bail!("boom");
+            }
+            Ok(message.to_uppercase().into())
+        }
+    }
+
+    fn batch_request(body: &serde_json::Value) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/batch")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn batch_returns_ordered_results_with_item_errors() {
+        let provider_impl = Arc::new(BatchProvider::default());
+        let state = test_state(provider_impl.clone());
+        state.settings.write().unwrap().batch_concurrency = 2;
+        let app = build_router(state);
+
+        let messages = ["a", "fail", "c", "d", "e"];
+        let response = send(
+            app,
+            batch_request(&serde_json::json!({"messages": messages})),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = json_body(response).await;
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0]["response"], "A");
+        assert_eq!(results[1]["index"], 1);
+        assert_eq!(results[1]["error"]["message"], "LLM request failed");
+        assert_eq!(results[4]["response"], "E");
+        assert_eq!(provider_impl.peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn batch_rejects_empty_and_oversized_requests() {
+        let state = test_state(Arc::new(BatchProvider::default()));
+        state.settings.write().unwrap().batch_max_items = 2;
+        let app = build_router(state);
+
+        for messages in [vec![], vec!["a", "b", "c"]] {
+            let response = send(
+                app.clone(),
+                batch_request(&serde_json::json!({"messages": messages})),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_requires_pairing() {
+        let mut state = test_state(Arc::new(BatchProvider::default()));
+        state.pairing = Arc::new(PairingGuard::new(true, &["zc_paired".into()]));
+        let app = build_router(state);
+
+        let response = send(app, batch_request(&serde_json::json!({"messages": ["a"]}))).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    /// Chats fine but reports rejected credentials to health checks.
+    struct BrokenCredentialsProvider;
+
+    #[async_trait]
+    impl Provider for BrokenCredentialsProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            Ok("ok".into())
+        }
+
+        async fn health_check(&self) -> anyhow::Result<()> {
+            anyhow::bail!("OpenRouter API error (401 Unauthorized): invalid key")
+        }
+    }
+
+    fn get_request(uri: &str) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::builder()
+            .uri(uri)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn health_includes_last_provider_check() {
+        let _check = crate::health::PROVIDER_CHECK_LOCK.lock().await;
+        let app = build_router(test_state(Arc::new(BrokenCredentialsProvider)));
+
+        let timeout = Duration::from_secs(1);
+        assert!(
+            providers::check_provider_health(&BrokenCredentialsProvider, timeout)
+                .await
+                .is_err()
+        );
+        let response = send(app.clone(), get_request("/health")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["provider"], "error");
+
+        let healthy = MockProvider::default();
+        assert!(providers::check_provider_health(&healthy, timeout)
+            .await
+            .is_ok());
+        let response = send(app, get_request("/health")).await;
+        assert_eq!(json_body(response).await["provider"], "ok");
+    }
+
+    #[tokio::test]
+    async fn livez_reports_process_up() {
+        let app = build_router(test_state(Arc::new(BrokenCredentialsProvider)));
+        let response = send(app, get_request("/livez")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["pid"], std::process::id());
+    }
+
+    #[tokio::test]
+    async fn readyz_fails_when_provider_credentials_are_broken() {
+        let _check = crate::health::PROVIDER_CHECK_LOCK.lock().await;
+        let app = build_router(test_state(Arc::new(BrokenCredentialsProvider)));
+        let response = send(app, get_request("/readyz")).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = json_body(response).await;
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["provider"]["ready"], false);
+        assert!(body["provider"]["error"].as_str().unwrap().contains("401"));
+        assert_eq!(body["memory"]["ready"], true);
+    }
+
+    #[tokio::test]
+    async fn readyz_reports_channel_listener_failures() {
+        let _check = crate::health::PROVIDER_CHECK_LOCK.lock().await;
+        crate::health::mark_component_error("channel:readyz-test", "listener down");
+        let state = test_state(Arc::new(MockProvider::default()));
+        let report = readiness_report(&state).await;
+
+        assert!(report.provider.ready);
+        assert!(report.memory.ready);
+        let channel = &report.channels["readyz-test"];
+        assert!(!channel.ready);
+        assert_eq!(channel.error.as_deref(), Some("listener down"));
+        assert!(!report.ready);
+    }
+
+    #[tokio::test]
+    async fn probes_bypass_auth_token() {
+        let _check = crate::health::PROVIDER_CHECK_LOCK.lock().await;
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.auth_token = Some(Arc::from("static-secret"));
+        let app = build_router(state);
+
+        let response = send(app.clone(), get_request("/livez")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(app, get_request("/readyz")).await;
+        assert_ne!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    fn admin_request(
+        method: &str,
+        uri: &str,
+        bearer: Option<&str>,
+    ) -> axum::http::Request<axum::body::Body> {
+        let mut builder = axum::http::Request::builder().method(method).uri(uri);
+        if let Some(token) = bearer {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        builder.body(axum::body::Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn admin_config_requires_credentials_and_redacts_secrets() {
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        {
+            let mut config = state.config.write().unwrap();
+            config.api_key = Some("sk-very-secret".into());
+            config.gateway.auth_token = Some("static-secret".into());
+        }
+        let app = build_router(state.clone());
+        let response = send(app, admin_request("GET", "/admin/config", None)).await;
+        assert_eq!(
+            response.status(),
+            StatusCode::FORBIDDEN,
+            "admin must be disabled without auth token or pairing"
+        );
+
+        state.auth_token = Some(Arc::from("static-secret"));
+        let app = build_router(state);
+        let response = send(app.clone(), admin_request("GET", "/admin/config", None)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = send(
+            app,
+            admin_request("GET", "/admin/config", Some("static-secret")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["api_key"], "***");
+        assert_eq!(body["gateway"]["auth_token"], "***");
+        assert!(!body.to_string().contains("sk-very-secret"));
+    }
+
+    #[tokio::test]
+    async fn admin_reload_applies_settings_without_restart() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut state = test_state(Arc::new(ModelEchoProvider));
+        state.auth_token = Some(Arc::from("static-secret"));
+        let mut on_disk = {
+            let mut config = state.config.write().unwrap();
+            config.config_path = tmp.path().join("config.toml");
+            config.workspace_dir = tmp.path().to_path_buf();
+            config.clone()
+        };
+        on_disk.gateway.allowed_models = vec!["cheap-model".into()];
+        on_disk.memory.auto_save = !on_disk.memory.auto_save;
+        on_disk.save().unwrap();
+
+        let app = build_router(state.clone());
+        let override_body = serde_json::json!({"message": "hi", "model": "cheap-model"});
+        let mut request = override_request(&override_body);
+        request.headers_mut().insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer static-secret"),
+        );
+        let response = send(app.clone(), request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = send(
+            app.clone(),
+            admin_request("POST", "/admin/reload", Some("static-secret")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert!(body["applied"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("gateway.allowed_models")));
+        assert!(body["restart_required"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("memory.auto_save")));
+
+        let mut request = override_request(&override_body);
+        request.headers_mut().insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer static-secret"),
+        );
+        let response = send(app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            state.settings().allowed_models,
+            vec!["cheap-model".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn admin_reload_keeps_config_when_file_is_invalid() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.auth_token = Some(Arc::from("static-secret"));
+        let path = tmp.path().join("config.toml");
+        std::fs::write(&path, "default_temperature = [").unwrap();
+        state.config.write().unwrap().config_path = path;
+
+        let app = build_router(state.clone());
+        let response = send(
+            app,
+            admin_request("POST", "/admin/reload", Some("static-secret")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(state.settings(), test_settings());
+    }
+
+    /// Records delivered messages; refuses recipients named "offline".
+    #[derive(Default)]
+    struct RecordingChannel {
+        sent: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "slack"
+        }
+
+        async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+            if recipient == "offline" {
+                anyhow::bail!("channel_not_found");
+            }
+            self.sent
+                .lock()
+                .unwrap()
+                .push((recipient.to_string(), message.to_string()));
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<crate::channels::traits::ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
         }
     }
 
-    // Acknowledge the webhook
-    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
-}
+    fn send_request(
+        channel: &str,
+        body: &serde_json::Value,
+    ) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri(format!("/send/{channel}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::memory::{Memory, MemoryCategory, MemoryEntry};
-    use crate::providers::Provider;
-    use async_trait::async_trait;
-    use axum::http::HeaderValue;
-    use axum::response::IntoResponse;
-    use http_body_util::BodyExt;
-    use std::sync::atomic::{AtomicUsize, Ordering};
+    fn state_with_slack(channel: Arc<RecordingChannel>) -> AppState {
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        let mut channels: HashMap<String, Arc<dyn Channel>> = HashMap::new();
+        channels.insert("slack".into(), channel);
+        state.channels = Arc::new(channels);
+        state
+    }
 
-    #[test]
-    fn security_body_limit_is_64kb() {
-        assert_eq!(MAX_BODY_SIZE, 65_536);
+    #[tokio::test]
+    async fn send_delivers_through_configured_channel() {
+        let channel = Arc::new(RecordingChannel::default());
+        let app = build_router(state_with_slack(channel.clone()));
+
+        let response = send(
+            app,
+            send_request(
+                "slack",
+                &serde_json::json!({"target": "C123", "message": "deploy finished"}),
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["status"], "sent");
+        assert_eq!(
+            *channel.sent.lock().unwrap(),
+            vec![("C123".to_string(), "deploy finished".to_string())]
+        );
     }
 
-    #[test]
-    fn security_timeout_is_30_seconds() {
-        assert_eq!(REQUEST_TIMEOUT_SECS, 30);
+    #[tokio::test]
+    async fn send_with_blocks_falls_back_to_message_text() {
+        let channel = Arc::new(RecordingChannel::default());
+        let app = build_router(state_with_slack(channel.clone()));
+
+        let response = send(
+            app,
+            send_request(
+                "slack",
+                &serde_json::json!({
+                    "target": "C123",
+                    "message": "Approve deploy-42?",
+                    "blocks": [crate::channels::SlackChannel::approval_block("deploy-42")]
+                }),
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            *channel.sent.lock().unwrap(),
+            vec![("C123".to_string(), "Approve deploy-42?".to_string())]
+        );
     }
 
-    #[test]
-    fn webhook_body_requires_message_field() {
-        let valid = r#"{"message": "hello"}"#;
-        let parsed: Result<WebhookBody, _> = serde_json::from_str(valid);
-        assert!(parsed.is_ok());
-        assert_eq!(parsed.unwrap().message, "hello");
+    #[tokio::test]
+    async fn send_reports_unknown_channel_and_delivery_failure() {
+        let app = build_router(state_with_slack(Arc::new(RecordingChannel::default())));
+
+        let response = send(
+            app.clone(),
+            send_request(
+                "telegram",
+                &serde_json::json!({"target": "1", "message": "hi"}),
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = send(
+            app.clone(),
+            send_request(
+                "slack",
+                &serde_json::json!({"target": "offline", "message": "hi"}),
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        let response = send(
+            app,
+            send_request(
+                "slack",
+                &serde_json::json!({"target": " ", "message": "hi"}),
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 
-        let missing = r#"{"other": "field"}"#;
-        let parsed: Result<WebhookBody, _> = serde_json::from_str(missing);
-        assert!(parsed.is_err());
+    #[tokio::test]
+    async fn send_requires_pairing() {
+        let channel = Arc::new(RecordingChannel::default());
+        let mut state = state_with_slack(channel.clone());
+        state.pairing = Arc::new(PairingGuard::new(true, &["zc_paired".into()]));
+        let app = build_router(state);
+
+        let response = send(
+            app,
+            send_request(
+                "slack",
+                &serde_json::json!({"target": "C1", "message": "hi"}),
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(channel.sent.lock().unwrap().is_empty());
     }
 
-    #[test]
-    fn whatsapp_query_fields_are_optional() {
-        let q = WhatsAppVerifyQuery {
-            mode: None,
-            verify_token: None,
-            challenge: None,
-        };
-        assert!(q.mode.is_none());
+    fn ingest_request(
+        uri: &str,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header(header::CONTENT_TYPE, content_type)
+            .body(axum::body::Body::from(body))
+            .unwrap()
     }
 
-    #[test]
-    fn app_state_is_clone() {
-        fn assert_clone<T: Clone>() {}
-        assert_clone::<AppState>();
+    #[tokio::test]
+    async fn ingest_raw_text_stores_chunks_under_document_category() {
+        let (_tmp, mut state) = sqlite_state();
+        state.chunk_max_tokens = 16;
+        let mem = Arc::clone(&state.mem);
+        let app = build_router(state);
+
+        let doc = "# Editors\n\nThe user prefers neovim.\n\n# Shell\n\nThe user runs fish.";
+        let response = send(
+            app,
+            ingest_request("/ingest?name=prefs", "text/markdown", doc.into()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["category"], "document");
+        let keys = body["documents"][0]["keys"].as_array().unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0], "doc:prefs:0");
+
+        let entry = mem.get("doc:prefs:1").await.unwrap().unwrap();
+        assert!(entry.content.contains("fish"));
+        assert_eq!(entry.category, MemoryCategory::Custom("document".into()));
     }
 
-    #[test]
-    fn gateway_rate_limiter_blocks_after_limit() {
-        let limiter = GatewayRateLimiter::new(2, 2);
-        assert!(limiter.allow_pair("127.0.0.1"));
-        assert!(limiter.allow_pair("127.0.0.1"));
-        assert!(!limiter.allow_pair("127.0.0.1"));
+    #[tokio::test]
+    async fn ingest_replaces_previous_chunks_of_same_document() {
+        let (_tmp, mut state) = sqlite_state();
+        state.chunk_max_tokens = 8;
+        let mem = Arc::clone(&state.mem);
+        let app = build_router(state);
+
+        let long = "# A\n\nfirst section\n\n# B\n\nsecond section";
+        send(
+            app.clone(),
+            ingest_request("/ingest?name=notes", "text/plain", long.into()),
+        )
+        .await;
+        assert!(mem.get("doc:notes:1").await.unwrap().is_some());
+
+        let response = send(
+            app,
+            ingest_request(
+                "/ingest?name=notes",
+                "text/plain",
+                b"just one line".to_vec(),
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(mem.get("doc:notes:1").await.unwrap().is_none());
+        assert_eq!(
+            mem.get("doc:notes:0").await.unwrap().unwrap().content,
+            "just one line"
+        );
+    }
+
+    #[tokio::test]
+    async fn ingest_accepts_multipart_files() {
+        let (_tmp, state) = sqlite_state();
+        let app = build_router(state);
+
+        let body = "--XBOUNDARY\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"guide.md\"\r\n\
+             Content-Type: text/markdown\r\n\r\n\
+             Deploy with cargo build --release.\r\n\
+             --XBOUNDARY\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"faq.txt\"\r\n\r\n\
+             Restart the daemon after config changes.\r\n\
+             --XBOUNDARY--\r\n";
+        let response = send(
+            app,
+            ingest_request(
+                "/ingest?category=reference",
+                "multipart/form-data; boundary=XBOUNDARY",
+                body.into(),
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["category"], "reference");
+        assert_eq!(body["documents"][0]["name"], "guide.md");
+        assert_eq!(body["documents"][1]["keys"][0], "doc:faq.txt:0");
+    }
+
+    #[tokio::test]
+    async fn ingest_has_its_own_body_limit() {
+        let (_tmp, mut state) = sqlite_state();
+        state.ingest_max_bytes = MAX_BODY_SIZE * 2;
+        let app = build_router(state);
+
+        let fits = "word ".repeat(MAX_BODY_SIZE / 5 + 100).into_bytes();
+        assert!(fits.len() > MAX_BODY_SIZE);
+        let response = send(
+            app.clone(),
+            ingest_request("/ingest?name=big", "text/plain", fits),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let too_big = vec![b'a'; MAX_BODY_SIZE * 2 + 1];
+        let response = send(
+            app.clone(),
+            ingest_request("/ingest?name=huge", "text/plain", too_big),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let oversized_webhook =
+            serde_json::json!({"message": "a".repeat(MAX_BODY_SIZE)}).to_string();
+        let mut request = ingest_request(
+            "/webhook",
+            "application/json",
+            oversized_webhook.clone().into(),
+        );
+        request
+            .headers_mut()
+            .insert(header::CONTENT_LENGTH, oversized_webhook.len().into());
+        let response = send(app, request).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    fn session_request(
+        session: &str,
+        message: &str,
+        stream: bool,
+    ) -> axum::http::Request<axum::body::Body> {
+        let body = serde_json::json!({"message": message, "session_id": session, "stream": stream});
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/webhook")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
     }
 
     #[test]
-    fn idempotency_store_rejects_duplicate_key() {
-        let store = IdempotencyStore::new(Duration::from_secs(30));
-        assert!(store.record_if_new("req-1"));
-        assert!(!store.record_if_new("req-1"));
-        assert!(store.record_if_new("req-2"));
+    fn valid_session_id_rules() {
+        assert!(valid_session_id("user-42:chat_1.a"));
+        assert!(!valid_session_id(""));
+        assert!(!valid_session_id("has space"));
+        assert!(!valid_session_id(&"x".repeat(129)));
     }
 
-    #[derive(Default)]
-    struct MockMemory;
+    #[tokio::test]
+    async fn webhook_session_includes_previous_turns() {
+        let (_tmp, mut state) = sqlite_state();
+        state.provider = Arc::new(HistoryCountProvider);
+        let mem = Arc::clone(&state.mem);
+        let app = build_router(state);
+
+        let response = send(app.clone(), session_request("s1", "first", false)).await;
+        let body = json_body(response).await;
+        assert_eq!(body["response"], "messages=1");
+        assert_eq!(body["session_id"], "s1");
+
+        let response = send(app.clone(), session_request("s1", "second", false)).await;
+        assert_eq!(json_body(response).await["response"], "messages=3");
+
+        // Other sessions are independent
+        let response = send(app, session_request("s2", "hi", false)).await;
+        assert_eq!(json_body(response).await["response"], "messages=1");
+
+        let stored = load_session_history(mem.as_ref(), "s1").await;
+        assert_eq!(stored.len(), 4);
+        assert_eq!(stored[2].content, "second");
+        assert_eq!(stored[3].role, "assistant");
+    }
 
-    #[async_trait]
-    impl Memory for MockMemory {
-        fn name(&self) -> &str {
-            "mock"
-        }
+    #[tokio::test]
+    async fn memory_list_filters_by_session() {
+        let (_tmp, mut state) = sqlite_state();
+        state.provider = Arc::new(HistoryCountProvider);
+        state.auto_save = true;
+        let app = build_router(state);
+
+        send(app.clone(), session_request("s1", "first", false)).await;
+        send(app.clone(), session_request("s1", "second", false)).await;
+        send(app.clone(), session_request("s2", "other", false)).await;
+
+        let response = send(app, memory_request("GET", "/memory?session_id=s1", None)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        let entries = body["entries"].as_array().unwrap();
+        assert!(entries.iter().all(|e| e["session_id"] == "s1"));
+        let contents: Vec<&str> = entries
+            .iter()
+            .filter_map(|e| e["content"].as_str())
+            .collect();
+        assert!(contents.contains(&"first"));
+        assert!(contents.contains(&"second"));
+        assert!(!contents.contains(&"other"));
+    }
 
-        async fn store(
-            &self,
-            _key: &str,
-            _content: &str,
-            _category: MemoryCategory,
-        ) -> anyhow::Result<()> {
-            Ok(())
-        }
+    #[tokio::test]
+    async fn webhook_stream_persists_session_reply() {
+        let (_tmp, mut state) = sqlite_state();
+        state.provider = Arc::new(HistoryCountProvider);
+        let mem = Arc::clone(&state.mem);
+        let app = build_router(state);
+
+        let response = send(app, session_request("streamed", "hello", true)).await;
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(payload.to_vec()).unwrap();
+        assert!(text.contains(r#""session_id":"streamed""#));
+
+        let stored = load_session_history(mem.as_ref(), "streamed").await;
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[1].content, "messages=1");
+    }
 
-        async fn recall(&self, _query: &str, _limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
-            Ok(Vec::new())
+    #[tokio::test]
+    async fn webhook_session_history_is_capped() {
+        let (_tmp, state) = sqlite_state();
+        let mut history = Vec::new();
+        for i in 0..WEBHOOK_SESSION_MAX_MESSAGES {
+            history.push(ChatMessage::user(format!("q{i}")));
         }
+        save_session_history(state.mem.as_ref(), "long", &history).await;
+        let app = build_router(state.clone());
+
+        let response = send(app, session_request("long", "next", false)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let stored = load_session_history(state.mem.as_ref(), "long").await;
+        assert_eq!(stored.len(), WEBHOOK_SESSION_MAX_MESSAGES);
+        assert_eq!(stored.last().unwrap().role, "assistant");
+    }
 
-        async fn get(&self, _key: &str) -> anyhow::Result<Option<MemoryEntry>> {
-            Ok(None)
-        }
+    #[tokio::test]
+    async fn webhook_rejects_invalid_session_id() {
+        let app = build_router(test_state(Arc::new(MockProvider::default())));
+        let response = send(app, session_request("bad id!", "hi", false)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 
-        async fn list(
-            &self,
-            _category: Option<&MemoryCategory>,
-        ) -> anyhow::Result<Vec<MemoryEntry>> {
-            Ok(Vec::new())
-        }
+    #[tokio::test]
+    async fn concurrency_limiter_queues_then_sheds() {
+        let limiter = Arc::new(ConcurrencyLimiter::new(1, 1));
+        let held = limiter.acquire().await.unwrap();
+        assert!(held.is_some());
+
+        // Second caller waits in the queue
+        let waiter = {
+            let limiter = Arc::clone(&limiter);
+            tokio::spawn(async move { limiter.acquire().await.map(|p| p.is_some()) })
+        };
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while limiter.queued.load(Ordering::SeqCst) == 0 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
 
-        async fn forget(&self, _key: &str) -> anyhow::Result<bool> {
-            Ok(false)
-        }
+        // Queue is full — third caller is rejected immediately
+        assert!(limiter.acquire().await.is_err());
 
-        async fn count(&self) -> anyhow::Result<usize> {
-            Ok(0)
-        }
+        drop(held);
+        assert_eq!(waiter.await.unwrap(), Ok(true));
+        assert_eq!(limiter.queued.load(Ordering::SeqCst), 0);
+    }
 
-        async fn health_check(&self) -> bool {
-            true
+    #[tokio::test]
+    async fn cancelled_queued_acquire_frees_its_queue_slot() {
+        let limiter = ConcurrencyLimiter::new(1, 1);
+        let _held = limiter.acquire().await.unwrap();
+
+        // Every waiter times out; a leaked slot would fill the queue for good.
+        for _ in 0..3 {
+            let waited = tokio::time::timeout(Duration::from_millis(10), limiter.acquire()).await;
+            assert!(waited.is_err());
+            assert_eq!(limiter.queued.load(Ordering::SeqCst), 0);
         }
     }
 
-    #[derive(Default)]
-    struct MockProvider {
-        calls: AtomicUsize,
+    #[tokio::test]
+    async fn concurrency_limiter_zero_disables_limit() {
+        let limiter = ConcurrencyLimiter::new(0, 0);
+        for _ in 0..10 {
+            assert!(limiter.acquire().await.unwrap().is_none());
+        }
     }
 
-    #[async_trait]
-    impl Provider for MockProvider {
-        async fn chat_with_system(
-            &self,
-            _system_prompt: Option<&str>,
-            _message: &str,
-            _model: &str,
-            _temperature: f64,
-        ) -> anyhow::Result<String> {
-            self.calls.fetch_add(1, Ordering::SeqCst);
-            Ok("ok".into())
-        }
+    #[tokio::test]
+    async fn saturated_gateway_returns_503_but_health_answers() {
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.concurrency = Arc::new(ConcurrencyLimiter::new(1, 0));
+        let _held = state.concurrency.acquire().await.unwrap();
+        let app = build_router(state);
+
+        let response = send(app.clone(), webhook_request(None)).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers().get(header::RETRY_AFTER),
+            Some(&HeaderValue::from_static("1"))
+        );
+
+        let health = axum::http::Request::builder()
+            .uri("/health")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(send(app, health).await.status(), StatusCode::OK);
     }
 
     #[tokio::test]
-    async fn webhook_idempotency_skips_duplicate_provider_calls() {
-        let provider_impl = Arc::new(MockProvider::default());
-        let provider: Arc<dyn Provider> = provider_impl.clone();
-        let memory: Arc<dyn Memory> = Arc::new(MockMemory);
+    async fn audit_log_records_webhook_and_rejections() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cfg = crate::config::GatewayAuditConfig {
+            enabled: true,
+            ..crate::config::GatewayAuditConfig::default()
+        };
+        let log = Arc::new(AuditLog::from_config(&cfg, tmp.path()).unwrap().unwrap());
+
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        state.audit = Some(Arc::clone(&log));
+        state.auth_token = Some(Arc::from("gw-secret"));
+        let app = build_router(state);
+
+        let response = send(app.clone(), webhook_request(Some("gw-secret"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(app, webhook_request(None)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        let records: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["path"], "/webhook");
+        assert_eq!(records[0]["status"], 200);
+        assert_eq!(records[0]["prompt"], "hello");
+        assert_eq!(records[0]["model"], "test-model");
+        assert_eq!(records[1]["status"], 401);
+        assert!(records[1].get("prompt").is_none());
+        assert!(records[0].get("prompt_tokens").is_none());
+    }
 
-        let state = AppState {
-            provider,
-            model: "test-model".into(),
-            temperature: 0.0,
-            mem: memory,
-            auto_save: false,
-            webhook_secret: None,
-            pairing: Arc::new(PairingGuard::new(false, &[])),
-            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100)),
-            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300))),
-            whatsapp: None,
-            whatsapp_app_secret: None,
+    #[tokio::test]
+    async fn audit_log_records_webhook_token_usage() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cfg = crate::config::GatewayAuditConfig {
+            enabled: true,
+            ..crate::config::GatewayAuditConfig::default()
         };
+        let log = Arc::new(AuditLog::from_config(&cfg, tmp.path()).unwrap().unwrap());
 
+        let mut state = test_state(Arc::new(UsageProvider));
+        state.audit = Some(Arc::clone(&log));
+        let response = send(build_router(state), webhook_request(None)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        let record: serde_json::Value = serde_json::from_str(content.trim()).unwrap();
+        assert_eq!(record["prompt_tokens"], 12);
+        assert_eq!(record["completion_tokens"], 5);
+    }
+
+    #[test]
+    fn auth_token_exempt_paths() {
+        assert!(auth_token_exempt("/health"));
+        assert!(auth_token_exempt("/whatsapp"));
+        assert!(auth_token_exempt("/slack/commands"));
+        assert!(auth_token_exempt("/livez"));
+        assert!(auth_token_exempt("/readyz"));
+        assert!(!auth_token_exempt("/webhook"));
+        assert!(!auth_token_exempt("/pair"));
+    }
+
+    #[test]
+    fn webhook_body_stream_defaults_to_false() {
+        let parsed: WebhookBody = serde_json::from_str(r#"{"message": "hi"}"#).unwrap();
+        assert!(!parsed.stream);
+
+        let parsed: WebhookBody =
+            serde_json::from_str(r#"{"message": "hi", "stream": true}"#).unwrap();
+        assert!(parsed.stream);
+    }
+
+    #[test]
+    fn wants_event_stream_checks_accept_header() {
         let mut headers = HeaderMap::new();
-        headers.insert("X-Idempotency-Key", HeaderValue::from_static("abc-123"));
+        assert!(!wants_event_stream(&headers));
+
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        assert!(!wants_event_stream(&headers));
+
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("text/event-stream, */*"),
+        );
+        assert!(wants_event_stream(&headers));
+    }
+
+    #[tokio::test]
+    async fn webhook_streams_sse_when_requested() {
+        let provider_impl = Arc::new(MockProvider::default());
+        let state = test_state(provider_impl.clone());
 
         let body = Ok(Json(WebhookBody {
             message: "hello".into(),
+            stream: true,
+            session_id: None,
+            model: None,
+            overrides: GenerationOverrides::default(),
+            images: Vec::new(),
+            json_schema: None,
         }));
-        let first = handle_webhook(State(state.clone()), headers.clone(), body)
+        let response = handle_webhook(State(state), None, HeaderMap::new(), body)
             .await
             .into_response();
-        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream")));
+
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(payload.to_vec()).unwrap();
+        assert!(text.contains(r#"data: {"delta":"ok"}"#));
+        assert!(text.contains("event: done"));
+        assert!(text.contains(r#"data: {"model":"test-model"}"#));
+        assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 1);
+    }
 
+    #[tokio::test]
+    async fn webhook_streams_sse_for_accept_header() {
+        let provider_impl = Arc::new(MockProvider::default());
+        let state = test_state(provider_impl.clone());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("text/event-stream"),
+        );
         let body = Ok(Json(WebhookBody {
             message: "hello".into(),
+            stream: false,
+            session_id: None,
+            model: None,
+            overrides: GenerationOverrides::default(),
+            images: Vec::new(),
+            json_schema: None,
         }));
-        let second = handle_webhook(State(state), headers, body)
+        let response = handle_webhook(State(state), None, headers, body)
             .await
             .into_response();
-        assert_eq!(second.status(), StatusCode::OK);
-
-        let payload = second.into_body().collect().await.unwrap().to_bytes();
-        let parsed: serde_json::Value = serde_json::from_slice(&payload).unwrap();
-        assert_eq!(parsed["status"], "duplicate");
-        assert_eq!(parsed["idempotent"], true);
-        assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 1);
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(payload.to_vec()).unwrap();
+        assert!(text.contains("event: done"));
     }
 
     // ══════════════════════════════════════════════════════════
@@ -1031,4 +5629,286 @@ mod tests {
             &signature_header
         ));
     }
+
+    // ══════════════════════════════════════════════════════════
+    // Slack Slash Command Tests
+    // ══════════════════════════════════════════════════════════
+
+    fn compute_slack_signature_header(secret: &str, timestamp: &str, body: &[u8]) -> String {
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{timestamp}:").as_bytes());
+        mac.update(body);
+        format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn slack_signature_checks_secret_body_and_age() {
+        let body = b"command=%2Fzeroclaw&text=help";
+        let header = compute_slack_signature_header("secret", "1700000000", body);
+
+        assert!(verify_slack_signature(
+            "secret",
+            "1700000000",
+            body,
+            &header,
+            1_700_000_060
+        ));
+        assert!(!verify_slack_signature(
+            "other",
+            "1700000000",
+            body,
+            &header,
+            1_700_000_060
+        ));
+        assert!(!verify_slack_signature(
+            "secret",
+            "1700000000",
+            b"command=%2Fzeroclaw&text=ask",
+            &header,
+            1_700_000_060
+        ));
+        assert!(!verify_slack_signature(
+            "secret",
+            "1700000000",
+            body,
+            &header,
+            1_700_000_301
+        ));
+        assert!(!verify_slack_signature(
+            "secret",
+            "1700000000",
+            body,
+            header.trim_start_matches("v0="),
+            1_700_000_060
+        ));
+        assert!(!verify_slack_signature(
+            "secret",
+            "",
+            body,
+            &header,
+            1_700_000_060
+        ));
+
+        // Extreme timestamps must be rejected, not overflow the age check.
+        for extreme in [i64::MIN, i64::MAX] {
+            let timestamp = extreme.to_string();
+            let header = compute_slack_signature_header("secret", &timestamp, body);
+            assert!(!verify_slack_signature(
+                "secret",
+                &timestamp,
+                body,
+                &header,
+                1_700_000_060
+            ));
+        }
+    }
+
+    #[test]
+    fn slack_command_parses_subcommands() {
+        assert_eq!(SlackCommand::parse(""), SlackCommand::Help);
+        assert_eq!(SlackCommand::parse(" help "), SlackCommand::Help);
+        assert_eq!(SlackCommand::parse("remember"), SlackCommand::Help);
+        assert_eq!(
+            SlackCommand::parse("remember  I prefer Rust"),
+            SlackCommand::Remember("I prefer Rust")
+        );
+        assert_eq!(
+            SlackCommand::parse("Ask what's new?"),
+            SlackCommand::Ask("what's new?")
+        );
+        assert_eq!(
+            SlackCommand::parse("what's new?"),
+            SlackCommand::Ask("what's new?")
+        );
+    }
+
+    fn slack_state(state: &mut AppState) {
+        state.slack_signing_secret = Some(Arc::from("secret"));
+        let mut config = state.config.write().unwrap();
+        config.channels_config.slack = Some(crate::config::schema::SlackConfig {
+            bot_token: "xoxb-test".into(),
+            app_token: None,
+            channel_id: None,
+            channel_ids: Vec::new(),
+            direct_messages: true,
+            allowed_users: vec!["U1".into()],
+            signing_secret: Some("secret".into()),
+        });
+    }
+
+    fn slack_request(secret: &str, body: &str) -> axum::http::Request<axum::body::Body> {
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/slack/commands")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header("X-Slack-Request-Timestamp", &timestamp)
+            .header(
+                "X-Slack-Signature",
+                compute_slack_signature_header(secret, &timestamp, body.as_bytes()),
+            )
+            .body(axum::body::Body::from(body.to_owned()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn slack_commands_need_a_signing_secret_and_valid_signature() {
+        let body = "command=%2Fzeroclaw&text=help&user_id=U1";
+        let mut state = test_state(Arc::new(MockProvider::default()));
+        let response = send(build_router(state.clone()), slack_request("secret", body)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        slack_state(&mut state);
+        let response = send(build_router(state.clone()), slack_request("wrong", body)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        state.auth_token = Some(Arc::from("gateway-token"));
+        let response = send(build_router(state), slack_request("secret", body)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let reply = json_body(response).await;
+        assert_eq!(reply["response_type"], "ephemeral");
+        assert!(reply["text"]
+            .as_str()
+            .unwrap()
+            .contains("/zeroclaw remember <fact>"));
+    }
+
+    #[tokio::test]
+    async fn slack_remember_stores_core_memory_for_allowed_users() {
+        let (_tmp, mut state) = sqlite_state();
+        slack_state(&mut state);
+        let app = build_router(state.clone());
+
+        let response = send(
+            app.clone(),
+            slack_request("secret", "text=remember+deploys+are+on+Fridays&user_id=U2"),
+        )
+        .await;
+        assert!(json_body(response).await["text"]
+            .as_str()
+            .unwrap()
+            .contains("not allowed"));
+        assert_eq!(state.mem.count().await.unwrap(), 0);
+
+        let response = send(
+            app,
+            slack_request("secret", "text=remember+deploys+are+on+Fridays&user_id=U1"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let stored = state.mem.list(Some(&MemoryCategory::Core)).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].content, "deploys are on Fridays");
+        assert!(stored[0].key.starts_with("slack_remember:U1:"));
+    }
+
+    #[tokio::test]
+    async fn slack_ask_replies_in_channel_when_the_answer_is_quick() {
+        let provider = Arc::new(MockProvider::default());
+        let mut state = test_state(provider.clone());
+        slack_state(&mut state);
+
+        let response = send(
+            build_router(state),
+            slack_request("secret", "command=%2Fzeroclaw&text=ask+hi&user_id=U1"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let reply = json_body(response).await;
+        assert_eq!(reply["response_type"], "in_channel");
+        assert_eq!(reply["text"], "ok");
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+    }
+
+    fn deny(entry: &str) -> Option<ChannelAccessConfig> {
+        Some(ChannelAccessConfig {
+            deny: vec![entry.into()],
+            denied_reply: Some("Sorry, ops only.".into()),
+            ..ChannelAccessConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn slack_commands_respect_the_channel_access_list() {
+        let provider = Arc::new(MockProvider::default());
+        let mut state = test_state(provider.clone());
+        slack_state(&mut state);
+        state.settings.write().unwrap().slack.access = deny("C9");
+
+        let response = send(
+            build_router(state),
+            slack_request("secret", "text=ask+hi&user_id=U1&channel_id=C9"),
+        )
+        .await;
+        let reply = json_body(response).await;
+        assert_eq!(reply["response_type"], "ephemeral");
+        assert_eq!(reply["text"], "Sorry, ops only.");
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn whatsapp_respects_the_channel_access_list() {
+        let provider = Arc::new(MockProvider::default());
+        let mut state = test_state(provider.clone());
+        state.whatsapp = Some(Arc::new(WhatsAppChannel::new(
+            "token".into(),
+            "123456789".into(),
+            "verify".into(),
+            vec!["*".into()],
+        )));
+        let mut access = deny("+1234567890").unwrap();
+        access.denied_reply = None;
+        state.settings.write().unwrap().whatsapp.access = Some(access);
+
+        let payload = serde_json::json!({
+            "entry": [{"changes": [{"value": {"messages": [{
+                "from": "1234567890",
+                "timestamp": "1699999999",
+                "type": "text",
+                "text": {"body": "Hello"}
+            }]}}]}]
+        });
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/whatsapp")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(payload.to_string()))
+            .unwrap();
+        let response = send(build_router(state), request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn gateway_channels_use_their_overrides() {
+        let config: Config = toml::from_str(
+            r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_model = "anthropic/claude-sonnet-4"
+default_temperature = 0.7
+
+[channels_config]
+cli = true
+
+[channels_config.overrides.whatsapp]
+model = "openai/gpt-4o-mini"
+temperature = 0.2
+
+[channels_config.access.slack]
+allow = ["U1"]
+"#,
+        )
+        .unwrap();
+        let settings = GatewaySettings::from_config(&config);
+        assert_eq!(settings.whatsapp.model, "openai/gpt-4o-mini");
+        assert!((settings.whatsapp.options.temperature - 0.2).abs() < f64::EPSILON);
+        assert!(settings.whatsapp.permits(&["+15550001"]));
+        assert_eq!(settings.slack.model, "anthropic/claude-sonnet-4");
+        assert!(settings.slack.permits(&["C1", "U1"]));
+        assert!(!settings.slack.permits(&["C1", "U2"]));
+    }
 }
@@ -0,0 +1,283 @@
+//! Client IP resolution behind reverse proxies.
+//!
+//! The one forwarding header named by `gateway.client_ip_header`
+//! (`X-Forwarded-For` by default, or `Forwarded` / `X-Real-IP`) is only
+//! believed when the TCP peer is listed in `gateway.trusted_proxies`;
+//! otherwise any client could spoof its address and dodge rate limits.
+//! Other forwarding headers are ignored, since a client can send them too.
+
+use anyhow::{bail, Context, Result};
+use axum::http::HeaderMap;
+use std::net::IpAddr;
+
+/// Forwarding header the trusted proxy sets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClientIpHeader {
+    /// RFC 7239 `Forwarded: for=...`
+    Forwarded,
+    #[default]
+    XForwardedFor,
+    XRealIp,
+}
+
+impl ClientIpHeader {
+    /// Parse a header name, case-insensitively.
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "forwarded" => Ok(Self::Forwarded),
+            "x-forwarded-for" => Ok(Self::XForwardedFor),
+            "x-real-ip" => Ok(Self::XRealIp),
+            other => bail!(
+                "Invalid gateway.client_ip_header: {other} \
+                 (expected Forwarded, X-Forwarded-For or X-Real-IP)"
+            ),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Forwarded => "Forwarded",
+            Self::XForwardedFor => "X-Forwarded-For",
+            Self::XRealIp => "X-Real-IP",
+        }
+    }
+}
+
+/// Set of proxy addresses/CIDR ranges whose forwarding headers we trust.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    nets: Vec<(IpAddr, u8)>,
+    header: ClientIpHeader,
+}
+
+impl TrustedProxies {
+    /// Parse entries like `10.0.0.1`, `10.0.0.0/8`, or `::1`.
+    pub fn parse(entries: &[String]) -> Result<Self> {
+        let mut nets = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let entry = entry.trim();
+            let (addr, prefix) = match entry.split_once('/') {
+                Some((addr, prefix)) => (addr, Some(prefix)),
+                None => (entry, None),
+            };
+            let addr: IpAddr = addr
+                .parse()
+                .with_context(|| format!("Invalid gateway.trusted_proxies entry: {entry}"))?;
+            let max = if addr.is_ipv4() { 32 } else { 128 };
+            let prefix = match prefix {
+                Some(p) => p
+                    .parse::<u8>()
+                    .with_context(|| format!("Invalid prefix length in {entry}"))?,
+                None => max,
+            };
+            if prefix > max {
+                bail!("Prefix length /{prefix} is too long for {entry}");
+            }
+            nets.push((addr, prefix));
+        }
+        Ok(Self {
+            nets,
+            header: ClientIpHeader::default(),
+        })
+    }
+
+    /// Read the client IP from `header` instead of `X-Forwarded-For`.
+    #[must_use]
+    pub fn with_header(mut self, header: ClientIpHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = canonical(ip);
+        self.nets
+            .iter()
+            .any(|&(net, prefix)| in_network(ip, canonical(net), prefix))
+    }
+
+    /// Resolve the originating client IP for a request from `peer`.
+    ///
+    /// Walks the configured header's chain, across every instance of it,
+    /// right to left and returns the first hop that is not itself a trusted
+    /// proxy. Falls back to `peer` when it is untrusted or no usable header
+    /// is present.
+    pub fn client_ip(&self, headers: &HeaderMap, peer: IpAddr) -> IpAddr {
+        if !self.contains(peer) {
+            return peer;
+        }
+
+        let chain = forwarded_chain(headers, self.header);
+        let mut client = peer;
+        for hop in chain.into_iter().rev() {
+            client = hop;
+            if !self.contains(hop) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// Addresses from every instance of `header`, in client→proxy order.
+fn forwarded_chain(headers: &HeaderMap, header: ClientIpHeader) -> Vec<IpAddr> {
+    let values = headers
+        .get_all(header.name())
+        .iter()
+        .filter_map(|v| v.to_str().ok());
+    match header {
+        ClientIpHeader::Forwarded => values
+            .flat_map(|value| value.split(','))
+            .filter_map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (key, value) = pair.split_once('=')?;
+                    key.trim()
+                        .eq_ignore_ascii_case("for")
+                        .then(|| parse_node(value))?
+                })
+            })
+            .collect(),
+        ClientIpHeader::XForwardedFor | ClientIpHeader::XRealIp => values
+            .flat_map(|value| value.split(','))
+            .filter_map(parse_node)
+            .collect(),
+    }
+}
+
+/// Parse a forwarding node: `1.2.3.4`, `1.2.3.4:80`, `"[2001:db8::1]:4711"`.
+fn parse_node(raw: &str) -> Option<IpAddr> {
+    let node = raw.trim().trim_matches('"');
+    if let Ok(ip) = node.parse() {
+        return Some(ip);
+    }
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+    node.rsplit_once(':')?.0.parse().ok()
+}
+
+/// Treat IPv4-mapped IPv6 (`::ffff:1.2.3.4`) as plain IPv4.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+fn in_network(ip: IpAddr, net: IpAddr, prefix: u8) -> bool {
+    match (ip, net) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn proxies(entries: &[&str]) -> TrustedProxies {
+        let entries: Vec<String> = entries.iter().map(ToString::to_string).collect();
+        TrustedProxies::parse(&entries).unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_addresses_and_cidrs() {
+        let trusted = proxies(&["10.0.0.0/8", "192.168.1.5", "fd00::/8"]);
+        assert!(trusted.contains(ip("10.20.30.40")));
+        assert!(trusted.contains(ip("192.168.1.5")));
+        assert!(!trusted.contains(ip("192.168.1.6")));
+        assert!(trusted.contains(ip("fd12::1")));
+        assert!(trusted.contains(ip("::ffff:10.1.1.1")));
+        assert!(!trusted.contains(ip("8.8.8.8")));
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        assert!(TrustedProxies::parse(&["not-an-ip".into()]).is_err());
+        assert!(TrustedProxies::parse(&["10.0.0.0/33".into()]).is_err());
+        assert!(TrustedProxies::parse(&["10.0.0.0/x".into()]).is_err());
+    }
+
+    #[test]
+    fn ignores_headers_from_untrusted_peers() {
+        let trusted = proxies(&["127.0.0.1"]);
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-For", HeaderValue::from_static("1.2.3.4"));
+        assert_eq!(
+            trusted.client_ip(&headers, ip("203.0.113.9")),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn walks_chain_past_trusted_hops() {
+        let trusted = proxies(&["10.0.0.0/8"]);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Forwarded-For",
+            HeaderValue::from_static("6.6.6.6, 1.2.3.4, 10.0.0.7"),
+        );
+        // 6.6.6.6 was supplied by the client and cannot be trusted.
+        assert_eq!(trusted.client_ip(&headers, ip("10.0.0.1")), ip("1.2.3.4"));
+    }
+
+    #[test]
+    fn forged_forwarded_header_is_ignored() {
+        let trusted = proxies(&["10.0.0.0/8"]);
+        let mut headers = HeaderMap::new();
+        // Sent by the client; the proxy only appends X-Forwarded-For.
+        headers.insert("Forwarded", HeaderValue::from_static("for=6.6.6.6"));
+        headers.insert("X-Forwarded-For", HeaderValue::from_static("1.2.3.4"));
+        assert_eq!(trusted.client_ip(&headers, ip("10.0.0.1")), ip("1.2.3.4"));
+    }
+
+    #[test]
+    fn reads_every_header_instance() {
+        let trusted = proxies(&["10.0.0.0/8"]);
+        let mut headers = HeaderMap::new();
+        // A client-sent instance first, then the one the proxy appended.
+        headers.append("X-Forwarded-For", HeaderValue::from_static("6.6.6.6"));
+        headers.append("X-Forwarded-For", HeaderValue::from_static("1.2.3.4"));
+        assert_eq!(trusted.client_ip(&headers, ip("10.0.0.1")), ip("1.2.3.4"));
+    }
+
+    #[test]
+    fn reads_rfc7239_forwarded_header_when_configured() {
+        let trusted = proxies(&["127.0.0.1"]).with_header(ClientIpHeader::Forwarded);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Forwarded",
+            HeaderValue::from_static(r#"for="[2001:db8::1]:4711";proto=https"#),
+        );
+        headers.insert("X-Forwarded-For", HeaderValue::from_static("9.9.9.9"));
+        assert_eq!(
+            trusted.client_ip(&headers, ip("127.0.0.1")),
+            ip("2001:db8::1")
+        );
+
+        assert_eq!(
+            ClientIpHeader::parse("x-real-ip").unwrap(),
+            ClientIpHeader::XRealIp
+        );
+        assert!(ClientIpHeader::parse("X-Client-IP").is_err());
+    }
+
+    #[test]
+    fn parse_node_handles_ports_and_quotes() {
+        assert_eq!(parse_node(" 1.2.3.4:8080 "), Some(ip("1.2.3.4")));
+        assert_eq!(parse_node("\"[::1]\""), Some(ip("::1")));
+        assert_eq!(parse_node("unknown"), None);
+        assert_eq!(parse_node("_hidden"), None);
+    }
+}
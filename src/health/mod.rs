@@ -1,5 +1,5 @@
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::sync::{Mutex, OnceLock};
 use std::time::Instant;
@@ -13,25 +13,42 @@ pub struct ComponentHealth {
     pub restart_count: u64,
 }
 
+/// Outcome of the periodic `health_check` calls for one channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelHealth {
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    pub last_check: String,
+    pub last_success: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct HealthSnapshot {
     pub pid: u32,
     pub updated_at: String,
     pub uptime_seconds: u64,
     pub components: BTreeMap<String, ComponentHealth>,
+    pub channels: BTreeMap<String, ChannelHealth>,
 }
 
 struct HealthRegistry {
     started_at: Instant,
     components: Mutex<BTreeMap<String, ComponentHealth>>,
+    channels: Mutex<BTreeMap<String, ChannelHealth>>,
 }
 
 static REGISTRY: OnceLock<HealthRegistry> = OnceLock::new();
 
+/// Held by tests that run a provider health check, so assertions on the
+/// shared `provider` component are not raced by other tests.
+#[cfg(test)]
+pub static PROVIDER_CHECK_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
 fn registry() -> &'static HealthRegistry {
     REGISTRY.get_or_init(|| HealthRegistry {
         started_at: Instant::now(),
         components: Mutex::new(BTreeMap::new()),
+        channels: Mutex::new(BTreeMap::new()),
     })
 }
 
@@ -82,17 +99,55 @@ pub fn bump_component_restart(component: &str) {
     });
 }
 
+/// Record one `health_check` of `channel`; returns its consecutive failures.
+pub fn record_channel_check(channel: &str, healthy: bool) -> u32 {
+    let Ok(mut map) = registry().channels.lock() else {
+        return 0;
+    };
+    let now = now_rfc3339();
+    let entry = map
+        .entry(channel.to_string())
+        .or_insert_with(|| ChannelHealth {
+            healthy,
+            consecutive_failures: 0,
+            last_check: now.clone(),
+            last_success: None,
+        });
+    entry.healthy = healthy;
+    entry.last_check.clone_from(&now);
+    if healthy {
+        entry.consecutive_failures = 0;
+        entry.last_success = Some(now);
+    } else {
+        entry.consecutive_failures = entry.consecutive_failures.saturating_add(1);
+    }
+    entry.consecutive_failures
+}
+
+/// Whether `channel`'s last health check failed; `false` before its first.
+pub fn channel_failing(channel: &str) -> bool {
+    registry()
+        .channels
+        .lock()
+        .is_ok_and(|map| map.get(channel).is_some_and(|health| !health.healthy))
+}
+
 pub fn snapshot() -> HealthSnapshot {
     let components = registry()
         .components
         .lock()
         .map_or_else(|_| BTreeMap::new(), |map| map.clone());
+    let channels = registry()
+        .channels
+        .lock()
+        .map_or_else(|_| BTreeMap::new(), |map| map.clone());
 
     HealthSnapshot {
         pid: std::process::id(),
         updated_at: now_rfc3339(),
         uptime_seconds: registry().started_at.elapsed().as_secs(),
         components,
+        channels,
     }
 }
 
@@ -17,6 +17,7 @@ use serde::{Deserialize, Serialize};
 pub mod agent;
 pub mod channels;
 pub mod config;
+pub mod cost;
 pub mod cron;
 pub mod daemon;
 pub mod doctor;
@@ -29,7 +30,9 @@ pub mod memory;
 pub mod migration;
 pub mod observability;
 pub mod onboard;
+pub mod prompts;
 pub mod providers;
+pub mod proxy;
 pub mod runtime;
 pub mod security;
 pub mod service;
@@ -64,6 +67,13 @@ pub enum ChannelCommands {
     Start,
     /// Run health checks for configured channels (handled in main.rs for async)
     Doctor,
+    /// Check credentials and health of configured channels, optionally
+    /// sending a test message (handled in main.rs for async)
+    Test {
+        /// Send a test message to `channel:recipient` (repeatable)
+        #[arg(long = "send", value_name = "CHANNEL:RECIPIENT")]
+        send: Vec<String>,
+    },
     /// Add a new channel configuration
     Add {
         /// Channel type (telegram, discord, slack, whatsapp, matrix, imessage, email)
@@ -110,6 +120,281 @@ pub enum MigrateCommands {
     },
 }
 
+/// Memory subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum MemoryCommands {
+    /// Store (or replace) a memory
+    Add {
+        /// Unique key
+        key: String,
+        /// Memory content
+        content: String,
+        /// Category: core, daily, conversation, or a custom name
+        #[arg(long, default_value = "core")]
+        category: String,
+        /// Tag to attach (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Recall weight from 0.0 to 1.0 (default 0.5; kept when re-adding a key)
+        #[arg(long)]
+        importance: Option<f64>,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Change an existing memory, keeping its creation time (fails if missing)
+    Update {
+        /// Memory key
+        key: String,
+        /// New content
+        #[arg(long)]
+        content: Option<String>,
+        /// New category
+        #[arg(long)]
+        category: Option<String>,
+        /// Replace the tags with these (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Remove all tags
+        #[arg(long, conflicts_with = "tags")]
+        clear_tags: bool,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show a memory by key
+    Get {
+        /// Memory key
+        key: String,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search memories by relevance, or by exact text, regex or key prefix
+    Search {
+        /// Search query
+        query: String,
+        /// Match mode: keyword (ranked), exact, regex, or prefix (on the key)
+        #[arg(long, default_value = "keyword")]
+        mode: String,
+        /// Max results
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        /// Only entries with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Only entries from this time on (RFC 3339, YYYY-MM-DD, or an age like 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries before this time (same formats as --since)
+        #[arg(long)]
+        until: Option<String>,
+        /// Apply --since/--until to the last update instead of creation
+        #[arg(long)]
+        updated: bool,
+        /// Also show entries related to the hits (one hop)
+        #[arg(long)]
+        related: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List memories, newest first
+    List {
+        /// Only this category
+        #[arg(long)]
+        category: Option<String>,
+        /// Only entries with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Max entries to show
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Only entries from this time on (RFC 3339, YYYY-MM-DD, or an age like 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries before this time (same formats as --since)
+        #[arg(long)]
+        until: Option<String>,
+        /// Apply --since/--until to the last update instead of creation
+        #[arg(long)]
+        updated: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete a memory by key
+    Forget {
+        /// Memory key
+        key: String,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Link two memories, e.g. a project to its deadline
+    Relate {
+        /// Key the link starts from
+        from: String,
+        /// Key the link points to
+        to: String,
+        /// Relation label
+        #[arg(long, default_value = "related")]
+        relation: String,
+        /// Scope both keys to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Remove links between two memories
+    Unrelate {
+        /// Key the link starts from
+        from: String,
+        /// Key the link points to
+        to: String,
+        /// Only this relation label (default: every link between them)
+        #[arg(long)]
+        relation: Option<String>,
+        /// Scope both keys to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the links to and from a memory
+    Related {
+        /// Memory key
+        key: String,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Inspect forgotten memories
+    Trash {
+        #[command(subcommand)]
+        trash_command: MemoryTrashCommands,
+    },
+    /// Bring a forgotten memory back from the trash
+    Restore {
+        /// Memory key
+        key: String,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Push or pull snapshots on the `[memory.backup]` S3 bucket
+    Backup {
+        #[command(subcommand)]
+        backup_command: MemoryBackupCommands,
+    },
+    /// Exchange changes with the `[memory.sync]` peer instance once
+    Sync {
+        /// both, push or pull (default: `memory.sync.direction`)
+        #[arg(long)]
+        direction: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Merge near-duplicate memories into the oldest copy (duplicates go to the trash)
+    Dedup {
+        /// Similarity from 0.0 to 1.0 at which entries count as duplicates
+        /// (default: `memory.dedup_similarity`)
+        #[arg(long)]
+        similarity: Option<f64>,
+        /// Only show what would be merged
+        #[arg(long)]
+        dry_run: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Move memories not recalled for a period to the trash
+    Prune {
+        /// How long an entry must have gone unread, e.g. `90d` or `12h`
+        #[arg(long, value_name = "AGE")]
+        unused_for: String,
+        /// Only this category
+        #[arg(long)]
+        category: Option<String>,
+        /// Only show what would be pruned
+        #[arg(long)]
+        dry_run: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the number of stored memories
+    Count {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize the backend: totals per category and tag, time range
+    Stats {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Memory trash subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MemoryTrashCommands {
+    /// List forgotten memories, most recently deleted first
+    List {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Memory backup subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MemoryBackupCommands {
+    /// Upload a snapshot of every memory now
+    Push {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Store the latest snapshot into this instance
+    Pull {
+        /// Pull even if memories already exist (same keys are overwritten)
+        #[arg(long)]
+        force: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 /// Cron subcommands
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum CronCommands {
@@ -122,7 +407,18 @@ pub enum CronCommands {
         /// Command to run
         command: String,
     },
-    /// Remove a scheduled task
+    /// Send a message through a channel later
+    SendAt {
+        /// When to send: RFC 3339 timestamp or a delay like 30m, 2h, 1d
+        when: String,
+        /// Channel to send through (telegram, slack, discord, ...)
+        channel: String,
+        /// Recipient on that channel (chat ID, channel ID, address)
+        target: String,
+        /// Message text
+        message: String,
+    },
+    /// Remove a scheduled task or message
     Remove {
         /// Task ID
         id: String,
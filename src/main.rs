@@ -16,6 +16,7 @@ use tracing_subscriber::FmtSubscriber;
 mod agent;
 mod channels;
 mod config;
+mod cost;
 mod cron;
 mod daemon;
 mod doctor;
@@ -28,7 +29,9 @@ mod memory;
 mod migration;
 mod observability;
 mod onboard;
+mod prompts;
 mod providers;
+mod proxy;
 mod runtime;
 mod security;
 mod service;
@@ -143,6 +146,36 @@ enum Commands {
     /// Show system status (full details)
     Status,
 
+    /// Show token usage and spend recorded from provider replies
+    Usage {
+        /// Number of days to report, today included
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+
+        /// Group totals by day, provider, channel or model
+        #[arg(long, default_value = "day")]
+        by: String,
+
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List the models a provider offers (ids, context window, pricing)
+    Models {
+        /// Provider to query (default: `default_provider`)
+        #[arg(short, long)]
+        provider: Option<String>,
+
+        /// Only models whose id contains this text
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Configure and manage scheduled tasks
     Cron {
         #[command(subcommand)]
@@ -167,6 +200,12 @@ enum Commands {
         skill_command: SkillCommands,
     },
 
+    /// Inspect and edit the agent's memory
+    Memory {
+        #[command(subcommand)]
+        memory_command: MemoryCommands,
+    },
+
     /// Migrate data from other agent runtimes
     Migrate {
         #[command(subcommand)]
@@ -188,6 +227,278 @@ enum MigrateCommands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum MemoryCommands {
+    /// Store (or replace) a memory
+    Add {
+        /// Unique key
+        key: String,
+        /// Memory content
+        content: String,
+        /// Category: core, daily, conversation, or a custom name
+        #[arg(long, default_value = "core")]
+        category: String,
+        /// Tag to attach (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Recall weight from 0.0 to 1.0 (default 0.5; kept when re-adding a key)
+        #[arg(long)]
+        importance: Option<f64>,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Change an existing memory, keeping its creation time (fails if missing)
+    Update {
+        /// Memory key
+        key: String,
+        /// New content
+        #[arg(long)]
+        content: Option<String>,
+        /// New category
+        #[arg(long)]
+        category: Option<String>,
+        /// Replace the tags with these (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Remove all tags
+        #[arg(long, conflicts_with = "tags")]
+        clear_tags: bool,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show a memory by key
+    Get {
+        /// Memory key
+        key: String,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search memories by relevance, or by exact text, regex or key prefix
+    Search {
+        /// Search query
+        query: String,
+        /// Match mode: keyword (ranked), exact, regex, or prefix (on the key)
+        #[arg(long, default_value = "keyword")]
+        mode: String,
+        /// Max results
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        /// Only entries with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Only entries from this time on (RFC 3339, YYYY-MM-DD, or an age like 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries before this time (same formats as --since)
+        #[arg(long)]
+        until: Option<String>,
+        /// Apply --since/--until to the last update instead of creation
+        #[arg(long)]
+        updated: bool,
+        /// Also show entries related to the hits (one hop)
+        #[arg(long)]
+        related: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List memories, newest first
+    List {
+        /// Only this category
+        #[arg(long)]
+        category: Option<String>,
+        /// Only entries with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Max entries to show
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Only entries from this time on (RFC 3339, YYYY-MM-DD, or an age like 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries before this time (same formats as --since)
+        #[arg(long)]
+        until: Option<String>,
+        /// Apply --since/--until to the last update instead of creation
+        #[arg(long)]
+        updated: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete a memory by key
+    Forget {
+        /// Memory key
+        key: String,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Link two memories, e.g. a project to its deadline
+    Relate {
+        /// Key the link starts from
+        from: String,
+        /// Key the link points to
+        to: String,
+        /// Relation label
+        #[arg(long, default_value = "related")]
+        relation: String,
+        /// Scope both keys to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Remove links between two memories
+    Unrelate {
+        /// Key the link starts from
+        from: String,
+        /// Key the link points to
+        to: String,
+        /// Only this relation label (default: every link between them)
+        #[arg(long)]
+        relation: Option<String>,
+        /// Scope both keys to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the links to and from a memory
+    Related {
+        /// Memory key
+        key: String,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Inspect forgotten memories
+    Trash {
+        #[command(subcommand)]
+        trash_command: MemoryTrashCommands,
+    },
+    /// Bring a forgotten memory back from the trash
+    Restore {
+        /// Memory key
+        key: String,
+        /// Scope to one namespace (e.g. a user or workspace)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Push or pull snapshots on the `[memory.backup]` S3 bucket
+    Backup {
+        #[command(subcommand)]
+        backup_command: MemoryBackupCommands,
+    },
+    /// Exchange changes with the `[memory.sync]` peer instance once
+    Sync {
+        /// both, push or pull (default: `memory.sync.direction`)
+        #[arg(long)]
+        direction: Option<String>,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Merge near-duplicate memories into the oldest copy (duplicates go to the trash)
+    Dedup {
+        /// Similarity from 0.0 to 1.0 at which entries count as duplicates
+        /// (default: `memory.dedup_similarity`)
+        #[arg(long)]
+        similarity: Option<f64>,
+        /// Only show what would be merged
+        #[arg(long)]
+        dry_run: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Move memories not recalled for a period to the trash
+    Prune {
+        /// How long an entry must have gone unread, e.g. `90d` or `12h`
+        #[arg(long, value_name = "AGE")]
+        unused_for: String,
+        /// Only this category
+        #[arg(long)]
+        category: Option<String>,
+        /// Only show what would be pruned
+        #[arg(long)]
+        dry_run: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the number of stored memories
+    Count {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize the backend: totals per category and tag, time range
+    Stats {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum MemoryTrashCommands {
+    /// List forgotten memories, most recently deleted first
+    List {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum MemoryBackupCommands {
+    /// Upload a snapshot of every memory now
+    Push {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Store the latest snapshot into this instance
+    Pull {
+        /// Pull even if memories already exist (same keys are overwritten)
+        #[arg(long)]
+        force: bool,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 enum CronCommands {
     /// List all scheduled tasks
@@ -199,7 +510,18 @@ enum CronCommands {
         /// Command to run
         command: String,
     },
-    /// Remove a scheduled task
+    /// Send a message through a channel later
+    SendAt {
+        /// When to send: RFC 3339 timestamp or a delay like 30m, 2h, 1d
+        when: String,
+        /// Channel to send through (telegram, slack, discord, ...)
+        channel: String,
+        /// Recipient on that channel (chat ID, channel ID, address)
+        target: String,
+        /// Message text
+        message: String,
+    },
+    /// Remove a scheduled task or message
     Remove {
         /// Task ID
         id: String,
@@ -214,6 +536,12 @@ enum ChannelCommands {
     Start,
     /// Run health checks for configured channels
     Doctor,
+    /// Check credentials and health of each configured channel
+    Test {
+        /// Also send a test message, e.g. `--send telegram:123456789`
+        #[arg(long = "send", value_name = "CHANNEL:RECIPIENT")]
+        send: Vec<String>,
+    },
     /// Add a new channel
     Add {
         /// Channel type
@@ -297,6 +625,8 @@ async fn main() -> Result<()> {
         };
         // Auto-start channels if user said yes during wizard
         if std::env::var("ZEROCLAW_AUTOSTART_CHANNELS").as_deref() == Ok("1") {
+            proxy::install(&config.proxy)?;
+            providers::install(&config.providers);
             channels::start_channels(config).await?;
         }
         return Ok(());
@@ -305,6 +635,8 @@ async fn main() -> Result<()> {
     // All other commands need config loaded first
     let mut config = Config::load_or_init()?;
     config.apply_env_overrides();
+    proxy::install(&config.proxy)?;
+    providers::install(&config.providers);
 
     match cli.command {
         Commands::Onboard { .. } => unreachable!(),
@@ -349,6 +681,37 @@ async fn main() -> Result<()> {
                 "   Model:         {}",
                 config.default_model.as_deref().unwrap_or("(default)")
             );
+            let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+            let provider_check = async {
+                let mem: std::sync::Arc<dyn memory::Memory> =
+                    std::sync::Arc::from(memory::create_memory(
+                        &config.memory,
+                        &config.workspace_dir,
+                        config.api_key.as_deref(),
+                    )?);
+                let provider = providers::create_configured_provider(
+                    &config,
+                    provider_name,
+                    config
+                        .default_model
+                        .as_deref()
+                        .unwrap_or("anthropic/claude-sonnet-4-20250514"),
+                    mem,
+                )?;
+                providers::check_provider_health(
+                    provider.as_ref(),
+                    std::time::Duration::from_secs(10),
+                )
+                .await
+            }
+            .await;
+            println!(
+                "   Health:        {}",
+                match provider_check {
+                    Ok(()) => "✅ reachable, credentials accepted".to_string(),
+                    Err(e) => format!("❌ {e}"),
+                }
+            );
             println!("📊 Observability:  {}", config.observability.backend);
             println!("🛡️  Autonomy:      {:?}", config.autonomy.level);
             println!("⚙️  Runtime:       {}", config.runtime.kind);
@@ -399,10 +762,38 @@ async fn main() -> Result<()> {
                     }
                 );
             }
+            if let Some((written_at, health)) = daemon::channel_health(&config) {
+                if !health.is_empty() {
+                    println!();
+                    println!("Channel health (daemon, as of {written_at}):");
+                }
+                for (name, check) in health {
+                    if check.healthy {
+                        println!("  {name:9} ✅ healthy (checked {})", check.last_check);
+                    } else {
+                        println!(
+                            "  {name:9} ❌ {} failed checks in a row, last success {}",
+                            check.consecutive_failures,
+                            check.last_success.as_deref().unwrap_or("never")
+                        );
+                    }
+                }
+            }
 
             Ok(())
         }
 
+        Commands::Usage { days, by, json } => cost::handle_command(&config, days, &by, json),
+
+        Commands::Models {
+            provider,
+            filter,
+            json,
+        } => {
+            providers::models::handle_command(&config, provider.as_deref(), filter.as_deref(), json)
+                .await
+        }
+
         Commands::Cron { cron_command } => cron::handle_command(cron_command, &config),
 
         Commands::Service { service_command } => service::handle_command(&service_command, &config),
@@ -412,6 +803,7 @@ async fn main() -> Result<()> {
         Commands::Channel { channel_command } => match channel_command {
             ChannelCommands::Start => channels::start_channels(config).await,
             ChannelCommands::Doctor => channels::doctor_channels(config).await,
+            ChannelCommands::Test { send } => channels::test_channels(config, &send).await,
             other => channels::handle_command(other, &config),
         },
 
@@ -423,6 +815,10 @@ async fn main() -> Result<()> {
             skills::handle_command(skill_command, &config.workspace_dir)
         }
 
+        Commands::Memory { memory_command } => {
+            memory::cli::handle_command(memory_command, &config).await
+        }
+
         Commands::Migrate { migrate_command } => {
             migration::handle_command(migrate_command, &config).await
         }
@@ -0,0 +1,431 @@
+// Remote backups — serialize every live memory into a JSON snapshot, push it
+// to an S3-compatible bucket (AWS S3, Cloudflare R2, MinIO, …), and pull the
+// newest one back into an empty brain when a fresh instance boots.
+//
+// Requests are signed with AWS Signature V4 by hand; the only S3 calls
+// needed are a PUT and a GET of a single object.
+
+use super::ranking::DEFAULT_IMPORTANCE;
+use super::traits::{Memory, MemoryEntry};
+use crate::config::MemoryBackupConfig;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Format version written into every snapshot.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Object overwritten on every push, so a restore needs no bucket listing.
+const LATEST_OBJECT: &str = "latest.json";
+
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+
+/// Every live memory at one point in time. Keys, content, category, tags
+/// and importance round-trip; timestamps and session ids are reset on
+/// restore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub created_at: String,
+    pub entries: Vec<MemoryEntry>,
+}
+
+impl Snapshot {
+    pub async fn capture(mem: &dyn Memory) -> anyhow::Result<Self> {
+        Ok(Self {
+            version: SNAPSHOT_VERSION,
+            created_at: Utc::now().to_rfc3339(),
+            entries: mem.list_all(None).await?,
+        })
+    }
+
+    /// Store every entry into `mem`, overwriting same-keyed ones.
+    pub async fn apply(&self, mem: &dyn Memory) -> anyhow::Result<usize> {
+        if self.version > SNAPSHOT_VERSION {
+            anyhow::bail!(
+                "snapshot format v{} is newer than this build supports (v{SNAPSHOT_VERSION})",
+                self.version
+            );
+        }
+        for entry in &self.entries {
+            mem.store_with_tags(
+                &entry.key,
+                &entry.content,
+                entry.category.clone(),
+                &entry.tags,
+            )
+            .await?;
+            if (entry.importance - DEFAULT_IMPORTANCE).abs() > f64::EPSILON {
+                mem.set_importance(&entry.key, entry.importance).await?;
+            }
+        }
+        Ok(self.entries.len())
+    }
+}
+
+/// What a push uploaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupReport {
+    /// Object key of the timestamped snapshot
+    pub object: String,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+/// One bucket on an S3-compatible service, addressed path-style.
+pub struct S3Target {
+    client: reqwest::Client,
+    endpoint: String,
+    bucket: String,
+    region: String,
+    prefix: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3Target {
+    pub fn from_config(config: &MemoryBackupConfig) -> anyhow::Result<Self> {
+        let endpoint = config.endpoint.trim().trim_end_matches('/');
+        if endpoint.is_empty() || config.bucket.trim().is_empty() {
+            anyhow::bail!("memory.backup needs an endpoint and a bucket");
+        }
+        let (Some(access_key_id), Some(secret_access_key)) = (
+            config.access_key_id.as_deref().filter(|k| !k.is_empty()),
+            config
+                .secret_access_key
+                .as_deref()
+                .filter(|k| !k.is_empty()),
+        ) else {
+            anyhow::bail!(
+                "memory.backup needs access_key_id and secret_access_key \
+                 (or ZEROCLAW_BACKUP_ACCESS_KEY_ID / ZEROCLAW_BACKUP_SECRET_ACCESS_KEY)"
+            );
+        };
+        let client = crate::proxy::client_builder("backup")?
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()?;
+        Ok(Self {
+            client,
+            endpoint: endpoint.to_string(),
+            bucket: config.bucket.trim().to_string(),
+            region: config.region.clone(),
+            prefix: config.prefix.clone(),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+        })
+    }
+
+    fn object_url(&self, name: &str) -> anyhow::Result<reqwest::Url> {
+        let url = format!("{}/{}/{}{name}", self.endpoint, self.bucket, self.prefix);
+        reqwest::Url::parse(&url).map_err(|e| anyhow::anyhow!("invalid backup URL '{url}': {e}"))
+    }
+
+    /// Upload `body` as `{prefix}{name}`.
+    pub async fn put(&self, name: &str, body: Vec<u8>) -> anyhow::Result<()> {
+        let url = self.object_url(name)?;
+        let headers = self.sign("PUT", &url, &body, Utc::now())?;
+        let response = self
+            .client
+            .put(url)
+            .headers(headers)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "backup upload of '{name}' failed: HTTP {}",
+                response.status()
+            );
+        }
+        Ok(())
+    }
+
+    /// Download `{prefix}{name}`; `None` if the object doesn't exist.
+    pub async fn get(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let url = self.object_url(name)?;
+        let headers = self.sign("GET", &url, &[], Utc::now())?;
+        let response = self.client.get(url).headers(headers).send().await?;
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response.bytes().await?.to_vec())),
+            status => anyhow::bail!("backup download of '{name}' failed: HTTP {status}"),
+        }
+    }
+
+    /// AWS Signature V4 headers for a request without a query string.
+    fn sign(
+        &self,
+        method: &str,
+        url: &reqwest::Url,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<reqwest::header::HeaderMap> {
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => anyhow::bail!("backup URL has no host: {url}"),
+        };
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(body));
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{method}\n{}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n{signed_headers}\n{payload_hash}",
+            url.path()
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&self.secret_access_key, &date, &self.region, "s3");
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-amz-date", amz_date.parse()?);
+        headers.insert("x-amz-content-sha256", payload_hash.parse()?);
+        headers.insert(
+            reqwest::header::AUTHORIZATION,
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                self.access_key_id
+            )
+            .parse()?,
+        );
+        Ok(headers)
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC key of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Signing key derived from the secret for one day, region and service.
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{secret}").as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+/// Upload a snapshot as `snapshots/<timestamp>.json` and as `latest.json`.
+pub async fn push_snapshot(mem: &dyn Memory, target: &S3Target) -> anyhow::Result<BackupReport> {
+    let snapshot = Snapshot::capture(mem).await?;
+    let body = serde_json::to_vec(&snapshot)?;
+    let object = format!("snapshots/{}.json", Utc::now().format("%Y%m%dT%H%M%SZ"));
+    target.put(&object, body.clone()).await?;
+    let bytes = body.len();
+    target.put(LATEST_OBJECT, body).await?;
+    Ok(BackupReport {
+        object: format!("{}{object}", target.prefix),
+        entries: snapshot.entries.len(),
+        bytes,
+    })
+}
+
+/// Store the latest snapshot into `mem`. `None` if none was pushed yet.
+pub async fn pull_latest(mem: &dyn Memory, target: &S3Target) -> anyhow::Result<Option<usize>> {
+    let Some(body) = target.get(LATEST_OBJECT).await? else {
+        return Ok(None);
+    };
+    let snapshot: Snapshot = serde_json::from_slice(&body)
+        .map_err(|e| anyhow::anyhow!("latest backup snapshot is not valid: {e}"))?;
+    snapshot.apply(mem).await.map(Some)
+}
+
+/// Pull the latest snapshot only when `mem` holds nothing yet, so a
+/// rebuilt instance picks up where the old one left off.
+pub async fn restore_if_empty(
+    mem: &dyn Memory,
+    target: &S3Target,
+) -> anyhow::Result<Option<usize>> {
+    if mem.count().await? > 0 {
+        return Ok(None);
+    }
+    pull_latest(mem, target).await
+}
+
+/// Push a snapshot every `interval_secs` until aborted. An empty memory
+/// is never pushed, so a botched restore can't clobber `latest.json`.
+pub async fn run_backups(mem: Arc<dyn Memory>, config: &MemoryBackupConfig) -> anyhow::Result<()> {
+    let target = S3Target::from_config(config)?;
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs.max(60)));
+    loop {
+        interval.tick().await;
+        if mem.count().await? == 0 {
+            tracing::debug!("memory backup skipped: nothing stored");
+            continue;
+        }
+        match push_snapshot(mem.as_ref(), &target).await {
+            Ok(report) => tracing::info!(
+                "memory: backed up {} entries to {}",
+                report.entries,
+                report.object
+            ),
+            Err(e) => tracing::warn!("memory backup failed: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{InMemoryMemory, MemoryCategory};
+    use axum::body::Bytes;
+    use axum::extract::{Path, State};
+    use axum::http::{HeaderMap, StatusCode};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    type Bucket = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// Minimal S3 stand-in: stores PUT bodies, serves them on GET, and
+    /// rejects requests without a SigV4 authorization header.
+    async fn mock_s3() -> (String, Bucket) {
+        async fn put(
+            State(bucket): State<Bucket>,
+            Path(key): Path<String>,
+            headers: HeaderMap,
+            body: Bytes,
+        ) -> StatusCode {
+            if !signed(&headers) {
+                return StatusCode::FORBIDDEN;
+            }
+            bucket.lock().unwrap().insert(key, body.to_vec());
+            StatusCode::OK
+        }
+        async fn get(
+            State(bucket): State<Bucket>,
+            Path(key): Path<String>,
+            headers: HeaderMap,
+        ) -> Result<Vec<u8>, StatusCode> {
+            if !signed(&headers) {
+                return Err(StatusCode::FORBIDDEN);
+            }
+            bucket
+                .lock()
+                .unwrap()
+                .get(&key)
+                .cloned()
+                .ok_or(StatusCode::NOT_FOUND)
+        }
+        fn signed(headers: &HeaderMap) -> bool {
+            headers
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("AWS4-HMAC-SHA256 Credential=AKID/"))
+        }
+
+        let bucket = Bucket::default();
+        let app = axum::Router::new()
+            .route("/*key", axum::routing::put(put).get(get))
+            .with_state(Arc::clone(&bucket));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{addr}"), bucket)
+    }
+
+    fn backup_config(endpoint: &str) -> MemoryBackupConfig {
+        MemoryBackupConfig {
+            enabled: true,
+            endpoint: endpoint.into(),
+            bucket: "brains".into(),
+            access_key_id: Some("AKID".into()),
+            secret_access_key: Some("secret".into()),
+            ..MemoryBackupConfig::default()
+        }
+    }
+
+    #[test]
+    fn signing_key_matches_aws_example() {
+        // From the AWS Signature V4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn target_requires_bucket_and_credentials() {
+        let err = S3Target::from_config(&MemoryBackupConfig::default())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("endpoint and a bucket"));
+
+        let config = MemoryBackupConfig {
+            secret_access_key: None,
+            ..backup_config("https://s3.example.com")
+        };
+        let err = S3Target::from_config(&config).err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("ZEROCLAW_BACKUP_SECRET_ACCESS_KEY"));
+    }
+
+    #[tokio::test]
+    async fn push_then_restore_into_fresh_instance() {
+        let (endpoint, bucket) = mock_s3().await;
+        let target = S3Target::from_config(&backup_config(&endpoint)).unwrap();
+
+        let fresh = InMemoryMemory::new();
+        assert_eq!(restore_if_empty(&fresh, &target).await.unwrap(), None);
+
+        let old = InMemoryMemory::new();
+        old.store_with_tags(
+            "ns/alice/tz",
+            "Timezone is CET",
+            MemoryCategory::Core,
+            &["profile".into()],
+        )
+        .await
+        .unwrap();
+        old.store_with_importance("deadline", "Ship in June", MemoryCategory::Daily, 0.9)
+            .await
+            .unwrap();
+        let report = push_snapshot(&old, &target).await.unwrap();
+        assert_eq!(report.entries, 2);
+        assert!(report.object.starts_with("zeroclaw/snapshots/"));
+        {
+            let objects = bucket.lock().unwrap();
+            assert!(objects.contains_key("brains/zeroclaw/latest.json"));
+            assert!(objects.contains_key(&format!("brains/{}", report.object)));
+        }
+
+        assert_eq!(restore_if_empty(&fresh, &target).await.unwrap(), Some(2));
+        let tz = fresh.get("ns/alice/tz").await.unwrap().unwrap();
+        assert_eq!(tz.content, "Timezone is CET");
+        assert_eq!(tz.tags, vec!["profile"]);
+        let deadline = fresh.get("deadline").await.unwrap().unwrap();
+        assert_eq!(deadline.category, MemoryCategory::Daily);
+        assert!((deadline.importance - 0.9).abs() < f64::EPSILON);
+
+        // A populated instance is left alone
+        assert_eq!(restore_if_empty(&fresh, &target).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn newer_snapshot_format_is_refused() {
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION + 1,
+            created_at: Utc::now().to_rfc3339(),
+            entries: Vec::new(),
+        };
+        let err = snapshot.apply(&InMemoryMemory::new()).await.unwrap_err();
+        assert!(err.to_string().contains("newer than this build"));
+    }
+}
@@ -106,6 +106,39 @@ pub fn chunk_markdown(text: &str, max_tokens: usize) -> Vec<Chunk> {
     chunks
 }
 
+/// Split `text` into consecutive pieces of at most `max_bytes` that
+/// concatenate back to exactly `text`. Cuts after the last newline in each
+/// window, else after the last whitespace, else at a char boundary.
+pub fn split_content(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while rest.len() > max_bytes {
+        let mut end = max_bytes;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let window = &rest[..end];
+        let cut = window
+            .rfind('\n')
+            .or_else(|| window.rfind(char::is_whitespace))
+            .map_or(end, |i| {
+                i + rest[i..].chars().next().map_or(1, char::len_utf8)
+            });
+        // Always make progress, even if one char exceeds `max_bytes`
+        let cut = if cut == 0 {
+            rest.chars().next().map_or(rest.len(), char::len_utf8)
+        } else {
+            cut
+        };
+        pieces.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    if !rest.is_empty() || pieces.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
 /// Split text into `(heading, body)` sections.
 fn split_on_headings(text: &str) -> Vec<(Option<String>, String)> {
     let mut sections = Vec::new();
@@ -181,6 +214,23 @@ fn split_on_lines(text: &str, max_chars: usize) -> Vec<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn split_content_is_lossless_and_bounded() {
+        let text = "first line\nsecond line that is longer\nünïcödé wörds without breaks";
+        for max in [1, 5, 12, 30, 1000] {
+            let pieces = split_content(text, max);
+            assert_eq!(pieces.concat(), text);
+            assert!(pieces
+                .iter()
+                .all(|p| !p.is_empty() && (p.len() <= max || p.chars().count() == 1)));
+        }
+        assert_eq!(
+            split_content("alpha beta\ngamma", 12),
+            vec!["alpha beta\n", "gamma"]
+        );
+        assert_eq!(split_content("", 8), vec![""]);
+    }
+
     #[test]
     fn empty_text() {
         assert!(chunk_markdown("", 512).is_empty());
@@ -0,0 +1,122 @@
+// Content encryption at rest for the SQLite brain.
+//
+// Memory content is sealed with ChaCha20-Poly1305 (the same AEAD as the
+// secret store) before it is written to `brain.db`. Each value gets a fresh
+// 12-byte nonce and is stored as `encm:<hex(nonce ‖ ciphertext ‖ tag)>`.
+//
+// Keys, categories, timestamps, and embedding vectors stay in the clear so
+// lookups and vector search keep working. FTS5 only ever sees ciphertext, so
+// `create_memory` refuses `encrypt` unless recall is semantic.
+
+use anyhow::{Context, Result};
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{AeadCore, ChaCha20Poly1305, Key, Nonce};
+
+/// Prefix marking an encrypted content value.
+const PREFIX: &str = "encm:";
+
+/// 256-bit key, hex-encoded as 64 characters.
+const KEY_LEN: usize = 32;
+
+/// ChaCha20-Poly1305 nonce length in bytes.
+const NONCE_LEN: usize = 12;
+
+/// Seals and opens memory content with a fixed key.
+pub struct ContentCipher {
+    cipher: ChaCha20Poly1305,
+}
+
+impl ContentCipher {
+    /// Build from a 64-character hex key (e.g. `openssl rand -hex 32`).
+    pub fn from_hex_key(hex_key: &str) -> Result<Self> {
+        let key = hex::decode(hex_key.trim()).context("Memory encryption key is not valid hex")?;
+        anyhow::ensure!(
+            key.len() == KEY_LEN,
+            "Memory encryption key must be {KEY_LEN} bytes ({} hex chars), got {}",
+            KEY_LEN * 2,
+            key.len()
+        );
+        Ok(Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+        })
+    }
+
+    /// Whether a stored value is already sealed.
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(PREFIX)
+    }
+
+    /// Encrypt plaintext into the `encm:` storage format.
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Memory encryption failed: {e}"))?;
+
+        let mut blob = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&ciphertext);
+        Ok(format!("{PREFIX}{}", hex::encode(blob)))
+    }
+
+    /// Decrypt an `encm:` value; anything else is legacy plaintext and is
+    /// returned unchanged.
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let Some(hex_str) = value.strip_prefix(PREFIX) else {
+            return Ok(value.to_string());
+        };
+
+        let blob = hex::decode(hex_str).context("Encrypted memory is corrupt (bad hex)")?;
+        anyhow::ensure!(
+            blob.len() > NONCE_LEN,
+            "Encrypted memory too short (missing nonce)"
+        );
+        let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                anyhow::anyhow!("Memory decryption failed — wrong key or tampered data")
+            })?;
+        String::from_utf8(plaintext).context("Decrypted memory is not valid UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn roundtrip() {
+        let cipher = ContentCipher::from_hex_key(KEY).unwrap();
+        let sealed = cipher.encrypt("prefers neovim 🦀").unwrap();
+        assert!(ContentCipher::is_encrypted(&sealed));
+        assert!(!sealed.contains("neovim"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "prefers neovim 🦀");
+    }
+
+    #[test]
+    fn plaintext_passes_through() {
+        let cipher = ContentCipher::from_hex_key(KEY).unwrap();
+        assert_eq!(cipher.decrypt("legacy note").unwrap(), "legacy note");
+    }
+
+    #[test]
+    fn rejects_bad_keys() {
+        assert!(ContentCipher::from_hex_key("zz").is_err());
+        assert!(ContentCipher::from_hex_key("abcd").is_err());
+    }
+
+    #[test]
+    fn wrong_key_fails() {
+        let sealed = ContentCipher::from_hex_key(KEY)
+            .unwrap()
+            .encrypt("secret")
+            .unwrap();
+        let other = ContentCipher::from_hex_key(&"ff".repeat(32)).unwrap();
+        assert!(other.decrypt(&sealed).is_err());
+    }
+}
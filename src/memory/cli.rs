@@ -0,0 +1,1160 @@
+// `zeroclaw memory …` — inspect and edit the configured memory backend from
+// the terminal. Each command renders to a string so it can be tested without
+// capturing stdout.
+
+use super::sync::{sync_once, SyncCursor, SyncDirection, SyncPeer};
+use super::traits::{
+    expand_related, normalize_relation, parse_time_bound, DateField, DateRange, EntryMatcher,
+    MatchMode, Memory, MemoryCategory, MemoryEntry, MemoryPatch,
+};
+use crate::config::{Config, MemoryBackupConfig, MemorySyncConfig};
+use crate::{MemoryBackupCommands, MemoryCommands, MemoryTrashCommands};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+pub async fn handle_command(command: MemoryCommands, config: &Config) -> Result<()> {
+    let mem = super::create_memory(
+        &config.memory,
+        &config.workspace_dir,
+        config.api_key.as_deref(),
+    )?;
+    let output = match command {
+        MemoryCommands::Backup { backup_command } => {
+            backup(backup_command, mem.as_ref(), &config.memory.backup).await?
+        }
+        MemoryCommands::Dedup {
+            similarity,
+            dry_run,
+            json,
+        } => {
+            let similarity = similarity.unwrap_or(config.memory.dedup_similarity);
+            dedup(mem.as_ref(), similarity, dry_run, json).await?
+        }
+        MemoryCommands::Sync { direction, json } => {
+            let direction = direction.unwrap_or_else(|| config.memory.sync.direction.clone());
+            sync(mem.as_ref(), &config.memory.sync, direction.parse()?, json).await?
+        }
+        command => run(command, mem.as_ref()).await?,
+    };
+    print!("{output}");
+    Ok(())
+}
+
+/// Execute one memory command and return what should be printed.
+#[allow(clippy::too_many_lines)]
+pub async fn run(command: MemoryCommands, mem: &dyn Memory) -> Result<String> {
+    match command {
+        MemoryCommands::Add {
+            key,
+            content,
+            category,
+            tags,
+            importance,
+            namespace,
+            json,
+        } => {
+            let key = scoped_key(namespace.as_deref(), &key)?;
+            add(mem, &key, &content, &category, &tags, importance, json).await
+        }
+        MemoryCommands::Update {
+            key,
+            content,
+            category,
+            tags,
+            clear_tags,
+            namespace,
+            json,
+        } => {
+            let patch = MemoryPatch {
+                content,
+                category: category.as_deref().map(MemoryCategory::from),
+                tags: (clear_tags || !tags.is_empty()).then_some(tags),
+            };
+            if patch.is_empty() {
+                anyhow::bail!(
+                    "Nothing to update: pass --content, --category, --tag or --clear-tags"
+                );
+            }
+            let entry = mem
+                .update(&scoped_key(namespace.as_deref(), &key)?, &patch)
+                .await?;
+            Ok(if json {
+                json_line(&entry_json(&entry))
+            } else {
+                format!("✏️  Updated '{}' [{}]\n", entry.key, entry.category)
+            })
+        }
+        MemoryCommands::Get {
+            key,
+            namespace,
+            json,
+        } => match mem.get(&scoped_key(namespace.as_deref(), &key)?).await? {
+            Some(entry) if json => Ok(json_line(&entry_json(&entry))),
+            Some(entry) => Ok(render_entries(&[entry])),
+            None if json => Ok(json_line(&serde_json::Value::Null)),
+            None => anyhow::bail!("No memory with key '{key}'"),
+        },
+        MemoryCommands::Search {
+            query,
+            mode,
+            limit,
+            tag,
+            namespace,
+            since,
+            until,
+            updated,
+            related,
+            json,
+        } => {
+            let (ns, tag) = (namespace.as_deref(), tag.as_deref());
+            let range = date_range(since.as_deref(), until.as_deref(), updated)?;
+            let mut entries = search(mem, &query, mode.parse()?, limit, (ns, tag), &range).await?;
+            if related {
+                entries = expand_related(mem, entries, limit).await?;
+            }
+            Ok(render_list(&entries, json, "No matching memories."))
+        }
+        MemoryCommands::List {
+            category,
+            tag,
+            limit,
+            namespace,
+            since,
+            until,
+            updated,
+            json,
+        } => {
+            let category = category.as_deref().map(MemoryCategory::from);
+            let range = date_range(since.as_deref(), until.as_deref(), updated)?;
+            let scope = (namespace.as_deref(), tag.as_deref());
+            let entries = list(mem, category.as_ref(), scope, &range, limit).await?;
+            Ok(render_list(&entries, json, "No memories stored."))
+        }
+        MemoryCommands::Forget {
+            key,
+            namespace,
+            json,
+        } => {
+            let key = scoped_key(namespace.as_deref(), &key)?;
+            let forgotten = mem.forget(&key).await?;
+            Ok(if json {
+                json_line(&serde_json::json!({ "key": key, "forgotten": forgotten }))
+            } else if forgotten {
+                format!("🗑️  Forgot '{key}'\n")
+            } else {
+                format!("No memory with key '{key}'\n")
+            })
+        }
+        MemoryCommands::Relate {
+            from,
+            to,
+            relation,
+            namespace,
+            json,
+        } => {
+            let from = scoped_key(namespace.as_deref(), &from)?;
+            let to = scoped_key(namespace.as_deref(), &to)?;
+            let linked = mem.relate(&from, &to, &relation).await?;
+            let relation = normalize_relation(&relation);
+            Ok(if json {
+                json_line(&serde_json::json!({
+                    "from": from, "to": to, "relation": relation, "linked": linked,
+                }))
+            } else if linked {
+                format!("🔗 Linked '{from}' -[{relation}]-> '{to}'\n")
+            } else {
+                format!("Not linked: '{from}' and '{to}' must both exist\n")
+            })
+        }
+        MemoryCommands::Unrelate {
+            from,
+            to,
+            relation,
+            namespace,
+            json,
+        } => {
+            let from = scoped_key(namespace.as_deref(), &from)?;
+            let to = scoped_key(namespace.as_deref(), &to)?;
+            let removed = mem.unrelate(&from, &to, relation.as_deref()).await?;
+            Ok(if json {
+                json_line(&serde_json::json!({ "from": from, "to": to, "removed": removed }))
+            } else {
+                format!("Removed {removed} link(s) from '{from}' to '{to}'\n")
+            })
+        }
+        MemoryCommands::Related {
+            key,
+            namespace,
+            json,
+        } => {
+            let key = scoped_key(namespace.as_deref(), &key)?;
+            let relations = mem.relations(&key).await?;
+            if json {
+                return Ok(json_line(&serde_json::to_value(&relations)?));
+            }
+            if relations.is_empty() {
+                return Ok(format!("No relations for '{key}'.\n"));
+            }
+            let mut out = String::new();
+            for r in &relations {
+                let _ = writeln!(out, "- {} -[{}]-> {}", r.from, r.relation, r.to);
+            }
+            Ok(out)
+        }
+        MemoryCommands::Trash {
+            trash_command: MemoryTrashCommands::List { json },
+        } => trash_list(mem, json).await,
+        MemoryCommands::Restore {
+            key,
+            namespace,
+            json,
+        } => {
+            let key = scoped_key(namespace.as_deref(), &key)?;
+            let restored = mem.restore(&key).await?;
+            Ok(if json {
+                json_line(&serde_json::json!({ "key": key, "restored": restored }))
+            } else if restored {
+                format!("♻️  Restored '{key}'\n")
+            } else {
+                format!("No memory with key '{key}' in the trash\n")
+            })
+        }
+        MemoryCommands::Backup { .. } => {
+            anyhow::bail!("memory backup needs the [memory.backup] config; use handle_command")
+        }
+        MemoryCommands::Sync { .. } => {
+            anyhow::bail!("memory sync needs the [memory.sync] config; use handle_command")
+        }
+        MemoryCommands::Dedup {
+            similarity,
+            dry_run,
+            json,
+        } => {
+            let similarity = similarity.unwrap_or(super::dedup::DEFAULT_SIMILARITY);
+            dedup(mem, similarity, dry_run, json).await
+        }
+        MemoryCommands::Prune {
+            unused_for,
+            category,
+            dry_run,
+            json,
+        } => {
+            let category = category.as_deref().map(MemoryCategory::from);
+            prune(mem, &unused_for, category.as_ref(), dry_run, json).await
+        }
+        MemoryCommands::Count { json } => count(mem, json).await,
+        MemoryCommands::Stats { json } => stats(mem, json).await,
+    }
+}
+
+async fn count(mem: &dyn Memory, json: bool) -> Result<String> {
+    let count = mem.count().await?;
+    Ok(if json {
+        json_line(&serde_json::json!({ "count": count }))
+    } else {
+        format!("{count}\n")
+    })
+}
+
+async fn prune(
+    mem: &dyn Memory,
+    unused_for: &str,
+    category: Option<&MemoryCategory>,
+    dry_run: bool,
+    json: bool,
+) -> Result<String> {
+    let Some(age) = super::retention::parse_retention(unused_for)? else {
+        anyhow::bail!("--unused-for needs a period like \"90d\", not '{unused_for}'");
+    };
+    let cutoff = chrono::Utc::now() - age;
+    let keys = super::retention::prune_unused(mem, cutoff, category, dry_run).await?;
+    if json {
+        return Ok(json_line(
+            &serde_json::json!({ "keys": keys, "dry_run": dry_run }),
+        ));
+    }
+    if keys.is_empty() {
+        return Ok(format!("No memories unused for {unused_for}.\n"));
+    }
+    let mut out = if dry_run {
+        format!(
+            "Would prune {} memories unused for {unused_for}:\n",
+            keys.len()
+        )
+    } else {
+        format!(
+            "🗑️  Pruned {} memories unused for {unused_for}:\n",
+            keys.len()
+        )
+    };
+    for key in &keys {
+        let _ = writeln!(out, "- {key}");
+    }
+    Ok(out)
+}
+
+async fn dedup(mem: &dyn Memory, similarity: f64, dry_run: bool, json: bool) -> Result<String> {
+    let report = super::dedup::dedup_memories(mem, similarity, dry_run).await?;
+    if json {
+        return Ok(json_line(&serde_json::to_value(&report)?));
+    }
+    if report.groups.is_empty() {
+        return Ok("No near-duplicate memories.\n".into());
+    }
+    let duplicates: usize = report.groups.iter().map(|g| g.duplicates.len()).sum();
+    let mut out = if dry_run {
+        format!(
+            "Would merge {duplicates} duplicates into {} memories:\n",
+            report.groups.len()
+        )
+    } else {
+        format!(
+            "🧹 Merged {} duplicates into {} memories:\n",
+            report.merged,
+            report.groups.len()
+        )
+    };
+    for group in &report.groups {
+        let _ = writeln!(out, "- {} ← {}", group.keep, group.duplicates.join(", "));
+    }
+    Ok(out)
+}
+
+/// `memory backup push|pull` against the configured bucket.
+pub async fn backup(
+    command: MemoryBackupCommands,
+    mem: &dyn Memory,
+    config: &MemoryBackupConfig,
+) -> Result<String> {
+    match command {
+        MemoryBackupCommands::Push { json } => {
+            let target = super::backup::S3Target::from_config(config)?;
+            let report = super::backup::push_snapshot(mem, &target).await?;
+            Ok(if json {
+                json_line(&serde_json::json!({
+                    "object": report.object,
+                    "entries": report.entries,
+                    "bytes": report.bytes,
+                }))
+            } else {
+                format!(
+                    "☁️  Backed up {} memories to {} ({} bytes)\n",
+                    report.entries, report.object, report.bytes
+                )
+            })
+        }
+        MemoryBackupCommands::Pull { force, json } => {
+            let existing = mem.count().await?;
+            if existing > 0 && !force {
+                anyhow::bail!(
+                    "memory already holds {existing} entries; pass --force to pull the backup over them"
+                );
+            }
+            let target = super::backup::S3Target::from_config(config)?;
+            let restored = super::backup::pull_latest(mem, &target).await?;
+            Ok(match (json, restored) {
+                (true, restored) => json_line(&serde_json::json!({ "restored": restored })),
+                (false, Some(n)) => format!("☁️  Restored {n} memories from the latest backup\n"),
+                (false, None) => "No backup snapshot found.\n".into(),
+            })
+        }
+    }
+}
+
+/// `memory sync`: one full round with the configured peer.
+pub async fn sync(
+    mem: &dyn Memory,
+    config: &MemorySyncConfig,
+    direction: SyncDirection,
+    json: bool,
+) -> Result<String> {
+    let peer = SyncPeer::from_config(config)?;
+    let outcome = sync_once(mem, &peer, direction, &mut SyncCursor::default()).await?;
+    if json {
+        return Ok(json_line(&serde_json::to_value(outcome)?));
+    }
+    let mut out = String::new();
+    for (label, report) in [("Pulled", outcome.pulled), ("Pushed", outcome.pushed)] {
+        if let Some(report) = report {
+            let _ = writeln!(
+                out,
+                "🔄 {label}: {} stored, {} deleted, {} unchanged",
+                report.stored, report.deleted, report.skipped
+            );
+        }
+    }
+    Ok(out)
+}
+
+async fn add(
+    mem: &dyn Memory,
+    key: &str,
+    content: &str,
+    category: &str,
+    tags: &[String],
+    importance: Option<f64>,
+    json: bool,
+) -> Result<String> {
+    if let Some(importance) = importance {
+        if !(0.0..=1.0).contains(&importance) {
+            anyhow::bail!("--importance must be between 0.0 and 1.0, got {importance}");
+        }
+    }
+    let category = MemoryCategory::from(category);
+    if tags.is_empty() {
+        mem.store(key, content, category.clone()).await?;
+    } else {
+        mem.store_with_tags(key, content, category.clone(), tags)
+            .await?;
+    }
+    if let Some(importance) = importance {
+        mem.set_importance(key, importance).await?;
+    }
+    Ok(if json {
+        json_line(&serde_json::json!({
+            "stored": true,
+            "key": key,
+            "category": category.to_string(),
+            "tags": super::normalize_tags(tags),
+            "importance": importance,
+        }))
+    } else {
+        format!("✅ Stored '{key}' [{category}]\n")
+    })
+}
+
+async fn trash_list(mem: &dyn Memory, json: bool) -> Result<String> {
+    let trashed = mem.list_trash().await?;
+    if json {
+        let values: Vec<_> = trashed
+            .iter()
+            .map(|t| {
+                let mut value = entry_json(&t.entry);
+                value["deleted_at"] = serde_json::json!(t.deleted_at);
+                value
+            })
+            .collect();
+        return Ok(json_line(&serde_json::Value::Array(values)));
+    }
+    if trashed.is_empty() {
+        return Ok("Trash is empty.\n".into());
+    }
+    let mut out = String::new();
+    for t in &trashed {
+        let _ = writeln!(out, "(deleted {})", t.deleted_at);
+        out.push_str(&render_entries(std::slice::from_ref(&t.entry)));
+    }
+    Ok(out)
+}
+
+/// `--since`/`--until`/`--updated` as a [`DateRange`].
+fn date_range(since: Option<&str>, until: Option<&str>, updated: bool) -> Result<DateRange> {
+    let range = DateRange::new(
+        since.map(parse_time_bound).transpose()?,
+        until.map(parse_time_bound).transpose()?,
+    );
+    Ok(if updated {
+        range.on(DateField::Updated)
+    } else {
+        range
+    })
+}
+
+async fn search(
+    mem: &dyn Memory,
+    query: &str,
+    mode: MatchMode,
+    limit: usize,
+    (namespace, tag): (Option<&str>, Option<&str>),
+    range: &DateRange,
+) -> Result<Vec<MemoryEntry>> {
+    let bounded = !range.is_unbounded();
+    if mode != MatchMode::Keyword {
+        let narrowed = tag.is_some() || bounded;
+        let mut entries = match namespace {
+            // Unscoped matching leaves namespaces out, so match their listing;
+            // a namespaced prefix search is just a longer prefix
+            Some(ns) => {
+                let query = match mode {
+                    MatchMode::Prefix => super::namespaced_key(ns, query)?,
+                    _ => query.to_string(),
+                };
+                let matcher = EntryMatcher::new(mode, &query)?;
+                let mut entries = mem.list_in_namespace(ns, None).await?;
+                entries.retain(|e| matcher.matches(e));
+                entries
+            }
+            None => {
+                mem.recall_matching(query, mode, if narrowed { usize::MAX } else { limit })
+                    .await?
+            }
+        };
+        entries.retain(|e| tag.is_none_or(|t| e.has_tag(t)) && range.contains(e));
+        entries.truncate(limit);
+        return Ok(entries);
+    }
+
+    // Scoped recalls can't take a window, so fetch them whole when one is set
+    let fetch = if bounded { usize::MAX } else { limit };
+    let mut entries = match (namespace, tag) {
+        (Some(ns), _) => mem.recall_in_namespace(ns, query, fetch).await?,
+        (None, Some(tag)) => mem.recall_tagged(query, tag, fetch).await?,
+        (None, None) => return mem.recall_in_range(query, limit, range).await,
+    };
+    entries.retain(|e| tag.is_none_or(|t| e.has_tag(t)) && range.contains(e));
+    entries.truncate(limit);
+    Ok(entries)
+}
+
+async fn list(
+    mem: &dyn Memory,
+    category: Option<&MemoryCategory>,
+    (namespace, tag): (Option<&str>, Option<&str>),
+    range: &DateRange,
+    limit: Option<usize>,
+) -> Result<Vec<MemoryEntry>> {
+    let mut entries = match (namespace, tag) {
+        (Some(ns), _) => mem.list_in_namespace(ns, category).await?,
+        (None, Some(tag)) => mem.list_tagged(tag, category).await?,
+        (None, None) => mem.list_in_range(category, range).await?,
+    };
+    entries.retain(|e| tag.is_none_or(|t| e.has_tag(t)) && range.contains(e));
+    entries.truncate(limit.unwrap_or(usize::MAX));
+    Ok(entries)
+}
+
+async fn stats(mem: &dyn Memory, json: bool) -> Result<String> {
+    let entries = mem.list_all(None).await?;
+    let trashed = mem.list_trash().await?.len();
+    let cache = mem.embedding_cache_stats().await?;
+    let healthy = mem.health_check().await;
+    let mut by_category: BTreeMap<String, usize> = BTreeMap::new();
+    let mut by_tag: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in &entries {
+        *by_category.entry(entry.category.to_string()).or_default() += 1;
+        for tag in &entry.tags {
+            *by_tag.entry(tag.clone()).or_default() += 1;
+        }
+    }
+    let oldest = entries.iter().map(|e| e.timestamp.as_str()).min();
+    let newest = entries.iter().map(|e| e.timestamp.as_str()).max();
+
+    if json {
+        return Ok(json_line(&serde_json::json!({
+            "backend": mem.name(),
+            "healthy": healthy,
+            "total": entries.len(),
+            "trashed": trashed,
+            "categories": by_category,
+            "tags": by_tag,
+            "oldest": oldest,
+            "newest": newest,
+            "embedding_cache": cache.map(|c| serde_json::json!({
+                "entries": c.entries,
+                "capacity": c.capacity,
+                "hits": c.hits,
+                "misses": c.misses,
+                "hit_rate": c.hit_rate(),
+            })),
+        })));
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "🧠 Memory ({})", mem.name());
+    let _ = writeln!(out, "  Healthy: {}", if healthy { "yes" } else { "no" });
+    let _ = writeln!(out, "  Total:   {}", entries.len());
+    for (category, count) in &by_category {
+        let _ = writeln!(out, "    {category}: {count}");
+    }
+    if trashed > 0 {
+        let _ = writeln!(out, "  Trash:   {trashed}");
+    }
+    if !by_tag.is_empty() {
+        let tags: Vec<String> = by_tag.iter().map(|(t, n)| format!("#{t} ({n})")).collect();
+        let _ = writeln!(out, "  Tags:    {}", tags.join(", "));
+    }
+    if let (Some(oldest), Some(newest)) = (oldest, newest) {
+        let _ = writeln!(out, "  Oldest:  {oldest}");
+        let _ = writeln!(out, "  Newest:  {newest}");
+    }
+    if let Some(cache) = cache {
+        let _ = write!(
+            out,
+            "  Embedding cache: {}/{} vectors, {} hits, {} misses",
+            cache.entries, cache.capacity, cache.hits, cache.misses
+        );
+        if let Some(rate) = cache.hit_rate() {
+            let _ = write!(out, " ({:.0}% hit rate)", rate * 100.0);
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// `key` as stored: prefixed with its namespace when one is given.
+fn scoped_key(namespace: Option<&str>, key: &str) -> Result<String> {
+    match namespace {
+        Some(ns) => super::namespaced_key(ns, key),
+        None => Ok(key.to_string()),
+    }
+}
+
+fn entry_json(entry: &MemoryEntry) -> serde_json::Value {
+    serde_json::json!({
+        "id": entry.id,
+        "key": entry.key,
+        "content": entry.content,
+        "category": entry.category.to_string(),
+        "timestamp": entry.timestamp,
+        "updated_at": entry.updated_at,
+        "session_id": entry.session_id,
+        "tags": entry.tags,
+        "importance": entry.importance,
+        "last_accessed_at": entry.last_accessed_at,
+        "access_count": entry.access_count,
+        "score": entry.score,
+    })
+}
+
+fn json_line(value: &serde_json::Value) -> String {
+    format!("{value}\n")
+}
+
+fn render_list(entries: &[MemoryEntry], json: bool, empty: &str) -> String {
+    if json {
+        let values: Vec<_> = entries.iter().map(entry_json).collect();
+        json_line(&serde_json::Value::Array(values))
+    } else if entries.is_empty() {
+        format!("{empty}\n")
+    } else {
+        render_entries(entries)
+    }
+}
+
+fn render_entries(entries: &[MemoryEntry]) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        let _ = write!(
+            out,
+            "- {} [{}] {}",
+            entry.key, entry.category, entry.timestamp
+        );
+        for tag in &entry.tags {
+            let _ = write!(out, " #{tag}");
+        }
+        if let Some(score) = entry.score {
+            let _ = write!(out, " (score {score:.2})");
+        }
+        out.push('\n');
+        for line in entry.content.lines() {
+            let _ = writeln!(out, "    {line}");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use tempfile::TempDir;
+
+    async fn exec(mem: &SqliteMemory, command: MemoryCommands) -> String {
+        run(command, mem).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn add_get_forget_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Add {
+                key: "pref".into(),
+                content: "User likes Rust".into(),
+                category: "core".into(),
+                tags: vec!["Lang".into()],
+                importance: Some(0.9),
+                namespace: None,
+                json: false,
+            },
+        )
+        .await;
+        assert!(out.contains("Stored 'pref' [core]"));
+        let out_of_range = MemoryCommands::Add {
+            key: "pref".into(),
+            content: "x".into(),
+            category: "core".into(),
+            tags: Vec::new(),
+            importance: Some(1.5),
+            namespace: None,
+            json: false,
+        };
+        assert!(run(out_of_range, &mem).await.is_err());
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Get {
+                key: "pref".into(),
+                namespace: None,
+                json: true,
+            },
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(value["content"], "User likes Rust");
+        assert_eq!(value["tags"], serde_json::json!(["lang"]));
+        assert_eq!(value["importance"], 0.9);
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Forget {
+                key: "pref".into(),
+                namespace: None,
+                json: true,
+            },
+        )
+        .await;
+        assert!(out.contains("\"forgotten\":true"));
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Trash {
+                trash_command: MemoryTrashCommands::List { json: true },
+            },
+        )
+        .await;
+        let trashed: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(trashed[0]["key"], "pref");
+        assert!(trashed[0]["deleted_at"].is_string());
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Restore {
+                key: "pref".into(),
+                namespace: None,
+                json: false,
+            },
+        )
+        .await;
+        assert!(out.contains("Restored 'pref'"));
+        exec(
+            &mem,
+            MemoryCommands::Forget {
+                key: "pref".into(),
+                namespace: None,
+                json: false,
+            },
+        )
+        .await;
+
+        let err = run(
+            MemoryCommands::Get {
+                key: "pref".into(),
+                namespace: None,
+                json: false,
+            },
+            &mem,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("No memory with key 'pref'"));
+    }
+
+    #[tokio::test]
+    async fn namespace_scopes_add_get_and_list() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        for (ns, content) in [("alice", "likes tea"), ("bob", "likes coffee")] {
+            exec(
+                &mem,
+                MemoryCommands::Add {
+                    key: "pref".into(),
+                    content: content.into(),
+                    category: "core".into(),
+                    tags: Vec::new(),
+                    importance: None,
+                    namespace: Some(ns.into()),
+                    json: false,
+                },
+            )
+            .await;
+        }
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Get {
+                key: "pref".into(),
+                namespace: Some("bob".into()),
+                json: false,
+            },
+        )
+        .await;
+        assert!(out.contains("likes coffee"));
+
+        let out = exec(
+            &mem,
+            MemoryCommands::List {
+                category: None,
+                tag: None,
+                limit: None,
+                namespace: Some("alice".into()),
+                since: None,
+                until: None,
+                updated: false,
+                json: true,
+            },
+        )
+        .await;
+        let listed: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0]["key"], "ns/alice/pref");
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Search {
+                query: "pr".into(),
+                mode: "prefix".into(),
+                limit: 10,
+                tag: None,
+                namespace: Some("bob".into()),
+                since: None,
+                until: None,
+                updated: false,
+                related: false,
+                json: true,
+            },
+        )
+        .await;
+        let hits: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["key"], "ns/bob/pref");
+
+        let regex = |namespace: Option<&str>| MemoryCommands::Search {
+            query: "likes t.a$".into(),
+            mode: "regex".into(),
+            limit: 10,
+            tag: None,
+            namespace: namespace.map(Into::into),
+            since: None,
+            until: None,
+            updated: false,
+            related: false,
+            json: true,
+        };
+        let out = exec(&mem, regex(Some("alice"))).await;
+        let hits: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(hits[0]["key"], "ns/alice/pref");
+        assert_eq!(exec(&mem, regex(Some("bob"))).await.trim(), "[]");
+        assert_eq!(exec(&mem, regex(None)).await.trim(), "[]");
+    }
+
+    #[tokio::test]
+    async fn date_flags_bound_list_and_search() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("deploy", "shipped the release", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let list = |since: Option<&str>, until: Option<&str>| MemoryCommands::List {
+            category: None,
+            tag: None,
+            limit: None,
+            namespace: None,
+            since: since.map(Into::into),
+            until: until.map(Into::into),
+            updated: true,
+            json: true,
+        };
+
+        let out = exec(&mem, list(Some("1d"), None)).await;
+        let listed: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0]["updated_at"].is_string());
+        let out = exec(&mem, list(None, Some("2000-01-01"))).await;
+        assert_eq!(out, "[]\n");
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Search {
+                query: "release".into(),
+                mode: "keyword".into(),
+                limit: 10,
+                tag: None,
+                namespace: None,
+                since: Some("2000-01-01T00:00:00Z".into()),
+                until: None,
+                updated: false,
+                related: false,
+                json: false,
+            },
+        )
+        .await;
+        assert!(out.contains("- deploy [core]"));
+
+        let err = run(list(Some("last week"), None), &mem).await.unwrap_err();
+        assert!(err.to_string().contains("invalid time 'last week'"));
+    }
+
+    #[tokio::test]
+    async fn backup_pull_refuses_to_overwrite_without_force() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("k", "v", MemoryCategory::Core).await.unwrap();
+        let config = MemoryBackupConfig::default();
+
+        let pull = |force| MemoryBackupCommands::Pull { force, json: false };
+        let err = backup(pull(false), &mem, &config).await.unwrap_err();
+        assert!(err.to_string().contains("pass --force"));
+        // Forced, it gets as far as the unconfigured bucket
+        let err = backup(pull(true), &mem, &config).await.unwrap_err();
+        assert!(err.to_string().contains("endpoint and a bucket"));
+    }
+
+    #[tokio::test]
+    async fn sync_needs_a_peer_and_a_known_direction() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        let config = MemorySyncConfig::default();
+
+        let err = sync(&mem, &config, SyncDirection::Both, false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("peer_url"));
+        assert!("sideways".parse::<SyncDirection>().is_err());
+        let command = MemoryCommands::Sync {
+            direction: None,
+            json: false,
+        };
+        assert!(run(command, &mem).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn update_patches_fields_and_rejects_missing_key() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store_with_tags("tz", "UTC", MemoryCategory::Core, &["profile".into()])
+            .await
+            .unwrap();
+        let created = mem.get("tz").await.unwrap().unwrap().timestamp;
+
+        let update = |key: &str, content: Option<&str>, clear_tags| MemoryCommands::Update {
+            key: key.into(),
+            content: content.map(Into::into),
+            category: None,
+            tags: Vec::new(),
+            clear_tags,
+            namespace: None,
+            json: false,
+        };
+        let out = exec(&mem, update("tz", Some("Europe/Berlin"), false)).await;
+        assert_eq!(out, "✏️  Updated 'tz' [core]\n");
+        let entry = mem.get("tz").await.unwrap().unwrap();
+        assert_eq!(entry.content, "Europe/Berlin");
+        assert_eq!(entry.tags, vec!["profile"]);
+        assert_eq!(entry.timestamp, created);
+
+        exec(&mem, update("tz", None, true)).await;
+        assert!(mem.get("tz").await.unwrap().unwrap().tags.is_empty());
+
+        let err = run(update("missing", Some("x"), false), &mem)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no memory with key 'missing'"));
+        assert!(mem.get("missing").await.unwrap().is_none());
+        assert!(run(update("tz", None, false), &mem).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn dedup_previews_then_merges() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        for key in ["hook_1", "hook_2", "hook_3"] {
+            mem.store(key, "Build #42 passed!", MemoryCategory::Conversation)
+                .await
+                .unwrap();
+        }
+        mem.store("other", "Build #43 failed", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+
+        let dedup = |dry_run| MemoryCommands::Dedup {
+            similarity: Some(1.0),
+            dry_run,
+            json: false,
+        };
+        let out = exec(&mem, dedup(true)).await;
+        assert_eq!(
+            out,
+            "Would merge 2 duplicates into 1 memories:\n- hook_1 ← hook_2, hook_3\n"
+        );
+        assert_eq!(mem.count().await.unwrap(), 4);
+
+        let out = exec(&mem, dedup(false)).await;
+        assert!(out.starts_with("🧹 Merged 2 duplicates"));
+        assert_eq!(mem.count().await.unwrap(), 2);
+        assert_eq!(
+            exec(&mem, dedup(false)).await,
+            "No near-duplicate memories.\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn prune_refuses_bad_periods_and_untracked_backends() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("tz", "UTC", MemoryCategory::Core).await.unwrap();
+
+        let prune = |unused_for: &str| MemoryCommands::Prune {
+            unused_for: unused_for.into(),
+            category: None,
+            dry_run: true,
+            json: false,
+        };
+        assert_eq!(
+            exec(&mem, prune("90d")).await,
+            "No memories unused for 90d.\n"
+        );
+        assert!(run(prune("forever"), &mem).await.is_err());
+        assert!(run(prune("ninety"), &mem).await.is_err());
+
+        let markdown = crate::memory::MarkdownMemory::new(tmp.path());
+        let err = run(prune("90d"), &markdown).await.unwrap_err();
+        assert!(err.to_string().contains("does not track access"));
+    }
+
+    #[tokio::test]
+    async fn relate_show_and_search_related() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store(
+            "project-x",
+            "Project X migrates billing",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+        mem.store("deadline-june", "Due end of June", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Relate {
+                from: "project-x".into(),
+                to: "deadline-june".into(),
+                relation: "Deadline".into(),
+                namespace: None,
+                json: false,
+            },
+        )
+        .await;
+        assert_eq!(out, "🔗 Linked 'project-x' -[deadline]-> 'deadline-june'\n");
+
+        let related = |key: &str| MemoryCommands::Related {
+            key: key.into(),
+            namespace: None,
+            json: false,
+        };
+        let out = exec(&mem, related("deadline-june")).await;
+        assert_eq!(out, "- project-x -[deadline]-> deadline-june\n");
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Search {
+                query: "billing".into(),
+                mode: "keyword".into(),
+                limit: 10,
+                tag: None,
+                namespace: None,
+                since: None,
+                until: None,
+                updated: false,
+                related: true,
+                json: true,
+            },
+        )
+        .await;
+        let hits: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[1]["key"], "deadline-june");
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Unrelate {
+                from: "project-x".into(),
+                to: "deadline-june".into(),
+                relation: None,
+                namespace: None,
+                json: true,
+            },
+        )
+        .await;
+        assert!(out.contains(r#""removed":1"#));
+        assert_eq!(
+            exec(&mem, related("project-x")).await,
+            "No relations for 'project-x'.\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn list_search_and_stats() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store_with_tags("a", "Rust is fast", MemoryCategory::Core, &["lang".into()])
+            .await
+            .unwrap();
+        mem.store("b", "Rust is safe", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let out = exec(
+            &mem,
+            MemoryCommands::List {
+                category: Some("daily".into()),
+                tag: None,
+                limit: None,
+                namespace: None,
+                since: None,
+                until: None,
+                updated: false,
+                json: false,
+            },
+        )
+        .await;
+        assert!(out.contains("- b [daily]"));
+        assert!(!out.contains("- a"));
+
+        let out = exec(
+            &mem,
+            MemoryCommands::Search {
+                query: "Rust".into(),
+                mode: "keyword".into(),
+                limit: 10,
+                tag: Some("lang".into()),
+                namespace: None,
+                since: None,
+                until: None,
+                updated: false,
+                related: false,
+                json: true,
+            },
+        )
+        .await;
+        let hits: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["key"], "a");
+
+        let out = exec(&mem, MemoryCommands::Count { json: false }).await;
+        assert_eq!(out, "2\n");
+
+        let out = exec(&mem, MemoryCommands::Stats { json: true }).await;
+        let stats: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(stats["backend"], "sqlite");
+        assert_eq!(stats["total"], 2);
+        assert_eq!(stats["categories"]["core"], 1);
+        assert_eq!(stats["tags"]["lang"], 1);
+    }
+}
@@ -0,0 +1,236 @@
+// Conversation compaction — folds old conversation memories into a
+// provider-written summary stored as a core memory, then deletes the raw
+// entries so the brain doesn't grow unbounded.
+
+use super::traits::{Memory, MemoryCategory, MemoryEntry, MemoryOp};
+use crate::providers::traits::ChatOptions;
+use crate::providers::Provider;
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::Write;
+
+/// Low temperature keeps summaries factual.
+const SUMMARY_TEMPERATURE: f64 = 0.2;
+
+const SUMMARY_SYSTEM_PROMPT: &str = "You compress an assistant's conversation memory. \
+Summarize the entries below into concise bullet points that keep facts, decisions, \
+preferences and open tasks. Drop greetings and small talk. Reply with the summary only.";
+
+/// Outcome of one compaction pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// Summaries written as core memories
+    pub summaries: usize,
+    /// Raw conversation entries folded into those summaries and deleted
+    pub compacted: usize,
+}
+
+/// Summarize conversation entries older than `older_than_days`, at most
+/// `batch_size` entries per summary. Entries whose timestamp can't be
+/// parsed are left untouched.
+pub async fn compact_conversations(
+    mem: &dyn Memory,
+    provider: &dyn Provider,
+    model: &str,
+    older_than_days: u32,
+    batch_size: usize,
+) -> anyhow::Result<CompactionReport> {
+    let cutoff = Utc::now() - Duration::days(i64::from(older_than_days));
+    let mut stale: Vec<(DateTime<Utc>, MemoryEntry)> = mem
+        .list(Some(&MemoryCategory::Conversation))
+        .await?
+        .into_iter()
+        .filter_map(|entry| {
+            let at = DateTime::parse_from_rfc3339(&entry.timestamp)
+                .ok()?
+                .with_timezone(&Utc);
+            (at < cutoff).then_some((at, entry))
+        })
+        .collect();
+    stale.sort_by_key(|(at, _)| *at);
+
+    let mut report = CompactionReport::default();
+    for batch in stale.chunks(batch_size.max(1)) {
+        let mut transcript = String::new();
+        for (at, entry) in batch {
+            let _ = writeln!(
+                transcript,
+                "[{}] {}: {}",
+                at.format("%Y-%m-%d %H:%M"),
+                entry.key,
+                entry.content
+            );
+        }
+
+        let summary = provider
+            .chat_with_system(
+                Some(SUMMARY_SYSTEM_PROMPT),
+                &transcript,
+                model,
+                &ChatOptions::new(SUMMARY_TEMPERATURE),
+            )
+            .await?
+            .text;
+        let summary = summary.trim();
+        if summary.is_empty() {
+            anyhow::bail!("provider returned an empty summary");
+        }
+
+        let (first, last) = (batch[0].0, batch[batch.len() - 1].0);
+        let key = format!(
+            "conversation_summary_{}_{}",
+            first.format("%Y%m%dT%H%M%S"),
+            last.format("%Y%m%dT%H%M%S")
+        );
+        let content = format!(
+            "Conversation summary ({} – {}, {} entries):\n{summary}",
+            first.format("%Y-%m-%d"),
+            last.format("%Y-%m-%d"),
+            batch.len()
+        );
+        // Summary and deletions land together, or not at all
+        let mut ops = vec![MemoryOp::Store {
+            key,
+            content,
+            category: MemoryCategory::Core,
+            tags: Vec::new(),
+        }];
+        ops.extend(batch.iter().map(|(_, entry)| MemoryOp::Forget {
+            key: entry.key.clone(),
+        }));
+        let applied = mem.apply_batch(&ops).await?;
+        report.summaries += 1;
+        report.compacted += applied - 1;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use crate::providers::traits::ChatReply;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    #[derive(Default)]
+    struct SummaryProvider {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Provider for SummaryProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            self.prompts.lock().unwrap().push(message.to_string());
+            Ok("- user prefers dark mode".into())
+        }
+    }
+
+    /// Rewrite an entry's timestamps so it looks `days` old.
+    fn backdate(tmp: &TempDir, key: &str, days: i64) {
+        let conn = rusqlite::Connection::open(tmp.path().join("memory").join("brain.db")).unwrap();
+        let at = (Utc::now() - Duration::days(days)).to_rfc3339();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1, updated_at = ?1 WHERE key = ?2",
+            rusqlite::params![at, key],
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn compacts_only_old_conversation_entries() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("old_a", "I like dark mode", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("old_b", "hello there", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("fresh", "what's the weather", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("fact", "user is in Berlin", MemoryCategory::Core)
+            .await
+            .unwrap();
+        backdate(&tmp, "old_a", 10);
+        backdate(&tmp, "old_b", 9);
+        backdate(&tmp, "fact", 30);
+
+        let provider = SummaryProvider::default();
+        let report = compact_conversations(&mem, &provider, "m", 7, 50)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            CompactionReport {
+                summaries: 1,
+                compacted: 2
+            }
+        );
+
+        let prompts = provider.prompts.lock().unwrap();
+        assert!(prompts[0].contains("I like dark mode"));
+        assert!(!prompts[0].contains("weather"));
+
+        assert!(mem.get("old_a").await.unwrap().is_none());
+        assert!(mem.get("fresh").await.unwrap().is_some());
+        assert!(mem.get("fact").await.unwrap().is_some());
+        let core = mem.list(Some(&MemoryCategory::Core)).await.unwrap();
+        let summary = core
+            .iter()
+            .find(|e| e.key.starts_with("conversation_summary_"))
+            .unwrap();
+        assert!(summary.content.contains("dark mode"));
+    }
+
+    #[tokio::test]
+    async fn splits_into_batches() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        for i in 0..5 {
+            let key = format!("turn_{i}");
+            mem.store(&key, "chat", MemoryCategory::Conversation)
+                .await
+                .unwrap();
+            backdate(&tmp, &key, 20 - i);
+        }
+
+        let provider = SummaryProvider::default();
+        let report = compact_conversations(&mem, &provider, "m", 7, 2)
+            .await
+            .unwrap();
+        assert_eq!(report.summaries, 3);
+        assert_eq!(report.compacted, 5);
+        assert_eq!(
+            mem.list(Some(&MemoryCategory::Conversation))
+                .await
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn nothing_to_compact_skips_provider() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("fresh", "hi", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+
+        let provider = SummaryProvider::default();
+        let report = compact_conversations(&mem, &provider, "m", 7, 50)
+            .await
+            .unwrap();
+        assert_eq!(report, CompactionReport::default());
+        assert!(provider.prompts.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+// Near-duplicate deduplication — folds memories whose content is identical
+// up to case, punctuation and whitespace (or similar above a threshold) into
+// the oldest copy, so a webhook that fires the same message a hundred times
+// leaves one entry behind.
+
+use super::traits::{namespace_of, normalize_tags, Memory, MemoryEntry};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// Default `memory.dedup_similarity`.
+pub const DEFAULT_SIMILARITY: f64 = 0.9;
+
+/// Entries that will be (or were) folded into `keep`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateGroup {
+    pub keep: String,
+    pub duplicates: Vec<String>,
+}
+
+/// Outcome of one dedup pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DedupReport {
+    pub groups: Vec<DuplicateGroup>,
+    /// Duplicates moved to the trash
+    pub merged: usize,
+}
+
+/// Content reduced to lowercase alphanumeric words separated by single
+/// spaces; equal strings mean "the same message".
+pub fn normalize_content(content: &str) -> String {
+    content
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Jaccard similarity of the word-pair shingles of two normalized texts
+/// (single words for one-word texts), 0.0–1.0.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (shingles(a), shingles(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let ratio = a.intersection(&b).count() as f64 / union as f64;
+    ratio
+}
+
+fn shingles(normalized: &str) -> BTreeSet<String> {
+    let words: Vec<&str> = normalized.split(' ').filter(|w| !w.is_empty()).collect();
+    if words.len() < 2 {
+        return words.into_iter().map(String::from).collect();
+    }
+    words.windows(2).map(|pair| pair.join(" ")).collect()
+}
+
+/// Group `entries` into near-duplicates: same category, same namespace,
+/// and content similarity of at least `threshold` (1.0 = identical after
+/// normalization). The oldest entry of each group is kept.
+pub fn find_duplicates(entries: &[MemoryEntry], threshold: f64) -> Vec<DuplicateGroup> {
+    let mut sorted: Vec<&MemoryEntry> = entries.iter().collect();
+    sorted.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.key.cmp(&b.key)));
+
+    // (partition, normalized content of the kept entry, group)
+    let mut groups: Vec<(String, String, DuplicateGroup)> = Vec::new();
+    let mut exact: HashMap<(String, String), usize> = HashMap::new();
+    for entry in sorted {
+        let partition = format!(
+            "{}\u{0}{}",
+            entry.category,
+            namespace_of(&entry.key).unwrap_or_default()
+        );
+        let normalized = normalize_content(&entry.content);
+
+        let found = exact
+            .get(&(partition.clone(), normalized.clone()))
+            .copied()
+            .or_else(|| {
+                if threshold >= 1.0 {
+                    return None;
+                }
+                groups.iter().position(|(p, kept, _)| {
+                    *p == partition && similarity(kept, &normalized) >= threshold
+                })
+            });
+        if let Some(i) = found {
+            groups[i].2.duplicates.push(entry.key.clone());
+            continue;
+        }
+        exact.insert((partition.clone(), normalized.clone()), groups.len());
+        groups.push((
+            partition,
+            normalized,
+            DuplicateGroup {
+                keep: entry.key.clone(),
+                duplicates: Vec::new(),
+            },
+        ));
+    }
+
+    groups
+        .into_iter()
+        .map(|(_, _, group)| group)
+        .filter(|group| !group.duplicates.is_empty())
+        .collect()
+}
+
+/// Find near-duplicates across all memories and, unless `dry_run`, merge
+/// each group into its kept entry: tags are unioned, the highest importance
+/// wins, relations move over, and the duplicates go to the trash.
+pub async fn dedup_memories(
+    mem: &dyn Memory,
+    threshold: f64,
+    dry_run: bool,
+) -> anyhow::Result<DedupReport> {
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        anyhow::bail!("dedup similarity must be in (0, 1], got {threshold}");
+    }
+    let entries = mem.list_all(None).await?;
+    let groups = find_duplicates(&entries, threshold);
+    let mut report = DedupReport::default();
+    if !dry_run {
+        let by_key: HashMap<&str, &MemoryEntry> =
+            entries.iter().map(|e| (e.key.as_str(), e)).collect();
+        for group in &groups {
+            report.merged += merge_group(mem, group, &by_key).await?;
+        }
+    }
+    report.groups = groups;
+    Ok(report)
+}
+
+async fn merge_group(
+    mem: &dyn Memory,
+    group: &DuplicateGroup,
+    by_key: &HashMap<&str, &MemoryEntry>,
+) -> anyhow::Result<usize> {
+    let keep = by_key[group.keep.as_str()];
+    let dups: Vec<&MemoryEntry> = group
+        .duplicates
+        .iter()
+        .map(|k| by_key[k.as_str()])
+        .collect();
+
+    let tags = normalize_tags(
+        &dups
+            .iter()
+            .flat_map(|d| d.tags.iter())
+            .chain(&keep.tags)
+            .collect::<Vec<_>>(),
+    );
+    // Re-storing resets session and expiry, so only do it for new tags
+    if tags != normalize_tags(&keep.tags) {
+        mem.store_with_tags(&keep.key, &keep.content, keep.category.clone(), &tags)
+            .await?;
+    }
+    let importance = dups
+        .iter()
+        .map(|d| d.importance)
+        .fold(keep.importance, f64::max);
+    if importance > keep.importance {
+        mem.set_importance(&keep.key, importance).await?;
+    }
+
+    let mut merged = 0;
+    for dup in dups {
+        for relation in mem.relations(&dup.key).await? {
+            let other = relation.other(&dup.key);
+            if other == keep.key {
+                continue;
+            }
+            if relation.from == dup.key {
+                mem.relate(&keep.key, other, &relation.relation).await?;
+            } else {
+                mem.relate(other, &keep.key, &relation.relation).await?;
+            }
+        }
+        if mem.forget(&dup.key).await? {
+            merged += 1;
+        }
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{MemoryCategory, SqliteMemory};
+    use tempfile::TempDir;
+
+    #[test]
+    fn normalization_ignores_case_punctuation_and_spacing() {
+        assert_eq!(
+            normalize_content("  Deploy FAILED:   build #42!\n"),
+            "deploy failed build 42"
+        );
+        assert!((similarity("a b c d", "a b c d") - 1.0).abs() < f64::EPSILON);
+        assert!(similarity("deploy failed on main", "deploy succeeded on staging") < 0.5);
+    }
+
+    #[tokio::test]
+    async fn merges_copies_into_the_oldest_entry() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        let alert = "Webhook: deploy of api to production finished with status ok";
+        mem.store("hook_1", alert, MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store_with_tags(
+            "hook_2",
+            &alert.to_uppercase(),
+            MemoryCategory::Conversation,
+            &["deploy".into()],
+        )
+        .await
+        .unwrap();
+        mem.store_with_importance(
+            "hook_3",
+            "Webhook: deploy of api to production finished with status ok (retry 2)",
+            MemoryCategory::Conversation,
+            0.9,
+        )
+        .await
+        .unwrap();
+        mem.store("release", "Release notes for v2", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.relate("release", "hook_3", "announced_by")
+            .await
+            .unwrap();
+        mem.store_in_namespace("alice", "hook", alert, MemoryCategory::Conversation)
+            .await
+            .unwrap();
+
+        let report = dedup_memories(&mem, 0.99, true).await.unwrap();
+        assert_eq!(report.groups[0].duplicates, vec!["hook_2".to_string()]);
+        assert_eq!(report.merged, 0);
+        assert_eq!(mem.count().await.unwrap(), 5);
+
+        let report = dedup_memories(&mem, 0.7, false).await.unwrap();
+        assert_eq!(
+            report.groups,
+            vec![DuplicateGroup {
+                keep: "hook_1".into(),
+                duplicates: vec!["hook_2".into(), "hook_3".into()],
+            }]
+        );
+        assert_eq!(report.merged, 2);
+        assert_eq!(mem.count().await.unwrap(), 3);
+
+        let kept = mem.get("hook_1").await.unwrap().unwrap();
+        assert_eq!(kept.tags, vec!["deploy"]);
+        assert!((kept.importance - 0.9).abs() < 1e-9);
+        let relations = mem.relations("hook_1").await.unwrap();
+        assert_eq!(relations[0].from, "release");
+        assert_eq!(relations[0].relation, "announced_by");
+        // Other namespaces are left alone; merged copies are recoverable
+        assert!(mem.get("ns/alice/hook").await.unwrap().is_some());
+        assert_eq!(mem.list_trash().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn rejects_out_of_range_similarity() {
+        let mem = crate::memory::InMemoryMemory::new();
+        assert!(dedup_memories(&mem, 0.0, true).await.is_err());
+        assert!(dedup_memories(&mem, 1.5, true).await.is_err());
+    }
+}
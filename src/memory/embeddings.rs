@@ -44,6 +44,7 @@ impl EmbeddingProvider for NoopEmbedding {
 
 pub struct OpenAiEmbedding {
     client: reqwest::Client,
+    name: &'static str,
     base_url: String,
     api_key: String,
     model: String,
@@ -53,19 +54,29 @@ pub struct OpenAiEmbedding {
 impl OpenAiEmbedding {
     pub fn new(base_url: &str, api_key: &str, model: &str, dims: usize) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::proxy::client("openai"),
+            name: "openai",
             base_url: base_url.trim_end_matches('/').to_string(),
             api_key: api_key.to_string(),
             model: model.to_string(),
             dims,
         }
     }
+
+    /// `OpenRouter` serves the same `/v1/embeddings` API under `/api`.
+    pub fn openrouter(api_key: &str, model: &str, dims: usize) -> Self {
+        Self {
+            client: crate::proxy::client("openrouter"),
+            name: "openrouter",
+            ..Self::new("https://openrouter.ai/api", api_key, model, dims)
+        }
+    }
 }
 
 #[async_trait]
 impl EmbeddingProvider for OpenAiEmbedding {
     fn name(&self) -> &str {
-        "openai"
+        self.name
     }
 
     fn dimensions(&self) -> usize {
@@ -123,6 +134,77 @@ impl EmbeddingProvider for OpenAiEmbedding {
     }
 }
 
+// ── Ollama embedding provider ────────────────────────────────
+
+/// Local embeddings via Ollama's `/api/embed` (e.g. `nomic-embed-text`).
+pub struct OllamaEmbedding {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    dims: usize,
+}
+
+impl OllamaEmbedding {
+    pub fn new(base_url: Option<&str>, model: &str, dims: usize) -> Self {
+        Self {
+            client: crate::proxy::client("ollama"),
+            base_url: base_url
+                .filter(|url| !url.is_empty())
+                .unwrap_or("http://localhost:11434")
+                .trim_end_matches('/')
+                .to_string(),
+            model: model.to_string(),
+            dims,
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbedding {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dims
+    }
+
+    async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = serde_json::json!({
+            "model": self.model,
+            "input": texts,
+        });
+
+        let resp = self
+            .client
+            .post(format!("{}/api/embed", self.base_url))
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Ollama embedding error {status}: {text}");
+        }
+
+        let parsed: OllamaEmbedResponse = resp
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("Invalid Ollama embedding response: {e}"))?;
+        Ok(parsed.embeddings)
+    }
+}
+
 // ── Factory ──────────────────────────────────────────────────
 
 pub fn create_embedding_provider(
@@ -141,6 +223,17 @@ pub fn create_embedding_provider(
                 dims,
             ))
         }
+        "openrouter" => Box::new(OpenAiEmbedding::openrouter(
+            api_key.unwrap_or(""),
+            model,
+            dims,
+        )),
+        "ollama" => Box::new(OllamaEmbedding::new(None, model, dims)),
+        name if name.starts_with("ollama:") => Box::new(OllamaEmbedding::new(
+            name.strip_prefix("ollama:"),
+            model,
+            dims,
+        )),
         name if name.starts_with("custom:") => {
             let base_url = name.strip_prefix("custom:").unwrap_or("");
             let key = api_key.unwrap_or("");
@@ -212,6 +305,48 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn factory_openrouter_and_ollama() {
+        let p = create_embedding_provider(
+            "openrouter",
+            Some("key"),
+            "openai/text-embedding-3-small",
+            1536,
+        );
+        assert_eq!(p.name(), "openrouter");
+        assert_eq!(p.dimensions(), 1536);
+
+        let p = create_embedding_provider("ollama", None, "nomic-embed-text", 768);
+        assert_eq!(p.name(), "ollama");
+        assert_eq!(p.dimensions(), 768);
+
+        let p = create_embedding_provider(
+            "ollama:http://gpu-box:11434/",
+            None,
+            "nomic-embed-text",
+            768,
+        );
+        assert_eq!(p.name(), "ollama");
+    }
+
+    #[test]
+    fn openrouter_uses_api_prefix() {
+        let p = OpenAiEmbedding::openrouter("key", "model", 1536);
+        assert_eq!(p.base_url, "https://openrouter.ai/api");
+    }
+
+    #[test]
+    fn ollama_default_and_custom_urls() {
+        assert_eq!(
+            OllamaEmbedding::new(None, "m", 768).base_url,
+            "http://localhost:11434"
+        );
+        assert_eq!(
+            OllamaEmbedding::new(Some("http://gpu-box:11434/"), "m", 768).base_url,
+            "http://gpu-box:11434"
+        );
+    }
+
     #[test]
     fn factory_empty_string_returns_noop() {
         let p = create_embedding_provider("", None, "model", 1536);
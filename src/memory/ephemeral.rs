@@ -0,0 +1,848 @@
+use super::ranking::{clamp_importance, RecallRanking, DEFAULT_IMPORTANCE};
+use super::traits::{
+    distinct_keys, is_namespaced, namespace_prefix, normalize_relation, normalize_tags,
+    EntryMatcher, MatchMode, Memory, MemoryCategory, MemoryEntry, MemoryOp, MemoryPatch,
+    MemoryRelation, TrashedEntry,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use std::collections::HashMap;
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+/// In-process memory — a `HashMap` that is gone when the process exits.
+///
+/// For tests, CI, and stateless deployments where nothing should touch
+/// disk (`memory.backend = "ephemeral"`). Supports the same keyed upsert,
+/// tags, sessions and TTLs as the `SQLite` backend; recall is keyword-only.
+pub struct InMemoryMemory {
+    entries: RwLock<HashMap<String, StoredEntry>>,
+    /// `(from_id, to_id, relation)`, oldest first. By id, so a link
+    /// survives overwrites and dies with a purged entry.
+    relations: RwLock<Vec<(String, String, String)>>,
+    ranking: RecallRanking,
+}
+
+struct StoredEntry {
+    entry: MemoryEntry,
+    /// Bumped on every write; orders listings newest first
+    revision: u64,
+    expires_at: Option<Instant>,
+    /// Set by `forget`; the entry sits in the trash until restored or purged
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+impl StoredEntry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+
+    fn is_live(&self, now: Instant) -> bool {
+        self.deleted_at.is_none() && !self.is_expired(now)
+    }
+}
+
+impl Default for InMemoryMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryMemory {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            relations: RwLock::new(Vec::new()),
+            ranking: RecallRanking::default(),
+        }
+    }
+
+    /// Halve the recency boost of recall hits every `days` (0 disables).
+    #[must_use]
+    pub fn with_recency_half_life_days(mut self, days: u32) -> Self {
+        self.ranking = RecallRanking::new(days);
+        self
+    }
+
+    fn upsert(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+        tags: &[String],
+        ttl: Option<Duration>,
+    ) {
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        Self::upsert_locked(&mut entries, key, content, category, session_id, tags, ttl);
+    }
+
+    fn upsert_locked(
+        entries: &mut HashMap<String, StoredEntry>,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+        tags: &[String],
+        ttl: Option<Duration>,
+    ) {
+        let revision = entries.values().map(|s| s.revision).max().unwrap_or(0) + 1;
+        // Like SQLite, a replaced entry keeps its id, creation time,
+        // importance and access stats
+        let now = Local::now().to_rfc3339();
+        let (id, timestamp, importance, last_accessed_at, access_count) =
+            entries.get(key).map_or_else(
+                || {
+                    (
+                        uuid::Uuid::new_v4().to_string(),
+                        now.clone(),
+                        DEFAULT_IMPORTANCE,
+                        None,
+                        0,
+                    )
+                },
+                |s| {
+                    (
+                        s.entry.id.clone(),
+                        s.entry.timestamp.clone(),
+                        s.entry.importance,
+                        s.entry.last_accessed_at.clone(),
+                        s.entry.access_count,
+                    )
+                },
+            );
+        entries.insert(
+            key.to_string(),
+            StoredEntry {
+                entry: MemoryEntry {
+                    id,
+                    key: key.to_string(),
+                    content: content.to_string(),
+                    category,
+                    timestamp,
+                    updated_at: Some(now),
+                    session_id: session_id.map(str::to_string),
+                    tags: normalize_tags(tags),
+                    importance,
+                    last_accessed_at,
+                    access_count,
+                    score: None,
+                },
+                revision,
+                expires_at: ttl.map(|ttl| Instant::now() + ttl),
+                deleted_at: None,
+            },
+        );
+    }
+
+    /// Move a live entry to the trash.
+    fn trash_locked(entries: &mut HashMap<String, StoredEntry>, key: &str) -> bool {
+        match entries.get_mut(key) {
+            Some(stored) if stored.is_live(Instant::now()) => {
+                stored.deleted_at = Some(Utc::now());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Count a read of `entries`, in the store and on the returned copies.
+    fn record_access(&self, mut entries: Vec<MemoryEntry>) -> Vec<MemoryEntry> {
+        let now = Local::now().to_rfc3339();
+        let mut stored = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        for entry in &mut entries {
+            if let Some(s) = stored.get_mut(&entry.key) {
+                s.entry.access_count += 1;
+                s.entry.last_accessed_at = Some(now.clone());
+                entry.access_count = s.entry.access_count;
+                entry.last_accessed_at = Some(now.clone());
+            }
+        }
+        entries
+    }
+
+    /// Live entry under `key`, without counting it as accessed.
+    fn lookup(&self, key: &str) -> Option<MemoryEntry> {
+        self.collect(|e| e.key == key).into_iter().next()
+    }
+
+    /// Live entries matching `filter`, newest first.
+    fn collect(&self, filter: impl Fn(&MemoryEntry) -> bool) -> Vec<MemoryEntry> {
+        let now = Instant::now();
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        let mut live: Vec<&StoredEntry> = entries
+            .values()
+            .filter(|s| s.is_live(now) && filter(&s.entry))
+            .collect();
+        live.sort_by(|a, b| b.revision.cmp(&a.revision));
+        live.into_iter().map(|s| s.entry.clone()).collect()
+    }
+
+    /// Keyword recall over the live entries `filter` keeps.
+    fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        filter: impl Fn(&MemoryEntry) -> bool,
+    ) -> Vec<MemoryEntry> {
+        let query_lower = query.to_lowercase();
+        let keywords: Vec<&str> = query_lower.split_whitespace().collect();
+        if keywords.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored: Vec<MemoryEntry> = self
+            .collect(filter)
+            .into_iter()
+            .filter_map(|mut entry| {
+                let haystack = format!("{} {}", entry.key, entry.content).to_lowercase();
+                let matched = keywords.iter().filter(|kw| haystack.contains(**kw)).count();
+                if matched == 0 {
+                    return None;
+                }
+                #[allow(clippy::cast_precision_loss)]
+                let score = matched as f64 / keywords.len() as f64;
+                entry.score = Some(score);
+                Some(entry)
+            })
+            .collect();
+
+        // Stable sort keeps newest-first among equal scores
+        self.ranking.rerank(&mut scored);
+        scored.truncate(limit);
+        self.record_access(scored)
+    }
+}
+
+#[async_trait]
+impl Memory for InMemoryMemory {
+    fn name(&self) -> &str {
+        "ephemeral"
+    }
+
+    async fn store(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+    ) -> anyhow::Result<()> {
+        self.upsert(key, content, category, None, &[], None);
+        Ok(())
+    }
+
+    async fn store_with_ttl(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        ttl: Duration,
+    ) -> anyhow::Result<()> {
+        self.upsert(key, content, category, None, &[], Some(ttl));
+        Ok(())
+    }
+
+    async fn store_with_tags(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        tags: &[String],
+    ) -> anyhow::Result<()> {
+        self.upsert(key, content, category, None, tags, None);
+        Ok(())
+    }
+
+    async fn apply_batch(&self, ops: &[MemoryOp]) -> anyhow::Result<usize> {
+        // Nothing here can fail, so holding the lock for the whole batch
+        // is enough to make it all-or-nothing to readers
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        let mut applied = 0;
+        for op in ops {
+            match op {
+                MemoryOp::Store {
+                    key,
+                    content,
+                    category,
+                    tags,
+                } => {
+                    Self::upsert_locked(
+                        &mut entries,
+                        key,
+                        content,
+                        category.clone(),
+                        None,
+                        tags,
+                        None,
+                    );
+                    applied += 1;
+                }
+                MemoryOp::Forget { key } => {
+                    if Self::trash_locked(&mut entries, key) {
+                        applied += 1;
+                    }
+                }
+            }
+        }
+        Ok(applied)
+    }
+
+    async fn store_in_session(
+        &self,
+        session_id: &str,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+    ) -> anyhow::Result<()> {
+        self.upsert(key, content, category, Some(session_id), &[], None);
+        Ok(())
+    }
+
+    async fn recall_session(
+        &self,
+        session_id: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mut entries = self.collect(|e| e.session_id.as_deref() == Some(session_id));
+        entries.truncate(limit);
+        entries.reverse();
+        Ok(self.record_access(entries))
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<usize> {
+        let now = Instant::now();
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        let before = entries.len();
+        entries.retain(|_, s| !s.is_expired(now));
+        Ok(before - entries.len())
+    }
+
+    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
+        Ok(self.search(query, limit, |e| !is_namespaced(&e.key)))
+    }
+
+    async fn recall_in_namespace(
+        &self,
+        namespace: &str,
+        query: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let prefix = namespace_prefix(namespace)?;
+        Ok(self.search(query, limit, |e| e.key.starts_with(&prefix)))
+    }
+
+    async fn recall_matching(
+        &self,
+        query: &str,
+        mode: MatchMode,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        if mode == MatchMode::Keyword {
+            return self.recall(query, limit).await;
+        }
+        // Match under the read lock; only hits are cloned
+        let matcher = EntryMatcher::new(mode, query)?;
+        let mut entries = self.collect(|e| !is_namespaced(&e.key) && matcher.matches(e));
+        entries.truncate(limit);
+        Ok(self.record_access(entries))
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
+        Ok(self
+            .record_access(self.lookup(key).into_iter().collect())
+            .pop())
+    }
+
+    async fn get_many(&self, keys: &[&str]) -> anyhow::Result<Vec<MemoryEntry>> {
+        let entries = distinct_keys(keys)
+            .into_iter()
+            .filter_map(|key| self.lookup(key))
+            .collect();
+        Ok(self.record_access(entries))
+    }
+
+    async fn list(&self, category: Option<&MemoryCategory>) -> anyhow::Result<Vec<MemoryEntry>> {
+        Ok(self.collect(|e| !is_namespaced(&e.key) && category.is_none_or(|c| &e.category == c)))
+    }
+
+    async fn list_all(
+        &self,
+        category: Option<&MemoryCategory>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        Ok(self.collect(|e| category.is_none_or(|c| &e.category == c)))
+    }
+
+    async fn relate(&self, from: &str, to: &str, relation: &str) -> anyhow::Result<bool> {
+        if from == to {
+            anyhow::bail!("a memory can't be related to itself ('{from}')");
+        }
+        let (Some(from), Some(to)) = (self.lookup(from), self.lookup(to)) else {
+            return Ok(false);
+        };
+        let link = (from.id, to.id, normalize_relation(relation));
+        let mut relations = self
+            .relations
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if !relations.contains(&link) {
+            relations.push(link);
+        }
+        Ok(true)
+    }
+
+    async fn unrelate(
+        &self,
+        from: &str,
+        to: &str,
+        relation: Option<&str>,
+    ) -> anyhow::Result<usize> {
+        let (Some(from), Some(to)) = (self.lookup(from), self.lookup(to)) else {
+            return Ok(0);
+        };
+        let relation = relation.map(normalize_relation);
+        let mut relations = self
+            .relations
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let before = relations.len();
+        relations.retain(|(f, t, r)| {
+            !(f == &from.id && t == &to.id && relation.as_ref().is_none_or(|rel| rel == r))
+        });
+        Ok(before - relations.len())
+    }
+
+    async fn relations(&self, key: &str) -> anyhow::Result<Vec<MemoryRelation>> {
+        let keys: HashMap<String, String> = self
+            .collect(|_| true)
+            .into_iter()
+            .map(|e| (e.id, e.key))
+            .collect();
+        let relations = self
+            .relations
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        Ok(relations
+            .iter()
+            .filter_map(|(f, t, r)| {
+                let (from, to) = (keys.get(f)?, keys.get(t)?);
+                (from == key || to == key).then(|| MemoryRelation {
+                    from: from.clone(),
+                    to: to.clone(),
+                    relation: r.clone(),
+                })
+            })
+            .collect())
+    }
+
+    async fn forget(&self, key: &str) -> anyhow::Result<bool> {
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        Ok(Self::trash_locked(&mut entries, key))
+    }
+
+    async fn set_importance(&self, key: &str, importance: f64) -> anyhow::Result<bool> {
+        let now = Instant::now();
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        match entries.get_mut(key) {
+            Some(stored) if stored.is_live(now) => {
+                stored.entry.importance = clamp_importance(importance);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn update(&self, key: &str, patch: &MemoryPatch) -> anyhow::Result<MemoryEntry> {
+        let now = Instant::now();
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        let revision = entries.values().map(|s| s.revision).max().unwrap_or(0) + 1;
+        let Some(stored) = entries.get_mut(key).filter(|s| s.is_live(now)) else {
+            anyhow::bail!("no memory with key '{key}'");
+        };
+        let entry = &mut stored.entry;
+        if let Some(content) = &patch.content {
+            entry.content.clone_from(content);
+        }
+        if let Some(category) = &patch.category {
+            entry.category = category.clone();
+        }
+        if let Some(tags) = &patch.tags {
+            entry.tags = normalize_tags(tags);
+        }
+        entry.updated_at = Some(Local::now().to_rfc3339());
+        stored.revision = revision;
+        Ok(stored.entry.clone())
+    }
+
+    fn tracks_access(&self) -> bool {
+        true
+    }
+
+    async fn list_trash(&self) -> anyhow::Result<Vec<TrashedEntry>> {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        let mut trashed: Vec<(DateTime<Utc>, &MemoryEntry)> = entries
+            .values()
+            .filter_map(|s| Some((s.deleted_at?, &s.entry)))
+            .collect();
+        trashed.sort_by(|a, b| b.0.cmp(&a.0));
+        Ok(trashed
+            .into_iter()
+            .map(|(at, entry)| TrashedEntry {
+                entry: entry.clone(),
+                deleted_at: at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            })
+            .collect())
+    }
+
+    async fn restore(&self, key: &str) -> anyhow::Result<bool> {
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        Ok(entries
+            .get_mut(key)
+            .and_then(|s| s.deleted_at.take())
+            .is_some())
+    }
+
+    async fn purge_trash(&self, cutoff: DateTime<Utc>) -> anyhow::Result<usize> {
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        let before = entries.len();
+        entries.retain(|_, s| s.deleted_at.is_none_or(|at| at > cutoff));
+        Ok(before - entries.len())
+    }
+
+    async fn count(&self) -> anyhow::Result<usize> {
+        Ok(self.collect(|_| true).len())
+    }
+
+    async fn health_check(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn relations_link_live_entries_and_expand_recall() {
+        let mem = InMemoryMemory::new();
+        mem.store(
+            "project-x",
+            "Project X migrates billing",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+        mem.store("deadline-june", "Due end of June", MemoryCategory::Core)
+            .await
+            .unwrap();
+        assert!(mem
+            .relate("project-x", "deadline-june", "deadline")
+            .await
+            .unwrap());
+        assert!(!mem.relate("project-x", "ghost", "").await.unwrap());
+
+        let expanded = mem.recall_with_related("billing", 5).await.unwrap();
+        assert_eq!(expanded.len(), 2);
+        assert_eq!(expanded[1].key, "deadline-june");
+
+        // Overwriting keeps the link; trashing hides it
+        mem.store("deadline-june", "Due mid June", MemoryCategory::Core)
+            .await
+            .unwrap();
+        assert_eq!(mem.relations("deadline-june").await.unwrap().len(), 1);
+        mem.forget("project-x").await.unwrap();
+        assert!(mem.relations("deadline-june").await.unwrap().is_empty());
+        mem.restore("project-x").await.unwrap();
+        assert_eq!(
+            mem.unrelate("project-x", "deadline-june", None)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn store_get_upsert_and_forget() {
+        let mem = InMemoryMemory::new();
+        mem.store("pref", "likes tea", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let first = mem.get("pref").await.unwrap().unwrap();
+        mem.store("pref", "likes coffee", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let entry = mem.get("pref").await.unwrap().unwrap();
+        assert_eq!(entry.content, "likes coffee");
+        assert_eq!(entry.id, first.id);
+        assert_eq!(mem.count().await.unwrap(), 1);
+
+        assert!(mem.forget("pref").await.unwrap());
+        assert!(!mem.forget("pref").await.unwrap());
+        assert_eq!(mem.count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn forget_trashes_until_restored_or_purged() {
+        let mem = InMemoryMemory::new();
+        mem.store("a", "keep me", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("b", "drop me", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.forget("a").await.unwrap();
+        mem.forget("b").await.unwrap();
+
+        assert!(mem.recall("me", 10).await.unwrap().is_empty());
+        assert_eq!(mem.list_trash().await.unwrap().len(), 2);
+
+        assert!(mem.restore("a").await.unwrap());
+        assert!(!mem.restore("a").await.unwrap());
+        assert_eq!(mem.get("a").await.unwrap().unwrap().content, "keep me");
+
+        let purged = mem
+            .purge_trash(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+        assert!(mem.list_trash().await.unwrap().is_empty());
+        assert!(!mem.restore("b").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn list_filters_category_newest_first() {
+        let mem = InMemoryMemory::new();
+        mem.store("a", "one", MemoryCategory::Core).await.unwrap();
+        mem.store("b", "two", MemoryCategory::Daily).await.unwrap();
+        mem.store("c", "three", MemoryCategory::Core).await.unwrap();
+
+        let core: Vec<String> = mem
+            .list(Some(&MemoryCategory::Core))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(core, vec!["c", "a"]);
+        assert_eq!(mem.list(None).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn recall_ranks_by_keyword_overlap_and_filters_tags() {
+        let mem = InMemoryMemory::new();
+        mem.store_with_tags(
+            "a",
+            "Rust is fast and safe",
+            MemoryCategory::Core,
+            &["Lang".into()],
+        )
+        .await
+        .unwrap();
+        mem.store("b", "Rust is fun", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("c", "Python", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let hits = mem.recall("rust safe", 10).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].key, "a");
+        assert!(hits[0].score.unwrap() > hits[1].score.unwrap());
+
+        let tagged = mem.recall_tagged("rust", "lang", 10).await.unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].tags, vec!["lang"]);
+    }
+
+    #[tokio::test]
+    async fn importance_outranks_recency_and_survives_restore() {
+        let mem = InMemoryMemory::new();
+        mem.store_with_importance("critical", "deploy key rotation", MemoryCategory::Core, 1.0)
+            .await
+            .unwrap();
+        mem.store("trivial", "deploy went fine", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+
+        let hits = mem.recall("deploy", 10).await.unwrap();
+        assert_eq!(hits[0].key, "critical");
+        assert!((hits[0].importance - 1.0).abs() < f64::EPSILON);
+
+        // Re-storing keeps the importance; setting clamps it
+        mem.store("critical", "deploy key rotated", MemoryCategory::Core)
+            .await
+            .unwrap();
+        assert!(
+            (mem.get("critical").await.unwrap().unwrap().importance - 1.0).abs() < f64::EPSILON
+        );
+        assert!(mem.set_importance("trivial", -3.0).await.unwrap());
+        assert!(mem.get("trivial").await.unwrap().unwrap().importance.abs() < f64::EPSILON);
+        assert!(!mem.set_importance("missing", 0.9).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn get_many_skips_missing_and_trashed_keys() {
+        let mem = InMemoryMemory::new();
+        for key in ["user_name", "user_tz", "old"] {
+            mem.store(key, key, MemoryCategory::Core).await.unwrap();
+        }
+        mem.forget("old").await.unwrap();
+        let entries = mem
+            .get_many(&["user_tz", "old", "missing", "user_name"])
+            .await
+            .unwrap();
+        let keys: Vec<_> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["user_tz", "user_name"]);
+        assert_eq!(entries[0].access_count, 1);
+    }
+
+    #[tokio::test]
+    async fn apply_batch_stores_and_trashes_in_order() {
+        let mem = InMemoryMemory::new();
+        mem.store("turn", "hello", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        let ops = [
+            MemoryOp::Store {
+                key: "summary".into(),
+                content: "user said hello".into(),
+                category: MemoryCategory::Core,
+                tags: Vec::new(),
+            },
+            MemoryOp::Forget { key: "turn".into() },
+            MemoryOp::Forget {
+                key: "missing".into(),
+            },
+        ];
+        assert_eq!(mem.apply_batch(&ops).await.unwrap(), 2);
+        assert!(mem.get("turn").await.unwrap().is_none());
+        assert_eq!(mem.list_trash().await.unwrap().len(), 1);
+        assert_eq!(mem.count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn reads_are_counted_and_kept_on_overwrite() {
+        let mem = InMemoryMemory::new();
+        mem.store("tz", "timezone UTC", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.get("tz").await.unwrap();
+        assert_eq!(mem.recall("timezone", 10).await.unwrap()[0].access_count, 2);
+        assert_eq!(mem.list(None).await.unwrap()[0].access_count, 2);
+
+        mem.store("tz", "timezone CET", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let entry = mem.get("tz").await.unwrap().unwrap();
+        assert_eq!(entry.access_count, 3);
+        assert!(entry.last_accessed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn recall_matching_regex_and_prefix() {
+        let mem = InMemoryMemory::new();
+        mem.store("slack_user_1", "alice", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("slack_team", "eng", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let hits = mem
+            .recall_matching("^slack_user_.*", MatchMode::Regex, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, "slack_user_1");
+        let hits = mem
+            .recall_matching("slack_", MatchMode::Prefix, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(mem
+            .recall_matching("Alice", MatchMode::Exact, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn namespaces_stay_out_of_unscoped_recall() {
+        let mem = InMemoryMemory::new();
+        mem.store_in_namespace("alice", "pref", "likes tea", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store_in_namespace("bob", "pref", "likes coffee", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let hits = mem.recall_in_namespace("alice", "likes", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, "ns/alice/pref");
+        let listed = mem.list_in_namespace("bob", None).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].content, "likes coffee");
+
+        // Unscoped callers (agent loop, channels, tools) see neither
+        assert!(mem.recall("likes", 10).await.unwrap().is_empty());
+        assert!(mem.list(None).await.unwrap().is_empty());
+        assert!(mem
+            .recall_matching("likes", MatchMode::Exact, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(mem.list_all(None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn update_patches_live_entries_only() {
+        let mem = InMemoryMemory::new();
+        let patch = MemoryPatch {
+            content: Some("dark mode".into()),
+            ..MemoryPatch::default()
+        };
+        assert!(mem.update("theme", &patch).await.is_err());
+
+        mem.store_in_session("s1", "theme", "light mode", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let before = mem.get("theme").await.unwrap().unwrap();
+        let after = mem.update("theme", &patch).await.unwrap();
+        assert_eq!(after.content, "dark mode");
+        assert_eq!(after.timestamp, before.timestamp);
+        assert_eq!(after.session_id.as_deref(), Some("s1"));
+
+        mem.forget("theme").await.unwrap();
+        assert!(mem.update("theme", &patch).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn sessions_and_ttl() {
+        let mem = InMemoryMemory::new();
+        for i in 0..3 {
+            mem.store_in_session(
+                "s1",
+                &format!("turn_{i}"),
+                "hi",
+                MemoryCategory::Conversation,
+            )
+            .await
+            .unwrap();
+        }
+        let turns: Vec<String> = mem
+            .recall_session("s1", 2)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(turns, vec!["turn_1", "turn_2"]);
+
+        mem.store_with_ttl("tmp", "gone soon", MemoryCategory::Core, Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(mem.get("tmp").await.unwrap().is_none());
+        assert_eq!(mem.purge_expired().await.unwrap(), 1);
+        assert_eq!(mem.count().await.unwrap(), 3);
+    }
+}
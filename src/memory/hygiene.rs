@@ -306,6 +306,9 @@ fn prune_conversation_rows(workspace_dir: &Path, retention_days: u32) -> Result<
     }
 
     let conn = Connection::open(db_path)?;
+    conn.busy_timeout(StdDuration::from_millis(
+        super::sqlite::DEFAULT_BUSY_TIMEOUT_MS,
+    ))?;
     let cutoff = (Local::now() - Duration::days(i64::from(retention_days))).to_rfc3339();
 
     let affected = conn.execute(
@@ -1,163 +1,406 @@
-use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::ranking::{clamp_importance, RecallRanking, DEFAULT_IMPORTANCE};
+use super::traits::{
+    distinct_keys, is_namespaced, namespace_prefix, normalize_tags, Memory, MemoryCategory,
+    MemoryEntry, MemoryOp,
+};
 use async_trait::async_trait;
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use std::collections::HashMap;
+use std::fmt::Write;
 use std::path::{Path, PathBuf};
 use tokio::fs;
+use tokio::sync::{Mutex, OnceCell};
 
 /// Markdown-based memory — plain files as source of truth
 ///
-/// Layout:
-///   workspace/MEMORY.md          — curated long-term memory (core)
-///   workspace/memory/YYYY-MM-DD.md — daily logs (append-only)
+/// Layout: one file per category, `workspace/memory/<category>.md`, holding
+/// a sequence of entries, each a YAML frontmatter block followed by the
+/// content:
+///
+/// ```text
+/// ---
+/// id: "…"
+/// key: "pref"
+/// category: "core"
+/// created_at: "2026-01-01T09:00:00+01:00"
+/// updated_at: "2026-01-01T09:00:00+01:00"
+/// tags: ["lang"]
+/// importance: 0.9
+/// ---
+/// User likes Rust
+/// ```
+///
+/// Keys are unique across categories, like the `SQLite` backend.
+///
+/// Workspaces from before this layout — curated `MEMORY.md` plus dated
+/// `memory/YYYY-MM-DD.md` logs, one entry per line — are imported on first
+/// open; see [`MarkdownMemory::import_legacy`].
 pub struct MarkdownMemory {
     workspace_dir: PathBuf,
+    /// Serializes read-modify-write cycles on the category files
+    write_lock: Mutex<()>,
+    /// Set once the legacy layout has been checked for
+    legacy_checked: OnceCell<()>,
+    ranking: RecallRanking,
+}
+
+/// An entry as stored on disk; `updated_at` orders listings.
+#[derive(Debug, Clone)]
+struct StoredEntry {
+    entry: MemoryEntry,
+    updated_at: String,
 }
 
+/// A category file loaded for rewriting.
+struct CategoryFile {
+    path: PathBuf,
+    entries: Vec<StoredEntry>,
+    dirty: bool,
+}
+
+const DELIMITER: &str = "---";
+
+/// Written to `memory/` once the legacy layout has been imported
+const LEGACY_MARKER: &str = ".legacy-imported";
+
 impl MarkdownMemory {
     pub fn new(workspace_dir: &Path) -> Self {
         Self {
             workspace_dir: workspace_dir.to_path_buf(),
+            write_lock: Mutex::new(()),
+            legacy_checked: OnceCell::new(),
+            ranking: RecallRanking::default(),
         }
     }
 
+    /// Halve the recency boost of recall hits every `days` (0 disables).
+    #[must_use]
+    pub fn with_recency_half_life_days(mut self, days: u32) -> Self {
+        self.ranking = RecallRanking::new(days);
+        self
+    }
+
     fn memory_dir(&self) -> PathBuf {
         self.workspace_dir.join("memory")
     }
 
-    fn core_path(&self) -> PathBuf {
-        self.workspace_dir.join("MEMORY.md")
+    /// `memory/<category>.md`, with anything but `[A-Za-z0-9_-]` in custom
+    /// category names replaced so they can't escape the directory.
+    fn path_for(&self, category: &MemoryCategory) -> PathBuf {
+        let stem: String = category
+            .to_string()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.memory_dir().join(format!("{stem}.md"))
     }
 
-    fn daily_path(&self) -> PathBuf {
-        let date = Local::now().format("%Y-%m-%d").to_string();
-        self.memory_dir().join(format!("{date}.md"))
+    /// Content lines that look like a delimiter get one more leading
+    /// backslash so they can't end the entry.
+    fn escape_line(line: &str) -> String {
+        if line.trim_start_matches('\\') == DELIMITER {
+            format!("\\{line}")
+        } else {
+            line.to_string()
+        }
     }
 
-    async fn ensure_dirs(&self) -> anyhow::Result<()> {
-        fs::create_dir_all(self.memory_dir()).await?;
-        Ok(())
+    fn unescape_line(line: &str) -> &str {
+        match line.strip_prefix('\\') {
+            Some(rest) if rest.trim_start_matches('\\') == DELIMITER => rest,
+            _ => line,
+        }
     }
 
-    async fn append_to_file(&self, path: &Path, content: &str) -> anyhow::Result<()> {
-        self.ensure_dirs().await?;
+    fn render(entries: &[StoredEntry]) -> String {
+        let mut out = String::new();
+        for (i, stored) in entries.iter().enumerate() {
+            let entry = &stored.entry;
+            if i > 0 {
+                out.push('\n');
+            }
+            // JSON strings and arrays are valid YAML flow scalars, and
+            // round-trip any content exactly
+            let quote = |value: &str| serde_json::to_string(value).unwrap_or_default();
+            let _ = writeln!(out, "{DELIMITER}");
+            let _ = writeln!(out, "id: {}", quote(&entry.id));
+            let _ = writeln!(out, "key: {}", quote(&entry.key));
+            let _ = writeln!(out, "category: {}", quote(&entry.category.to_string()));
+            let _ = writeln!(out, "created_at: {}", quote(&entry.timestamp));
+            let _ = writeln!(out, "updated_at: {}", quote(&stored.updated_at));
+            if !entry.tags.is_empty() {
+                let tags = serde_json::to_string(&entry.tags).unwrap_or_default();
+                let _ = writeln!(out, "tags: {tags}");
+            }
+            if (entry.importance - DEFAULT_IMPORTANCE).abs() > f64::EPSILON {
+                let _ = writeln!(out, "importance: {}", entry.importance);
+            }
+            let _ = writeln!(out, "{DELIMITER}");
+            for line in entry.content.split('\n') {
+                let _ = writeln!(out, "{}", Self::escape_line(line));
+            }
+        }
+        out
+    }
 
-        let existing = if path.exists() {
-            fs::read_to_string(path).await.unwrap_or_default()
-        } else {
-            String::new()
-        };
+    /// Parse a category file. Blocks without a `key` are skipped; anything
+    /// before the first delimiter (e.g. a hand-written heading) is ignored.
+    fn parse(text: &str) -> Vec<StoredEntry> {
+        let mut entries = Vec::new();
+        let mut lines = text.split('\n').peekable();
+        while lines.peek().is_some_and(|l| *l != DELIMITER) {
+            lines.next();
+        }
+
+        while lines.next().is_some() {
+            let mut fields = std::collections::HashMap::new();
+            for line in lines.by_ref() {
+                if line == DELIMITER {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    fields.insert(name.trim().to_string(), value.trim().to_string());
+                }
+            }
+
+            let mut body = Vec::new();
+            while let Some(line) = lines.next_if(|l| *l != DELIMITER) {
+                body.push(Self::unescape_line(line));
+            }
+            let content = body.join("\n");
+            // Every block ends with one newline that belongs to the layout
+            let content = content.strip_suffix('\n').unwrap_or(&content);
 
-        let updated = if existing.is_empty() {
-            let header = if path == self.core_path() {
-                "# Long-Term Memory\n\n"
-            } else {
-                let date = Local::now().format("%Y-%m-%d").to_string();
-                &format!("# Daily Log — {date}\n\n")
+            let text_field =
+                |name: &str| -> Option<String> { serde_json::from_str(fields.get(name)?).ok() };
+            let Some(key) = text_field("key") else {
+                continue;
             };
-            format!("{header}{content}\n")
-        } else {
-            format!("{existing}\n{content}\n")
-        };
+            let timestamp = text_field("created_at").unwrap_or_default();
+            let updated_at = text_field("updated_at").unwrap_or_else(|| timestamp.clone());
+            entries.push(StoredEntry {
+                entry: MemoryEntry {
+                    id: text_field("id").unwrap_or_else(|| key.clone()),
+                    category: MemoryCategory::from(
+                        text_field("category").as_deref().unwrap_or("core"),
+                    ),
+                    key,
+                    content: content.to_string(),
+                    timestamp,
+                    updated_at: Some(updated_at.clone()),
+                    session_id: None,
+                    tags: fields
+                        .get("tags")
+                        .and_then(|t| serde_json::from_str(t).ok())
+                        .unwrap_or_default(),
+                    importance: fields
+                        .get("importance")
+                        .and_then(|i| i.parse().ok())
+                        .map_or(DEFAULT_IMPORTANCE, clamp_importance),
+                    last_accessed_at: None,
+                    access_count: 0,
+                    score: None,
+                },
+                updated_at,
+            });
+        }
+        entries
+    }
 
-        fs::write(path, updated).await?;
-        Ok(())
+    /// Load every category file, importing the legacy layout first if this
+    /// workspace still has one.
+    async fn load_files(&self) -> anyhow::Result<Vec<CategoryFile>> {
+        self.legacy_checked
+            .get_or_try_init(|| self.import_legacy())
+            .await?;
+        self.read_category_files().await
     }
 
-    fn parse_entries_from_file(
-        path: &Path,
-        content: &str,
-        category: &MemoryCategory,
-    ) -> Vec<MemoryEntry> {
-        let filename = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown");
+    /// Every category file: `memory/*.md` that starts with a frontmatter
+    /// block. Legacy daily logs and other notes are skipped.
+    async fn read_category_files(&self) -> anyhow::Result<Vec<CategoryFile>> {
+        let mut files = Vec::new();
+        let mem_dir = self.memory_dir();
+        if !mem_dir.is_dir() {
+            return Ok(files);
+        }
 
-        content
-            .lines()
-            .filter(|line| {
-                let trimmed = line.trim();
-                !trimmed.is_empty() && !trimmed.starts_with('#')
-            })
-            .enumerate()
-            .map(|(i, line)| {
-                let trimmed = line.trim();
-                let clean = trimmed.strip_prefix("- ").unwrap_or(trimmed);
-                MemoryEntry {
-                    id: format!("{filename}:{i}"),
-                    key: format!("{filename}:{i}"),
-                    content: clean.to_string(),
-                    category: category.clone(),
-                    timestamp: filename.to_string(),
-                    session_id: None,
-                    score: None,
+        let mut dir = fs::read_dir(&mem_dir).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("md") || !path.is_file() {
+                continue;
+            }
+            let text = fs::read_to_string(&path).await?;
+            if !text.starts_with(DELIMITER) {
+                continue;
+            }
+            files.push(CategoryFile {
+                path,
+                entries: Self::parse(&text),
+                dirty: false,
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// Copy entries from the layout this backend used before category
+    /// files into them, once per workspace. Each non-empty, non-heading line
+    /// of `MEMORY.md` becomes a core entry and each line of a
+    /// `memory/YYYY-MM-DD.md` log a daily one, keyed `<file stem>:<n>` as
+    /// they were then. The old files stay in place (`MEMORY.md` is still
+    /// injected into prompts); `memory/.legacy-imported` records the import
+    /// so forgotten entries don't come back.
+    async fn import_legacy(&self) -> anyhow::Result<()> {
+        let marker = self.memory_dir().join(LEGACY_MARKER);
+        if marker.exists() {
+            return Ok(());
+        }
+        let sources = self.legacy_sources().await?;
+        if sources.is_empty() {
+            return Ok(());
+        }
+
+        let mut files = self.read_category_files().await?;
+        let mut imported = 0;
+        for (path, category, stamp) in sources {
+            let text = fs::read_to_string(&path).await?;
+            for (key, content) in Self::parse_legacy(&path, &text) {
+                // Never clobber an entry already stored under the same key
+                let taken = files
+                    .iter()
+                    .any(|f| f.entries.iter().any(|e| e.entry.key == key));
+                if !taken {
+                    self.put(&mut files, &key, &content, &category, &[], &stamp);
+                    imported += 1;
                 }
-            })
-            .collect()
+            }
+        }
+        fs::create_dir_all(self.memory_dir()).await?;
+        Self::save_files(files).await?;
+        fs::write(&marker, format!("{imported} entries imported\n")).await?;
+        tracing::info!("Imported {imported} legacy markdown memories");
+        Ok(())
     }
 
-    async fn read_all_entries(&self) -> anyhow::Result<Vec<MemoryEntry>> {
-        let mut entries = Vec::new();
+    /// Legacy files still in the old line-per-entry format, with the
+    /// category and timestamp their entries get: `MEMORY.md` (core, as of
+    /// its last change) and dated logs (daily, as of that day).
+    async fn legacy_sources(&self) -> anyhow::Result<Vec<(PathBuf, MemoryCategory, String)>> {
+        let is_legacy = |text: &str| !text.starts_with(DELIMITER);
+        let mut sources = Vec::new();
 
-        // Read MEMORY.md (core)
-        let core_path = self.core_path();
-        if core_path.exists() {
-            let content = fs::read_to_string(&core_path).await?;
-            entries.extend(Self::parse_entries_from_file(
-                &core_path,
-                &content,
-                &MemoryCategory::Core,
-            ));
+        let core = self.workspace_dir.join("MEMORY.md");
+        if core.is_file() && is_legacy(&fs::read_to_string(&core).await?) {
+            let changed = fs::metadata(&core).await?.modified()?;
+            let stamp = DateTime::<Local>::from(changed).to_rfc3339();
+            sources.push((core, MemoryCategory::Core, stamp));
         }
 
-        // Read daily logs
         let mem_dir = self.memory_dir();
-        if mem_dir.exists() {
+        if mem_dir.is_dir() {
+            let mut logs = Vec::new();
             let mut dir = fs::read_dir(&mem_dir).await?;
             while let Some(entry) = dir.next_entry().await? {
                 let path = entry.path();
-                if path.extension().and_then(|e| e.to_str()) == Some("md") {
-                    let content = fs::read_to_string(&path).await?;
-                    entries.extend(Self::parse_entries_from_file(
-                        &path,
-                        &content,
-                        &MemoryCategory::Daily,
-                    ));
+                if path.extension().and_then(|e| e.to_str()) != Some("md") || !path.is_file() {
+                    continue;
+                }
+                let Some(day) = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+                else {
+                    continue;
+                };
+                if is_legacy(&fs::read_to_string(&path).await?) {
+                    let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+                    let stamp = Local
+                        .from_local_datetime(&midnight)
+                        .earliest()
+                        .map_or_else(|| Local::now().to_rfc3339(), |at| at.to_rfc3339());
+                    logs.push((path, MemoryCategory::Daily, stamp));
                 }
             }
+            logs.sort_by(|a, b| a.0.cmp(&b.0));
+            sources.extend(logs);
         }
+        Ok(sources)
+    }
 
-        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        Ok(entries)
+    /// `(key, content)` of each entry in a legacy file: every line that is
+    /// neither blank nor a heading, without its list marker.
+    fn parse_legacy(path: &Path, text: &str) -> Vec<(String, String)> {
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown");
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .enumerate()
+            .map(|(i, line)| {
+                let content = line.strip_prefix("- ").unwrap_or(line);
+                (format!("{stem}:{i}"), content.to_string())
+            })
+            .collect()
     }
-}
 
-#[async_trait]
-impl Memory for MarkdownMemory {
-    fn name(&self) -> &str {
-        "markdown"
+    /// Rewrite changed files; a category with no entries left is removed.
+    ///
+    /// Every new file is written next to its target first and only swapped
+    /// in once all of them are on disk, so a failed write leaves the old
+    /// files untouched.
+    async fn save_files(files: Vec<CategoryFile>) -> anyhow::Result<()> {
+        let dirty: Vec<CategoryFile> = files.into_iter().filter(|f| f.dirty).collect();
+        let mut staged = Vec::new();
+        for file in dirty.iter().filter(|f| !f.entries.is_empty()) {
+            let tmp = file.path.with_extension("md.tmp");
+            let written = fs::write(&tmp, Self::render(&file.entries)).await;
+            staged.push((tmp, &file.path));
+            if let Err(e) = written {
+                for (tmp, _) in &staged {
+                    let _ = fs::remove_file(tmp).await;
+                }
+                return Err(e.into());
+            }
+        }
+
+        for (tmp, path) in staged {
+            fs::rename(&tmp, path).await?;
+        }
+        for file in dirty.iter().filter(|f| f.entries.is_empty()) {
+            if file.path.exists() {
+                fs::remove_file(&file.path).await?;
+            }
+        }
+        Ok(())
     }
 
-    async fn store(
-        &self,
-        key: &str,
-        content: &str,
-        category: MemoryCategory,
-    ) -> anyhow::Result<()> {
-        let entry = format!("- **{key}**: {content}");
-        let path = match category {
-            MemoryCategory::Core => self.core_path(),
-            _ => self.daily_path(),
-        };
-        self.append_to_file(&path, &entry).await
+    async fn read_all_entries(&self) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mut stored: Vec<StoredEntry> = self
+            .load_files()
+            .await?
+            .into_iter()
+            .flat_map(|f| f.entries)
+            .collect();
+        stored.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(stored.into_iter().map(|s| s.entry).collect())
     }
 
-    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
-        let all = self.read_all_entries().await?;
+    /// Entries among `entries` matching `query`'s keywords, best first.
+    fn rank(&self, entries: Vec<MemoryEntry>, query: &str, limit: usize) -> Vec<MemoryEntry> {
         let query_lower = query.to_lowercase();
         let keywords: Vec<&str> = query_lower.split_whitespace().collect();
 
-        let mut scored: Vec<MemoryEntry> = all
+        let mut scored: Vec<MemoryEntry> = entries
             .into_iter()
             .filter_map(|mut entry| {
                 let content_lower = entry.content.to_lowercase();
@@ -176,23 +419,215 @@ impl Memory for MarkdownMemory {
             })
             .collect();
 
-        scored.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        self.ranking.rerank(&mut scored);
         scored.truncate(limit);
-        Ok(scored)
+        scored
+    }
+
+    /// Insert or replace entries by key. A replaced entry keeps its id and
+    /// creation time, and moves file if its category changed.
+    async fn upsert(
+        &self,
+        rows: &[(String, String, MemoryCategory, Vec<String>)],
+    ) -> anyhow::Result<()> {
+        let _guard = self.write_lock.lock().await;
+        fs::create_dir_all(self.memory_dir()).await?;
+        let mut files = self.load_files().await?;
+        let now = Local::now().to_rfc3339();
+
+        for (key, content, category, tags) in rows {
+            self.put(&mut files, key, content, category, tags, &now);
+        }
+
+        Self::save_files(files).await
+    }
+
+    /// Remove `key` from whichever loaded file holds it.
+    fn take(files: &mut [CategoryFile], key: &str) -> Option<MemoryEntry> {
+        let mut previous = None;
+        for file in files {
+            if let Some(pos) = file.entries.iter().position(|e| e.entry.key == key) {
+                previous = Some(file.entries.remove(pos).entry);
+                file.dirty = true;
+            }
+        }
+        previous
+    }
+
+    /// Insert or replace `key` in the loaded files.
+    fn put(
+        &self,
+        files: &mut Vec<CategoryFile>,
+        key: &str,
+        content: &str,
+        category: &MemoryCategory,
+        tags: &[String],
+        now: &str,
+    ) {
+        let previous = Self::take(files, key);
+
+        let path = self.path_for(category);
+        let index = if let Some(i) = files.iter().position(|f| f.path == path) {
+            i
+        } else {
+            files.push(CategoryFile {
+                path,
+                entries: Vec::new(),
+                dirty: true,
+            });
+            files.len() - 1
+        };
+        let (id, timestamp, importance) = previous.map_or_else(
+            || {
+                (
+                    uuid::Uuid::new_v4().to_string(),
+                    now.to_string(),
+                    DEFAULT_IMPORTANCE,
+                )
+            },
+            |e| (e.id, e.timestamp, e.importance),
+        );
+        let file = &mut files[index];
+        file.entries.push(StoredEntry {
+            entry: MemoryEntry {
+                id,
+                key: key.to_string(),
+                content: content.to_string(),
+                category: category.clone(),
+                timestamp,
+                updated_at: Some(now.to_string()),
+                session_id: None,
+                tags: normalize_tags(tags),
+                importance,
+                last_accessed_at: None,
+                access_count: 0,
+                score: None,
+            },
+            updated_at: now.to_string(),
+        });
+        file.dirty = true;
+    }
+}
+
+#[async_trait]
+impl Memory for MarkdownMemory {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+
+    async fn store(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+    ) -> anyhow::Result<()> {
+        self.upsert(&[(key.to_string(), content.to_string(), category, Vec::new())])
+            .await
+    }
+
+    async fn store_many(&self, entries: &[(String, String, MemoryCategory)]) -> anyhow::Result<()> {
+        // One load and one write per touched file for the whole batch
+        let rows: Vec<_> = entries
+            .iter()
+            .map(|(key, content, category)| {
+                (key.clone(), content.clone(), category.clone(), Vec::new())
+            })
+            .collect();
+        self.upsert(&rows).await
+    }
+
+    async fn store_with_tags(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        tags: &[String],
+    ) -> anyhow::Result<()> {
+        self.upsert(&[(
+            key.to_string(),
+            content.to_string(),
+            category,
+            tags.to_vec(),
+        )])
+        .await
+    }
+
+    async fn apply_batch(&self, ops: &[MemoryOp]) -> anyhow::Result<usize> {
+        // Apply to the loaded files, then swap them all in at once
+        let _guard = self.write_lock.lock().await;
+        fs::create_dir_all(self.memory_dir()).await?;
+        let mut files = self.load_files().await?;
+        let now = Local::now().to_rfc3339();
+        let mut applied = 0;
+        for op in ops {
+            match op {
+                MemoryOp::Store {
+                    key,
+                    content,
+                    category,
+                    tags,
+                } => {
+                    self.put(&mut files, key, content, category, tags, &now);
+                    applied += 1;
+                }
+                MemoryOp::Forget { key } => {
+                    if Self::take(&mut files, key).is_some() {
+                        applied += 1;
+                    }
+                }
+            }
+        }
+        Self::save_files(files).await?;
+        Ok(applied)
+    }
+
+    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mut entries = self.read_all_entries().await?;
+        entries.retain(|e| !is_namespaced(&e.key));
+        Ok(self.rank(entries, query, limit))
+    }
+
+    async fn recall_in_namespace(
+        &self,
+        namespace: &str,
+        query: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let prefix = namespace_prefix(namespace)?;
+        let mut entries = self.read_all_entries().await?;
+        entries.retain(|e| e.key.starts_with(&prefix));
+        Ok(self.rank(entries, query, limit))
     }
 
     async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
         let all = self.read_all_entries().await?;
-        Ok(all
+        Ok(all.into_iter().find(|e| e.key == key))
+    }
+
+    async fn get_many(&self, keys: &[&str]) -> anyhow::Result<Vec<MemoryEntry>> {
+        // One pass over the files for the whole set
+        let mut all: HashMap<String, MemoryEntry> = self
+            .read_all_entries()
+            .await?
+            .into_iter()
+            .map(|e| (e.key.clone(), e))
+            .collect();
+        Ok(distinct_keys(keys)
             .into_iter()
-            .find(|e| e.key == key || e.content.contains(key)))
+            .filter_map(|key| all.remove(key))
+            .collect())
     }
 
     async fn list(&self, category: Option<&MemoryCategory>) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mut entries = self.list_all(category).await?;
+        entries.retain(|e| !is_namespaced(&e.key));
+        Ok(entries)
+    }
+
+    async fn list_all(
+        &self,
+        category: Option<&MemoryCategory>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
         let all = self.read_all_entries().await?;
         match category {
             Some(cat) => Ok(all.into_iter().filter(|e| &e.category == cat).collect()),
@@ -200,10 +635,27 @@ impl Memory for MarkdownMemory {
         }
     }
 
-    async fn forget(&self, _key: &str) -> anyhow::Result<bool> {
-        // Markdown memory is append-only by design (audit trail)
-        // Return false to indicate the entry wasn't removed
-        Ok(false)
+    async fn forget(&self, key: &str) -> anyhow::Result<bool> {
+        let _guard = self.write_lock.lock().await;
+        let mut files = self.load_files().await?;
+        let removed = Self::take(&mut files, key).is_some();
+        Self::save_files(files).await?;
+        Ok(removed)
+    }
+
+    async fn set_importance(&self, key: &str, importance: f64) -> anyhow::Result<bool> {
+        let _guard = self.write_lock.lock().await;
+        let mut files = self.load_files().await?;
+        let mut found = false;
+        for file in &mut files {
+            for stored in file.entries.iter_mut().filter(|e| e.entry.key == key) {
+                stored.entry.importance = clamp_importance(importance);
+                file.dirty = true;
+                found = true;
+            }
+        }
+        Self::save_files(files).await?;
+        Ok(found)
     }
 
     async fn count(&self) -> anyhow::Result<usize> {
@@ -246,8 +698,27 @@ mod tests {
         mem.store("pref", "User likes Rust", MemoryCategory::Core)
             .await
             .unwrap();
-        let content = sync_fs::read_to_string(mem.core_path()).unwrap();
-        assert!(content.contains("User likes Rust"));
+        let content = sync_fs::read_to_string(mem.path_for(&MemoryCategory::Core)).unwrap();
+        assert!(content.starts_with("---\nid: "));
+        assert!(content.contains("key: \"pref\"\ncategory: \"core\""));
+        assert!(content.contains("---\nUser likes Rust\n"));
+    }
+
+    #[tokio::test]
+    async fn markdown_store_many_groups_by_file() {
+        let (_tmp, mem) = temp_workspace();
+        mem.store_many(&[
+            ("a".into(), "fact one".into(), MemoryCategory::Core),
+            ("b".into(), "note one".into(), MemoryCategory::Daily),
+            ("c".into(), "fact two".into(), MemoryCategory::Core),
+        ])
+        .await
+        .unwrap();
+        let core = sync_fs::read_to_string(mem.path_for(&MemoryCategory::Core)).unwrap();
+        assert!(core.contains("fact one") && core.contains("fact two"));
+        let daily = sync_fs::read_to_string(mem.path_for(&MemoryCategory::Daily)).unwrap();
+        assert!(daily.contains("note one"));
+        assert_eq!(mem.count().await.unwrap(), 3);
     }
 
     #[tokio::test]
@@ -256,7 +727,7 @@ mod tests {
         mem.store("note", "Finished tests", MemoryCategory::Daily)
             .await
             .unwrap();
-        let path = mem.daily_path();
+        let path = mem.path_for(&MemoryCategory::Daily);
         let content = sync_fs::read_to_string(path).unwrap();
         assert!(content.contains("Finished tests"));
     }
@@ -320,13 +791,270 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn markdown_forget_is_noop() {
+    async fn markdown_forget_removes_entry() {
         let (_tmp, mem) = temp_workspace();
-        mem.store("a", "permanent", MemoryCategory::Core)
+        mem.store("a", "first", MemoryCategory::Core).await.unwrap();
+        mem.store("b", "second", MemoryCategory::Core)
             .await
             .unwrap();
-        let removed = mem.forget("a").await.unwrap();
-        assert!(!removed, "Markdown memory is append-only");
+        assert!(mem.forget("a").await.unwrap());
+        assert!(!mem.forget("a").await.unwrap());
+        assert!(mem.get("a").await.unwrap().is_none());
+        assert_eq!(mem.count().await.unwrap(), 1);
+
+        // Last entry gone → category file removed
+        assert!(mem.forget("b").await.unwrap());
+        assert!(!mem.path_for(&MemoryCategory::Core).exists());
+    }
+
+    #[tokio::test]
+    async fn markdown_batch_swaps_in_all_files_or_none() {
+        let (tmp, mem) = temp_workspace();
+        mem.store("turn", "hello", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        let ops = [
+            MemoryOp::Store {
+                key: "summary".into(),
+                content: "user said hello".into(),
+                category: MemoryCategory::Core,
+                tags: Vec::new(),
+            },
+            MemoryOp::Forget { key: "turn".into() },
+        ];
+
+        // The core file can't be staged, so the forget must not land either
+        let blocker = tmp.path().join("memory").join("core.md.tmp");
+        sync_fs::create_dir(&blocker).unwrap();
+        assert!(mem.apply_batch(&ops).await.is_err());
+        assert!(mem.get("turn").await.unwrap().is_some());
+        assert!(mem.get("summary").await.unwrap().is_none());
+        assert!(!tmp
+            .path()
+            .join("memory")
+            .join("conversation.md.tmp")
+            .exists());
+
+        sync_fs::remove_dir(&blocker).unwrap();
+        assert_eq!(mem.apply_batch(&ops).await.unwrap(), 2);
+        assert!(mem.get("turn").await.unwrap().is_none());
+        assert_eq!(mem.count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn markdown_get_many_across_categories() {
+        let (_tmp, mem) = temp_workspace();
+        mem.store("user_name", "Ada", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("standup", "notes", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        let entries = mem
+            .get_many(&["standup", "missing", "user_name", "standup"])
+            .await
+            .unwrap();
+        let keys: Vec<_> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["standup", "user_name"]);
+    }
+
+    #[tokio::test]
+    async fn markdown_store_upserts_by_key() {
+        let (_tmp, mem) = temp_workspace();
+        mem.store("pref", "likes tea", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let first = mem.get("pref").await.unwrap().unwrap();
+        mem.store("pref", "likes coffee", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let entry = mem.get("pref").await.unwrap().unwrap();
+        assert_eq!(entry.content, "likes coffee");
+        assert_eq!(entry.id, first.id);
+        assert_eq!(entry.timestamp, first.timestamp);
+        assert_eq!(mem.count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn markdown_update_patches_existing_keys_only() {
+        let (_tmp, mem) = temp_workspace();
+        let patch = crate::memory::MemoryPatch {
+            category: Some(MemoryCategory::Daily),
+            ..Default::default()
+        };
+        assert!(mem.update("pref", &patch).await.is_err());
+        assert!(mem.get("pref").await.unwrap().is_none());
+
+        mem.store_with_tags("pref", "likes tea", MemoryCategory::Core, &["drink".into()])
+            .await
+            .unwrap();
+        let first = mem.get("pref").await.unwrap().unwrap();
+        let entry = mem.update("pref", &patch).await.unwrap();
+        assert_eq!(entry.category, MemoryCategory::Daily);
+        assert_eq!(entry.content, "likes tea");
+        assert_eq!(entry.tags, vec!["drink"]);
+        assert_eq!(entry.timestamp, first.timestamp);
+    }
+
+    #[tokio::test]
+    async fn markdown_store_moves_key_between_categories() {
+        let (_tmp, mem) = temp_workspace();
+        mem.store("k", "draft", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        mem.store("k", "kept", MemoryCategory::Custom("projects/x".into()))
+            .await
+            .unwrap();
+
+        assert!(mem
+            .list(Some(&MemoryCategory::Daily))
+            .await
+            .unwrap()
+            .is_empty());
+        let custom = MemoryCategory::Custom("projects/x".into());
+        let entries = mem.list(Some(&custom)).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, "kept");
+        assert!(mem.path_for(&custom).ends_with("memory/projects_x.md"));
+    }
+
+    #[tokio::test]
+    async fn markdown_importance_persists_and_ranks_recall() {
+        let (tmp, mem) = temp_workspace();
+        mem.store("chatter", "the build passed", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store_with_importance("rule", "never build on friday", MemoryCategory::Core, 0.9)
+            .await
+            .unwrap();
+
+        let text = sync_fs::read_to_string(tmp.path().join("memory/core.md")).unwrap();
+        assert!(text.contains("importance: 0.9"));
+
+        let reopened = MarkdownMemory::new(tmp.path());
+        let hits = reopened.recall("build", 10).await.unwrap();
+        assert_eq!(hits[0].key, "rule");
+        assert!((hits[0].importance - 0.9).abs() < f64::EPSILON);
+        assert!((hits[1].importance - DEFAULT_IMPORTANCE).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn markdown_roundtrips_awkward_content_and_tags() {
+        let (_tmp, mem) = temp_workspace();
+        let content = "line one\n---\n\\---\nkey: \"fake\"\n\ntrailing\n";
+        mem.store_with_tags(
+            "weird: key",
+            content,
+            MemoryCategory::Core,
+            &["Lang".into(), "rust".into()],
+        )
+        .await
+        .unwrap();
+        mem.store("next", "after", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let entry = mem.get("weird: key").await.unwrap().unwrap();
+        assert_eq!(entry.content, content);
+        assert_eq!(entry.tags, vec!["lang", "rust"]);
+        assert_eq!(mem.get("next").await.unwrap().unwrap().content, "after");
+        assert_eq!(mem.count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn markdown_list_newest_first() {
+        let (tmp, mem) = temp_workspace();
+        sync_fs::create_dir_all(tmp.path().join("memory")).unwrap();
+        sync_fs::write(
+            tmp.path().join("memory").join("2024-01-01.md"),
+            "# Daily Log — 2024-01-01\n\n- note\n",
+        )
+        .unwrap();
+        mem.store("old", "first", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("new", "second", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let keys: Vec<String> = mem
+            .list(None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        // The dated log predates both and was imported as a daily entry
+        assert_eq!(keys, vec!["new", "old", "2024-01-01:0"]);
+    }
+
+    #[tokio::test]
+    async fn markdown_imports_legacy_layout_on_first_open() {
+        let tmp = TempDir::new().unwrap();
+        let memory_md =
+            "# Long-Term Memory\n\n- **lang**: User likes Rust\n\n- Prefers short answers\n";
+        sync_fs::write(tmp.path().join("MEMORY.md"), memory_md).unwrap();
+        sync_fs::create_dir_all(tmp.path().join("memory")).unwrap();
+        sync_fs::write(
+            tmp.path().join("memory").join("2025-03-04.md"),
+            "# Daily Log — 2025-03-04\n\n- **deploy**: shipped v2\n",
+        )
+        .unwrap();
+        sync_fs::write(tmp.path().join("memory").join("notes.md"), "scratch\n").unwrap();
+
+        let mem = MarkdownMemory::new(tmp.path());
+        assert_eq!(mem.count().await.unwrap(), 3);
+        let lang = mem.get("MEMORY:0").await.unwrap().unwrap();
+        assert_eq!(lang.content, "**lang**: User likes Rust");
+        assert_eq!(lang.category, MemoryCategory::Core);
+        let log = mem.get("2025-03-04:0").await.unwrap().unwrap();
+        assert_eq!(log.category, MemoryCategory::Daily);
+        assert!(log.timestamp.starts_with("2025-03-04T00:00:00"));
+        let hits = mem.recall("shipped", 5).await.unwrap();
+        assert_eq!(hits[0].key, "2025-03-04:0");
+        assert_eq!(
+            mem.list(Some(&MemoryCategory::Core)).await.unwrap().len(),
+            2
+        );
+
+        // The old files stay put, and the import happens once: a forgotten
+        // entry doesn't come back on the next open
+        assert_eq!(
+            sync_fs::read_to_string(tmp.path().join("MEMORY.md")).unwrap(),
+            memory_md
+        );
+        assert!(mem.forget("MEMORY:1").await.unwrap());
+        let reopened = MarkdownMemory::new(tmp.path());
+        assert_eq!(reopened.count().await.unwrap(), 2);
+        assert!(reopened.get("MEMORY:1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn markdown_namespaces_stay_out_of_unscoped_recall() {
+        let (_tmp, mem) = temp_workspace();
+        mem.store_in_namespace("alice", "pref", "likes tea", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("pref", "team likes coffee", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let hits = mem.recall("likes", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, "pref");
+        assert_eq!(mem.list(None).await.unwrap().len(), 1);
+
+        let hits = mem.recall_in_namespace("alice", "likes", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, "ns/alice/pref");
+        assert!(mem
+            .recall_in_namespace("bob", "likes", 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(mem.list_in_namespace("alice", None).await.unwrap().len(), 1);
+        assert_eq!(mem.list_all(None).await.unwrap().len(), 2);
     }
 
     #[tokio::test]
@@ -1,20 +1,36 @@
+pub mod backup;
 pub mod chunker;
+pub mod cipher;
+pub mod cli;
+pub mod compaction;
+pub mod dedup;
 pub mod embeddings;
+pub mod ephemeral;
 pub mod hygiene;
 pub mod markdown;
+pub mod ranking;
+pub mod retention;
 pub mod sqlite;
+pub mod sync;
 pub mod traits;
 pub mod vector;
 
+pub use ephemeral::InMemoryMemory;
 pub use markdown::MarkdownMemory;
-pub use sqlite::SqliteMemory;
+pub use retention::RetentionPolicy;
+pub use sqlite::{HybridFusion, SearchMode, SqliteMemory};
 pub use traits::Memory;
 #[allow(unused_imports)]
-pub use traits::{MemoryCategory, MemoryEntry};
+pub use traits::{
+    expand_related, namespaced_key, normalize_relation, normalize_tags, parse_time_bound,
+    DateField, DateRange, EmbeddingCacheStats, MatchMode, MemoryCategory, MemoryEntry, MemoryOp,
+    MemoryPatch, MemoryRelation, TrashedEntry, DEFAULT_RELATION,
+};
 
 use crate::config::MemoryConfig;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Factory: create the right memory backend from config
 pub fn create_memory(
@@ -22,6 +38,13 @@ pub fn create_memory(
     workspace_dir: &Path,
     api_key: Option<&str>,
 ) -> anyhow::Result<Box<dyn Memory>> {
+    // Nothing on disk to clean up, and hygiene would write a state file
+    if config.backend == "ephemeral" {
+        return Ok(Box::new(
+            InMemoryMemory::new().with_recency_half_life_days(config.recency_half_life_days),
+        ));
+    }
+
     // Best-effort memory hygiene/retention pass (throttled by state file).
     if let Err(e) = hygiene::run_if_due(config, workspace_dir) {
         tracing::warn!("memory hygiene skipped: {e}");
@@ -32,11 +55,29 @@ pub fn create_memory(
             let embedder: Arc<dyn embeddings::EmbeddingProvider> =
                 Arc::from(embeddings::create_embedding_provider(
                     &config.embedding_provider,
-                    api_key,
+                    config.embedding_api_key.as_deref().or(api_key),
                     &config.embedding_model,
                     config.embedding_dimensions,
                 ));
 
+            let search_mode = SearchMode::from_config(&config.search_mode);
+            if search_mode == SearchMode::Semantic && embedder.dimensions() == 0 {
+                tracing::warn!(
+                    "memory.search_mode = \"semantic\" needs an embedding_provider; \
+                     falling back to keyword recall"
+                );
+            }
+            // Sealed content never reaches the FTS5 index, so only vector
+            // recall can find it by meaning
+            if config.encrypt && (search_mode != SearchMode::Semantic || embedder.dimensions() == 0)
+            {
+                anyhow::bail!(
+                    "memory.encrypt keeps content out of the keyword index, so keyword recall \
+                     can't match it: set memory.search_mode = \"semantic\" with an \
+                     embedding_provider, or turn encryption off"
+                );
+            }
+
             #[allow(clippy::cast_possible_truncation)]
             let mem = SqliteMemory::with_embedder(
                 workspace_dir,
@@ -44,15 +85,136 @@ pub fn create_memory(
                 config.vector_weight as f32,
                 config.keyword_weight as f32,
                 config.embedding_cache_size,
-            )?;
+            )?
+            .with_busy_timeout_ms(config.sqlite_busy_timeout_ms)?
+            .with_search_mode(search_mode)
+            .with_hybrid_fusion(HybridFusion::from_config(
+                &config.hybrid_fusion,
+                config.rrf_k,
+            ))
+            .with_conversation_ttl_days(config.conversation_ttl_days)
+            .with_recency_half_life_days(config.recency_half_life_days)
+            .with_max_content_bytes(config.max_entry_bytes);
+            let mem = if config.encrypt {
+                let key = config.encryption_key.as_deref().ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "memory.encrypt needs memory.encryption_key or ZEROCLAW_MEMORY_KEY \
+                         (generate one with `openssl rand -hex 32`)"
+                    )
+                })?;
+                mem.with_cipher(cipher::ContentCipher::from_hex_key(key)?)?
+            } else {
+                mem
+            };
             Ok(Box::new(mem))
         }
-        "markdown" | "none" => Ok(Box::new(MarkdownMemory::new(workspace_dir))),
+        "markdown" | "none" => {
+            if config.encrypt {
+                tracing::warn!("memory.encrypt only applies to the sqlite backend");
+            }
+            Ok(Box::new(
+                MarkdownMemory::new(workspace_dir)
+                    .with_recency_half_life_days(config.recency_half_life_days),
+            ))
+        }
         other => {
             tracing::warn!("Unknown memory backend '{other}', falling back to markdown");
-            Ok(Box::new(MarkdownMemory::new(workspace_dir)))
+            Ok(Box::new(
+                MarkdownMemory::new(workspace_dir)
+                    .with_recency_half_life_days(config.recency_half_life_days),
+            ))
+        }
+    }
+}
+
+/// Periodically purge expired entries and enforce `[memory.retention]` in
+/// the background. Returns `None` when the sweep is disabled
+/// (`interval_secs == 0`).
+pub fn spawn_expiry_sweep(
+    mem: Arc<dyn Memory>,
+    interval_secs: u64,
+    retention: RetentionPolicy,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if interval_secs == 0 {
+        if !retention.is_empty() {
+            tracing::warn!(
+                "memory.retention is set but expiry_sweep_interval_secs = 0; not enforced"
+            );
+        }
+        return None;
+    }
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match mem.purge_expired().await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("memory: purged {n} expired entries"),
+                Err(e) => tracing::warn!("memory expiry sweep failed: {e}"),
+            }
+            match retention.enforce(mem.as_ref()).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("memory: pruned {n} entries past retention"),
+                Err(e) => tracing::warn!("memory retention sweep failed: {e}"),
+            }
+        }
+    }))
+}
+
+/// Memory category for ingested documents (`POST /ingest`, channel
+/// attachments) unless the caller picks another.
+pub const DOCUMENT_CATEGORY: &str = "document";
+
+/// Key-safe document name; falls back to a random id.
+pub fn document_name(name: Option<&str>) -> String {
+    let cleaned: String = name
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .take(64)
+        .collect();
+    if cleaned.trim_matches(['-', '.']).is_empty() {
+        format!("doc-{}", &uuid::Uuid::new_v4().simple().to_string()[..8])
+    } else {
+        cleaned
+    }
+}
+
+/// Store `text` as chunks `doc:<name>:<n>`, dropping chunks left over from
+/// an earlier, longer version of the same document.
+pub async fn ingest_document(
+    mem: &dyn Memory,
+    name: &str,
+    text: &str,
+    category: &MemoryCategory,
+    chunk_max_tokens: usize,
+) -> anyhow::Result<Vec<String>> {
+    let prefix = format!("doc:{name}:");
+    for entry in mem.list(Some(category)).await? {
+        if entry.key.starts_with(&prefix) {
+            mem.forget(&entry.key).await?;
         }
     }
+
+    let chunks: Vec<_> = chunker::chunk_markdown(text, chunk_max_tokens.max(1))
+        .into_iter()
+        .map(|chunk| {
+            (
+                format!("{prefix}{}", chunk.index),
+                chunk.content,
+                category.clone(),
+            )
+        })
+        .collect();
+    mem.store_many(&chunks).await?;
+    Ok(chunks.into_iter().map(|(key, _, _)| key).collect())
 }
 
 #[cfg(test)]
@@ -60,6 +222,13 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn document_name_is_key_safe() {
+        assert_eq!(document_name(Some("My Notes/2026.md")), "My-Notes-2026.md");
+        assert!(document_name(None).starts_with("doc-"));
+        assert!(document_name(Some("///")).starts_with("doc-"));
+    }
+
     #[test]
     fn factory_sqlite() {
         let tmp = TempDir::new().unwrap();
@@ -82,6 +251,18 @@ mod tests {
         assert_eq!(mem.name(), "markdown");
     }
 
+    #[test]
+    fn factory_ephemeral_touches_no_files() {
+        let tmp = TempDir::new().unwrap();
+        let cfg = MemoryConfig {
+            backend: "ephemeral".into(),
+            ..MemoryConfig::default()
+        };
+        let mem = create_memory(&cfg, tmp.path(), None).unwrap();
+        assert_eq!(mem.name(), "ephemeral");
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 0);
+    }
+
     #[test]
     fn factory_none_falls_back_to_markdown() {
         let tmp = TempDir::new().unwrap();
@@ -103,4 +284,45 @@ mod tests {
         let mem = create_memory(&cfg, tmp.path(), None).unwrap();
         assert_eq!(mem.name(), "markdown");
     }
+
+    #[test]
+    fn factory_encrypt_requires_key() {
+        let tmp = TempDir::new().unwrap();
+        let cfg = MemoryConfig {
+            backend: "sqlite".into(),
+            encrypt: true,
+            search_mode: "semantic".into(),
+            embedding_provider: "openai".into(),
+            ..MemoryConfig::default()
+        };
+        let err = create_memory(&cfg, tmp.path(), None).err().unwrap();
+        assert!(err.to_string().contains("ZEROCLAW_MEMORY_KEY"));
+
+        let cfg = MemoryConfig {
+            encryption_key: Some("ab".repeat(32)),
+            ..cfg
+        };
+        assert!(create_memory(&cfg, tmp.path(), None).is_ok());
+    }
+
+    #[test]
+    fn factory_encrypt_refuses_keyword_recall() {
+        let tmp = TempDir::new().unwrap();
+        let cfg = MemoryConfig {
+            backend: "sqlite".into(),
+            encrypt: true,
+            encryption_key: Some("ab".repeat(32)),
+            ..MemoryConfig::default()
+        };
+        let err = create_memory(&cfg, tmp.path(), None).err().unwrap();
+        assert!(err.to_string().contains("search_mode"));
+
+        // Semantic without an embedder still falls back to keywords
+        let cfg = MemoryConfig {
+            search_mode: "semantic".into(),
+            embedding_provider: "none".into(),
+            ..cfg
+        };
+        assert!(create_memory(&cfg, tmp.path(), None).is_err());
+    }
 }
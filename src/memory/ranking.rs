@@ -0,0 +1,154 @@
+// Recall ranking — blends a backend's match relevance with the entry's
+// importance and age, so a critical fact from last month can outrank a
+// trivial line from yesterday.
+
+use super::traits::MemoryEntry;
+use chrono::{DateTime, Utc};
+
+/// Importance of entries stored without one (scale 0.0–1.0).
+pub const DEFAULT_IMPORTANCE: f64 = 0.5;
+
+/// Default age at which an entry's recency boost has halved.
+pub const DEFAULT_HALF_LIFE_DAYS: u32 = 30;
+
+/// Clamp a caller-supplied importance into 0.0–1.0 (NaN → default).
+pub fn clamp_importance(importance: f64) -> f64 {
+    if importance.is_nan() {
+        DEFAULT_IMPORTANCE
+    } else {
+        importance.clamp(0.0, 1.0)
+    }
+}
+
+/// How recall turns match relevance into a final score:
+///
+/// `relevance × (0.5 + importance) × (0.5 + 0.5 × 2^(-age / half_life))`
+///
+/// Importance scales a hit between 0.5× and 1.5×; recency decays from 1×
+/// toward a 0.5× floor, so old memories fade but never vanish.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecallRanking {
+    /// `None` disables recency decay
+    half_life_days: Option<f64>,
+}
+
+impl Default for RecallRanking {
+    fn default() -> Self {
+        Self::new(DEFAULT_HALF_LIFE_DAYS)
+    }
+}
+
+impl RecallRanking {
+    /// `half_life_days = 0` ranks by relevance and importance only.
+    pub fn new(half_life_days: u32) -> Self {
+        Self {
+            half_life_days: (half_life_days > 0).then(|| f64::from(half_life_days)),
+        }
+    }
+
+    /// Final score for `entry` matched with `relevance`.
+    pub fn score(&self, relevance: f64, entry: &MemoryEntry, now: DateTime<Utc>) -> f64 {
+        let importance = clamp_importance(entry.importance);
+        let recency = match (self.half_life_days, entry_age_days(entry, now)) {
+            (Some(half_life), Some(age)) => 0.5 + 0.5 * (-age / half_life).exp2(),
+            _ => 1.0,
+        };
+        relevance * (0.5 + importance) * recency
+    }
+
+    /// Rescore entries whose `score` holds match relevance, then sort best
+    /// first. The sort is stable, so ties keep the backend's order.
+    pub fn rerank(&self, entries: &mut [MemoryEntry]) {
+        let now = Utc::now();
+        for entry in entries.iter_mut() {
+            let relevance = entry.score.unwrap_or(1.0);
+            entry.score = Some(self.score(relevance, entry, now));
+        }
+        entries.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+}
+
+/// Days since the entry was created; `None` if its timestamp won't parse.
+fn entry_age_days(entry: &MemoryEntry, now: DateTime<Utc>) -> Option<f64> {
+    let created = DateTime::parse_from_rfc3339(&entry.timestamp).ok()?;
+    #[allow(clippy::cast_precision_loss)]
+    let seconds = (now - created.with_timezone(&Utc)).num_seconds().max(0) as f64;
+    Some(seconds / 86_400.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryCategory;
+    use chrono::Duration;
+
+    fn entry(age: Duration, importance: f64, category: MemoryCategory) -> MemoryEntry {
+        MemoryEntry {
+            id: "id".into(),
+            key: "k".into(),
+            content: "c".into(),
+            category,
+            timestamp: (Utc::now() - age).to_rfc3339(),
+            updated_at: None,
+            session_id: None,
+            tags: Vec::new(),
+            importance,
+            last_accessed_at: None,
+            access_count: 0,
+            score: None,
+        }
+    }
+
+    #[test]
+    fn important_old_memory_outranks_trivial_recent_one() {
+        let ranking = RecallRanking::default();
+        let now = Utc::now();
+        let critical = entry(Duration::days(30), 1.0, MemoryCategory::Core);
+        let trivial = entry(Duration::days(1), 0.1, MemoryCategory::Conversation);
+        assert!(ranking.score(1.0, &critical, now) > ranking.score(1.0, &trivial, now));
+    }
+
+    #[test]
+    fn recency_decays_to_a_floor_and_can_be_disabled() {
+        let now = Utc::now();
+        let fresh = entry(Duration::zero(), DEFAULT_IMPORTANCE, MemoryCategory::Core);
+        let half = entry(Duration::days(30), DEFAULT_IMPORTANCE, MemoryCategory::Core);
+        let ancient = entry(
+            Duration::days(3650),
+            DEFAULT_IMPORTANCE,
+            MemoryCategory::Core,
+        );
+
+        let ranking = RecallRanking::new(30);
+        assert!((ranking.score(1.0, &fresh, now) - 1.0).abs() < 0.001);
+        assert!((ranking.score(1.0, &half, now) - 0.75).abs() < 0.001);
+        assert!(ranking.score(1.0, &ancient, now) >= 0.5);
+
+        let flat = RecallRanking::new(0);
+        assert!((flat.score(1.0, &ancient, now) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn rerank_sorts_by_final_score() {
+        let mut entries = vec![
+            entry(Duration::days(1), 0.0, MemoryCategory::Conversation),
+            entry(Duration::days(1), 1.0, MemoryCategory::Core),
+        ];
+        entries[0].score = Some(1.0);
+        entries[1].score = Some(0.8);
+        RecallRanking::default().rerank(&mut entries);
+        assert_eq!(entries[0].category, MemoryCategory::Core);
+        assert!(entries[0].score.unwrap() > entries[1].score.unwrap());
+    }
+
+    #[test]
+    fn clamp_importance_bounds_input() {
+        assert!((clamp_importance(2.0) - 1.0).abs() < f64::EPSILON);
+        assert!(clamp_importance(-1.0).abs() < f64::EPSILON);
+        assert!((clamp_importance(f64::NAN) - DEFAULT_IMPORTANCE).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,223 @@
+// Per-category retention — `[memory.retention]` maps a category name to how
+// long its entries are kept ("30d", "12h", "forever"), enforced by the
+// background memory sweep.
+
+use super::traits::{Memory, MemoryCategory};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+
+/// Parsed `[memory.retention]` rules. Categories without a rule (or set to
+/// "forever") are never pruned.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionPolicy {
+    rules: Vec<(MemoryCategory, Duration)>,
+    /// How long forgotten entries stay restorable in the trash
+    trash: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    /// Build the policy from config; fails on an unparseable duration so a
+    /// typo can't silently disable (or widen) pruning.
+    pub fn from_config(retention: &BTreeMap<String, String>) -> anyhow::Result<Self> {
+        let mut rules = Vec::new();
+        for (category, value) in retention {
+            if let Some(max_age) = parse_retention(value)
+                .map_err(|e| anyhow::anyhow!("memory.retention.{category}: {e}"))?
+            {
+                rules.push((MemoryCategory::from(category.as_str()), max_age));
+            }
+        }
+        Ok(Self { rules, trash: None })
+    }
+
+    /// Also purge trashed entries older than `days` (0 = never).
+    pub fn with_trash_purge_after_days(mut self, days: u32) -> Self {
+        self.trash = (days > 0).then(|| Duration::days(i64::from(days)));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.trash.is_none()
+    }
+
+    /// Delete entries older than their category's limit, returning how many
+    /// were removed.
+    pub async fn enforce(&self, mem: &dyn Memory) -> anyhow::Result<usize> {
+        let mut removed = 0;
+        for (category, max_age) in &self.rules {
+            removed += mem.prune_before(category, Utc::now() - *max_age).await?;
+        }
+        if let Some(max_age) = self.trash {
+            removed += mem.purge_trash(Utc::now() - max_age).await?;
+        }
+        Ok(removed)
+    }
+}
+
+/// Keys of entries (optionally only `category`) not read by `get` or recall
+/// since `cutoff`; entries never read count from when they were stored.
+/// Unless `dry_run`, they are moved to the trash.
+pub async fn prune_unused(
+    mem: &dyn Memory,
+    cutoff: DateTime<Utc>,
+    category: Option<&MemoryCategory>,
+    dry_run: bool,
+) -> anyhow::Result<Vec<String>> {
+    if !mem.tracks_access() {
+        anyhow::bail!(
+            "the {} memory backend does not track access; prune needs sqlite or ephemeral",
+            mem.name()
+        );
+    }
+    let mut stale = Vec::new();
+    for entry in mem.list(category).await? {
+        let last_used = entry
+            .last_accessed_at
+            .as_deref()
+            .unwrap_or(&entry.timestamp);
+        let unused =
+            DateTime::parse_from_rfc3339(last_used).is_ok_and(|at| at.with_timezone(&Utc) < cutoff);
+        if unused {
+            stale.push(entry.key);
+        }
+    }
+    if !dry_run {
+        for key in &stale {
+            mem.forget(key).await?;
+        }
+    }
+    Ok(stale)
+}
+
+/// Parse a retention period: `<n>d`, `<n>h`, `<n>m` (days, hours, minutes)
+/// or `forever` / `never` for no limit.
+pub fn parse_retention(value: &str) -> anyhow::Result<Option<Duration>> {
+    let value = value.trim().to_lowercase();
+    if value == "forever" || value == "never" {
+        return Ok(None);
+    }
+
+    let split = value.len().saturating_sub(1);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.trim().parse().map_err(|_| {
+        anyhow::anyhow!(
+            "invalid retention '{value}' (expected e.g. \"30d\", \"12h\" or \"forever\")"
+        )
+    })?;
+    if amount <= 0 {
+        anyhow::bail!("retention must be positive, got '{value}'");
+    }
+    match unit {
+        "d" => Ok(Some(Duration::days(amount))),
+        "h" => Ok(Some(Duration::hours(amount))),
+        "m" => Ok(Some(Duration::minutes(amount))),
+        _ => anyhow::bail!(
+            "invalid retention '{value}' (expected e.g. \"30d\", \"12h\" or \"forever\")"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use tempfile::TempDir;
+
+    fn retention(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_units_and_forever() {
+        assert_eq!(parse_retention("30d").unwrap(), Some(Duration::days(30)));
+        assert_eq!(parse_retention(" 12H ").unwrap(), Some(Duration::hours(12)));
+        assert_eq!(parse_retention("45m").unwrap(), Some(Duration::minutes(45)));
+        assert_eq!(parse_retention("forever").unwrap(), None);
+        assert_eq!(parse_retention("never").unwrap(), None);
+    }
+
+    #[test]
+    fn parse_rejects_garbage() {
+        for bad in ["", "d", "30", "30w", "-5d", "0d", "thirty days"] {
+            assert!(parse_retention(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn policy_skips_forever_and_names_bad_category() {
+        let policy =
+            RetentionPolicy::from_config(&retention(&[("core", "forever"), ("daily", "90d")]))
+                .unwrap();
+        assert_eq!(
+            policy.rules,
+            vec![(MemoryCategory::Daily, Duration::days(90))]
+        );
+        assert!(RetentionPolicy::default()
+            .with_trash_purge_after_days(0)
+            .is_empty());
+
+        let err = RetentionPolicy::from_config(&retention(&[("conversation", "soon")]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("memory.retention.conversation"));
+    }
+
+    #[tokio::test]
+    async fn enforce_prunes_only_expired_categories() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("chat", "hello", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("fact", "likes Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let conn = rusqlite::Connection::open(tmp.path().join("memory").join("brain.db")).unwrap();
+        let old = (chrono::Local::now() - Duration::days(40)).to_rfc3339();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1, updated_at = ?1",
+            rusqlite::params![old],
+        )
+        .unwrap();
+
+        let policy = RetentionPolicy::from_config(&retention(&[
+            ("conversation", "30d"),
+            ("core", "forever"),
+        ]))
+        .unwrap();
+        assert_eq!(policy.enforce(&mem).await.unwrap(), 1);
+        assert!(mem.get("chat").await.unwrap().is_none());
+        assert!(mem.get("fact").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn enforce_purges_old_trash() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("old", "x", MemoryCategory::Core).await.unwrap();
+        mem.store("recent", "y", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.forget("old").await.unwrap();
+        mem.forget("recent").await.unwrap();
+
+        let conn = rusqlite::Connection::open(tmp.path().join("memory").join("brain.db")).unwrap();
+        let old =
+            (Utc::now() - Duration::days(40)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        conn.execute(
+            "UPDATE memories SET deleted_at = ?1 WHERE key = 'old'",
+            rusqlite::params![old],
+        )
+        .unwrap();
+
+        let policy = RetentionPolicy::default().with_trash_purge_after_days(30);
+        assert_eq!(policy.enforce(&mem).await.unwrap(), 1);
+        let trash = mem.list_trash().await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].entry.key, "recent");
+    }
+}
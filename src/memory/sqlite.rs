@@ -1,28 +1,224 @@
+use super::chunker;
+use super::cipher::ContentCipher;
 use super::embeddings::EmbeddingProvider;
-use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::ranking::{clamp_importance, RecallRanking};
+use super::traits::{
+    distinct_keys, namespace_prefix, normalize_relation, normalize_tags, DateField, DateRange,
+    EmbeddingCacheStats, EntryMatcher, MatchMode, Memory, MemoryCategory, MemoryEntry, MemoryOp,
+    MemoryPatch, MemoryRelation, TrashedEntry, NAMESPACE_ROOT,
+};
 use super::vector;
 use async_trait::async_trait;
-use chrono::Local;
+use chrono::{Local, SecondsFormat, Utc};
+use rusqlite::functions::FunctionFlags;
 use rusqlite::{params, Connection};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use uuid::Uuid;
 
+/// How long a query waits on a competing writer before `database is locked`.
+pub const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5_000;
+
+/// Content size above which an entry is split into chunk rows.
+pub const DEFAULT_MAX_CONTENT_BYTES: usize = 32_768;
+
+/// Keys per `IN (…)` in `get_many`, well under `SQLite`'s parameter limit.
+const GET_MANY_BATCH: usize = 500;
+
+/// Row shape `entry_from_row` expects; tags are folded in from `memory_tags`.
+const ENTRY_SELECT: &str = "SELECT id, key, content, category, created_at, session_id,
+        (SELECT group_concat(tag, ',') FROM memory_tags WHERE memory_id = memories.id),
+        importance, updated_at, last_accessed_at, access_count
+     FROM memories";
+
 /// SQLite-backed persistent memory — the brain
 ///
 /// Full-stack search engine:
 /// - **Vector DB**: embeddings stored as BLOB, cosine similarity search
 /// - **Keyword Search**: FTS5 virtual table with BM25 scoring
-/// - **Hybrid Merge**: weighted fusion of vector + keyword results
-/// - **Embedding Cache**: LRU-evicted cache to avoid redundant API calls
+/// - **Hybrid Merge**: reciprocal rank or weighted fusion of vector + keyword results
+/// - **Semantic Mode**: cosine-similarity-only recall (see [`SearchMode`])
+/// - **Encryption at Rest**: optional AEAD-sealed content column
+/// - **Tags**: `memory_tags` join table for slicing by topic
+/// - **Relations**: `memory_relations` links entries into a small graph
+/// - **Chunking**: oversized content is stored as the entry plus linked
+///   `key#2`, `key#3`… rows (`parent_key`), recalled separately and
+///   reassembled by `get` and `list`
+/// - **Ranking**: hits weighed by importance and recency ([`RecallRanking`])
+/// - **Embedding Cache**: LRU-evicted cache keyed by content hash, so
+///   identical content is embedded once; hits and misses are counted
 /// - **Safe Reindex**: temp DB → seed → sync → atomic swap → rollback
+/// - **WAL Journaling**: readers never block on the writer; `synchronous =
+///   NORMAL` keeps commits off the fsync path
+///
+/// Queries run on Tokio's blocking pool (`spawn_blocking`) so a busy or
+/// locked database never stalls the async executor.
 pub struct SqliteMemory {
-    conn: Mutex<Connection>,
+    conn: Arc<Mutex<Connection>>,
     db_path: PathBuf,
     embedder: Arc<dyn EmbeddingProvider>,
     vector_weight: f32,
     keyword_weight: f32,
     cache_max: usize,
+    search_mode: SearchMode,
+    fusion: HybridFusion,
+    conversation_ttl: Option<chrono::Duration>,
+    cipher: Option<ContentCipher>,
+    ranking: RecallRanking,
+    max_content_bytes: usize,
+}
+
+/// How `recall` ranks memories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// Fusion of vector and BM25 keyword results (see [`HybridFusion`])
+    #[default]
+    Hybrid,
+    /// Cosine similarity against stored embeddings only, so queries match
+    /// by meaning without keyword overlap
+    Semantic,
+    /// BM25 keyword search only; never calls the embedding API on recall
+    Keyword,
+}
+
+impl SearchMode {
+    /// Parse `memory.search_mode`; unknown values fall back to hybrid.
+    pub fn from_config(mode: &str) -> Self {
+        match mode {
+            "semantic" => Self::Semantic,
+            "keyword" => Self::Keyword,
+            "hybrid" => Self::Hybrid,
+            other => {
+                tracing::warn!("Unknown memory search_mode '{other}', using hybrid");
+                Self::Hybrid
+            }
+        }
+    }
+}
+
+/// How hybrid recall combines vector and keyword hits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HybridFusion {
+    /// Reciprocal rank fusion; `k` damps the advantage of top ranks
+    Rrf { k: f32 },
+    /// Weighted sum of cosine and max-normalized BM25 scores
+    Weighted,
+}
+
+impl Default for HybridFusion {
+    fn default() -> Self {
+        Self::Rrf { k: 60.0 }
+    }
+}
+
+impl HybridFusion {
+    /// Parse `memory.hybrid_fusion`; unknown values fall back to RRF.
+    pub fn from_config(mode: &str, rrf_k: u32) -> Self {
+        #[allow(clippy::cast_precision_loss)]
+        let rrf = Self::Rrf { k: rrf_k as f32 };
+        match mode {
+            "rrf" => rrf,
+            "weighted" => Self::Weighted,
+            other => {
+                tracing::warn!("Unknown memory hybrid_fusion '{other}', using rrf");
+                rrf
+            }
+        }
+    }
+}
+
+/// An entry ready to write: content sealed, embedding computed.
+struct PendingRow {
+    key: String,
+    /// Entry this row is a chunk of
+    parent_key: Option<String>,
+    content: String,
+    category: String,
+    embedding: Option<Vec<u8>>,
+    expires_at: Option<String>,
+    session_id: Option<String>,
+    tags: Vec<String>,
+}
+
+/// A prepared [`MemoryOp`], ready to run inside the batch transaction.
+enum BatchStep {
+    Write(Vec<PendingRow>),
+    Trash(String),
+}
+
+/// Optional recall/list restrictions, applied in SQL.
+#[derive(Debug, Clone, Default)]
+struct EntryFilter {
+    /// Normalized tag the entry must carry
+    tag: Option<String>,
+    /// Key prefix of a namespace, `"ns/{namespace}/"`; `""` keeps every
+    /// key and `None` only keys outside all namespaces
+    key_prefix: Option<String>,
+    /// Inclusive lower bound on `date_field`, fixed-width UTC
+    since: Option<String>,
+    /// Exclusive upper bound on `date_field`, fixed-width UTC
+    until: Option<String>,
+    date_field: DateField,
+}
+
+impl EntryFilter {
+    fn tagged(tag: &str) -> Self {
+        Self {
+            tag: Some(tag.trim().to_lowercase()),
+            ..Self::default()
+        }
+    }
+
+    fn namespace(namespace: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            key_prefix: Some(namespace_prefix(namespace)?),
+            ..Self::default()
+        })
+    }
+
+    /// Namespaced entries included
+    fn all_keys() -> Self {
+        Self {
+            key_prefix: Some(String::new()),
+            ..Self::default()
+        }
+    }
+
+    fn within(range: &DateRange) -> Self {
+        Self {
+            since: range.since.map(SqliteMemory::expiry_timestamp),
+            until: range.until.map(SqliteMemory::expiry_timestamp),
+            date_field: range.field,
+            ..Self::default()
+        }
+    }
+
+    /// SQL predicate keeping the live rows of `table` this filter allows:
+    /// tag, key prefix, since and until are bound at `?{param}` through
+    /// `?{param + 3}`; NULLs keep everything, except that a NULL key
+    /// prefix leaves out namespaced keys.
+    fn clause(&self, table: &str, param: usize) -> String {
+        let (prefix, since, until) = (param + 1, param + 2, param + 3);
+        let root_len = NAMESPACE_ROOT.len();
+        let column = match self.date_field {
+            DateField::Created => "created_at",
+            DateField::Updated => "updated_at",
+        };
+        // Timestamps carry a local offset, so compare as instants
+        format!(
+            "{table}.deleted_at IS NULL
+             AND (?{param} IS NULL OR EXISTS (
+                SELECT 1 FROM memory_tags t WHERE t.memory_id = {table}.id AND t.tag = ?{param}
+            ))
+             AND CASE WHEN ?{prefix} IS NULL
+                 THEN substr({table}.key, 1, {root_len}) <> '{NAMESPACE_ROOT}'
+                 ELSE substr({table}.key, 1, length(?{prefix})) = ?{prefix} END
+             AND (?{since} IS NULL OR julianday({table}.{column}) >= julianday(?{since}))
+             AND (?{until} IS NULL OR julianday({table}.{column}) < julianday(?{until}))"
+        )
+    }
 }
 
 impl SqliteMemory {
@@ -50,16 +246,298 @@ impl SqliteMemory {
         }
 
         let conn = Connection::open(&db_path)?;
+        Self::configure_connection(&conn)?;
         Self::init_schema(&conn)?;
 
         Ok(Self {
-            conn: Mutex::new(conn),
+            conn: Arc::new(Mutex::new(conn)),
             db_path,
             embedder,
             vector_weight,
             keyword_weight,
             cache_max,
+            search_mode: SearchMode::default(),
+            fusion: HybridFusion::default(),
+            conversation_ttl: None,
+            cipher: None,
+            ranking: RecallRanking::default(),
+            max_content_bytes: DEFAULT_MAX_CONTENT_BYTES,
+        })
+    }
+
+    /// Wait up to `ms` for a competing writer before failing with
+    /// `database is locked` (0 fails immediately).
+    pub fn with_busy_timeout_ms(self, ms: u64) -> anyhow::Result<Self> {
+        self.conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?
+            .busy_timeout(Duration::from_millis(ms))?;
+        Ok(self)
+    }
+
+    /// Set how `recall` ranks results.
+    #[must_use]
+    pub fn with_search_mode(mut self, search_mode: SearchMode) -> Self {
+        self.search_mode = search_mode;
+        self
+    }
+
+    /// Set how hybrid recall fuses vector and keyword results.
+    #[must_use]
+    pub fn with_hybrid_fusion(mut self, fusion: HybridFusion) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    /// Expire conversation-category entries `days` after they are stored
+    /// (0 keeps them until hygiene prunes them).
+    #[must_use]
+    pub fn with_conversation_ttl_days(mut self, days: u32) -> Self {
+        self.conversation_ttl = (days > 0).then(|| chrono::Duration::days(i64::from(days)));
+        self
+    }
+
+    /// Halve the recency boost of recall hits every `days` (0 ranks by
+    /// relevance and importance only).
+    #[must_use]
+    pub fn with_recency_half_life_days(mut self, days: u32) -> Self {
+        self.ranking = RecallRanking::new(days);
+        self
+    }
+
+    /// Split content larger than `bytes` into chunk rows (0 = never).
+    #[must_use]
+    pub fn with_max_content_bytes(mut self, bytes: usize) -> Self {
+        self.max_content_bytes = bytes;
+        self
+    }
+
+    /// Encrypt memory content at rest. Existing plaintext rows are sealed
+    /// immediately so nothing readable is left behind.
+    pub fn with_cipher(mut self, cipher: ContentCipher) -> anyhow::Result<Self> {
+        {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+            let plaintext: Vec<(String, String)> = {
+                let mut stmt = conn.prepare("SELECT id, content FROM memories")?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                rows.filter_map(std::result::Result::ok)
+                    .filter(|(_, content): &(String, String)| !ContentCipher::is_encrypted(content))
+                    .collect()
+            };
+            for (id, content) in &plaintext {
+                conn.execute(
+                    "UPDATE memories SET content = ?1 WHERE id = ?2",
+                    params![cipher.encrypt(content)?, id],
+                )?;
+            }
+            if !plaintext.is_empty() {
+                // The old plaintext still lingers in FTS segments, the WAL and
+                // free pages until they are merged, checkpointed and vacuumed
+                conn.execute_batch("INSERT INTO memories_fts(memories_fts) VALUES('optimize');")?;
+                conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+                conn.execute_batch("VACUUM;")?;
+                tracing::info!("memory: encrypted {} existing entries", plaintext.len());
+            }
+        }
+        self.cipher = Some(cipher);
+        Ok(self)
+    }
+
+    /// Run `f` against the connection on the blocking thread pool.
+    async fn with_conn<T, F>(&self, f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&Connection) -> anyhow::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+            f(&conn)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("SQLite task failed: {e}"))?
+    }
+
+    /// Map an [`ENTRY_SELECT`] row.
+    fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<MemoryEntry> {
+        let mut tags: Vec<String> = row
+            .get::<_, Option<String>>(6)?
+            .map(|joined| joined.split(',').map(ToString::to_string).collect())
+            .unwrap_or_default();
+        tags.sort();
+        Ok(MemoryEntry {
+            id: row.get(0)?,
+            key: row.get(1)?,
+            content: row.get(2)?,
+            category: Self::str_to_category(&row.get::<_, String>(3)?),
+            timestamp: row.get(4)?,
+            session_id: row.get(5)?,
+            tags,
+            importance: row.get(7)?,
+            updated_at: row.get(8)?,
+            last_accessed_at: row.get(9)?,
+            access_count: u64::try_from(row.get::<_, i64>(10)?).unwrap_or(0),
+            score: None,
+        })
+    }
+
+    /// Decrypt stored content (no-op without a cipher or for plaintext rows).
+    fn open_content(&self, content: &str) -> anyhow::Result<String> {
+        match &self.cipher {
+            Some(cipher) => cipher.decrypt(content),
+            None => Ok(content.to_string()),
+        }
+    }
+
+    fn open_entry(&self, mut entry: MemoryEntry) -> anyhow::Result<MemoryEntry> {
+        entry.content = self.open_content(&entry.content)?;
+        Ok(entry)
+    }
+
+    /// Open an entry and append the content of its chunk rows.
+    fn open_chunked(&self, entry: MemoryEntry, chunks: &[String]) -> anyhow::Result<MemoryEntry> {
+        let mut entry = self.open_entry(entry)?;
+        for chunk in chunks {
+            entry.content.push_str(&self.open_content(chunk)?);
+        }
+        Ok(entry)
+    }
+
+    /// Count a read of `entries` (a chunk's read counts for its entry too)
+    /// and reflect it on the returned copies.
+    async fn record_access(
+        &self,
+        mut entries: Vec<MemoryEntry>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        if entries.is_empty() {
+            return Ok(entries);
+        }
+        let now = Local::now().to_rfc3339();
+        let ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
+        let stamp = now.clone();
+        self.with_conn(move |conn| {
+            let tx = conn.unchecked_transaction()?;
+            for id in &ids {
+                tx.execute(
+                    "UPDATE memories SET last_accessed_at = ?2, access_count = access_count + 1
+                     WHERE id = ?1
+                        OR key = (SELECT parent_key FROM memories WHERE id = ?1)",
+                    params![id, stamp],
+                )?;
+            }
+            tx.commit()?;
+            Ok(())
         })
+        .await?;
+        for entry in &mut entries {
+            entry.access_count += 1;
+            entry.last_accessed_at = Some(now.clone());
+        }
+        Ok(entries)
+    }
+
+    /// Live entry under `key` with its chunks reassembled, without counting
+    /// it as accessed.
+    async fn fetch(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
+        let key = key.to_string();
+        let entry = self
+            .with_conn(move |conn| {
+                Self::delete_expired(conn)?;
+                let mut stmt = conn.prepare(&format!(
+                    "{ENTRY_SELECT} WHERE key = ?1 AND deleted_at IS NULL"
+                ))?;
+                let mut rows = stmt.query_map(params![key], Self::entry_from_row)?;
+                match rows.next().and_then(Result::ok) {
+                    Some(entry) => {
+                        let chunks = Self::chunk_contents(conn, &entry.key)?;
+                        Ok(Some((entry, chunks)))
+                    }
+                    None => Ok(None),
+                }
+            })
+            .await?;
+
+        entry
+            .map(|(e, chunks)| self.open_chunked(e, &chunks))
+            .transpose()
+    }
+
+    /// Key of the `n`th piece (from 2) of a chunked entry.
+    fn chunk_key(key: &str, n: usize) -> String {
+        format!("{key}#{n}")
+    }
+
+    /// Sealed content of `key`'s chunk rows, in order (empty if unchunked).
+    fn chunk_contents(conn: &Connection, key: &str) -> anyhow::Result<Vec<String>> {
+        let mut stmt = conn.prepare("SELECT key, content FROM memories WHERE parent_key = ?1")?;
+        let mut chunks = stmt
+            .query_map(params![key], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        chunks.sort_by_key(|(chunk_key, _)| {
+            chunk_key
+                .strip_prefix(key)
+                .and_then(|n| n.trim_start_matches('#').parse::<usize>().ok())
+        });
+        Ok(chunks.into_iter().map(|(_, content)| content).collect())
+    }
+
+    /// Pair each entry with its sealed chunk contents.
+    fn with_chunks(
+        conn: &Connection,
+        entries: Vec<MemoryEntry>,
+    ) -> anyhow::Result<Vec<(MemoryEntry, Vec<String>)>> {
+        entries
+            .into_iter()
+            .map(|entry| {
+                let chunks = Self::chunk_contents(conn, &entry.key)?;
+                Ok((entry, chunks))
+            })
+            .collect()
+    }
+
+    /// Connection tuning: WAL so reads proceed alongside a write, `NORMAL`
+    /// sync (durable in WAL mode, minus an fsync per commit), and a default
+    /// busy timeout so bursts queue instead of erroring. Also registers the
+    /// `regexp()` function behind SQL's `REGEXP` operator.
+    fn configure_connection(conn: &Connection) -> anyhow::Result<()> {
+        conn.busy_timeout(Duration::from_millis(DEFAULT_BUSY_TIMEOUT_MS))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.create_scalar_function(
+            "regexp",
+            2,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            |ctx| {
+                // Compiled once per statement, not per row
+                let re: Arc<regex::Regex> = ctx.get_or_create_aux(0, |pattern| {
+                    regex::Regex::new(pattern.as_str()?)
+                        .map_err(|e| rusqlite::Error::UserFunctionError(Box::new(e)))
+                })?;
+                Ok(ctx
+                    .get_raw(1)
+                    .as_str_or_null()?
+                    .is_some_and(|text| re.is_match(text)))
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Add `column` to `memories` on databases created before it existed
+    fn add_column_if_missing(conn: &Connection, column: &str, decl: &str) -> anyhow::Result<()> {
+        let exists = conn
+            .prepare("SELECT 1 FROM pragma_table_info('memories') WHERE name = ?1")?
+            .exists(params![column])?;
+        if !exists {
+            conn.execute_batch(&format!("ALTER TABLE memories ADD COLUMN {column} {decl};"))?;
+        }
+        Ok(())
     }
 
     /// Initialize all tables: memories, FTS5, `embedding_cache`
@@ -88,16 +566,6 @@ impl SqliteMemory {
                 INSERT INTO memories_fts(rowid, key, content)
                 VALUES (new.rowid, new.key, new.content);
             END;
-            CREATE TRIGGER IF NOT EXISTS memories_ad AFTER DELETE ON memories BEGIN
-                INSERT INTO memories_fts(memories_fts, rowid, key, content)
-                VALUES ('delete', old.rowid, old.key, old.content);
-            END;
-            CREATE TRIGGER IF NOT EXISTS memories_au AFTER UPDATE ON memories BEGIN
-                INSERT INTO memories_fts(memories_fts, rowid, key, content)
-                VALUES ('delete', old.rowid, old.key, old.content);
-                INSERT INTO memories_fts(rowid, key, content)
-                VALUES (new.rowid, new.key, new.content);
-            END;
 
             -- Embedding cache with LRU eviction
             CREATE TABLE IF NOT EXISTS embedding_cache (
@@ -106,11 +574,243 @@ impl SqliteMemory {
                 created_at   TEXT NOT NULL,
                 accessed_at  TEXT NOT NULL
             );
-            CREATE INDEX IF NOT EXISTS idx_cache_accessed ON embedding_cache(accessed_at);",
+            CREATE INDEX IF NOT EXISTS idx_cache_accessed ON embedding_cache(accessed_at);
+
+            -- Lifetime embedding cache counters (a single row)
+            CREATE TABLE IF NOT EXISTS embedding_cache_stats (
+                id     INTEGER PRIMARY KEY CHECK (id = 1),
+                hits   INTEGER NOT NULL DEFAULT 0,
+                misses INTEGER NOT NULL DEFAULT 0
+            );
+            INSERT OR IGNORE INTO embedding_cache_stats (id) VALUES (1);
+
+            -- Tags: many per memory, removed with it
+            CREATE TABLE IF NOT EXISTS memory_tags (
+                memory_id TEXT NOT NULL,
+                tag       TEXT NOT NULL,
+                PRIMARY KEY (memory_id, tag)
+            );
+            CREATE INDEX IF NOT EXISTS idx_memory_tags_tag ON memory_tags(tag);
+            CREATE TRIGGER IF NOT EXISTS memories_tags_ad AFTER DELETE ON memories BEGIN
+                DELETE FROM memory_tags WHERE memory_id = old.id;
+            END;
+
+            -- Relations: labelled links between memories, removed with either end
+            CREATE TABLE IF NOT EXISTS memory_relations (
+                from_id    TEXT NOT NULL,
+                to_id      TEXT NOT NULL,
+                relation   TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (from_id, to_id, relation)
+            );
+            CREATE INDEX IF NOT EXISTS idx_memory_relations_to ON memory_relations(to_id);
+            CREATE TRIGGER IF NOT EXISTS memories_relations_ad AFTER DELETE ON memories BEGIN
+                DELETE FROM memory_relations WHERE from_id = old.id OR to_id = old.id;
+            END;",
+        )?;
+
+        // Migrations: per-entry expiry (UTC RFC 3339, NULL = never expires),
+        // conversation grouping (NULL = not tied to a session), recall
+        // weight (0.0–1.0) and soft delete (UTC RFC 3339, NULL = live)
+        Self::add_column_if_missing(conn, "expires_at", "TEXT")?;
+        Self::add_column_if_missing(conn, "session_id", "TEXT")?;
+        Self::add_column_if_missing(conn, "importance", "REAL NOT NULL DEFAULT 0.5")?;
+        Self::add_column_if_missing(conn, "deleted_at", "TEXT")?;
+        // Migration: chunk rows point at the entry they continue
+        Self::add_column_if_missing(conn, "parent_key", "TEXT")?;
+        // Migration: read tracking for `get`/recall (NULL = never read)
+        Self::add_column_if_missing(conn, "last_accessed_at", "TEXT")?;
+        Self::add_column_if_missing(conn, "access_count", "INTEGER NOT NULL DEFAULT 0")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_memories_expires ON memories(expires_at);
+             CREATE INDEX IF NOT EXISTS idx_memories_session ON memories(session_id);
+             CREATE INDEX IF NOT EXISTS idx_memories_deleted ON memories(deleted_at);
+             CREATE INDEX IF NOT EXISTS idx_memories_parent ON memories(parent_key);
+
+             -- Delete/update FTS triggers, recreated so trashed rows stay out
+             -- of the index (older databases have unconditional versions)
+             DROP TRIGGER IF EXISTS memories_ad;
+             DROP TRIGGER IF EXISTS memories_au;
+             CREATE TRIGGER memories_ad AFTER DELETE ON memories
+             WHEN old.deleted_at IS NULL BEGIN
+                 INSERT INTO memories_fts(memories_fts, rowid, key, content)
+                 VALUES ('delete', old.rowid, old.key, old.content);
+             END;
+             CREATE TRIGGER memories_au AFTER UPDATE OF key, content, deleted_at ON memories BEGIN
+                 INSERT INTO memories_fts(memories_fts, rowid, key, content)
+                 SELECT 'delete', old.rowid, old.key, old.content WHERE old.deleted_at IS NULL;
+                 INSERT INTO memories_fts(rowid, key, content)
+                 SELECT new.rowid, new.key, new.content WHERE new.deleted_at IS NULL;
+             END;",
+        )?;
+        Ok(())
+    }
+
+    /// Fixed-width UTC timestamp so expiry compares lexicographically.
+    fn expiry_timestamp(at: chrono::DateTime<Utc>) -> String {
+        at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+
+    /// Expiry applied by plain stores: the conversation TTL, if configured.
+    fn default_expiry(&self, category: &MemoryCategory) -> Option<String> {
+        match (category, self.conversation_ttl) {
+            (MemoryCategory::Conversation, Some(ttl)) => {
+                Some(Self::expiry_timestamp(Utc::now() + ttl))
+            }
+            _ => None,
+        }
+    }
+
+    /// Delete rows whose expiry has passed.
+    fn delete_expired(conn: &Connection) -> anyhow::Result<usize> {
+        let now = Self::expiry_timestamp(Utc::now());
+        Ok(conn.execute(
+            "DELETE FROM memories WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+            params![now],
+        )?)
+    }
+
+    /// Upsert an entry; `session_id`, `expires_at` and `tags` replace any
+    /// previous values for the key.
+    async fn upsert(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+        expires_at: Option<String>,
+        tags: &[String],
+    ) -> anyhow::Result<()> {
+        let rows = self
+            .prepare_rows(key, content, &category, session_id, expires_at, tags)
+            .await?;
+        self.with_conn(move |conn| {
+            let tx = conn.unchecked_transaction()?;
+            for row in &rows {
+                Self::write_row(&tx, row)?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Embed and seal an entry ahead of taking the connection. Content over
+    /// `max_content_bytes` yields the entry's row plus one chunk row per
+    /// further piece, each embedded on its own.
+    async fn prepare_rows(
+        &self,
+        key: &str,
+        content: &str,
+        category: &MemoryCategory,
+        session_id: Option<&str>,
+        expires_at: Option<String>,
+        tags: &[String],
+    ) -> anyhow::Result<Vec<PendingRow>> {
+        let pieces = if self.max_content_bytes > 0 {
+            chunker::split_content(content, self.max_content_bytes)
+        } else {
+            vec![content]
+        };
+
+        let mut rows = Vec::with_capacity(pieces.len());
+        for (i, piece) in pieces.into_iter().enumerate() {
+            let embedding = self
+                .get_or_compute_embedding(piece)
+                .await?
+                .map(|emb| vector::vec_to_bytes(&emb));
+
+            let content = match &self.cipher {
+                Some(cipher) => cipher.encrypt(piece)?,
+                None => piece.to_string(),
+            };
+
+            rows.push(PendingRow {
+                key: if i == 0 {
+                    key.to_string()
+                } else {
+                    Self::chunk_key(key, i + 1)
+                },
+                parent_key: (i > 0).then(|| key.to_string()),
+                content,
+                category: Self::category_to_str(category),
+                embedding,
+                expires_at: expires_at.clone(),
+                session_id: session_id.map(ToString::to_string),
+                tags: normalize_tags(tags),
+            });
+        }
+        Ok(rows)
+    }
+
+    /// Upsert one prepared row and replace its tags; the caller owns the
+    /// transaction. Writing an entry drops the chunk rows of its previous
+    /// content.
+    fn write_row(conn: &Connection, row: &PendingRow) -> anyhow::Result<()> {
+        if row.parent_key.is_none() {
+            conn.execute(
+                "DELETE FROM memories WHERE parent_key = ?1",
+                params![row.key],
+            )?;
+        }
+        let now = Local::now().to_rfc3339();
+        let id = Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at, expires_at, session_id, parent_key)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(key) DO UPDATE SET
+                content = excluded.content,
+                category = excluded.category,
+                embedding = excluded.embedding,
+                updated_at = excluded.updated_at,
+                expires_at = excluded.expires_at,
+                session_id = excluded.session_id,
+                parent_key = excluded.parent_key,
+                deleted_at = NULL",
+            params![
+                id,
+                row.key,
+                row.content,
+                row.category,
+                row.embedding,
+                now,
+                now,
+                row.expires_at,
+                row.session_id,
+                row.parent_key
+            ],
+        )?;
+
+        // An existing key keeps its original id
+        let memory_id: String = conn.query_row(
+            "SELECT id FROM memories WHERE key = ?1",
+            params![row.key],
+            |r| r.get(0),
+        )?;
+        conn.execute(
+            "DELETE FROM memory_tags WHERE memory_id = ?1",
+            params![memory_id],
         )?;
+        for tag in &row.tags {
+            conn.execute(
+                "INSERT INTO memory_tags (memory_id, tag) VALUES (?1, ?2)",
+                params![memory_id, tag],
+            )?;
+        }
         Ok(())
     }
 
+    /// Move an entry (chunks included) to the trash rather than deleting
+    /// it; `purge_trash` removes it for good.
+    fn trash(conn: &Connection, key: &str) -> anyhow::Result<bool> {
+        let affected = conn.execute(
+            "UPDATE memories SET deleted_at = ?2
+             WHERE (key = ?1 OR parent_key = ?1) AND deleted_at IS NULL",
+            params![key, Self::expiry_timestamp(Utc::now())],
+        )?;
+        Ok(affected > 0)
+    }
+
     fn category_to_str(cat: &MemoryCategory) -> String {
         match cat {
             MemoryCategory::Core => "core".into(),
@@ -156,24 +856,27 @@ impl SqliteMemory {
         let now = Local::now().to_rfc3339();
 
         // Check cache
-        {
-            let conn = self
-                .conn
-                .lock()
-                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-
-            let mut stmt =
-                conn.prepare("SELECT embedding FROM embedding_cache WHERE content_hash = ?1")?;
-            let cached: Option<Vec<u8>> = stmt.query_row(params![hash], |row| row.get(0)).ok();
-
-            if let Some(bytes) = cached {
-                // Update accessed_at for LRU
-                conn.execute(
-                    "UPDATE embedding_cache SET accessed_at = ?1 WHERE content_hash = ?2",
-                    params![now, hash],
-                )?;
-                return Ok(Some(vector::bytes_to_vec(&bytes)));
-            }
+        let cached = {
+            let hash = hash.clone();
+            let now = now.clone();
+            self.with_conn(move |conn| {
+                let mut stmt =
+                    conn.prepare("SELECT embedding FROM embedding_cache WHERE content_hash = ?1")?;
+                let cached: Option<Vec<u8>> = stmt.query_row(params![hash], |row| row.get(0)).ok();
+                if cached.is_some() {
+                    // Update accessed_at for LRU
+                    conn.execute(
+                        "UPDATE embedding_cache SET accessed_at = ?1 WHERE content_hash = ?2",
+                        params![now, hash],
+                    )?;
+                    conn.execute("UPDATE embedding_cache_stats SET hits = hits + 1", [])?;
+                }
+                Ok(cached)
+            })
+            .await?
+        };
+        if let Some(bytes) = cached {
+            return Ok(Some(vector::bytes_to_vec(&bytes)));
         }
 
         // Compute embedding
@@ -181,21 +884,17 @@ impl SqliteMemory {
         let bytes = vector::vec_to_bytes(&embedding);
 
         // Store in cache + LRU eviction
-        {
-            let conn = self
-                .conn
-                .lock()
-                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-
+        #[allow(clippy::cast_possible_wrap)]
+        let max = self.cache_max as i64;
+        self.with_conn(move |conn| {
             conn.execute(
                 "INSERT OR REPLACE INTO embedding_cache (content_hash, embedding, created_at, accessed_at)
                  VALUES (?1, ?2, ?3, ?4)",
                 params![hash, bytes, now, now],
             )?;
+            conn.execute("UPDATE embedding_cache_stats SET misses = misses + 1", [])?;
 
             // LRU eviction: keep only cache_max entries
-            #[allow(clippy::cast_possible_wrap)]
-            let max = self.cache_max as i64;
             conn.execute(
                 "DELETE FROM embedding_cache WHERE content_hash IN (
                     SELECT content_hash FROM embedding_cache
@@ -204,7 +903,9 @@ impl SqliteMemory {
                 )",
                 params![max],
             )?;
-        }
+            Ok(())
+        })
+        .await?;
 
         Ok(Some(embedding))
     }
@@ -214,6 +915,7 @@ impl SqliteMemory {
         conn: &Connection,
         query: &str,
         limit: usize,
+        filter: &EntryFilter,
     ) -> anyhow::Result<Vec<(String, f32)>> {
         // Escape FTS5 special chars and build query
         let fts_query: String = query
@@ -226,24 +928,37 @@ impl SqliteMemory {
             return Ok(Vec::new());
         }
 
-        let sql = "SELECT m.id, bm25(memories_fts) as score
-                   FROM memories_fts f
-                   JOIN memories m ON m.rowid = f.rowid
-                   WHERE memories_fts MATCH ?1
-                   ORDER BY score
-                   LIMIT ?2";
+        let sql = format!(
+            "SELECT m.id, bm25(memories_fts) as score
+             FROM memories_fts f
+             JOIN memories m ON m.rowid = f.rowid
+             WHERE memories_fts MATCH ?1 AND {}
+             ORDER BY score
+             LIMIT ?2",
+            filter.clause("m", 3)
+        );
 
-        let mut stmt = conn.prepare(sql)?;
+        let mut stmt = conn.prepare(&sql)?;
         #[allow(clippy::cast_possible_wrap)]
         let limit_i64 = limit as i64;
 
-        let rows = stmt.query_map(params![fts_query, limit_i64], |row| {
-            let id: String = row.get(0)?;
-            let score: f64 = row.get(1)?;
-            // BM25 returns negative scores (lower = better), negate for ranking
-            #[allow(clippy::cast_possible_truncation)]
-            Ok((id, (-score) as f32))
-        })?;
+        let rows = stmt.query_map(
+            params![
+                fts_query,
+                limit_i64,
+                filter.tag,
+                filter.key_prefix,
+                filter.since,
+                filter.until
+            ],
+            |row| {
+                let id: String = row.get(0)?;
+                let score: f64 = row.get(1)?;
+                // BM25 returns negative scores (lower = better), negate for ranking
+                #[allow(clippy::cast_possible_truncation)]
+                Ok((id, (-score) as f32))
+            },
+        )?;
 
         let mut results = Vec::new();
         for row in rows {
@@ -257,15 +972,21 @@ impl SqliteMemory {
         conn: &Connection,
         query_embedding: &[f32],
         limit: usize,
+        filter: &EntryFilter,
     ) -> anyhow::Result<Vec<(String, f32)>> {
-        let mut stmt =
-            conn.prepare("SELECT id, embedding FROM memories WHERE embedding IS NOT NULL")?;
-
-        let rows = stmt.query_map([], |row| {
-            let id: String = row.get(0)?;
-            let blob: Vec<u8> = row.get(1)?;
-            Ok((id, blob))
-        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, embedding FROM memories WHERE embedding IS NOT NULL AND {}",
+            filter.clause("memories", 1)
+        ))?;
+
+        let rows = stmt.query_map(
+            params![filter.tag, filter.key_prefix, filter.since, filter.until],
+            |row| {
+                let id: String = row.get(0)?;
+                let blob: Vec<u8> = row.get(1)?;
+                Ok((id, blob))
+            },
+        )?;
 
         let mut scored: Vec<(String, f32)> = Vec::new();
         for row in rows {
@@ -282,50 +1003,328 @@ impl SqliteMemory {
         Ok(scored)
     }
 
-    /// Safe reindex: rebuild FTS5 + embeddings with rollback on failure
-    #[allow(dead_code)]
-    pub async fn reindex(&self) -> anyhow::Result<usize> {
-        // Step 1: Rebuild FTS5
-        {
-            let conn = self
-                .conn
-                .lock()
-                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-
-            conn.execute_batch("INSERT INTO memories_fts(memories_fts) VALUES('rebuild');")?;
+    /// Rank search hits: pure cosine in semantic mode, keyword-only when no
+    /// vectors matched, `fusion` of both otherwise.
+    fn merge_results(
+        semantic: bool,
+        vector_results: &[(String, f32)],
+        keyword_results: &[(String, f32)],
+        (vector_weight, keyword_weight): (f32, f32),
+        fusion: HybridFusion,
+        limit: usize,
+    ) -> Vec<vector::ScoredResult> {
+        if semantic {
+            // vector_search already sorted by similarity
+            vector_results
+                .iter()
+                .take(limit)
+                .map(|(id, score)| vector::ScoredResult {
+                    id: id.clone(),
+                    vector_score: Some(*score),
+                    keyword_score: None,
+                    final_score: *score,
+                })
+                .collect()
+        } else if vector_results.is_empty() {
+            // No embeddings — use keyword results only
+            keyword_results
+                .iter()
+                .map(|(id, score)| vector::ScoredResult {
+                    id: id.clone(),
+                    vector_score: None,
+                    keyword_score: Some(*score),
+                    final_score: *score,
+                })
+                .collect()
+        } else {
+            match fusion {
+                HybridFusion::Rrf { k } => vector::rrf_merge(
+                    vector_results,
+                    keyword_results,
+                    vector_weight,
+                    keyword_weight,
+                    k,
+                    limit,
+                ),
+                HybridFusion::Weighted => vector::hybrid_merge(
+                    vector_results,
+                    keyword_results,
+                    vector_weight,
+                    keyword_weight,
+                    limit,
+                ),
+            }
         }
+    }
 
-        // Step 2: Re-embed all memories that lack embeddings
-        if self.embedder.dimensions() == 0 {
-            return Ok(0);
+    /// Substring fallback when FTS5 and vector search both come up empty.
+    fn like_search(
+        conn: &Connection,
+        query: &str,
+        limit: usize,
+        filter: &EntryFilter,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let keywords: Vec<String> = query.split_whitespace().map(|w| format!("%{w}%")).collect();
+        if keywords.is_empty() {
+            return Ok(Vec::new());
         }
 
-        let entries: Vec<(String, String)> = {
-            let conn = self
-                .conn
-                .lock()
-                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-
-            let mut stmt =
-                conn.prepare("SELECT id, content FROM memories WHERE embedding IS NULL")?;
-            let rows = stmt.query_map([], |row| {
-                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-            })?;
-            rows.filter_map(std::result::Result::ok).collect()
-        };
+        let conditions: Vec<String> = keywords
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("(content LIKE ?{} OR key LIKE ?{})", i * 2 + 1, i * 2 + 2))
+            .collect();
+        let where_clause = conditions.join(" OR ");
+        let filter_param = keywords.len() * 2 + 1;
+        let sql = format!(
+            "{ENTRY_SELECT}
+             WHERE ({where_clause}) AND {}
+             ORDER BY updated_at DESC
+             LIMIT ?{}",
+            filter.clause("memories", filter_param),
+            filter_param + 4
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+        for kw in &keywords {
+            param_values.push(Box::new(kw.clone()));
+            param_values.push(Box::new(kw.clone()));
+        }
+        param_values.push(Box::new(filter.tag.clone()));
+        param_values.push(Box::new(filter.key_prefix.clone()));
+        param_values.push(Box::new(filter.since.clone()));
+        param_values.push(Box::new(filter.until.clone()));
+        #[allow(clippy::cast_possible_wrap)]
+        param_values.push(Box::new(limit as i64));
+        let params_ref: Vec<&dyn rusqlite::types::ToSql> =
+            param_values.iter().map(AsRef::as_ref).collect();
+        let rows = stmt.query_map(params_ref.as_slice(), |row| {
+            let mut entry = Self::entry_from_row(row)?;
+            entry.score = Some(1.0);
+            Ok(entry)
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Hybrid recall over the entries `filter` keeps, reranked by
+    /// importance and recency.
+    async fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        filter: EntryFilter,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Compute query embedding (async, before touching the database)
+        let query_embedding = if self.search_mode == SearchMode::Keyword {
+            None
+        } else {
+            self.get_or_compute_embedding(query).await?
+        };
+
+        let query = query.to_string();
+        let semantic = self.search_mode == SearchMode::Semantic && query_embedding.is_some();
+        let weights = (self.vector_weight, self.keyword_weight);
+        let fusion = self.fusion;
+        // Extra candidates so reranking can promote an important older hit
+        let candidates = limit.saturating_mul(2);
+
+        let mut results = self
+            .with_conn(move |conn| {
+                Self::delete_expired(conn)?;
+
+                // Vector similarity search (if embeddings available)
+                let vector_results = if let Some(ref qe) = query_embedding {
+                    Self::vector_search(conn, qe, candidates, &filter).unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+
+                // FTS5 BM25 keyword search — semantic mode skips it once vectors exist
+                let keyword_results = if semantic {
+                    Vec::new()
+                } else {
+                    Self::fts5_search(conn, &query, candidates, &filter).unwrap_or_default()
+                };
+
+                let merged = Self::merge_results(
+                    semantic,
+                    &vector_results,
+                    &keyword_results,
+                    weights,
+                    fusion,
+                    candidates,
+                );
+
+                // Fetch full entries for merged results
+                let mut results = Vec::new();
+                let mut stmt = conn.prepare(&format!("{ENTRY_SELECT} WHERE id = ?1"))?;
+                for scored in &merged {
+                    if let Ok(mut entry) = stmt.query_row(params![scored.id], Self::entry_from_row)
+                    {
+                        entry.score = Some(f64::from(scored.final_score));
+                        results.push(entry);
+                    }
+                }
+
+                // If hybrid returned nothing, fall back to LIKE search
+                if results.is_empty() {
+                    results = Self::like_search(conn, &query, candidates, &filter)?;
+                }
+                Ok(results)
+            })
+            .await?;
+
+        self.ranking.rerank(&mut results);
+        results.truncate(limit);
+
+        let results = results
+            .into_iter()
+            .map(|e| self.open_entry(e))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.record_access(results).await
+    }
+
+    /// `exact`/`regex`/`prefix` lookup evaluated by `SQLite`, newest first.
+    /// Sealed content can't be matched in SQL, so with encryption on the
+    /// entries are opened and matched in process instead.
+    async fn match_entries(
+        &self,
+        query: &str,
+        mode: MatchMode,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        // Compile up front: a clear error beats SQLite's opaque one
+        let matcher = EntryMatcher::new(mode, query)?;
+        if self.cipher.is_some() {
+            let mut entries = self.list(None).await?;
+            entries.retain(|e| matcher.matches(e));
+            entries.truncate(limit);
+            return Ok(entries);
+        }
+
+        let (predicate, pattern) = match mode {
+            MatchMode::Exact => (
+                "(instr(key, ?1) > 0 OR instr(content, ?1) > 0)",
+                query.to_string(),
+            ),
+            MatchMode::Regex => ("(key REGEXP ?1 OR content REGEXP ?1)", query.to_string()),
+            // GLOB with a literal prefix is served by the key index
+            MatchMode::Prefix | MatchMode::Keyword => {
+                ("key GLOB ?1", format!("{}*", glob_escape(query)))
+            }
+        };
+        let sql = format!(
+            "{ENTRY_SELECT}
+             WHERE {predicate} AND {}
+             ORDER BY updated_at DESC
+             LIMIT ?6",
+            EntryFilter::default().clause("memories", 2)
+        );
+        #[allow(clippy::cast_possible_wrap)]
+        let limit = limit as i64;
+        self.with_conn(move |conn| {
+            Self::delete_expired(conn)?;
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(
+                params![
+                    pattern,
+                    None::<String>,
+                    None::<String>,
+                    None::<String>,
+                    None::<String>,
+                    limit
+                ],
+                Self::entry_from_row,
+            )?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
+
+    /// Entries newest first, optionally filtered by category and `filter`.
+    async fn list_filtered(
+        &self,
+        category: Option<&MemoryCategory>,
+        filter: EntryFilter,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let category = category.map(Self::category_to_str);
+        let results = self
+            .with_conn(move |conn| {
+                Self::delete_expired(conn)?;
+                let mut stmt = conn.prepare(&format!(
+                    "{ENTRY_SELECT}
+                     WHERE (?1 IS NULL OR category = ?1) AND parent_key IS NULL AND {}
+                     ORDER BY updated_at DESC",
+                    filter.clause("memories", 2)
+                ))?;
+                let rows = stmt.query_map(
+                    params![
+                        category,
+                        filter.tag,
+                        filter.key_prefix,
+                        filter.since,
+                        filter.until
+                    ],
+                    Self::entry_from_row,
+                )?;
+                Self::with_chunks(conn, rows.collect::<rusqlite::Result<Vec<_>>>()?)
+            })
+            .await?;
+
+        results
+            .into_iter()
+            .map(|(e, chunks)| self.open_chunked(e, &chunks))
+            .collect()
+    }
+
+    /// Safe reindex: rebuild FTS5 + embeddings with rollback on failure
+    #[allow(dead_code)]
+    pub async fn reindex(&self) -> anyhow::Result<usize> {
+        // Step 1: Rebuild FTS5
+        self.with_conn(|conn| {
+            // `rebuild` indexes every row; drop the trashed ones again
+            conn.execute_batch(
+                "INSERT INTO memories_fts(memories_fts) VALUES('rebuild');
+                 INSERT INTO memories_fts(memories_fts, rowid, key, content)
+                 SELECT 'delete', rowid, key, content FROM memories WHERE deleted_at IS NOT NULL;",
+            )?;
+            Ok(())
+        })
+        .await?;
+
+        // Step 2: Re-embed all memories that lack embeddings
+        if self.embedder.dimensions() == 0 {
+            return Ok(0);
+        }
+
+        let entries: Vec<(String, String)> = self
+            .with_conn(|conn| {
+                let mut stmt =
+                    conn.prepare("SELECT id, content FROM memories WHERE embedding IS NULL")?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?;
+                Ok(rows.filter_map(std::result::Result::ok).collect())
+            })
+            .await?;
 
         let mut count = 0;
-        for (id, content) in &entries {
-            if let Ok(Some(emb)) = self.get_or_compute_embedding(content).await {
+        for (id, content) in entries {
+            let content = self.open_content(&content)?;
+            if let Ok(Some(emb)) = self.get_or_compute_embedding(&content).await {
                 let bytes = vector::vec_to_bytes(&emb);
-                let conn = self
-                    .conn
-                    .lock()
-                    .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-                conn.execute(
-                    "UPDATE memories SET embedding = ?1 WHERE id = ?2",
-                    params![bytes, id],
-                )?;
+                self.with_conn(move |conn| {
+                    conn.execute(
+                        "UPDATE memories SET embedding = ?1 WHERE id = ?2",
+                        params![bytes, id],
+                    )?;
+                    Ok(())
+                })
+                .await?;
                 count += 1;
             }
         }
@@ -346,656 +1345,2212 @@ impl Memory for SqliteMemory {
         content: &str,
         category: MemoryCategory,
     ) -> anyhow::Result<()> {
-        // Compute embedding (async, before lock)
-        let embedding_bytes = self
-            .get_or_compute_embedding(content)
-            .await?
-            .map(|emb| vector::vec_to_bytes(&emb));
+        let expires_at = self.default_expiry(&category);
+        self.upsert(key, content, category, None, expires_at, &[])
+            .await
+    }
 
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-        let now = Local::now().to_rfc3339();
-        let cat = Self::category_to_str(&category);
-        let id = Uuid::new_v4().to_string();
+    async fn store_many(&self, entries: &[(String, String, MemoryCategory)]) -> anyhow::Result<()> {
+        let mut rows = Vec::with_capacity(entries.len());
+        for (key, content, category) in entries {
+            let expires_at = self.default_expiry(category);
+            rows.extend(
+                self.prepare_rows(key, content, category, None, expires_at, &[])
+                    .await?,
+            );
+        }
+        if rows.is_empty() {
+            return Ok(());
+        }
 
-        conn.execute(
-            "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-             ON CONFLICT(key) DO UPDATE SET
-                content = excluded.content,
-                category = excluded.category,
-                embedding = excluded.embedding,
-                updated_at = excluded.updated_at",
-            params![id, key, content, cat, embedding_bytes, now, now],
-        )?;
+        // One transaction: a single commit instead of one per row
+        self.with_conn(move |conn| {
+            let tx = conn.unchecked_transaction()?;
+            for row in &rows {
+                Self::write_row(&tx, row)?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
 
-        Ok(())
+    async fn store_with_tags(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        tags: &[String],
+    ) -> anyhow::Result<()> {
+        let expires_at = self.default_expiry(&category);
+        self.upsert(key, content, category, None, expires_at, tags)
+            .await
+    }
+
+    async fn apply_batch(&self, ops: &[MemoryOp]) -> anyhow::Result<usize> {
+        // Embed and seal up front so the transaction only does SQL
+        let mut steps = Vec::with_capacity(ops.len());
+        for op in ops {
+            steps.push(match op {
+                MemoryOp::Store {
+                    key,
+                    content,
+                    category,
+                    tags,
+                } => {
+                    let expires_at = self.default_expiry(category);
+                    BatchStep::Write(
+                        self.prepare_rows(key, content, category, None, expires_at, tags)
+                            .await?,
+                    )
+                }
+                MemoryOp::Forget { key } => BatchStep::Trash(key.clone()),
+            });
+        }
+
+        self.with_conn(move |conn| {
+            // Dropped without commit on error, which rolls everything back
+            let tx = conn.unchecked_transaction()?;
+            let mut applied = 0;
+            for step in &steps {
+                match step {
+                    BatchStep::Write(rows) => {
+                        for row in rows {
+                            Self::write_row(&tx, row)?;
+                        }
+                        applied += 1;
+                    }
+                    BatchStep::Trash(key) => {
+                        if Self::trash(&tx, key)? {
+                            applied += 1;
+                        }
+                    }
+                }
+            }
+            tx.commit()?;
+            Ok(applied)
+        })
+        .await
+    }
+
+    async fn store_in_session(
+        &self,
+        session_id: &str,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+    ) -> anyhow::Result<()> {
+        let expires_at = self.default_expiry(&category);
+        self.upsert(key, content, category, Some(session_id), expires_at, &[])
+            .await
+    }
+
+    async fn store_with_ttl(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        ttl: std::time::Duration,
+    ) -> anyhow::Result<()> {
+        let ttl = chrono::Duration::from_std(ttl)
+            .map_err(|_| anyhow::anyhow!("TTL out of range: {ttl:?}"))?;
+        let expires_at = Self::expiry_timestamp(Utc::now() + ttl);
+        self.upsert(key, content, category, None, Some(expires_at), &[])
+            .await
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<usize> {
+        self.with_conn(Self::delete_expired).await
+    }
+
+    async fn prune_before(
+        &self,
+        category: &MemoryCategory,
+        cutoff: chrono::DateTime<Utc>,
+    ) -> anyhow::Result<usize> {
+        let category = Self::category_to_str(category);
+        let cutoff = Self::expiry_timestamp(cutoff);
+        // updated_at carries a local offset, so compare as instants
+        self.with_conn(move |conn| {
+            Ok(conn.execute(
+                "DELETE FROM memories
+                 WHERE category = ?1 AND julianday(updated_at) < julianday(?2)",
+                params![category, cutoff],
+            )?)
+        })
+        .await
     }
 
     async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
-        if query.trim().is_empty() {
+        self.search(query, limit, EntryFilter::default()).await
+    }
+
+    async fn recall_matching(
+        &self,
+        query: &str,
+        mode: MatchMode,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        if mode == MatchMode::Keyword {
+            return self.recall(query, limit).await;
+        }
+        let entries = self.match_entries(query, mode, limit).await?;
+        self.record_access(entries).await
+    }
+
+    async fn recall_tagged(
+        &self,
+        query: &str,
+        tag: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.search(query, limit, EntryFilter::tagged(tag)).await
+    }
+
+    async fn recall_in_range(
+        &self,
+        query: &str,
+        limit: usize,
+        range: &DateRange,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.search(query, limit, EntryFilter::within(range)).await
+    }
+
+    async fn recall_in_namespace(
+        &self,
+        namespace: &str,
+        query: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.search(query, limit, EntryFilter::namespace(namespace)?)
+            .await
+    }
+
+    async fn recall_session(
+        &self,
+        session_id: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let session_id = session_id.to_string();
+        let mut results = self
+            .with_conn(move |conn| {
+                Self::delete_expired(conn)?;
+                let mut stmt = conn.prepare(&format!(
+                    "{ENTRY_SELECT}
+                     WHERE session_id = ?1 AND deleted_at IS NULL AND parent_key IS NULL
+                     ORDER BY updated_at DESC, rowid DESC LIMIT ?2"
+                ))?;
+                #[allow(clippy::cast_possible_wrap)]
+                let rows =
+                    stmt.query_map(params![session_id, limit as i64], Self::entry_from_row)?;
+                Self::with_chunks(conn, rows.collect::<rusqlite::Result<Vec<_>>>()?)
+            })
+            .await?;
+
+        // Most recent `limit` entries, returned oldest first
+        results.reverse();
+        let results = results
+            .into_iter()
+            .map(|(e, chunks)| self.open_chunked(e, &chunks))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.record_access(results).await
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
+        let entry = self.fetch(key).await?;
+        Ok(self.record_access(entry.into_iter().collect()).await?.pop())
+    }
+
+    async fn get_many(&self, keys: &[&str]) -> anyhow::Result<Vec<MemoryEntry>> {
+        let keys: Vec<String> = distinct_keys(keys).into_iter().map(String::from).collect();
+        if keys.is_empty() {
             return Ok(Vec::new());
         }
+        let found = self
+            .with_conn(move |conn| {
+                Self::delete_expired(conn)?;
+                let mut found = HashMap::new();
+                for batch in keys.chunks(GET_MANY_BATCH) {
+                    let placeholders = vec!["?"; batch.len()].join(", ");
+                    let mut stmt = conn.prepare(&format!(
+                        "{ENTRY_SELECT} WHERE key IN ({placeholders}) AND deleted_at IS NULL"
+                    ))?;
+                    let rows =
+                        stmt.query_map(rusqlite::params_from_iter(batch), Self::entry_from_row)?;
+                    for entry in rows {
+                        let entry = entry?;
+                        found.insert(entry.key.clone(), entry);
+                    }
+                }
+                let ordered = keys.iter().filter_map(|k| found.remove(k)).collect();
+                Self::with_chunks(conn, ordered)
+            })
+            .await?;
 
-        // Compute query embedding (async, before lock)
-        let query_embedding = self.get_or_compute_embedding(query).await?;
+        let entries = found
+            .into_iter()
+            .map(|(e, chunks)| self.open_chunked(e, &chunks))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.record_access(entries).await
+    }
 
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+    async fn list(&self, category: Option<&MemoryCategory>) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.list_filtered(category, EntryFilter::default()).await
+    }
 
-        // FTS5 BM25 keyword search
-        let keyword_results = Self::fts5_search(&conn, query, limit * 2).unwrap_or_default();
+    async fn list_tagged(
+        &self,
+        tag: &str,
+        category: Option<&MemoryCategory>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.list_filtered(category, EntryFilter::tagged(tag)).await
+    }
 
-        // Vector similarity search (if embeddings available)
-        let vector_results = if let Some(ref qe) = query_embedding {
-            Self::vector_search(&conn, qe, limit * 2).unwrap_or_default()
-        } else {
-            Vec::new()
-        };
+    async fn list_in_range(
+        &self,
+        category: Option<&MemoryCategory>,
+        range: &DateRange,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.list_filtered(category, EntryFilter::within(range))
+            .await
+    }
 
-        // Hybrid merge
-        let merged = if vector_results.is_empty() {
-            // No embeddings — use keyword results only
-            keyword_results
-                .iter()
-                .map(|(id, score)| vector::ScoredResult {
-                    id: id.clone(),
-                    vector_score: None,
-                    keyword_score: Some(*score),
-                    final_score: *score,
-                })
-                .collect::<Vec<_>>()
-        } else {
-            vector::hybrid_merge(
-                &vector_results,
-                &keyword_results,
-                self.vector_weight,
-                self.keyword_weight,
-                limit,
-            )
-        };
+    async fn relate(&self, from: &str, to: &str, relation: &str) -> anyhow::Result<bool> {
+        if from == to {
+            anyhow::bail!("a memory can't be related to itself ('{from}')");
+        }
+        let (from, to) = (from.to_string(), to.to_string());
+        let relation = normalize_relation(relation);
+        self.with_conn(move |conn| {
+            let live: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM memories WHERE key IN (?1, ?2) AND deleted_at IS NULL",
+                params![from, to],
+                |row| row.get(0),
+            )?;
+            if live < 2 {
+                return Ok(false);
+            }
+            conn.execute(
+                "INSERT OR IGNORE INTO memory_relations (from_id, to_id, relation, created_at)
+                 SELECT f.id, t.id, ?3, ?4 FROM memories f, memories t
+                 WHERE f.key = ?1 AND t.key = ?2",
+                params![from, to, relation, Local::now().to_rfc3339()],
+            )?;
+            Ok(true)
+        })
+        .await
+    }
 
-        // Fetch full entries for merged results
-        let mut results = Vec::new();
-        for scored in &merged {
+    async fn unrelate(
+        &self,
+        from: &str,
+        to: &str,
+        relation: Option<&str>,
+    ) -> anyhow::Result<usize> {
+        let (from, to) = (from.to_string(), to.to_string());
+        let relation = relation.map(normalize_relation);
+        self.with_conn(move |conn| {
+            Ok(conn.execute(
+                "DELETE FROM memory_relations
+                 WHERE from_id = (SELECT id FROM memories WHERE key = ?1)
+                   AND to_id = (SELECT id FROM memories WHERE key = ?2)
+                   AND (?3 IS NULL OR relation = ?3)",
+                params![from, to, relation],
+            )?)
+        })
+        .await
+    }
+
+    async fn relations(&self, key: &str) -> anyhow::Result<Vec<MemoryRelation>> {
+        let key = key.to_string();
+        self.with_conn(move |conn| {
+            // Links to a trashed entry are hidden until it's restored
             let mut stmt = conn.prepare(
-                "SELECT id, key, content, category, created_at FROM memories WHERE id = ?1",
+                "SELECT f.key, t.key, r.relation FROM memory_relations r
+                 JOIN memories f ON f.id = r.from_id
+                 JOIN memories t ON t.id = r.to_id
+                 WHERE (f.key = ?1 OR t.key = ?1)
+                   AND f.deleted_at IS NULL AND t.deleted_at IS NULL
+                 ORDER BY r.created_at, r.rowid",
             )?;
-            if let Ok(entry) = stmt.query_row(params![scored.id], |row| {
-                Ok(MemoryEntry {
-                    id: row.get(0)?,
-                    key: row.get(1)?,
-                    content: row.get(2)?,
-                    category: Self::str_to_category(&row.get::<_, String>(3)?),
-                    timestamp: row.get(4)?,
-                    session_id: None,
-                    score: Some(f64::from(scored.final_score)),
+            let rows = stmt.query_map(params![key], |row| {
+                Ok(MemoryRelation {
+                    from: row.get(0)?,
+                    to: row.get(1)?,
+                    relation: row.get(2)?,
                 })
-            }) {
-                results.push(entry);
-            }
-        }
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
 
-        // If hybrid returned nothing, fall back to LIKE search
-        if results.is_empty() {
-            let keywords: Vec<String> =
-                query.split_whitespace().map(|w| format!("%{w}%")).collect();
-            if !keywords.is_empty() {
-                let conditions: Vec<String> = keywords
-                    .iter()
-                    .enumerate()
-                    .map(|(i, _)| {
-                        format!("(content LIKE ?{} OR key LIKE ?{})", i * 2 + 1, i * 2 + 2)
-                    })
-                    .collect();
-                let where_clause = conditions.join(" OR ");
-                let sql = format!(
-                    "SELECT id, key, content, category, created_at FROM memories
-                     WHERE {where_clause}
-                     ORDER BY updated_at DESC
-                     LIMIT ?{}",
-                    keywords.len() * 2 + 1
-                );
-                let mut stmt = conn.prepare(&sql)?;
-                let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
-                for kw in &keywords {
-                    param_values.push(Box::new(kw.clone()));
-                    param_values.push(Box::new(kw.clone()));
-                }
-                #[allow(clippy::cast_possible_wrap)]
-                param_values.push(Box::new(limit as i64));
-                let params_ref: Vec<&dyn rusqlite::types::ToSql> =
-                    param_values.iter().map(AsRef::as_ref).collect();
-                let rows = stmt.query_map(params_ref.as_slice(), |row| {
-                    Ok(MemoryEntry {
-                        id: row.get(0)?,
-                        key: row.get(1)?,
-                        content: row.get(2)?,
-                        category: Self::str_to_category(&row.get::<_, String>(3)?),
-                        timestamp: row.get(4)?,
-                        session_id: None,
-                        score: Some(1.0),
+    async fn list_in_namespace(
+        &self,
+        namespace: &str,
+        category: Option<&MemoryCategory>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.list_filtered(category, EntryFilter::namespace(namespace)?)
+            .await
+    }
+
+    async fn list_all(
+        &self,
+        category: Option<&MemoryCategory>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.list_filtered(category, EntryFilter::all_keys()).await
+    }
+
+    async fn forget(&self, key: &str) -> anyhow::Result<bool> {
+        let key = key.to_string();
+        self.with_conn(move |conn| Self::trash(conn, &key)).await
+    }
+
+    async fn list_trash(&self) -> anyhow::Result<Vec<TrashedEntry>> {
+        let trashed = self
+            .with_conn(|conn| {
+                // `ENTRY_SELECT` columns, then `deleted_at`
+                let mut stmt = conn.prepare(
+                    "SELECT id, key, content, category, created_at, session_id,
+                        (SELECT group_concat(tag, ',') FROM memory_tags WHERE memory_id = memories.id),
+                        importance, updated_at, last_accessed_at, access_count, deleted_at
+                     FROM memories WHERE deleted_at IS NOT NULL AND parent_key IS NULL
+                     ORDER BY deleted_at DESC",
+                )?;
+                let rows = stmt.query_map([], |row| {
+                    Ok(TrashedEntry {
+                        entry: Self::entry_from_row(row)?,
+                        deleted_at: row.get(11)?,
                     })
                 })?;
-                for row in rows {
-                    results.push(row?);
-                }
-            }
+                rows.map(|t| {
+                    let t = t?;
+                    let chunks = Self::chunk_contents(conn, &t.entry.key)?;
+                    Ok((t, chunks))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .await?;
+
+        trashed
+            .into_iter()
+            .map(|(t, chunks)| {
+                Ok(TrashedEntry {
+                    entry: self.open_chunked(t.entry, &chunks)?,
+                    deleted_at: t.deleted_at,
+                })
+            })
+            .collect()
+    }
+
+    async fn set_importance(&self, key: &str, importance: f64) -> anyhow::Result<bool> {
+        let key = key.to_string();
+        let importance = clamp_importance(importance);
+        self.with_conn(move |conn| {
+            let affected = conn.execute(
+                "UPDATE memories SET importance = ?1
+                 WHERE (key = ?2 OR parent_key = ?2) AND deleted_at IS NULL",
+                params![importance, key],
+            )?;
+            Ok(affected > 0)
+        })
+        .await
+    }
+
+    async fn update(&self, key: &str, patch: &MemoryPatch) -> anyhow::Result<MemoryEntry> {
+        let Some(current) = self.fetch(key).await? else {
+            anyhow::bail!("no memory with key '{key}'");
+        };
+        // Unlike a plain store, keep the entry's session and expiry
+        let lookup = key.to_string();
+        let expires_at: Option<String> = self
+            .with_conn(move |conn| {
+                Ok(conn.query_row(
+                    "SELECT expires_at FROM memories WHERE key = ?1",
+                    params![lookup],
+                    |row| row.get(0),
+                )?)
+            })
+            .await?;
+        let content = patch.content.as_deref().unwrap_or(&current.content);
+        let category = patch.category.clone().unwrap_or(current.category.clone());
+        let tags = patch.tags.as_ref().unwrap_or(&current.tags);
+        self.upsert(
+            key,
+            content,
+            category,
+            current.session_id.as_deref(),
+            expires_at,
+            tags,
+        )
+        .await?;
+        self.fetch(key)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("memory '{key}' vanished during update"))
+    }
+
+    async fn restore(&self, key: &str) -> anyhow::Result<bool> {
+        let key = key.to_string();
+        self.with_conn(move |conn| {
+            let affected = conn.execute(
+                "UPDATE memories SET deleted_at = NULL
+                 WHERE (key = ?1 OR parent_key = ?1) AND deleted_at IS NOT NULL",
+                params![key],
+            )?;
+            Ok(affected > 0)
+        })
+        .await
+    }
+
+    async fn purge_trash(&self, cutoff: chrono::DateTime<Utc>) -> anyhow::Result<usize> {
+        let cutoff = Self::expiry_timestamp(cutoff);
+        self.with_conn(move |conn| {
+            Ok(conn.execute(
+                "DELETE FROM memories WHERE deleted_at IS NOT NULL AND deleted_at <= ?1",
+                params![cutoff],
+            )?)
+        })
+        .await
+    }
+
+    fn tracks_access(&self) -> bool {
+        true
+    }
+
+    async fn embedding_cache_stats(&self) -> anyhow::Result<Option<EmbeddingCacheStats>> {
+        if self.embedder.dimensions() == 0 {
+            return Ok(None);
         }
+        let capacity = self.cache_max;
+        self.with_conn(move |conn| {
+            let entries: i64 =
+                conn.query_row("SELECT COUNT(*) FROM embedding_cache", [], |row| row.get(0))?;
+            let (hits, misses): (i64, i64) = conn.query_row(
+                "SELECT hits, misses FROM embedding_cache_stats WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+            Ok(Some(EmbeddingCacheStats {
+                entries: entries as usize,
+                capacity,
+                hits: hits as u64,
+                misses: misses as u64,
+            }))
+        })
+        .await
+    }
+
+    async fn count(&self) -> anyhow::Result<usize> {
+        self.with_conn(|conn| {
+            Self::delete_expired(conn)?;
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM memories WHERE deleted_at IS NULL AND parent_key IS NULL",
+                [],
+                |row| row.get(0),
+            )?;
+            #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+            Ok(count as usize)
+        })
+        .await
+    }
+
+    async fn health_check(&self) -> bool {
+        self.with_conn(|conn| Ok(conn.execute_batch("SELECT 1").is_ok()))
+            .await
+            .unwrap_or(false)
+    }
+}
+
+/// Escape GLOB wildcards so `text` matches literally.
+fn glob_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '*' | '?' | '[' => {
+                out.push('[');
+                out.push(c);
+                out.push(']');
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn temp_sqlite() -> (TempDir, SqliteMemory) {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        (tmp, mem)
+    }
+
+    #[tokio::test]
+    async fn sqlite_name() {
+        let (_tmp, mem) = temp_sqlite();
+        assert_eq!(mem.name(), "sqlite");
+    }
+
+    #[tokio::test]
+    async fn sqlite_health() {
+        let (_tmp, mem) = temp_sqlite();
+        assert!(mem.health_check().await);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[allow(clippy::await_holding_lock)]
+    async fn sqlite_concurrent_access_does_not_block_runtime() {
+        let (_tmp, mem) = temp_sqlite();
+        let mem = Arc::new(mem);
+
+        // Hold the connection lock so queued queries wait on the blocking pool.
+        let guard = mem.conn.lock().unwrap();
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let mem = Arc::clone(&mem);
+                tokio::spawn(async move {
+                    mem.store(&format!("k{i}"), "v", MemoryCategory::Core)
+                        .await
+                        .unwrap();
+                })
+            })
+            .collect();
+
+        // The executor keeps making progress while the database is busy.
+        tokio::time::timeout(Duration::from_secs(1), tokio::task::yield_now())
+            .await
+            .unwrap();
+        drop(guard);
+
+        for writer in writers {
+            writer.await.unwrap();
+        }
+        assert_eq!(mem.count().await.unwrap(), 8);
+    }
+
+    #[tokio::test]
+    async fn sqlite_store_and_get() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("user_lang", "Prefers Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let entry = mem.get("user_lang").await.unwrap();
+        assert!(entry.is_some());
+        let entry = entry.unwrap();
+        assert_eq!(entry.key, "user_lang");
+        assert_eq!(entry.content, "Prefers Rust");
+        assert_eq!(entry.category, MemoryCategory::Core);
+    }
+
+    #[tokio::test]
+    async fn sqlite_store_upsert() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("pref", "likes Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("pref", "loves Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let entry = mem.get("pref").await.unwrap().unwrap();
+        assert_eq!(entry.content, "loves Rust");
+        assert_eq!(mem.count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn sqlite_recall_keyword() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "Rust is fast and safe", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("b", "Python is interpreted", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("c", "Rust has zero-cost abstractions", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let results = mem.recall("Rust", 10).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|r| r.content.to_lowercase().contains("rust")));
+    }
+
+    #[tokio::test]
+    async fn sqlite_recall_multi_keyword() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "Rust is fast", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("b", "Rust is safe and fast", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let results = mem.recall("fast safe", 10).await.unwrap();
+        assert!(!results.is_empty());
+        // Entry with both keywords should score higher
+        assert!(results[0].content.contains("safe") && results[0].content.contains("fast"));
+    }
+
+    #[tokio::test]
+    async fn sqlite_recall_no_match() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "Rust rocks", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let results = mem.recall("javascript", 10).await.unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn sqlite_forget() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("temp", "temporary data", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        assert_eq!(mem.count().await.unwrap(), 1);
+
+        let removed = mem.forget("temp").await.unwrap();
+        assert!(removed);
+        assert_eq!(mem.count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn sqlite_forget_nonexistent() {
+        let (_tmp, mem) = temp_sqlite();
+        let removed = mem.forget("nope").await.unwrap();
+        assert!(!removed);
+    }
+
+    #[tokio::test]
+    async fn sqlite_list_all() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "one", MemoryCategory::Core).await.unwrap();
+        mem.store("b", "two", MemoryCategory::Daily).await.unwrap();
+        mem.store("c", "three", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+
+        let all = mem.list(None).await.unwrap();
+        assert_eq!(all.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn sqlite_list_by_category() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "core1", MemoryCategory::Core).await.unwrap();
+        mem.store("b", "core2", MemoryCategory::Core).await.unwrap();
+        mem.store("c", "daily1", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let core = mem.list(Some(&MemoryCategory::Core)).await.unwrap();
+        assert_eq!(core.len(), 2);
+
+        let daily = mem.list(Some(&MemoryCategory::Daily)).await.unwrap();
+        assert_eq!(daily.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn sqlite_count_empty() {
+        let (_tmp, mem) = temp_sqlite();
+        assert_eq!(mem.count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn sqlite_get_nonexistent() {
+        let (_tmp, mem) = temp_sqlite();
+        assert!(mem.get("nope").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn sqlite_db_persists() {
+        let tmp = TempDir::new().unwrap();
+
+        {
+            let mem = SqliteMemory::new(tmp.path()).unwrap();
+            mem.store("persist", "I survive restarts", MemoryCategory::Core)
+                .await
+                .unwrap();
+        }
+
+        // Reopen
+        let mem2 = SqliteMemory::new(tmp.path()).unwrap();
+        let entry = mem2.get("persist").await.unwrap();
+        assert!(entry.is_some());
+        assert_eq!(entry.unwrap().content, "I survive restarts");
+    }
+
+    #[tokio::test]
+    async fn sqlite_category_roundtrip() {
+        let (_tmp, mem) = temp_sqlite();
+        let categories = [
+            MemoryCategory::Core,
+            MemoryCategory::Daily,
+            MemoryCategory::Conversation,
+            MemoryCategory::Custom("project".into()),
+        ];
+
+        for (i, cat) in categories.iter().enumerate() {
+            mem.store(&format!("k{i}"), &format!("v{i}"), cat.clone())
+                .await
+                .unwrap();
+        }
+
+        for (i, cat) in categories.iter().enumerate() {
+            let entry = mem.get(&format!("k{i}")).await.unwrap().unwrap();
+            assert_eq!(&entry.category, cat);
+        }
+    }
+
+    // ── FTS5 search tests ────────────────────────────────────────
+
+    #[tokio::test]
+    async fn fts5_bm25_ranking() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store(
+            "a",
+            "Rust is a systems programming language",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+        mem.store("b", "Python is great for scripting", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store(
+            "c",
+            "Rust and Rust and Rust everywhere",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+
+        let results = mem.recall("Rust", 10).await.unwrap();
+        assert!(results.len() >= 2);
+        // All results should contain "Rust"
+        for r in &results {
+            assert!(
+                r.content.to_lowercase().contains("rust"),
+                "Expected 'rust' in: {}",
+                r.content
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn fts5_multi_word_query() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "The quick brown fox jumps", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("b", "A lazy dog sleeps", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("c", "The quick dog runs fast", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let results = mem.recall("quick dog", 10).await.unwrap();
+        assert!(!results.is_empty());
+        // "The quick dog runs fast" matches both terms
+        assert!(results[0].content.contains("quick"));
+    }
+
+    #[tokio::test]
+    async fn recall_empty_query_returns_empty() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "data", MemoryCategory::Core).await.unwrap();
+        let results = mem.recall("", 10).await.unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn recall_whitespace_query_returns_empty() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "data", MemoryCategory::Core).await.unwrap();
+        let results = mem.recall("   ", 10).await.unwrap();
+        assert!(results.is_empty());
+    }
+
+    // ── Embedding cache tests ────────────────────────────────────
+
+    #[test]
+    fn content_hash_deterministic() {
+        let h1 = SqliteMemory::content_hash("hello world");
+        let h2 = SqliteMemory::content_hash("hello world");
+        assert_eq!(h1, h2);
+    }
+
+    #[test]
+    fn content_hash_different_inputs() {
+        let h1 = SqliteMemory::content_hash("hello");
+        let h2 = SqliteMemory::content_hash("world");
+        assert_ne!(h1, h2);
+    }
+
+    // ── Schema tests ─────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn schema_has_fts5_table() {
+        let (_tmp, mem) = temp_sqlite();
+        let conn = mem.conn.lock().unwrap();
+        // FTS5 table should exist
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='memories_fts'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn schema_has_embedding_cache() {
+        let (_tmp, mem) = temp_sqlite();
+        let conn = mem.conn.lock().unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='embedding_cache'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn connection_uses_wal_and_busy_timeout() {
+        let (_tmp, mem) = temp_sqlite();
+        let conn = mem.conn.lock().unwrap();
+        let mode: String = conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+        let sync: i64 = conn
+            .query_row("PRAGMA synchronous", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(sync, 1); // NORMAL
+        let timeout: i64 = conn
+            .query_row("PRAGMA busy_timeout", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(timeout, 5_000);
+    }
+
+    #[tokio::test]
+    async fn busy_timeout_is_configurable() {
+        let (_tmp, mem) = temp_sqlite();
+        let mem = mem.with_busy_timeout_ms(250).unwrap();
+        let conn = mem.conn.lock().unwrap();
+        let timeout: i64 = conn
+            .query_row("PRAGMA busy_timeout", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(timeout, 250);
+    }
+
+    #[tokio::test]
+    async fn schema_memories_has_embedding_column() {
+        let (_tmp, mem) = temp_sqlite();
+        let conn = mem.conn.lock().unwrap();
+        // Check that embedding column exists by querying it
+        let result = conn.execute_batch("SELECT embedding FROM memories LIMIT 0");
+        assert!(result.is_ok());
+    }
+
+    // ── FTS5 sync trigger tests ──────────────────────────────────
+
+    #[tokio::test]
+    async fn fts5_syncs_on_insert() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("test_key", "unique_searchterm_xyz", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let conn = mem.conn.lock().unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH '\"unique_searchterm_xyz\"'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn fts5_syncs_on_delete() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("del_key", "deletable_content_abc", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.forget("del_key").await.unwrap();
+
+        let conn = mem.conn.lock().unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH '\"deletable_content_abc\"'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn fts5_syncs_on_update() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("upd_key", "original_content_111", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("upd_key", "updated_content_222", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let conn = mem.conn.lock().unwrap();
+        // Old content should not be findable
+        let old: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH '\"original_content_111\"'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(old, 0);
+
+        // New content should be findable
+        let new: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH '\"updated_content_222\"'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(new, 1);
+    }
+
+    // ── With-embedder constructor test ───────────────────────────
+
+    #[test]
+    fn with_embedder_noop() {
+        let tmp = TempDir::new().unwrap();
+        let embedder = Arc::new(super::super::embeddings::NoopEmbedding);
+        let mem = SqliteMemory::with_embedder(tmp.path(), embedder, 0.7, 0.3, 1000);
+        assert!(mem.is_ok());
+        assert_eq!(mem.unwrap().name(), "sqlite");
+    }
+
+    // ── Semantic search ──────────────────────────────────────────
+
+    /// Maps words onto fixed "concept" axes so related texts share a vector
+    /// without sharing keywords.
+    struct ConceptEmbedding;
+
+    #[async_trait]
+    impl EmbeddingProvider for ConceptEmbedding {
+        fn name(&self) -> &str {
+            "concept"
+        }
+
+        fn dimensions(&self) -> usize {
+            3
+        }
+
+        async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let mut v = vec![0.0_f32; 3];
+                    for word in text.to_lowercase().split_whitespace() {
+                        match word {
+                            "editor" | "neovim" | "vim" | "emacs" => v[0] += 1.0,
+                            "food" | "pizza" | "sushi" => v[1] += 1.0,
+                            _ => v[2] += 0.1,
+                        }
+                    }
+                    v
+                })
+                .collect())
+        }
+    }
+
+    fn semantic_sqlite(mode: SearchMode) -> (TempDir, SqliteMemory) {
+        let tmp = TempDir::new().unwrap();
+        let mem =
+            SqliteMemory::with_embedder(tmp.path(), Arc::new(ConceptEmbedding), 0.7, 0.3, 100)
+                .unwrap()
+                .with_search_mode(mode);
+        (tmp, mem)
+    }
+
+    #[test]
+    fn search_mode_from_config() {
+        assert_eq!(SearchMode::from_config("semantic"), SearchMode::Semantic);
+        assert_eq!(SearchMode::from_config("keyword"), SearchMode::Keyword);
+        assert_eq!(SearchMode::from_config("hybrid"), SearchMode::Hybrid);
+        assert_eq!(SearchMode::from_config("bogus"), SearchMode::Hybrid);
+    }
+
+    #[tokio::test]
+    async fn semantic_recall_matches_without_keyword_overlap() {
+        let (_tmp, mem) = semantic_sqlite(SearchMode::Semantic);
+        mem.store("editor_pref", "prefers neovim", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("lunch", "ordered pizza", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let results = mem
+            .recall("what editor does the user like", 5)
+            .await
+            .unwrap();
+        assert_eq!(results[0].key, "editor_pref");
+        assert!(results[0].score.unwrap() > 0.9);
+    }
+
+    #[tokio::test]
+    async fn semantic_mode_persists_vectors() {
+        let (_tmp, mem) = semantic_sqlite(SearchMode::Semantic);
+        mem.store("k", "prefers neovim", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let conn = mem.conn.lock().unwrap();
+        let blob: Vec<u8> = conn
+            .query_row(
+                "SELECT embedding FROM memories WHERE key = 'k'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(vector::bytes_to_vec(&blob).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn keyword_mode_ignores_vectors() {
+        let (_tmp, mem) = semantic_sqlite(SearchMode::Keyword);
+        mem.store("pref", "prefers neovim", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let results = mem.recall("editor", 5).await.unwrap();
+        assert!(results.is_empty());
+        let results = mem.recall("neovim", 5).await.unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn embedding_cache_reuses_vectors_and_counts_hits() {
+        let (tmp, mem) = semantic_sqlite(SearchMode::Semantic);
+        mem.store("a", "prefers neovim", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("b", "prefers neovim", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("a", "prefers neovim", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let stats = mem.embedding_cache_stats().await.unwrap().unwrap();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.capacity, 100);
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert!((stats.hit_rate().unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        drop(mem);
+
+        // Counters and vectors survive a reopen
+        let reopened =
+            SqliteMemory::with_embedder(tmp.path(), Arc::new(ConceptEmbedding), 0.7, 0.3, 100)
+                .unwrap();
+        reopened
+            .store("c", "prefers neovim", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let stats = reopened.embedding_cache_stats().await.unwrap().unwrap();
+        assert_eq!((stats.hits, stats.misses), (3, 1));
+    }
+
+    #[tokio::test]
+    async fn embedding_cache_stats_absent_without_embedder() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("k", "v", MemoryCategory::Core).await.unwrap();
+        assert!(mem.embedding_cache_stats().await.unwrap().is_none());
+    }
+
+    #[test]
+    fn hybrid_fusion_from_config() {
+        assert_eq!(
+            HybridFusion::from_config("rrf", 10),
+            HybridFusion::Rrf { k: 10.0 }
+        );
+        assert_eq!(
+            HybridFusion::from_config("weighted", 10),
+            HybridFusion::Weighted
+        );
+        assert_eq!(
+            HybridFusion::from_config("bogus", 60),
+            HybridFusion::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn hybrid_rrf_ranks_agreement_first_and_exposes_scores() {
+        let (_tmp, mem) = semantic_sqlite(SearchMode::Hybrid);
+        mem.store(
+            "editor_pref",
+            "favorite editor is neovim",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+        mem.store("vim_note", "uses vim daily", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let results = mem.recall("editor", 5).await.unwrap();
+        assert_eq!(results.len(), 2);
+        // Top hit in both the vector and the keyword list
+        assert_eq!(results[0].key, "editor_pref");
+        assert!((results[0].score.unwrap() - 1.0).abs() < 0.001);
+        // Vector-only hit still surfaces, with a lower fused score
+        assert_eq!(results[1].key, "vim_note");
+        let score = results[1].score.unwrap();
+        assert!(score > 0.0 && score < 1.0);
+    }
+
+    // ── Expiry ───────────────────────────────────────────────────
+
+    fn expires_at(mem: &SqliteMemory, key: &str) -> Option<String> {
+        let conn = mem.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT expires_at FROM memories WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_hidden_and_purged() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store_with_ttl("gone", "short lived", MemoryCategory::Core, Duration::ZERO)
+            .await
+            .unwrap();
+        mem.store_with_ttl(
+            "kept",
+            "long lived",
+            MemoryCategory::Core,
+            Duration::from_secs(3600),
+        )
+        .await
+        .unwrap();
+
+        assert!(mem.get("gone").await.unwrap().is_none());
+        assert!(mem.get("kept").await.unwrap().is_some());
+        assert!(mem
+            .recall("lived", 10)
+            .await
+            .unwrap()
+            .iter()
+            .all(|e| e.key == "kept"));
+        assert_eq!(mem.count().await.unwrap(), 1);
+        assert_eq!(mem.purge_expired().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn purge_expired_deletes_rows() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store_with_ttl("a", "x", MemoryCategory::Core, Duration::ZERO)
+            .await
+            .unwrap();
+        mem.store("b", "y", MemoryCategory::Core).await.unwrap();
+        assert_eq!(mem.purge_expired().await.unwrap(), 1);
+        assert!(mem.get("b").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn plain_store_clears_previous_ttl() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store_with_ttl("k", "v1", MemoryCategory::Core, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(expires_at(&mem, "k").is_some());
+        mem.store("k", "v2", MemoryCategory::Core).await.unwrap();
+        assert!(expires_at(&mem, "k").is_none());
+    }
+
+    #[tokio::test]
+    async fn conversation_ttl_applies_only_to_conversation() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path())
+            .unwrap()
+            .with_conversation_ttl_days(7);
+        mem.store("chat", "hi", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("fact", "likes rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let expiry = expires_at(&mem, "chat").unwrap();
+        assert!(expiry > SqliteMemory::expiry_timestamp(Utc::now() + chrono::Duration::days(6)));
+        assert!(expires_at(&mem, "fact").is_none());
+    }
+
+    #[tokio::test]
+    async fn schema_migrates_legacy_db_without_expiry() {
+        let tmp = TempDir::new().unwrap();
+        let db_dir = tmp.path().join("memory");
+        std::fs::create_dir_all(&db_dir).unwrap();
+        {
+            let conn = Connection::open(db_dir.join("brain.db")).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE memories (
+                    id TEXT PRIMARY KEY, key TEXT NOT NULL UNIQUE, content TEXT NOT NULL,
+                    category TEXT NOT NULL DEFAULT 'core', embedding BLOB,
+                    created_at TEXT NOT NULL, updated_at TEXT NOT NULL
+                );
+                INSERT INTO memories VALUES ('1', 'old', 'legacy', 'core', NULL, 't', 't');",
+            )
+            .unwrap();
+        }
+
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        assert!(mem.get("old").await.unwrap().is_some());
+        assert!(expires_at(&mem, "old").is_none());
+    }
+
+    // ── Encryption at rest ───────────────────────────────────────
 
-        results.truncate(limit);
-        Ok(results)
+    const TEST_KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    fn raw_content(mem: &SqliteMemory, key: &str) -> String {
+        let conn = mem.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT content FROM memories WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .unwrap()
     }
 
-    async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+    #[tokio::test]
+    async fn encrypted_content_is_sealed_on_disk() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path())
+            .unwrap()
+            .with_cipher(ContentCipher::from_hex_key(TEST_KEY).unwrap())
+            .unwrap();
+        mem.store("user_pref", "prefers neovim", MemoryCategory::Core)
+            .await
+            .unwrap();
 
-        let mut stmt = conn.prepare(
-            "SELECT id, key, content, category, created_at FROM memories WHERE key = ?1",
-        )?;
+        assert!(!raw_content(&mem, "user_pref").contains("neovim"));
+        assert_eq!(
+            mem.get("user_pref").await.unwrap().unwrap().content,
+            "prefers neovim"
+        );
+        assert_eq!(mem.list(None).await.unwrap()[0].content, "prefers neovim");
+        let recalled = mem.recall("user_pref", 5).await.unwrap();
+        assert_eq!(recalled[0].content, "prefers neovim");
+    }
 
-        let mut rows = stmt.query_map(params![key], |row| {
-            Ok(MemoryEntry {
-                id: row.get(0)?,
-                key: row.get(1)?,
-                content: row.get(2)?,
-                category: Self::str_to_category(&row.get::<_, String>(3)?),
-                timestamp: row.get(4)?,
-                session_id: None,
-                score: None,
-            })
-        })?;
+    #[tokio::test]
+    async fn enabling_encryption_seals_existing_rows() {
+        let tmp = TempDir::new().unwrap();
+        {
+            let mem = SqliteMemory::new(tmp.path()).unwrap();
+            mem.store("old", "plaintext note", MemoryCategory::Core)
+                .await
+                .unwrap();
+        }
+
+        let mem = SqliteMemory::new(tmp.path())
+            .unwrap()
+            .with_cipher(ContentCipher::from_hex_key(TEST_KEY).unwrap())
+            .unwrap();
+        assert!(ContentCipher::is_encrypted(&raw_content(&mem, "old")));
+        assert_eq!(
+            mem.get("old").await.unwrap().unwrap().content,
+            "plaintext note"
+        );
 
-        match rows.next() {
-            Some(Ok(entry)) => Ok(Some(entry)),
-            _ => Ok(None),
+        // No stale copy survives in FTS segments, the WAL or free pages
+        for entry in std::fs::read_dir(tmp.path().join("memory")).unwrap() {
+            let bytes = std::fs::read(entry.unwrap().path()).unwrap();
+            assert!(!bytes
+                .windows(b"plaintext note".len())
+                .any(|w| w == b"plaintext note"));
         }
     }
 
-    async fn list(&self, category: Option<&MemoryCategory>) -> anyhow::Result<Vec<MemoryEntry>> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+    #[tokio::test]
+    async fn encrypted_recall_matches_by_meaning_not_keywords() {
+        let (_tmp, mem) = semantic_sqlite(SearchMode::Semantic);
+        let mem = mem
+            .with_cipher(ContentCipher::from_hex_key(TEST_KEY).unwrap())
+            .unwrap();
+        mem.store("tool", "prefers neovim", MemoryCategory::Core)
+            .await
+            .unwrap();
+        assert!(ContentCipher::is_encrypted(&raw_content(&mem, "tool")));
 
-        let mut results = Vec::new();
+        let hits = mem.recall("editor", 5).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].content, "prefers neovim");
 
-        let row_mapper = |row: &rusqlite::Row| -> rusqlite::Result<MemoryEntry> {
-            Ok(MemoryEntry {
-                id: row.get(0)?,
-                key: row.get(1)?,
-                content: row.get(2)?,
-                category: Self::str_to_category(&row.get::<_, String>(3)?),
-                timestamp: row.get(4)?,
-                session_id: None,
-                score: None,
-            })
-        };
+        // The keyword index only ever saw ciphertext
+        let conn = mem.conn.lock().unwrap();
+        let indexed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH '\"neovim\"'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, 0);
+    }
 
-        if let Some(cat) = category {
-            let cat_str = Self::category_to_str(cat);
-            let mut stmt = conn.prepare(
-                "SELECT id, key, content, category, created_at FROM memories
-                 WHERE category = ?1 ORDER BY updated_at DESC",
-            )?;
-            let rows = stmt.query_map(params![cat_str], row_mapper)?;
-            for row in rows {
-                results.push(row?);
-            }
-        } else {
-            let mut stmt = conn.prepare(
-                "SELECT id, key, content, category, created_at FROM memories
-                 ORDER BY updated_at DESC",
-            )?;
-            let rows = stmt.query_map([], row_mapper)?;
-            for row in rows {
-                results.push(row?);
-            }
+    // ── Sessions ─────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn recall_session_groups_entries_oldest_first() {
+        let (_tmp, mem) = temp_sqlite();
+        for (session, key, content) in [
+            ("a", "a1", "hello"),
+            ("b", "b1", "other chat"),
+            ("a", "a2", "how are you"),
+            ("a", "a3", "bye"),
+        ] {
+            mem.store_in_session(session, key, content, MemoryCategory::Conversation)
+                .await
+                .unwrap();
         }
+        mem.store("loose", "no session", MemoryCategory::Core)
+            .await
+            .unwrap();
 
-        Ok(results)
-    }
+        let all: Vec<String> = mem
+            .recall_session("a", 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(all, vec!["a1", "a2", "a3"]);
 
-    async fn forget(&self, key: &str) -> anyhow::Result<bool> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-        let affected = conn.execute("DELETE FROM memories WHERE key = ?1", params![key])?;
-        Ok(affected > 0)
+        let recent = mem.recall_session("a", 2).await.unwrap();
+        assert_eq!(recent[0].key, "a2");
+        assert_eq!(recent[1].session_id.as_deref(), Some("a"));
+        assert!(mem.recall_session("missing", 10).await.unwrap().is_empty());
     }
 
-    async fn count(&self) -> anyhow::Result<usize> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-        let count: i64 = conn.query_row("SELECT COUNT(*) FROM memories", [], |row| row.get(0))?;
-        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-        Ok(count as usize)
-    }
+    #[tokio::test]
+    async fn session_id_roundtrips_through_reads() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store_in_session("s1", "k", "in session", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("plain", "no session", MemoryCategory::Core)
+            .await
+            .unwrap();
 
-    async fn health_check(&self) -> bool {
-        self.conn
-            .lock()
-            .map(|c| c.execute_batch("SELECT 1").is_ok())
-            .unwrap_or(false)
+        let entry = mem.get("k").await.unwrap().unwrap();
+        assert_eq!(entry.session_id.as_deref(), Some("s1"));
+        assert!(mem
+            .get("plain")
+            .await
+            .unwrap()
+            .unwrap()
+            .session_id
+            .is_none());
+        let recalled = mem.recall("session", 5).await.unwrap();
+        assert!(recalled
+            .iter()
+            .any(|e| e.key == "k" && e.session_id.as_deref() == Some("s1")));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+    // ── Bulk store ───────────────────────────────────────────────
 
-    fn temp_sqlite() -> (TempDir, SqliteMemory) {
-        let tmp = TempDir::new().unwrap();
-        let mem = SqliteMemory::new(tmp.path()).unwrap();
-        (tmp, mem)
+    #[tokio::test]
+    async fn store_many_writes_all_rows() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("existing", "old", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let entries: Vec<_> = (0..50)
+            .map(|i| {
+                (
+                    format!("bulk_{i}"),
+                    format!("bulk row {i}"),
+                    MemoryCategory::Daily,
+                )
+            })
+            .chain([("existing".into(), "new".into(), MemoryCategory::Core)])
+            .collect();
+        mem.store_many(&entries).await.unwrap();
+
+        assert_eq!(mem.count().await.unwrap(), 51);
+        assert_eq!(mem.get("existing").await.unwrap().unwrap().content, "new");
+        let hit = mem.get("bulk_7").await.unwrap().unwrap();
+        assert_eq!(hit.content, "bulk row 7");
+        assert_eq!(hit.category, MemoryCategory::Daily);
+        assert!(!mem.recall("bulk", 5).await.unwrap().is_empty());
     }
 
     #[tokio::test]
-    async fn sqlite_name() {
+    async fn store_many_empty_is_noop() {
         let (_tmp, mem) = temp_sqlite();
-        assert_eq!(mem.name(), "sqlite");
+        mem.store_many(&[]).await.unwrap();
+        assert_eq!(mem.count().await.unwrap(), 0);
+    }
+
+    // ── Tags ─────────────────────────────────────────────────────
+
+    fn tags(list: &[&str]) -> Vec<String> {
+        list.iter().map(ToString::to_string).collect()
     }
 
     #[tokio::test]
-    async fn sqlite_health() {
+    async fn tags_roundtrip_normalized() {
         let (_tmp, mem) = temp_sqlite();
-        assert!(mem.health_check().await);
+        mem.store_with_tags(
+            "stack",
+            "Rust and Postgres",
+            MemoryCategory::Core,
+            &tags(&["Project-X", " infra ", "project-x"]),
+        )
+        .await
+        .unwrap();
+
+        let entry = mem.get("stack").await.unwrap().unwrap();
+        assert_eq!(entry.tags, vec!["infra", "project-x"]);
+        assert!(entry.has_tag("PROJECT-X"));
     }
 
     #[tokio::test]
-    async fn sqlite_store_and_get() {
+    async fn list_and_recall_filter_by_tag() {
         let (_tmp, mem) = temp_sqlite();
-        mem.store("user_lang", "Prefers Rust", MemoryCategory::Core)
+        mem.store_with_tags(
+            "x_db",
+            "database is Postgres",
+            MemoryCategory::Core,
+            &tags(&["project-x"]),
+        )
+        .await
+        .unwrap();
+        mem.store_with_tags(
+            "y_db",
+            "database is SQLite",
+            MemoryCategory::Daily,
+            &tags(&["project-y"]),
+        )
+        .await
+        .unwrap();
+        mem.store("plain_db", "database notes", MemoryCategory::Core)
             .await
             .unwrap();
 
-        let entry = mem.get("user_lang").await.unwrap();
-        assert!(entry.is_some());
-        let entry = entry.unwrap();
-        assert_eq!(entry.key, "user_lang");
-        assert_eq!(entry.content, "Prefers Rust");
-        assert_eq!(entry.category, MemoryCategory::Core);
+        let listed = mem.list_tagged("project-x", None).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].key, "x_db");
+        assert!(mem
+            .list_tagged("project-y", Some(&MemoryCategory::Core))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let recalled = mem
+            .recall_tagged("database", "Project-Y", 10)
+            .await
+            .unwrap();
+        assert_eq!(recalled.len(), 1);
+        assert_eq!(recalled[0].key, "y_db");
+        assert_eq!(mem.recall("database", 10).await.unwrap().len(), 3);
     }
 
     #[tokio::test]
-    async fn sqlite_store_upsert() {
+    async fn overwrite_replaces_tags_and_purge_drops_them() {
         let (_tmp, mem) = temp_sqlite();
-        mem.store("pref", "likes Rust", MemoryCategory::Core)
+        mem.store_with_tags("k", "v1", MemoryCategory::Core, &tags(&["a", "b"]))
             .await
             .unwrap();
-        mem.store("pref", "loves Rust", MemoryCategory::Core)
+        mem.store_with_tags("k", "v2", MemoryCategory::Core, &tags(&["c"]))
             .await
             .unwrap();
+        assert_eq!(mem.get("k").await.unwrap().unwrap().tags, vec!["c"]);
 
-        let entry = mem.get("pref").await.unwrap().unwrap();
-        assert_eq!(entry.content, "loves Rust");
-        assert_eq!(mem.count().await.unwrap(), 1);
+        mem.forget("k").await.unwrap();
+        let trashed = mem.list_trash().await.unwrap();
+        assert_eq!(trashed[0].entry.tags, vec!["c"]);
+
+        mem.purge_trash(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        let conn = mem.conn.lock().unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM memory_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
     }
 
+    // ── Match modes ──────────────────────────────────────────────
+
     #[tokio::test]
-    async fn sqlite_recall_keyword() {
+    async fn match_modes_run_in_sql() {
         let (_tmp, mem) = temp_sqlite();
-        mem.store("a", "Rust is fast and safe", MemoryCategory::Core)
+        for (key, content) in [
+            ("slack_user_1", "Alice, prefers email"),
+            ("slack_user_22", "Bob [admin]"),
+            ("slack_channel", "general"),
+            ("note", "ping slack_user_1 about 50% off*"),
+        ] {
+            mem.store(key, content, MemoryCategory::Core).await.unwrap();
+        }
+        mem.forget("slack_channel").await.unwrap();
+        let keys = |entries: Vec<MemoryEntry>| -> Vec<String> {
+            entries.into_iter().map(|e| e.key).collect()
+        };
+
+        let hits = mem
+            .recall_matching("^slack_user_\\d+$", MatchMode::Regex, 10)
             .await
             .unwrap();
-        mem.store("b", "Python is interpreted", MemoryCategory::Core)
+        assert_eq!(keys(hits), vec!["slack_user_22", "slack_user_1"]);
+
+        let hits = mem
+            .recall_matching("slack_", MatchMode::Prefix, 10)
             .await
             .unwrap();
-        mem.store("c", "Rust has zero-cost abstractions", MemoryCategory::Core)
+        assert_eq!(keys(hits), vec!["slack_user_22", "slack_user_1"]);
+        let hits = mem
+            .recall_matching("slack_user_1", MatchMode::Prefix, 1)
             .await
             .unwrap();
+        assert_eq!(hits.len(), 1);
 
-        let results = mem.recall("Rust", 10).await.unwrap();
-        assert_eq!(results.len(), 2);
-        assert!(results
-            .iter()
-            .all(|r| r.content.to_lowercase().contains("rust")));
-    }
-
-    #[tokio::test]
-    async fn sqlite_recall_multi_keyword() {
-        let (_tmp, mem) = temp_sqlite();
-        mem.store("a", "Rust is fast", MemoryCategory::Core)
+        // Wildcards in the query are literal
+        assert!(mem
+            .recall_matching("slack_*", MatchMode::Prefix, 10)
             .await
-            .unwrap();
-        mem.store("b", "Rust is safe and fast", MemoryCategory::Core)
+            .unwrap()
+            .is_empty());
+        let hits = mem
+            .recall_matching("50% off*", MatchMode::Exact, 10)
             .await
             .unwrap();
+        assert_eq!(keys(hits), vec!["note"]);
+        assert!(mem
+            .recall_matching("bob", MatchMode::Exact, 10)
+            .await
+            .unwrap()
+            .is_empty());
 
-        let results = mem.recall("fast safe", 10).await.unwrap();
-        assert!(!results.is_empty());
-        // Entry with both keywords should score higher
-        assert!(results[0].content.contains("safe") && results[0].content.contains("fast"));
+        assert!(mem
+            .recall_matching("([", MatchMode::Regex, 10)
+            .await
+            .is_err());
+        let ranked = mem
+            .recall_matching("email", MatchMode::Keyword, 10)
+            .await
+            .unwrap();
+        assert!(ranked[0].score.is_some());
     }
 
     #[tokio::test]
-    async fn sqlite_recall_no_match() {
-        let (_tmp, mem) = temp_sqlite();
-        mem.store("a", "Rust rocks", MemoryCategory::Core)
+    async fn date_range_filters_recall_and_list_in_sql() {
+        let (tmp, mem) = temp_sqlite();
+        for key in ["old_deploy", "new_deploy", "touched_deploy"] {
+            mem.store(key, "deploy notes", MemoryCategory::Core)
+                .await
+                .unwrap();
+        }
+        let conn = Connection::open(tmp.path().join("memory").join("brain.db")).unwrap();
+        let days_ago = |days| (Local::now() - chrono::Duration::days(days)).to_rfc3339();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1, updated_at = ?1 WHERE key = 'old_deploy'",
+            params![days_ago(20)],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1 WHERE key = 'touched_deploy'",
+            params![days_ago(20)],
+        )
+        .unwrap();
+        let keys = |entries: Vec<MemoryEntry>| -> Vec<String> {
+            let mut keys: Vec<String> = entries.into_iter().map(|e| e.key).collect();
+            keys.sort();
+            keys
+        };
+
+        let last_week = DateRange::new(Some(Utc::now() - chrono::Duration::days(7)), None);
+        let hits = mem.recall_in_range("deploy", 10, &last_week).await.unwrap();
+        assert_eq!(keys(hits), vec!["new_deploy"]);
+        let listed = mem
+            .list_in_range(None, &last_week.on(DateField::Updated))
             .await
             .unwrap();
-        let results = mem.recall("javascript", 10).await.unwrap();
-        assert!(results.is_empty());
+        assert_eq!(keys(listed), vec!["new_deploy", "touched_deploy"]);
+        assert!(mem
+            .list(None)
+            .await
+            .unwrap()
+            .iter()
+            .all(|e| e.updated_at.is_some()));
+
+        let before = DateRange::new(None, Some(Utc::now() - chrono::Duration::days(7)));
+        let listed = mem
+            .list_in_range(Some(&MemoryCategory::Core), &before)
+            .await
+            .unwrap();
+        assert_eq!(keys(listed), vec!["old_deploy", "touched_deploy"]);
+        assert_eq!(
+            mem.list_in_range(None, &DateRange::default())
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
     }
 
     #[tokio::test]
-    async fn sqlite_forget() {
+    async fn relations_expand_recall_and_follow_entry_lifecycle() {
         let (_tmp, mem) = temp_sqlite();
-        mem.store("temp", "temporary data", MemoryCategory::Conversation)
+        mem.store(
+            "project-x",
+            "Project X migrates billing",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+        mem.store("deadline-june", "Due end of June", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("owner", "Dana owns it", MemoryCategory::Core)
             .await
             .unwrap();
-        assert_eq!(mem.count().await.unwrap(), 1);
 
-        let removed = mem.forget("temp").await.unwrap();
-        assert!(removed);
-        assert_eq!(mem.count().await.unwrap(), 0);
-    }
+        assert!(mem
+            .relate("project-x", "deadline-june", "Deadline")
+            .await
+            .unwrap());
+        assert!(mem.relate("owner", "project-x", "").await.unwrap());
+        // Re-linking is a no-op; missing ends link nothing
+        assert!(mem
+            .relate("project-x", "deadline-june", "deadline")
+            .await
+            .unwrap());
+        assert!(!mem.relate("project-x", "ghost", "x").await.unwrap());
+        assert!(mem.relate("owner", "owner", "x").await.is_err());
+
+        let relations = mem.relations("project-x").await.unwrap();
+        assert_eq!(relations.len(), 2);
+        assert_eq!(relations[0].relation, "deadline");
+        assert_eq!(relations[1].relation, "related");
+
+        let plain = mem.recall("billing", 5).await.unwrap();
+        assert_eq!(plain.len(), 1);
+        let expanded = mem.recall_with_related("billing", 5).await.unwrap();
+        let keys: Vec<&str> = expanded.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["project-x", "deadline-june", "owner"]);
+        assert!(expanded[1].score.is_none());
+
+        // Trashing hides a link, restoring brings it back, purging drops it
+        mem.forget("deadline-june").await.unwrap();
+        assert_eq!(mem.relations("project-x").await.unwrap().len(), 1);
+        mem.restore("deadline-june").await.unwrap();
+        assert_eq!(mem.relations("project-x").await.unwrap().len(), 2);
+        mem.forget("owner").await.unwrap();
+        mem.purge_trash(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        let count: i64 = mem
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM memory_relations", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 1);
 
-    #[tokio::test]
-    async fn sqlite_forget_nonexistent() {
-        let (_tmp, mem) = temp_sqlite();
-        let removed = mem.forget("nope").await.unwrap();
-        assert!(!removed);
+        assert_eq!(
+            mem.unrelate("project-x", "deadline-june", Some("other"))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            mem.unrelate("project-x", "deadline-june", None)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(mem.relations("project-x").await.unwrap().is_empty());
     }
 
     #[tokio::test]
-    async fn sqlite_list_all() {
+    async fn match_modes_see_through_encryption() {
         let (_tmp, mem) = temp_sqlite();
-        mem.store("a", "one", MemoryCategory::Core).await.unwrap();
-        mem.store("b", "two", MemoryCategory::Daily).await.unwrap();
-        mem.store("c", "three", MemoryCategory::Conversation)
+        let mem = mem
+            .with_cipher(ContentCipher::from_hex_key(TEST_KEY).unwrap())
+            .unwrap();
+        mem.store("k", "secret launch code 1234", MemoryCategory::Core)
             .await
             .unwrap();
 
-        let all = mem.list(None).await.unwrap();
-        assert_eq!(all.len(), 3);
+        let hits = mem
+            .recall_matching("code \\d{4}", MatchMode::Regex, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].content, "secret launch code 1234");
     }
 
+    // ── Ranking ──────────────────────────────────────────────────
+
     #[tokio::test]
-    async fn sqlite_list_by_category() {
-        let (_tmp, mem) = temp_sqlite();
-        mem.store("a", "core1", MemoryCategory::Core).await.unwrap();
-        mem.store("b", "core2", MemoryCategory::Core).await.unwrap();
-        mem.store("c", "daily1", MemoryCategory::Daily)
-            .await
-            .unwrap();
+    async fn important_old_memory_outranks_trivial_recent_one() {
+        let (tmp, mem) = temp_sqlite();
+        mem.store_with_importance(
+            "rule",
+            "database migrations need review",
+            MemoryCategory::Core,
+            1.0,
+        )
+        .await
+        .unwrap();
+        mem.store_with_importance(
+            "chatter",
+            "database migrations ran",
+            MemoryCategory::Conversation,
+            0.1,
+        )
+        .await
+        .unwrap();
 
-        let core = mem.list(Some(&MemoryCategory::Core)).await.unwrap();
-        assert_eq!(core.len(), 2);
+        let conn = Connection::open(tmp.path().join("memory").join("brain.db")).unwrap();
+        let month_ago = (Local::now() - chrono::Duration::days(30)).to_rfc3339();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1 WHERE key = 'rule'",
+            params![month_ago],
+        )
+        .unwrap();
 
-        let daily = mem.list(Some(&MemoryCategory::Daily)).await.unwrap();
-        assert_eq!(daily.len(), 1);
+        let hits = mem.recall("database migrations", 10).await.unwrap();
+        assert_eq!(hits[0].key, "rule");
+        assert!((hits[0].importance - 1.0).abs() < f64::EPSILON);
+        assert!(hits[0].score.unwrap() > hits[1].score.unwrap());
     }
 
     #[tokio::test]
-    async fn sqlite_count_empty() {
+    async fn importance_survives_restore_and_plain_overwrite() {
         let (_tmp, mem) = temp_sqlite();
-        assert_eq!(mem.count().await.unwrap(), 0);
+        mem.store_with_importance("k", "v1", MemoryCategory::Core, 0.8)
+            .await
+            .unwrap();
+        mem.store("k", "v2", MemoryCategory::Core).await.unwrap();
+        assert!((mem.get("k").await.unwrap().unwrap().importance - 0.8).abs() < 1e-9);
+
+        mem.forget("k").await.unwrap();
+        assert!(!mem.set_importance("k", 0.2).await.unwrap());
+        mem.restore("k").await.unwrap();
+        assert!(mem.set_importance("k", 7.0).await.unwrap());
+        assert!((mem.get("k").await.unwrap().unwrap().importance - 1.0).abs() < 1e-9);
+        assert_eq!(mem.recall("v2", 10).await.unwrap().len(), 1);
     }
 
+    // ── Trash ────────────────────────────────────────────────────
+
     #[tokio::test]
-    async fn sqlite_get_nonexistent() {
+    async fn forget_trashes_until_restored_or_purged() {
         let (_tmp, mem) = temp_sqlite();
-        assert!(mem.get("nope").await.unwrap().is_none());
-    }
+        mem.store("pref", "prefers tabs_xyz", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("other", "unrelated", MemoryCategory::Core)
+            .await
+            .unwrap();
 
-    #[tokio::test]
-    async fn sqlite_db_persists() {
-        let tmp = TempDir::new().unwrap();
+        assert!(mem.forget("pref").await.unwrap());
+        assert!(!mem.forget("pref").await.unwrap());
+        assert!(mem.get("pref").await.unwrap().is_none());
+        assert!(mem.recall("tabs_xyz", 10).await.unwrap().is_empty());
+        assert_eq!(mem.list(None).await.unwrap().len(), 1);
+        assert_eq!(mem.count().await.unwrap(), 1);
 
-        {
-            let mem = SqliteMemory::new(tmp.path()).unwrap();
-            mem.store("persist", "I survive restarts", MemoryCategory::Core)
-                .await
-                .unwrap();
-        }
+        let trashed = mem.list_trash().await.unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].entry.key, "pref");
+        assert!(!trashed[0].deleted_at.is_empty());
 
-        // Reopen
-        let mem2 = SqliteMemory::new(tmp.path()).unwrap();
-        let entry = mem2.get("persist").await.unwrap();
-        assert!(entry.is_some());
-        assert_eq!(entry.unwrap().content, "I survive restarts");
+        assert!(mem.restore("pref").await.unwrap());
+        assert!(!mem.restore("pref").await.unwrap());
+        assert_eq!(mem.recall("tabs_xyz", 10).await.unwrap().len(), 1);
+        assert!(mem.list_trash().await.unwrap().is_empty());
+
+        mem.forget("pref").await.unwrap();
+        assert_eq!(
+            mem.purge_trash(Utc::now() - chrono::Duration::days(1))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            mem.purge_trash(Utc::now() + chrono::Duration::seconds(1))
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(mem.list_trash().await.unwrap().is_empty());
+        assert!(!mem.restore("pref").await.unwrap());
     }
 
     #[tokio::test]
-    async fn sqlite_category_roundtrip() {
+    async fn storing_a_trashed_key_revives_it() {
         let (_tmp, mem) = temp_sqlite();
-        let categories = [
-            MemoryCategory::Core,
-            MemoryCategory::Daily,
-            MemoryCategory::Conversation,
-            MemoryCategory::Custom("project".into()),
-        ];
+        mem.store("k", "old", MemoryCategory::Core).await.unwrap();
+        mem.forget("k").await.unwrap();
+        mem.store("k", "new_content_qq", MemoryCategory::Core)
+            .await
+            .unwrap();
 
-        for (i, cat) in categories.iter().enumerate() {
-            mem.store(&format!("k{i}"), &format!("v{i}"), cat.clone())
+        assert!(mem.list_trash().await.unwrap().is_empty());
+        assert_eq!(
+            mem.get("k").await.unwrap().unwrap().content,
+            "new_content_qq"
+        );
+        assert_eq!(mem.recall("new_content_qq", 10).await.unwrap().len(), 1);
+    }
+
+    // ── Namespaces ───────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn namespaces_isolate_keys_list_and_recall() {
+        let (_tmp, mem) = temp_sqlite();
+        for (ns, content) in [
+            ("alice", "likes green tea"),
+            ("bob", "likes black coffee"),
+            ("alice2", "likes water"),
+        ] {
+            mem.store_in_namespace(ns, "drink", content, MemoryCategory::Core)
                 .await
                 .unwrap();
         }
+        mem.store("drink", "global default", MemoryCategory::Core)
+            .await
+            .unwrap();
 
-        for (i, cat) in categories.iter().enumerate() {
-            let entry = mem.get(&format!("k{i}")).await.unwrap().unwrap();
-            assert_eq!(&entry.category, cat);
+        assert_eq!(mem.count().await.unwrap(), 4);
+        let alice = mem.get("ns/alice/drink").await.unwrap().unwrap();
+        assert_eq!(alice.content, "likes green tea");
+
+        let listed = mem.list_in_namespace("alice", None).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].key, "ns/alice/drink");
+
+        // FTS path and LIKE fallback both stay inside the namespace
+        for query in ["likes", "lik"] {
+            let hits = mem.recall_in_namespace("bob", query, 10).await.unwrap();
+            assert_eq!(hits.len(), 1, "query {query}");
+            assert_eq!(hits[0].content, "likes black coffee");
         }
+        assert!(mem
+            .recall_in_namespace("carol", "likes", 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(mem.recall_in_namespace("a/b", "likes", 10).await.is_err());
     }
 
-    // ── FTS5 search tests ────────────────────────────────────────
-
     #[tokio::test]
-    async fn fts5_bm25_ranking() {
+    async fn unscoped_recall_and_list_never_return_namespaced_entries() {
         let (_tmp, mem) = temp_sqlite();
-        mem.store(
-            "a",
-            "Rust is a systems programming language",
+        mem.store_with_tags(
+            "ns/alice/secret",
+            "alice password hint is tea",
             MemoryCategory::Core,
+            &["private".into()],
         )
         .await
         .unwrap();
-        mem.store("b", "Python is great for scripting", MemoryCategory::Core)
-            .await
-            .unwrap();
-        mem.store(
-            "c",
-            "Rust and Rust and Rust everywhere",
+        mem.store_with_tags(
+            "tea",
+            "team likes tea",
             MemoryCategory::Core,
+            &["private".into()],
         )
         .await
         .unwrap();
 
-        let results = mem.recall("Rust", 10).await.unwrap();
-        assert!(results.len() >= 2);
-        // All results should contain "Rust"
-        for r in &results {
-            assert!(
-                r.content.to_lowercase().contains("rust"),
-                "Expected 'rust' in: {}",
-                r.content
-            );
+        let only_shared = |entries: Vec<MemoryEntry>| {
+            assert!(!entries.is_empty());
+            assert!(entries.iter().all(|e| e.key == "tea"), "{entries:?}");
+        };
+        // FTS path and LIKE fallback
+        only_shared(mem.recall("tea", 10).await.unwrap());
+        only_shared(mem.recall("te", 10).await.unwrap());
+        only_shared(mem.recall_tagged("tea", "private", 10).await.unwrap());
+        let range = DateRange::new(Some(Utc::now() - chrono::Duration::hours(1)), None);
+        only_shared(mem.recall_in_range("tea", 10, &range).await.unwrap());
+        only_shared(mem.recall_with_related("tea", 10).await.unwrap());
+        for mode in [MatchMode::Exact, MatchMode::Regex] {
+            only_shared(mem.recall_matching("tea", mode, 10).await.unwrap());
         }
+        assert!(mem
+            .recall_matching("ns/", MatchMode::Prefix, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        only_shared(mem.list(None).await.unwrap());
+        only_shared(mem.list_tagged("private", None).await.unwrap());
+        only_shared(mem.list_in_range(None, &range).await.unwrap());
+
+        // Backups and sync still see everything
+        assert_eq!(mem.list_all(None).await.unwrap().len(), 2);
+        let hits = mem.recall_in_namespace("alice", "tea", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, "ns/alice/secret");
     }
 
+    // ── Chunking ─────────────────────────────────────────────────
+
     #[tokio::test]
-    async fn fts5_multi_word_query() {
-        let (_tmp, mem) = temp_sqlite();
-        mem.store("a", "The quick brown fox jumps", MemoryCategory::Core)
-            .await
-            .unwrap();
-        mem.store("b", "A lazy dog sleeps", MemoryCategory::Core)
+    async fn oversized_content_is_chunked_and_reassembled() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path())
+            .unwrap()
+            .with_max_content_bytes(32);
+        let doc = "Intro paragraph one.\nMiddle mentions zebra_term here.\nClosing line.";
+        mem.store_with_tags("doc", doc, MemoryCategory::Core, &["spec".into()])
             .await
             .unwrap();
-        mem.store("c", "The quick dog runs fast", MemoryCategory::Core)
+
+        assert_eq!(mem.get("doc").await.unwrap().unwrap().content, doc);
+        assert_eq!(mem.count().await.unwrap(), 1);
+        let listed = mem.list(None).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].content, doc);
+
+        // Recall hits the piece that matched, tagged like its entry
+        let hits = mem.recall_tagged("zebra_term", "spec", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, "doc#2");
+        assert!(hits[0].content.len() <= 32);
+
+        mem.forget("doc").await.unwrap();
+        assert!(mem.recall("zebra_term", 10).await.unwrap().is_empty());
+        assert_eq!(mem.list_trash().await.unwrap()[0].entry.content, doc);
+        mem.restore("doc").await.unwrap();
+        assert_eq!(mem.recall("zebra_term", 10).await.unwrap().len(), 1);
+
+        // Re-storing short content drops the old chunks
+        mem.store("doc", "short now", MemoryCategory::Core)
             .await
             .unwrap();
-
-        let results = mem.recall("quick dog", 10).await.unwrap();
-        assert!(!results.is_empty());
-        // "The quick dog runs fast" matches both terms
-        assert!(results[0].content.contains("quick"));
+        assert!(mem.recall("zebra_term", 10).await.unwrap().is_empty());
+        assert_eq!(mem.get("doc").await.unwrap().unwrap().content, "short now");
     }
 
-    #[tokio::test]
-    async fn recall_empty_query_returns_empty() {
-        let (_tmp, mem) = temp_sqlite();
-        mem.store("a", "data", MemoryCategory::Core).await.unwrap();
-        let results = mem.recall("", 10).await.unwrap();
-        assert!(results.is_empty());
-    }
+    // ── Multi-key get ────────────────────────────────────────────
 
     #[tokio::test]
-    async fn recall_whitespace_query_returns_empty() {
-        let (_tmp, mem) = temp_sqlite();
-        mem.store("a", "data", MemoryCategory::Core).await.unwrap();
-        let results = mem.recall("   ", 10).await.unwrap();
-        assert!(results.is_empty());
-    }
-
-    // ── Embedding cache tests ────────────────────────────────────
+    async fn get_many_returns_live_entries_in_requested_order() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path())
+            .unwrap()
+            .with_max_content_bytes(16);
+        mem.store("user_name", "Ada", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("user_tz", "Europe/London", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let prefs = "Prefers short answers.\nUses Rust daily.";
+        mem.store("user_prefs", prefs, MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("gone", "x", MemoryCategory::Core).await.unwrap();
+        mem.forget("gone").await.unwrap();
 
-    #[test]
-    fn content_hash_deterministic() {
-        let h1 = SqliteMemory::content_hash("hello world");
-        let h2 = SqliteMemory::content_hash("hello world");
-        assert_eq!(h1, h2);
-    }
+        let entries = mem
+            .get_many(&["user_prefs", "missing", "user_name", "gone", "user_name"])
+            .await
+            .unwrap();
+        let keys: Vec<_> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["user_prefs", "user_name"]);
+        assert_eq!(entries[0].content, prefs);
+        assert_eq!(entries[1].access_count, 1);
+        assert!(mem.get_many(&[]).await.unwrap().is_empty());
 
-    #[test]
-    fn content_hash_different_inputs() {
-        let h1 = SqliteMemory::content_hash("hello");
-        let h2 = SqliteMemory::content_hash("world");
-        assert_ne!(h1, h2);
+        // More keys than one `IN (…)` holds
+        let many: Vec<String> = (0..GET_MANY_BATCH + 5).map(|i| format!("k{i}")).collect();
+        let mut asked: Vec<&str> = many.iter().map(String::as_str).collect();
+        asked.push("user_tz");
+        assert_eq!(mem.get_many(&asked).await.unwrap()[0].key, "user_tz");
     }
 
-    // ── Schema tests ─────────────────────────────────────────────
+    // ── Batches ──────────────────────────────────────────────────
 
     #[tokio::test]
-    async fn schema_has_fts5_table() {
+    async fn apply_batch_rolls_back_on_failure() {
         let (_tmp, mem) = temp_sqlite();
-        let conn = mem.conn.lock().unwrap();
-        // FTS5 table should exist
-        let count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='memories_fts'",
-                [],
-                |row| row.get(0),
+        mem.store("turn", "hello", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        {
+            let conn = mem.conn.lock().unwrap();
+            conn.execute_batch(
+                "CREATE TRIGGER reject_boom BEFORE INSERT ON memories
+                 WHEN NEW.key = 'boom' BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
             )
             .unwrap();
-        assert_eq!(count, 1);
+        }
+        let store = |key: &str| MemoryOp::Store {
+            key: key.into(),
+            content: "user said hello".into(),
+            category: MemoryCategory::Core,
+            tags: vec!["summary".into()],
+        };
+        let forget = MemoryOp::Forget { key: "turn".into() };
+
+        let ops = [store("summary"), forget.clone(), store("boom")];
+        assert!(mem.apply_batch(&ops).await.is_err());
+        assert!(mem.get("summary").await.unwrap().is_none());
+        assert!(mem.get("turn").await.unwrap().is_some());
+
+        let ops = [store("summary"), forget.clone(), forget];
+        assert_eq!(mem.apply_batch(&ops).await.unwrap(), 2);
+        assert_eq!(
+            mem.get("summary").await.unwrap().unwrap().tags,
+            vec!["summary"]
+        );
+        assert_eq!(mem.list_trash().await.unwrap()[0].entry.key, "turn");
     }
 
+    // ── Access tracking ──────────────────────────────────────────
+
     #[tokio::test]
-    async fn schema_has_embedding_cache() {
-        let (_tmp, mem) = temp_sqlite();
-        let conn = mem.conn.lock().unwrap();
-        let count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='embedding_cache'",
-                [],
-                |row| row.get(0),
-            )
+    async fn get_and_recall_count_access_but_list_does_not() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path())
+            .unwrap()
+            .with_max_content_bytes(32);
+        mem.store("tz", "User timezone is UTC", MemoryCategory::Core)
+            .await
             .unwrap();
-        assert_eq!(count, 1);
-    }
+        mem.store(
+            "doc",
+            "Intro paragraph one.\nMiddle mentions zebra_term here.",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
 
-    #[tokio::test]
-    async fn schema_memories_has_embedding_column() {
-        let (_tmp, mem) = temp_sqlite();
-        let conn = mem.conn.lock().unwrap();
-        // Check that embedding column exists by querying it
-        let result = conn.execute_batch("SELECT embedding FROM memories LIMIT 0");
-        assert!(result.is_ok());
-    }
+        assert!(mem.list(None).await.unwrap()[0].last_accessed_at.is_none());
+        let first = mem.get("tz").await.unwrap().unwrap();
+        assert_eq!(first.access_count, 1);
+        assert!(first.last_accessed_at.is_some());
+        let hits = mem.recall("timezone", 10).await.unwrap();
+        assert_eq!(hits[0].access_count, 2);
+        mem.list(None).await.unwrap();
+        assert_eq!(mem.get("tz").await.unwrap().unwrap().access_count, 3);
 
-    // ── FTS5 sync trigger tests ──────────────────────────────────
+        // A hit on a chunk counts for its entry
+        assert_eq!(mem.recall("zebra_term", 10).await.unwrap()[0].key, "doc#2");
+        assert_eq!(mem.get("doc").await.unwrap().unwrap().access_count, 2);
+    }
 
     #[tokio::test]
-    async fn fts5_syncs_on_insert() {
+    async fn prune_unused_trashes_entries_not_read_since_cutoff() {
         let (_tmp, mem) = temp_sqlite();
-        mem.store("test_key", "unique_searchterm_xyz", MemoryCategory::Core)
-            .await
-            .unwrap();
-
-        let conn = mem.conn.lock().unwrap();
-        let count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH '\"unique_searchterm_xyz\"'",
+        for key in ["read", "stale", "fresh"] {
+            mem.store(key, key, MemoryCategory::Core).await.unwrap();
+        }
+        {
+            let conn = mem.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE memories SET created_at = '2020-01-01T00:00:00+00:00'
+                 WHERE key IN ('read', 'stale')",
                 [],
-                |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(count, 1);
-    }
+        }
+        mem.get("read").await.unwrap();
 
-    #[tokio::test]
-    async fn fts5_syncs_on_delete() {
-        let (_tmp, mem) = temp_sqlite();
-        mem.store("del_key", "deletable_content_abc", MemoryCategory::Core)
+        let cutoff = Utc::now() - chrono::Duration::days(90);
+        let keys = super::super::retention::prune_unused(&mem, cutoff, None, true)
             .await
             .unwrap();
-        mem.forget("del_key").await.unwrap();
+        assert_eq!(keys, vec!["stale"]);
+        assert_eq!(mem.count().await.unwrap(), 3);
 
-        let conn = mem.conn.lock().unwrap();
-        let count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH '\"deletable_content_abc\"'",
-                [],
-                |row| row.get(0),
-            )
+        super::super::retention::prune_unused(&mem, cutoff, None, false)
+            .await
             .unwrap();
-        assert_eq!(count, 0);
+        assert!(mem.get("stale").await.unwrap().is_none());
+        assert_eq!(mem.list_trash().await.unwrap()[0].entry.key, "stale");
     }
 
+    // ── Partial update ───────────────────────────────────────────
+
     #[tokio::test]
-    async fn fts5_syncs_on_update() {
+    async fn update_keeps_identity_session_and_expiry() {
         let (_tmp, mem) = temp_sqlite();
-        mem.store("upd_key", "original_content_111", MemoryCategory::Core)
-            .await
-            .unwrap();
-        mem.store("upd_key", "updated_content_222", MemoryCategory::Core)
+        assert!(mem
+            .update("missing", &MemoryPatch::default())
             .await
-            .unwrap();
-
-        let conn = mem.conn.lock().unwrap();
-        // Old content should not be findable
-        let old: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH '\"original_content_111\"'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap();
-        assert_eq!(old, 0);
+            .is_err());
 
-        // New content should be findable
-        let new: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH '\"updated_content_222\"'",
-                [],
-                |row| row.get(0),
-            )
+        mem.store_in_session("s1", "turn", "hello", MemoryCategory::Conversation)
+            .await
             .unwrap();
-        assert_eq!(new, 1);
-    }
+        mem.set_importance("turn", 0.8).await.unwrap();
+        let before = mem.get("turn").await.unwrap().unwrap();
 
-    // ── With-embedder constructor test ───────────────────────────
-
-    #[test]
-    fn with_embedder_noop() {
-        let tmp = TempDir::new().unwrap();
-        let embedder = Arc::new(super::super::embeddings::NoopEmbedding);
-        let mem = SqliteMemory::with_embedder(tmp.path(), embedder, 0.7, 0.3, 1000);
-        assert!(mem.is_ok());
-        assert_eq!(mem.unwrap().name(), "sqlite");
+        let patch = MemoryPatch {
+            content: Some("hello again".into()),
+            tags: Some(vec!["Greeting".into()]),
+            ..MemoryPatch::default()
+        };
+        let after = mem.update("turn", &patch).await.unwrap();
+        assert_eq!(after.content, "hello again");
+        assert_eq!(after.category, MemoryCategory::Conversation);
+        assert_eq!(after.tags, vec!["greeting"]);
+        assert_eq!(after.id, before.id);
+        assert_eq!(after.timestamp, before.timestamp);
+        assert_eq!(after.session_id.as_deref(), Some("s1"));
+        assert!((after.importance - 0.8).abs() < 1e-9);
+        assert_eq!(mem.recall_session("s1", 10).await.unwrap().len(), 1);
+
+        mem.store_with_ttl(
+            "tmp",
+            "short lived",
+            MemoryCategory::Core,
+            std::time::Duration::from_secs(3600),
+        )
+        .await
+        .unwrap();
+        let expiry = expires_at(&mem, "tmp");
+        let category = MemoryPatch {
+            category: Some(MemoryCategory::Daily),
+            ..MemoryPatch::default()
+        };
+        mem.update("tmp", &category).await.unwrap();
+        assert_eq!(expires_at(&mem, "tmp"), expiry);
+        assert_eq!(
+            mem.get("tmp").await.unwrap().unwrap().content,
+            "short lived"
+        );
     }
 
     // ── Reindex test ─────────────────────────────────────────────
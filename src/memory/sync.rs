@@ -0,0 +1,601 @@
+// Peer replication — two instances exchange what changed since their last
+// round over the gateway's `/memory/sync` endpoint, so a laptop agent and a
+// VPS agent share one brain.
+//
+// A changeset carries live entries and trash tombstones. Conflicting edits
+// resolve last-write-wins on `updated_at`; an incoming entry identical to
+// the local one is skipped, which keeps a round-trip from echoing forever.
+
+use super::ranking::DEFAULT_IMPORTANCE;
+use super::traits::{Memory, MemoryEntry, MemoryOp};
+use crate::config::MemorySyncConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Format version written into every changeset.
+pub const SYNC_VERSION: u32 = 1;
+
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+
+/// Entries per `POST /memory/sync`, keeping each request well under the
+/// gateway's body limit.
+const PUSH_PAGE_SIZE: usize = 200;
+
+/// Which way `[memory.sync]` replicates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncDirection {
+    #[default]
+    Both,
+    /// Only send local changes to the peer
+    Push,
+    /// Only fetch the peer's changes
+    Pull,
+}
+
+impl std::str::FromStr for SyncDirection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "both" => Ok(Self::Both),
+            "push" => Ok(Self::Push),
+            "pull" => Ok(Self::Pull),
+            other => {
+                anyhow::bail!("unknown sync direction '{other}' (expected both, push or pull)")
+            }
+        }
+    }
+}
+
+impl SyncDirection {
+    pub fn from_config(value: &str) -> Self {
+        value.parse().unwrap_or_else(|_| {
+            tracing::warn!("Unknown memory.sync.direction '{value}', using both");
+            Self::Both
+        })
+    }
+
+    fn pulls(self) -> bool {
+        self != Self::Push
+    }
+
+    fn pushes(self) -> bool {
+        self != Self::Pull
+    }
+}
+
+/// A forgotten entry, so the peer forgets it too.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub key: String,
+    /// When it was forgotten (UTC RFC 3339)
+    pub deleted_at: String,
+}
+
+/// Everything that changed on one instance since a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Changeset {
+    pub version: u32,
+    /// Sender's clock when collecting started; the receiver's cursor for
+    /// the next round
+    pub generated_at: String,
+    pub entries: Vec<MemoryEntry>,
+    #[serde(default)]
+    pub tombstones: Vec<Tombstone>,
+}
+
+/// What applying a changeset did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// Entries written (new, or newer than the local copy)
+    pub stored: usize,
+    /// Entries moved to the trash by a newer tombstone
+    pub deleted: usize,
+    /// Entries and tombstones that were identical or older
+    pub skipped: usize,
+}
+
+impl std::ops::AddAssign for SyncReport {
+    fn add_assign(&mut self, other: Self) {
+        self.stored += other.stored;
+        self.deleted += other.deleted;
+        self.skipped += other.skipped;
+    }
+}
+
+fn parse_at(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// Trash times are stored to the second; read them as the end of that
+/// second so a forget is never judged older than the write it followed.
+fn deleted_at(value: &str) -> Option<DateTime<Utc>> {
+    parse_at(value).map(|at| at + chrono::Duration::milliseconds(999))
+}
+
+/// When an entry last changed.
+fn changed_at(entry: &MemoryEntry) -> Option<DateTime<Utc>> {
+    parse_at(entry.updated_at.as_deref().unwrap_or(&entry.timestamp))
+}
+
+/// Last-write-wins: the incoming side only loses to a strictly newer local
+/// change. Unreadable timestamps can't be compared, so incoming wins.
+fn incoming_wins(incoming: Option<DateTime<Utc>>, local: Option<DateTime<Utc>>) -> bool {
+    match (incoming, local) {
+        (Some(incoming), Some(local)) => incoming >= local,
+        _ => true,
+    }
+}
+
+fn same_content(a: &MemoryEntry, b: &MemoryEntry) -> bool {
+    a.content == b.content && a.category == b.category && a.tags == b.tags
+}
+
+impl Changeset {
+    /// Live entries and tombstones changed at or after `since` (everything
+    /// when `None`).
+    pub async fn collect(mem: &dyn Memory, since: Option<DateTime<Utc>>) -> anyhow::Result<Self> {
+        let generated_at = Utc::now().to_rfc3339();
+        let changed =
+            |at: Option<DateTime<Utc>>| since.is_none_or(|since| at.is_none_or(|at| at >= since));
+        let entries = mem
+            .list_all(None)
+            .await?
+            .into_iter()
+            .filter(|e| changed(changed_at(e)))
+            .collect();
+        let tombstones = mem
+            .list_trash()
+            .await?
+            .into_iter()
+            .filter(|t| changed(deleted_at(&t.deleted_at)))
+            .map(|t| Tombstone {
+                key: t.entry.key,
+                deleted_at: t.deleted_at,
+            })
+            .collect();
+        Ok(Self {
+            version: SYNC_VERSION,
+            generated_at,
+            entries,
+            tombstones,
+        })
+    }
+
+    /// Merge into `mem`: newer entries are stored and newer tombstones
+    /// forget, all in one batch. Importance travels with content changes.
+    pub async fn apply(&self, mem: &dyn Memory) -> anyhow::Result<SyncReport> {
+        if self.version > SYNC_VERSION {
+            anyhow::bail!(
+                "changeset format v{} is newer than this build supports (v{SYNC_VERSION})",
+                self.version
+            );
+        }
+        let local: HashMap<String, MemoryEntry> = mem
+            .list_all(None)
+            .await?
+            .into_iter()
+            .map(|e| (e.key.clone(), e))
+            .collect();
+        let trashed: HashMap<String, Option<DateTime<Utc>>> = mem
+            .list_trash()
+            .await?
+            .into_iter()
+            .map(|t| (t.entry.key, deleted_at(&t.deleted_at)))
+            .collect();
+
+        let mut report = SyncReport::default();
+        let mut ops = Vec::new();
+        let mut importance = Vec::new();
+        for entry in &self.entries {
+            let current = local.get(&entry.key);
+            let wins = match current {
+                Some(current) => {
+                    !same_content(current, entry)
+                        && incoming_wins(changed_at(entry), changed_at(current))
+                }
+                None => trashed
+                    .get(&entry.key)
+                    .is_none_or(|deleted| incoming_wins(changed_at(entry), *deleted)),
+            };
+            if !wins {
+                report.skipped += 1;
+                continue;
+            }
+            let previous = current.map_or(DEFAULT_IMPORTANCE, |c| c.importance);
+            if (entry.importance - previous).abs() > f64::EPSILON {
+                importance.push((entry.key.as_str(), entry.importance));
+            }
+            ops.push(MemoryOp::Store {
+                key: entry.key.clone(),
+                content: entry.content.clone(),
+                category: entry.category.clone(),
+                tags: entry.tags.clone(),
+            });
+            report.stored += 1;
+        }
+        for tombstone in &self.tombstones {
+            let wins = local.get(&tombstone.key).is_some_and(|current| {
+                incoming_wins(deleted_at(&tombstone.deleted_at), changed_at(current))
+            });
+            if wins {
+                ops.push(MemoryOp::Forget {
+                    key: tombstone.key.clone(),
+                });
+                report.deleted += 1;
+            } else {
+                report.skipped += 1;
+            }
+        }
+
+        mem.apply_batch(&ops).await?;
+        for (key, importance) in importance {
+            mem.set_importance(key, importance).await?;
+        }
+        Ok(report)
+    }
+}
+
+/// The other instance's gateway.
+pub struct SyncPeer {
+    client: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl SyncPeer {
+    pub fn from_config(config: &MemorySyncConfig) -> anyhow::Result<Self> {
+        let base_url = config.peer_url.trim().trim_end_matches('/');
+        if base_url.is_empty() {
+            anyhow::bail!("memory.sync needs a peer_url (the other instance's gateway)");
+        }
+        reqwest::Url::parse(base_url)
+            .map_err(|e| anyhow::anyhow!("invalid memory.sync.peer_url '{base_url}': {e}"))?;
+        let client = crate::proxy::client_builder("sync")?
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()?;
+        Ok(Self {
+            client,
+            base_url: base_url.to_string(),
+            token: config.token.clone().filter(|t| !t.is_empty()),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}/memory/sync", self.base_url));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// The peer's changes since `since` (by the peer's clock).
+    pub async fn fetch(&self, since: Option<&str>) -> anyhow::Result<Changeset> {
+        let mut request = self.request(reqwest::Method::GET);
+        if let Some(since) = since {
+            request = request.query(&[("since", since)]);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "memory sync fetch from {} failed: HTTP {}",
+                self.base_url,
+                response.status()
+            );
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Send `changes` to the peer, a page of entries at a time.
+    pub async fn send(&self, changes: &Changeset) -> anyhow::Result<SyncReport> {
+        let mut report = SyncReport::default();
+        let mut pages: Vec<&[MemoryEntry]> = changes.entries.chunks(PUSH_PAGE_SIZE).collect();
+        if pages.is_empty() {
+            pages.push(&[]);
+        }
+        let last = pages.len() - 1;
+        for (i, entries) in pages.into_iter().enumerate() {
+            // Tombstones go last so a page can't resurrect what they delete
+            let page = Changeset {
+                version: changes.version,
+                generated_at: changes.generated_at.clone(),
+                entries: entries.to_vec(),
+                tombstones: if i == last {
+                    changes.tombstones.clone()
+                } else {
+                    Vec::new()
+                },
+            };
+            let response = self
+                .request(reqwest::Method::POST)
+                .json(&page)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                anyhow::bail!(
+                    "memory sync push to {} failed: HTTP {}",
+                    self.base_url,
+                    response.status()
+                );
+            }
+            report += response.json::<SyncReport>().await?;
+        }
+        Ok(report)
+    }
+}
+
+/// Where the previous round left off, per direction.
+#[derive(Debug, Clone, Default)]
+pub struct SyncCursor {
+    /// Peer clock at the last fetch
+    pub pulled: Option<String>,
+    /// Local clock at the last push
+    pub pushed: Option<DateTime<Utc>>,
+}
+
+/// Outcome of one round; `None` for a direction that wasn't synced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SyncOutcome {
+    pub pulled: Option<SyncReport>,
+    pub pushed: Option<SyncReport>,
+}
+
+/// Pull, then push, whatever changed since `cursor`, and advance it.
+pub async fn sync_once(
+    mem: &dyn Memory,
+    peer: &SyncPeer,
+    direction: SyncDirection,
+    cursor: &mut SyncCursor,
+) -> anyhow::Result<SyncOutcome> {
+    let mut outcome = SyncOutcome::default();
+    if direction.pulls() {
+        let changes = peer.fetch(cursor.pulled.as_deref()).await?;
+        outcome.pulled = Some(changes.apply(mem).await?);
+        cursor.pulled = Some(changes.generated_at);
+    }
+    if direction.pushes() {
+        let changes = Changeset::collect(mem, cursor.pushed).await?;
+        outcome.pushed = Some(peer.send(&changes).await?);
+        cursor.pushed = parse_at(&changes.generated_at);
+    }
+    Ok(outcome)
+}
+
+/// Sync with the peer every `interval_secs` until aborted. The first round
+/// exchanges everything; later ones only what changed since.
+pub async fn run_sync(mem: Arc<dyn Memory>, config: &MemorySyncConfig) -> anyhow::Result<()> {
+    let peer = SyncPeer::from_config(config)?;
+    let direction = SyncDirection::from_config(&config.direction);
+    let mut cursor = SyncCursor::default();
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs.max(30)));
+    loop {
+        interval.tick().await;
+        match sync_once(mem.as_ref(), &peer, direction, &mut cursor).await {
+            Ok(outcome) => {
+                let pulled = outcome.pulled.unwrap_or_default();
+                let pushed = outcome.pushed.unwrap_or_default();
+                if pulled.stored + pulled.deleted + pushed.stored + pushed.deleted > 0 {
+                    tracing::info!(
+                        "memory sync: pulled {} stored / {} deleted, pushed {} stored / {} deleted",
+                        pulled.stored,
+                        pulled.deleted,
+                        pushed.stored,
+                        pushed.deleted
+                    );
+                }
+            }
+            Err(e) => tracing::warn!("memory sync with {} failed: {e}", config.peer_url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{InMemoryMemory, MemoryCategory};
+    use axum::extract::{Query, State};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::Json;
+
+    /// An entry as a peer would send it, last changed at `at`.
+    fn entry_at(key: &str, content: &str, at: &str) -> MemoryEntry {
+        MemoryEntry {
+            id: key.into(),
+            key: key.into(),
+            content: content.into(),
+            category: MemoryCategory::Core,
+            timestamp: at.into(),
+            updated_at: Some(at.into()),
+            session_id: None,
+            tags: Vec::new(),
+            importance: DEFAULT_IMPORTANCE,
+            last_accessed_at: None,
+            access_count: 0,
+            score: None,
+        }
+    }
+
+    fn changeset(entries: Vec<MemoryEntry>, tombstones: Vec<Tombstone>) -> Changeset {
+        Changeset {
+            version: SYNC_VERSION,
+            generated_at: Utc::now().to_rfc3339(),
+            entries,
+            tombstones,
+        }
+    }
+
+    #[tokio::test]
+    async fn newer_changes_win_and_identical_ones_are_skipped() {
+        let mem = InMemoryMemory::new();
+        mem.store("tz", "UTC", MemoryCategory::Core).await.unwrap();
+        mem.store("lang", "Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("old", "stale", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let report = changeset(
+            vec![
+                // Edited on the peer long ago: local is newer
+                entry_at("tz", "CET", "2020-01-01T00:00:00+00:00"),
+                // Edited on the peer after the local write
+                entry_at("lang", "Go", "2999-01-01T00:00:00+00:00"),
+                entry_at("new", "fresh", "2020-01-01T00:00:00+00:00"),
+            ],
+            vec![
+                Tombstone {
+                    key: "old".into(),
+                    deleted_at: "2999-01-01T00:00:00+00:00".into(),
+                },
+                Tombstone {
+                    key: "tz".into(),
+                    deleted_at: "2020-01-01T00:00:00+00:00".into(),
+                },
+            ],
+        )
+        .apply(&mem)
+        .await
+        .unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                stored: 2,
+                deleted: 1,
+                skipped: 2
+            }
+        );
+        assert_eq!(mem.get("tz").await.unwrap().unwrap().content, "UTC");
+        assert_eq!(mem.get("lang").await.unwrap().unwrap().content, "Go");
+        assert!(mem.get("new").await.unwrap().is_some());
+        assert!(mem.get("old").await.unwrap().is_none());
+
+        // Sending everything back changes nothing: no echo
+        let echo = Changeset::collect(&mem, None).await.unwrap();
+        let report = echo.apply(&mem).await.unwrap();
+        assert_eq!(report.stored + report.deleted, 0);
+    }
+
+    #[tokio::test]
+    async fn collect_only_returns_changes_since_cursor() {
+        let mem = InMemoryMemory::new();
+        mem.store("before", "a", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let cursor = Utc::now();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        mem.store("after", "b", MemoryCategory::Core).await.unwrap();
+        mem.forget("before").await.unwrap();
+
+        let changes = Changeset::collect(&mem, Some(cursor)).await.unwrap();
+        let keys: Vec<_> = changes.entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["after"]);
+        assert_eq!(changes.tombstones[0].key, "before");
+    }
+
+    #[tokio::test]
+    async fn rejects_newer_format() {
+        let mut changes = changeset(Vec::new(), Vec::new());
+        changes.version = SYNC_VERSION + 1;
+        assert!(changes.apply(&InMemoryMemory::new()).await.is_err());
+    }
+
+    /// Minimal peer gateway over an in-process memory, requiring a bearer.
+    async fn mock_peer(mem: Arc<InMemoryMemory>) -> String {
+        #[derive(serde::Deserialize)]
+        struct Since {
+            since: Option<String>,
+        }
+        fn authorized(headers: &HeaderMap) -> bool {
+            headers
+                .get("authorization")
+                .is_some_and(|v| v == "Bearer peer-token")
+        }
+        async fn fetch(
+            State(mem): State<Arc<InMemoryMemory>>,
+            headers: HeaderMap,
+            Query(q): Query<Since>,
+        ) -> Result<Json<Changeset>, StatusCode> {
+            if !authorized(&headers) {
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+            let since = q.since.as_deref().and_then(parse_at);
+            Ok(Json(Changeset::collect(mem.as_ref(), since).await.unwrap()))
+        }
+        async fn apply(
+            State(mem): State<Arc<InMemoryMemory>>,
+            headers: HeaderMap,
+            Json(changes): Json<Changeset>,
+        ) -> Result<Json<SyncReport>, StatusCode> {
+            if !authorized(&headers) {
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+            Ok(Json(changes.apply(mem.as_ref()).await.unwrap()))
+        }
+
+        let app = axum::Router::new()
+            .route("/memory/sync", axum::routing::get(fetch).post(apply))
+            .with_state(mem);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn two_instances_converge() {
+        let remote = Arc::new(InMemoryMemory::new());
+        remote
+            .store("vps_fact", "runs on Hetzner", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let local = InMemoryMemory::new();
+        local
+            .store("laptop_fact", "uses a Mac", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let mut config = MemorySyncConfig {
+            peer_url: mock_peer(Arc::clone(&remote)).await,
+            token: Some("wrong".into()),
+            ..MemorySyncConfig::default()
+        };
+        let mut cursor = SyncCursor::default();
+        let peer = SyncPeer::from_config(&config).unwrap();
+        assert!(sync_once(&local, &peer, SyncDirection::Both, &mut cursor)
+            .await
+            .is_err());
+
+        config.token = Some("peer-token".into());
+        let peer = SyncPeer::from_config(&config).unwrap();
+        let outcome = sync_once(&local, &peer, SyncDirection::Both, &mut cursor)
+            .await
+            .unwrap();
+        assert_eq!(outcome.pulled.unwrap().stored, 1);
+        assert_eq!(outcome.pushed.unwrap().stored, 1);
+        assert!(local.get("vps_fact").await.unwrap().is_some());
+        assert!(remote.get("laptop_fact").await.unwrap().is_some());
+
+        // A forget on one side reaches the other on the next round
+        local.forget("vps_fact").await.unwrap();
+        sync_once(&local, &peer, SyncDirection::Both, &mut cursor)
+            .await
+            .unwrap();
+        assert!(remote.get("vps_fact").await.unwrap().is_none());
+        assert_eq!(remote.count().await.unwrap(), 1);
+    }
+
+    #[test]
+    fn from_config_requires_a_peer() {
+        assert!(SyncPeer::from_config(&MemorySyncConfig::default()).is_err());
+        assert_eq!(SyncDirection::from_config("pull"), SyncDirection::Pull);
+        assert_eq!(SyncDirection::from_config("sideways"), SyncDirection::Both);
+    }
+}
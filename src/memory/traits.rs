@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// A single memory entry
@@ -9,10 +10,91 @@ pub struct MemoryEntry {
     pub content: String,
     pub category: MemoryCategory,
     pub timestamp: String,
+    /// Last write (RFC 3339); `None` if the backend doesn't track it
+    #[serde(default)]
+    pub updated_at: Option<String>,
     pub session_id: Option<String>,
+    /// Free-form topic labels, independent of `category`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// How much the entry matters to recall ranking, 0.0–1.0
+    #[serde(default = "default_importance")]
+    pub importance: f64,
+    /// Last time `get` or a recall returned the entry (RFC 3339); `None` if
+    /// never, or if the backend doesn't track access
+    #[serde(default)]
+    pub last_accessed_at: Option<String>,
+    /// How many times `get` or a recall has returned the entry
+    #[serde(default)]
+    pub access_count: u64,
     pub score: Option<f64>,
 }
 
+fn default_importance() -> f64 {
+    super::ranking::DEFAULT_IMPORTANCE
+}
+
+impl MemoryEntry {
+    /// Whether the entry is labelled with `tag` (compared normalized).
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim().to_lowercase();
+        self.tags.contains(&tag)
+    }
+}
+
+/// Canonical tag set: trimmed, lowercased, split on commas, deduplicated
+/// and sorted, with empty labels dropped.
+pub fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut out: Vec<String> = tags
+        .iter()
+        .flat_map(|t| t.as_ref().split(','))
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Fields to change with [`Memory::update`]; `None` leaves a field as is.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryPatch {
+    pub content: Option<String>,
+    pub category: Option<MemoryCategory>,
+    /// Replaces the entry's tags (`Some(vec![])` clears them)
+    pub tags: Option<Vec<String>>,
+}
+
+impl MemoryPatch {
+    /// Whether applying the patch would change nothing.
+    pub fn is_empty(&self) -> bool {
+        self.content.is_none() && self.category.is_none() && self.tags.is_none()
+    }
+}
+
+/// One write in a [`Memory::apply_batch`] batch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemoryOp {
+    /// Like `store_with_tags`
+    Store {
+        key: String,
+        content: String,
+        category: MemoryCategory,
+        tags: Vec<String>,
+    },
+    /// Like `forget`: the entry moves to the trash
+    Forget { key: String },
+}
+
+/// `keys` with repeats removed, first occurrence kept.
+pub fn distinct_keys<'a>(keys: &[&'a str]) -> Vec<&'a str> {
+    let mut seen = std::collections::HashSet::new();
+    keys.iter()
+        .copied()
+        .filter(|key| seen.insert(*key))
+        .collect()
+}
+
 /// Memory categories for organization
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -38,6 +120,301 @@ impl std::fmt::Display for MemoryCategory {
     }
 }
 
+impl From<&str> for MemoryCategory {
+    /// Parse a category name; anything other than the built-ins is `Custom`.
+    fn from(name: &str) -> Self {
+        match name {
+            "core" => Self::Core,
+            "daily" => Self::Daily,
+            "conversation" => Self::Conversation,
+            other => Self::Custom(other.to_string()),
+        }
+    }
+}
+
+/// An entry moved to the trash by `forget`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedEntry {
+    pub entry: MemoryEntry,
+    /// When it was forgotten (UTC RFC 3339)
+    pub deleted_at: String,
+}
+
+/// Which timestamp a [`DateRange`] filters on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateField {
+    /// When the entry was first stored (`timestamp`)
+    #[default]
+    Created,
+    /// When it was last written (`updated_at`, else `timestamp`)
+    Updated,
+}
+
+/// Time window `[since, until)` for `recall_in_range` / `list_in_range`;
+/// a missing end is unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DateRange {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub field: DateField,
+}
+
+impl DateRange {
+    pub fn new(since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Self {
+        Self {
+            since,
+            until,
+            field: DateField::Created,
+        }
+    }
+
+    /// Filter on `field` instead of creation time.
+    pub fn on(mut self, field: DateField) -> Self {
+        self.field = field;
+        self
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    /// Whether `entry` falls inside the window. Entries whose timestamp
+    /// doesn't parse only pass an unbounded range.
+    pub fn contains(&self, entry: &MemoryEntry) -> bool {
+        if self.is_unbounded() {
+            return true;
+        }
+        let stamp = match self.field {
+            DateField::Created => &entry.timestamp,
+            DateField::Updated => entry.updated_at.as_ref().unwrap_or(&entry.timestamp),
+        };
+        let Ok(at) = DateTime::parse_from_rfc3339(stamp) else {
+            return false;
+        };
+        let at = at.with_timezone(&Utc);
+        self.since.is_none_or(|since| at >= since) && self.until.is_none_or(|until| at < until)
+    }
+}
+
+/// Parse a `since`/`until` bound: RFC 3339, a date (`2026-01-31`, local
+/// midnight), or an age like `7d`, `12h`, `30m` before now.
+pub fn parse_time_bound(value: &str) -> anyhow::Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(chrono::Local).earliest())
+            .map(|at| at.with_timezone(&Utc))
+            .ok_or_else(|| anyhow::anyhow!("'{value}' is not a valid local date"));
+    }
+    match super::retention::parse_retention(value) {
+        Ok(Some(age)) => Ok(Utc::now() - age),
+        _ => anyhow::bail!(
+            "invalid time '{value}' (expected RFC 3339, YYYY-MM-DD, or an age like \"7d\")"
+        ),
+    }
+}
+
+/// How `recall_matching` compares a query with entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// Ranked keyword (or hybrid) recall — plain `recall`
+    #[default]
+    Keyword,
+    /// Key or content contains the query verbatim (case-sensitive)
+    Exact,
+    /// Key or content matches the query as a regular expression
+    Regex,
+    /// Key starts with the query
+    Prefix,
+}
+
+impl std::fmt::Display for MatchMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keyword => write!(f, "keyword"),
+            Self::Exact => write!(f, "exact"),
+            Self::Regex => write!(f, "regex"),
+            Self::Prefix => write!(f, "prefix"),
+        }
+    }
+}
+
+impl std::str::FromStr for MatchMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "keyword" => Ok(Self::Keyword),
+            "exact" => Ok(Self::Exact),
+            "regex" => Ok(Self::Regex),
+            "prefix" => Ok(Self::Prefix),
+            other => anyhow::bail!(
+                "unknown search mode '{other}' (expected keyword, exact, regex or prefix)"
+            ),
+        }
+    }
+}
+
+/// A compiled `exact`/`regex`/`prefix` query for backends that match
+/// entries in process.
+pub struct EntryMatcher {
+    mode: MatchMode,
+    query: String,
+    regex: Option<regex::Regex>,
+}
+
+impl EntryMatcher {
+    /// Fails on an invalid regex. `Keyword` matches entries containing any
+    /// query word, case-insensitively.
+    pub fn new(mode: MatchMode, query: &str) -> anyhow::Result<Self> {
+        let regex = match mode {
+            MatchMode::Regex => Some(
+                regex::Regex::new(query)
+                    .map_err(|e| anyhow::anyhow!("invalid regex '{query}': {e}"))?,
+            ),
+            _ => None,
+        };
+        let query = match mode {
+            MatchMode::Keyword => query.to_lowercase(),
+            _ => query.to_string(),
+        };
+        Ok(Self { mode, query, regex })
+    }
+
+    pub fn matches(&self, entry: &MemoryEntry) -> bool {
+        match (self.mode, &self.regex) {
+            (MatchMode::Regex, Some(re)) => re.is_match(&entry.key) || re.is_match(&entry.content),
+            (MatchMode::Exact, _) => {
+                entry.key.contains(&self.query) || entry.content.contains(&self.query)
+            }
+            (MatchMode::Prefix, _) => entry.key.starts_with(&self.query),
+            _ => {
+                let haystack = format!("{} {}", entry.key, entry.content).to_lowercase();
+                self.query
+                    .split_whitespace()
+                    .any(|kw| haystack.contains(kw))
+            }
+        }
+    }
+}
+
+/// Embedding cache counters, kept across restarts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingCacheStats {
+    /// Vectors currently cached
+    pub entries: usize,
+    /// Max entries before LRU eviction
+    pub capacity: usize,
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that called the embedding provider
+    pub misses: u64,
+}
+
+impl EmbeddingCacheStats {
+    /// Share of lookups served from the cache; `None` before any lookup.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        #[allow(clippy::cast_precision_loss)]
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+}
+
+/// Label of a relation created without one
+pub const DEFAULT_RELATION: &str = "related";
+
+/// A directed, labelled link between two memories, by key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryRelation {
+    pub from: String,
+    pub to: String,
+    /// e.g. `"deadline_of"`; [`DEFAULT_RELATION`] when unspecified
+    pub relation: String,
+}
+
+impl MemoryRelation {
+    /// The key at the far end of this relation as seen from `key`
+    pub fn other(&self, key: &str) -> &str {
+        if self.from == key {
+            &self.to
+        } else {
+            &self.from
+        }
+    }
+}
+
+/// Trimmed, lowercased relation label; blank means [`DEFAULT_RELATION`].
+pub fn normalize_relation(relation: &str) -> String {
+    let relation = relation.trim().to_lowercase();
+    if relation.is_empty() {
+        DEFAULT_RELATION.to_string()
+    } else {
+        relation
+    }
+}
+
+/// Append entries one relation away from any of `entries` (unscored, at
+/// most `limit` of them), skipping ones already present.
+pub async fn expand_related<M: Memory + ?Sized>(
+    mem: &M,
+    mut entries: Vec<MemoryEntry>,
+    limit: usize,
+) -> anyhow::Result<Vec<MemoryEntry>> {
+    let mut seen: std::collections::HashSet<String> =
+        entries.iter().map(|e| e.key.clone()).collect();
+    let mut related = Vec::new();
+    for hit in &entries {
+        for relation in mem.relations(&hit.key).await? {
+            let other = relation.other(&hit.key);
+            if related.len() < limit && seen.insert(other.to_string()) {
+                if let Some(entry) = mem.get(other).await? {
+                    related.push(entry);
+                }
+            }
+        }
+    }
+    entries.extend(related);
+    Ok(entries)
+}
+
+/// Key prefix shared by every namespaced entry. Plain `recall`/`list`
+/// leave keys under it out, so a namespace is only reachable through the
+/// `*_in_namespace` methods.
+pub const NAMESPACE_ROOT: &str = "ns/";
+
+/// Key prefix of `namespace`: `"ns/{namespace}/"`. Namespaces are
+/// non-empty and can't contain `/`, so one can never reach into another.
+pub fn namespace_prefix(namespace: &str) -> anyhow::Result<String> {
+    let namespace = namespace.trim();
+    if namespace.is_empty() || namespace.contains('/') {
+        anyhow::bail!("invalid memory namespace '{namespace}' (must be non-empty, without '/')");
+    }
+    Ok(format!("{NAMESPACE_ROOT}{namespace}/"))
+}
+
+/// Namespace (`"alice"` for `"ns/alice/pref"`) a key belongs to, if any.
+pub fn namespace_of(key: &str) -> Option<&str> {
+    key.strip_prefix(NAMESPACE_ROOT)?
+        .split_once('/')
+        .map(|(namespace, _)| namespace)
+}
+
+/// Whether `key` lives in some namespace, hidden from unscoped recall.
+pub fn is_namespaced(key: &str) -> bool {
+    key.starts_with(NAMESPACE_ROOT)
+}
+
+/// Storage key of `key` inside `namespace`, as used by `get`/`forget`.
+pub fn namespaced_key(namespace: &str, key: &str) -> anyhow::Result<String> {
+    Ok(format!("{}{key}", namespace_prefix(namespace)?))
+}
+
 /// Core memory trait — implement for any persistence backend
 #[async_trait]
 pub trait Memory: Send + Sync {
@@ -48,21 +425,630 @@ pub trait Memory: Send + Sync {
     async fn store(&self, key: &str, content: &str, category: MemoryCategory)
         -> anyhow::Result<()>;
 
-    /// Recall memories matching a query (keyword search)
+    /// Store many entries at once. Backends override this to batch the
+    /// writes; the default stores them one by one.
+    async fn store_many(&self, entries: &[(String, String, MemoryCategory)]) -> anyhow::Result<()> {
+        for (key, content, category) in entries {
+            self.store(key, content, category.clone()).await?;
+        }
+        Ok(())
+    }
+
+    /// Apply `ops` in order as one unit: either every op takes effect or,
+    /// on error, none does. Returns how many ops changed something
+    /// (forgetting a missing key doesn't).
+    ///
+    /// The default applies them one by one and is not atomic; the built-in
+    /// backends override it.
+    async fn apply_batch(&self, ops: &[MemoryOp]) -> anyhow::Result<usize> {
+        let mut applied = 0;
+        for op in ops {
+            match op {
+                MemoryOp::Store {
+                    key,
+                    content,
+                    category,
+                    tags,
+                } => {
+                    self.store_with_tags(key, content, category.clone(), tags)
+                        .await?;
+                    applied += 1;
+                }
+                MemoryOp::Forget { key } => {
+                    if self.forget(key).await? {
+                        applied += 1;
+                    }
+                }
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Store a memory entry that expires after `ttl`.
+    ///
+    /// Backends without expiry support store the entry permanently.
+    async fn store_with_ttl(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        _ttl: std::time::Duration,
+    ) -> anyhow::Result<()> {
+        self.store(key, content, category).await
+    }
+
+    /// Store a memory entry labelled with `tags`, replacing any previous tags.
+    ///
+    /// Backends without tag support store the entry untagged.
+    async fn store_with_tags(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        _tags: &[String],
+    ) -> anyhow::Result<()> {
+        self.store(key, content, category).await
+    }
+
+    /// Store a memory entry with an `importance` (0.0–1.0) that weighs on
+    /// recall ranking.
+    async fn store_with_importance(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        importance: f64,
+    ) -> anyhow::Result<()> {
+        self.store(key, content, category).await?;
+        self.set_importance(key, importance).await?;
+        Ok(())
+    }
+
+    /// Change an entry's importance, clamped to 0.0–1.0; `false` if `key`
+    /// doesn't exist. Re-storing a key keeps its importance.
+    ///
+    /// Backends without importance support ignore it.
+    async fn set_importance(&self, _key: &str, _importance: f64) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    /// Change an existing entry's content, category and/or tags, keeping
+    /// its id, creation time and importance, and return the result. Unlike
+    /// `store`, this fails if `key` doesn't exist.
+    ///
+    /// The default re-stores the merged entry; backends that track sessions
+    /// or expiry override it to keep them.
+    async fn update(&self, key: &str, patch: &MemoryPatch) -> anyhow::Result<MemoryEntry> {
+        let Some(current) = self.get(key).await? else {
+            anyhow::bail!("no memory with key '{key}'");
+        };
+        let content = patch.content.as_deref().unwrap_or(&current.content);
+        let category = patch.category.clone().unwrap_or(current.category.clone());
+        let tags = patch.tags.as_ref().unwrap_or(&current.tags);
+        self.store_with_tags(key, content, category, tags).await?;
+        self.get(key)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("memory '{key}' vanished during update"))
+    }
+
+    /// Store a memory entry tied to a conversation session.
+    ///
+    /// Backends without session support store the entry unscoped.
+    async fn store_in_session(
+        &self,
+        _session_id: &str,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+    ) -> anyhow::Result<()> {
+        self.store(key, content, category).await
+    }
+
+    /// The most recent `limit` entries stored in `session_id`, oldest first.
+    /// Backends without session support return nothing.
+    async fn recall_session(
+        &self,
+        _session_id: &str,
+        _limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        Ok(Vec::new())
+    }
+
+    /// Store a memory entry in `namespace` (one user, workspace, ...).
+    ///
+    /// The entry is stored under [`namespaced_key`], so namespaces never
+    /// overwrite each other and `get`/`forget` take that full key.
+    async fn store_in_namespace(
+        &self,
+        namespace: &str,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+    ) -> anyhow::Result<()> {
+        self.store(&namespaced_key(namespace, key)?, content, category)
+            .await
+    }
+
+    /// Recall memories matching a query from `namespace` only.
+    ///
+    /// The default keyword-matches `list_all`, unranked; backends override
+    /// this to rank the namespace like `recall` does.
+    async fn recall_in_namespace(
+        &self,
+        namespace: &str,
+        query: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let prefix = namespace_prefix(namespace)?;
+        let matcher = EntryMatcher::new(MatchMode::Keyword, query)?;
+        let mut entries = self.list_all(None).await?;
+        entries.retain(|e| e.key.starts_with(&prefix) && matcher.matches(e));
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    /// List memories in `namespace`, optionally filtered by category.
+    /// The default filters `list_all`.
+    async fn list_in_namespace(
+        &self,
+        namespace: &str,
+        category: Option<&MemoryCategory>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let prefix = namespace_prefix(namespace)?;
+        let mut entries = self.list_all(category).await?;
+        entries.retain(|e| e.key.starts_with(&prefix));
+        Ok(entries)
+    }
+
+    /// Delete expired entries, returning how many were removed
+    async fn purge_expired(&self) -> anyhow::Result<usize> {
+        Ok(0)
+    }
+
+    /// Delete `category` entries last written before `cutoff`, returning how
+    /// many were removed. Entries with unparseable timestamps are kept.
+    async fn prune_before(
+        &self,
+        category: &MemoryCategory,
+        cutoff: DateTime<Utc>,
+    ) -> anyhow::Result<usize> {
+        let mut removed = 0;
+        for entry in self.list(Some(category)).await? {
+            let stale = DateTime::parse_from_rfc3339(&entry.timestamp)
+                .is_ok_and(|at| at.with_timezone(&Utc) < cutoff);
+            if stale && self.forget(&entry.key).await? {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Recall memories matching a query (keyword search). Namespaced
+    /// entries are left out; see [`Memory::recall_in_namespace`].
     async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>>;
 
+    /// Find up to `limit` entries by `mode`. `Keyword` is ranked `recall`;
+    /// the other modes return matches newest first, unscored.
+    ///
+    /// The default filters `list`; backends override this to match in
+    /// their storage layer instead.
+    async fn recall_matching(
+        &self,
+        query: &str,
+        mode: MatchMode,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        if mode == MatchMode::Keyword {
+            return self.recall(query, limit).await;
+        }
+        let matcher = EntryMatcher::new(mode, query)?;
+        let mut entries = self.list(None).await?;
+        entries.retain(|e| matcher.matches(e));
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    /// Recall memories matching a query that are labelled with `tag`
+    async fn recall_tagged(
+        &self,
+        query: &str,
+        tag: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mut entries = self.recall(query, limit).await?;
+        entries.retain(|e| e.has_tag(tag));
+        Ok(entries)
+    }
+
     /// Get a specific memory by key
     async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>>;
 
-    /// List all memory keys, optionally filtered by category
+    /// Get every existing entry among `keys`, in the order asked for;
+    /// missing keys are left out. Backends override this to fetch them in
+    /// one go; the default calls `get` per key.
+    async fn get_many(&self, keys: &[&str]) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mut entries = Vec::with_capacity(keys.len());
+        for key in distinct_keys(keys) {
+            if let Some(entry) = self.get(key).await? {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// List all memory keys, optionally filtered by category. Namespaced
+    /// entries are left out; see [`Memory::list_in_namespace`].
     async fn list(&self, category: Option<&MemoryCategory>) -> anyhow::Result<Vec<MemoryEntry>>;
 
-    /// Remove a memory by key
+    /// Every entry, namespaced ones included — for backups, sync and
+    /// maintenance, never for answering a caller. The `*_in_namespace`
+    /// defaults are built on it.
+    async fn list_all(&self, category: Option<&MemoryCategory>)
+        -> anyhow::Result<Vec<MemoryEntry>>;
+
+    /// Recall memories matching a query, keeping only those inside `range`.
+    ///
+    /// The default filters a full `recall`; backends override this to
+    /// apply the window while searching.
+    async fn recall_in_range(
+        &self,
+        query: &str,
+        limit: usize,
+        range: &DateRange,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        if range.is_unbounded() {
+            return self.recall(query, limit).await;
+        }
+        let mut entries = self.recall(query, usize::MAX).await?;
+        entries.retain(|e| range.contains(e));
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    /// List memories inside `range`, optionally filtered by category
+    async fn list_in_range(
+        &self,
+        category: Option<&MemoryCategory>,
+        range: &DateRange,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mut entries = self.list(category).await?;
+        entries.retain(|e| range.contains(e));
+        Ok(entries)
+    }
+
+    /// Link `from` to `to` under `relation`. Returns `false` (and links
+    /// nothing) unless both entries exist; backends without relations
+    /// always return `false`.
+    async fn relate(&self, _from: &str, _to: &str, _relation: &str) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    /// Remove links from `from` to `to` (only `relation`, if given),
+    /// returning how many were removed
+    async fn unrelate(
+        &self,
+        _from: &str,
+        _to: &str,
+        _relation: Option<&str>,
+    ) -> anyhow::Result<usize> {
+        Ok(0)
+    }
+
+    /// Relations touching `key` in either direction, oldest first
+    async fn relations(&self, _key: &str) -> anyhow::Result<Vec<MemoryRelation>> {
+        Ok(Vec::new())
+    }
+
+    /// `recall`, then one hop out: entries related to any hit follow the
+    /// hits (unscored, at most `limit` of them), so connected facts come
+    /// along as context.
+    async fn recall_with_related(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let entries = self.recall(query, limit).await?;
+        expand_related(self, entries, limit).await
+    }
+
+    /// List memories labelled with `tag`, optionally filtered by category
+    async fn list_tagged(
+        &self,
+        tag: &str,
+        category: Option<&MemoryCategory>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mut entries = self.list(category).await?;
+        entries.retain(|e| e.has_tag(tag));
+        Ok(entries)
+    }
+
+    /// Remove a memory by key. Backends with a trash keep it restorable
+    /// until `purge_trash`; others delete it outright.
     async fn forget(&self, key: &str) -> anyhow::Result<bool>;
 
+    /// Forgotten entries still in the trash, most recently deleted first.
+    /// Backends without a trash return nothing.
+    async fn list_trash(&self) -> anyhow::Result<Vec<TrashedEntry>> {
+        Ok(Vec::new())
+    }
+
+    /// Move a trashed entry back; `false` if `key` isn't in the trash
+    async fn restore(&self, _key: &str) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    /// Permanently delete entries trashed before `cutoff`, returning how
+    /// many were removed
+    async fn purge_trash(&self, _cutoff: DateTime<Utc>) -> anyhow::Result<usize> {
+        Ok(0)
+    }
+
+    /// Whether `get` and recalls record `last_accessed_at`/`access_count`
+    fn tracks_access(&self) -> bool {
+        false
+    }
+
+    /// Embedding cache counters; `None` when the backend doesn't embed
+    async fn embedding_cache_stats(&self) -> anyhow::Result<Option<EmbeddingCacheStats>> {
+        Ok(None)
+    }
+
     /// Count total memories
     async fn count(&self) -> anyhow::Result<usize>;
 
     /// Health check
     async fn health_check(&self) -> bool;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn category_from_str_roundtrips_display() {
+        for cat in [
+            MemoryCategory::Core,
+            MemoryCategory::Daily,
+            MemoryCategory::Conversation,
+            MemoryCategory::Custom("project".into()),
+        ] {
+            assert_eq!(MemoryCategory::from(cat.to_string().as_str()), cat);
+        }
+    }
+
+    #[test]
+    fn normalize_tags_canonicalizes() {
+        let tags = normalize_tags(&[" Project-X ", "infra,project-x", "", " , "]);
+        assert_eq!(tags, vec!["infra", "project-x"]);
+    }
+
+    #[test]
+    fn match_modes_parse_and_match() {
+        let entry = MemoryEntry {
+            id: "1".into(),
+            key: "slack_user_42".into(),
+            content: "Prefers Dark Mode".into(),
+            category: MemoryCategory::Core,
+            timestamp: String::new(),
+            updated_at: None,
+            session_id: None,
+            tags: Vec::new(),
+            importance: 0.5,
+            last_accessed_at: None,
+            access_count: 0,
+            score: None,
+        };
+        let matches = |mode: &str, query: &str| {
+            EntryMatcher::new(mode.parse().unwrap(), query)
+                .unwrap()
+                .matches(&entry)
+        };
+        assert!(matches("regex", "^slack_user_[0-9]+$"));
+        assert!(!matches("regex", "^user_"));
+        assert!(matches("exact", "Dark Mode"));
+        assert!(!matches("exact", "dark mode"));
+        assert!(matches("prefix", "slack_"));
+        assert!(!matches("prefix", "Prefers"));
+        assert!(matches("keyword", "DARK light"));
+
+        assert!("fuzzy".parse::<MatchMode>().is_err());
+        assert!(EntryMatcher::new(MatchMode::Regex, "(unclosed").is_err());
+        assert_eq!(MatchMode::Prefix.to_string(), "prefix");
+    }
+
+    #[test]
+    fn date_range_is_half_open_and_picks_field() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let entry = MemoryEntry {
+            id: "1".into(),
+            key: "k".into(),
+            content: "c".into(),
+            category: MemoryCategory::Core,
+            timestamp: "2026-03-01T10:00:00+02:00".into(),
+            updated_at: Some("2026-03-09T08:00:00Z".into()),
+            session_id: None,
+            tags: Vec::new(),
+            importance: 0.5,
+            last_accessed_at: None,
+            access_count: 0,
+            score: None,
+        };
+
+        let march = DateRange::new(
+            Some(at("2026-03-01T08:00:00Z")),
+            Some(at("2026-03-02T00:00:00Z")),
+        );
+        assert!(march.contains(&entry));
+        assert!(!DateRange::new(None, Some(at("2026-03-01T08:00:00Z"))).contains(&entry));
+        assert!(!march.on(DateField::Updated).contains(&entry));
+        assert!(DateRange::new(Some(at("2026-03-05T00:00:00Z")), None)
+            .on(DateField::Updated)
+            .contains(&entry));
+        assert!(DateRange::default().contains(&MemoryEntry {
+            timestamp: "garbage".into(),
+            ..entry
+        }));
+    }
+
+    #[test]
+    fn parse_time_bound_accepts_dates_and_ages() {
+        assert_eq!(
+            parse_time_bound("2026-01-31T12:00:00Z").unwrap(),
+            DateTime::parse_from_rfc3339("2026-01-31T12:00:00Z").unwrap()
+        );
+        assert!(parse_time_bound("2026-01-31").is_ok());
+        let week_ago = parse_time_bound("7d").unwrap();
+        let expected = Utc::now() - chrono::Duration::days(7);
+        assert!((week_ago - expected).num_seconds().abs() < 5);
+        assert!(parse_time_bound("last week").is_err());
+        assert!(parse_time_bound("forever").is_err());
+    }
+
+    #[test]
+    fn relations_normalize_and_point_both_ways() {
+        assert_eq!(normalize_relation("  Deadline_Of "), "deadline_of");
+        assert_eq!(normalize_relation(""), DEFAULT_RELATION);
+        let relation = MemoryRelation {
+            from: "project-x".into(),
+            to: "deadline-june".into(),
+            relation: DEFAULT_RELATION.into(),
+        };
+        assert_eq!(relation.other("project-x"), "deadline-june");
+        assert_eq!(relation.other("deadline-june"), "project-x");
+    }
+
+    #[test]
+    fn namespaced_keys_reject_separator() {
+        assert_eq!(namespaced_key(" alice ", "pref").unwrap(), "ns/alice/pref");
+        assert!(namespace_prefix("").is_err());
+        assert!(namespace_prefix("team/alice").is_err());
+        assert_eq!(namespace_of("ns/alice/pref"), Some("alice"));
+        assert_eq!(namespace_of("alice/pref"), None);
+        assert!(is_namespaced("ns/alice/pref"));
+        assert!(!is_namespaced("pref"));
+    }
+
+    /// Implements only the required methods, so namespaces go through the
+    /// trait defaults.
+    #[derive(Default)]
+    struct MinimalMemory {
+        entries: std::sync::Mutex<Vec<MemoryEntry>>,
+    }
+
+    #[async_trait]
+    impl Memory for MinimalMemory {
+        fn name(&self) -> &str {
+            "minimal"
+        }
+
+        async fn store(
+            &self,
+            key: &str,
+            content: &str,
+            category: MemoryCategory,
+        ) -> anyhow::Result<()> {
+            let mut entries = self.entries.lock().unwrap();
+            entries.retain(|e| e.key != key);
+            entries.push(MemoryEntry {
+                id: key.into(),
+                key: key.into(),
+                content: content.into(),
+                category,
+                timestamp: Utc::now().to_rfc3339(),
+                updated_at: None,
+                session_id: None,
+                tags: Vec::new(),
+                importance: 0.5,
+                last_accessed_at: None,
+                access_count: 0,
+                score: None,
+            });
+            Ok(())
+        }
+
+        async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
+            let matcher = EntryMatcher::new(MatchMode::Keyword, query)?;
+            let mut entries = self.list(None).await?;
+            entries.retain(|e| matcher.matches(e));
+            entries.truncate(limit);
+            Ok(entries)
+        }
+
+        async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
+            let entries = self.entries.lock().unwrap();
+            Ok(entries.iter().find(|e| e.key == key).cloned())
+        }
+
+        async fn list(
+            &self,
+            category: Option<&MemoryCategory>,
+        ) -> anyhow::Result<Vec<MemoryEntry>> {
+            let mut entries = self.list_all(category).await?;
+            entries.retain(|e| !is_namespaced(&e.key));
+            Ok(entries)
+        }
+
+        async fn list_all(
+            &self,
+            category: Option<&MemoryCategory>,
+        ) -> anyhow::Result<Vec<MemoryEntry>> {
+            let entries = self.entries.lock().unwrap();
+            Ok(entries
+                .iter()
+                .filter(|e| category.is_none_or(|c| &e.category == c))
+                .cloned()
+                .collect())
+        }
+
+        async fn forget(&self, key: &str) -> anyhow::Result<bool> {
+            let mut entries = self.entries.lock().unwrap();
+            let before = entries.len();
+            entries.retain(|e| e.key != key);
+            Ok(entries.len() < before)
+        }
+
+        async fn count(&self) -> anyhow::Result<usize> {
+            Ok(self.entries.lock().unwrap().len())
+        }
+
+        async fn health_check(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn namespace_defaults_reach_namespaced_entries() {
+        let mem = MinimalMemory::default();
+        for (ns, content) in [("alice", "likes tea"), ("bob", "likes coffee")] {
+            mem.store_in_namespace(ns, "drink", content, MemoryCategory::Core)
+                .await
+                .unwrap();
+        }
+        mem.store("drink", "likes water", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let listed = mem.list_in_namespace("alice", None).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].key, "ns/alice/drink");
+        assert!(mem
+            .list_in_namespace("alice", Some(&MemoryCategory::Daily))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let hits = mem.recall_in_namespace("bob", "coffee", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].content, "likes coffee");
+        assert!(mem
+            .recall_in_namespace("bob", "tea", 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(mem.recall_in_namespace("a/b", "likes", 10).await.is_err());
+
+        let unscoped = mem.recall("likes", 10).await.unwrap();
+        assert_eq!(unscoped.len(), 1);
+        assert_eq!(unscoped[0].key, "drink");
+    }
+}
@@ -131,6 +131,80 @@ pub fn hybrid_merge(
     results
 }
 
+/// Reciprocal rank fusion: combine vector and keyword results by rank
+/// instead of raw score, so BM25's unbounded scale can't swamp cosine.
+///
+/// Each source contributes `weight / (k + rank)` (1-based rank); the sum is
+/// divided by its best case `(vector_weight + keyword_weight) / (k + 1)`,
+/// so a result ranked first in both lists scores 1.0.
+pub fn rrf_merge(
+    vector_results: &[(String, f32)], // (id, cosine_similarity), best first
+    keyword_results: &[(String, f32)], // (id, bm25_score), best first
+    vector_weight: f32,
+    keyword_weight: f32,
+    k: f32,
+    limit: usize,
+) -> Vec<ScoredResult> {
+    use std::collections::HashMap;
+
+    let k = k.max(0.0);
+    let mut map: HashMap<String, ScoredResult> = HashMap::new();
+
+    let max_kw = keyword_results
+        .iter()
+        .map(|(_, s)| *s)
+        .fold(0.0_f32, f32::max);
+    let max_kw = if max_kw < f32::EPSILON { 1.0 } else { max_kw };
+
+    #[allow(clippy::cast_precision_loss)]
+    for (rank, (id, score)) in vector_results.iter().enumerate() {
+        let entry = map.entry(id.clone()).or_insert_with(|| ScoredResult {
+            id: id.clone(),
+            vector_score: None,
+            keyword_score: None,
+            final_score: 0.0,
+        });
+        // A repeated id keeps its best (first) rank
+        if entry.vector_score.is_none() {
+            entry.vector_score = Some(*score);
+            entry.final_score += vector_weight / (k + rank as f32 + 1.0);
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    for (rank, (id, score)) in keyword_results.iter().enumerate() {
+        let entry = map.entry(id.clone()).or_insert_with(|| ScoredResult {
+            id: id.clone(),
+            vector_score: None,
+            keyword_score: None,
+            final_score: 0.0,
+        });
+        if entry.keyword_score.is_none() {
+            entry.keyword_score = Some(score / max_kw);
+            entry.final_score += keyword_weight / (k + rank as f32 + 1.0);
+        }
+    }
+
+    let best = (vector_weight + keyword_weight) / (k + 1.0);
+    let mut results: Vec<ScoredResult> = map
+        .into_values()
+        .map(|mut r| {
+            if best > f32::EPSILON {
+                r.final_score /= best;
+            }
+            r
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        b.final_score
+            .partial_cmp(&a.final_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    results.truncate(limit);
+    results
+}
+
 #[cfg(test)]
 #[allow(
     clippy::float_cmp,
@@ -399,4 +473,57 @@ mod tests {
         assert_eq!(merged.len(), 1);
         assert_eq!(merged[0].id, "only");
     }
+
+    // ── Reciprocal rank fusion ───────────────────────────────────
+
+    #[test]
+    fn rrf_merge_rewards_agreement_between_sources() {
+        let vec_results = vec![("a".into(), 0.9), ("b".into(), 0.8)];
+        let kw_results = vec![("b".into(), 50.0), ("c".into(), 40.0)];
+        let merged = rrf_merge(&vec_results, &kw_results, 1.0, 1.0, 60.0, 10);
+        assert_eq!(merged.len(), 3);
+        // "b" appears in both lists, so it outranks either single-source hit
+        assert_eq!(merged[0].id, "b");
+        assert!(merged[0].vector_score.is_some());
+        assert!(merged[0].keyword_score.is_some());
+    }
+
+    #[test]
+    fn rrf_merge_ignores_raw_score_scale() {
+        // A huge BM25 score doesn't matter — only the rank does
+        let vec_results = vec![("v".into(), 0.5)];
+        let kw_results = vec![("k".into(), 1_000_000.0)];
+        let merged = rrf_merge(&vec_results, &kw_results, 1.0, 1.0, 60.0, 10);
+        assert!((merged[0].final_score - merged[1].final_score).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn rrf_merge_top_in_both_scores_one() {
+        let merged = rrf_merge(
+            &[("a".into(), 0.9)],
+            &[("a".into(), 3.0)],
+            0.7,
+            0.3,
+            60.0,
+            10,
+        );
+        assert!((merged[0].final_score - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn rrf_merge_weights_shift_ranking() {
+        let vec_results = vec![("v".into(), 0.9)];
+        let kw_results = vec![("k".into(), 9.0)];
+        let merged = rrf_merge(&vec_results, &kw_results, 0.2, 0.8, 60.0, 10);
+        assert_eq!(merged[0].id, "k");
+        let merged = rrf_merge(&vec_results, &kw_results, 0.8, 0.2, 60.0, 10);
+        assert_eq!(merged[0].id, "v");
+    }
+
+    #[test]
+    fn rrf_merge_respects_limit_and_empty() {
+        let vec_results: Vec<(String, f32)> = (0..20).map(|i| (format!("i{i}"), 0.5)).collect();
+        assert_eq!(rrf_merge(&vec_results, &[], 1.0, 1.0, 60.0, 5).len(), 5);
+        assert!(rrf_merge(&[], &[], 1.0, 1.0, 60.0, 10).is_empty());
+    }
 }
@@ -3,7 +3,7 @@ use crate::memory::{MarkdownMemory, Memory, MemoryCategory, SqliteMemory};
 use anyhow::{bail, Context, Result};
 use directories::UserDirs;
 use rusqlite::{Connection, OpenFlags, OptionalExtension};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -78,26 +78,35 @@ async fn migrate_openclaw_memory(
 
     let memory = target_memory_backend(config)?;
 
+    // Resolve keys up front, then write everything in one batch
+    let mut batch: Vec<(String, String, MemoryCategory)> = Vec::new();
+    let mut pending: HashMap<String, usize> = HashMap::new();
     for (idx, entry) in entries.into_iter().enumerate() {
         let mut key = entry.key.trim().to_string();
         if key.is_empty() {
             key = format!("openclaw_{idx}");
         }
 
-        if let Some(existing) = memory.get(&key).await? {
-            if existing.content.trim() == entry.content.trim() {
+        let existing = match pending.get(&key) {
+            Some(&i) => Some(batch[i].1.clone()),
+            None => memory.get(&key).await?.map(|e| e.content),
+        };
+        if let Some(existing) = existing {
+            if existing.trim() == entry.content.trim() {
                 stats.skipped_unchanged += 1;
                 continue;
             }
 
-            let renamed = next_available_key(memory.as_ref(), &key).await?;
+            let renamed = next_available_key(memory.as_ref(), &key, &pending).await?;
             key = renamed;
             stats.renamed_conflicts += 1;
         }
 
-        memory.store(&key, &entry.content, entry.category).await?;
-        stats.imported += 1;
+        pending.insert(key.clone(), batch.len());
+        batch.push((key, entry.content, entry.category));
     }
+    memory.store_many(&batch).await?;
+    stats.imported = batch.len();
 
     println!("✅ OpenClaw memory migration complete");
     println!("  Source: {}", source_workspace.display());
@@ -115,6 +124,9 @@ fn target_memory_backend(config: &Config) -> Result<Box<dyn Memory>> {
     match config.memory.backend.as_str() {
         "sqlite" => Ok(Box::new(SqliteMemory::new(&config.workspace_dir)?)),
         "markdown" | "none" => Ok(Box::new(MarkdownMemory::new(&config.workspace_dir))),
+        "ephemeral" => bail!(
+            "memory.backend = \"ephemeral\" keeps nothing on disk; switch to sqlite or markdown before migrating"
+        ),
         other => {
             tracing::warn!(
                 "Unknown memory backend '{other}' during migration, defaulting to markdown"
@@ -322,10 +334,14 @@ fn normalize_key(key: &str, fallback_idx: usize) -> String {
     trimmed.to_string()
 }
 
-async fn next_available_key(memory: &dyn Memory, base: &str) -> Result<String> {
+async fn next_available_key(
+    memory: &dyn Memory,
+    base: &str,
+    pending: &HashMap<String, usize>,
+) -> Result<String> {
     for i in 1..=10_000 {
         let candidate = format!("{base}__openclaw_{i}");
-        if memory.get(&candidate).await?.is_none() {
+        if !pending.contains_key(&candidate) && memory.get(&candidate).await?.is_none() {
             return Ok(candidate);
         }
     }
@@ -40,6 +40,7 @@ const BANNER: &str = r"
 
 // ── Main wizard entry point ──────────────────────────────────────
 
+#[allow(clippy::too_many_lines)]
 pub fn run_wizard() -> Result<Config> {
     println!("{}", style(BANNER).cyan().bold());
 
@@ -92,11 +93,13 @@ pub fn run_wizard() -> Result<Config> {
         default_provider: Some(provider),
         default_model: Some(model),
         default_temperature: 0.7,
+        system_prompt: None,
         observability: ObservabilityConfig::default(),
         autonomy: AutonomyConfig::default(),
         runtime: RuntimeConfig::default(),
         reliability: crate::config::ReliabilityConfig::default(),
         model_routes: Vec::new(),
+        routing_rules: Vec::new(),
         heartbeat: HeartbeatConfig::default(),
         channels_config,
         memory: memory_config, // User-selected memory backend
@@ -106,6 +109,13 @@ pub fn run_wizard() -> Result<Config> {
         secrets: secrets_config,
         browser: BrowserConfig::default(),
         identity: crate::config::IdentityConfig::default(),
+        cost: crate::config::CostConfig::default(),
+        response_cache: crate::config::ResponseCacheConfig::default(),
+        generation: crate::config::GenerationConfig::default(),
+        middleware: crate::config::MiddlewareConfig::default(),
+        fanout: crate::config::FanoutConfig::default(),
+        proxy: crate::config::ProxyConfig::default(),
+        providers: crate::config::ProvidersConfig::default(),
     };
 
     println!(
@@ -263,9 +273,20 @@ pub fn run_quick_setup(
             0
         },
         conversation_retention_days: 30,
+        conversation_ttl_days: 0,
+        expiry_sweep_interval_secs: 3600,
+        compaction_after_days: 0,
+        compaction_interval_secs: 21_600,
+        compaction_batch_size: 50,
+        dedup_interval_secs: 0,
+        dedup_similarity: 0.9,
         embedding_provider: "none".to_string(),
+        embedding_api_key: None,
         embedding_model: "text-embedding-3-small".to_string(),
         embedding_dimensions: 1536,
+        search_mode: "hybrid".to_string(),
+        hybrid_fusion: "rrf".to_string(),
+        rrf_k: 60,
         vector_weight: 0.7,
         keyword_weight: 0.3,
         embedding_cache_size: if memory_backend_name == "sqlite" {
@@ -274,6 +295,16 @@ pub fn run_quick_setup(
             0
         },
         chunk_max_tokens: 512,
+        max_entry_bytes: 32_768,
+        sqlite_busy_timeout_ms: 5000,
+        encrypt: false,
+        encryption_key: None,
+        recency_half_life_days: 30,
+        recall_related: false,
+        trash_purge_after_days: 30,
+        retention: std::collections::BTreeMap::new(),
+        backup: crate::config::MemoryBackupConfig::default(),
+        sync: crate::config::MemorySyncConfig::default(),
     };
 
     let config = Config {
@@ -283,11 +314,13 @@ pub fn run_quick_setup(
         default_provider: Some(provider_name.clone()),
         default_model: Some(model.clone()),
         default_temperature: 0.7,
+        system_prompt: None,
         observability: ObservabilityConfig::default(),
         autonomy: AutonomyConfig::default(),
         runtime: RuntimeConfig::default(),
         reliability: crate::config::ReliabilityConfig::default(),
         model_routes: Vec::new(),
+        routing_rules: Vec::new(),
         heartbeat: HeartbeatConfig::default(),
         channels_config: ChannelsConfig::default(),
         memory: memory_config,
@@ -297,6 +330,13 @@ pub fn run_quick_setup(
         secrets: SecretsConfig::default(),
         browser: BrowserConfig::default(),
         identity: crate::config::IdentityConfig::default(),
+        cost: crate::config::CostConfig::default(),
+        response_cache: crate::config::ResponseCacheConfig::default(),
+        generation: crate::config::GenerationConfig::default(),
+        middleware: crate::config::MiddlewareConfig::default(),
+        fanout: crate::config::FanoutConfig::default(),
+        proxy: crate::config::ProxyConfig::default(),
+        providers: crate::config::ProvidersConfig::default(),
     };
 
     config.save()?;
@@ -474,7 +514,7 @@ fn setup_provider() -> Result<(String, String, String)> {
     let tiers = vec![
         "⭐ Recommended (OpenRouter, Venice, Anthropic, OpenAI, Gemini)",
         "⚡ Fast inference (Groq, Fireworks, Together AI)",
-        "🌐 Gateway / proxy (Vercel AI, Cloudflare AI, Amazon Bedrock)",
+        "🌐 Gateway / proxy (Vercel AI, Cloudflare AI, Amazon Bedrock, Azure OpenAI)",
         "🔬 Specialized (Moonshot/Kimi, GLM/Zhipu, MiniMax, Qianfan, Z.AI, Synthetic, OpenCode Zen, Cohere)",
         "🏠 Local / private (Ollama — no API key needed)",
         "🔧 Custom — bring your own OpenAI-compatible API",
@@ -513,6 +553,10 @@ fn setup_provider() -> Result<(String, String, String)> {
             ("vercel", "Vercel AI Gateway"),
             ("cloudflare", "Cloudflare AI Gateway"),
             ("bedrock", "Amazon Bedrock — AWS managed models"),
+            (
+                "azure",
+                "Azure OpenAI — enterprise deployments (set AZURE_OPENAI_ENDPOINT)",
+            ),
         ],
         3 => vec![
             ("moonshot", "Moonshot — Kimi & Kimi Coding"),
@@ -653,6 +697,7 @@ fn setup_provider() -> Result<(String, String, String)> {
             "vercel" => "https://vercel.com/account/tokens",
             "cloudflare" => "https://dash.cloudflare.com/profile/api-tokens",
             "bedrock" => "https://console.aws.amazon.com/iam",
+            "azure" => "https://portal.azure.com",
             "gemini" | "google" | "google-gemini" => "https://aistudio.google.com/app/apikey",
             _ => "",
         };
@@ -773,6 +818,10 @@ fn setup_provider() -> Result<(String, String, String)> {
             ),
             ("mistralai/Mixtral-8x22B-Instruct-v0.1", "Mixtral 8x22B"),
         ],
+        "azure" | "azure-openai" => vec![
+            ("gpt-4o", "gpt-4o deployment"),
+            ("gpt-4o-mini", "gpt-4o-mini deployment"),
+        ],
         "cohere" => vec![
             ("command-r-plus", "Command R+ (flagship)"),
             ("command-r", "Command R (fast)"),
@@ -853,6 +902,7 @@ fn provider_env_var(name: &str) -> &'static str {
         "vercel" | "vercel-ai" => "VERCEL_API_KEY",
         "cloudflare" | "cloudflare-ai" => "CLOUDFLARE_API_KEY",
         "bedrock" | "aws-bedrock" => "AWS_ACCESS_KEY_ID",
+        "azure" | "azure-openai" => "AZURE_OPENAI_API_KEY",
         "gemini" | "google" | "google-gemini" => "GEMINI_API_KEY",
         _ => "API_KEY",
     }
@@ -1095,13 +1145,34 @@ fn setup_memory() -> Result<MemoryConfig> {
         archive_after_days: if backend == "sqlite" { 7 } else { 0 },
         purge_after_days: if backend == "sqlite" { 30 } else { 0 },
         conversation_retention_days: 30,
+        conversation_ttl_days: 0,
+        expiry_sweep_interval_secs: 3600,
+        compaction_after_days: 0,
+        compaction_interval_secs: 21_600,
+        compaction_batch_size: 50,
+        dedup_interval_secs: 0,
+        dedup_similarity: 0.9,
         embedding_provider: "none".to_string(),
+        embedding_api_key: None,
         embedding_model: "text-embedding-3-small".to_string(),
         embedding_dimensions: 1536,
+        search_mode: "hybrid".to_string(),
+        hybrid_fusion: "rrf".to_string(),
+        rrf_k: 60,
         vector_weight: 0.7,
         keyword_weight: 0.3,
         embedding_cache_size: if backend == "sqlite" { 10000 } else { 0 },
         chunk_max_tokens: 512,
+        max_entry_bytes: 32_768,
+        sqlite_busy_timeout_ms: 5000,
+        encrypt: false,
+        encryption_key: None,
+        recency_half_life_days: 30,
+        recall_related: false,
+        trash_purge_after_days: 30,
+        retention: std::collections::BTreeMap::new(),
+        backup: crate::config::MemoryBackupConfig::default(),
+        sync: crate::config::MemorySyncConfig::default(),
     })
 }
 
@@ -1124,6 +1195,17 @@ fn setup_channels() -> Result<ChannelsConfig> {
         whatsapp: None,
         email: None,
         irc: None,
+        xmpp: None,
+        mastodon: None,
+        system_prompts: std::collections::BTreeMap::new(),
+        prompt_templates: std::collections::BTreeMap::new(),
+        cancel_superseded: false,
+        history_turns: 10,
+        overrides: std::collections::BTreeMap::new(),
+        access: std::collections::BTreeMap::new(),
+        rate_limits: std::collections::BTreeMap::new(),
+        polling: std::collections::BTreeMap::new(),
+        bridges: Vec::new(),
     };
 
     loop {
@@ -1285,6 +1367,8 @@ fn setup_channels() -> Result<ChannelsConfig> {
                 config.telegram = Some(TelegramConfig {
                     bot_token: token,
                     allowed_users,
+                    allowed_chats: Vec::new(),
+                    group_mention_only: true,
                 });
             }
             1 => {
@@ -1375,6 +1459,8 @@ fn setup_channels() -> Result<ChannelsConfig> {
                     bot_token: token,
                     guild_id: if guild.is_empty() { None } else { Some(guild) },
                     allowed_users,
+                    channel_ids: Vec::new(),
+                    mention_only: false,
                 });
             }
             2 => {
@@ -1441,7 +1527,9 @@ fn setup_channels() -> Result<ChannelsConfig> {
                 }
 
                 let app_token: String = Input::new()
-                    .with_prompt("  App token (xapp-..., optional, Enter to skip)")
+                    .with_prompt(
+                        "  App token (xapp-..., enables Socket Mode; Enter to poll instead)",
+                    )
                     .allow_empty(true)
                     .interact_text()?;
 
@@ -1492,6 +1580,9 @@ fn setup_channels() -> Result<ChannelsConfig> {
                     } else {
                         Some(channel)
                     },
+                    channel_ids: Vec::new(),
+                    direct_messages: true,
+                    signing_secret: None,
                     allowed_users,
                 });
             }
@@ -1532,7 +1623,11 @@ fn setup_channels() -> Result<ChannelsConfig> {
                         .collect()
                 };
 
-                config.imessage = Some(IMessageConfig { allowed_contacts });
+                config.imessage = Some(IMessageConfig {
+                    allowed_contacts,
+                    poll_interval_secs: 3,
+                    shortcut: None,
+                });
                 println!(
                     "  {} iMessage configured (contacts: {})",
                     style("✅").green().bold(),
@@ -1841,6 +1936,7 @@ fn setup_channels() -> Result<ChannelsConfig> {
                         Some(sasl_password.trim().to_string())
                     },
                     verify_tls: Some(verify_tls),
+                    mention_only: true,
                 });
             }
             7 => {
@@ -0,0 +1,225 @@
+//! Prompt templates: markdown files in `workspace/prompts/` that are rendered
+//! for every message, so prompts can be edited without rebuilding or
+//! restarting. `[channels_config.prompt_templates]` picks a template per
+//! channel or gateway route; it takes the place of that channel's
+//! `system_prompt`.
+//!
+//! Placeholders are written `{{name}}`:
+//! - `{{message}}` — the incoming message
+//! - `{{channel}}`, `{{sender}}` — where it came from
+//! - `{{date}}` (`YYYY-MM-DD`), `{{time}}` (`HH:MM`) — local time
+//! - `{{memory.<key>}}` — content of that memory entry, empty when missing
+//!
+//! Unknown placeholders are left as written.
+
+use crate::config::Config;
+use crate::memory::Memory;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Directory under the workspace that holds `<name>.md` templates.
+pub const PROMPTS_DIR: &str = "prompts";
+
+/// Per-message values available to a template.
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateVars<'a> {
+    pub channel: &'a str,
+    pub sender: Option<&'a str>,
+    pub message: &'a str,
+}
+
+/// Path of template `name`. Names are plain file stems (letters, digits,
+/// `-`, `_`), so a template cannot point outside `workspace/prompts/`.
+pub fn template_path(workspace_dir: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        anyhow::bail!("Invalid prompt template name {name:?}: use letters, digits, - and _");
+    }
+    Ok(workspace_dir.join(PROMPTS_DIR).join(format!("{name}.md")))
+}
+
+/// Read template `name` from `workspace/prompts/<name>.md`.
+pub fn load(workspace_dir: &Path, name: &str) -> Result<String> {
+    let path = template_path(workspace_dir, name)?;
+    std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read prompt template {}", path.display()))
+}
+
+/// Fill in the placeholders of `template`. Memory entries are fetched in one
+/// `get_many` call; a failed lookup renders them empty.
+pub async fn render(template: &str, vars: &TemplateVars<'_>, mem: &dyn Memory) -> String {
+    let memory_keys: Vec<&str> = placeholders(template)
+        .filter_map(|name| name.strip_prefix("memory."))
+        .collect();
+    let memories: HashMap<String, String> = if memory_keys.is_empty() {
+        HashMap::new()
+    } else {
+        match mem.get_many(&memory_keys).await {
+            Ok(entries) => entries.into_iter().map(|e| (e.key, e.content)).collect(),
+            Err(e) => {
+                tracing::warn!("Prompt template memory lookup failed: {e}");
+                HashMap::new()
+            }
+        }
+    };
+
+    let now = chrono::Local::now();
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        out.push_str(&rest[..start]);
+        match name {
+            "message" => out.push_str(vars.message),
+            "channel" => out.push_str(vars.channel),
+            "sender" => out.push_str(vars.sender.unwrap_or_default()),
+            "date" => out.push_str(&now.format("%Y-%m-%d").to_string()),
+            "time" => out.push_str(&now.format("%H:%M").to_string()),
+            _ => match name.strip_prefix("memory.") {
+                Some(key) => out.push_str(memories.get(key).map_or("", String::as_str)),
+                None => out.push_str(&rest[start..start + len + 4]),
+            },
+        }
+        rest = &rest[start + len + 4..];
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+/// Names of the `{{...}}` placeholders in `template`, trimmed.
+fn placeholders(template: &str) -> impl Iterator<Item = &str> {
+    template
+        .split("{{")
+        .skip(1)
+        .filter_map(|part| part.split_once("}}").map(|(name, _)| name.trim()))
+}
+
+/// Load and render template `name`. Failures are logged and yield `None`,
+/// so callers fall back to the plain system prompt.
+pub async fn render_named(
+    workspace_dir: &Path,
+    name: &str,
+    vars: &TemplateVars<'_>,
+    mem: &dyn Memory,
+) -> Option<String> {
+    match load(workspace_dir, name) {
+        Ok(template) => Some(render(&template, vars, mem).await).filter(|p| !p.is_empty()),
+        Err(e) => {
+            tracing::warn!("{e:#}");
+            None
+        }
+    }
+}
+
+/// System prompt persona for `vars.channel`: its rendered prompt template
+/// when one is configured, else [`Config::system_prompt_for`].
+pub async fn persona_for(
+    config: &Config,
+    vars: &TemplateVars<'_>,
+    mem: &dyn Memory,
+) -> Option<String> {
+    if let Some(name) = config.prompt_template_for(vars.channel) {
+        if let Some(prompt) = render_named(&config.workspace_dir, name, vars, mem).await {
+            return Some(prompt);
+        }
+    }
+    config.system_prompt_for(vars.channel).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{InMemoryMemory, MemoryCategory};
+    use tempfile::TempDir;
+
+    fn vars(message: &str) -> TemplateVars<'_> {
+        TemplateVars {
+            channel: "telegram",
+            sender: Some("alice"),
+            message,
+        }
+    }
+
+    #[tokio::test]
+    async fn renders_message_memory_and_date() {
+        let mem = InMemoryMemory::new();
+        mem.store("user_name", "Alice", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let rendered = render(
+            "Talking to {{ memory.user_name }} on {{channel}} ({{date}}).\n{{memory.missing}}Asked: {{message}}",
+            &vars("hi there"),
+            &mem,
+        )
+        .await;
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        assert_eq!(
+            rendered,
+            format!("Talking to Alice on telegram ({today}).\nAsked: hi there")
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_and_unclosed_placeholders_are_kept() {
+        let mem = InMemoryMemory::new();
+        let rendered = render("{{nope}} and {{message", &vars("x"), &mem).await;
+        assert_eq!(rendered, "{{nope}} and {{message");
+    }
+
+    #[test]
+    fn template_names_cannot_escape_the_prompts_dir() {
+        let dir = Path::new("/ws");
+        assert_eq!(
+            template_path(dir, "support-bot").unwrap(),
+            Path::new("/ws/prompts/support-bot.md")
+        );
+        assert!(template_path(dir, "../secrets").is_err());
+        assert!(template_path(dir, "").is_err());
+    }
+
+    #[tokio::test]
+    async fn persona_prefers_template_and_falls_back_to_system_prompt() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join(PROMPTS_DIR)).unwrap();
+        std::fs::write(
+            tmp.path().join(PROMPTS_DIR).join("support.md"),
+            "Support agent. Question: {{message}}\n",
+        )
+        .unwrap();
+        let mut config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            system_prompt: Some("Default persona.".into()),
+            ..Config::default()
+        };
+        config
+            .channels_config
+            .prompt_templates
+            .insert("telegram".into(), "support".into());
+        config
+            .channels_config
+            .prompt_templates
+            .insert("discord".into(), "missing".into());
+        let mem = InMemoryMemory::new();
+
+        let persona = persona_for(&config, &vars("where is my order?"), &mem).await;
+        assert_eq!(
+            persona.as_deref(),
+            Some("Support agent. Question: where is my order?")
+        );
+
+        let discord = TemplateVars {
+            channel: "discord",
+            ..vars("hi")
+        };
+        let persona = persona_for(&config, &discord, &mem).await;
+        assert_eq!(persona.as_deref(), Some("Default persona."));
+    }
+}
@@ -1,8 +1,13 @@
-use crate::providers::traits::Provider;
+use crate::providers::traits::{
+    ChatMessage, ChatOptions, ChatReply, ImageInput, ModelInfo, Provider, TokenUsage,
+};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+/// Anthropic requires `max_tokens`; used when the request leaves it unset.
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
 pub struct AnthropicProvider {
     credential: Option<String>,
     base_url: String,
@@ -17,17 +22,71 @@ struct ChatRequest {
     system: Option<String>,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: MessageContent,
+}
+
+/// Plain text, or content blocks when the message carries images.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+enum MessageContent {
+    Text(String),
+    Blocks(Vec<InputBlock>),
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum InputBlock {
+    Text {
+        text: String,
+    },
+    /// `ImageInput` already serializes as Anthropic's `base64` / `url` source.
+    Image {
+        source: ImageInput,
+    },
+}
+
+impl MessageContent {
+    /// Images go first, as Anthropic recommends, followed by the text.
+    fn from_message(message: &ChatMessage) -> Self {
+        if message.images.is_empty() {
+            return Self::Text(message.content.clone());
+        }
+        let mut blocks: Vec<InputBlock> = message
+            .images
+            .iter()
+            .map(|image| InputBlock::Image {
+                source: image.clone(),
+            })
+            .collect();
+        if !message.content.is_empty() {
+            blocks.push(InputBlock::Text {
+                text: message.content.clone(),
+            });
+        }
+        Self::Blocks(blocks)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,28 +110,66 @@ impl AnthropicProvider {
                 .filter(|k| !k.is_empty())
                 .map(ToString::to_string),
             base_url,
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: crate::proxy::client_with("anthropic", |builder| {
+                builder.connect_timeout(std::time::Duration::from_secs(10))
+            }),
         }
     }
 
     fn is_setup_token(token: &str) -> bool {
         token.starts_with("sk-ant-oat01-")
     }
-}
 
-#[async_trait]
-impl Provider for AnthropicProvider {
-    async fn chat_with_system(
+    /// Authenticated `GET /v1/models`, used by the health check and listing.
+    fn models_request(&self) -> anyhow::Result<reqwest::RequestBuilder> {
+        let credential = self.credential.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Anthropic credentials not set. Set ANTHROPIC_API_KEY or ANTHROPIC_OAUTH_TOKEN (setup-token)."
+            )
+        })?;
+
+        let request = self
+            .client
+            .get(format!("{}/v1/models", self.base_url))
+            .header("anthropic-version", "2023-06-01");
+        Ok(if Self::is_setup_token(credential) {
+            request.header("Authorization", format!("Bearer {credential}"))
+        } else {
+            request.header("x-api-key", credential)
+        })
+    }
+
+    /// Split a conversation into Anthropic's top-level `system` field and the
+    /// user/assistant `messages` list. Multiple system messages are joined.
+    fn split_history(messages: &[ChatMessage]) -> (Option<String>, Vec<Message>) {
+        let system: Vec<&str> = messages
+            .iter()
+            .filter(|m| m.role == "system")
+            .map(|m| m.content.as_str())
+            .collect();
+        let turns = messages
+            .iter()
+            .filter(|m| m.role != "system")
+            .map(|m| Message {
+                role: if m.role == "assistant" {
+                    "assistant".to_string()
+                } else {
+                    "user".to_string()
+                },
+                content: MessageContent::from_message(m),
+            })
+            .collect();
+        let system = (!system.is_empty()).then(|| system.join("\n\n"));
+        (system, turns)
+    }
+
+    async fn send_messages(
         &self,
-        system_prompt: Option<&str>,
-        message: &str,
+        system: Option<String>,
+        messages: Vec<Message>,
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
         let credential = self.credential.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
                 "Anthropic credentials not set. Set ANTHROPIC_API_KEY or ANTHROPIC_OAUTH_TOKEN (setup-token)."
@@ -81,13 +178,12 @@ impl Provider for AnthropicProvider {
 
         let request = ChatRequest {
             model: model.to_string(),
-            max_tokens: 4096,
-            system: system_prompt.map(ToString::to_string),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: message.to_string(),
-            }],
-            temperature,
+            max_tokens: options.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            system,
+            messages,
+            temperature: options.temperature,
+            top_p: options.top_p,
+            stop_sequences: options.stop.clone(),
         };
 
         let mut request = self
@@ -111,15 +207,59 @@ impl Provider for AnthropicProvider {
 
         let chat_response: ChatResponse = response.json().await?;
 
+        let usage = chat_response.usage;
         chat_response
             .content
             .into_iter()
             .next()
-            .map(|c| c.text)
+            .map(|c| ChatReply::new(c.text, usage))
             .ok_or_else(|| anyhow::anyhow!("No response from Anthropic"))
     }
 }
 
+#[async_trait]
+impl Provider for AnthropicProvider {
+    async fn health_check(&self) -> anyhow::Result<()> {
+        super::probe_endpoint("Anthropic", self.models_request()?).await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let request = self.models_request()?.query(&[("limit", "1000")]);
+        let body = super::models::fetch_listing("Anthropic", request).await?;
+        Ok(super::models::parse_openai_listing(&body))
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let messages = vec![Message {
+            role: "user".to_string(),
+            content: message.into(),
+        }];
+        self.send_messages(
+            system_prompt.map(ToString::to_string),
+            messages,
+            model,
+            options,
+        )
+        .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let (system, messages) = Self::split_history(messages);
+        self.send_messages(system, messages, model, options).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,11 +312,28 @@ mod tests {
         assert_eq!(p.base_url, "https://api.anthropic.com");
     }
 
+    #[test]
+    fn history_moves_system_messages_to_top_level_field() {
+        let (system, messages) = AnthropicProvider::split_history(&[
+            ChatMessage::system("You are ZeroClaw"),
+            ChatMessage::user("hi"),
+            ChatMessage::assistant("hello"),
+            ChatMessage::user("what did I say?"),
+        ]);
+        assert_eq!(system.as_deref(), Some("You are ZeroClaw"));
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["user", "assistant", "user"]);
+        assert_eq!(messages[2].content, MessageContent::from("what did I say?"));
+
+        let (system, _) = AnthropicProvider::split_history(&[ChatMessage::user("hi")]);
+        assert!(system.is_none());
+    }
+
     #[tokio::test]
     async fn chat_fails_without_key() {
         let p = AnthropicProvider::new(None);
         let result = p
-            .chat_with_system(None, "hello", "claude-3-opus", 0.7)
+            .chat_with_system(None, "hello", "claude-3-opus", &ChatOptions::new(0.7))
             .await;
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
@@ -196,7 +353,12 @@ mod tests {
     async fn chat_with_system_fails_without_key() {
         let p = AnthropicProvider::new(None);
         let result = p
-            .chat_with_system(Some("You are ZeroClaw"), "hello", "claude-3-opus", 0.7)
+            .chat_with_system(
+                Some("You are ZeroClaw"),
+                "hello",
+                "claude-3-opus",
+                &ChatOptions::new(0.7),
+            )
             .await;
         assert!(result.is_err());
     }
@@ -209,9 +371,11 @@ mod tests {
             system: None,
             messages: vec![Message {
                 role: "user".to_string(),
-                content: "hello".to_string(),
+                content: "hello".into(),
             }],
             temperature: 0.7,
+            top_p: None,
+            stop_sequences: Vec::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(
@@ -230,9 +394,11 @@ mod tests {
             system: Some("You are ZeroClaw".to_string()),
             messages: vec![Message {
                 role: "user".to_string(),
-                content: "hello".to_string(),
+                content: "hello".into(),
             }],
             temperature: 0.7,
+            top_p: None,
+            stop_sequences: Vec::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"system\":\"You are ZeroClaw\""));
@@ -246,6 +412,13 @@ mod tests {
         assert_eq!(resp.content[0].text, "Hello there!");
     }
 
+    #[test]
+    fn chat_response_parses_usage() {
+        let json = r#"{"content":[{"type":"text","text":"Hi"}],"usage":{"input_tokens":12,"output_tokens":3}}"#;
+        let resp: ChatResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.usage, Some(TokenUsage::new(12, 3)));
+    }
+
     #[test]
     fn chat_response_empty_content() {
         let json = r#"{"content":[]}"#;
@@ -263,6 +436,50 @@ mod tests {
         assert_eq!(resp.content[1].text, "Second");
     }
 
+    #[test]
+    fn image_messages_serialize_as_content_blocks() {
+        let message = ChatMessage::user("describe").with_images(vec![
+            ImageInput::base64("image/jpeg", b"jpg"),
+            ImageInput::Url {
+                url: "https://x.test/a.png".into(),
+            },
+        ]);
+        let (_, turns) = AnthropicProvider::split_history(&[message]);
+        let json = serde_json::to_value(&turns[0]).unwrap();
+        assert_eq!(
+            json["content"][0],
+            serde_json::json!({
+                "type": "image",
+                "source": {"type": "base64", "media_type": "image/jpeg", "data": "anBn"}
+            })
+        );
+        assert_eq!(
+            json["content"][1]["source"],
+            serde_json::json!({"type": "url", "url": "https://x.test/a.png"})
+        );
+        assert_eq!(
+            json["content"][2],
+            serde_json::json!({"type": "text", "text": "describe"})
+        );
+    }
+
+    #[test]
+    fn chat_request_serializes_sampling_options() {
+        let req = ChatRequest {
+            model: "claude-3-opus".to_string(),
+            max_tokens: 256,
+            system: None,
+            messages: vec![],
+            temperature: 0.2,
+            top_p: Some(0.9),
+            stop_sequences: vec!["END".to_string()],
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"max_tokens\":256"));
+        assert!(json.contains("\"top_p\":0.9"));
+        assert!(json.contains("\"stop_sequences\":[\"END\"]"));
+    }
+
     #[test]
     fn temperature_range_serializes() {
         for temp in [0.0, 0.5, 1.0, 2.0] {
@@ -272,6 +489,8 @@ mod tests {
                 system: None,
                 messages: vec![],
                 temperature: temp,
+                top_p: None,
+                stop_sequences: Vec::new(),
             };
             let json = serde_json::to_string(&req).unwrap();
             assert!(json.contains(&format!("{temp}")));
@@ -0,0 +1,359 @@
+use super::compatible::MessageContent;
+use crate::providers::traits::{
+    ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo, Provider, TokenUsage,
+};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+/// Data-plane API version used when neither `[providers.azure] api_version`
+/// nor `AZURE_OPENAI_API_VERSION` is set.
+pub const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+/// Azure `OpenAI` Service. Requests go to a per-resource endpoint
+/// (`https://<resource>.openai.azure.com`) and address a *deployment* rather
+/// than a model, with the API version passed as a query parameter and the key
+/// sent in the `api-key` header.
+pub struct AzureOpenAiProvider {
+    api_key: Option<String>,
+    endpoint: Option<String>,
+    deployment: Option<String>,
+    api_version: String,
+    client: Client,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    messages: Vec<Message>,
+    #[serde(flatten)]
+    options: ChatOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    role: String,
+    content: MessageContent,
+}
+
+impl Message {
+    fn single_turn(system_prompt: Option<&str>, message: &str) -> Vec<Self> {
+        let mut messages = Vec::new();
+
+        if let Some(sys) = system_prompt {
+            messages.push(Message {
+                role: "system".to_string(),
+                content: sys.into(),
+            });
+        }
+
+        messages.push(Message {
+            role: "user".to_string(),
+            content: message.into(),
+        });
+        messages
+    }
+
+    fn from_history(messages: &[ChatMessage]) -> Vec<Self> {
+        messages
+            .iter()
+            .map(|m| Message {
+                role: m.role.clone(),
+                content: MessageContent::from_message(m),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: ResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string)
+}
+
+impl AzureOpenAiProvider {
+    /// `endpoint` is the resource URL, e.g. `https://my-resource.openai.azure.com`.
+    pub fn new(api_key: Option<&str>, endpoint: Option<&str>) -> Self {
+        Self {
+            api_key: non_empty(api_key),
+            endpoint: non_empty(endpoint).map(|e| e.trim_end_matches('/').to_string()),
+            deployment: None,
+            api_version: DEFAULT_API_VERSION.to_string(),
+            client: crate::proxy::client_with("azure", |builder| {
+                builder.connect_timeout(std::time::Duration::from_secs(10))
+            }),
+        }
+    }
+
+    /// Send every request to this deployment. Without it the requested model
+    /// name is used as the deployment name.
+    #[must_use]
+    pub fn with_deployment(mut self, deployment: Option<&str>) -> Self {
+        self.deployment = non_empty(deployment);
+        self
+    }
+
+    /// Override the `api-version` query parameter.
+    #[must_use]
+    pub fn with_api_version(mut self, api_version: Option<&str>) -> Self {
+        if let Some(version) = non_empty(api_version) {
+            self.api_version = version;
+        }
+        self
+    }
+
+    fn resource(&self) -> anyhow::Result<&str> {
+        self.endpoint.as_deref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Azure OpenAI endpoint not set. Set [providers.azure] endpoint or AZURE_OPENAI_ENDPOINT, or use \"azure:https://<resource>.openai.azure.com\"."
+            )
+        })
+    }
+
+    fn api_key(&self) -> anyhow::Result<&str> {
+        self.api_key.as_deref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Azure OpenAI API key not set. Set AZURE_OPENAI_API_KEY or edit config.toml."
+            )
+        })
+    }
+
+    fn chat_url(&self, model: &str) -> anyhow::Result<String> {
+        let deployment = self.deployment.as_deref().unwrap_or(model);
+        Ok(format!(
+            "{}/openai/deployments/{deployment}/chat/completions?api-version={}",
+            self.resource()?,
+            self.api_version
+        ))
+    }
+
+    fn models_url(&self) -> anyhow::Result<String> {
+        Ok(format!(
+            "{}/openai/models?api-version={}",
+            self.resource()?,
+            self.api_version
+        ))
+    }
+
+    async fn send(
+        &self,
+        messages: Vec<Message>,
+        model: &str,
+        options: &ChatOptions,
+        stream: Option<bool>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let api_key = self.api_key()?;
+        let url = self.chat_url(model)?;
+
+        let request = ChatRequest {
+            messages,
+            options: options.clone(),
+            stream,
+        };
+
+        let response = self
+            .client
+            .post(url)
+            .header("api-key", api_key)
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(super::api_error("Azure OpenAI", response).await);
+        }
+        Ok(response)
+    }
+
+    async fn chat_messages(
+        &self,
+        messages: Vec<Message>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let response = self.send(messages, model, options, None).await?;
+        let chat_response: ChatResponse = response.json().await?;
+
+        let usage = chat_response.usage;
+        chat_response
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| ChatReply::new(c.message.content.unwrap_or_default(), usage))
+            .ok_or_else(|| anyhow::anyhow!("No response from Azure OpenAI"))
+    }
+
+    async fn stream_messages(
+        &self,
+        messages: Vec<Message>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let response = self.send(messages, model, options, Some(true)).await?;
+        Ok(super::compatible::sse_response_stream(response))
+    }
+}
+
+#[async_trait]
+impl Provider for AzureOpenAiProvider {
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        self.chat_messages(Message::single_turn(system_prompt, message), model, options)
+            .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        self.chat_messages(Message::from_history(messages), model, options)
+            .await
+    }
+
+    async fn health_check(&self) -> anyhow::Result<()> {
+        let api_key = self.api_key()?;
+        let url = self.models_url()?;
+
+        super::probe_endpoint(
+            "Azure OpenAI",
+            self.client.get(url).header("api-key", api_key),
+        )
+        .await
+    }
+
+    /// Base models available to the resource (deployments are named separately).
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let api_key = self.api_key()?;
+        let request = self
+            .client
+            .get(self.models_url()?)
+            .header("api-key", api_key);
+        let body = super::models::fetch_listing("Azure OpenAI", request).await?;
+        Ok(super::models::parse_openai_listing(&body))
+    }
+
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.stream_messages(Message::single_turn(system_prompt, message), model, options)
+            .await
+    }
+
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.stream_messages(Message::from_history(messages), model, options)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> AzureOpenAiProvider {
+        AzureOpenAiProvider::new(Some("azure-key"), Some("https://contoso.openai.azure.com/"))
+    }
+
+    #[test]
+    fn chat_url_uses_model_as_deployment_by_default() {
+        assert_eq!(
+            provider().chat_url("gpt-4o").unwrap(),
+            "https://contoso.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-10-21"
+        );
+    }
+
+    #[test]
+    fn configured_deployment_and_version_override_defaults() {
+        let p = provider()
+            .with_deployment(Some("prod-gpt4"))
+            .with_api_version(Some("2025-01-01-preview"));
+        assert_eq!(
+            p.chat_url("gpt-4o").unwrap(),
+            "https://contoso.openai.azure.com/openai/deployments/prod-gpt4/chat/completions?api-version=2025-01-01-preview"
+        );
+    }
+
+    #[test]
+    fn blank_overrides_are_ignored() {
+        let p = provider()
+            .with_deployment(Some(" "))
+            .with_api_version(Some(""));
+        assert!(p.deployment.is_none());
+        assert_eq!(p.api_version, DEFAULT_API_VERSION);
+    }
+
+    #[tokio::test]
+    async fn chat_fails_without_endpoint() {
+        let p = AzureOpenAiProvider::new(Some("azure-key"), None);
+        let err = p
+            .chat_with_system(None, "hello", "gpt-4o", &ChatOptions::new(0.7))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("AZURE_OPENAI_ENDPOINT"));
+    }
+
+    #[tokio::test]
+    async fn chat_fails_without_key() {
+        let p = AzureOpenAiProvider::new(None, Some("https://contoso.openai.azure.com"));
+        let err = p
+            .chat_with_system(None, "hello", "gpt-4o", &ChatOptions::new(0.7))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("API key not set"));
+    }
+
+    #[test]
+    fn request_omits_model_field() {
+        let req = ChatRequest {
+            messages: Message::single_turn(Some("You are ZeroClaw"), "hello"),
+            options: ChatOptions::new(0.2),
+            stream: None,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(!json.contains("\"model\""));
+        assert!(json.contains("\"role\":\"system\""));
+        assert!(json.contains("\"temperature\":0.2"));
+    }
+
+    #[test]
+    fn response_tolerates_null_content() {
+        let json = r#"{"choices":[{"message":{"content":null}}]}"#;
+        let resp: ChatResponse = serde_json::from_str(json).unwrap();
+        assert!(resp.choices[0].message.content.is_none());
+    }
+}
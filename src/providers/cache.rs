@@ -0,0 +1,400 @@
+//! Reply cache for deterministic requests.
+//!
+//! Requests made at temperature 0 are keyed by model, generation options and
+//! a SHA-256 of the prompt; repeats (cron jobs, heartbeat checks) are answered
+//! from `workspace/cache/responses.db` without calling the provider. Least
+//! recently used entries are evicted past `max_entries`, and entries older
+//! than `ttl_secs` are ignored.
+
+use super::traits::{ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo};
+use super::Provider;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Queries run on Tokio's blocking pool (`spawn_blocking`) against one
+/// connection opened up front, so cache I/O never stalls the executor.
+pub struct CachedProvider {
+    inner: Box<dyn Provider>,
+    conn: Arc<Mutex<Connection>>,
+    max_entries: usize,
+    ttl_secs: u64,
+}
+
+impl CachedProvider {
+    /// Open (creating if needed) the cache database at `db_path`.
+    pub fn new(
+        inner: Box<dyn Provider>,
+        db_path: &Path,
+        max_entries: usize,
+        ttl_secs: u64,
+    ) -> Result<Self> {
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create cache directory: {}", parent.display())
+            })?;
+        }
+
+        let conn = Connection::open(db_path)
+            .with_context(|| format!("Failed to open response cache: {}", db_path.display()))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS response_cache (
+                key         TEXT PRIMARY KEY,
+                model       TEXT,
+                response    TEXT NOT NULL,
+                created_at  INTEGER NOT NULL,
+                accessed_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_response_cache_accessed
+                ON response_cache(accessed_at);",
+        )
+        .context("Failed to initialize response cache schema")?;
+
+        Ok(Self {
+            inner,
+            conn: Arc::new(Mutex::new(conn)),
+            max_entries: max_entries.max(1),
+            ttl_secs,
+        })
+    }
+
+    /// Cache key for a request, or `None` when the request is not deterministic.
+    fn cache_key(model: &str, messages: &[ChatMessage], options: &ChatOptions) -> Option<String> {
+        if options.temperature.abs() > f64::EPSILON {
+            return None;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(model.as_bytes());
+        hasher.update([0]);
+        hasher.update(serde_json::to_vec(options).ok()?);
+        for message in messages {
+            hasher.update([0]);
+            hasher.update(message.role.as_bytes());
+            hasher.update([0]);
+            hasher.update(message.content.as_bytes());
+            for image in &message.images {
+                hasher.update([0]);
+                hasher.update(image.to_url().as_bytes());
+            }
+        }
+        Some(hex::encode(hasher.finalize()))
+    }
+
+    async fn lookup(&self, key: String) -> Result<Option<ChatReply>> {
+        let now = chrono::Utc::now().timestamp();
+        let cutoff = if self.ttl_secs == 0 {
+            i64::MIN
+        } else {
+            now.saturating_sub(i64::try_from(self.ttl_secs).unwrap_or(i64::MAX))
+        };
+        self.with_conn(move |conn| {
+            let hit: Option<(Option<String>, String)> = conn
+                .query_row(
+                    "SELECT model, response FROM response_cache
+                     WHERE key = ?1 AND created_at > ?2",
+                    params![key, cutoff],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            if hit.is_some() {
+                conn.execute(
+                    "UPDATE response_cache SET accessed_at = ?1 WHERE key = ?2",
+                    params![now, key],
+                )?;
+            }
+            Ok(hit.map(|(model, text)| ChatReply {
+                text,
+                usage: None,
+                model,
+            }))
+        })
+        .await
+    }
+
+    async fn store(&self, key: String, reply: &ChatReply) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let max = i64::try_from(self.max_entries).unwrap_or(i64::MAX);
+        let (model, text) = (reply.model.clone(), reply.text.clone());
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO response_cache (key, model, response, created_at, accessed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?4)",
+                params![key, model, text, now],
+            )?;
+            // LRU eviction: keep only max_entries rows
+            conn.execute(
+                "DELETE FROM response_cache WHERE key IN (
+                    SELECT key FROM response_cache
+                    ORDER BY accessed_at ASC, rowid ASC
+                    LIMIT MAX(0, (SELECT COUNT(*) FROM response_cache) - ?1)
+                )",
+                params![max],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Serve `key` from the cache, or call `fetch` and cache its reply.
+    /// Cache failures are logged and never fail the request.
+    async fn cached(
+        &self,
+        key: Option<String>,
+        fetch: impl std::future::Future<Output = Result<ChatReply>>,
+    ) -> Result<ChatReply> {
+        let Some(key) = key else {
+            return fetch.await;
+        };
+        match self.lookup(key.clone()).await {
+            Ok(Some(reply)) => {
+                tracing::debug!("Response cache hit");
+                return Ok(reply);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Response cache lookup failed: {e:#}"),
+        }
+
+        let reply = fetch.await?;
+        if let Err(e) = self.store(key, &reply).await {
+            tracing::warn!("Response cache write failed: {e:#}");
+        }
+        Ok(reply)
+    }
+
+    /// Run `f` against the connection on the blocking thread pool.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+            f(&conn)
+        })
+        .await
+        .context("Response cache task failed")?
+    }
+}
+
+#[async_trait]
+impl Provider for CachedProvider {
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatReply> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        let key = Self::cache_key(model, &messages, options);
+        self.cached(
+            key,
+            self.inner
+                .chat_with_system(system_prompt, message, model, options),
+        )
+        .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatReply> {
+        let key = Self::cache_key(model, messages, options);
+        self.cached(key, self.inner.chat_with_history(messages, model, options))
+            .await
+    }
+
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatStream> {
+        self.inner
+            .stream_chat_with_system(system_prompt, message, model, options)
+            .await
+    }
+
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatStream> {
+        self.inner
+            .stream_chat_with_history(messages, model, options)
+            .await
+    }
+
+    async fn warmup(&self) -> Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        self.inner.list_models().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::traits::TokenUsage;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    struct CountingProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> Result<ChatReply> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(ChatReply::new(
+                format!("{message} #{n}"),
+                Some(TokenUsage::new(10, 5)),
+            ))
+        }
+    }
+
+    fn cached(tmp: &TempDir, max_entries: usize) -> (CachedProvider, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = CachedProvider::new(
+            Box::new(CountingProvider {
+                calls: Arc::clone(&calls),
+            }),
+            &tmp.path().join("cache").join("responses.db"),
+            max_entries,
+            3600,
+        )
+        .unwrap();
+        (provider, calls)
+    }
+
+    #[tokio::test]
+    async fn deterministic_repeats_are_served_from_cache() {
+        let tmp = TempDir::new().unwrap();
+        let (provider, calls) = cached(&tmp, 10);
+
+        let first = provider
+            .chat("ping", "model-a", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        let second = provider
+            .chat("ping", "model-a", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(first.text, "ping #1");
+        assert_eq!(second.text, "ping #1");
+        assert!(second.usage.is_none(), "cache hits cost no tokens");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Different model, system prompt or temperature bypasses the entry.
+        provider
+            .chat("ping", "model-b", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        provider
+            .chat_with_system(Some("be brief"), "ping", "model-a", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        provider
+            .chat("ping", "model-a", &ChatOptions::new(0.7))
+            .await
+            .unwrap();
+        provider
+            .chat("ping", "model-a", &ChatOptions::new(0.7))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+
+        // Other generation options are part of the key.
+        let capped = ChatOptions::new(0.0).with_max_tokens(Some(16));
+        provider.chat("ping", "model-a", &capped).await.unwrap();
+        provider.chat("ping", "model-a", &capped).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test]
+    async fn history_requests_are_keyed_on_every_message() {
+        let tmp = TempDir::new().unwrap();
+        let (provider, calls) = cached(&tmp, 10);
+        let history = vec![ChatMessage::user("a"), ChatMessage::user("b")];
+        provider
+            .chat_with_history(&history, "model", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        provider
+            .chat_with_history(&history, "model", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let other = vec![ChatMessage::user("x"), ChatMessage::user("b")];
+        provider
+            .chat_with_history(&other, "model", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_entries_are_evicted() {
+        let tmp = TempDir::new().unwrap();
+        let (provider, calls) = cached(&tmp, 1);
+        provider
+            .chat("one", "model", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        provider
+            .chat("two", "model", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        provider
+            .chat("one", "model", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn entries_persist_across_instances() {
+        let tmp = TempDir::new().unwrap();
+        let (provider, _) = cached(&tmp, 10);
+        provider
+            .chat("ping", "model", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+
+        let (reopened, calls) = cached(&tmp, 10);
+        let reply = reopened
+            .chat("ping", "model", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "ping #1");
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+}
@@ -2,8 +2,11 @@
 //! Most LLM APIs follow the same `/v1/chat/completions` format.
 //! This module provides a single implementation that works for all of them.
 
-use crate::providers::traits::{ChatMessage, Provider};
+use crate::providers::traits::{
+    ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo, Provider, TokenUsage,
+};
 use async_trait::async_trait;
+use futures_util::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +18,7 @@ pub struct OpenAiCompatibleProvider {
     pub(crate) base_url: String,
     pub(crate) api_key: Option<String>,
     pub(crate) auth_header: AuthStyle,
+    key_optional: bool,
     client: Client,
 }
 
@@ -36,11 +40,29 @@ impl OpenAiCompatibleProvider {
             base_url: base_url.trim_end_matches('/').to_string(),
             api_key: api_key.map(ToString::to_string),
             auth_header: auth_style,
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            key_optional: false,
+            client: crate::proxy::client_with(name, |builder| {
+                builder.connect_timeout(std::time::Duration::from_secs(10))
+            }),
+        }
+    }
+
+    /// Allow requests without an API key (self-hosted servers such as vLLM or
+    /// LM Studio). No auth header is sent when the key is missing.
+    #[must_use]
+    pub fn with_optional_key(mut self) -> Self {
+        self.key_optional = true;
+        self
+    }
+
+    fn api_key(&self) -> anyhow::Result<&str> {
+        match self.api_key.as_deref() {
+            Some(key) => Ok(key),
+            None if self.key_optional => Ok(""),
+            None => anyhow::bail!(
+                "{} API key not set. Run `zeroclaw onboard` or set the appropriate env var.",
+                self.name
+            ),
         }
     }
 
@@ -65,24 +87,105 @@ impl OpenAiCompatibleProvider {
             format!("{}/v1/responses", self.base_url)
         }
     }
+
+    /// Open a streaming chat completions request (`"stream": true`).
+    async fn stream_messages(
+        &self,
+        messages: Vec<Message>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let api_key = self.api_key()?;
+
+        let request = ChatRequest {
+            model: model.to_string(),
+            messages,
+            options: options.clone(),
+            stream: Some(true),
+        };
+
+        let url = self.chat_completions_url();
+        let response = self
+            .apply_auth_header(self.client.post(&url).json(&request), api_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(super::api_error(&self.name, response).await);
+        }
+
+        Ok(sse_response_stream(response))
+    }
 }
 
 #[derive(Debug, Serialize)]
 struct ChatRequest {
     model: String,
     messages: Vec<Message>,
-    temperature: f64,
+    #[serde(flatten)]
+    options: ChatOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: MessageContent,
+}
+
+/// Chat message content: plain text, or text plus `image_url` parts when the
+/// message carries images. Shared by the OpenAI-style providers.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub(crate) enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct ImageUrl {
+    url: String,
+}
+
+impl MessageContent {
+    pub(crate) fn from_message(message: &ChatMessage) -> Self {
+        if message.images.is_empty() {
+            return Self::Text(message.content.clone());
+        }
+        let mut parts = Vec::with_capacity(message.images.len() + 1);
+        if !message.content.is_empty() {
+            parts.push(ContentPart::Text {
+                text: message.content.clone(),
+            });
+        }
+        parts.extend(message.images.iter().map(|image| ContentPart::ImageUrl {
+            image_url: ImageUrl {
+                url: image.to_url(),
+            },
+        }));
+        Self::Parts(parts)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct ApiChatResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -95,6 +198,98 @@ struct ResponseMessage {
     content: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChoice {
+    #[serde(default)]
+    delta: StreamDelta,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StreamDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// One parsed line of an OpenAI-style `text/event-stream` body.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SseLine {
+    /// A non-empty text delta.
+    Delta(String),
+    /// The `[DONE]` sentinel.
+    Done,
+    /// Comments, keep-alives, role-only deltas and anything unparseable.
+    Skip,
+}
+
+pub(crate) fn parse_sse_line(line: &str) -> SseLine {
+    let Some(data) = line.strip_prefix("data:") else {
+        return SseLine::Skip;
+    };
+    let data = data.trim();
+    if data == "[DONE]" {
+        return SseLine::Done;
+    }
+    serde_json::from_str::<StreamChunk>(data)
+        .ok()
+        .and_then(|chunk| chunk.choices.into_iter().next())
+        .and_then(|choice| choice.delta.content)
+        .filter(|text| !text.is_empty())
+        .map_or(SseLine::Skip, SseLine::Delta)
+}
+
+/// Forward the text deltas of a streaming chat completions response into a
+/// `ChatStream`. Lines are split on raw bytes so multi-byte characters that
+/// straddle network chunks are decoded intact.
+pub(crate) fn sse_response_stream(response: reqwest::Response) -> ChatStream {
+    let (tx, rx) = tokio::sync::mpsc::channel(64);
+
+    tokio::spawn(async move {
+        let mut body = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+
+        loop {
+            // Stop reading as soon as the receiver goes away, even while the
+            // provider is silent, so the connection is released.
+            let chunk = tokio::select! {
+                () = tx.closed() => return,
+                chunk = body.next() => chunk,
+            };
+            let Some(chunk) = chunk else {
+                return;
+            };
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    let _ = tx.send(Err(e.into())).await;
+                    return;
+                }
+            };
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                match parse_sse_line(String::from_utf8_lossy(&line).trim()) {
+                    SseLine::Delta(text) => {
+                        if tx.send(Ok(text)).await.is_err() {
+                            return;
+                        }
+                    }
+                    SseLine::Done => return,
+                    SseLine::Skip => {}
+                }
+            }
+        }
+    });
+
+    rx
+}
+
 #[derive(Debug, Serialize)]
 struct ResponsesRequest {
     model: String,
@@ -117,6 +312,8 @@ struct ResponsesResponse {
     output: Vec<ResponsesOutput>,
     #[serde(default)]
     output_text: Option<String>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -175,6 +372,9 @@ impl OpenAiCompatibleProvider {
         req: reqwest::RequestBuilder,
         api_key: &str,
     ) -> reqwest::RequestBuilder {
+        if api_key.is_empty() && self.key_optional {
+            return req;
+        }
         match &self.auth_header {
             AuthStyle::Bearer => req.header("Authorization", format!("Bearer {api_key}")),
             AuthStyle::XApiKey => req.header("x-api-key", api_key),
@@ -188,7 +388,7 @@ impl OpenAiCompatibleProvider {
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
-    ) -> anyhow::Result<String> {
+    ) -> anyhow::Result<ChatReply> {
         let request = ResponsesRequest {
             model: model.to_string(),
             input: vec![ResponsesInput {
@@ -213,45 +413,71 @@ impl OpenAiCompatibleProvider {
 
         let responses: ResponsesResponse = response.json().await?;
 
+        let usage = responses.usage;
         extract_responses_text(responses)
+            .map(|text| ChatReply::new(text, usage))
             .ok_or_else(|| anyhow::anyhow!("No response from {} Responses API", self.name))
     }
 }
 
 #[async_trait]
 impl Provider for OpenAiCompatibleProvider {
+    async fn health_check(&self) -> anyhow::Result<()> {
+        let api_key = self.api_key()?;
+
+        // A full chat/completions URL gives us no listing endpoint to probe.
+        if self.base_url.contains("chat/completions") {
+            return Ok(());
+        }
+
+        let request = self.client.get(format!("{}/models", self.base_url));
+        super::probe_endpoint(&self.name, self.apply_auth_header(request, api_key)).await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let api_key = self.api_key()?;
+        if self.base_url.contains("chat/completions") {
+            anyhow::bail!(
+                "{} is configured with a full chat/completions URL, which has no model listing",
+                self.name
+            );
+        }
+
+        let request = self.client.get(format!("{}/models", self.base_url));
+        let body =
+            super::models::fetch_listing(&self.name, self.apply_auth_header(request, api_key))
+                .await?;
+        Ok(super::models::parse_openai_listing(&body))
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
-        let api_key = self.api_key.as_ref().ok_or_else(|| {
-            anyhow::anyhow!(
-                "{} API key not set. Run `zeroclaw onboard` or set the appropriate env var.",
-                self.name
-            )
-        })?;
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let api_key = self.api_key()?;
 
         let mut messages = Vec::new();
 
         if let Some(sys) = system_prompt {
             messages.push(Message {
                 role: "system".to_string(),
-                content: sys.to_string(),
+                content: sys.into(),
             });
         }
 
         messages.push(Message {
             role: "user".to_string(),
-            content: message.to_string(),
+            content: message.into(),
         });
 
         let request = ChatRequest {
             model: model.to_string(),
             messages,
-            temperature,
+            options: options.clone(),
+            stream: None,
         };
 
         let url = self.chat_completions_url();
@@ -283,11 +509,12 @@ impl Provider for OpenAiCompatibleProvider {
 
         let chat_response: ApiChatResponse = response.json().await?;
 
+        let usage = chat_response.usage;
         chat_response
             .choices
             .into_iter()
             .next()
-            .map(|c| c.message.content)
+            .map(|c| ChatReply::new(c.message.content, usage))
             .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))
     }
 
@@ -295,27 +522,23 @@ impl Provider for OpenAiCompatibleProvider {
         &self,
         messages: &[ChatMessage],
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
-        let api_key = self.api_key.as_ref().ok_or_else(|| {
-            anyhow::anyhow!(
-                "{} API key not set. Run `zeroclaw onboard` or set the appropriate env var.",
-                self.name
-            )
-        })?;
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let api_key = self.api_key()?;
 
         let api_messages: Vec<Message> = messages
             .iter()
             .map(|m| Message {
                 role: m.role.clone(),
-                content: m.content.clone(),
+                content: MessageContent::from_message(m),
             })
             .collect();
 
         let request = ChatRequest {
             model: model.to_string(),
             messages: api_messages,
-            temperature,
+            options: options.clone(),
+            stream: None,
         };
 
         let url = self.chat_completions_url();
@@ -355,18 +578,61 @@ impl Provider for OpenAiCompatibleProvider {
 
         let chat_response: ApiChatResponse = response.json().await?;
 
+        let usage = chat_response.usage;
         chat_response
             .choices
             .into_iter()
             .next()
-            .map(|c| c.message.content)
+            .map(|c| ChatReply::new(c.message.content, usage))
             .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))
     }
+
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let mut messages = Vec::new();
+
+        if let Some(sys) = system_prompt {
+            messages.push(Message {
+                role: "system".to_string(),
+                content: sys.into(),
+            });
+        }
+
+        messages.push(Message {
+            role: "user".to_string(),
+            content: message.into(),
+        });
+
+        self.stream_messages(messages, model, options).await
+    }
+
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let api_messages = messages
+            .iter()
+            .map(|m| Message {
+                role: m.role.clone(),
+                content: MessageContent::from_message(m),
+            })
+            .collect();
+
+        self.stream_messages(api_messages, model, options).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::traits::ImageInput;
 
     fn make_provider(name: &str, url: &str, key: Option<&str>) -> OpenAiCompatibleProvider {
         OpenAiCompatibleProvider::new(name, url, key, AuthStyle::Bearer)
@@ -396,7 +662,20 @@ mod tests {
     async fn chat_fails_without_key() {
         let p = make_provider("Venice", "https://api.venice.ai", None);
         let result = p
-            .chat_with_system(None, "hello", "llama-3.3-70b", 0.7)
+            .chat_with_system(None, "hello", "llama-3.3-70b", &ChatOptions::new(0.7))
+            .await;
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Venice API key not set"));
+    }
+
+    #[tokio::test]
+    async fn stream_fails_without_key() {
+        let p = make_provider("Venice", "https://api.venice.ai", None);
+        let result = p
+            .stream_chat_with_system(None, "hello", "llama-3.3-70b", &ChatOptions::new(0.7))
             .await;
         assert!(result.is_err());
         assert!(result
@@ -405,6 +684,61 @@ mod tests {
             .contains("Venice API key not set"));
     }
 
+    #[test]
+    fn stream_request_includes_stream_flag() {
+        let req = ChatRequest {
+            model: "m".to_string(),
+            messages: vec![],
+            options: ChatOptions::new(0.0),
+            stream: Some(true),
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"stream\":true"));
+    }
+
+    #[test]
+    fn sse_line_parses_content_delta() {
+        let line = r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#;
+        assert_eq!(parse_sse_line(line), SseLine::Delta("Hel".into()));
+    }
+
+    #[test]
+    fn sse_line_detects_done_sentinel() {
+        assert_eq!(parse_sse_line("data: [DONE]"), SseLine::Done);
+        assert_eq!(parse_sse_line("data:[DONE]"), SseLine::Done);
+    }
+
+    #[test]
+    fn sse_line_skips_role_only_and_comments() {
+        let role_only = r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#;
+        assert_eq!(parse_sse_line(role_only), SseLine::Skip);
+        assert_eq!(parse_sse_line(": keep-alive"), SseLine::Skip);
+        assert_eq!(parse_sse_line(""), SseLine::Skip);
+        assert_eq!(parse_sse_line("data: not json"), SseLine::Skip);
+    }
+
+    #[test]
+    fn image_messages_serialize_as_content_parts() {
+        let message = ChatMessage::user("what is this?").with_images(vec![
+            ImageInput::Url {
+                url: "https://x.test/cat.png".into(),
+            },
+            ImageInput::base64("image/png", b"png"),
+        ]);
+        let json = serde_json::to_value(MessageContent::from_message(&message)).unwrap();
+        assert_eq!(
+            json[0],
+            serde_json::json!({"type": "text", "text": "what is this?"})
+        );
+        assert_eq!(json[1]["type"], "image_url");
+        assert_eq!(json[1]["image_url"]["url"], "https://x.test/cat.png");
+        assert_eq!(json[2]["image_url"]["url"], "data:image/png;base64,cG5n");
+
+        let plain =
+            serde_json::to_value(MessageContent::from_message(&ChatMessage::user("hi"))).unwrap();
+        assert_eq!(plain, serde_json::json!("hi"));
+    }
+
     #[test]
     fn request_serializes_correctly() {
         let req = ChatRequest {
@@ -412,14 +746,15 @@ mod tests {
             messages: vec![
                 Message {
                     role: "system".to_string(),
-                    content: "You are ZeroClaw".to_string(),
+                    content: "You are ZeroClaw".into(),
                 },
                 Message {
                     role: "user".to_string(),
-                    content: "hello".to_string(),
+                    content: "hello".into(),
                 },
             ],
-            temperature: 0.7,
+            options: ChatOptions::new(0.7),
+            stream: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("llama-3.3-70b"));
@@ -434,6 +769,19 @@ mod tests {
         assert_eq!(resp.choices[0].message.content, "Hello from Venice!");
     }
 
+    #[test]
+    fn response_parses_usage() {
+        let json = r#"{"choices":[{"message":{"content":"Hi"}}],"usage":{"prompt_tokens":21,"completion_tokens":5,"total_tokens":26}}"#;
+        let resp: ApiChatResponse = serde_json::from_str(json).unwrap();
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage, TokenUsage::new(21, 5));
+        assert_eq!(usage.total_tokens(), 26);
+
+        let json = r#"{"output_text":"Hi","usage":{"input_tokens":8,"output_tokens":2}}"#;
+        let resp: ResponsesResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.usage, Some(TokenUsage::new(8, 2)));
+    }
+
     #[test]
     fn response_empty_choices() {
         let json = r#"{"choices":[]}"#;
@@ -463,6 +811,37 @@ mod tests {
         assert!(matches!(p.auth_header, AuthStyle::Custom(_)));
     }
 
+    #[test]
+    fn optional_key_skips_auth_header_when_missing() {
+        let p = make_provider("vLLM", "http://localhost:8000/v1", None).with_optional_key();
+        let key = p.api_key().unwrap();
+        let request = p
+            .apply_auth_header(p.client.get("http://localhost:8000/v1/models"), key)
+            .build()
+            .unwrap();
+        assert!(request.headers().get("Authorization").is_none());
+
+        let p = make_provider("vLLM", "http://localhost:8000/v1", Some("tok")).with_optional_key();
+        let request = p
+            .apply_auth_header(
+                p.client.get("http://localhost:8000/v1/models"),
+                p.api_key().unwrap(),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()["Authorization"], "Bearer tok");
+    }
+
+    #[test]
+    fn required_key_still_errors_when_missing() {
+        let p = make_provider("Groq", "https://api.groq.com/openai", None);
+        assert!(p
+            .api_key()
+            .unwrap_err()
+            .to_string()
+            .contains("API key not set"));
+    }
+
     #[tokio::test]
     async fn all_compatible_providers_fail_without_key() {
         let providers = vec![
@@ -476,7 +855,9 @@ mod tests {
         ];
 
         for p in providers {
-            let result = p.chat_with_system(None, "test", "model", 0.7).await;
+            let result = p
+                .chat_with_system(None, "test", "model", &ChatOptions::new(0.7))
+                .await;
             assert!(result.is_err(), "{} should fail without key", p.name);
             assert!(
                 result.unwrap_err().to_string().contains("API key not set"),
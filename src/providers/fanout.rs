@@ -0,0 +1,393 @@
+//! Multi-model fan-out: requests for the [`FANOUT_MODEL`] model are sent to
+//! every configured model concurrently and one reply is picked — the first
+//! to finish, or the one a cheap arbiter model judges best. The reply's
+//! `model` names the winner. Other models pass straight through.
+
+use super::traits::{ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo, TokenUsage};
+use super::Provider;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+/// Model name that triggers a fan-out.
+pub const FANOUT_MODEL: &str = "fanout";
+
+/// How the winning reply is chosen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FanoutStrategy {
+    /// First successful reply; the slower requests are cancelled.
+    First,
+    /// Wait for every model, then ask `model` which reply is best.
+    Arbiter { model: String },
+}
+
+pub struct FanoutProvider {
+    inner: Arc<dyn Provider>,
+    models: Vec<String>,
+    strategy: FanoutStrategy,
+}
+
+/// A successful candidate reply and the model it was requested from.
+struct Candidate {
+    model: String,
+    reply: ChatReply,
+}
+
+impl FanoutProvider {
+    pub fn new(inner: Box<dyn Provider>, models: Vec<String>, strategy: FanoutStrategy) -> Self {
+        Self {
+            inner: Arc::from(inner),
+            models,
+            strategy,
+        }
+    }
+
+    fn spawn_all(
+        &self,
+        messages: &[ChatMessage],
+        options: &ChatOptions,
+    ) -> JoinSet<(String, Result<ChatReply>)> {
+        let mut tasks = JoinSet::new();
+        for model in &self.models {
+            let inner = Arc::clone(&self.inner);
+            let messages = messages.to_vec();
+            let options = options.clone();
+            let model = model.clone();
+            tasks.spawn(async move {
+                let reply = inner.chat_with_history(&messages, &model, &options).await;
+                (model, reply)
+            });
+        }
+        tasks
+    }
+
+    /// Wait for the next finished task, logging failures. `None` once every
+    /// task is done.
+    async fn next_candidate(
+        tasks: &mut JoinSet<(String, Result<ChatReply>)>,
+        errors: &mut Vec<String>,
+    ) -> Option<Candidate> {
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((model, Ok(reply))) => return Some(Candidate { model, reply }),
+                Ok((model, Err(e))) => {
+                    tracing::warn!(model = model.as_str(), "Fan-out candidate failed: {e}");
+                    errors.push(format!("{model}: {e}"));
+                }
+                Err(e) => errors.push(format!("task failed: {e}")),
+            }
+        }
+        None
+    }
+
+    async fn fan_out(&self, messages: &[ChatMessage], options: &ChatOptions) -> Result<ChatReply> {
+        if self.models.is_empty() {
+            anyhow::bail!("Fan-out requested but [fanout] models is empty");
+        }
+        let mut tasks = self.spawn_all(messages, options);
+        let mut errors = Vec::new();
+
+        let arbiter = match &self.strategy {
+            FanoutStrategy::First => {
+                if let Some(winner) = Self::next_candidate(&mut tasks, &mut errors).await {
+                    // Dropping the set aborts the requests still running.
+                    let usage = winner.reply.usage;
+                    return Ok(winner.into_reply(usage));
+                }
+                anyhow::bail!("All fan-out models failed: {}", errors.join("; "));
+            }
+            FanoutStrategy::Arbiter { model } => model,
+        };
+
+        let mut candidates = Vec::new();
+        while let Some(candidate) = Self::next_candidate(&mut tasks, &mut errors).await {
+            candidates.push(candidate);
+        }
+        if candidates.is_empty() {
+            anyhow::bail!("All fan-out models failed: {}", errors.join("; "));
+        }
+        let mut usage = total_usage(candidates.iter().map(|c| c.reply.usage));
+        let winner = if candidates.len() == 1 {
+            0
+        } else {
+            match self.judge(arbiter, messages, &candidates).await {
+                Ok((index, judge_usage)) => {
+                    usage = total_usage([usage, judge_usage]);
+                    index
+                }
+                Err(e) => {
+                    tracing::warn!("Fan-out arbiter failed, using the first reply: {e}");
+                    0
+                }
+            }
+        };
+        Ok(candidates.swap_remove(winner).into_reply(usage))
+    }
+
+    /// Ask the arbiter for the best candidate; returns its index.
+    async fn judge(
+        &self,
+        arbiter: &str,
+        messages: &[ChatMessage],
+        candidates: &[Candidate],
+    ) -> Result<(usize, Option<TokenUsage>)> {
+        let question = messages
+            .iter()
+            .rfind(|m| m.role == "user")
+            .map_or("", |m| m.content.as_str());
+        let mut prompt = format!("Question:\n{question}\n");
+        for (i, candidate) in candidates.iter().enumerate() {
+            let _ = write!(prompt, "\nAnswer {}:\n{}\n", i + 1, candidate.reply.text);
+        }
+        prompt.push_str("\nReply with only the number of the best answer.");
+
+        let verdict = self
+            .inner
+            .chat_with_system(
+                Some("You compare answers to a question and pick the most accurate, helpful one."),
+                &prompt,
+                arbiter,
+                &ChatOptions::new(0.0),
+            )
+            .await?;
+        let index = parse_choice(&verdict.text, candidates.len())
+            .ok_or_else(|| anyhow::anyhow!("Unusable arbiter verdict: {}", verdict.text.trim()))?;
+        Ok((index, verdict.usage))
+    }
+}
+
+impl Candidate {
+    fn into_reply(self, usage: Option<TokenUsage>) -> ChatReply {
+        let model = self.reply.model.unwrap_or(self.model);
+        ChatReply::new(self.reply.text, usage).with_model(model)
+    }
+}
+
+/// Sum the usage that was reported; `None` when nothing was.
+fn total_usage(usages: impl IntoIterator<Item = Option<TokenUsage>>) -> Option<TokenUsage> {
+    usages.into_iter().flatten().reduce(|a, b| {
+        TokenUsage::new(
+            a.prompt_tokens + b.prompt_tokens,
+            a.completion_tokens + b.completion_tokens,
+        )
+    })
+}
+
+/// First number in `verdict`, as a 0-based index below `count`.
+fn parse_choice(verdict: &str, count: usize) -> Option<usize> {
+    let digits: String = verdict
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(char::is_ascii_digit)
+        .collect();
+    let choice: usize = digits.parse().ok()?;
+    (1..=count).contains(&choice).then(|| choice - 1)
+}
+
+#[async_trait]
+impl Provider for FanoutProvider {
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatReply> {
+        if model != FANOUT_MODEL {
+            return self
+                .inner
+                .chat_with_system(system_prompt, message, model, options)
+                .await;
+        }
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.fan_out(&messages, options).await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatReply> {
+        if model != FANOUT_MODEL {
+            return self.inner.chat_with_history(messages, model, options).await;
+        }
+        self.fan_out(messages, options).await
+    }
+
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatStream> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.stream_chat_with_history(&messages, model, options)
+            .await
+    }
+
+    /// A fan-out reply is only known once the winner is picked, so it is
+    /// delivered as a single chunk.
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatStream> {
+        if model != FANOUT_MODEL {
+            return self
+                .inner
+                .stream_chat_with_history(messages, model, options)
+                .await;
+        }
+        let text = self.fan_out(messages, options).await?.text;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let _ = tx.send(Ok(text)).await;
+        Ok(rx)
+    }
+
+    async fn warmup(&self) -> Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        self.inner.list_models().await
+    }
+}
+
+/// Wrap `provider` in a [`FanoutProvider`] when `[fanout]` lists models;
+/// otherwise return it unchanged.
+pub fn from_config(
+    provider: Box<dyn Provider>,
+    config: &crate::config::FanoutConfig,
+) -> Result<Box<dyn Provider>> {
+    if config.models.is_empty() {
+        return Ok(provider);
+    }
+    let strategy = match config.strategy.as_str() {
+        "first" => FanoutStrategy::First,
+        "arbiter" => {
+            let Some(model) = config.arbiter_model.clone() else {
+                anyhow::bail!("[fanout] strategy = \"arbiter\" requires arbiter_model");
+            };
+            FanoutStrategy::Arbiter { model }
+        }
+        other => anyhow::bail!("Unknown [fanout] strategy {other:?}: use \"first\" or \"arbiter\""),
+    };
+    Ok(Box::new(FanoutProvider::new(
+        provider,
+        config.models.clone(),
+        strategy,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Replies `answer from <model>` after a per-model delay; `broken`
+    /// fails and `judge` votes for answer 2.
+    struct Models;
+
+    #[async_trait]
+    impl Provider for Models {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            model: &str,
+            _options: &ChatOptions,
+        ) -> Result<ChatReply> {
+            let delay = match model {
+                "fast" => 10,
+                "slow" => 200,
+                "judge" => {
+                    assert!(message.contains("Answer 2:"));
+                    return Ok(ChatReply::new("2", Some(TokenUsage::new(5, 1))));
+                }
+                "broken" => anyhow::bail!("model unavailable"),
+                _ => 50,
+            };
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok(ChatReply::new(
+                format!("answer from {model}"),
+                Some(TokenUsage::new(10, 2)),
+            ))
+        }
+    }
+
+    fn fanout(models: &[&str], strategy: FanoutStrategy) -> FanoutProvider {
+        FanoutProvider::new(
+            Box::new(Models),
+            models.iter().map(|m| (*m).to_string()).collect(),
+            strategy,
+        )
+    }
+
+    #[tokio::test]
+    async fn first_strategy_returns_fastest_successful_model() {
+        let provider = fanout(&["slow", "broken", "fast"], FanoutStrategy::First);
+        let reply = provider
+            .chat("hi", FANOUT_MODEL, &ChatOptions::new(0.7))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "answer from fast");
+        assert_eq!(reply.model.as_deref(), Some("fast"));
+        assert_eq!(reply.usage, Some(TokenUsage::new(10, 2)));
+
+        let reply = provider
+            .chat("hi", "slow", &ChatOptions::new(0.7))
+            .await
+            .unwrap();
+        assert_eq!(reply.model, None, "other models pass straight through");
+    }
+
+    #[tokio::test]
+    async fn arbiter_picks_winner_and_usage_covers_every_call() {
+        let strategy = FanoutStrategy::Arbiter {
+            model: "judge".into(),
+        };
+        let provider = fanout(&["fast", "broken", "slow"], strategy);
+        let reply = provider
+            .chat("hi", FANOUT_MODEL, &ChatOptions::new(0.7))
+            .await
+            .unwrap();
+        // Candidates are ordered by completion: fast, then slow.
+        assert_eq!(reply.model.as_deref(), Some("slow"));
+        assert_eq!(reply.usage, Some(TokenUsage::new(25, 5)));
+    }
+
+    #[tokio::test]
+    async fn fails_only_when_every_model_fails() {
+        let provider = fanout(&["broken"], FanoutStrategy::First);
+        let err = provider
+            .chat("hi", FANOUT_MODEL, &ChatOptions::new(0.7))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("model unavailable"));
+    }
+
+    #[test]
+    fn parses_arbiter_verdicts() {
+        assert_eq!(parse_choice("2", 3), Some(1));
+        assert_eq!(parse_choice("Answer 3 is best.", 3), Some(2));
+        assert_eq!(parse_choice("4", 3), None);
+        assert_eq!(parse_choice("none", 3), None);
+    }
+}
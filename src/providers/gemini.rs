@@ -3,7 +3,10 @@
 //! - Gemini CLI OAuth tokens (reuse existing ~/.gemini/ authentication)
 //! - Google Cloud ADC (`GOOGLE_APPLICATION_CREDENTIALS`)
 
-use crate::providers::traits::Provider;
+use crate::providers::traits::{
+    ChatMessage, ChatOptions, ChatReply, ImageInput, ModelInfo, Provider, ResponseFormat,
+    TokenUsage,
+};
 use async_trait::async_trait;
 use directories::UserDirs;
 use reqwest::Client;
@@ -70,9 +73,71 @@ struct Content {
     parts: Vec<Part>,
 }
 
+impl Content {
+    fn text(role: Option<&str>, text: &str) -> Self {
+        Self {
+            role: role.map(ToString::to_string),
+            parts: vec![Part::Text {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    /// Text followed by any attached images.
+    fn message(role: &str, message: &ChatMessage) -> Self {
+        let mut content = Self::text(Some(role), &message.content);
+        content.parts.extend(message.images.iter().map(Part::from));
+        content
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Part {
+    Text {
+        text: String,
+    },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: InlineData,
+    },
+    FileData {
+        #[serde(rename = "fileData")]
+        file_data: FileData,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InlineData {
+    mime_type: String,
+    data: String,
+}
+
 #[derive(Debug, Serialize)]
-struct Part {
-    text: String,
+#[serde(rename_all = "camelCase")]
+struct FileData {
+    mime_type: String,
+    file_uri: String,
+}
+
+impl From<&ImageInput> for Part {
+    fn from(image: &ImageInput) -> Self {
+        match image {
+            ImageInput::Base64 { media_type, data } => Self::InlineData {
+                inline_data: InlineData {
+                    mime_type: media_type.clone(),
+                    data: data.clone(),
+                },
+            },
+            ImageInput::Url { url } => Self::FileData {
+                file_data: FileData {
+                    mime_type: image.media_type().to_string(),
+                    file_uri: url.clone(),
+                },
+            },
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -80,12 +145,50 @@ struct GenerationConfig {
     temperature: f64,
     #[serde(rename = "maxOutputTokens")]
     max_output_tokens: u32,
+    #[serde(rename = "topP", skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(rename = "stopSequences", skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<&'static str>,
+    #[serde(rename = "responseJsonSchema", skip_serializing_if = "Option::is_none")]
+    response_json_schema: Option<serde_json::Value>,
+}
+
+impl From<&ChatOptions> for GenerationConfig {
+    fn from(options: &ChatOptions) -> Self {
+        Self {
+            temperature: options.temperature,
+            max_output_tokens: options.max_tokens.unwrap_or(8192),
+            top_p: options.top_p,
+            stop_sequences: options.stop.clone(),
+            seed: options.seed,
+            response_mime_type: options.response_format.as_ref().map(|_| "application/json"),
+            response_json_schema: options
+                .response_format
+                .as_ref()
+                .and_then(ResponseFormat::schema)
+                .cloned(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct GenerateContentResponse {
     candidates: Option<Vec<Candidate>>,
     error: Option<ApiError>,
+    #[serde(rename = "usageMetadata")]
+    usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UsageMetadata {
+    #[serde(rename = "promptTokenCount", default)]
+    prompt_token_count: u64,
+    #[serde(rename = "candidatesTokenCount", default)]
+    candidates_token_count: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -137,11 +240,9 @@ impl GeminiProvider {
 
         Self {
             auth: resolved_auth,
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: crate::proxy::client_with("gemini", |builder| {
+                builder.connect_timeout(std::time::Duration::from_secs(10))
+            }),
         }
     }
 
@@ -238,6 +339,17 @@ impl GeminiProvider {
         }
     }
 
+    fn build_list_models_request(&self, auth: &GeminiAuth) -> reqwest::RequestBuilder {
+        let req = self
+            .client
+            .get("https://generativelanguage.googleapis.com/v1beta/models")
+            .query(&[("pageSize", "1000")]);
+        match auth {
+            GeminiAuth::OAuthToken(token) => req.bearer_auth(token),
+            _ => req.query(&[("key", auth.credential())]),
+        }
+    }
+
     fn build_generate_content_request(
         &self,
         auth: &GeminiAuth,
@@ -254,13 +366,85 @@ impl GeminiProvider {
 
 #[async_trait]
 impl Provider for GeminiProvider {
+    async fn health_check(&self) -> anyhow::Result<()> {
+        if self.auth.is_none() {
+            anyhow::bail!(
+                "Gemini API key not found. Set GEMINI_API_KEY or run `zeroclaw onboard`."
+            );
+        }
+        Ok(())
+    }
+
+    /// Models that support `generateContent`, with their input token limit.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let auth = self.auth.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Gemini API key not found. Set GEMINI_API_KEY or run `zeroclaw onboard`."
+            )
+        })?;
+        let body =
+            super::models::fetch_listing("Gemini", self.build_list_models_request(auth)).await?;
+        Ok(parse_model_list(&body))
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let system_instruction = system_prompt.map(|sys| Content::text(None, sys));
+        let contents = vec![Content::text(Some("user"), message)];
+        self.generate(system_instruction, contents, model, options)
+            .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let (system_instruction, contents) = Self::history_contents(messages);
+        self.generate(system_instruction, contents, model, options)
+            .await
+    }
+}
+
+impl GeminiProvider {
+    /// Map a conversation onto Gemini's `contents` (roles `user` / `model`)
+    /// plus a `system_instruction` built from the system messages.
+    fn history_contents(messages: &[ChatMessage]) -> (Option<Content>, Vec<Content>) {
+        let system: Vec<&str> = messages
+            .iter()
+            .filter(|m| m.role == "system")
+            .map(|m| m.content.as_str())
+            .collect();
+        let contents = messages
+            .iter()
+            .filter(|m| m.role != "system")
+            .map(|m| {
+                let role = if m.role == "assistant" {
+                    "model"
+                } else {
+                    "user"
+                };
+                Content::message(role, m)
+            })
+            .collect();
+        let system_instruction =
+            (!system.is_empty()).then(|| Content::text(None, &system.join("\n\n")));
+        (system_instruction, contents)
+    }
+
+    async fn generate(
+        &self,
+        system_instruction: Option<Content>,
+        contents: Vec<Content>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
         let auth = self.auth.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
                 "Gemini API key not found. Options:\n\
@@ -271,26 +455,10 @@ impl Provider for GeminiProvider {
             )
         })?;
 
-        // Build request
-        let system_instruction = system_prompt.map(|sys| Content {
-            role: None,
-            parts: vec![Part {
-                text: sys.to_string(),
-            }],
-        });
-
         let request = GenerateContentRequest {
-            contents: vec![Content {
-                role: Some("user".to_string()),
-                parts: vec![Part {
-                    text: message.to_string(),
-                }],
-            }],
+            contents,
             system_instruction,
-            generation_config: GenerationConfig {
-                temperature,
-                max_output_tokens: 8192,
-            },
+            generation_config: GenerationConfig::from(options),
         };
 
         let url = Self::build_generate_content_url(model, auth);
@@ -313,16 +481,44 @@ impl Provider for GeminiProvider {
             anyhow::bail!("Gemini API error: {}", err.message);
         }
 
+        let usage = result
+            .usage_metadata
+            .map(|u| TokenUsage::new(u.prompt_token_count, u.candidates_token_count));
+
         // Extract text from response
         result
             .candidates
             .and_then(|c| c.into_iter().next())
             .and_then(|c| c.content.parts.into_iter().next())
             .and_then(|p| p.text)
+            .map(|text| ChatReply::new(text, usage))
             .ok_or_else(|| anyhow::anyhow!("No response from Gemini"))
     }
 }
 
+/// Chat-capable models from a `v1beta/models` response, without the
+/// `models/` prefix.
+fn parse_model_list(body: &serde_json::Value) -> Vec<ModelInfo> {
+    let models = body["models"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|model| {
+            model["supportedGenerationMethods"]
+                .as_array()
+                .is_some_and(|methods| methods.iter().any(|m| m == "generateContent"))
+        })
+        .filter_map(|model| {
+            let name = model["name"].as_str()?;
+            Some(ModelInfo {
+                context_window: model["inputTokenLimit"].as_u64(),
+                ..ModelInfo::new(name.strip_prefix("models/").unwrap_or(name))
+            })
+        })
+        .collect();
+    super::models::sorted(models)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -435,15 +631,12 @@ mod tests {
         let body = GenerateContentRequest {
             contents: vec![Content {
                 role: Some("user".into()),
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: "hello".into(),
                 }],
             }],
             system_instruction: None,
-            generation_config: GenerationConfig {
-                temperature: 0.7,
-                max_output_tokens: 8192,
-            },
+            generation_config: GenerationConfig::from(&ChatOptions::new(0.7)),
         };
 
         let request = provider
@@ -471,15 +664,12 @@ mod tests {
         let body = GenerateContentRequest {
             contents: vec![Content {
                 role: Some("user".into()),
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: "hello".into(),
                 }],
             }],
             system_instruction: None,
-            generation_config: GenerationConfig {
-                temperature: 0.7,
-                max_output_tokens: 8192,
-            },
+            generation_config: GenerationConfig::from(&ChatOptions::new(0.7)),
         };
 
         let request = provider
@@ -495,20 +685,17 @@ mod tests {
         let request = GenerateContentRequest {
             contents: vec![Content {
                 role: Some("user".to_string()),
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: "Hello".to_string(),
                 }],
             }],
             system_instruction: Some(Content {
                 role: None,
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: "You are helpful".to_string(),
                 }],
             }),
-            generation_config: GenerationConfig {
-                temperature: 0.7,
-                max_output_tokens: 8192,
-            },
+            generation_config: GenerationConfig::from(&ChatOptions::new(0.7)),
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -516,6 +703,70 @@ mod tests {
         assert!(json.contains("\"text\":\"Hello\""));
         assert!(json.contains("\"temperature\":0.7"));
         assert!(json.contains("\"maxOutputTokens\":8192"));
+        assert!(!json.contains("topP"));
+    }
+
+    #[test]
+    fn history_images_become_inline_and_file_parts() {
+        let messages = [ChatMessage::user("what is this?").with_images(vec![
+            ImageInput::base64("image/png", b"png"),
+            ImageInput::Url {
+                url: "gs://bucket/cat.jpg".into(),
+            },
+        ])];
+        let (_, contents) = GeminiProvider::history_contents(&messages);
+        let json = serde_json::to_value(&contents[0]).unwrap();
+        assert_eq!(
+            json["parts"][0],
+            serde_json::json!({"text": "what is this?"})
+        );
+        assert_eq!(
+            json["parts"][1],
+            serde_json::json!({"inlineData": {"mimeType": "image/png", "data": "cG5n"}})
+        );
+        assert_eq!(
+            json["parts"][2],
+            serde_json::json!({"fileData": {"mimeType": "image/jpeg", "fileUri": "gs://bucket/cat.jpg"}})
+        );
+    }
+
+    #[test]
+    fn generation_config_maps_chat_options() {
+        let options = ChatOptions::new(0.2)
+            .with_max_tokens(Some(256))
+            .with_top_p(Some(0.9))
+            .with_stop(vec!["END".into()])
+            .with_seed(Some(7));
+        let json = serde_json::to_string(&GenerationConfig::from(&options)).unwrap();
+        assert!(json.contains("\"maxOutputTokens\":256"));
+        assert!(json.contains("\"topP\":0.9"));
+        assert!(json.contains("\"stopSequences\":[\"END\"]"));
+        assert!(json.contains("\"seed\":7"));
+        assert!(!json.contains("responseMimeType"));
+    }
+
+    #[test]
+    fn generation_config_requests_json_schema() {
+        let schema = serde_json::json!({"type": "object"});
+        let options = ChatOptions::new(0.2)
+            .with_response_format(Some(ResponseFormat::json_schema(schema.clone())));
+        let json = serde_json::to_value(GenerationConfig::from(&options)).unwrap();
+        assert_eq!(json["responseMimeType"], "application/json");
+        assert_eq!(json["responseJsonSchema"], schema);
+    }
+
+    #[test]
+    fn model_list_keeps_chat_models() {
+        let body = serde_json::json!({"models": [
+            {"name": "models/gemini-2.0-flash", "inputTokenLimit": 1_048_576,
+             "supportedGenerationMethods": ["generateContent", "countTokens"]},
+            {"name": "models/text-embedding-004", "inputTokenLimit": 2048,
+             "supportedGenerationMethods": ["embedContent"]}
+        ]});
+        let models = parse_model_list(&body);
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].id, "gemini-2.0-flash");
+        assert_eq!(models[0].context_window, Some(1_048_576));
     }
 
     #[test]
@@ -545,6 +796,19 @@ mod tests {
         assert_eq!(text, Some("Hello there!".to_string()));
     }
 
+    #[test]
+    fn response_usage_metadata_deserialization() {
+        let json = r#"{
+            "candidates": [{"content": {"parts": [{"text": "Hi"}]}}],
+            "usageMetadata": {"promptTokenCount": 9, "candidatesTokenCount": 4, "totalTokenCount": 13}
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        let usage = response.usage_metadata.unwrap();
+        assert_eq!(usage.prompt_token_count, 9);
+        assert_eq!(usage.candidates_token_count, 4);
+    }
+
     #[test]
     fn error_response_deserialization() {
         let json = r#"{
@@ -0,0 +1,483 @@
+//! Provider middleware: hooks that rewrite a request before it reaches the
+//! provider (inject memory context, redact secrets) and post-process the
+//! reply (strip markdown, enforce a length), composed into one pipeline
+//! around the provider chain.
+//!
+//! Request hooks run in the order the layers were added; response hooks run
+//! in reverse, so the first layer sees the final reply. Streamed replies only
+//! pass through the request hooks.
+
+use super::traits::{ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo};
+use super::Provider;
+use crate::memory::Memory;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::fmt::Write;
+use std::sync::{Arc, OnceLock};
+
+/// Prefix of the recalled-memory preamble, shared with the agent loop.
+pub const MEMORY_CONTEXT_HEADER: &str = "[Memory context]\n";
+
+/// A provider call as seen by middleware.
+#[derive(Debug, Clone)]
+pub struct ProviderRequest {
+    pub messages: Vec<ChatMessage>,
+    pub model: String,
+    pub options: ChatOptions,
+}
+
+impl ProviderRequest {
+    fn last_user_mut(&mut self) -> Option<&mut ChatMessage> {
+        self.messages.iter_mut().rfind(|m| m.role == "user")
+    }
+}
+
+/// One stage of the pipeline. Both hooks default to doing nothing; an
+/// error from either fails the call.
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn on_request(&self, _request: &mut ProviderRequest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_response(&self, _request: &ProviderRequest, _reply: &mut ChatReply) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `layers` around every call to `inner`.
+pub struct MiddlewareProvider {
+    inner: Box<dyn Provider>,
+    layers: Vec<Box<dyn Middleware>>,
+}
+
+impl MiddlewareProvider {
+    pub fn new(inner: Box<dyn Provider>) -> Self {
+        Self {
+            inner,
+            layers: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_layer(mut self, layer: impl Middleware + 'static) -> Self {
+        self.layers.push(Box::new(layer));
+        self
+    }
+
+    pub fn layer_names(&self) -> Vec<&str> {
+        self.layers.iter().map(|l| l.name()).collect()
+    }
+
+    async fn prepare(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ProviderRequest> {
+        let mut request = ProviderRequest {
+            messages: messages.to_vec(),
+            model: model.to_string(),
+            options: options.clone(),
+        };
+        for layer in &self.layers {
+            layer.on_request(&mut request).await?;
+        }
+        Ok(request)
+    }
+}
+
+#[async_trait]
+impl Provider for MiddlewareProvider {
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatReply> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_with_history(&messages, model, options).await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatReply> {
+        let request = self.prepare(messages, model, options).await?;
+        let mut reply = self
+            .inner
+            .chat_with_history(&request.messages, &request.model, &request.options)
+            .await?;
+        for layer in self.layers.iter().rev() {
+            layer.on_response(&request, &mut reply).await?;
+        }
+        Ok(reply)
+    }
+
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatStream> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.stream_chat_with_history(&messages, model, options)
+            .await
+    }
+
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatStream> {
+        let request = self.prepare(messages, model, options).await?;
+        self.inner
+            .stream_chat_with_history(&request.messages, &request.model, &request.options)
+            .await
+    }
+
+    async fn warmup(&self) -> Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        self.inner.list_models().await
+    }
+}
+
+// ── Built-in layers ──────────────────────────────────────────────
+
+/// Prepends up to `limit` memories recalled for the last user message.
+/// Messages that already carry a memory preamble are left alone.
+pub struct MemoryContext {
+    mem: Arc<dyn Memory>,
+    limit: usize,
+}
+
+impl MemoryContext {
+    pub fn new(mem: Arc<dyn Memory>, limit: usize) -> Self {
+        Self { mem, limit }
+    }
+}
+
+#[async_trait]
+impl Middleware for MemoryContext {
+    fn name(&self) -> &str {
+        "memory_context"
+    }
+
+    async fn on_request(&self, request: &mut ProviderRequest) -> Result<()> {
+        let Some(message) = request.last_user_mut() else {
+            return Ok(());
+        };
+        if message.content.starts_with(MEMORY_CONTEXT_HEADER) {
+            return Ok(());
+        }
+        let entries = match self.mem.recall(&message.content, self.limit).await {
+            Ok(entries) if !entries.is_empty() => entries,
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                tracing::warn!("Memory context lookup failed: {e}");
+                return Ok(());
+            }
+        };
+        let mut context = String::from(MEMORY_CONTEXT_HEADER);
+        for entry in &entries {
+            let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
+        }
+        context.push('\n');
+        message.content.insert_str(0, &context);
+        Ok(())
+    }
+}
+
+/// Masks secret-looking tokens in every outgoing message.
+pub struct RedactSecrets;
+
+#[async_trait]
+impl Middleware for RedactSecrets {
+    fn name(&self) -> &str {
+        "redact_secrets"
+    }
+
+    async fn on_request(&self, request: &mut ProviderRequest) -> Result<()> {
+        for message in &mut request.messages {
+            message.content = super::scrub_secret_patterns(&message.content);
+        }
+        Ok(())
+    }
+}
+
+/// Reduces markdown replies to plain text. JSON-mode replies are skipped.
+pub struct StripMarkdown;
+
+#[async_trait]
+impl Middleware for StripMarkdown {
+    fn name(&self) -> &str {
+        "strip_markdown"
+    }
+
+    async fn on_response(&self, request: &ProviderRequest, reply: &mut ChatReply) -> Result<()> {
+        if request.options.response_format.is_none() {
+            reply.text = strip_markdown(&reply.text);
+        }
+        Ok(())
+    }
+}
+
+/// Truncates replies longer than `max_chars`. JSON-mode replies are skipped.
+pub struct MaxResponseChars(pub usize);
+
+#[async_trait]
+impl Middleware for MaxResponseChars {
+    fn name(&self) -> &str {
+        "max_response_chars"
+    }
+
+    async fn on_response(&self, request: &ProviderRequest, reply: &mut ChatReply) -> Result<()> {
+        if request.options.response_format.is_none() {
+            reply.text = crate::util::truncate_with_ellipsis(&reply.text, self.0);
+        }
+        Ok(())
+    }
+}
+
+/// Plain-text rendering of `text`: headings, quotes, emphasis and code
+/// fences lose their markers, bullets become `- `, links become
+/// `text (url)`. Lines inside code blocks are kept as written.
+pub fn strip_markdown(text: &str) -> String {
+    static LINK: OnceLock<regex::Regex> = OnceLock::new();
+    let link_pattern = LINK.get_or_init(|| {
+        regex::Regex::new(r"(!?)\[([^\]]*)\]\(([^)\s]+)\)").expect("valid link pattern")
+    });
+
+    let mut out = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            out.push(line.to_string());
+            continue;
+        }
+        let mut line = trimmed;
+        if let Some(rest) = line.strip_prefix('#') {
+            let rest = rest.trim_start_matches('#');
+            if rest.starts_with(' ') {
+                line = rest.trim_start();
+            }
+        }
+        while let Some(rest) = line.strip_prefix('>') {
+            line = rest.trim_start();
+        }
+        let bullet = line.strip_prefix("* ").or_else(|| line.strip_prefix("+ "));
+        let line = match bullet {
+            Some(rest) => format!("- {rest}"),
+            None => line.to_string(),
+        };
+        let line = link_pattern.replace_all(&line, |caps: &regex::Captures| {
+            if caps[1].is_empty() {
+                format!("{} ({})", &caps[2], &caps[3])
+            } else {
+                caps[2].to_string()
+            }
+        });
+        out.push(
+            line.replace("**", "")
+                .replace("__", "")
+                .replace("~~", "")
+                .replace('`', ""),
+        );
+    }
+    out.join("\n").trim().to_string()
+}
+
+/// Wrap `provider` in the layers enabled by `[middleware]`; without any it
+/// is returned unchanged. `mem` feeds `memory_context`.
+pub fn from_config(
+    provider: Box<dyn Provider>,
+    config: &crate::config::MiddlewareConfig,
+    mem: Arc<dyn Memory>,
+) -> Box<dyn Provider> {
+    let mut pipeline = MiddlewareProvider::new(provider);
+    if config.memory_context > 0 {
+        pipeline = pipeline.with_layer(MemoryContext::new(mem, config.memory_context));
+    }
+    if config.redact_secrets {
+        pipeline = pipeline.with_layer(RedactSecrets);
+    }
+    if config.max_response_chars > 0 {
+        pipeline = pipeline.with_layer(MaxResponseChars(config.max_response_chars));
+    }
+    if config.strip_markdown {
+        pipeline = pipeline.with_layer(StripMarkdown);
+    }
+    if pipeline.layers.is_empty() {
+        return pipeline.inner;
+    }
+    tracing::info!(layers = ?pipeline.layer_names(), "Provider middleware enabled");
+    Box::new(pipeline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MiddlewareConfig;
+    use crate::memory::{InMemoryMemory, MemoryCategory};
+    use crate::providers::traits::ResponseFormat;
+    use std::sync::Mutex;
+
+    /// Records the last history request and replies with a fixed markdown
+    /// text; `chat_with_system` is only reached when nothing wraps it.
+    struct Capture {
+        seen: Arc<Mutex<Vec<ChatMessage>>>,
+    }
+
+    #[async_trait]
+    impl Provider for Capture {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> Result<ChatReply> {
+            Ok(ChatReply::new("unwrapped", None))
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> Result<ChatReply> {
+            *self.seen.lock().unwrap() = messages.to_vec();
+            Ok(ChatReply::new(
+                "## Done\n**All** set, see [docs](https://x.io)",
+                None,
+            ))
+        }
+    }
+
+    struct Tag(&'static str);
+
+    #[async_trait]
+    impl Middleware for Tag {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        async fn on_request(&self, request: &mut ProviderRequest) -> Result<()> {
+            if let Some(message) = request.last_user_mut() {
+                message.content.push_str(self.0);
+            }
+            Ok(())
+        }
+
+        async fn on_response(
+            &self,
+            _request: &ProviderRequest,
+            reply: &mut ChatReply,
+        ) -> Result<()> {
+            reply.text.push_str(self.0);
+            Ok(())
+        }
+    }
+
+    fn capture() -> (Box<dyn Provider>, Arc<Mutex<Vec<ChatMessage>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let provider = Capture {
+            seen: Arc::clone(&seen),
+        };
+        (Box::new(provider), seen)
+    }
+
+    #[tokio::test]
+    async fn request_hooks_run_in_order_and_response_hooks_in_reverse() {
+        let (inner, seen) = capture();
+        let pipeline = MiddlewareProvider::new(inner)
+            .with_layer(Tag("a"))
+            .with_layer(Tag("b"));
+        let reply = pipeline
+            .chat_with_system(Some("sys"), "hi ", "m", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert!(reply.text.ends_with("ba"));
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].content, "sys");
+        assert_eq!(seen[1].content, "hi ab");
+    }
+
+    #[tokio::test]
+    async fn configured_pipeline_injects_memory_redacts_and_cleans_reply() {
+        let mem: Arc<dyn Memory> = Arc::new(InMemoryMemory::new());
+        mem.store("deploy_host", "deploys go to fly.io", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let config = MiddlewareConfig {
+            memory_context: 3,
+            redact_secrets: true,
+            strip_markdown: true,
+            max_response_chars: 0,
+        };
+        let (inner, seen) = capture();
+        let pipeline = from_config(inner, &config, mem);
+
+        let reply = pipeline
+            .chat("deploy with key sk-abc123", "m", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "Done\nAll set, see docs (https://x.io)");
+        let sent = seen.lock().unwrap()[0].content.clone();
+        assert!(sent.starts_with(MEMORY_CONTEXT_HEADER));
+        assert!(sent.contains("- deploy_host: deploys go to fly.io"));
+        assert!(sent.ends_with("deploy with key [REDACTED]"));
+
+        let json = ChatOptions::new(0.0).with_response_format(Some(ResponseFormat::JsonObject));
+        let reply = pipeline.chat("x", "m", &json).await.unwrap();
+        assert!(reply.text.starts_with("## Done"));
+    }
+
+    #[tokio::test]
+    async fn empty_config_leaves_provider_unwrapped() {
+        let (inner, _) = capture();
+        let mem: Arc<dyn Memory> = Arc::new(InMemoryMemory::new());
+        let provider = from_config(inner, &MiddlewareConfig::default(), mem);
+        let reply = provider
+            .chat("x", "m", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "unwrapped");
+    }
+
+    #[test]
+    fn strip_markdown_keeps_code_and_flattens_formatting() {
+        let text = "# Title\n> quoted **bold**\n* item with `code`\n![logo](a.png)\n```rust\n  let x = **y;\n```";
+        assert_eq!(
+            strip_markdown(text),
+            "Title\nquoted bold\n- item with code\nlogo\n  let x = **y;"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Offline providers for tests and local development.
+//!
+//! - `mock` answers from canned fixtures (a JSON array, see [`Fixture`]) or,
+//!   without any, echoes the last user message.
+//! - `record:<provider>` forwards to a real provider and appends every
+//!   exchange to a JSONL recording.
+//! - `replay` answers from such a recording, so a session captured once can
+//!   be re-run without network access or spending tokens.
+
+use super::traits::{ChatMessage, ChatOptions, ChatReply, ModelInfo, TokenUsage};
+use super::Provider;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// One canned response. The first fixture whose filters all match the
+/// request is used.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Fixture {
+    /// Case-insensitive substring of the last user message
+    #[serde(default, rename = "match")]
+    pub matches: Option<String>,
+    /// Only for requests to this model
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub response: String,
+    /// Fail the request with this message instead of replying
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Token usage to report with the reply
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+}
+
+impl Fixture {
+    fn applies_to(&self, last_user: &str, model: &str) -> bool {
+        let text_matches = self
+            .matches
+            .as_ref()
+            .is_none_or(|needle| last_user.to_lowercase().contains(&needle.to_lowercase()));
+        let model_matches = self.model.as_ref().is_none_or(|m| m == model);
+        text_matches && model_matches
+    }
+}
+
+/// One recorded exchange: a line of a recording file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    pub model: String,
+    pub messages: Vec<RecordedMessage>,
+    pub response: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+}
+
+/// Role and text of a recorded message; images are not recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    pub role: String,
+    pub content: String,
+}
+
+fn recorded_messages(messages: &[ChatMessage]) -> Vec<RecordedMessage> {
+    messages
+        .iter()
+        .map(|m| RecordedMessage {
+            role: m.role.clone(),
+            content: m.content.clone(),
+        })
+        .collect()
+}
+
+enum Mode {
+    Fixtures(Vec<Fixture>),
+    Record {
+        inner: Box<dyn Provider>,
+        path: PathBuf,
+        /// Serializes appends so concurrent calls do not interleave lines.
+        lock: Mutex<()>,
+    },
+    Replay(Vec<Recording>),
+}
+
+pub struct MockProvider {
+    mode: Mode,
+}
+
+impl MockProvider {
+    /// Answer from `fixtures`; with none, echo the last user message.
+    pub fn new(fixtures: Vec<Fixture>) -> Self {
+        Self {
+            mode: Mode::Fixtures(fixtures),
+        }
+    }
+
+    /// Load fixtures from a JSON array file.
+    pub fn from_fixtures(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read mock fixtures {}", path.display()))?;
+        let fixtures = serde_json::from_str(&raw)
+            .with_context(|| format!("Invalid mock fixtures in {}", path.display()))?;
+        Ok(Self::new(fixtures))
+    }
+
+    /// Forward to `inner` and append each exchange to `path`.
+    pub fn record(inner: Box<dyn Provider>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: Mode::Record {
+                inner,
+                path: path.into(),
+                lock: Mutex::new(()),
+            },
+        }
+    }
+
+    /// Answer from a recording written by [`MockProvider::record`].
+    pub fn replay(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recording {}", path.display()))?;
+        let recordings = raw
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| {
+                    format!("Invalid recording on line {} of {}", i + 1, path.display())
+                })
+            })
+            .collect::<Result<Vec<Recording>>>()?;
+        Ok(Self {
+            mode: Mode::Replay(recordings),
+        })
+    }
+
+    fn append(path: &Path, lock: &Mutex<()>, recording: &Recording) -> Result<()> {
+        let line = serde_json::to_string(recording)?;
+        let _guard = lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open recording {}", path.display()))?;
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Provider for MockProvider {
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatReply> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_with_history(&messages, model, options).await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> Result<ChatReply> {
+        match &self.mode {
+            Mode::Fixtures(fixtures) => {
+                let last_user = messages
+                    .iter()
+                    .rfind(|m| m.role == "user")
+                    .map_or("", |m| m.content.as_str());
+                if fixtures.is_empty() {
+                    return Ok(ChatReply::new(format!("mock: {last_user}"), None));
+                }
+                let fixture = fixtures
+                    .iter()
+                    .find(|f| f.applies_to(last_user, model))
+                    .ok_or_else(|| anyhow::anyhow!("No mock fixture matches: {last_user}"))?;
+                if let Some(error) = &fixture.error {
+                    anyhow::bail!("{error}");
+                }
+                Ok(ChatReply::new(fixture.response.as_str(), fixture.usage))
+            }
+            Mode::Record { inner, path, lock } => {
+                let reply = inner.chat_with_history(messages, model, options).await?;
+                let recording = Recording {
+                    model: model.to_string(),
+                    messages: recorded_messages(messages),
+                    response: reply.text.clone(),
+                    usage: reply.usage,
+                };
+                if let Err(e) = Self::append(path, lock, &recording) {
+                    tracing::warn!("Failed to record provider exchange: {e:#}");
+                }
+                Ok(reply)
+            }
+            Mode::Replay(recordings) => {
+                let wanted = recorded_messages(messages);
+                let recording = recordings
+                    .iter()
+                    .find(|r| r.model == model && r.messages == wanted)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "No recorded response for this request ({} message(s), model {model})",
+                            messages.len()
+                        )
+                    })?;
+                Ok(ChatReply::new(recording.response.as_str(), recording.usage))
+            }
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let models = match &self.mode {
+            Mode::Record { inner, .. } => return inner.list_models().await,
+            Mode::Fixtures(fixtures) => fixtures
+                .iter()
+                .filter_map(|f| f.model.as_deref())
+                .map(ModelInfo::new)
+                .collect(),
+            Mode::Replay(recordings) => recordings
+                .iter()
+                .map(|r| ModelInfo::new(r.model.as_str()))
+                .collect(),
+        };
+        Ok(super::models::sorted(models))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn options() -> ChatOptions {
+        ChatOptions::new(0.0)
+    }
+
+    #[tokio::test]
+    async fn echoes_without_fixtures() {
+        let provider = MockProvider::new(Vec::new());
+        let reply = provider
+            .chat_with_system(Some("sys"), "hello", "any", &options())
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "mock: hello");
+    }
+
+    #[tokio::test]
+    async fn first_matching_fixture_wins() {
+        let fixtures: Vec<Fixture> = serde_json::from_str(
+            r#"[
+                {"match": "WEATHER", "response": "Sunny", "usage": {"prompt_tokens": 3, "completion_tokens": 1}},
+                {"match": "fail", "error": "Mock API error (500)"},
+                {"model": "big", "response": "big model"},
+                {"response": "fallback"}
+            ]"#,
+        )
+        .unwrap();
+        let provider = MockProvider::new(fixtures);
+
+        let reply = provider
+            .chat("what's the weather?", "small", &options())
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "Sunny");
+        assert_eq!(reply.usage, Some(TokenUsage::new(3, 1)));
+
+        let err = provider.chat("please fail", "small", &options()).await;
+        assert!(err.unwrap_err().to_string().contains("500"));
+
+        let reply = provider.chat("hi", "big", &options()).await.unwrap();
+        assert_eq!(reply.text, "big model");
+        let reply = provider.chat("hi", "small", &options()).await.unwrap();
+        assert_eq!(reply.text, "fallback");
+    }
+
+    #[tokio::test]
+    async fn recorded_session_replays_offline() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("session.jsonl");
+        let live = MockProvider::new(vec![Fixture {
+            response: "recorded answer".into(),
+            ..Fixture::default()
+        }]);
+        let recorder = MockProvider::record(Box::new(live), &path);
+        let history = [ChatMessage::system("sys"), ChatMessage::user("question")];
+        recorder
+            .chat_with_history(&history, "model-a", &options())
+            .await
+            .unwrap();
+
+        let replay = MockProvider::replay(&path).unwrap();
+        let reply = replay
+            .chat_with_system(Some("sys"), "question", "model-a", &options())
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "recorded answer");
+
+        let miss = replay.chat("other question", "model-a", &options()).await;
+        assert!(miss
+            .unwrap_err()
+            .to_string()
+            .contains("No recorded response"));
+        let models = replay.list_models().await.unwrap();
+        assert_eq!(models, vec![ModelInfo::new("model-a")]);
+    }
+}
@@ -1,11 +1,19 @@
 pub mod anthropic;
+pub mod azure;
+pub mod cache;
 pub mod compatible;
+pub mod fanout;
 pub mod gemini;
+pub mod middleware;
+pub mod mock;
+pub mod models;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
 pub mod reliable;
 pub mod router;
+pub mod structured;
+pub mod throttle;
 pub mod traits;
 
 pub use traits::{ChatMessage, Provider};
@@ -80,15 +88,70 @@ pub fn sanitize_api_error(input: &str) -> String {
     format!("{}...", &scrubbed[..end])
 }
 
-/// Build a sanitized provider error from a failed HTTP response.
+/// Send a lightweight request for a provider health check.
+///
+/// Rejected credentials (401/403) and server errors fail the check; any other
+/// status (e.g. 404 from a server without a models listing) proves the
+/// endpoint is up and the key was not refused.
+pub async fn probe_endpoint(
+    provider: &str,
+    request: reqwest::RequestBuilder,
+) -> anyhow::Result<()> {
+    let response = request
+        .timeout(std::time::Duration::from_secs(5))
+        .send()
+        .await?;
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED
+        || status == reqwest::StatusCode::FORBIDDEN
+        || status.is_server_error()
+    {
+        return Err(api_error(provider, response).await);
+    }
+    Ok(())
+}
+
+/// Run `provider.health_check()` under `timeout` and record the outcome as
+/// the `provider` health component (shown by `/health` and the daemon state
+/// file). Errors are sanitized so they are safe to log or serve.
+pub async fn check_provider_health(
+    provider: &dyn Provider,
+    timeout: std::time::Duration,
+) -> anyhow::Result<()> {
+    let result = tokio::time::timeout(timeout, provider.health_check())
+        .await
+        .unwrap_or_else(|_| {
+            Err(anyhow::anyhow!(
+                "health check timed out after {}s",
+                timeout.as_secs()
+            ))
+        })
+        .map_err(|e| anyhow::anyhow!(sanitize_api_error(&e.to_string())));
+    match &result {
+        Ok(()) => crate::health::mark_component_ok("provider"),
+        Err(e) => crate::health::mark_component_error("provider", e),
+    }
+    result
+}
+
+/// Build a sanitized provider error from a failed HTTP response. 429s carry
+/// the provider's rate-limit reset hint as a [`throttle::RateLimitedError`].
 pub async fn api_error(provider: &str, response: reqwest::Response) -> anyhow::Error {
     let status = response.status();
+    let retry_after = throttle::retry_after_from_headers(response.headers());
     let body = response
         .text()
         .await
         .unwrap_or_else(|_| "<failed to read provider error body>".to_string());
     let sanitized = sanitize_api_error(&body);
-    anyhow::anyhow!("{provider} API error ({status}): {sanitized}")
+    let message = format!("{provider} API error ({status}): {sanitized}");
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return anyhow::Error::new(throttle::RateLimitedError {
+            message,
+            retry_after,
+        });
+    }
+    anyhow::anyhow!(message)
 }
 
 /// Resolve API key for a provider from config and environment variables.
@@ -109,6 +172,8 @@ fn resolve_api_key(name: &str, api_key: Option<&str>) -> Option<String> {
         "anthropic" => vec!["ANTHROPIC_OAUTH_TOKEN", "ANTHROPIC_API_KEY"],
         "openrouter" => vec!["OPENROUTER_API_KEY"],
         "openai" => vec!["OPENAI_API_KEY"],
+        "azure" | "azure-openai" => vec!["AZURE_OPENAI_API_KEY"],
+        "compatible" | "openai-compatible" => vec!["COMPATIBLE_API_KEY"],
         "venice" => vec!["VENICE_API_KEY"],
         "groq" => vec!["GROQ_API_KEY"],
         "mistral" => vec!["MISTRAL_API_KEY"],
@@ -151,6 +216,49 @@ fn resolve_api_key(name: &str, api_key: Option<&str>) -> Option<String> {
     None
 }
 
+/// Read a non-empty, trimmed environment variable.
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+static SETTINGS: std::sync::OnceLock<crate::config::ProvidersConfig> = std::sync::OnceLock::new();
+
+/// Use `[providers]` endpoint settings for every provider created
+/// afterwards. Only the first call takes effect.
+pub fn install(config: &crate::config::ProvidersConfig) {
+    let _ = SETTINGS.set(config.clone());
+}
+
+/// An endpoint setting: the environment variable `env` when set, else the
+/// installed `[providers]` value picked by `configured`.
+fn setting(
+    env: &str,
+    configured: impl FnOnce(&crate::config::ProvidersConfig) -> Option<&String>,
+) -> Option<String> {
+    env_value(env).or_else(|| {
+        SETTINGS
+            .get()
+            .and_then(configured)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    })
+}
+
+/// Azure `OpenAI` with deployment and API version from `[providers.azure]`
+/// or `AZURE_OPENAI_DEPLOYMENT` / `AZURE_OPENAI_API_VERSION`.
+fn azure_provider(key: Option<&str>, endpoint: Option<&str>) -> azure::AzureOpenAiProvider {
+    azure::AzureOpenAiProvider::new(key, endpoint)
+        .with_deployment(
+            setting("AZURE_OPENAI_DEPLOYMENT", |p| p.azure.deployment.as_ref()).as_deref(),
+        )
+        .with_api_version(
+            setting("AZURE_OPENAI_API_VERSION", |p| p.azure.api_version.as_ref()).as_deref(),
+        )
+}
+
 /// Factory: create the right provider from config
 #[allow(clippy::too_many_lines)]
 pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<dyn Provider>> {
@@ -160,10 +268,22 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
         // ── Primary providers (custom implementations) ───────
         "openrouter" => Ok(Box::new(openrouter::OpenRouterProvider::new(key))),
         "anthropic" => Ok(Box::new(anthropic::AnthropicProvider::new(key))),
-        "openai" => Ok(Box::new(openai::OpenAiProvider::new(key))),
+        "openai" => Ok(Box::new(
+            openai::OpenAiProvider::with_base_url(
+                key,
+                setting("OPENAI_BASE_URL", |p| p.openai.base_url.as_ref()).as_deref(),
+            )
+            .with_organization(
+                setting("OPENAI_ORG_ID", |p| p.openai.organization.as_ref()).as_deref(),
+            ),
+        )),
         // Ollama is a local service that doesn't use API keys.
         // The api_key parameter is ignored to avoid it being misinterpreted as a base_url.
         "ollama" => Ok(Box::new(ollama::OllamaProvider::new(None))),
+        "azure" | "azure-openai" => Ok(Box::new(azure_provider(
+            key,
+            setting("AZURE_OPENAI_ENDPOINT", |p| p.azure.endpoint.as_ref()).as_deref(),
+        ))),
         "gemini" | "google" | "google-gemini" => {
             Ok(Box::new(gemini::GeminiProvider::new(key)))
         }
@@ -238,6 +358,47 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
             "GitHub Copilot", "https://api.githubcopilot.com", key, AuthStyle::Bearer,
         ))),
 
+        // ── Generic OpenAI-compatible endpoint (vLLM, LM Studio, ...) ──
+        // Base URL from [providers.compatible] or COMPATIBLE_BASE_URL; the API key is optional.
+        "compatible" | "openai-compatible" => {
+            let Some(base_url) =
+                setting("COMPATIBLE_BASE_URL", |p| p.compatible.base_url.as_ref())
+            else {
+                anyhow::bail!(
+                    "Compatible provider requires [providers.compatible] base_url or COMPATIBLE_BASE_URL (e.g. http://localhost:8000/v1), or use \"custom:https://your-api.com\"."
+                );
+            };
+            Ok(Box::new(
+                OpenAiCompatibleProvider::new("Compatible", &base_url, key, AuthStyle::Bearer)
+                    .with_optional_key(),
+            ))
+        }
+
+        // ── Offline testing: canned fixtures, record / replay ──
+        // Fixtures from MOCK_FIXTURES; recordings in MOCK_RECORDING (JSONL).
+        "mock" => match env_value("MOCK_FIXTURES") {
+            Some(path) => Ok(Box::new(mock::MockProvider::from_fixtures(path.as_ref())?)),
+            None => Ok(Box::new(mock::MockProvider::new(Vec::new()))),
+        },
+        "replay" => {
+            let Some(path) = env_value("MOCK_RECORDING") else {
+                anyhow::bail!("Replay provider requires MOCK_RECORDING (path to a JSONL recording).");
+            };
+            Ok(Box::new(mock::MockProvider::replay(path.as_ref())?))
+        }
+        // Format: "record:openrouter" — calls the named provider and records to MOCK_RECORDING
+        name if name.starts_with("record:") => {
+            let inner = name.strip_prefix("record:").unwrap_or("");
+            if inner.is_empty() {
+                anyhow::bail!("Record provider requires a provider name. Format: record:openrouter");
+            }
+            let Some(path) = env_value("MOCK_RECORDING") else {
+                anyhow::bail!("Record provider requires MOCK_RECORDING (path to a JSONL recording).");
+            };
+            let inner = create_provider(inner, api_key)?;
+            Ok(Box::new(mock::MockProvider::record(inner, path)))
+        }
+
         // ── Bring Your Own Provider (custom URL) ───────────
         // Format: "custom:https://your-api.com" or "custom:http://localhost:1234"
         name if name.starts_with("custom:") => {
@@ -245,12 +406,21 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
             if base_url.is_empty() {
                 anyhow::bail!("Custom provider requires a URL. Format: custom:https://your-api.com");
             }
-            Ok(Box::new(OpenAiCompatibleProvider::new(
-                "Custom",
-                base_url,
-                key,
-                AuthStyle::Bearer,
-            )))
+            Ok(Box::new(
+                OpenAiCompatibleProvider::new("Custom", base_url, key, AuthStyle::Bearer)
+                    .with_optional_key(),
+            ))
+        }
+
+        // ── Azure OpenAI resource endpoint ──────────────────
+        // Format: "azure:https://your-resource.openai.azure.com"
+        name if name.starts_with("azure:") => {
+            let endpoint = name.strip_prefix("azure:").unwrap_or("");
+            if endpoint.is_empty() {
+                anyhow::bail!("Azure provider requires a URL. Format: azure:https://your-resource.openai.azure.com");
+            }
+            let key = resolve_api_key("azure", api_key);
+            Ok(Box::new(azure_provider(key.as_deref(), Some(endpoint))))
         }
 
         // ── Anthropic-compatible custom endpoints ───────────
@@ -273,6 +443,20 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
     }
 }
 
+/// Wrap `provider` in the shared per-provider rate limiter.
+fn throttled(
+    name: &str,
+    provider: Box<dyn Provider>,
+    reliability: &crate::config::ReliabilityConfig,
+) -> Box<dyn Provider> {
+    Box::new(throttle::ThrottledProvider::new(
+        name,
+        provider,
+        reliability.provider_requests_per_minute,
+        std::time::Duration::from_secs(reliability.provider_throttle_max_wait_secs),
+    ))
+}
+
 /// Create provider chain with retry and fallback behavior.
 pub fn create_resilient_provider(
     primary_name: &str,
@@ -283,7 +467,11 @@ pub fn create_resilient_provider(
 
     providers.push((
         primary_name.to_string(),
-        create_provider(primary_name, api_key)?,
+        throttled(
+            primary_name,
+            create_provider(primary_name, api_key)?,
+            reliability,
+        ),
     ));
 
     for fallback in &reliability.fallback_providers {
@@ -301,7 +489,9 @@ pub fn create_resilient_provider(
         }
 
         match create_provider(fallback, api_key) {
-            Ok(provider) => providers.push((fallback.clone(), provider)),
+            Ok(provider) => {
+                providers.push((fallback.clone(), throttled(fallback, provider, reliability)));
+            }
             Err(e) => {
                 tracing::warn!(
                     fallback_provider = fallback,
@@ -311,21 +501,37 @@ pub fn create_resilient_provider(
         }
     }
 
-    Ok(Box::new(ReliableProvider::new(
-        providers,
-        reliability.provider_retries,
-        reliability.provider_backoff_ms,
-    )))
+    Ok(Box::new(
+        ReliableProvider::new(
+            providers,
+            reliability.provider_retries,
+            reliability.provider_backoff_ms,
+        )
+        .with_backoff_limits(
+            reliability.provider_max_backoff_ms,
+            reliability.provider_jitter_ms,
+        )
+        .with_timeouts(
+            std::time::Duration::from_secs(reliability.provider_timeout_secs),
+            reliability
+                .provider_timeouts
+                .iter()
+                .map(|(name, secs)| (name.clone(), std::time::Duration::from_secs(*secs)))
+                .collect(),
+        ),
+    ))
 }
 
 /// Create a RouterProvider if model routes are configured, otherwise return a
 /// standard resilient provider. The router wraps individual providers per route,
-/// each with its own retry/fallback chain.
+/// each with its own retry/fallback chain, and applies `routing_rules` to
+/// requests for `default_model`.
 pub fn create_routed_provider(
     primary_name: &str,
     api_key: Option<&str>,
     reliability: &crate::config::ReliabilityConfig,
     model_routes: &[crate::config::ModelRouteConfig],
+    routing_rules: &[crate::config::RoutingRuleConfig],
     default_model: &str,
 ) -> anyhow::Result<Box<dyn Provider>> {
     if model_routes.is_empty() {
@@ -376,11 +582,61 @@ pub fn create_routed_provider(
         })
         .collect();
 
-    Ok(Box::new(router::RouterProvider::new(
-        providers,
-        routes,
-        default_model.to_string(),
-    )))
+    let rules: Vec<router::RoutingRule> = routing_rules
+        .iter()
+        .map(|r| router::RoutingRule {
+            hint: r.route.clone(),
+            max_chars: r.max_chars,
+            keywords: r.keywords.clone(),
+            images: r.images,
+        })
+        .collect();
+
+    Ok(Box::new(
+        router::RouterProvider::new(providers, routes, default_model.to_string()).with_rules(rules),
+    ))
+}
+
+/// Wrap `provider` in a [`cache::CachedProvider`] when `[response_cache]` is
+/// enabled; otherwise return it unchanged.
+pub fn create_cached_provider(
+    provider: Box<dyn Provider>,
+    cache: &crate::config::ResponseCacheConfig,
+    workspace_dir: &std::path::Path,
+) -> anyhow::Result<Box<dyn Provider>> {
+    if !cache.enabled {
+        return Ok(provider);
+    }
+    Ok(Box::new(cache::CachedProvider::new(
+        provider,
+        &workspace_dir.join("cache").join("responses.db"),
+        cache.max_entries,
+        cache.ttl_secs,
+    )?))
+}
+
+/// Full provider stack for `config`: routed providers with retries and
+/// fallbacks, then the response cache, fan-out and middleware (outermost).
+pub fn create_configured_provider(
+    config: &crate::config::Config,
+    provider_name: &str,
+    default_model: &str,
+    mem: std::sync::Arc<dyn crate::memory::Memory>,
+) -> anyhow::Result<Box<dyn Provider>> {
+    let provider = create_cached_provider(
+        create_routed_provider(
+            provider_name,
+            config.api_key.as_deref(),
+            &config.reliability,
+            &config.model_routes,
+            &config.routing_rules,
+            default_model,
+        )?,
+        &config.response_cache,
+        &config.workspace_dir,
+    )?;
+    let provider = fanout::from_config(provider, &config.fanout)?;
+    Ok(middleware::from_config(provider, &config.middleware, mem))
 }
 
 #[cfg(test)]
@@ -422,6 +678,28 @@ mod tests {
         assert!(create_provider("gemini", None).is_ok());
     }
 
+    #[test]
+    fn factory_azure() {
+        assert!(create_provider("azure", Some("azure-key")).is_ok());
+        assert!(create_provider("azure-openai", None).is_ok());
+        assert!(
+            create_provider("azure:https://contoso.openai.azure.com", Some("azure-key")).is_ok()
+        );
+        match create_provider("azure:", None) {
+            Err(e) => assert!(e.to_string().contains("requires a URL")),
+            Ok(_) => panic!("Expected error for empty Azure endpoint"),
+        }
+    }
+
+    #[test]
+    fn factory_mock() {
+        assert!(create_provider("mock", None).is_ok());
+        match create_provider("record:", None) {
+            Err(e) => assert!(e.to_string().contains("requires a provider name")),
+            Ok(_) => panic!("Expected error for record without a provider"),
+        }
+    }
+
     // ── OpenAI-compatible providers ──────────────────────────
 
     #[test]
@@ -540,6 +818,17 @@ mod tests {
 
     // ── Custom / BYOP provider ─────────────────────────────
 
+    #[test]
+    fn factory_compatible_requires_base_url() {
+        if std::env::var("COMPATIBLE_BASE_URL").is_ok() {
+            return;
+        }
+        match create_provider("compatible", None) {
+            Err(e) => assert!(e.to_string().contains("COMPATIBLE_BASE_URL")),
+            Ok(_) => panic!("Expected error without COMPATIBLE_BASE_URL"),
+        }
+    }
+
     #[test]
     fn factory_custom_url() {
         let p = create_provider("custom:https://my-llm.example.com", Some("key"));
@@ -621,14 +910,25 @@ mod tests {
         let reliability = crate::config::ReliabilityConfig {
             provider_retries: 1,
             provider_backoff_ms: 100,
+            provider_max_backoff_ms: 1_000,
+            provider_jitter_ms: 0,
             fallback_providers: vec![
                 "openrouter".into(),
                 "nonexistent-provider".into(),
                 "openai".into(),
                 "openai".into(),
             ],
+            provider_timeout_secs: 30,
+            provider_timeouts: std::collections::BTreeMap::new(),
+            provider_requests_per_minute: 0,
+            provider_throttle_max_wait_secs: 60,
             channel_initial_backoff_secs: 2,
             channel_max_backoff_secs: 60,
+            channel_alert_after_failures: 5,
+            channel_alert_target: None,
+            channel_health_check_interval_secs: 60,
+            channel_retry_attempts: 5,
+            channel_retry_backoff_secs: 30,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
         };
@@ -762,6 +1062,65 @@ mod tests {
         assert!(!result.contains("sk-abcdef123"));
     }
 
+    struct HealthProbeProvider {
+        delay: std::time::Duration,
+        error: Option<&'static str>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for HealthProbeProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &traits::ChatOptions,
+        ) -> anyhow::Result<traits::ChatReply> {
+            Ok("ok".into())
+        }
+
+        async fn health_check(&self) -> anyhow::Result<()> {
+            tokio::time::sleep(self.delay).await;
+            match self.error {
+                Some(error) => anyhow::bail!(error),
+                None => Ok(()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn check_provider_health_reports_timeouts_and_scrubs_errors() {
+        let _check = crate::health::PROVIDER_CHECK_LOCK.lock().await;
+        let timeout = std::time::Duration::from_millis(50);
+
+        let healthy = HealthProbeProvider {
+            delay: std::time::Duration::ZERO,
+            error: None,
+        };
+        assert!(check_provider_health(&healthy, timeout).await.is_ok());
+
+        let rejected = HealthProbeProvider {
+            delay: std::time::Duration::ZERO,
+            error: Some("OpenAI API error (401 Unauthorized): bad key sk-abcdef123456"),
+        };
+        let err = check_provider_health(&rejected, timeout)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("401"));
+        assert!(!err.contains("sk-abcdef123456"));
+
+        let hung = HealthProbeProvider {
+            delay: std::time::Duration::from_secs(5),
+            error: None,
+        };
+        let err = check_provider_health(&hung, timeout)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("timed out"));
+    }
+
     #[test]
     fn sanitize_no_secret_no_change() {
         let input = "simple upstream timeout";
@@ -0,0 +1,199 @@
+//! Model listings: shared parsing for [`super::Provider::list_models`]
+//! implementations and the `zeroclaw models` command.
+
+use super::traits::ModelInfo;
+use crate::config::{Config, CostConfig};
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+/// Send a model-listing request and parse the JSON body.
+pub async fn fetch_listing(provider: &str, request: reqwest::RequestBuilder) -> Result<Value> {
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(super::api_error(provider, response).await);
+    }
+    Ok(response.json().await?)
+}
+
+/// Parse an `OpenAI`-style `{"data": [{"id": ...}]}` listing. The context
+/// window comes from `context_length` / `context_window` where present, and
+/// per-token `pricing.prompt` / `pricing.completion` strings (`OpenRouter`)
+/// are converted to USD per million tokens.
+pub fn parse_openai_listing(body: &Value) -> Vec<ModelInfo> {
+    let Some(data) = body.get("data").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    let per_million = |model: &Value, key: &str| {
+        model
+            .get("pricing")
+            .and_then(|p| p.get(key))
+            .and_then(Value::as_str)
+            .and_then(|price| price.parse::<f64>().ok())
+            .filter(|price| *price >= 0.0)
+            .map(|price| price * 1_000_000.0)
+    };
+    let models = data.iter().filter_map(|model| {
+        let id = model.get("id")?.as_str()?;
+        Some(ModelInfo {
+            id: id.to_string(),
+            context_window: model
+                .get("context_length")
+                .or_else(|| model.get("context_window"))
+                .and_then(Value::as_u64),
+            input_per_million: per_million(model, "prompt"),
+            output_per_million: per_million(model, "completion"),
+        })
+    });
+    sorted(models.collect())
+}
+
+/// Sort by id and drop duplicates.
+pub fn sorted(mut models: Vec<ModelInfo>) -> Vec<ModelInfo> {
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models.dedup_by(|a, b| a.id == b.id);
+    models
+}
+
+/// Fill prices the provider did not report from `[cost.prices]`.
+fn apply_configured_prices(models: &mut [ModelInfo], cost: &CostConfig) {
+    for model in models {
+        if model.input_per_million.is_some() || model.output_per_million.is_some() {
+            continue;
+        }
+        if let Some(price) = crate::cost::price_for(cost, &model.id) {
+            model.input_per_million = Some(price.input_per_million);
+            model.output_per_million = Some(price.output_per_million);
+        }
+    }
+}
+
+fn price_cell(price: Option<f64>) -> String {
+    price.map_or_else(|| "-".into(), |p| format!("${p:.2}"))
+}
+
+/// `zeroclaw models`: list the models `provider` (default: the configured
+/// `default_provider`) offers, optionally only ids containing `filter`.
+pub async fn handle_command(
+    config: &Config,
+    provider: Option<&str>,
+    filter: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let name = provider
+        .or(config.default_provider.as_deref())
+        .unwrap_or("openrouter");
+    let mem: std::sync::Arc<dyn crate::memory::Memory> =
+        std::sync::Arc::from(crate::memory::create_memory(
+            &config.memory,
+            &config.workspace_dir,
+            config.api_key.as_deref(),
+        )?);
+    let client = super::create_configured_provider(
+        config,
+        name,
+        config
+            .default_model
+            .as_deref()
+            .unwrap_or("anthropic/claude-sonnet-4-20250514"),
+        mem,
+    )?;
+    let mut models = client
+        .list_models()
+        .await
+        .with_context(|| format!("Failed to list models for provider {name}"))?;
+    if let Some(filter) = filter.map(str::to_lowercase) {
+        models.retain(|m| m.id.to_lowercase().contains(&filter));
+    }
+    apply_configured_prices(&mut models, &config.cost);
+
+    if json {
+        let body = serde_json::json!({"provider": name, "models": models});
+        println!("{}", serde_json::to_string_pretty(&body)?);
+        return Ok(());
+    }
+
+    if models.is_empty() {
+        println!("No models found for {name}.");
+        return Ok(());
+    }
+
+    let current = config.default_model.as_deref();
+    println!(
+        "🧠 {} model(s) from {name} (* = default_model):",
+        models.len()
+    );
+    println!(
+        "  {:<52} {:>10} {:>10} {:>10}",
+        "model", "context", "$/M in", "$/M out"
+    );
+    for model in &models {
+        let marker = if current == Some(model.id.as_str()) {
+            '*'
+        } else {
+            ' '
+        };
+        println!(
+            "{marker} {:<52} {:>10} {:>10} {:>10}",
+            model.id,
+            model
+                .context_window
+                .map_or_else(|| "-".into(), |c| c.to_string()),
+            price_cell(model.input_per_million),
+            price_cell(model.output_per_million)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelPriceConfig;
+    use serde_json::json;
+
+    #[test]
+    fn parses_openai_style_listing_with_openrouter_extras() {
+        let body = json!({"data": [
+            {"id": "openai/gpt-4o", "context_length": 128_000,
+             "pricing": {"prompt": "0.0000025", "completion": "0.00001"}},
+            {"id": "anthropic/claude-sonnet-4", "context_window": 200_000},
+            {"object": "model"},
+            {"id": "openai/gpt-4o"}
+        ]});
+        let models = parse_openai_listing(&body);
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].id, "anthropic/claude-sonnet-4");
+        assert_eq!(models[0].context_window, Some(200_000));
+        assert_eq!(models[0].input_per_million, None);
+        assert_eq!(models[1].context_window, Some(128_000));
+        assert!((models[1].input_per_million.unwrap() - 2.5).abs() < 1e-9);
+        assert!((models[1].output_per_million.unwrap() - 10.0).abs() < 1e-9);
+
+        assert!(parse_openai_listing(&json!({"error": "nope"})).is_empty());
+    }
+
+    #[test]
+    fn configured_prices_fill_only_unpriced_models() {
+        let mut cost = CostConfig::default();
+        for model in ["gpt-4o", "o3"] {
+            cost.prices.insert(
+                model.into(),
+                ModelPriceConfig {
+                    input_per_million: 1.0,
+                    output_per_million: 4.0,
+                },
+            );
+        }
+        let mut models = vec![
+            ModelInfo::new("openai/gpt-4o"),
+            ModelInfo {
+                input_per_million: Some(2.0),
+                ..ModelInfo::new("o3")
+            },
+        ];
+        apply_configured_prices(&mut models, &cost);
+        assert_eq!(models[0].output_per_million, Some(4.0));
+        assert_eq!(models[1].input_per_million, Some(2.0));
+        assert_eq!(models[1].output_per_million, None);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::providers::traits::Provider;
+use crate::providers::traits::{
+    ChatMessage, ChatOptions, ChatReply, ImageInput, ModelInfo, Provider, TokenUsage,
+};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -14,22 +16,84 @@ struct ChatRequest {
     messages: Vec<Message>,
     stream: bool,
     options: Options,
+    /// `"json"` or a JSON schema the reply must follow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
 struct Message {
     role: String,
     content: String,
+    /// Base64 image data; Ollama does not fetch image URLs.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>,
+}
+
+impl Message {
+    fn from_chat(message: &ChatMessage) -> anyhow::Result<Self> {
+        let images = message
+            .images
+            .iter()
+            .map(|image| match image {
+                ImageInput::Base64 { data, .. } => Ok(data.clone()),
+                ImageInput::Url { .. } => {
+                    anyhow::bail!("Ollama only accepts inline (base64) images, not URLs")
+                }
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            role: message.role.clone(),
+            content: message.content.clone(),
+            images,
+        })
+    }
 }
 
 #[derive(Debug, Serialize)]
 struct Options {
     temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+}
+
+impl From<&ChatOptions> for Options {
+    fn from(options: &ChatOptions) -> Self {
+        Self {
+            temperature: options.temperature,
+            num_predict: options.max_tokens,
+            top_p: options.top_p,
+            stop: options.stop.clone(),
+            seed: options.seed,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     message: ResponseMessage,
+    /// Prompt tokens evaluated (omitted when the prompt was cached).
+    prompt_eval_count: Option<u64>,
+    /// Tokens generated for the reply.
+    eval_count: Option<u64>,
+}
+
+impl ChatResponse {
+    fn usage(&self) -> Option<TokenUsage> {
+        if self.prompt_eval_count.is_none() && self.eval_count.is_none() {
+            return None;
+        }
+        Some(TokenUsage::new(
+            self.prompt_eval_count.unwrap_or(0),
+            self.eval_count.unwrap_or(0),
+        ))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -44,59 +108,116 @@ impl OllamaProvider {
                 .unwrap_or("http://localhost:11434")
                 .trim_end_matches('/')
                 .to_string(),
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(300)) // Ollama runs locally, may be slow
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: crate::proxy::client_with("ollama", |builder| {
+                builder.connect_timeout(std::time::Duration::from_secs(10))
+            }),
+        }
+    }
+
+    async fn chat_messages(
+        &self,
+        messages: Vec<Message>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let request = ChatRequest {
+            model: model.to_string(),
+            messages,
+            stream: false,
+            options: Options::from(options),
+            format: options.response_format.as_ref().map(|format| {
+                format
+                    .schema()
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::Value::from("json"))
+            }),
+        };
+
+        let url = format!("{}/api/chat", self.base_url);
+
+        let response = self.client.post(&url).json(&request).send().await?;
+
+        if !response.status().is_success() {
+            let err = super::api_error("Ollama", response).await;
+            anyhow::bail!("{err}. Is Ollama running? (brew install ollama && ollama serve)");
         }
+
+        let chat_response: ChatResponse = response.json().await?;
+        let usage = chat_response.usage();
+        Ok(ChatReply::new(chat_response.message.content, usage))
     }
 }
 
 #[async_trait]
 impl Provider for OllamaProvider {
+    async fn health_check(&self) -> anyhow::Result<()> {
+        let url = format!("{}/api/tags", self.base_url);
+        super::probe_endpoint("Ollama", self.client.get(&url))
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!("{e}. Is Ollama running? (brew install ollama && ollama serve)")
+            })
+    }
+
+    /// Locally pulled models, from `/api/tags`.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let url = format!("{}/api/tags", self.base_url);
+        let body = super::models::fetch_listing("Ollama", self.client.get(&url)).await?;
+        Ok(parse_tags(&body))
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
         let mut messages = Vec::new();
 
         if let Some(sys) = system_prompt {
             messages.push(Message {
                 role: "system".to_string(),
                 content: sys.to_string(),
+                images: Vec::new(),
             });
         }
 
         messages.push(Message {
             role: "user".to_string(),
             content: message.to_string(),
+            images: Vec::new(),
         });
 
-        let request = ChatRequest {
-            model: model.to_string(),
-            messages,
-            stream: false,
-            options: Options { temperature },
-        };
-
-        let url = format!("{}/api/chat", self.base_url);
-
-        let response = self.client.post(&url).json(&request).send().await?;
-
-        if !response.status().is_success() {
-            let err = super::api_error("Ollama", response).await;
-            anyhow::bail!("{err}. Is Ollama running? (brew install ollama && ollama serve)");
-        }
+        self.chat_messages(messages, model, options).await
+    }
 
-        let chat_response: ChatResponse = response.json().await?;
-        Ok(chat_response.message.content)
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let messages = messages
+            .iter()
+            .map(Message::from_chat)
+            .collect::<anyhow::Result<_>>()?;
+        self.chat_messages(messages, model, options).await
     }
 }
 
+/// Model names from an `/api/tags` response.
+fn parse_tags(body: &serde_json::Value) -> Vec<ModelInfo> {
+    let models = body["models"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|model| model["name"].as_str())
+        .map(ModelInfo::new)
+        .collect();
+    super::models::sorted(models)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -133,14 +254,17 @@ mod tests {
                 Message {
                     role: "system".to_string(),
                     content: "You are ZeroClaw".to_string(),
+                    images: Vec::new(),
                 },
                 Message {
                     role: "user".to_string(),
                     content: "hello".to_string(),
+                    images: Vec::new(),
                 },
             ],
             stream: false,
-            options: Options { temperature: 0.7 },
+            options: Options::from(&ChatOptions::new(0.7)),
+            format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"stream\":false"));
@@ -156,13 +280,52 @@ mod tests {
             messages: vec![Message {
                 role: "user".to_string(),
                 content: "test".to_string(),
+                images: Vec::new(),
             }],
             stream: false,
-            options: Options { temperature: 0.0 },
+            options: Options::from(&ChatOptions::new(0.0)),
+            format: Some(serde_json::Value::from("json")),
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("\"role\":\"system\""));
         assert!(json.contains("mistral"));
+        assert!(!json.contains("num_predict"));
+        assert!(json.contains("\"format\":\"json\""));
+    }
+
+    #[test]
+    fn history_images_are_sent_as_base64() {
+        let message = ChatMessage::user("what is this?")
+            .with_images(vec![ImageInput::base64("image/png", b"png")]);
+        let json = serde_json::to_string(&Message::from_chat(&message).unwrap()).unwrap();
+        assert!(json.contains("\"images\":[\"cG5n\"]"));
+
+        let remote = ChatMessage::user("and this?").with_images(vec![ImageInput::Url {
+            url: "https://x.test/a.png".into(),
+        }]);
+        assert!(Message::from_chat(&remote).is_err());
+    }
+
+    #[test]
+    fn options_map_max_tokens_to_num_predict() {
+        let options = ChatOptions::new(0.2)
+            .with_max_tokens(Some(128))
+            .with_stop(vec!["###".into()])
+            .with_seed(Some(42));
+        let json = serde_json::to_string(&Options::from(&options)).unwrap();
+        assert!(json.contains("\"num_predict\":128"));
+        assert!(json.contains("\"stop\":[\"###\"]"));
+        assert!(json.contains("\"seed\":42"));
+    }
+
+    #[test]
+    fn tags_list_pulled_models() {
+        let body = serde_json::json!({"models": [
+            {"name": "qwen2.5:7b", "size": 4_683_087_332_u64},
+            {"name": "llama3.2:latest"}
+        ]});
+        let ids: Vec<String> = parse_tags(&body).into_iter().map(|m| m.id).collect();
+        assert_eq!(ids, ["llama3.2:latest", "qwen2.5:7b"]);
     }
 
     #[test]
@@ -172,6 +335,17 @@ mod tests {
         assert_eq!(resp.message.content, "Hello from Ollama!");
     }
 
+    #[test]
+    fn response_reports_eval_counts_as_usage() {
+        let json = r#"{"message":{"role":"assistant","content":"hi"},"prompt_eval_count":26,"eval_count":7}"#;
+        let resp: ChatResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.usage(), Some(TokenUsage::new(26, 7)));
+
+        let json = r#"{"message":{"role":"assistant","content":"hi"}}"#;
+        let resp: ChatResponse = serde_json::from_str(json).unwrap();
+        assert!(resp.usage().is_none());
+    }
+
     #[test]
     fn response_with_empty_content() {
         let json = r#"{"message":{"role":"assistant","content":""}}"#;
@@ -1,10 +1,15 @@
-use crate::providers::traits::Provider;
+use super::compatible::MessageContent;
+use crate::providers::traits::{
+    ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo, Provider, TokenUsage,
+};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 pub struct OpenAiProvider {
     api_key: Option<String>,
+    base_url: String,
+    organization: Option<String>,
     client: Client,
 }
 
@@ -12,18 +17,53 @@ pub struct OpenAiProvider {
 struct ChatRequest {
     model: String,
     messages: Vec<Message>,
-    temperature: f64,
+    #[serde(flatten)]
+    options: ChatOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: MessageContent,
+}
+
+impl Message {
+    /// Request messages for a single turn with an optional system prompt.
+    fn single_turn(system_prompt: Option<&str>, message: &str) -> Vec<Self> {
+        let mut messages = Vec::new();
+
+        if let Some(sys) = system_prompt {
+            messages.push(Message {
+                role: "system".to_string(),
+                content: sys.into(),
+            });
+        }
+
+        messages.push(Message {
+            role: "user".to_string(),
+            content: message.into(),
+        });
+        messages
+    }
+
+    fn from_history(messages: &[ChatMessage]) -> Vec<Self> {
+        messages
+            .iter()
+            .map(|m| Message {
+                role: m.role.clone(),
+                content: MessageContent::from_message(m),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -38,54 +78,75 @@ struct ResponseMessage {
 
 impl OpenAiProvider {
     pub fn new(api_key: Option<&str>) -> Self {
+        Self::with_base_url(api_key, None)
+    }
+
+    /// Point the provider at an OpenAI-compatible endpoint other than
+    /// `https://api.openai.com/v1` (e.g. Azure-style gateways or a proxy).
+    /// The URL should include the `/v1` segment.
+    pub fn with_base_url(api_key: Option<&str>, base_url: Option<&str>) -> Self {
+        let base_url = base_url
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map_or("https://api.openai.com/v1", |u| u.trim_end_matches('/'))
+            .to_string();
         Self {
             api_key: api_key.map(ToString::to_string),
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            base_url,
+            organization: None,
+            client: crate::proxy::client_with("openai", |builder| {
+                builder.connect_timeout(std::time::Duration::from_secs(10))
+            }),
         }
     }
-}
 
-#[async_trait]
-impl Provider for OpenAiProvider {
-    async fn chat_with_system(
+    /// Send `OpenAI-Organization` with every request so usage is billed to
+    /// that organization instead of the key's default one.
+    #[must_use]
+    pub fn with_organization(mut self, organization: Option<&str>) -> Self {
+        self.organization = organization
+            .map(str::trim)
+            .filter(|o| !o.is_empty())
+            .map(ToString::to_string);
+        self
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{path}", self.base_url)
+    }
+
+    fn authorized(
         &self,
-        system_prompt: Option<&str>,
-        message: &str,
+        request: reqwest::RequestBuilder,
+        api_key: &str,
+    ) -> reqwest::RequestBuilder {
+        let request = request.header("Authorization", format!("Bearer {api_key}"));
+        match &self.organization {
+            Some(org) => request.header("OpenAI-Organization", org),
+            None => request,
+        }
+    }
+
+    /// Send a blocking chat completions request.
+    async fn chat_messages(
+        &self,
+        messages: Vec<Message>,
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
         let api_key = self.api_key.as_ref().ok_or_else(|| {
             anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
         })?;
 
-        let mut messages = Vec::new();
-
-        if let Some(sys) = system_prompt {
-            messages.push(Message {
-                role: "system".to_string(),
-                content: sys.to_string(),
-            });
-        }
-
-        messages.push(Message {
-            role: "user".to_string(),
-            content: message.to_string(),
-        });
-
         let request = ChatRequest {
             model: model.to_string(),
             messages,
-            temperature,
+            options: options.clone(),
+            stream: None,
         };
 
         let response = self
-            .client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {api_key}"))
+            .authorized(self.client.post(self.endpoint("chat/completions")), api_key)
             .json(&request)
             .send()
             .await?;
@@ -96,13 +157,112 @@ impl Provider for OpenAiProvider {
 
         let chat_response: ChatResponse = response.json().await?;
 
+        let usage = chat_response.usage;
         chat_response
             .choices
             .into_iter()
             .next()
-            .map(|c| c.message.content)
+            .map(|c| ChatReply::new(c.message.content, usage))
             .ok_or_else(|| anyhow::anyhow!("No response from OpenAI"))
     }
+
+    /// Open a streaming chat completions request (`"stream": true`).
+    async fn stream_messages(
+        &self,
+        messages: Vec<Message>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
+        })?;
+
+        let request = ChatRequest {
+            model: model.to_string(),
+            messages,
+            options: options.clone(),
+            stream: Some(true),
+        };
+
+        let response = self
+            .authorized(self.client.post(self.endpoint("chat/completions")), api_key)
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenAI", response).await);
+        }
+
+        Ok(super::compatible::sse_response_stream(response))
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAiProvider {
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        self.chat_messages(Message::single_turn(system_prompt, message), model, options)
+            .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        self.chat_messages(Message::from_history(messages), model, options)
+            .await
+    }
+
+    async fn health_check(&self) -> anyhow::Result<()> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
+        })?;
+
+        super::probe_endpoint(
+            "OpenAI",
+            self.authorized(self.client.get(self.endpoint("models")), api_key),
+        )
+        .await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
+        })?;
+
+        let request = self.authorized(self.client.get(self.endpoint("models")), api_key);
+        let body = super::models::fetch_listing("OpenAI", request).await?;
+        Ok(super::models::parse_openai_listing(&body))
+    }
+
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.stream_messages(Message::single_turn(system_prompt, message), model, options)
+            .await
+    }
+
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.stream_messages(Message::from_history(messages), model, options)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -127,10 +287,56 @@ mod tests {
         assert_eq!(p.api_key.as_deref(), Some(""));
     }
 
+    #[test]
+    fn defaults_to_openai_api() {
+        let p = OpenAiProvider::new(Some("sk-test"));
+        assert_eq!(
+            p.endpoint("chat/completions"),
+            "https://api.openai.com/v1/chat/completions"
+        );
+        assert!(p.organization.is_none());
+    }
+
+    #[test]
+    fn custom_base_url_trims_trailing_slash() {
+        let p =
+            OpenAiProvider::with_base_url(Some("sk-test"), Some("https://proxy.example.com/v1/"));
+        assert_eq!(p.endpoint("models"), "https://proxy.example.com/v1/models");
+    }
+
+    #[test]
+    fn blank_base_url_falls_back_to_default() {
+        let p = OpenAiProvider::with_base_url(Some("sk-test"), Some("  "));
+        assert_eq!(p.base_url, "https://api.openai.com/v1");
+    }
+
+    #[test]
+    fn organization_header_is_sent_when_set() {
+        let p = OpenAiProvider::new(Some("sk-test")).with_organization(Some(" org-abc "));
+        let request = p
+            .authorized(p.client.get(p.endpoint("models")), "sk-test")
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()["OpenAI-Organization"], "org-abc");
+        assert_eq!(request.headers()["Authorization"], "Bearer sk-test");
+    }
+
+    #[test]
+    fn empty_organization_is_ignored() {
+        let p = OpenAiProvider::new(Some("sk-test")).with_organization(Some(""));
+        let request = p
+            .authorized(p.client.get(p.endpoint("models")), "sk-test")
+            .build()
+            .unwrap();
+        assert!(request.headers().get("OpenAI-Organization").is_none());
+    }
+
     #[tokio::test]
     async fn chat_fails_without_key() {
         let p = OpenAiProvider::new(None);
-        let result = p.chat_with_system(None, "hello", "gpt-4o", 0.7).await;
+        let result = p
+            .chat_with_system(None, "hello", "gpt-4o", &ChatOptions::new(0.7))
+            .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("API key not set"));
     }
@@ -139,9 +345,24 @@ mod tests {
     async fn chat_with_system_fails_without_key() {
         let p = OpenAiProvider::new(None);
         let result = p
-            .chat_with_system(Some("You are ZeroClaw"), "test", "gpt-4o", 0.5)
+            .chat_with_system(
+                Some("You are ZeroClaw"),
+                "test",
+                "gpt-4o",
+                &ChatOptions::new(0.5),
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_fails_without_key() {
+        let p = OpenAiProvider::new(None);
+        let result = p
+            .stream_chat_with_system(None, "hello", "gpt-4o", &ChatOptions::new(0.7))
             .await;
         assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("API key not set"));
     }
 
     #[test]
@@ -151,14 +372,15 @@ mod tests {
             messages: vec![
                 Message {
                     role: "system".to_string(),
-                    content: "You are ZeroClaw".to_string(),
+                    content: "You are ZeroClaw".into(),
                 },
                 Message {
                     role: "user".to_string(),
-                    content: "hello".to_string(),
+                    content: "hello".into(),
                 },
             ],
-            temperature: 0.7,
+            options: ChatOptions::new(0.7),
+            stream: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"role\":\"system\""));
@@ -166,21 +388,56 @@ mod tests {
         assert!(json.contains("gpt-4o"));
     }
 
+    #[test]
+    fn history_messages_keep_roles_and_order() {
+        let messages = Message::from_history(&[
+            ChatMessage::system("You are ZeroClaw"),
+            ChatMessage::user("hi"),
+            ChatMessage::assistant("hello"),
+            ChatMessage::user("again"),
+        ]);
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "assistant", "user"]);
+        assert_eq!(messages[3].content, MessageContent::from("again"));
+
+        let single = Message::single_turn(None, "hi");
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].role, "user");
+    }
+
     #[test]
     fn request_serializes_without_system() {
         let req = ChatRequest {
             model: "gpt-4o".to_string(),
             messages: vec![Message {
                 role: "user".to_string(),
-                content: "hello".to_string(),
+                content: "hello".into(),
             }],
-            temperature: 0.0,
+            options: ChatOptions::new(0.0),
+            stream: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("system"));
         assert!(json.contains("\"temperature\":0.0"));
     }
 
+    #[test]
+    fn request_serializes_response_format() {
+        let schema = serde_json::json!({"type": "object"});
+        let req = ChatRequest {
+            model: "gpt-4o".to_string(),
+            messages: Vec::new(),
+            options: ChatOptions::new(0.0).with_response_format(Some(
+                crate::providers::traits::ResponseFormat::json_schema(schema.clone()),
+            )),
+            stream: None,
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["response_format"]["type"], "json_schema");
+        assert_eq!(json["response_format"]["json_schema"]["name"], "response");
+        assert_eq!(json["response_format"]["json_schema"]["schema"], schema);
+    }
+
     #[test]
     fn response_deserializes_single_choice() {
         let json = r#"{"choices":[{"message":{"content":"Hi!"}}]}"#;
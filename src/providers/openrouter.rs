@@ -1,4 +1,7 @@
-use crate::providers::traits::{ChatMessage, Provider};
+use super::compatible::MessageContent;
+use crate::providers::traits::{
+    ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo, Provider, TokenUsage,
+};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -12,18 +15,23 @@ pub struct OpenRouterProvider {
 struct ChatRequest {
     model: String,
     messages: Vec<Message>,
-    temperature: f64,
+    #[serde(flatten)]
+    options: ChatOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: MessageContent,
 }
 
 #[derive(Debug, Deserialize)]
 struct ApiChatResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,17 +48,69 @@ impl OpenRouterProvider {
     pub fn new(api_key: Option<&str>) -> Self {
         Self {
             api_key: api_key.map(ToString::to_string),
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: crate::proxy::client_with("openrouter", |builder| {
+                builder.connect_timeout(std::time::Duration::from_secs(10))
+            }),
         }
     }
+
+    /// Open a streaming chat completions request (`"stream": true`).
+    async fn stream_messages(
+        &self,
+        messages: Vec<Message>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let api_key = self.api_key.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("OpenRouter API key not set. Run `zeroclaw onboard` or set OPENROUTER_API_KEY env var."))?;
+
+        let request = ChatRequest {
+            model: model.to_string(),
+            messages,
+            options: options.clone(),
+            stream: Some(true),
+        };
+
+        let response = self
+            .client
+            .post("https://openrouter.ai/api/v1/chat/completions")
+            .header("Authorization", format!("Bearer {api_key}"))
+            .header(
+                "HTTP-Referer",
+                "https://github.com/theonlyhennygod/zeroclaw",
+            )
+            .header("X-Title", "ZeroClaw")
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenRouter", response).await);
+        }
+
+        Ok(super::compatible::sse_response_stream(response))
+    }
 }
 
 #[async_trait]
 impl Provider for OpenRouterProvider {
+    async fn health_check(&self) -> anyhow::Result<()> {
+        if self.api_key.is_none() {
+            anyhow::bail!("OpenRouter API key not set. Run `zeroclaw onboard` or set OPENROUTER_API_KEY env var.");
+        }
+        self.warmup().await
+    }
+
+    /// The listing is public; it includes context lengths and prices.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let mut request = self.client.get("https://openrouter.ai/api/v1/models");
+        if let Some(api_key) = self.api_key.as_ref() {
+            request = request.header("Authorization", format!("Bearer {api_key}"));
+        }
+        let body = super::models::fetch_listing("OpenRouter", request).await?;
+        Ok(super::models::parse_openai_listing(&body))
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         // Hit a lightweight endpoint to establish TLS + HTTP/2 connection pool.
         // This prevents the first real chat request from timing out on cold start.
@@ -70,8 +130,8 @@ impl Provider for OpenRouterProvider {
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
         let api_key = self.api_key.as_ref()
             .ok_or_else(|| anyhow::anyhow!("OpenRouter API key not set. Run `zeroclaw onboard` or set OPENROUTER_API_KEY env var."))?;
 
@@ -80,19 +140,20 @@ impl Provider for OpenRouterProvider {
         if let Some(sys) = system_prompt {
             messages.push(Message {
                 role: "system".to_string(),
-                content: sys.to_string(),
+                content: sys.into(),
             });
         }
 
         messages.push(Message {
             role: "user".to_string(),
-            content: message.to_string(),
+            content: message.into(),
         });
 
         let request = ChatRequest {
             model: model.to_string(),
             messages,
-            temperature,
+            options: options.clone(),
+            stream: None,
         };
 
         let response = self
@@ -114,11 +175,12 @@ impl Provider for OpenRouterProvider {
 
         let chat_response: ApiChatResponse = response.json().await?;
 
+        let usage = chat_response.usage;
         chat_response
             .choices
             .into_iter()
             .next()
-            .map(|c| c.message.content)
+            .map(|c| ChatReply::new(c.message.content, usage))
             .ok_or_else(|| anyhow::anyhow!("No response from OpenRouter"))
     }
 
@@ -126,8 +188,8 @@ impl Provider for OpenRouterProvider {
         &self,
         messages: &[ChatMessage],
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
         let api_key = self.api_key.as_ref()
             .ok_or_else(|| anyhow::anyhow!("OpenRouter API key not set. Run `zeroclaw onboard` or set OPENROUTER_API_KEY env var."))?;
 
@@ -135,14 +197,15 @@ impl Provider for OpenRouterProvider {
             .iter()
             .map(|m| Message {
                 role: m.role.clone(),
-                content: m.content.clone(),
+                content: MessageContent::from_message(m),
             })
             .collect();
 
         let request = ChatRequest {
             model: model.to_string(),
             messages: api_messages,
-            temperature,
+            options: options.clone(),
+            stream: None,
         };
 
         let response = self
@@ -164,11 +227,53 @@ impl Provider for OpenRouterProvider {
 
         let chat_response: ApiChatResponse = response.json().await?;
 
+        let usage = chat_response.usage;
         chat_response
             .choices
             .into_iter()
             .next()
-            .map(|c| c.message.content)
+            .map(|c| ChatReply::new(c.message.content, usage))
             .ok_or_else(|| anyhow::anyhow!("No response from OpenRouter"))
     }
+
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let mut messages = Vec::new();
+
+        if let Some(sys) = system_prompt {
+            messages.push(Message {
+                role: "system".to_string(),
+                content: sys.into(),
+            });
+        }
+
+        messages.push(Message {
+            role: "user".to_string(),
+            content: message.into(),
+        });
+
+        self.stream_messages(messages, model, options).await
+    }
+
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let api_messages = messages
+            .iter()
+            .map(|m| Message {
+                role: m.role.clone(),
+                content: MessageContent::from_message(m),
+            })
+            .collect();
+
+        self.stream_messages(api_messages, model, options).await
+    }
 }
@@ -1,37 +1,128 @@
-use super::traits::ChatMessage;
+use super::traits::{ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo};
 use super::Provider;
 use async_trait::async_trait;
+use std::collections::HashMap;
+use std::future::Future;
 use std::time::Duration;
 
-/// Check if an error is non-retryable (client errors that won't resolve with retries).
+/// Per-attempt deadline when none is configured.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Check if an error is non-retryable (failures that won't resolve with retries).
+///
+/// Timeouts, connection failures, 5xx, 408 and 429 are transient. Other 4xx
+/// responses and missing credentials fail the same way on every attempt.
 fn is_non_retryable(err: &anyhow::Error) -> bool {
-    // Check for reqwest status errors (returned by .error_for_status())
-    if let Some(reqwest_err) = err.downcast_ref::<reqwest::Error>() {
-        if let Some(status) = reqwest_err.status() {
-            let code = status.as_u16();
-            // 4xx client errors are non-retryable, except:
-            // - 429 Too Many Requests (rate limiting, transient)
-            // - 408 Request Timeout (transient)
-            return status.is_client_error() && code != 429 && code != 408;
+    for cause in err.chain() {
+        // Check for reqwest errors (transport failures or `.error_for_status()`)
+        if let Some(reqwest_err) = cause.downcast_ref::<reqwest::Error>() {
+            if reqwest_err.is_timeout() || reqwest_err.is_connect() {
+                return false;
+            }
+            if let Some(status) = reqwest_err.status() {
+                return is_non_retryable_status(status.as_u16());
+            }
+        }
+        if cause.downcast_ref::<std::io::Error>().is_some() {
+            return false;
         }
     }
-    // String fallback: scan for any 4xx status code in error message
+
     let msg = err.to_string();
+    if msg.contains("key not set") || msg.contains("credentials not set") {
+        return true;
+    }
+    // Errors built by `api_error` lead with the HTTP status; trust it over any
+    // other number that appears in the provider's error body.
+    if let Some(code) = api_error_status(&msg) {
+        return is_non_retryable_status(code);
+    }
+    // String fallback: scan for any 4xx status code in error message
     for word in msg.split(|c: char| !c.is_ascii_digit()) {
         if let Ok(code) = word.parse::<u16>() {
             if (400..500).contains(&code) {
-                return code != 429 && code != 408;
+                return is_non_retryable_status(code);
             }
         }
     }
     false
 }
 
-/// Provider wrapper with retry + fallback behavior.
+/// 4xx client errors are non-retryable, except:
+/// - 429 Too Many Requests (rate limiting, transient)
+/// - 408 Request Timeout (transient)
+fn is_non_retryable_status(code: u16) -> bool {
+    (400..500).contains(&code) && code != 429 && code != 408
+}
+
+/// Extract the status from an `"<Provider> API error (<status>): …"` message.
+fn api_error_status(msg: &str) -> Option<u16> {
+    let (_, rest) = msg.split_once("API error (")?;
+    rest.get(..3)?.parse().ok()
+}
+
+/// Random extra delay in `0..=jitter_ms`, so clients that failed together
+/// don't retry in lockstep.
+fn jitter(jitter_ms: u64) -> Duration {
+    if jitter_ms == 0 {
+        return Duration::ZERO;
+    }
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| u64::from(d.subsec_nanos()));
+    Duration::from_millis(seed % (jitter_ms + 1))
+}
+
+/// Error for an attempt that outlived its deadline. Built on an I/O
+/// `TimedOut` error so the retry classifier treats it as transient.
+fn timeout_error(provider: &str, timeout: Duration) -> anyhow::Error {
+    anyhow::Error::new(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        format!(
+            "{provider} request timed out after {}s",
+            timeout.as_secs_f64()
+        ),
+    ))
+}
+
+/// Forward `stream` until it ends, the receiver is dropped, or no chunk
+/// arrives for `idle`, in which case a timeout error ends the stream.
+fn with_idle_timeout(provider: &str, mut stream: ChatStream, idle: Duration) -> ChatStream {
+    let (tx, rx) = tokio::sync::mpsc::channel(64);
+    let provider = provider.to_string();
+    tokio::spawn(async move {
+        loop {
+            let next = tokio::select! {
+                () = tx.closed() => return,
+                next = tokio::time::timeout(idle, stream.recv()) => next,
+            };
+            match next {
+                Ok(Some(item)) => {
+                    if tx.send(item).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(None) => return,
+                Err(_) => {
+                    let _ = tx.send(Err(timeout_error(&provider, idle))).await;
+                    return;
+                }
+            }
+        }
+    });
+    rx
+}
+
+/// Provider wrapper with retry + fallback behavior. Each attempt runs under
+/// a deadline; the providers' HTTP clients only bound the connect phase.
 pub struct ReliableProvider {
     providers: Vec<(String, Box<dyn Provider>)>,
     max_retries: u32,
     base_backoff_ms: u64,
+    max_backoff_ms: u64,
+    jitter_ms: u64,
+    timeout: Duration,
+    timeouts: HashMap<String, Duration>,
 }
 
 impl ReliableProvider {
@@ -44,8 +135,50 @@ impl ReliableProvider {
             providers,
             max_retries,
             base_backoff_ms: base_backoff_ms.max(50),
+            max_backoff_ms: 10_000,
+            jitter_ms: 0,
+            timeout: DEFAULT_TIMEOUT,
+            timeouts: HashMap::new(),
         }
     }
+
+    /// Abandon an attempt after `timeout` (per provider name in `timeouts`,
+    /// falling back to `default`). Dropping the attempt cancels the request.
+    #[must_use]
+    pub fn with_timeouts(mut self, default: Duration, timeouts: HashMap<String, Duration>) -> Self {
+        self.timeout = default;
+        self.timeouts = timeouts;
+        self
+    }
+
+    fn timeout_for(&self, provider: &str) -> Duration {
+        self.timeouts.get(provider).copied().unwrap_or(self.timeout)
+    }
+
+    /// Run one attempt against `provider` under its deadline.
+    async fn attempt<T>(
+        &self,
+        provider: &str,
+        call: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let timeout = self.timeout_for(provider);
+        tokio::time::timeout(timeout, call)
+            .await
+            .unwrap_or_else(|_| Err(timeout_error(provider, timeout)))
+    }
+
+    /// Cap the exponential backoff at `max_backoff_ms` and add up to
+    /// `jitter_ms` of random delay to every retry.
+    #[must_use]
+    pub fn with_backoff_limits(mut self, max_backoff_ms: u64, jitter_ms: u64) -> Self {
+        self.max_backoff_ms = max_backoff_ms.max(self.base_backoff_ms);
+        self.jitter_ms = jitter_ms;
+        self
+    }
+
+    fn retry_delay(&self, backoff_ms: u64) -> Duration {
+        Duration::from_millis(backoff_ms) + jitter(self.jitter_ms)
+    }
 }
 
 #[async_trait]
@@ -60,21 +193,48 @@ impl Provider for ReliableProvider {
         Ok(())
     }
 
+    /// Healthy while at least one provider in the fallback chain is.
+    async fn health_check(&self) -> anyhow::Result<()> {
+        let mut failures = Vec::new();
+        for (name, provider) in &self.providers {
+            match provider.health_check().await {
+                Ok(()) => return Ok(()),
+                Err(e) => failures.push(format!("{name}: {e}")),
+            }
+        }
+        anyhow::bail!("All providers unhealthy. {}", failures.join("; "))
+    }
+
+    /// Models of the first provider in the chain that can list them.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let mut failures = Vec::new();
+        for (name, provider) in &self.providers {
+            match provider.list_models().await {
+                Ok(models) => return Ok(models),
+                Err(e) => failures.push(format!("{name}: {e}")),
+            }
+        }
+        anyhow::bail!("No provider could list models. {}", failures.join("; "))
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
         let mut failures = Vec::new();
 
         for (provider_name, provider) in &self.providers {
             let mut backoff_ms = self.base_backoff_ms;
 
             for attempt in 0..=self.max_retries {
-                match provider
-                    .chat_with_system(system_prompt, message, model, temperature)
+                match self
+                    .attempt(
+                        provider_name,
+                        provider.chat_with_system(system_prompt, message, model, options),
+                    )
                     .await
                 {
                     Ok(resp) => {
@@ -110,8 +270,8 @@ impl Provider for ReliableProvider {
                                 max_retries = self.max_retries,
                                 "Provider call failed, retrying"
                             );
-                            tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
-                            backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
+                            tokio::time::sleep(self.retry_delay(backoff_ms)).await;
+                            backoff_ms = backoff_ms.saturating_mul(2).min(self.max_backoff_ms);
                         }
                     }
                 }
@@ -127,16 +287,19 @@ impl Provider for ReliableProvider {
         &self,
         messages: &[ChatMessage],
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
         let mut failures = Vec::new();
 
         for (provider_name, provider) in &self.providers {
             let mut backoff_ms = self.base_backoff_ms;
 
             for attempt in 0..=self.max_retries {
-                match provider
-                    .chat_with_history(messages, model, temperature)
+                match self
+                    .attempt(
+                        provider_name,
+                        provider.chat_with_history(messages, model, options),
+                    )
                     .await
                 {
                     Ok(resp) => {
@@ -172,8 +335,145 @@ impl Provider for ReliableProvider {
                                 max_retries = self.max_retries,
                                 "Provider call failed, retrying"
                             );
-                            tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
-                            backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
+                            tokio::time::sleep(self.retry_delay(backoff_ms)).await;
+                            backoff_ms = backoff_ms.saturating_mul(2).min(self.max_backoff_ms);
+                        }
+                    }
+                }
+            }
+
+            tracing::warn!(provider = provider_name, "Switching to fallback provider");
+        }
+
+        anyhow::bail!("All providers failed. Attempts:\n{}", failures.join("\n"))
+    }
+
+    /// Retries and falls back only while opening the stream. Once the first
+    /// provider accepts the request, mid-stream errors are passed through to
+    /// the caller because partial output has already been delivered.
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let mut failures = Vec::new();
+
+        for (provider_name, provider) in &self.providers {
+            let mut backoff_ms = self.base_backoff_ms;
+
+            for attempt in 0..=self.max_retries {
+                match self
+                    .attempt(
+                        provider_name,
+                        provider.stream_chat_with_system(system_prompt, message, model, options),
+                    )
+                    .await
+                {
+                    Ok(stream) => {
+                        if attempt > 0 {
+                            tracing::info!(
+                                provider = provider_name,
+                                attempt,
+                                "Provider recovered after retries"
+                            );
+                        }
+                        let idle = self.timeout_for(provider_name);
+                        return Ok(with_idle_timeout(provider_name, stream, idle));
+                    }
+                    Err(e) => {
+                        let non_retryable = is_non_retryable(&e);
+                        failures.push(format!(
+                            "{provider_name} attempt {}/{}: {e}",
+                            attempt + 1,
+                            self.max_retries + 1
+                        ));
+
+                        if non_retryable {
+                            tracing::warn!(
+                                provider = provider_name,
+                                "Non-retryable error, switching provider"
+                            );
+                            break;
+                        }
+
+                        if attempt < self.max_retries {
+                            tracing::warn!(
+                                provider = provider_name,
+                                attempt = attempt + 1,
+                                max_retries = self.max_retries,
+                                "Provider stream failed to open, retrying"
+                            );
+                            tokio::time::sleep(self.retry_delay(backoff_ms)).await;
+                            backoff_ms = backoff_ms.saturating_mul(2).min(self.max_backoff_ms);
+                        }
+                    }
+                }
+            }
+
+            tracing::warn!(provider = provider_name, "Switching to fallback provider");
+        }
+
+        anyhow::bail!("All providers failed. Attempts:\n{}", failures.join("\n"))
+    }
+
+    /// Same open-time retry and fallback semantics as `stream_chat_with_system`.
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let mut failures = Vec::new();
+
+        for (provider_name, provider) in &self.providers {
+            let mut backoff_ms = self.base_backoff_ms;
+
+            for attempt in 0..=self.max_retries {
+                match self
+                    .attempt(
+                        provider_name,
+                        provider.stream_chat_with_history(messages, model, options),
+                    )
+                    .await
+                {
+                    Ok(stream) => {
+                        if attempt > 0 {
+                            tracing::info!(
+                                provider = provider_name,
+                                attempt,
+                                "Provider recovered after retries"
+                            );
+                        }
+                        let idle = self.timeout_for(provider_name);
+                        return Ok(with_idle_timeout(provider_name, stream, idle));
+                    }
+                    Err(e) => {
+                        let non_retryable = is_non_retryable(&e);
+                        failures.push(format!(
+                            "{provider_name} attempt {}/{}: {e}",
+                            attempt + 1,
+                            self.max_retries + 1
+                        ));
+
+                        if non_retryable {
+                            tracing::warn!(
+                                provider = provider_name,
+                                "Non-retryable error, switching provider"
+                            );
+                            break;
+                        }
+
+                        if attempt < self.max_retries {
+                            tracing::warn!(
+                                provider = provider_name,
+                                attempt = attempt + 1,
+                                max_retries = self.max_retries,
+                                "Provider stream failed to open, retrying"
+                            );
+                            tokio::time::sleep(self.retry_delay(backoff_ms)).await;
+                            backoff_ms = backoff_ms.saturating_mul(2).min(self.max_backoff_ms);
                         }
                     }
                 }
@@ -206,26 +506,26 @@ mod tests {
             _system_prompt: Option<&str>,
             _message: &str,
             _model: &str,
-            _temperature: f64,
-        ) -> anyhow::Result<String> {
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
             let attempt = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
             if attempt <= self.fail_until_attempt {
                 anyhow::bail!(self.error);
             }
-            Ok(self.response.to_string())
+            Ok(self.response.into())
         }
 
         async fn chat_with_history(
             &self,
             _messages: &[ChatMessage],
             _model: &str,
-            _temperature: f64,
-        ) -> anyhow::Result<String> {
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
             let attempt = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
             if attempt <= self.fail_until_attempt {
                 anyhow::bail!(self.error);
             }
-            Ok(self.response.to_string())
+            Ok(self.response.into())
         }
     }
 
@@ -246,8 +546,11 @@ mod tests {
             1,
         );
 
-        let result = provider.chat("hello", "test", 0.0).await.unwrap();
-        assert_eq!(result, "ok");
+        let result = provider
+            .chat("hello", "test", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(result.text, "ok");
         assert_eq!(calls.load(Ordering::SeqCst), 1);
     }
 
@@ -268,8 +571,11 @@ mod tests {
             1,
         );
 
-        let result = provider.chat("hello", "test", 0.0).await.unwrap();
-        assert_eq!(result, "recovered");
+        let result = provider
+            .chat("hello", "test", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(result.text, "recovered");
         assert_eq!(calls.load(Ordering::SeqCst), 2);
     }
 
@@ -303,8 +609,11 @@ mod tests {
             1,
         );
 
-        let result = provider.chat("hello", "test", 0.0).await.unwrap();
-        assert_eq!(result, "from fallback");
+        let result = provider
+            .chat("hello", "test", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(result.text, "from fallback");
         assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
     }
@@ -337,7 +646,7 @@ mod tests {
         );
 
         let err = provider
-            .chat("hello", "test", 0.0)
+            .chat("hello", "test", &ChatOptions::new(0.0))
             .await
             .expect_err("all providers should fail");
         let msg = err.to_string();
@@ -370,6 +679,54 @@ mod tests {
         assert!(!is_non_retryable(&anyhow::anyhow!("connection reset")));
     }
 
+    #[test]
+    fn api_error_status_wins_over_numbers_in_body() {
+        // 5xx with a 4xx-looking number in the body is still transient
+        assert!(!is_non_retryable(&anyhow::anyhow!(
+            "OpenAI API error (503 Service Unavailable): upstream returned 404"
+        )));
+        assert!(is_non_retryable(&anyhow::anyhow!(
+            "Anthropic API error (401 Unauthorized): invalid x-api-key"
+        )));
+        assert!(!is_non_retryable(&anyhow::anyhow!(
+            "Groq API error (429 Too Many Requests): slow down"
+        )));
+    }
+
+    #[test]
+    fn missing_credentials_are_not_retried() {
+        assert!(is_non_retryable(&anyhow::anyhow!(
+            "OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml."
+        )));
+        assert!(is_non_retryable(&anyhow::anyhow!(
+            "Anthropic credentials not set. Set ANTHROPIC_API_KEY."
+        )));
+    }
+
+    #[test]
+    fn io_errors_are_retryable() {
+        let err = anyhow::Error::new(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "peer closed connection 400 bytes in",
+        ));
+        assert!(!is_non_retryable(&err));
+    }
+
+    #[test]
+    fn backoff_is_capped_and_jittered_within_bounds() {
+        let provider = ReliableProvider::new(Vec::new(), 3, 100).with_backoff_limits(40, 25);
+        // The cap never drops below the base delay
+        assert_eq!(provider.max_backoff_ms, 100);
+        for _ in 0..20 {
+            let delay = provider.retry_delay(100);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= Duration::from_millis(125));
+        }
+
+        let provider = ReliableProvider::new(Vec::new(), 3, 100);
+        assert_eq!(provider.retry_delay(400), Duration::from_millis(400));
+    }
+
     #[tokio::test]
     async fn skips_retries_on_non_retryable_error() {
         let primary_calls = Arc::new(AtomicUsize::new(0));
@@ -400,8 +757,11 @@ mod tests {
             1,
         );
 
-        let result = provider.chat("hello", "test", 0.0).await.unwrap();
-        assert_eq!(result, "from fallback");
+        let result = provider
+            .chat("hello", "test", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(result.text, "from fallback");
         // Primary should have been called only once (no retries)
         assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
@@ -429,10 +789,10 @@ mod tests {
             ChatMessage::user("hello"),
         ];
         let result = provider
-            .chat_with_history(&messages, "test", 0.0)
+            .chat_with_history(&messages, "test", &ChatOptions::new(0.0))
             .await
             .unwrap();
-        assert_eq!(result, "history ok");
+        assert_eq!(result.text, "history ok");
         assert_eq!(calls.load(Ordering::SeqCst), 2);
     }
 
@@ -468,11 +828,218 @@ mod tests {
 
         let messages = vec![ChatMessage::user("hello")];
         let result = provider
-            .chat_with_history(&messages, "test", 0.0)
+            .chat_with_history(&messages, "test", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(result.text, "fallback ok");
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn stream_falls_back_when_primary_cannot_open() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&primary_calls),
+                        fail_until_attempt: usize::MAX,
+                        response: "never",
+                        error: "primary down",
+                    }),
+                ),
+                (
+                    "fallback".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&fallback_calls),
+                        fail_until_attempt: 0,
+                        response: "streamed fallback",
+                        error: "fallback err",
+                    }),
+                ),
+            ],
+            1,
+            1,
+        );
+
+        let mut stream = provider
+            .stream_chat_with_system(None, "hello", "test", &ChatOptions::new(0.0))
             .await
             .unwrap();
-        assert_eq!(result, "fallback ok");
+        assert_eq!(stream.recv().await.unwrap().unwrap(), "streamed fallback");
+        assert!(stream.recv().await.is_none());
         assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
     }
+
+    /// Never answers; counts calls and drops of its in-flight request.
+    struct HangingProvider {
+        calls: Arc<AtomicUsize>,
+        cancelled: Arc<AtomicUsize>,
+    }
+
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl Provider for HangingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let _guard = DropCounter(Arc::clone(&self.cancelled));
+            std::future::pending().await
+        }
+
+        async fn stream_chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatStream> {
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            tokio::spawn(async move {
+                let _ = tx.send(Ok("partial".to_string())).await;
+                tx.closed().await;
+            });
+            Ok(rx)
+        }
+    }
+
+    fn hanging(calls: &Arc<AtomicUsize>, cancelled: &Arc<AtomicUsize>) -> Box<dyn Provider> {
+        Box::new(HangingProvider {
+            calls: Arc::clone(calls),
+            cancelled: Arc::clone(cancelled),
+        })
+    }
+
+    #[tokio::test]
+    async fn hung_attempts_time_out_and_fall_back() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![
+                ("slow".into(), hanging(&calls, &cancelled)),
+                (
+                    "fallback".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&fallback_calls),
+                        fail_until_attempt: 0,
+                        response: "fallback ok",
+                        error: "fallback err",
+                    }),
+                ),
+            ],
+            1,
+            1,
+        )
+        .with_timeouts(
+            Duration::from_secs(5),
+            HashMap::from([("slow".to_string(), Duration::from_millis(20))]),
+        );
+
+        let reply = provider
+            .chat_with_system(None, "hello", "test", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "fallback ok");
+        // Timeouts are retried, and every abandoned attempt was dropped.
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(cancelled.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn timeout_errors_are_retryable() {
+        let err = timeout_error("slow", Duration::from_secs(450));
+        assert!(!is_non_retryable(&err));
+        assert!(err.to_string().contains("timed out after 450s"));
+    }
+
+    #[tokio::test]
+    async fn stalled_stream_ends_with_timeout_error() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicUsize::new(0));
+        let provider =
+            ReliableProvider::new(vec![("slow".into(), hanging(&calls, &cancelled))], 0, 1)
+                .with_timeouts(Duration::from_millis(20), HashMap::new());
+
+        let mut stream = provider
+            .stream_chat_with_system(None, "hello", "test", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(stream.recv().await.unwrap().unwrap(), "partial");
+        let err = stream.recv().await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(stream.recv().await.is_none());
+    }
+
+    struct HealthProvider {
+        healthy: bool,
+    }
+
+    #[async_trait]
+    impl Provider for HealthProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            Ok("ok".into())
+        }
+
+        async fn health_check(&self) -> anyhow::Result<()> {
+            if self.healthy {
+                Ok(())
+            } else {
+                anyhow::bail!("401 Unauthorized")
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn health_check_passes_when_any_fallback_is_healthy() {
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(HealthProvider { healthy: false }),
+                ),
+                (
+                    "fallback".into(),
+                    Box::new(HealthProvider { healthy: true }),
+                ),
+            ],
+            0,
+            1,
+        );
+        assert!(provider.health_check().await.is_ok());
+
+        let provider = ReliableProvider::new(
+            vec![(
+                "primary".into(),
+                Box::new(HealthProvider { healthy: false }),
+            )],
+            0,
+            1,
+        );
+        let err = provider.health_check().await.unwrap_err().to_string();
+        assert!(err.contains("primary: 401 Unauthorized"));
+    }
 }
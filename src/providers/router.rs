@@ -1,4 +1,4 @@
-use super::traits::ChatMessage;
+use super::traits::{ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo};
 use super::Provider;
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -10,6 +10,47 @@ pub struct Route {
     pub model: String,
 }
 
+/// Content rule that selects a route hint for requests on the default model.
+#[derive(Debug, Clone, Default)]
+pub struct RoutingRule {
+    pub hint: String,
+    pub max_chars: Option<usize>,
+    pub keywords: Vec<String>,
+    /// Only match messages that carry images
+    pub images: bool,
+}
+
+impl RoutingRule {
+    /// All configured conditions must hold; a rule without conditions matches everything.
+    fn matches(&self, message: &str, has_images: bool) -> bool {
+        if self.images && !has_images {
+            return false;
+        }
+        let text = strip_memory_context(message);
+        if self.max_chars.is_some_and(|max| text.chars().count() > max) {
+            return false;
+        }
+        if self.keywords.is_empty() {
+            return true;
+        }
+        let lower = text.to_lowercase();
+        self.keywords
+            .iter()
+            .any(|keyword| lower.contains(&keyword.to_lowercase()))
+    }
+}
+
+/// The agent prepends recalled memories to the user message; rules should
+/// only look at what the user actually sent.
+fn strip_memory_context(message: &str) -> &str {
+    if message.starts_with(super::middleware::MEMORY_CONTEXT_HEADER) {
+        if let Some((_, rest)) = message.split_once("\n\n") {
+            return rest;
+        }
+    }
+    message
+}
+
 /// Multi-model router — routes requests to different provider+model combos
 /// based on a task hint encoded in the model parameter.
 ///
@@ -17,10 +58,14 @@ pub struct Route {
 /// - A regular model name (e.g. "anthropic/claude-sonnet-4-20250514") → uses default provider
 /// - A hint-prefixed string (e.g. "hint:reasoning") → resolves via route table
 ///
+/// Requests for the default model are additionally matched against content
+/// rules (see [`RouterProvider::with_rules`]) before falling back to it.
+///
 /// This wraps multiple pre-created providers and selects the right one per request.
 pub struct RouterProvider {
     routes: HashMap<String, (usize, String)>, // hint → (provider_index, model)
     providers: Vec<(String, Box<dyn Provider>)>,
+    rules: Vec<RoutingRule>,
     default_index: usize,
     default_model: String,
 }
@@ -64,16 +109,53 @@ impl RouterProvider {
         Self {
             routes: resolved_routes,
             providers,
+            rules: Vec::new(),
             default_index: 0,
             default_model,
         }
     }
 
-    /// Resolve a model parameter to a (provider, actual_model) pair.
+    /// Attach content rules, checked in order. Rules naming an unknown hint are dropped.
+    #[must_use]
+    pub fn with_rules(mut self, rules: Vec<RoutingRule>) -> Self {
+        self.rules = rules
+            .into_iter()
+            .filter(|rule| {
+                let known = self.routes.contains_key(&rule.hint);
+                if !known {
+                    tracing::warn!(
+                        hint = rule.hint,
+                        "Routing rule references unknown route, skipping"
+                    );
+                }
+                known
+            })
+            .collect();
+        self
+    }
+
+    /// Resolve a request to a provider index and model, letting the
+    /// first matching rule override the default model.
+    fn resolve_for(&self, model: &str, message: &str, has_images: bool) -> (usize, String) {
+        if model == self.default_model {
+            if let Some(rule) = self
+                .rules
+                .iter()
+                .find(|rule| rule.matches(message, has_images))
+            {
+                if let Some((idx, resolved_model)) = self.routes.get(&rule.hint) {
+                    tracing::debug!(hint = rule.hint, "Routing rule matched");
+                    return (*idx, resolved_model.clone());
+                }
+            }
+        }
+        self.resolve(model)
+    }
+
+    /// Resolve a model parameter to a (provider_index, actual_model) pair.
     ///
     /// If the model starts with "hint:", look up the hint in the route table.
     /// Otherwise, use the default provider with the given model name.
-    /// Resolve a model parameter to a (provider_index, actual_model) pair.
     fn resolve(&self, model: &str) -> (usize, String) {
         if let Some(hint) = model.strip_prefix("hint:") {
             if let Some((idx, resolved_model)) = self.routes.get(hint) {
@@ -88,6 +170,14 @@ impl RouterProvider {
         // Not a hint or hint not found — use default provider with the model as-is
         (self.default_index, model.to_string())
     }
+
+    /// [`Self::resolve_for`] on the last user message of a conversation.
+    fn resolve_history(&self, model: &str, messages: &[ChatMessage]) -> (usize, String) {
+        match messages.iter().rfind(|m| m.role == "user") {
+            Some(last) => self.resolve_for(model, &last.content, !last.images.is_empty()),
+            None => self.resolve_for(model, "", false),
+        }
+    }
 }
 
 #[async_trait]
@@ -97,10 +187,30 @@ impl Provider for RouterProvider {
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let (provider_idx, resolved_model) = self.resolve_for(model, message, false);
+
+        let (provider_name, provider) = &self.providers[provider_idx];
+        tracing::info!(
+            provider = provider_name.as_str(),
+            model = resolved_model.as_str(),
+            "Router dispatching request"
+        );
+
+        let reply = provider
+            .chat_with_system(system_prompt, message, &resolved_model, options)
+            .await?;
+        Ok(reply.with_model(resolved_model))
+    }
 
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        let (provider_idx, resolved_model) = self.resolve_history(model, messages);
         let (provider_name, provider) = &self.providers[provider_idx];
         tracing::info!(
             provider = provider_name.as_str(),
@@ -108,24 +218,56 @@ impl Provider for RouterProvider {
             "Router dispatching request"
         );
 
+        let reply = provider
+            .chat_with_history(messages, &resolved_model, options)
+            .await?;
+        Ok(reply.with_model(resolved_model))
+    }
+
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let (provider_idx, resolved_model) = self.resolve_for(model, message, false);
+        let (_, provider) = &self.providers[provider_idx];
         provider
-            .chat_with_system(system_prompt, message, &resolved_model, temperature)
+            .stream_chat_with_system(system_prompt, message, &resolved_model, options)
             .await
     }
 
-    async fn chat_with_history(
+    async fn stream_chat_with_history(
         &self,
         messages: &[ChatMessage],
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let (provider_idx, resolved_model) = self.resolve_history(model, messages);
         let (_, provider) = &self.providers[provider_idx];
         provider
-            .chat_with_history(messages, &resolved_model, temperature)
+            .stream_chat_with_history(messages, &resolved_model, options)
             .await
     }
 
+    /// Every routed provider must be healthy, since any hint may select it.
+    async fn health_check(&self) -> anyhow::Result<()> {
+        for (name, provider) in &self.providers {
+            provider
+                .health_check()
+                .await
+                .map_err(|e| anyhow::anyhow!("{name}: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Models of the default route's provider.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let (_, provider) = &self.providers[self.default_index];
+        provider.list_models().await
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         for (name, provider) in &self.providers {
             tracing::info!(provider = name, "Warming up routed provider");
@@ -140,6 +282,7 @@ impl Provider for RouterProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::traits::ImageInput;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
 
@@ -174,11 +317,15 @@ mod tests {
             _system_prompt: Option<&str>,
             _message: &str,
             model: &str,
-            _temperature: f64,
-        ) -> anyhow::Result<String> {
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
             self.calls.fetch_add(1, Ordering::SeqCst);
             *self.last_model.lock().unwrap() = model.to_string();
-            Ok(self.response.to_string())
+            Ok(self.response.into())
+        }
+
+        async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+            Ok(vec![ModelInfo::new(self.response)])
         }
     }
 
@@ -228,12 +375,27 @@ mod tests {
             system_prompt: Option<&str>,
             message: &str,
             model: &str,
-            temperature: f64,
-        ) -> anyhow::Result<String> {
+            options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
             self.as_ref()
-                .chat_with_system(system_prompt, message, model, temperature)
+                .chat_with_system(system_prompt, message, model, options)
                 .await
         }
+
+        async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+            self.as_ref().list_models().await
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_models_of_the_default_provider() {
+        let (router, _) = make_router(
+            vec![("default", "default-model"), ("smart", "smart-model")],
+            vec![("reasoning", "smart", "claude-opus")],
+        );
+
+        let models = router.list_models().await.unwrap();
+        assert_eq!(models, [ModelInfo::new("default-model")]);
     }
 
     #[tokio::test]
@@ -246,8 +408,11 @@ mod tests {
             ],
         );
 
-        let result = router.chat("hello", "hint:reasoning", 0.5).await.unwrap();
-        assert_eq!(result, "smart-response");
+        let result = router
+            .chat("hello", "hint:reasoning", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(result.text, "smart-response");
         assert_eq!(mocks[1].call_count(), 1);
         assert_eq!(mocks[1].last_model(), "claude-opus");
         assert_eq!(mocks[0].call_count(), 0);
@@ -260,8 +425,11 @@ mod tests {
             vec![("fast", "fast", "llama-3-70b")],
         );
 
-        let result = router.chat("hello", "hint:fast", 0.5).await.unwrap();
-        assert_eq!(result, "fast-response");
+        let result = router
+            .chat("hello", "hint:fast", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(result.text, "fast-response");
         assert_eq!(mocks[0].call_count(), 1);
         assert_eq!(mocks[0].last_model(), "llama-3-70b");
     }
@@ -273,8 +441,11 @@ mod tests {
             vec![],
         );
 
-        let result = router.chat("hello", "hint:nonexistent", 0.5).await.unwrap();
-        assert_eq!(result, "default-response");
+        let result = router
+            .chat("hello", "hint:nonexistent", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(result.text, "default-response");
         assert_eq!(mocks[0].call_count(), 1);
         // Falls back to default with the hint as model name
         assert_eq!(mocks[0].last_model(), "hint:nonexistent");
@@ -291,10 +462,14 @@ mod tests {
         );
 
         let result = router
-            .chat("hello", "anthropic/claude-sonnet-4-20250514", 0.5)
+            .chat(
+                "hello",
+                "anthropic/claude-sonnet-4-20250514",
+                &ChatOptions::new(0.5),
+            )
             .await
             .unwrap();
-        assert_eq!(result, "primary-response");
+        assert_eq!(result.text, "primary-response");
         assert_eq!(mocks[0].call_count(), 1);
         assert_eq!(mocks[0].last_model(), "anthropic/claude-sonnet-4-20250514");
     }
@@ -339,6 +514,135 @@ mod tests {
         assert!(router.warmup().await.is_ok());
     }
 
+    fn rule(hint: &str, max_chars: Option<usize>, keywords: &[&str]) -> RoutingRule {
+        RoutingRule {
+            hint: hint.to_string(),
+            max_chars,
+            keywords: keywords.iter().map(|k| (*k).to_string()).collect(),
+            images: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn rules_pick_route_for_default_model_and_report_it() {
+        let (router, mocks) = make_router(
+            vec![("main", "main"), ("cheap", "cheap"), ("coder", "coder")],
+            vec![
+                ("fast", "cheap", "gpt-4o-mini"),
+                ("code", "coder", "codestral"),
+            ],
+        );
+        let router = router.with_rules(vec![
+            rule("code", None, &["Function", "```"]),
+            rule("fast", Some(20), &[]),
+        ]);
+
+        let reply = router
+            .chat(
+                "refactor this function please",
+                "default-model",
+                &ChatOptions::new(0.5),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "coder");
+        assert_eq!(reply.model.as_deref(), Some("codestral"));
+
+        let reply = router
+            .chat("hi", "default-model", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "cheap");
+        assert_eq!(mocks[1].last_model(), "gpt-4o-mini");
+
+        let long = "tell me a long story about the sea and its creatures";
+        let reply = router
+            .chat(long, "default-model", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "main");
+        assert_eq!(reply.model.as_deref(), Some("default-model"));
+
+        // An explicitly requested model bypasses the rules.
+        let reply = router
+            .chat("hi", "gpt-4o", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "main");
+        assert_eq!(mocks[0].last_model(), "gpt-4o");
+    }
+
+    #[tokio::test]
+    async fn rules_ignore_memory_context_and_match_last_user_message() {
+        let (router, _) = make_router(
+            vec![("main", "main"), ("local", "local")],
+            vec![("local", "local", "llama3")],
+        );
+        let router = router.with_rules(vec![rule("local", None, &["[heartbeat task]"])]);
+
+        let messages = vec![
+            ChatMessage::system("sys"),
+            ChatMessage::user("[Heartbeat Task] earlier"),
+            ChatMessage::assistant("done"),
+            ChatMessage::user(
+                "[Memory context]\n- note: mentions [Heartbeat Task]\n\nwhat's the weather?",
+            ),
+        ];
+        let reply = router
+            .chat_with_history(&messages, "default-model", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "main");
+
+        let messages = vec![ChatMessage::user(
+            "[Memory context]\n- note: x\n\n[Heartbeat Task] check inbox",
+        )];
+        let reply = router
+            .chat_with_history(&messages, "default-model", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "local");
+        assert_eq!(reply.model.as_deref(), Some("llama3"));
+    }
+
+    #[tokio::test]
+    async fn image_rules_route_only_messages_with_images() {
+        let (router, _) = make_router(
+            vec![("main", "main"), ("vision", "vision")],
+            vec![("vision", "vision", "gpt-4o")],
+        );
+        let router = router.with_rules(vec![RoutingRule {
+            images: true,
+            ..rule("vision", None, &[])
+        }]);
+
+        let text_only = vec![ChatMessage::user("describe the sea")];
+        let reply = router
+            .chat_with_history(&text_only, "default-model", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "main");
+
+        let photo = vec![
+            ChatMessage::user("describe this").with_images(vec![ImageInput::Url {
+                url: "https://x.test/sea.jpg".into(),
+            }]),
+        ];
+        let reply = router
+            .chat_with_history(&photo, "default-model", &ChatOptions::new(0.5))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "vision");
+        assert_eq!(reply.model.as_deref(), Some("gpt-4o"));
+    }
+
+    #[test]
+    fn rules_with_unknown_route_are_dropped() {
+        let (router, _) = make_router(vec![("default", "ok")], vec![]);
+        let router = router.with_rules(vec![rule("missing", Some(10), &[])]);
+        assert!(router.rules.is_empty());
+    }
+
     #[tokio::test]
     async fn chat_with_system_passes_system_prompt() {
         let mock = Arc::new(MockProvider::new("response"));
@@ -352,10 +656,10 @@ mod tests {
         );
 
         let result = router
-            .chat_with_system(Some("system"), "hello", "model", 0.5)
+            .chat_with_system(Some("system"), "hello", "model", &ChatOptions::new(0.5))
             .await
             .unwrap();
-        assert_eq!(result, "response");
+        assert_eq!(result.text, "response");
         assert_eq!(mock.call_count(), 1);
     }
 }
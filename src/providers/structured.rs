@@ -0,0 +1,548 @@
+//! Structured (JSON) output.
+//!
+//! [`chat_structured`] asks the provider for its native JSON mode via
+//! [`ChatOptions::response_format`], tells the model the schema in the system
+//! prompt (for providers without a native mode), then parses and validates the
+//! reply. A reply that is not valid JSON or does not match the schema is sent
+//! back with the validation errors, up to [`STRUCTURED_MAX_ATTEMPTS`] times.
+//!
+//! The validator covers the JSON Schema keywords models are usually given:
+//! `type`, `enum`, `const`, `properties`, `required`, `additionalProperties`,
+//! `items`, `minItems`/`maxItems`, `minLength`/`maxLength`, `pattern`,
+//! `minimum`/`maximum` (and the exclusive forms), `anyOf`, `oneOf` and
+//! `allOf`. Other keywords are ignored.
+
+use super::traits::{ChatMessage, ChatOptions, ChatReply, ResponseFormat, TokenUsage};
+use super::Provider;
+use serde_json::Value;
+
+/// Attempts (first request plus corrections) before giving up.
+pub const STRUCTURED_MAX_ATTEMPTS: u32 = 3;
+
+/// A reply that parsed and validated against the requested schema.
+#[derive(Debug, Clone)]
+pub struct StructuredReply {
+    pub value: Value,
+    /// The final reply; `usage` is summed over all attempts.
+    pub reply: ChatReply,
+    pub attempts: u32,
+}
+
+/// Every attempt produced invalid JSON or JSON that did not match the schema.
+#[derive(Debug)]
+pub struct StructuredOutputError {
+    pub errors: Vec<String>,
+    /// The last reply, for callers that want to show it anyway.
+    pub last_reply: ChatReply,
+    pub attempts: u32,
+}
+
+impl std::fmt::Display for StructuredOutputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Reply did not match the JSON schema after {} attempt(s): {}",
+            self.attempts,
+            self.errors.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for StructuredOutputError {}
+
+/// Chat over `messages` until the reply is JSON matching `schema`.
+pub async fn chat_structured<P: Provider + ?Sized>(
+    provider: &P,
+    messages: &[ChatMessage],
+    schema: &Value,
+    model: &str,
+    options: &ChatOptions,
+) -> anyhow::Result<StructuredReply> {
+    if !schema.is_object() && !schema.is_boolean() {
+        anyhow::bail!("JSON schema must be an object");
+    }
+
+    let options = options
+        .clone()
+        .with_response_format(Some(ResponseFormat::json_schema(schema.clone())));
+    let mut messages = with_schema_instruction(messages, schema);
+    let mut usage: Option<TokenUsage> = None;
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let mut reply = provider
+            .chat_with_history(&messages, model, &options)
+            .await?;
+        if let Some(u) = reply.usage {
+            let total = usage.get_or_insert_with(TokenUsage::default);
+            total.prompt_tokens += u.prompt_tokens;
+            total.completion_tokens += u.completion_tokens;
+        }
+        reply.usage = usage;
+
+        let errors = match extract_json(&reply.text) {
+            Ok(value) => {
+                let errors = validate(schema, &value);
+                if errors.is_empty() {
+                    return Ok(StructuredReply {
+                        value,
+                        reply,
+                        attempts: attempt,
+                    });
+                }
+                errors
+            }
+            Err(e) => vec![e],
+        };
+
+        tracing::debug!(attempt, ?errors, "Structured reply failed validation");
+        if attempt >= STRUCTURED_MAX_ATTEMPTS {
+            return Err(anyhow::Error::new(StructuredOutputError {
+                errors,
+                last_reply: reply,
+                attempts: attempt,
+            }));
+        }
+
+        messages.push(ChatMessage::assistant(reply.text));
+        messages.push(ChatMessage::user(format!(
+            "That reply did not match the JSON schema:\n- {}\nRespond again with only the corrected JSON.",
+            errors.join("\n- ")
+        )));
+    }
+}
+
+/// Append the schema instruction to the system prompt, adding one if missing.
+fn with_schema_instruction(messages: &[ChatMessage], schema: &Value) -> Vec<ChatMessage> {
+    let instruction = format!(
+        "Respond with only a JSON value, without any other text, that matches this JSON Schema:\n{schema}"
+    );
+    let mut messages = messages.to_vec();
+    if let Some(system) = messages.iter_mut().find(|m| m.role == "system") {
+        system.content = format!("{}\n\n{instruction}", system.content);
+    } else {
+        messages.insert(0, ChatMessage::system(instruction));
+    }
+    messages
+}
+
+/// Parse the JSON in a model reply, tolerating code fences and surrounding
+/// prose.
+pub fn extract_json(text: &str) -> Result<Value, String> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Ok(value);
+    }
+
+    if let Some(start) = trimmed.find("```") {
+        let body = &trimmed[start + 3..];
+        let body = body.split_once('\n').map_or(body, |(_, rest)| rest);
+        if let Some(end) = body.find("```") {
+            if let Ok(value) = serde_json::from_str(body[..end].trim()) {
+                return Ok(value);
+            }
+        }
+    }
+
+    let start = trimmed.find(['{', '[']);
+    let end = trimmed.rfind(['}', ']']);
+    if let (Some(start), Some(end)) = (start, end) {
+        if start < end {
+            return serde_json::from_str(&trimmed[start..=end])
+                .map_err(|e| format!("reply is not valid JSON: {e}"));
+        }
+    }
+    Err("reply does not contain a JSON value".into())
+}
+
+/// Validate `value` against `schema`, returning one message per violation.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, value, "$", &mut errors);
+    errors
+}
+
+type Object = serde_json::Map<String, Value>;
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        Value::Bool(false) => {
+            errors.push(format!("{path}: no value is allowed here"));
+            return;
+        }
+        _ => return,
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| type_matches(t, value)) {
+            errors.push(format!(
+                "{path}: expected {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            errors.push(format!(
+                "{path}: must be one of {}",
+                Value::Array(allowed.clone())
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{path}: must be {expected}"));
+        }
+    }
+
+    match value {
+        Value::Object(map) => check_object(schema, map, path, errors),
+        Value::Array(items) => check_array(schema, items, path, errors),
+        Value::String(s) => check_string(schema, s, path, errors),
+        Value::Number(n) => check_number(schema, n.as_f64().unwrap_or_default(), path, errors),
+        Value::Bool(_) | Value::Null => {}
+    }
+
+    check_combinators(schema, value, path, errors);
+}
+
+fn check_object(schema: &Object, map: &Object, path: &str, errors: &mut Vec<String>) {
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for key in required.iter().filter_map(Value::as_str) {
+            if !map.contains_key(key) {
+                errors.push(format!("{path}: missing required property \"{key}\""));
+            }
+        }
+    }
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (key, item) in map {
+        let item_path = format!("{path}.{key}");
+        match properties.and_then(|p| p.get(key)) {
+            Some(item_schema) => check(item_schema, item, &item_path, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(format!("{path}: unexpected property \"{key}\""));
+                }
+                Some(extra) => check(extra, item, &item_path, errors),
+                None => {}
+            },
+        }
+    }
+}
+
+fn check_array(schema: &Object, items: &[Value], path: &str, errors: &mut Vec<String>) {
+    if let Some(item_schema) = schema.get("items") {
+        for (i, item) in items.iter().enumerate() {
+            check(item_schema, item, &format!("{path}[{i}]"), errors);
+        }
+    }
+    let len = items.len() as u64;
+    if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+        if len < min {
+            errors.push(format!(
+                "{path}: expected at least {min} item(s), got {len}"
+            ));
+        }
+    }
+    if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+        if len > max {
+            errors.push(format!("{path}: expected at most {max} item(s), got {len}"));
+        }
+    }
+}
+
+fn check_string(schema: &Object, s: &str, path: &str, errors: &mut Vec<String>) {
+    let len = s.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+        if len < min {
+            errors.push(format!("{path}: expected at least {min} character(s)"));
+        }
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+        if len > max {
+            errors.push(format!("{path}: expected at most {max} character(s)"));
+        }
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        if let Ok(re) = regex::Regex::new(pattern) {
+            if !re.is_match(s) {
+                errors.push(format!("{path}: does not match pattern {pattern}"));
+            }
+        }
+    }
+}
+
+fn check_number(schema: &Object, n: f64, path: &str, errors: &mut Vec<String>) {
+    let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+    if let Some(min) = bound("minimum") {
+        if n < min {
+            errors.push(format!("{path}: must be >= {min}"));
+        }
+    }
+    if let Some(max) = bound("maximum") {
+        if n > max {
+            errors.push(format!("{path}: must be <= {max}"));
+        }
+    }
+    if let Some(min) = bound("exclusiveMinimum") {
+        if n <= min {
+            errors.push(format!("{path}: must be > {min}"));
+        }
+    }
+    if let Some(max) = bound("exclusiveMaximum") {
+        if n >= max {
+            errors.push(format!("{path}: must be < {max}"));
+        }
+    }
+}
+
+fn check_combinators(schema: &Object, value: &Value, path: &str, errors: &mut Vec<String>) {
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        for sub in all {
+            check(sub, value, path, errors);
+        }
+    }
+    if let Some(Value::Array(any)) = schema.get("anyOf") {
+        if !any
+            .iter()
+            .any(|sub| validate_at(sub, value, path).is_empty())
+        {
+            errors.push(format!("{path}: does not match any allowed schema"));
+        }
+    }
+    if let Some(Value::Array(one)) = schema.get("oneOf") {
+        let matches = one
+            .iter()
+            .filter(|sub| validate_at(sub, value, path).is_empty())
+            .count();
+        if matches != 1 {
+            errors.push(format!(
+                "{path}: must match exactly one allowed schema, matched {matches}"
+            ));
+        }
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, value, path, &mut errors);
+    errors
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => match value {
+            Value::Number(n) => {
+                n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+            }
+            _ => false,
+        },
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// Replies with the queued texts in order and records each request.
+    struct Scripted {
+        replies: Mutex<Vec<&'static str>>,
+        requests: Mutex<Vec<(Vec<ChatMessage>, ChatOptions)>>,
+    }
+
+    impl Scripted {
+        fn new(replies: &[&'static str]) -> Self {
+            Self {
+                replies: Mutex::new(replies.iter().rev().copied().collect()),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Provider for Scripted {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            unreachable!("chat_with_history is overridden")
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((messages.to_vec(), options.clone()));
+            let text = self.replies.lock().unwrap().pop().unwrap_or("");
+            Ok(ChatReply::new(text, Some(TokenUsage::new(10, 5))))
+        }
+    }
+
+    fn person_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "age": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 2}
+            },
+            "required": ["name", "age"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn valid_value_has_no_errors() {
+        let value = json!({"name": "Ada", "age": 36, "tags": ["math"]});
+        assert!(validate(&person_schema(), &value).is_empty());
+    }
+
+    #[test]
+    fn reports_each_violation_with_its_path() {
+        let value = json!({"name": "", "age": 1.5, "tags": ["a", 2, "c"], "extra": true});
+        let errors = validate(&person_schema(), &value);
+        assert!(errors.contains(&"$.name: expected at least 1 character(s)".to_string()));
+        assert!(errors.contains(&"$.age: expected integer, got number".to_string()));
+        assert!(errors.contains(&"$.tags[1]: expected string, got integer".to_string()));
+        assert!(errors.contains(&"$.tags: expected at most 2 item(s), got 3".to_string()));
+        assert!(errors.contains(&"$: unexpected property \"extra\"".to_string()));
+
+        let errors = validate(&person_schema(), &json!({"name": "Ada"}));
+        assert_eq!(errors, vec!["$: missing required property \"age\""]);
+    }
+
+    #[test]
+    fn supports_enum_and_combinators() {
+        let schema = json!({"enum": ["low", "high"]});
+        assert!(validate(&schema, &json!("low")).is_empty());
+        assert_eq!(validate(&schema, &json!("mid")).len(), 1);
+
+        let schema = json!({"anyOf": [{"type": "string"}, {"type": "null"}]});
+        assert!(validate(&schema, &Value::Null).is_empty());
+        assert_eq!(validate(&schema, &json!(3)).len(), 1);
+
+        let schema = json!({"oneOf": [{"type": "number"}, {"type": "integer"}]});
+        assert_eq!(validate(&schema, &json!(3)).len(), 1);
+        assert!(validate(&schema, &json!(3.5)).is_empty());
+
+        let schema = json!({"type": ["string", "null"], "pattern": "^[a-z]+$"});
+        assert!(validate(&schema, &json!("abc")).is_empty());
+        assert_eq!(validate(&schema, &json!("ABC")).len(), 1);
+    }
+
+    #[test]
+    fn extracts_json_from_fences_and_prose() {
+        assert_eq!(extract_json(" {\"a\": 1} ").unwrap(), json!({"a": 1}));
+        assert_eq!(
+            extract_json("```json\n{\"a\": 1}\n```").unwrap(),
+            json!({"a": 1})
+        );
+        assert_eq!(
+            extract_json("Here you go: [1, 2] Hope that helps.").unwrap(),
+            json!([1, 2])
+        );
+        assert!(extract_json("no json here").is_err());
+        assert!(extract_json("{\"a\": }").is_err());
+    }
+
+    #[tokio::test]
+    async fn retries_with_validation_errors_until_valid() {
+        let provider = Scripted::new(&[
+            "Sure! {\"name\": \"Ada\"}",
+            "{\"name\": \"Ada\", \"age\": 36}",
+        ]);
+        let messages = [ChatMessage::system("Be terse."), ChatMessage::user("Who?")];
+        let reply = chat_structured(
+            &provider,
+            &messages,
+            &person_schema(),
+            "model",
+            &ChatOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(reply.value, json!({"name": "Ada", "age": 36}));
+        assert_eq!(reply.attempts, 2);
+        assert_eq!(reply.reply.usage, Some(TokenUsage::new(20, 10)));
+
+        let requests = provider.requests.lock().unwrap();
+        let (first, options) = &requests[0];
+        assert!(first[0]
+            .content
+            .starts_with("Be terse.\n\nRespond with only a JSON value"));
+        assert_eq!(
+            options
+                .response_format
+                .as_ref()
+                .and_then(ResponseFormat::schema),
+            Some(&person_schema())
+        );
+        let (second, _) = &requests[1];
+        assert_eq!(second.len(), 4);
+        assert_eq!(second[2].role, "assistant");
+        assert!(second[3]
+            .content
+            .contains("missing required property \"age\""));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let provider = Scripted::new(&["nope", "still no", "{}"]);
+        let err = chat_structured(
+            &provider,
+            &[ChatMessage::user("Who?")],
+            &person_schema(),
+            "model",
+            &ChatOptions::default(),
+        )
+        .await
+        .unwrap_err();
+
+        let err = err.downcast_ref::<StructuredOutputError>().unwrap();
+        assert_eq!(err.attempts, STRUCTURED_MAX_ATTEMPTS);
+        assert_eq!(err.last_reply.text, "{}");
+        assert_eq!(err.errors.len(), 2);
+        assert_eq!(provider.requests.lock().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,684 @@
+//! Client-side rate limiting for providers.
+//!
+//! Every provider gets a token bucket sized by
+//! `reliability.provider_requests_per_minute`, shared by all wrappers with the
+//! same provider name. A 429 pauses the provider until the time announced by
+//! `Retry-After` / rate-limit reset headers (or an exponential backoff when the
+//! provider sends none), halves the bucket rate, and the request is queued and
+//! retried instead of failing — for at most `provider_throttle_max_wait_secs`.
+//! Successful calls restore the configured rate one request per minute at a
+//! time. Counters per provider are exposed through [`snapshot`].
+
+use super::traits::{ChatMessage, ChatOptions, ChatReply, ChatStream, ModelInfo};
+use super::Provider;
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Backoff after a 429 that carries no reset hint; doubled per consecutive 429.
+const DEFAULT_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// A 429 response, with the wait the provider asked for when it sent one.
+/// Displays exactly like other [`super::api_error`] errors.
+#[derive(Debug)]
+pub struct RateLimitedError {
+    pub message: String,
+    pub retry_after: Option<Duration>,
+}
+
+impl std::fmt::Display for RateLimitedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RateLimitedError {}
+
+/// Longest reset time announced by the standard rate-limit headers:
+/// `retry-after-ms`, `retry-after` (seconds or HTTP date),
+/// `x-ratelimit-reset-{requests,tokens}` (`"6m0s"`, `"250ms"`),
+/// `anthropic-ratelimit-{requests,tokens}-reset` (RFC 3339) and
+/// `x-ratelimit-reset` (epoch milliseconds or seconds).
+pub fn retry_after_from_headers(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+    };
+    let now = chrono::Utc::now();
+    let until = |at: chrono::DateTime<chrono::Utc>| (at - now).to_std().ok();
+
+    let mut waits = Vec::new();
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.parse::<f64>().ok()) {
+        waits.push(Duration::try_from_secs_f64(ms / 1000.0).ok());
+    }
+    if let Some(value) = header("retry-after") {
+        waits.push(match value.parse::<f64>() {
+            Ok(secs) => Duration::try_from_secs_f64(secs).ok(),
+            Err(_) => chrono::DateTime::parse_from_rfc2822(value)
+                .ok()
+                .and_then(|at| until(at.into())),
+        });
+    }
+    for name in ["x-ratelimit-reset-requests", "x-ratelimit-reset-tokens"] {
+        waits.push(header(name).and_then(parse_go_duration));
+    }
+    for name in [
+        "anthropic-ratelimit-requests-reset",
+        "anthropic-ratelimit-tokens-reset",
+    ] {
+        waits.push(
+            header(name)
+                .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+                .and_then(|at| until(at.into())),
+        );
+    }
+    if let Some(epoch) = header("x-ratelimit-reset").and_then(|v| v.parse::<i64>().ok()) {
+        let at = if epoch > 1_000_000_000_000 {
+            chrono::DateTime::from_timestamp_millis(epoch)
+        } else {
+            chrono::DateTime::from_timestamp(epoch, 0)
+        };
+        waits.push(at.and_then(until));
+    }
+
+    waits.into_iter().flatten().max()
+}
+
+/// Parse durations like `"1s"`, `"6m0s"`, `"1h2m3.5s"` or `"250ms"`.
+fn parse_go_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .filter(|&i| i > 0)?;
+        let number: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 1e-3,
+            "us" | "µs" => 1e-6,
+            "ns" => 1e-9,
+            _ => return None,
+        };
+        total += number * scale;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+/// How a failed call was rate limited.
+#[derive(Debug, PartialEq)]
+enum RateLimit {
+    /// The provider announced when to retry.
+    RetryAfter(Duration),
+    /// 429 without a usable reset hint.
+    Unspecified,
+}
+
+/// Classify `err` as a 429, if it is one.
+fn rate_limit_of(err: &anyhow::Error) -> Option<RateLimit> {
+    if let Some(limited) = err
+        .chain()
+        .find_map(|c| c.downcast_ref::<RateLimitedError>())
+    {
+        return Some(
+            limited
+                .retry_after
+                .map_or(RateLimit::Unspecified, RateLimit::RetryAfter),
+        );
+    }
+    err.to_string()
+        .contains("API error (429")
+        .then_some(RateLimit::Unspecified)
+}
+
+/// Throttling counters for one provider.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ThrottleSnapshot {
+    /// Requests that passed through the throttle.
+    pub requests: u64,
+    /// Requests that had to wait before being sent.
+    pub delayed: u64,
+    /// 429 responses received.
+    pub rate_limited: u64,
+    /// Requests that failed because the wait would exceed the limit.
+    pub rejected: u64,
+    /// Total time spent waiting, in milliseconds.
+    pub wait_ms: u64,
+    /// Current adaptive rate (0 = unlimited).
+    pub requests_per_minute: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicU64,
+    delayed: AtomicU64,
+    rate_limited: AtomicU64,
+    rejected: AtomicU64,
+    wait_ms: AtomicU64,
+}
+
+struct Bucket {
+    configured_rpm: u32,
+    rpm: u32,
+    tokens: f64,
+    refilled_at: Instant,
+    blocked_until: Option<Instant>,
+    consecutive_429: u32,
+}
+
+impl Bucket {
+    fn new(rpm: u32) -> Self {
+        Self {
+            configured_rpm: rpm,
+            rpm,
+            tokens: f64::from(rpm),
+            refilled_at: Instant::now(),
+            blocked_until: None,
+            consecutive_429: 0,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if self.rpm > 0 {
+            let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+            let rate = f64::from(self.rpm) / 60.0;
+            self.tokens = (self.tokens + elapsed * rate).min(f64::from(self.rpm));
+        }
+        self.refilled_at = now;
+    }
+
+    /// Take a token now, or return how long until one is available.
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(until) = self.blocked_until {
+            if until > now {
+                return Err(until - now);
+            }
+            self.blocked_until = None;
+        }
+        if self.rpm == 0 {
+            return Ok(());
+        }
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        let rate = f64::from(self.rpm) / 60.0;
+        Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+    }
+
+    /// Pause after a 429 and halve the rate; returns the pause.
+    fn on_rate_limited(&mut self, now: Instant, retry_after: Option<Duration>) -> Duration {
+        self.consecutive_429 = self.consecutive_429.saturating_add(1);
+        let pause = retry_after.unwrap_or_else(|| {
+            DEFAULT_RATE_LIMIT_BACKOFF
+                .saturating_mul(1 << self.consecutive_429.saturating_sub(1).min(6))
+        });
+        let until = now + pause;
+        self.blocked_until = Some(self.blocked_until.map_or(until, |b| b.max(until)));
+        if self.rpm > 1 {
+            self.rpm /= 2;
+            self.tokens = self.tokens.min(f64::from(self.rpm));
+        }
+        pause
+    }
+
+    fn on_success(&mut self) {
+        self.consecutive_429 = 0;
+        if self.rpm < self.configured_rpm {
+            self.rpm += 1;
+        }
+    }
+}
+
+struct Shared {
+    bucket: Mutex<Bucket>,
+    counters: Counters,
+}
+
+static REGISTRY: OnceLock<Mutex<BTreeMap<String, Arc<Shared>>>> = OnceLock::new();
+
+fn registry() -> &'static Mutex<BTreeMap<String, Arc<Shared>>> {
+    REGISTRY.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Bucket for `provider`, created on first use. A later call with a different
+/// rate reconfigures the existing bucket.
+fn shared(provider: &str, rpm: u32) -> Arc<Shared> {
+    let mut map = registry()
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let entry = map.entry(provider.to_string()).or_insert_with(|| {
+        Arc::new(Shared {
+            bucket: Mutex::new(Bucket::new(rpm)),
+            counters: Counters::default(),
+        })
+    });
+    {
+        let mut bucket = entry
+            .bucket
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if bucket.configured_rpm != rpm {
+            *bucket = Bucket::new(rpm);
+        }
+    }
+    Arc::clone(entry)
+}
+
+/// Throttling counters for every provider seen so far, by name.
+pub fn snapshot() -> BTreeMap<String, ThrottleSnapshot> {
+    let map = registry()
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    map.iter()
+        .map(|(name, shared)| {
+            let c = &shared.counters;
+            let rpm = shared
+                .bucket
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .rpm;
+            let snapshot = ThrottleSnapshot {
+                requests: c.requests.load(Ordering::Relaxed),
+                delayed: c.delayed.load(Ordering::Relaxed),
+                rate_limited: c.rate_limited.load(Ordering::Relaxed),
+                rejected: c.rejected.load(Ordering::Relaxed),
+                wait_ms: c.wait_ms.load(Ordering::Relaxed),
+                requests_per_minute: u64::from(rpm),
+            };
+            (name.clone(), snapshot)
+        })
+        .collect()
+}
+
+/// Provider wrapper that queues requests behind the provider's rate limits.
+pub struct ThrottledProvider {
+    name: String,
+    inner: Box<dyn Provider>,
+    shared: Arc<Shared>,
+    max_wait: Duration,
+}
+
+impl ThrottledProvider {
+    pub fn new(
+        name: &str,
+        inner: Box<dyn Provider>,
+        requests_per_minute: u32,
+        max_wait: Duration,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            inner,
+            shared: shared(name, requests_per_minute),
+            max_wait,
+        }
+    }
+
+    fn bucket(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.shared
+            .bucket
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Wait for a token, spending at most what is left of the wait budget.
+    async fn acquire(&self, waited: &mut Duration) -> anyhow::Result<()> {
+        loop {
+            let wait = match self.bucket().try_acquire(Instant::now()) {
+                Ok(()) => return Ok(()),
+                Err(wait) => wait,
+            };
+            if *waited + wait > self.max_wait {
+                self.shared
+                    .counters
+                    .rejected
+                    .fetch_add(1, Ordering::Relaxed);
+                anyhow::bail!(
+                    "{} API error (429 Too Many Requests): client-side rate limit, next slot in {}s",
+                    self.name,
+                    wait.as_secs_f64().ceil()
+                );
+            }
+            if waited.is_zero() {
+                self.shared.counters.delayed.fetch_add(1, Ordering::Relaxed);
+            }
+            tracing::debug!(
+                provider = self.name,
+                wait_ms = wait.as_millis(),
+                "Throttling provider request"
+            );
+            tokio::time::sleep(wait).await;
+            *waited += wait;
+            let wait_ms = u64::try_from(wait.as_millis()).unwrap_or(u64::MAX);
+            self.shared
+                .counters
+                .wait_ms
+                .fetch_add(wait_ms, Ordering::Relaxed);
+        }
+    }
+
+    /// Run `call` once a token is available; on 429, pause the provider and
+    /// retry while the wait budget allows.
+    async fn run<T, F, Fut>(&self, call: F) -> anyhow::Result<T>
+    where
+        F: Fn() -> Fut + Send,
+        Fut: Future<Output = anyhow::Result<T>> + Send,
+    {
+        if self.max_wait.is_zero() {
+            return call().await;
+        }
+        self.shared
+            .counters
+            .requests
+            .fetch_add(1, Ordering::Relaxed);
+        let mut waited = Duration::ZERO;
+        loop {
+            self.acquire(&mut waited).await?;
+            match call().await {
+                Ok(value) => {
+                    self.bucket().on_success();
+                    return Ok(value);
+                }
+                Err(e) => {
+                    let Some(limit) = rate_limit_of(&e) else {
+                        return Err(e);
+                    };
+                    let retry_after = match limit {
+                        RateLimit::RetryAfter(wait) => Some(wait),
+                        RateLimit::Unspecified => None,
+                    };
+                    self.shared
+                        .counters
+                        .rate_limited
+                        .fetch_add(1, Ordering::Relaxed);
+                    let pause = self.bucket().on_rate_limited(Instant::now(), retry_after);
+                    if waited + pause > self.max_wait {
+                        self.shared
+                            .counters
+                            .rejected
+                            .fetch_add(1, Ordering::Relaxed);
+                        return Err(e);
+                    }
+                    tracing::warn!(
+                        provider = self.name,
+                        pause_ms = pause.as_millis(),
+                        "Provider rate limited, queueing request"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for ThrottledProvider {
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        self.run(|| {
+            self.inner
+                .chat_with_system(system_prompt, message, model, options)
+        })
+        .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        self.run(|| self.inner.chat_with_history(messages, model, options))
+            .await
+    }
+
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.run(|| {
+            self.inner
+                .stream_chat_with_system(system_prompt, message, model, options)
+        })
+        .await
+    }
+
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.run(|| {
+            self.inner
+                .stream_chat_with_history(messages, model, options)
+        })
+        .await
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn health_check(&self) -> anyhow::Result<()> {
+        self.inner.health_check().await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        self.inner.list_models().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Fails with 429 `failures` times, then succeeds.
+    struct RateLimitedThenOk {
+        calls: Arc<AtomicUsize>,
+        failures: usize,
+        retry_after: Option<Duration>,
+    }
+
+    #[async_trait]
+    impl Provider for RateLimitedThenOk {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            let attempt = self.calls.fetch_add(1, Ordering::SeqCst);
+            if attempt < self.failures {
+                return Err(anyhow::Error::new(RateLimitedError {
+                    message: "Test API error (429 Too Many Requests): slow down".into(),
+                    retry_after: self.retry_after,
+                }));
+            }
+            Ok(ChatReply::new("ok", None))
+        }
+    }
+
+    fn throttled(
+        name: &str,
+        failures: usize,
+        rpm: u32,
+        max_wait: Duration,
+    ) -> (ThrottledProvider, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let inner = RateLimitedThenOk {
+            calls: Arc::clone(&calls),
+            failures,
+            retry_after: Some(Duration::from_millis(20)),
+        };
+        (
+            ThrottledProvider::new(name, Box::new(inner), rpm, max_wait),
+            calls,
+        )
+    }
+
+    #[test]
+    fn parses_go_style_durations() {
+        assert_eq!(parse_go_duration("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_go_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_go_duration("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(
+            parse_go_duration("1h2m3.5s"),
+            Some(Duration::from_secs_f64(3723.5))
+        );
+        assert_eq!(parse_go_duration("soon"), None);
+        assert_eq!(parse_go_duration("5"), None);
+    }
+
+    #[test]
+    fn retry_after_prefers_longest_hint() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("2"));
+        headers.insert("x-ratelimit-reset-tokens", HeaderValue::from_static("6m0s"));
+        assert_eq!(
+            retry_after_from_headers(&headers),
+            Some(Duration::from_secs(360))
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after-ms", HeaderValue::from_static("1500"));
+        assert_eq!(
+            retry_after_from_headers(&headers),
+            Some(Duration::from_millis(1500))
+        );
+
+        assert_eq!(retry_after_from_headers(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn retry_after_reads_anthropic_reset_timestamp() {
+        let reset = (chrono::Utc::now() + chrono::Duration::seconds(30)).to_rfc3339();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "anthropic-ratelimit-requests-reset",
+            HeaderValue::from_str(&reset).unwrap(),
+        );
+        let wait = retry_after_from_headers(&headers).unwrap();
+        assert!(wait > Duration::from_secs(28) && wait <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn detects_rate_limit_errors() {
+        let typed = anyhow::Error::new(RateLimitedError {
+            message: "X API error (429 Too Many Requests): busy".into(),
+            retry_after: Some(Duration::from_secs(3)),
+        });
+        assert_eq!(
+            rate_limit_of(&typed),
+            Some(RateLimit::RetryAfter(Duration::from_secs(3)))
+        );
+        let plain = anyhow::anyhow!("X API error (429 Too Many Requests): busy");
+        assert_eq!(rate_limit_of(&plain), Some(RateLimit::Unspecified));
+        let other = anyhow::anyhow!("X API error (500 Internal Server Error): boom");
+        assert_eq!(rate_limit_of(&other), None);
+    }
+
+    #[test]
+    fn bucket_spends_tokens_then_reports_wait() {
+        let mut bucket = Bucket::new(2);
+        let now = Instant::now();
+        assert!(bucket.try_acquire(now).is_ok());
+        assert!(bucket.try_acquire(now).is_ok());
+        let wait = bucket.try_acquire(now).unwrap_err();
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn rate_limit_halves_rate_and_success_recovers() {
+        let mut bucket = Bucket::new(60);
+        let now = Instant::now();
+        let pause = bucket.on_rate_limited(now, None);
+        assert_eq!(pause, DEFAULT_RATE_LIMIT_BACKOFF);
+        assert_eq!(bucket.rpm, 30);
+        assert!(bucket.try_acquire(now).is_err());
+        assert_eq!(
+            bucket.on_rate_limited(now, None),
+            DEFAULT_RATE_LIMIT_BACKOFF * 2
+        );
+        bucket.on_success();
+        assert_eq!(bucket.rpm, 16);
+        assert_eq!(bucket.consecutive_429, 0);
+    }
+
+    #[test]
+    fn unlimited_bucket_only_blocks_after_rate_limit() {
+        let mut bucket = Bucket::new(0);
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert!(bucket.try_acquire(now).is_ok());
+        }
+        bucket.on_rate_limited(now, Some(Duration::from_secs(5)));
+        assert_eq!(bucket.rpm, 0);
+        assert!(bucket.try_acquire(now).is_err());
+        assert!(bucket.try_acquire(now + Duration::from_secs(5)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn queues_request_through_rate_limit() {
+        let (provider, calls) = throttled("throttle-test-queue", 2, 0, Duration::from_secs(5));
+        let reply = provider
+            .chat_with_system(None, "hi", "m", &ChatOptions::new(0.0))
+            .await
+            .unwrap();
+        assert_eq!(reply.text, "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let stats = &snapshot()["throttle-test-queue"];
+        assert_eq!(stats.requests, 1);
+        assert_eq!(stats.rate_limited, 2);
+        assert_eq!(stats.delayed, 1);
+        assert!(stats.wait_ms >= 20);
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_wait_exceeds_budget() {
+        let (provider, calls) = throttled("throttle-test-budget", 5, 0, Duration::from_millis(30));
+        let err = provider
+            .chat_with_system(None, "hi", "m", &ChatOptions::new(0.0))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("429"));
+        assert!(calls.load(Ordering::SeqCst) < 5);
+        assert_eq!(snapshot()["throttle-test-budget"].rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn zero_max_wait_passes_errors_through() {
+        let (provider, calls) = throttled("throttle-test-off", 1, 0, Duration::ZERO);
+        assert!(provider
+            .chat_with_system(None, "hi", "m", &ChatOptions::new(0.0))
+            .await
+            .is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
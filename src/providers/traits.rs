@@ -6,27 +6,297 @@ use serde::{Deserialize, Serialize};
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
+    /// Images attached to a user message, for vision-capable models.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageInput>,
 }
 
 impl ChatMessage {
     pub fn system(content: impl Into<String>) -> Self {
+        Self::with_role("system", content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::with_role("user", content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::with_role("assistant", content)
+    }
+
+    fn with_role(role: &str, content: impl Into<String>) -> Self {
         Self {
-            role: "system".into(),
+            role: role.into(),
             content: content.into(),
+            images: Vec::new(),
         }
     }
 
-    pub fn user(content: impl Into<String>) -> Self {
+    #[must_use]
+    pub fn with_images(mut self, images: Vec<ImageInput>) -> Self {
+        self.images = images;
+        self
+    }
+}
+
+/// An image sent alongside a message. Serialized as
+/// `{"type": "url", "url": "..."}` or
+/// `{"type": "base64", "media_type": "image/png", "data": "..."}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageInput {
+    /// Image the provider fetches itself
+    Url { url: String },
+    /// Inline image bytes, base64-encoded
+    Base64 { media_type: String, data: String },
+}
+
+impl ImageInput {
+    pub fn base64(media_type: impl Into<String>, bytes: &[u8]) -> Self {
+        use base64::Engine as _;
+        Self::Base64 {
+            media_type: media_type.into(),
+            data: base64::engine::general_purpose::STANDARD.encode(bytes),
+        }
+    }
+
+    /// The image as a URL: the URL itself, or a `data:` URL for inline bytes.
+    pub fn to_url(&self) -> String {
+        match self {
+            Self::Url { url } => url.clone(),
+            Self::Base64 { media_type, data } => format!("data:{media_type};base64,{data}"),
+        }
+    }
+
+    /// MIME type of the image; guessed from the extension for URLs.
+    pub fn media_type(&self) -> &str {
+        match self {
+            Self::Base64 { media_type, .. } => media_type,
+            Self::Url { url } => {
+                let path = url.split(['?', '#']).next().unwrap_or(url);
+                let ext = path.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+                match ext.as_str() {
+                    "png" => "image/png",
+                    "gif" => "image/gif",
+                    "webp" => "image/webp",
+                    _ => "image/jpeg",
+                }
+            }
+        }
+    }
+}
+
+/// Token counts reported by the provider for one completion.
+///
+/// Field names follow the `OpenAI` `usage` object; Anthropic-style
+/// `input_tokens` / `output_tokens` deserialize into the same fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default, alias = "input_tokens")]
+    pub prompt_tokens: u64,
+    #[serde(default, alias = "output_tokens")]
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn new(prompt_tokens: u64, completion_tokens: u64) -> Self {
         Self {
-            role: "user".into(),
-            content: content.into(),
+            prompt_tokens,
+            completion_tokens,
         }
     }
 
-    pub fn assistant(content: impl Into<String>) -> Self {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+impl std::fmt::Display for TokenUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} tokens ({} prompt + {} completion)",
+            self.total_tokens(),
+            self.prompt_tokens,
+            self.completion_tokens
+        )
+    }
+}
+
+/// A completed chat reply plus the token usage the provider reported, if any.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChatReply {
+    pub text: String,
+    pub usage: Option<TokenUsage>,
+    /// Model that actually served the request, when a router picked it.
+    pub model: Option<String>,
+}
+
+impl ChatReply {
+    pub fn new(text: impl Into<String>, usage: Option<TokenUsage>) -> Self {
         Self {
-            role: "assistant".into(),
-            content: content.into(),
+            text: text.into(),
+            usage,
+            model: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Usage as a JSON object with prompt, completion and total counts.
+    pub fn usage_json(&self) -> Option<serde_json::Value> {
+        self.usage.map(|u| {
+            serde_json::json!({
+                "prompt_tokens": u.prompt_tokens,
+                "completion_tokens": u.completion_tokens,
+                "total_tokens": u.total_tokens(),
+            })
+        })
+    }
+}
+
+impl From<String> for ChatReply {
+    fn from(text: String) -> Self {
+        Self::new(text, None)
+    }
+}
+
+impl From<&str> for ChatReply {
+    fn from(text: &str) -> Self {
+        Self::new(text, None)
+    }
+}
+
+/// Generation settings for one request. Unset fields use the provider's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatOptions {
+    pub temperature: f64,
+    /// Upper bound on completion tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Nucleus sampling probability mass (0.0–1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Sequences that end the completion when generated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    /// Sampling seed, for providers that support reproducible output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Constrain the reply to JSON, using the provider's native JSON mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+impl ChatOptions {
+    pub fn new(temperature: f64) -> Self {
+        Self {
+            temperature,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn with_max_tokens(mut self, max_tokens: Option<u32>) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    #[must_use]
+    pub fn with_top_p(mut self, top_p: Option<f64>) -> Self {
+        self.top_p = top_p;
+        self
+    }
+
+    #[must_use]
+    pub fn with_stop(mut self, stop: Vec<String>) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    #[must_use]
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    #[must_use]
+    pub fn with_response_format(mut self, response_format: Option<ResponseFormat>) -> Self {
+        self.response_format = response_format;
+        self
+    }
+}
+
+/// JSON output mode, serialized as the OpenAI-style `response_format` object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Any JSON object
+    JsonObject,
+    /// JSON matching `json_schema.schema`
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub schema: serde_json::Value,
+    /// Ask for strict schema adherence (`OpenAI` rejects schemas that do not
+    /// meet its strict-mode rules, so this stays off by default).
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl ResponseFormat {
+    pub fn json_schema(schema: serde_json::Value) -> Self {
+        Self::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: "response".into(),
+                schema,
+                strict: false,
+            },
+        }
+    }
+
+    /// The requested schema, if any.
+    pub fn schema(&self) -> Option<&serde_json::Value> {
+        match self {
+            Self::JsonObject => None,
+            Self::JsonSchema { json_schema } => Some(&json_schema.schema),
+        }
+    }
+}
+
+impl From<f64> for ChatOptions {
+    fn from(temperature: f64) -> Self {
+        Self::new(temperature)
+    }
+}
+
+/// A model from a provider's model listing.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    /// Context window in tokens, when the provider reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    /// USD per million prompt tokens, when the provider reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_per_million: Option<f64>,
+    /// USD per million completion tokens, when the provider reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_per_million: Option<f64>,
+}
+
+impl ModelInfo {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            ..Self::default()
         }
     }
 }
@@ -82,11 +352,19 @@ pub enum ConversationMessage {
     ToolResult(ToolResultMessage),
 }
 
+/// Receiver end of a streaming completion. Each item is the next text delta;
+/// the channel closes once the provider has finished the response.
+pub type ChatStream = tokio::sync::mpsc::Receiver<anyhow::Result<String>>;
+
 #[async_trait]
 pub trait Provider: Send + Sync {
-    async fn chat(&self, message: &str, model: &str, temperature: f64) -> anyhow::Result<String> {
-        self.chat_with_system(None, message, model, temperature)
-            .await
+    async fn chat(
+        &self,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
+        self.chat_with_system(None, message, model, options).await
     }
 
     async fn chat_with_system(
@@ -94,17 +372,19 @@ pub trait Provider: Send + Sync {
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String>;
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply>;
 
-    /// Multi-turn conversation. Default implementation extracts the last user
-    /// message and delegates to `chat_with_system`.
+    /// Multi-turn conversation over role-tagged messages. Built-in providers
+    /// send the whole list, including image attachments, in their native
+    /// format; the default implementation keeps only the system prompt and
+    /// last user message text and delegates to `chat_with_system`.
     async fn chat_with_history(
         &self,
         messages: &[ChatMessage],
         model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatReply> {
         let system = messages
             .iter()
             .find(|m| m.role == "system")
@@ -114,15 +394,79 @@ pub trait Provider: Send + Sync {
             .rfind(|m| m.role == "user")
             .map(|m| m.content.as_str())
             .unwrap_or("");
-        self.chat_with_system(system, last_user, model, temperature)
+        self.chat_with_system(system, last_user, model, options)
             .await
     }
 
+    /// Streaming variant of `chat_with_system`. Default implementation waits
+    /// for the full completion and delivers it as a single chunk, so every
+    /// provider can be streamed even without native SSE support.
+    async fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let text = self
+            .chat_with_system(system_prompt, message, model, options)
+            .await?
+            .text;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let _ = tx.send(Ok(text)).await;
+        Ok(rx)
+    }
+
+    /// Streaming variant of `chat_with_history`. Default implementation waits
+    /// for `chat_with_history` and delivers the reply as a single chunk.
+    async fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let text = self.chat_with_history(messages, model, options).await?.text;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let _ = tx.send(Ok(text)).await;
+        Ok(rx)
+    }
+
+    /// Chat constrained to JSON matching `schema`: requests the provider's
+    /// native JSON mode, validates the reply and re-asks with the validation
+    /// errors when it does not match. See [`super::structured`].
+    async fn chat_structured(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        schema: &serde_json::Value,
+        model: &str,
+        options: &ChatOptions,
+    ) -> anyhow::Result<super::structured::StructuredReply> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        super::structured::chat_structured(self, &messages, schema, model, options).await
+    }
+
     /// Warm up the HTTP connection pool (TLS handshake, DNS, HTTP/2 setup).
     /// Default implementation is a no-op; providers with HTTP clients should override.
     async fn warmup(&self) -> anyhow::Result<()> {
         Ok(())
     }
+
+    /// Check that the provider is reachable and accepts our credentials.
+    /// Used by the gateway readiness probe; defaults to [`Provider::warmup`].
+    async fn health_check(&self) -> anyhow::Result<()> {
+        self.warmup().await
+    }
+
+    /// Models this provider offers, sorted by id. Providers without a
+    /// listing API return an error.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        anyhow::bail!("This provider cannot list its models")
+    }
 }
 
 #[cfg(test)]
@@ -163,6 +507,78 @@ mod tests {
         assert_eq!(with_tools.text_or_empty(), "Let me check");
     }
 
+    #[test]
+    fn token_usage_totals_and_display() {
+        let usage = TokenUsage::new(120, 30);
+        assert_eq!(usage.total_tokens(), 150);
+        assert_eq!(usage.to_string(), "150 tokens (120 prompt + 30 completion)");
+
+        let reply = ChatReply::new("hi", Some(usage));
+        let json = reply.usage_json().unwrap();
+        assert_eq!(json["total_tokens"], 150);
+        assert!(ChatReply::from("hi").usage_json().is_none());
+    }
+
+    struct EchoProvider;
+
+    #[async_trait]
+    impl Provider for EchoProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _options: &ChatOptions,
+        ) -> anyhow::Result<ChatReply> {
+            Ok(format!("echo: {message}").into())
+        }
+    }
+
+    #[tokio::test]
+    async fn default_stream_yields_full_response_as_single_chunk() {
+        let mut rx = EchoProvider
+            .stream_chat_with_system(None, "hi", "model", &ChatOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap().unwrap(), "echo: hi");
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn default_history_stream_uses_last_user_message() {
+        let messages = vec![
+            ChatMessage::user("first"),
+            ChatMessage::assistant("echo: first"),
+            ChatMessage::user("second"),
+        ];
+        let mut rx = EchoProvider
+            .stream_chat_with_history(&messages, "model", &ChatOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap().unwrap(), "echo: second");
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn image_input_round_trips_and_builds_urls() {
+        let inline = ImageInput::base64("image/png", b"png-bytes");
+        let json = serde_json::to_string(&inline).unwrap();
+        assert!(json.contains("\"type\":\"base64\""));
+        assert_eq!(serde_json::from_str::<ImageInput>(&json).unwrap(), inline);
+        assert_eq!(inline.to_url(), "data:image/png;base64,cG5nLWJ5dGVz");
+
+        let remote: ImageInput =
+            serde_json::from_str(r#"{"type":"url","url":"https://x.test/cat.WEBP?s=1"}"#).unwrap();
+        assert_eq!(remote.to_url(), "https://x.test/cat.WEBP?s=1");
+        assert_eq!(remote.media_type(), "image/webp");
+
+        let msg = ChatMessage::user("what is this?").with_images(vec![remote]);
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"images\""));
+        let plain = serde_json::to_string(&ChatMessage::user("hi")).unwrap();
+        assert!(!plain.contains("images"));
+    }
+
     #[test]
     fn tool_call_serialization() {
         let tc = ToolCall {
@@ -91,11 +91,11 @@ impl GitHubScout {
             }
         }
 
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("failed to build reqwest client");
+        let client = crate::proxy::client_with("skillforge", |builder| {
+            builder
+                .default_headers(headers)
+                .timeout(Duration::from_secs(30))
+        });
 
         Self {
             client,
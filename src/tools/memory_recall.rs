@@ -1,5 +1,5 @@
 use super::traits::{Tool, ToolResult};
-use crate::memory::Memory;
+use crate::memory::{expand_related, parse_time_bound, DateRange, MatchMode, Memory, MemoryEntry};
 use async_trait::async_trait;
 use serde_json::json;
 use std::fmt::Write;
@@ -14,6 +14,34 @@ impl MemoryRecallTool {
     pub fn new(memory: Arc<dyn Memory>) -> Self {
         Self { memory }
     }
+
+    async fn recall_matching(
+        &self,
+        query: &str,
+        mode: &str,
+        tag: Option<&str>,
+        range: &DateRange,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mode: MatchMode = mode.parse()?;
+        if tag.is_none() {
+            if mode == MatchMode::Keyword {
+                return self.memory.recall_in_range(query, limit, range).await;
+            }
+            if range.is_unbounded() {
+                return self.memory.recall_matching(query, mode, limit).await;
+            }
+        }
+        let mut entries = match tag {
+            Some(tag) if mode == MatchMode::Keyword => {
+                self.memory.recall_tagged(query, tag, usize::MAX).await?
+            }
+            _ => self.memory.recall_matching(query, mode, usize::MAX).await?,
+        };
+        entries.retain(|e| tag.is_none_or(|t| e.has_tag(t)) && range.contains(e));
+        entries.truncate(limit);
+        Ok(entries)
+    }
 }
 
 #[async_trait]
@@ -37,6 +65,27 @@ impl Tool for MemoryRecallTool {
                 "limit": {
                     "type": "integer",
                     "description": "Max results to return (default: 5)"
+                },
+                "tag": {
+                    "type": "string",
+                    "description": "Only return memories labelled with this tag"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["keyword", "exact", "regex", "prefix"],
+                    "description": "keyword (default, ranked); exact (verbatim text in key or content); regex (e.g. '^slack_user_'); prefix (key starts with query)"
+                },
+                "since": {
+                    "type": "string",
+                    "description": "Only memories created at or after this time: RFC 3339, YYYY-MM-DD, or an age like '7d' or '12h'"
+                },
+                "until": {
+                    "type": "string",
+                    "description": "Only memories created before this time (same formats as since)"
+                },
+                "include_related": {
+                    "type": "boolean",
+                    "description": "Also return memories linked to the matches (one hop, up to limit more)"
                 }
             },
             "required": ["query"]
@@ -55,7 +104,34 @@ impl Tool for MemoryRecallTool {
             .and_then(serde_json::Value::as_u64)
             .map_or(5, |v| v as usize);
 
-        match self.memory.recall(query, limit).await {
+        let tag = args.get("tag").and_then(|v| v.as_str());
+        let mode = args
+            .get("mode")
+            .and_then(|v| v.as_str())
+            .unwrap_or("keyword");
+        let since = args.get("since").and_then(|v| v.as_str());
+        let until = args.get("until").and_then(|v| v.as_str());
+        let include_related = args
+            .get("include_related")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        let recalled = async {
+            let range = DateRange::new(
+                since.map(parse_time_bound).transpose()?,
+                until.map(parse_time_bound).transpose()?,
+            );
+            let entries = self
+                .recall_matching(query, mode, tag, &range, limit)
+                .await?;
+            if include_related {
+                expand_related(self.memory.as_ref(), entries, limit).await
+            } else {
+                Ok(entries)
+            }
+        }
+        .await;
+
+        match recalled {
             Ok(entries) if entries.is_empty() => Ok(ToolResult {
                 success: true,
                 output: "No memories found matching that query.".into(),
@@ -67,9 +143,14 @@ impl Tool for MemoryRecallTool {
                     let score = entry
                         .score
                         .map_or_else(String::new, |s| format!(" [{s:.0}%]"));
+                    let tags = if entry.tags.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" #{}", entry.tags.join(" #"))
+                    };
                     let _ = writeln!(
                         output,
-                        "- [{}] {}: {}{score}",
+                        "- [{}] {}: {}{tags}{score}",
                         entry.category, entry.key, entry.content
                     );
                 }
@@ -148,6 +229,116 @@ mod tests {
         assert!(result.output.contains("Found 3"));
     }
 
+    #[tokio::test]
+    async fn recall_filters_by_tag() {
+        let (_tmp, mem) = seeded_mem();
+        mem.store_with_tags(
+            "x",
+            "Rust for project x",
+            MemoryCategory::Core,
+            &["project-x".into()],
+        )
+        .await
+        .unwrap();
+        mem.store("y", "Rust elsewhere", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let tool = MemoryRecallTool::new(mem);
+        let result = tool
+            .execute(json!({"query": "Rust", "tag": "project-x"}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("Found 1"));
+        assert!(result.output.contains("project x #project-x"));
+    }
+
+    #[tokio::test]
+    async fn recall_by_regex_and_rejects_unknown_mode() {
+        let (_tmp, mem) = seeded_mem();
+        mem.store("slack_user_7", "Dana", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("slack_team", "Platform", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let tool = MemoryRecallTool::new(mem);
+        let result = tool
+            .execute(json!({"query": "^slack_user_", "mode": "regex"}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("Found 1"));
+        assert!(result.output.contains("slack_user_7: Dana"));
+
+        let result = tool
+            .execute(json!({"query": "x", "mode": "fuzzy"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+    }
+
+    #[tokio::test]
+    async fn recall_within_date_range() {
+        let (_tmp, mem) = seeded_mem();
+        mem.store(
+            "standup",
+            "Discussed the Rust migration",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+
+        let tool = MemoryRecallTool::new(mem);
+        let result = tool
+            .execute(json!({"query": "Rust", "since": "7d"}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("Found 1"));
+
+        let result = tool
+            .execute(json!({"query": "Rust", "until": "2000-01-01"}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("No memories found"));
+
+        let result = tool
+            .execute(json!({"query": "Rust", "since": "last week"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("invalid time"));
+    }
+
+    #[tokio::test]
+    async fn recall_includes_related_on_request() {
+        let (_tmp, mem) = seeded_mem();
+        mem.store(
+            "project-x",
+            "Rust rewrite of the billing service",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+        mem.store("deadline-june", "Ship by June 30", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.relate("project-x", "deadline-june", "deadline")
+            .await
+            .unwrap();
+
+        let tool = MemoryRecallTool::new(mem);
+        let result = tool.execute(json!({"query": "Rust"})).await.unwrap();
+        assert!(result.output.contains("Found 1"));
+
+        let result = tool
+            .execute(json!({"query": "Rust", "include_related": true}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("Found 2"));
+        assert!(result.output.contains("deadline-june: Ship by June 30"));
+    }
+
     #[tokio::test]
     async fn recall_missing_query() {
         let (_tmp, mem) = seeded_mem();
@@ -41,6 +41,17 @@ impl Tool for MemoryStoreTool {
                     "type": "string",
                     "enum": ["core", "daily", "conversation"],
                     "description": "Memory category: core (permanent), daily (session), conversation (chat)"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Optional topic labels (e.g. 'project-x') for filtering later"
+                },
+                "importance": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 1,
+                    "description": "How much this matters for later recall, 0.0-1.0 (default 0.5). Use ~0.9 for critical facts, ~0.1 for trivia"
                 }
             },
             "required": ["key", "content"]
@@ -64,7 +75,30 @@ impl Tool for MemoryStoreTool {
             _ => MemoryCategory::Core,
         };
 
-        match self.memory.store(key, content, category).await {
+        let tags: Vec<String> = args
+            .get("tags")
+            .and_then(|v| v.as_array())
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|t| t.as_str().map(ToString::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let importance = args.get("importance").and_then(serde_json::Value::as_f64);
+
+        let stored = async {
+            self.memory
+                .store_with_tags(key, content, category, &tags)
+                .await?;
+            if let Some(importance) = importance {
+                self.memory.set_importance(key, importance).await?;
+            }
+            anyhow::Ok(())
+        }
+        .await;
+
+        match stored {
             Ok(()) => Ok(ToolResult {
                 success: true,
                 output: format!("Stored memory: {key}"),
@@ -128,6 +162,34 @@ mod tests {
         assert!(result.success);
     }
 
+    #[tokio::test]
+    async fn store_with_tags() {
+        let (_tmp, mem) = test_mem();
+        let tool = MemoryStoreTool::new(mem.clone());
+        let result = tool
+            .execute(json!({"key": "db", "content": "Uses Postgres", "tags": ["project-x"]}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(
+            mem.get("db").await.unwrap().unwrap().tags,
+            vec!["project-x"]
+        );
+    }
+
+    #[tokio::test]
+    async fn store_with_importance() {
+        let (_tmp, mem) = test_mem();
+        let tool = MemoryStoreTool::new(mem.clone());
+        let result = tool
+            .execute(json!({"key": "rule", "content": "Never deploy Fridays", "importance": 0.9}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        let importance = mem.get("rule").await.unwrap().unwrap().importance;
+        assert!((importance - 0.9).abs() < 1e-9);
+    }
+
     #[tokio::test]
     async fn store_missing_key() {
         let (_tmp, mem) = test_mem();
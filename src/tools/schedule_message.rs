@@ -0,0 +1,152 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::Config;
+use crate::cron::{parse_send_time, send_at};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+
+/// Let the agent send a message later — "I'll remind you at 5pm"
+pub struct ScheduleMessageTool {
+    config: Config,
+}
+
+impl ScheduleMessageTool {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl Tool for ScheduleMessageTool {
+    fn name(&self) -> &str {
+        "schedule_message"
+    }
+
+    fn description(&self) -> &str {
+        "Send a message through a channel at a later time, e.g. a reminder the user asked for. The daemon delivers it when due."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "channel": {
+                    "type": "string",
+                    "description": "Channel to send through (e.g. 'telegram', 'slack', 'discord')"
+                },
+                "target": {
+                    "type": "string",
+                    "description": "Recipient on that channel: chat ID, channel ID or address"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "The message to send"
+                },
+                "when": {
+                    "type": "string",
+                    "description": "RFC 3339 timestamp with offset (e.g. '2026-03-01T17:00:00+01:00') or a delay like '30m', '2h', '1d'"
+                }
+            },
+            "required": ["channel", "target", "message", "when"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let arg = |name: &str| {
+            args.get(name)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Missing '{name}' parameter"))
+        };
+        let channel = arg("channel")?;
+        let target = arg("target")?;
+        let message = arg("message")?;
+        let when = arg("when")?;
+
+        let scheduled = parse_send_time(when, Utc::now())
+            .and_then(|when| send_at(&self.config, channel, target, message, when));
+
+        match scheduled {
+            Ok(msg) => Ok(ToolResult {
+                success: true,
+                output: format!(
+                    "Scheduled message {} to {} on {} at {}",
+                    msg.id,
+                    msg.target,
+                    msg.channel,
+                    msg.send_at.to_rfc3339()
+                ),
+                error: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Failed to schedule message: {e}")),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_config(tmp: &TempDir) -> Config {
+        Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn name_and_schema() {
+        let tmp = TempDir::new().unwrap();
+        let tool = ScheduleMessageTool::new(test_config(&tmp));
+        assert_eq!(tool.name(), "schedule_message");
+        let schema = tool.parameters_schema();
+        assert!(schema["properties"]["when"].is_object());
+        assert_eq!(schema["required"].as_array().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn schedules_a_reminder() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let tool = ScheduleMessageTool::new(config.clone());
+        let result = tool
+            .execute(json!({
+                "channel": "telegram",
+                "target": "42",
+                "message": "Call the dentist",
+                "when": "2h"
+            }))
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        let queued = crate::cron::list_messages(&config).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].message, "Call the dentist");
+        assert!(result.output.contains(&queued[0].id));
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_times_and_missing_args() {
+        let tmp = TempDir::new().unwrap();
+        let tool = ScheduleMessageTool::new(test_config(&tmp));
+        let result = tool
+            .execute(json!({
+                "channel": "telegram",
+                "target": "42",
+                "message": "Too late",
+                "when": "2001-01-01T00:00:00Z"
+            }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("in the past"));
+
+        let missing = tool.execute(json!({"channel": "telegram"})).await;
+        assert!(missing.is_err());
+    }
+}
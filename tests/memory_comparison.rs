@@ -268,8 +268,8 @@ async fn compare_persistence() {
     assert!(sq_entry.is_some());
     assert_eq!(sq_entry.unwrap().content, "I should survive");
 
-    // Markdown persists content to files (get uses content search)
-    assert!(md_entry.is_some());
+    // Markdown persists entries by key in per-category files
+    assert_eq!(md_entry.unwrap().content, "I should survive");
 }
 
 // ── Test 5: Upsert / update behavior ──────────────────────────
@@ -300,7 +300,7 @@ async fn compare_upsert() {
     let md_count = md.count().await.unwrap();
 
     let sq_entry = sq.get("pref").await.unwrap();
-    let md_results = md.recall("loves Rust", 5).await.unwrap();
+    let md_entry = md.get("pref").await.unwrap();
 
     println!("\n============================================================");
     println!("UPSERT (store same key twice):");
@@ -308,15 +308,18 @@ async fn compare_upsert() {
         "  SQLite:   count={sq_count}, latest=\"{}\"",
         sq_entry.as_ref().map_or("none", |e| &e.content)
     );
-    println!("  Markdown: count={md_count} (append-only, both entries kept)");
-    println!("    Can still find latest: {}", !md_results.is_empty());
+    println!(
+        "  Markdown: count={md_count}, latest=\"{}\"",
+        md_entry.as_ref().map_or("none", |e| &e.content)
+    );
 
     // SQLite: upsert replaces, count stays at 1
     assert_eq!(sq_count, 1);
     assert_eq!(sq_entry.unwrap().content, "loves Rust");
 
-    // Markdown: append-only, count increases
-    assert!(md_count >= 2, "Markdown should keep both entries");
+    // Markdown: same keyed upsert semantics
+    assert_eq!(md_count, 1);
+    assert_eq!(md_entry.unwrap().content, "loves Rust");
 }
 
 // ── Test 6: Forget / delete capability ─────────────────────────
@@ -346,20 +349,18 @@ async fn compare_forget() {
         sq.count().await.unwrap()
     );
     println!(
-        "  Markdown: {} (append-only by design)",
-        if md_forgot {
-            "✅ Deleted"
-        } else {
-            "⚠️  Cannot delete (audit trail)"
-        },
+        "  Markdown: {} (count={})",
+        if md_forgot { "✅ Deleted" } else { "❌ Kept" },
+        md.count().await.unwrap()
     );
 
     // SQLite can delete
     assert!(sq_forgot);
     assert_eq!(sq.count().await.unwrap(), 0);
 
-    // Markdown cannot delete (by design)
-    assert!(!md_forgot);
+    // Markdown rewrites the category file without the entry
+    assert!(md_forgot);
+    assert_eq!(md.count().await.unwrap(), 0);
 }
 
 // ── Test 7: Category filtering ─────────────────────────────────
@@ -426,7 +427,8 @@ async fn compare_category_filter() {
     assert_eq!(sq_conv.len(), 1);
     assert_eq!(sq_all.len(), 4);
 
-    // Markdown: categories determined by file location
-    assert!(!md_core.is_empty());
-    assert!(!md_all.is_empty());
+    // Markdown: one file per category
+    assert_eq!(md_core.len(), 2);
+    assert_eq!(md_daily.len(), 1);
+    assert_eq!(md_all.len(), 3);
 }